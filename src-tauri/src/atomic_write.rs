@@ -0,0 +1,203 @@
+//! Atomic File Writes
+//! Crash-safe saving of small config files: content goes to a temp file in
+//! the same directory, is fsynced, then renamed over the original, so a
+//! crash leaves either the old or the new file but never a truncated one.
+//! Files saved with `save_with_backup` also keep a `.bak` copy of the last
+//! successful save, which `load_with_backup` falls back to.
+
+use log::warn;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Replaces `path` with `contents` atomically, creating parent directories
+/// as needed.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+    if let Some(dir) = dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tmp_path = with_suffix(
+        path,
+        &format!(
+            ".tmp.{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ),
+    );
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?; // Ensure flush to disk
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+
+    // Persist the rename itself; best effort, not every filesystem allows it
+    if let Some(dir) = dir {
+        if let Ok(dir) = fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Where the last successfully saved copy of `path` is kept.
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+/// Saves `contents` to `path` atomically, then refreshes its backup.
+pub fn save_with_backup(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic(path, contents)?;
+    if let Err(e) = write_atomic(&backup_path(path), contents) {
+        warn!("failed to update backup of {}: {}", path.display(), e);
+    }
+    Ok(())
+}
+
+/// A problem found while loading a file saved with `save_with_backup`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadIssue {
+    /// The file was damaged and its backup was restored
+    Recovered(String),
+    /// The file was damaged and no backup was usable; defaults are in use
+    Corrupt(String),
+}
+
+impl LoadIssue {
+    /// Event the UI listens for to tell the user.
+    pub fn event(&self) -> &'static str {
+        match self {
+            Self::Recovered(_) => "settings-recovered",
+            Self::Corrupt(_) => "settings-corrupt",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Recovered(message) | Self::Corrupt(message) => message,
+        }
+    }
+}
+
+/// Reads and parses `path`, falling back to its backup when the file is
+/// unreadable or doesn't parse. A usable backup is copied over the damaged
+/// file; without one the damaged file is moved aside to `<name>.corrupt`
+/// so later loads start clean. Returns `None` for a missing or damaged file.
+pub fn load_with_backup<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> (Option<T>, Option<LoadIssue>) {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let error = match fs::read_to_string(path) {
+        Ok(content) => match parse(&content) {
+            Ok(value) => return (Some(value), None),
+            Err(e) => e,
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return (None, None),
+        Err(e) => e.to_string(),
+    };
+    warn!("failed to load {}: {}", path.display(), error);
+
+    let backup = backup_path(path);
+    let restored = fs::read_to_string(&backup)
+        .ok()
+        .and_then(|content| Some((parse(&content).ok()?, content)));
+    if let Some((value, content)) = restored {
+        if let Err(e) = write_atomic(path, content.as_bytes()) {
+            warn!("failed to restore {} from backup: {}", path.display(), e);
+        }
+        let message = format!(
+            "{} was damaged ({}). The last saved copy was restored.",
+            name, error
+        );
+        return (Some(value), Some(LoadIssue::Recovered(message)));
+    }
+
+    let corrupt = with_suffix(path, ".corrupt");
+    if let Err(e) = fs::rename(path, &corrupt) {
+        warn!("failed to move aside {}: {}", path.display(), e);
+    }
+    let message = format!(
+        "{} was damaged ({}) and no backup was usable, so defaults are in use. \
+         The damaged file was kept as {}.",
+        name,
+        error,
+        corrupt.display()
+    );
+    (None, Some(LoadIssue::Corrupt(message)))
+}
+
+/// `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = temp_dir().join(format!("penguinclip_test_atomic_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("settings.json")
+    }
+
+    fn parse(content: &str) -> Result<serde_json::Value, String> {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_without_leftovers() {
+        let path = temp_path("replace");
+        write_atomic(&path, b"one").unwrap();
+        write_atomic(&path, b"two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "two");
+        let entries = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn test_truncated_file_is_restored_from_backup() {
+        let path = temp_path("restore");
+        save_with_backup(&path, br#"{"theme": "dark"}"#).unwrap();
+        // Simulate a write cut short by a crash
+        fs::write(&path, br#"{"theme": "da"#).unwrap();
+
+        let (value, issue) = load_with_backup(&path, parse);
+        assert_eq!(value.unwrap()["theme"], "dark");
+        assert!(matches!(issue, Some(LoadIssue::Recovered(_))));
+        // The damaged file was repaired, so the next load is clean
+        assert_eq!(load_with_backup(&path, parse).1, None);
+    }
+
+    #[test]
+    fn test_truncated_file_without_backup_is_moved_aside() {
+        let path = temp_path("corrupt");
+        write_atomic(&path, br#"{"theme": "#).unwrap();
+
+        let (value, issue) = load_with_backup(&path, parse);
+        assert!(value.is_none());
+        assert_eq!(
+            issue.as_ref().map(LoadIssue::event),
+            Some("settings-corrupt")
+        );
+        assert!(!path.exists());
+        assert!(with_suffix(&path, ".corrupt").exists());
+        assert_eq!(load_with_backup(&path, parse), (None, None));
+    }
+}
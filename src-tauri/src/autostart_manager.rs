@@ -96,10 +96,7 @@ pub fn autostart_enable() -> Result<(), String> {
     file.write_all(content.as_bytes())
         .map_err(|e| format!("Failed to write autostart file: {}", e))?;
 
-    println!(
-        "[Autostart] Enabled autostart with exec path: {}",
-        exec_path
-    );
+    log::info!("Enabled autostart with exec path: {}", exec_path);
 
     Ok(())
 }
@@ -112,7 +109,7 @@ pub fn autostart_disable() -> Result<(), String> {
     if autostart_file.exists() {
         fs::remove_file(&autostart_file)
             .map_err(|e| format!("Failed to remove autostart file: {}", e))?;
-        println!("[Autostart] Disabled autostart");
+        log::info!("Disabled autostart");
     }
 
     Ok(())
@@ -181,16 +178,16 @@ pub fn autostart_migrate() -> Result<bool, String> {
 
     if needs_migration {
         if uses_old_binary {
-            println!("[Autostart] Migrating from old binary path to wrapper...");
+            log::info!("Migrating from old binary path to wrapper...");
         }
         if missing_sleep {
-            println!("[Autostart] Adding sleep to exec for proper tray initialization...");
+            log::info!("Adding sleep to exec for proper tray initialization...");
         }
         if missing_background {
-            println!("[Autostart] Adding --background flag for minimized startup...");
+            log::info!("Adding --background flag for minimized startup...");
         }
         if has_gnome_delay {
-            println!("[Autostart] Replacing X-GNOME-Autostart-Delay with sleep in exec (multi-distro compatibility)...");
+            log::info!("Replacing X-GNOME-Autostart-Delay with sleep in exec (multi-distro compatibility)...");
         }
 
         // Re-enable with correct path, sleep and --background
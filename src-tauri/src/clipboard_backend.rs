@@ -0,0 +1,261 @@
+//! Clipboard Backend
+//! The system clipboard operations `ClipboardManager` and the watcher
+//! perform, behind a trait so they can be tested without a display server.
+//!
+//! `ArboardBackend` is the real implementation. Tests use `MockClipboard`,
+//! which keeps contents in memory and records simulated pastes.
+
+use arboard::{Clipboard, GetExtLinux, ImageData, LinuxClipboardKind, SetExtLinux};
+use log::warn;
+use std::thread;
+use std::time::Duration;
+
+/// System clipboard access. `ClipboardManager` writes and pastes through
+/// it; the watcher reads through its own instance so it never waits on the
+/// manager lock.
+///
+/// Reads return `Ok(None)` when the clipboard holds no content of that kind.
+pub trait ClipboardBackend: Send {
+    fn get_text(&mut self) -> Result<Option<String>, String>;
+    fn get_html(&mut self) -> Option<String>;
+    fn get_image(&mut self) -> Result<Option<ImageData<'static>>, String>;
+    /// Reads the PRIMARY selection (the last selected text).
+    fn get_primary_text(&mut self) -> Result<Option<String>, String>;
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+    /// Sets HTML with `alt_text` as the plain-text fallback.
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), String>;
+    fn set_image(&mut self, image: ImageData<'_>) -> Result<(), String>;
+    /// Offers `text` as the PRIMARY selection until another app takes it.
+    fn set_primary_text(&mut self, text: String) -> Result<(), String>;
+    /// Sends the paste keystroke to the focused window.
+    fn simulate_paste(&mut self) -> Result<(), String>;
+}
+
+/// The OS clipboard via arboard. A fresh `Clipboard` is opened per call, so
+/// separate instances never contend.
+#[derive(Debug, Default)]
+pub struct ArboardBackend;
+
+fn open() -> Result<Clipboard, String> {
+    Clipboard::new().map_err(|e| e.to_string())
+}
+
+/// `Ok(None)` for a clipboard without that kind of content.
+fn content<T>(result: Result<T, arboard::Error>) -> Result<Option<T>, String> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+impl ClipboardBackend for ArboardBackend {
+    fn get_text(&mut self) -> Result<Option<String>, String> {
+        content(open()?.get_text())
+    }
+
+    fn get_html(&mut self) -> Option<String> {
+        open().ok()?.get().html().ok()
+    }
+
+    fn get_image(&mut self) -> Result<Option<ImageData<'static>>, String> {
+        let image = content(open()?.get_image())?;
+        Ok(image.map(|image| ImageData {
+            width: image.width,
+            height: image.height,
+            bytes: image.bytes.into_owned().into(),
+        }))
+    }
+
+    fn get_primary_text(&mut self) -> Result<Option<String>, String> {
+        content(open()?.get().clipboard(LinuxClipboardKind::Primary).text())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        open()?.set_text(text).map_err(|e| e.to_string())
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), String> {
+        open()?
+            .set_html(html, Some(alt_text))
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_image(&mut self, image: ImageData<'_>) -> Result<(), String> {
+        open()?.set_image(image).map_err(|e| e.to_string())
+    }
+
+    fn set_primary_text(&mut self, text: String) -> Result<(), String> {
+        // Serving the selection blocks, so it runs on its own thread
+        let mut clipboard = open()?;
+        thread::spawn(move || {
+            if let Err(e) = clipboard
+                .set()
+                .clipboard(LinuxClipboardKind::Primary)
+                .wait()
+                .text(text)
+            {
+                warn!("failed to set primary selection: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    fn simulate_paste(&mut self) -> Result<(), String> {
+        // Wait for clipboard write to settle
+        thread::sleep(Duration::from_millis(60));
+
+        crate::input_simulator::simulate_paste_keystroke()?;
+
+        // Wait for the target app to process the paste
+        thread::sleep(Duration::from_millis(250));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub use mock::{MockClipboard, MockContents};
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    /// What the mock clipboard currently holds.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum MockContents {
+        Empty,
+        Text(String),
+        Html {
+            html: String,
+            alt_text: String,
+        },
+        Image {
+            width: usize,
+            height: usize,
+            bytes: Vec<u8>,
+        },
+    }
+
+    #[derive(Debug)]
+    struct MockState {
+        contents: MockContents,
+        primary: Option<String>,
+        pastes: usize,
+        fail_paste: bool,
+    }
+
+    /// In-memory clipboard. Clones share state, so a test can keep one
+    /// handle and give the other to the manager.
+    #[derive(Debug, Clone)]
+    pub struct MockClipboard {
+        state: Arc<Mutex<MockState>>,
+    }
+
+    impl Default for MockClipboard {
+        fn default() -> Self {
+            Self {
+                state: Arc::new(Mutex::new(MockState {
+                    contents: MockContents::Empty,
+                    primary: None,
+                    pastes: 0,
+                    fail_paste: false,
+                })),
+            }
+        }
+    }
+
+    impl MockClipboard {
+        pub fn contents(&self) -> MockContents {
+            self.state.lock().contents.clone()
+        }
+
+        /// Current PRIMARY selection text.
+        pub fn primary(&self) -> Option<String> {
+            self.state.lock().primary.clone()
+        }
+
+        /// Number of successful simulated pastes.
+        pub fn pastes(&self) -> usize {
+            self.state.lock().pastes
+        }
+
+        /// Makes `simulate_paste` fail, as when no input tool is available.
+        pub fn fail_paste(&self, fail: bool) {
+            self.state.lock().fail_paste = fail;
+        }
+    }
+
+    impl ClipboardBackend for MockClipboard {
+        fn get_text(&mut self) -> Result<Option<String>, String> {
+            Ok(match &self.state.lock().contents {
+                MockContents::Text(text) => Some(text.clone()),
+                MockContents::Html { alt_text, .. } => Some(alt_text.clone()),
+                _ => None,
+            })
+        }
+
+        fn get_html(&mut self) -> Option<String> {
+            match &self.state.lock().contents {
+                MockContents::Html { html, .. } => Some(html.clone()),
+                _ => None,
+            }
+        }
+
+        fn get_image(&mut self) -> Result<Option<ImageData<'static>>, String> {
+            Ok(match &self.state.lock().contents {
+                MockContents::Image {
+                    width,
+                    height,
+                    bytes,
+                } => Some(ImageData {
+                    width: *width,
+                    height: *height,
+                    bytes: bytes.clone().into(),
+                }),
+                _ => None,
+            })
+        }
+
+        fn get_primary_text(&mut self) -> Result<Option<String>, String> {
+            Ok(self.state.lock().primary.clone())
+        }
+
+        fn set_text(&mut self, text: &str) -> Result<(), String> {
+            self.state.lock().contents = MockContents::Text(text.to_string());
+            Ok(())
+        }
+
+        fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), String> {
+            self.state.lock().contents = MockContents::Html {
+                html: html.to_string(),
+                alt_text: alt_text.to_string(),
+            };
+            Ok(())
+        }
+
+        fn set_image(&mut self, image: ImageData<'_>) -> Result<(), String> {
+            self.state.lock().contents = MockContents::Image {
+                width: image.width,
+                height: image.height,
+                bytes: image.bytes.into_owned(),
+            };
+            Ok(())
+        }
+
+        fn set_primary_text(&mut self, text: String) -> Result<(), String> {
+            self.state.lock().primary = Some(text);
+            Ok(())
+        }
+
+        fn simulate_paste(&mut self) -> Result<(), String> {
+            let mut state = self.state.lock();
+            if state.fail_paste {
+                return Err("paste keystroke failed".to_string());
+            }
+            state.pastes += 1;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,925 @@
+//! Clipboard Change Detection
+//! Tells the clipboard watcher when the clipboard may have changed and what is
+//! worth reading. On X11 it listens for XFIXES selection-owner notifications;
+//! on Wayland compositors with a data-control protocol (wlroots, and newer
+//! ones via ext-data-control) it receives selection offers with their MIME
+//! types. Either way clipboard contents are only read after an actual change.
+//! Elsewhere, or when neither is available, it falls back to polling.
+
+use crate::clipboard_manager::has_password_manager_hint;
+use crate::session;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_registry::WlRegistry, wl_seat::WlSeat};
+use wayland_client::{
+    delegate_noop, event_created_child, Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::ext::data_control::v1::client::{
+    ext_data_control_device_v1::{self, ExtDataControlDeviceV1},
+    ext_data_control_manager_v1::ExtDataControlManagerV1,
+    ext_data_control_offer_v1::{self, ExtDataControlOfferV1},
+};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::{self, ZwlrDataControlOfferV1},
+};
+
+/// Polling interval, and the longest an event-driven wait blocks before
+/// returning so the watcher can run its periodic housekeeping.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// MIME types (and X11-style target names some bridges forward) that mean a
+/// selection carries text worth reading. `text/uri-list` is read as text so
+/// copied image files can be upgraded by `add_clipboard_text`.
+const TEXT_MIME_TYPES: &[&str] = &[
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+    "text/uri-list",
+];
+
+/// Which clipboard reads are worth doing after a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadPlan {
+    pub text: bool,
+    pub image: bool,
+    /// Whether the owner marked the content as not for history, when the
+    /// change source already knows the offered types. `None` means the
+    /// targets still have to be queried.
+    pub no_history: Option<bool>,
+}
+
+impl ReadPlan {
+    /// Read everything; used when the change source can't tell what changed.
+    pub const ALL: Self = Self {
+        text: true,
+        image: true,
+        no_history: None,
+    };
+
+    /// Decides what to read from the MIME types a selection offers.
+    pub fn from_mime_types(types: &[String]) -> Self {
+        Self {
+            text: types.iter().any(|t| TEXT_MIME_TYPES.contains(&t.as_str())),
+            image: types.iter().any(|t| t.starts_with("image/")),
+            no_history: Some(has_password_manager_hint(types)),
+        }
+    }
+
+    /// Combines coalesced events. Any hinted offer keeps the hint, so a
+    /// secret is never recorded because a later event was merged in.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            text: self.text || other.text,
+            image: self.image || other.image,
+            no_history: match (self.no_history, other.no_history) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Source of "the clipboard may have changed" signals for the watcher loop.
+pub enum ChangeSource {
+    /// Read the clipboard on every tick.
+    Polling,
+    /// Read the clipboard only when a listener thread reports a change.
+    Events(Receiver<ReadPlan>),
+}
+
+impl ChangeSource {
+    /// Picks the best available source. `force_polling` (a debugging setting)
+    /// skips event-driven detection entirely.
+    pub fn start(force_polling: bool) -> Self {
+        if force_polling {
+            debug!("clipboard watch: polling forced by settings");
+            return Self::Polling;
+        }
+
+        let (backend, listener) = if session::is_wayland() {
+            ("Wayland data-control", spawn_wayland_listener())
+        } else if session::is_x11() {
+            ("XFIXES", spawn_xfixes_listener())
+        } else {
+            return Self::Polling;
+        };
+
+        match listener {
+            Ok(rx) => {
+                debug!("clipboard watch: using {} selection events", backend);
+                Self::Events(rx)
+            }
+            Err(e) => {
+                warn!(
+                    "clipboard watch: {} unavailable ({}), polling instead",
+                    backend, e
+                );
+                Self::Polling
+            }
+        }
+    }
+
+    /// Blocks for at most `POLL_INTERVAL`. Returns what to read now, or `None`
+    /// if nothing changed. Bursts of events are coalesced into a single read.
+    pub fn wait(&mut self) -> Option<ReadPlan> {
+        match self {
+            Self::Polling => {
+                thread::sleep(POLL_INTERVAL);
+                Some(ReadPlan::ALL)
+            }
+            Self::Events(rx) => match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(mut plan) => {
+                    while let Ok(next) = rx.try_recv() {
+                        plan = plan.merge(next);
+                    }
+                    Some(plan)
+                }
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    warn!("clipboard watch: event listener stopped, falling back to polling");
+                    *self = Self::Polling;
+                    Some(ReadPlan::ALL)
+                }
+            },
+        }
+    }
+
+    /// True when reads happen only on clipboard changes.
+    pub fn is_event_driven(&self) -> bool {
+        matches!(self, Self::Events(_))
+    }
+}
+
+// --- No-history hint ---
+
+/// Answers "did the owner mark this content as not for history?" for one
+/// clipboard change. The targets are queried at most once, and not at all
+/// when the change source already reported the offered types.
+pub struct NoHistoryHint<F> {
+    known: Option<bool>,
+    query_targets: F,
+}
+
+impl<F: FnMut() -> Option<Vec<String>>> NoHistoryHint<F> {
+    pub fn new(known: Option<bool>, query_targets: F) -> Self {
+        Self {
+            known,
+            query_targets,
+        }
+    }
+
+    pub fn is_set(&mut self) -> bool {
+        if let Some(known) = self.known {
+            return known;
+        }
+        let set = (self.query_targets)().is_some_and(|t| has_password_manager_hint(&t));
+        self.known = Some(set);
+        set
+    }
+}
+
+// --- Image read throttling ---
+
+/// While polling, an image read with an unchanged text state still happens
+/// every this many ticks (3 s), so image-only copies are picked up even from
+/// apps that never put text on the clipboard.
+const IMAGE_BACKSTOP_TICKS: u32 = 6;
+
+/// Decides when a polling watcher should do a full image read. Reading and
+/// hashing an image is expensive, so it only happens when the cheap text read
+/// shows the clipboard changed, or on the periodic backstop.
+#[derive(Debug, Default)]
+pub struct ImageReadThrottle {
+    /// Text hash seen on the previous tick (`None` inside = no text). The
+    /// outer `None` means no tick has happened yet.
+    last_text_state: Option<Option<u64>>,
+    idle_ticks: u32,
+}
+
+impl ImageReadThrottle {
+    /// Call once per polling tick with the hash of the current clipboard text.
+    pub fn should_read(&mut self, text_state: Option<u64>) -> bool {
+        let changed = self.last_text_state != Some(text_state);
+        self.last_text_state = Some(text_state);
+        if changed {
+            self.idle_ticks = 0;
+            return true;
+        }
+        self.idle_ticks += 1;
+        if self.idle_ticks >= IMAGE_BACKSTOP_TICKS {
+            self.idle_ticks = 0;
+            return true;
+        }
+        false
+    }
+}
+
+// --- PRIMARY selection ---
+
+/// How long the PRIMARY selection must stay unchanged before it is recorded.
+/// Selecting by dragging updates PRIMARY continuously, so only the final
+/// selection should reach history.
+pub const PRIMARY_SETTLE_TIME: Duration = Duration::from_millis(1200);
+
+static TRACK_PRIMARY: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables PRIMARY selection tracking (X11 only).
+pub fn set_primary_tracking(enabled: bool) {
+    TRACK_PRIMARY.store(enabled, Ordering::Relaxed);
+}
+
+/// True when the watcher should read the PRIMARY selection.
+pub fn primary_tracking_enabled() -> bool {
+    TRACK_PRIMARY.load(Ordering::Relaxed) && session::is_x11()
+}
+
+/// Debounces the PRIMARY selection: a value is reported once, after it has
+/// stayed the same for `PRIMARY_SETTLE_TIME`.
+#[derive(Debug, Default)]
+pub struct SelectionSettle {
+    candidate: Option<(u64, Instant)>,
+    last_reported: Option<u64>,
+}
+
+impl SelectionSettle {
+    /// Feeds the hash of the current selection. Returns true exactly once per
+    /// settled selection.
+    pub fn observe(&mut self, hash: u64, now: Instant) -> bool {
+        if self.last_reported == Some(hash) {
+            return false;
+        }
+        match self.candidate {
+            Some((candidate, since)) if candidate == hash => {
+                if now.duration_since(since) >= PRIMARY_SETTLE_TIME {
+                    self.last_reported = Some(hash);
+                    self.candidate = None;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => {
+                self.candidate = Some((hash, now));
+                false
+            }
+        }
+    }
+
+    /// Treats `hash` as already seen (e.g. text that was just copied to the
+    /// clipboard, which is usually still selected).
+    pub fn mark_seen(&mut self, hash: u64) {
+        self.last_reported = Some(hash);
+        self.candidate = None;
+    }
+}
+
+// --- Counters ---
+
+static WAKEUPS: AtomicU64 = AtomicU64::new(0);
+static TEXT_READS: AtomicU64 = AtomicU64::new(0);
+static IMAGE_READS: AtomicU64 = AtomicU64::new(0);
+static IMAGE_READS_SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Watcher activity since startup, for diagnosing idle CPU usage.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchStats {
+    /// Times the watcher loop woke up (ticks and events)
+    pub wakeups: u64,
+    pub text_reads: u64,
+    pub image_reads: u64,
+    /// Image reads avoided because nothing indicated a change
+    pub image_reads_skipped: u64,
+}
+
+pub fn note_wakeup() {
+    WAKEUPS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn note_text_read() {
+    TEXT_READS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn note_image_read() {
+    IMAGE_READS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn note_image_read_skipped() {
+    IMAGE_READS_SKIPPED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn stats() -> WatchStats {
+    WatchStats {
+        wakeups: WAKEUPS.load(Ordering::Relaxed),
+        text_reads: TEXT_READS.load(Ordering::Relaxed),
+        image_reads: IMAGE_READS.load(Ordering::Relaxed),
+        image_reads_skipped: IMAGE_READS_SKIPPED.load(Ordering::Relaxed),
+    }
+}
+
+// --- Read failures ---
+
+/// Consecutive failed reads before the user is told about it; a single
+/// failure right after resume is normal and recovers on its own.
+const FAILURES_BEFORE_REPORT: u32 = 3;
+
+/// Longest wait between reads while the clipboard keeps failing.
+const MAX_FAILURE_BACKOFF: Duration = Duration::from_secs(10);
+
+/// The two clipboard reads the watcher makes. Each backs off on its own,
+/// so images failing to decode doesn't slow down text, and vice versa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadKind {
+    Text,
+    Image,
+}
+
+impl ReadKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Image => "image",
+        }
+    }
+}
+
+/// Failure state of one kind of read.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReadHealth {
+    pub consecutive_failures: u32,
+    /// When the backoff after the last failure runs out
+    #[serde(skip)]
+    retry_at: Option<Instant>,
+}
+
+/// Clipboard read health, exposed to the settings UI for diagnostics.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WatcherHealth {
+    pub text: ReadHealth,
+    pub image: ReadHealth,
+    pub total_failures: u64,
+    pub last_error: Option<String>,
+    pub last_success: Option<DateTime<Utc>>,
+    /// True once the failure has been reported with `clipboard-error`
+    pub reported: bool,
+}
+
+/// A health transition the UI should hear about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthChange {
+    /// Reads keep failing; carries a human-readable message.
+    Failing(String),
+    /// Reads work again after a reported failure.
+    Recovered,
+}
+
+impl ReadHealth {
+    /// Extra delay before the next read: doubles per consecutive failure,
+    /// capped so a read is retried at least every `MAX_FAILURE_BACKOFF`.
+    fn backoff(&self) -> Duration {
+        if self.consecutive_failures == 0 {
+            return Duration::ZERO;
+        }
+        let factor = 1u32 << self.consecutive_failures.min(8);
+        (POLL_INTERVAL * factor).min(MAX_FAILURE_BACKOFF) - POLL_INTERVAL
+    }
+}
+
+impl WatcherHealth {
+    fn read(&mut self, kind: ReadKind) -> &mut ReadHealth {
+        match kind {
+            ReadKind::Text => &mut self.text,
+            ReadKind::Image => &mut self.image,
+        }
+    }
+
+    /// Recovery is only reported once neither kind of read is failing.
+    fn record_success(&mut self, kind: ReadKind, now: DateTime<Utc>) -> Option<HealthChange> {
+        self.last_success = Some(now);
+        *self.read(kind) = ReadHealth::default();
+        if self.text.consecutive_failures > 0 || self.image.consecutive_failures > 0 {
+            return None;
+        }
+        std::mem::take(&mut self.reported).then_some(HealthChange::Recovered)
+    }
+
+    fn record_failure(&mut self, kind: ReadKind, error: &str) -> Option<HealthChange> {
+        let read = self.read(kind);
+        read.consecutive_failures += 1;
+        let failures = read.consecutive_failures;
+        let delay = POLL_INTERVAL + read.backoff();
+        read.retry_at = Some(Instant::now() + delay);
+        self.total_failures += 1;
+        self.last_error = Some(error.to_string());
+        if self.reported || failures < FAILURES_BEFORE_REPORT {
+            return None;
+        }
+        self.reported = true;
+        Some(HealthChange::Failing(format!(
+            "Can't read clipboard {} ({}). Retrying in {} seconds.",
+            kind.label(),
+            error,
+            delay.as_secs_f64()
+        )))
+    }
+
+    fn backoff(&mut self, kind: ReadKind) -> Duration {
+        self.read(kind).backoff()
+    }
+
+    fn read_due(&mut self, kind: ReadKind, now: Instant) -> bool {
+        !self.read(kind).retry_at.is_some_and(|at| now < at)
+    }
+}
+
+static HEALTH: Mutex<Option<WatcherHealth>> = Mutex::new(None);
+
+fn with_health<T>(f: impl FnOnce(&mut WatcherHealth) -> T) -> T {
+    f(HEALTH.lock().get_or_insert_with(WatcherHealth::default))
+}
+
+/// Records a successful clipboard read (including "clipboard is empty").
+pub fn record_read_success(kind: ReadKind) -> Option<HealthChange> {
+    with_health(|health| health.record_success(kind, Utc::now()))
+}
+
+/// Records a failed clipboard read. Each read opens a fresh clipboard
+/// connection, so the next attempt after the backoff reconnects.
+pub fn record_read_failure(kind: ReadKind, error: &str) -> Option<HealthChange> {
+    with_health(|health| health.record_failure(kind, error))
+}
+
+/// How much longer than usual to wait before the next read of `kind`.
+pub fn failure_backoff(kind: ReadKind) -> Duration {
+    with_health(|health| health.backoff(kind))
+}
+
+/// Whether the backoff after the last failed read of `kind` has run out.
+pub fn read_due(kind: ReadKind) -> bool {
+    with_health(|health| health.read_due(kind, Instant::now()))
+}
+
+pub fn health() -> WatcherHealth {
+    with_health(|health| health.clone())
+}
+
+// --- X11 (XFIXES) ---
+
+/// Subscribes to CLIPBOARD owner changes on a dedicated X connection and
+/// forwards each notification over a channel. One signal is queued up front so
+/// the watcher reads whatever is on the clipboard at startup.
+fn spawn_xfixes_listener() -> Result<Receiver<ReadPlan>, String> {
+    use x11rb::connection::{Connection, RequestConnection};
+    use x11rb::protocol::xfixes::{self, ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, WindowClass};
+    use x11rb::protocol::Event;
+
+    let (conn, screen_num) =
+        x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+
+    if conn
+        .extension_information(xfixes::X11_EXTENSION_NAME)
+        .map_err(|e| format!("extension query: {}", e))?
+        .is_none()
+    {
+        return Err("XFIXES extension not present".to_string());
+    }
+    // XFIXES requests are only valid after version negotiation.
+    conn.xfixes_query_version(5, 0)
+        .map_err(|e| format!("xfixes_query_version: {}", e))?
+        .reply()
+        .map_err(|e| format!("xfixes version reply: {}", e))?;
+
+    let root = conn.setup().roots[screen_num].root;
+    let window = conn
+        .generate_id()
+        .map_err(|e| format!("generate_id: {}", e))?;
+    conn.create_window(
+        x11rb::COPY_DEPTH_FROM_PARENT,
+        window,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        x11rb::COPY_FROM_PARENT,
+        &CreateWindowAux::new(),
+    )
+    .map_err(|e| format!("create_window: {}", e))?;
+
+    let clipboard = conn
+        .intern_atom(false, b"CLIPBOARD")
+        .map_err(|e| format!("intern_atom: {}", e))?
+        .reply()
+        .map_err(|e| format!("intern_atom reply: {}", e))?
+        .atom;
+    conn.xfixes_select_selection_input(
+        window,
+        clipboard,
+        SelectionEventMask::SET_SELECTION_OWNER
+            | SelectionEventMask::SELECTION_WINDOW_DESTROY
+            | SelectionEventMask::SELECTION_CLIENT_CLOSE,
+    )
+    .map_err(|e| format!("xfixes_select_selection_input: {}", e))?;
+    conn.flush().map_err(|e| format!("flush: {}", e))?;
+
+    let (tx, rx) = mpsc::channel();
+    let _ = tx.send(ReadPlan::ALL);
+
+    thread::Builder::new()
+        .name("xfixes-clipboard".to_string())
+        .spawn(move || loop {
+            match conn.wait_for_event() {
+                Ok(Event::XfixesSelectionNotify(_)) => {
+                    if tx.send(ReadPlan::ALL).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("clipboard watch: X connection error: {}", e);
+                    break;
+                }
+            }
+        })
+        .map_err(|e| format!("spawn listener thread: {}", e))?;
+
+    Ok(rx)
+}
+
+// --- Wayland (data-control) ---
+
+/// Listener state: MIME types announced for each live offer, and the channel
+/// to the watcher.
+struct WaylandWatch {
+    offers: HashMap<ObjectId, Vec<String>>,
+    tx: Sender<ReadPlan>,
+    finished: bool,
+}
+
+impl WaylandWatch {
+    fn new_offer(&mut self, id: ObjectId) {
+        self.offers.insert(id, Vec::new());
+    }
+
+    fn offered_type(&mut self, id: ObjectId, mime_type: String) {
+        self.offers.entry(id).or_default().push(mime_type);
+    }
+
+    /// A new CLIPBOARD selection: tell the watcher what it offers. `None`
+    /// means the clipboard was cleared, which is not a copy.
+    fn selection(&mut self, offer: Option<ObjectId>) {
+        let Some(id) = offer else {
+            return;
+        };
+        let types = self.offers.remove(&id).unwrap_or_default();
+        if self.tx.send(ReadPlan::from_mime_types(&types)).is_err() {
+            self.finished = true;
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for WaylandWatch {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(WaylandWatch: ignore WlSeat);
+delegate_noop!(WaylandWatch: ZwlrDataControlManagerV1);
+delegate_noop!(WaylandWatch: ExtDataControlManagerV1);
+
+/// The wlr and ext data-control protocols are identical apart from their
+/// names, so both device/offer pairs share one implementation.
+macro_rules! data_control_dispatch {
+    ($device:ty, $device_mod:ident, $offer:ty, $offer_mod:ident) => {
+        impl Dispatch<$device, ()> for WaylandWatch {
+            fn event(
+                state: &mut Self,
+                _: &$device,
+                event: $device_mod::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                match event {
+                    $device_mod::Event::DataOffer { id } => state.new_offer(id.id()),
+                    $device_mod::Event::Selection { id } => {
+                        state.selection(id.as_ref().map(Proxy::id));
+                        if let Some(offer) = id {
+                            offer.destroy();
+                        }
+                    }
+                    $device_mod::Event::PrimarySelection { id: Some(offer) } => {
+                        state.offers.remove(&offer.id());
+                        offer.destroy();
+                    }
+                    $device_mod::Event::Finished => state.finished = true,
+                    _ => {}
+                }
+            }
+
+            event_created_child!(WaylandWatch, $device, [
+                $device_mod::EVT_DATA_OFFER_OPCODE => ($offer, ()),
+            ]);
+        }
+
+        impl Dispatch<$offer, ()> for WaylandWatch {
+            fn event(
+                state: &mut Self,
+                offer: &$offer,
+                event: $offer_mod::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                if let $offer_mod::Event::Offer { mime_type } = event {
+                    state.offered_type(offer.id(), mime_type);
+                }
+            }
+        }
+    };
+}
+
+data_control_dispatch!(
+    ZwlrDataControlDeviceV1,
+    zwlr_data_control_device_v1,
+    ZwlrDataControlOfferV1,
+    zwlr_data_control_offer_v1
+);
+data_control_dispatch!(
+    ExtDataControlDeviceV1,
+    ext_data_control_device_v1,
+    ExtDataControlOfferV1,
+    ext_data_control_offer_v1
+);
+
+/// Binds a data-control device for the first seat, preferring the standard
+/// ext protocol over the wlroots one, and dispatches its selection events on
+/// a background thread. Fails on compositors without either (GNOME, KDE).
+/// The compositor sends the current selection right away, which gives the
+/// watcher its startup read.
+fn spawn_wayland_listener() -> Result<Receiver<ReadPlan>, String> {
+    let conn =
+        Connection::connect_to_env().map_err(|e| format!("Wayland connect failed: {}", e))?;
+    let (globals, mut queue) =
+        registry_queue_init::<WaylandWatch>(&conn).map_err(|e| format!("registry: {}", e))?;
+    let qh = queue.handle();
+
+    let seat: WlSeat = globals
+        .bind(&qh, 1..=1, ())
+        .map_err(|e| format!("no wl_seat: {}", e))?;
+
+    if let Ok(manager) = globals.bind::<ExtDataControlManagerV1, _, _>(&qh, 1..=1, ()) {
+        manager.get_data_device(&seat, &qh, ());
+    } else if let Ok(manager) = globals.bind::<ZwlrDataControlManagerV1, _, _>(&qh, 1..=2, ()) {
+        manager.get_data_device(&seat, &qh, ());
+    } else {
+        return Err("compositor offers no data-control protocol".to_string());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut state = WaylandWatch {
+        offers: HashMap::new(),
+        tx,
+        finished: false,
+    };
+    // Surface protocol errors here rather than in the listener thread.
+    queue
+        .roundtrip(&mut state)
+        .map_err(|e| format!("roundtrip: {}", e))?;
+
+    thread::Builder::new()
+        .name("wayland-clipboard".to_string())
+        .spawn(move || {
+            while !state.finished {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    warn!("clipboard watch: Wayland dispatch error: {}", e);
+                    break;
+                }
+            }
+        })
+        .map_err(|e| format!("spawn listener thread: {}", e))?;
+
+    Ok(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forced_polling_reads_every_tick() {
+        let mut source = ChangeSource::start(true);
+        assert!(!source.is_event_driven());
+        assert_eq!(source.wait(), Some(ReadPlan::ALL));
+    }
+
+    #[test]
+    fn test_event_source_coalesces_and_falls_back() {
+        let (tx, rx) = mpsc::channel();
+        let mut source = ChangeSource::Events(rx);
+
+        // No event: the wait times out without asking for a read.
+        assert_eq!(source.wait(), None);
+
+        // A burst of events is a single read covering all of them.
+        let text_only = ReadPlan {
+            text: true,
+            image: false,
+            no_history: None,
+        };
+        let image_only = ReadPlan {
+            text: false,
+            image: true,
+            no_history: None,
+        };
+        tx.send(text_only).unwrap();
+        tx.send(image_only).unwrap();
+        assert_eq!(source.wait(), Some(ReadPlan::ALL));
+        assert_eq!(source.wait(), None);
+
+        // A dead listener degrades to polling instead of going silent.
+        drop(tx);
+        assert_eq!(source.wait(), Some(ReadPlan::ALL));
+        assert!(!source.is_event_driven());
+    }
+
+    #[test]
+    fn test_image_throttle_reads_on_change_and_backstop() {
+        let mut throttle = ImageReadThrottle::default();
+        assert!(throttle.should_read(None), "first tick always reads");
+
+        // An idle clipboard only gets the periodic backstop read.
+        let idle_reads = (0..60).filter(|_| throttle.should_read(None)).count();
+        assert_eq!(idle_reads, 60 / IMAGE_BACKSTOP_TICKS as usize);
+
+        // A text change triggers an immediate read and restarts the backstop.
+        assert!(throttle.should_read(Some(1)));
+        assert!(!throttle.should_read(Some(1)));
+        assert!(throttle.should_read(None), "text cleared is a change too");
+    }
+
+    #[test]
+    fn test_primary_selection_settles_before_reporting() {
+        let start = Instant::now();
+        let later = |ms| start + Duration::from_millis(ms);
+        let mut settle = SelectionSettle::default();
+
+        // A drag in progress keeps changing the selection.
+        assert!(!settle.observe(1, later(0)));
+        assert!(!settle.observe(2, later(500)));
+        assert!(!settle.observe(3, later(1000)));
+
+        // The final selection is reported once it has been stable long enough.
+        assert!(!settle.observe(3, later(1500)));
+        assert!(settle.observe(3, later(2200)));
+        assert!(!settle.observe(3, later(5000)), "reported only once");
+
+        settle.mark_seen(4);
+        assert!(!settle.observe(4, later(6000)));
+        assert!(!settle.observe(4, later(9000)));
+    }
+
+    #[test]
+    fn test_failures_back_off_and_report_once() {
+        let text = ReadKind::Text;
+        let mut health = WatcherHealth::default();
+        assert_eq!(health.backoff(text), Duration::ZERO);
+
+        assert_eq!(health.record_failure(text, "busy"), None);
+        assert_eq!(health.record_failure(text, "busy"), None);
+        assert!(matches!(
+            health.record_failure(text, "busy"),
+            Some(HealthChange::Failing(msg))
+                if msg.contains("busy") && msg.contains("Retrying in 4 seconds")
+        ));
+        assert_eq!(
+            health.record_failure(text, "busy"),
+            None,
+            "reported only once"
+        );
+
+        let backoffs: Vec<Duration> = (0..10)
+            .map(|_| {
+                health.record_failure(text, "busy");
+                health.backoff(text)
+            })
+            .collect();
+        assert!(backoffs.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(
+            *backoffs.last().unwrap(),
+            MAX_FAILURE_BACKOFF - POLL_INTERVAL
+        );
+        assert_eq!(health.total_failures, 14);
+        assert!(!health.read_due(text, Instant::now()));
+
+        let now = Utc::now();
+        assert_eq!(
+            health.record_success(text, now),
+            Some(HealthChange::Recovered)
+        );
+        assert_eq!(health.record_success(text, now), None);
+        assert_eq!(health.backoff(text), Duration::ZERO);
+        assert!(health.read_due(text, Instant::now()));
+        assert_eq!(health.last_success, Some(now));
+    }
+
+    #[test]
+    fn test_text_and_image_back_off_separately() {
+        let mut health = WatcherHealth::default();
+        for _ in 0..FAILURES_BEFORE_REPORT {
+            health.record_failure(ReadKind::Image, "decode failed");
+        }
+        assert!(health.reported);
+        assert_eq!(health.backoff(ReadKind::Text), Duration::ZERO);
+        assert!(health.read_due(ReadKind::Text, Instant::now()));
+        assert!(!health.read_due(ReadKind::Image, Instant::now()));
+
+        // Text reads working says nothing about images
+        assert_eq!(health.record_success(ReadKind::Text, Utc::now()), None);
+        assert!(health.backoff(ReadKind::Image) > Duration::ZERO);
+        assert_eq!(
+            health.record_success(ReadKind::Image, Utc::now()),
+            Some(HealthChange::Recovered)
+        );
+    }
+
+    #[test]
+    fn test_read_plan_from_mime_types() {
+        let types = |list: &[&str]| list.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        let text = ReadPlan::from_mime_types(&types(&["text/plain;charset=utf-8", "TEXT"]));
+        assert!(text.text && !text.image);
+
+        let screenshot = ReadPlan::from_mime_types(&types(&["image/png"]));
+        assert!(!screenshot.text && screenshot.image);
+
+        let file =
+            ReadPlan::from_mime_types(&types(&["text/uri-list", "x-special/gnome-copied-files"]));
+        assert!(file.text && !file.image);
+
+        let html_only = ReadPlan::from_mime_types(&types(&["text/html"]));
+        assert_eq!(
+            html_only,
+            ReadPlan {
+                text: false,
+                image: false,
+                no_history: Some(false),
+            }
+        );
+
+        let secret =
+            ReadPlan::from_mime_types(&types(&["text/plain", "x-kde-passwordManagerHint"]));
+        assert_eq!(secret.no_history, Some(true));
+        assert_eq!(
+            secret.merge(text).no_history,
+            Some(true),
+            "a merged event never drops the hint"
+        );
+        assert_eq!(text.merge(ReadPlan::ALL).no_history, None);
+    }
+
+    #[test]
+    fn test_no_history_hint_queries_targets_at_most_once() {
+        let queries = std::cell::Cell::new(0);
+        let mut hint = NoHistoryHint::new(None, || {
+            queries.set(queries.get() + 1);
+            Some(vec![
+                "UTF8_STRING".to_string(),
+                "x-kde-passwordManagerHint".to_string(),
+            ])
+        });
+        assert!(hint.is_set());
+        assert!(hint.is_set());
+        assert_eq!(queries.get(), 1);
+
+        // Known offers (Wayland) and missing tools never query.
+        let mut known = NoHistoryHint::new(Some(false), || -> Option<Vec<String>> {
+            panic!("targets must not be queried")
+        });
+        assert!(!known.is_set());
+        assert!(!NoHistoryHint::new(None, || None).is_set());
+    }
+}
@@ -1,25 +1,110 @@
 //! Config Manager Module
 //! Handles persistence of window state (position, monitor) specifically for Wayland usage.
 
+use crate::atomic_write::{self, LoadIssue};
 use log::warn;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use tauri::{Monitor, PhysicalPosition, PhysicalSize};
 
 const CONFIG_FILE: &str = "window_state.json";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WindowState {
-    pub monitor_name: Option<String>,
+/// Where the window was on one monitor. `width`/`height` are the inner size,
+/// 0 when not recorded (files from before sizes were kept).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowPlacement {
     pub x: i32,
     pub y: i32,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+}
+
+impl WindowPlacement {
+    fn size(&self) -> Option<PhysicalSize<u32>> {
+        (self.width > 0 && self.height > 0).then(|| PhysicalSize::new(self.width, self.height))
+    }
+}
+
+/// Key for placements recorded while the monitor was unknown.
+pub const UNKNOWN_MONITOR: &str = "";
+
+/// Saved window placements, one per monitor (see `monitor_key`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(from = "StoredWindowState")]
+pub struct WindowState {
+    pub monitors: BTreeMap<String, WindowPlacement>,
+    /// Key of the monitor the window was last placed on
+    pub last_monitor: Option<String>,
+}
+
+/// `window_state.json` as written by any version: the current per-monitor
+/// map, or the old single position.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredWindowState {
+    Current {
+        monitors: BTreeMap<String, WindowPlacement>,
+        #[serde(default)]
+        last_monitor: Option<String>,
+    },
+    Legacy {
+        monitor_name: Option<String>,
+        x: i32,
+        y: i32,
+        #[serde(default = "default_true")]
+        saved: bool,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl From<StoredWindowState> for WindowState {
+    fn from(stored: StoredWindowState) -> Self {
+        match stored {
+            StoredWindowState::Current {
+                monitors,
+                last_monitor,
+            } => Self {
+                monitors,
+                last_monitor,
+            },
+            StoredWindowState::Legacy { saved: false, .. } => Self::default(),
+            StoredWindowState::Legacy {
+                monitor_name, x, y, ..
+            } => {
+                let key = monitor_name.unwrap_or_else(|| UNKNOWN_MONITOR.to_string());
+                let placement = WindowPlacement {
+                    x,
+                    y,
+                    width: 0,
+                    height: 0,
+                };
+                Self {
+                    monitors: BTreeMap::from([(key.clone(), placement)]),
+                    last_monitor: Some(key),
+                }
+            }
+        }
+    }
+}
+
+/// Identifies a monitor across runs: its connector name, or its resolution
+/// and origin when the name is unavailable.
+pub fn monitor_key(monitor: &Monitor) -> String {
+    MonitorArea::from(monitor).key()
 }
 
 pub struct ConfigManager {
     data_dir: PathBuf,
     state: WindowState,
     dirty: bool, // Tracks if we have unsaved changes in memory
+    /// A damaged state file found on load, until taken for the UI
+    load_issue: Option<LoadIssue>,
 }
 
 impl ConfigManager {
@@ -28,27 +113,43 @@ impl ConfigManager {
             data_dir,
             state: WindowState::default(),
             dirty: false,
+            load_issue: None,
         };
-
-        if let Err(e) = manager.load() {
-            warn!(
-                "failed to load window state config: {}. Defaulting to empty state.",
-                e
-            );
-        }
-
+        manager.load();
         manager
     }
 
+    /// Returns and clears the problem found loading the state file, if any.
+    pub fn take_load_issue(&mut self) -> Option<LoadIssue> {
+        self.load_issue.take()
+    }
+
     pub fn get_state(&self) -> WindowState {
         self.state.clone()
     }
 
-    /// Updates the state in memory only. Use sync_to_disk() to flush.
-    pub fn update_state(&mut self, monitor_name: Option<String>, x: i32, y: i32) {
-        self.state.monitor_name = monitor_name;
-        self.state.x = x;
-        self.state.y = y;
+    /// Records the window's position and inner size on `monitor` in memory
+    /// only. Use sync_to_disk() to flush.
+    pub fn update_state(
+        &mut self,
+        monitor: Option<&Monitor>,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+    ) {
+        let key = monitor.map_or_else(|| UNKNOWN_MONITOR.to_string(), monitor_key);
+        let placement = WindowPlacement {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        };
+        if self.state.monitors.get(&key) == Some(&placement)
+            && self.state.last_monitor.as_ref() == Some(&key)
+        {
+            return;
+        }
+        self.state.monitors.insert(key.clone(), placement);
+        self.state.last_monitor = Some(key);
         self.dirty = true;
     }
 
@@ -69,67 +170,145 @@ impl ConfigManager {
         self.data_dir.join(CONFIG_FILE)
     }
 
-    fn load(&mut self) -> Result<(), String> {
-        let path = self.config_path();
-        if !path.exists() {
-            return Ok(());
-        }
-        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-        self.state = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-        Ok(())
+    fn load(&mut self) {
+        let (state, issue) = atomic_write::load_with_backup(&self.config_path(), |content| {
+            serde_json::from_str(content).map_err(|e| e.to_string())
+        });
+        self.state = state.unwrap_or_default();
+        self.load_issue = issue;
     }
 
     fn save_to_disk(&self) -> Result<(), String> {
-        if !self.data_dir.exists() {
-            fs::create_dir_all(&self.data_dir).map_err(|e| e.to_string())?;
-        }
         let content = serde_json::to_string_pretty(&self.state).map_err(|e| e.to_string())?;
-        fs::write(self.config_path(), content).map_err(|e| e.to_string())?;
-        Ok(())
+        atomic_write::save_with_backup(&self.config_path(), content.as_bytes())
+            .map_err(|e| e.to_string())
     }
 }
 
-/// Determines where the window should be placed based on saved state and available monitors.
+/// The parts of a monitor window placement depends on.
+#[derive(Debug, Clone, PartialEq)]
+struct MonitorArea {
+    name: Option<String>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+}
+
+impl MonitorArea {
+    fn key(&self) -> String {
+        match &self.name {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => format!(
+                "{}x{}@{},{}",
+                self.size.width, self.size.height, self.position.x, self.position.y
+            ),
+        }
+    }
+}
+
+impl From<&Monitor> for MonitorArea {
+    fn from(monitor: &Monitor) -> Self {
+        Self {
+            name: monitor.name().cloned(),
+            position: *monitor.position(),
+            size: *monitor.size(),
+        }
+    }
+}
+
+/// Where to put the window, and the size to restore if one was saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedPlacement {
+    pub position: PhysicalPosition<i32>,
+    pub size: Option<PhysicalSize<u32>>,
+}
+
+/// Determines where the window should be placed based on saved state and
+/// available monitors, preferring the entry for `current_monitor`.
 pub fn resolve_window_position(
-    _state: &WindowState,
+    state: &WindowState,
     available_monitors: &[Monitor],
+    current_monitor: Option<&Monitor>,
     window_size: PhysicalSize<u32>,
-) -> PhysicalPosition<i32> {
-    // 1. Try to restore saved position if monitor exists and position is valid
-    /*
-    if let Some(saved_monitor_name) = &state.monitor_name {
-        if let Some(monitor) = available_monitors.iter().find(|m| {
-            m.name()
-                .is_some_and(|n| n.as_str() == saved_monitor_name.as_str())
-        }) {
-            if is_position_valid(state.x, state.y, monitor, window_size) {
-                return PhysicalPosition::new(state.x, state.y);
+) -> ResolvedPlacement {
+    let monitors: Vec<MonitorArea> = available_monitors.iter().map(MonitorArea::from).collect();
+    let current = current_monitor.map(MonitorArea::from);
+    resolve_position(state, &monitors, current.as_ref(), window_size)
+}
+
+fn resolve_position(
+    state: &WindowState,
+    monitors: &[MonitorArea],
+    current: Option<&MonitorArea>,
+    window_size: PhysicalSize<u32>,
+) -> ResolvedPlacement {
+    let restored = |placement: &WindowPlacement| ResolvedPlacement {
+        position: PhysicalPosition::new(placement.x, placement.y),
+        size: placement.size(),
+    };
+    let valid_on = |placement: &WindowPlacement, monitor: &MonitorArea| {
+        is_position_valid(
+            placement.x,
+            placement.y,
+            monitor,
+            placement.size().unwrap_or(window_size),
+        )
+    };
+    let find_monitor = |key: &str| monitors.iter().find(|m| m.key() == key);
+
+    // 1. The placement saved for the monitor we're on
+    if let Some(monitor) = current {
+        if let Some(placement) = state.monitors.get(&monitor.key()) {
+            if valid_on(placement, monitor) {
+                return restored(placement);
             }
         }
     }
-    */
 
-    // 2. Fallback: Default to Bottom-Center of Primary (or first available)
-    let target_monitor = available_monitors
-        .iter()
-        .find(|m| m.scale_factor() > 0.0) // Just a check to get first valid one
-        .unwrap_or(&available_monitors[0]);
+    // 2. The last placement, on the monitor it was saved on when that is
+    // connected; when it's gone (docked/undocked), any monitor holding the
+    // point will do.
+    if let Some((key, placement)) = state
+        .last_monitor
+        .as_ref()
+        .and_then(|key| Some((key, state.monitors.get(key)?)))
+    {
+        let restorable = match find_monitor(key) {
+            Some(monitor) => valid_on(placement, monitor),
+            None => monitors.iter().any(|m| valid_on(placement, m)),
+        };
+        if restorable {
+            return restored(placement);
+        }
+    }
 
-    calculate_bottom_center(target_monitor, window_size)
+    // 3. Fallback: Default to Bottom-Center of the current (or first) monitor
+    let Some(target) = current.or(monitors.first()) else {
+        return ResolvedPlacement {
+            position: PhysicalPosition::new(0, 0),
+            size: None,
+        };
+    };
+    ResolvedPlacement {
+        position: calculate_bottom_center(target, window_size),
+        size: None,
+    }
 }
 
 /// Checks if a coordinate is "valid" based on bounds and visibility heuristics.
-#[allow(dead_code)]
-fn is_position_valid(x: i32, y: i32, monitor: &Monitor, window_size: PhysicalSize<u32>) -> bool {
+fn is_position_valid(
+    x: i32,
+    y: i32,
+    monitor: &MonitorArea,
+    window_size: PhysicalSize<u32>,
+) -> bool {
     is_top_left_within_monitor(x, y, monitor)
         && has_min_vertical_visibility(y, monitor, window_size)
 }
 
 /// Ensures the window's top-left corner is strictly inside the monitor bounds.
-#[allow(dead_code)]
-fn is_top_left_within_monitor(x: i32, y: i32, monitor: &Monitor) -> bool {
-    let m_pos = monitor.position();
-    let m_size = monitor.size();
+fn is_top_left_within_monitor(x: i32, y: i32, monitor: &MonitorArea) -> bool {
+    let m_pos = monitor.position;
+    let m_size = monitor.size;
 
     x >= m_pos.x
         && x < (m_pos.x + m_size.width as i32)
@@ -138,10 +317,13 @@ fn is_top_left_within_monitor(x: i32, y: i32, monitor: &Monitor) -> bool {
 }
 
 /// Ensures at least the top half of the window remains visible on the monitor.
-#[allow(dead_code)]
-fn has_min_vertical_visibility(y: i32, monitor: &Monitor, window_size: PhysicalSize<u32>) -> bool {
-    let m_pos = monitor.position();
-    let m_size = monitor.size();
+fn has_min_vertical_visibility(
+    y: i32,
+    monitor: &MonitorArea,
+    window_size: PhysicalSize<u32>,
+) -> bool {
+    let m_pos = monitor.position;
+    let m_size = monitor.size;
     // Ensure y is not so low that the window falls off the bottom entirely.
     // We require the top half (height/2) to be above the bottom edge of the monitor.
     y < (m_pos.y + m_size.height as i32 - (window_size.height as i32 / 2))
@@ -149,13 +331,13 @@ fn has_min_vertical_visibility(y: i32, monitor: &Monitor, window_size: PhysicalS
 
 /// Calculates a centered position at the bottom of the screen.
 fn calculate_bottom_center(
-    monitor: &Monitor,
+    monitor: &MonitorArea,
     window_size: PhysicalSize<u32>,
 ) -> PhysicalPosition<i32> {
     const PADDING_BOTTOM: i32 = 45;
 
-    let m_pos = monitor.position();
-    let m_size = monitor.size();
+    let m_pos = monitor.position;
+    let m_size = monitor.size;
 
     // X = center horizontally
     let x = m_pos.x + (m_size.width as i32 / 2) - (window_size.width as i32 / 2);
@@ -165,3 +347,143 @@ fn calculate_bottom_center(
 
     PhysicalPosition::new(x, y)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: PhysicalSize<u32> = PhysicalSize::new(360, 480);
+
+    fn monitor(name: &str, x: i32, width: u32) -> MonitorArea {
+        MonitorArea {
+            name: Some(name.to_string()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(width, 1080),
+        }
+    }
+
+    fn placement(x: i32, y: i32) -> WindowPlacement {
+        WindowPlacement {
+            x,
+            y,
+            width: 0,
+            height: 0,
+        }
+    }
+
+    fn saved(monitor_name: Option<&str>, x: i32, y: i32) -> WindowState {
+        let key = monitor_name.unwrap_or(UNKNOWN_MONITOR).to_string();
+        WindowState {
+            monitors: BTreeMap::from([(key.clone(), placement(x, y))]),
+            last_monitor: Some(key),
+        }
+    }
+
+    fn position(
+        state: &WindowState,
+        monitors: &[MonitorArea],
+        current: Option<&MonitorArea>,
+    ) -> PhysicalPosition<i32> {
+        resolve_position(state, monitors, current, WINDOW).position
+    }
+
+    #[test]
+    fn restores_position_on_saved_monitor() {
+        let monitors = [monitor("eDP-1", 0, 1920), monitor("HDMI-A-1", 1920, 2560)];
+        let state = saved(Some("HDMI-A-1"), 2500, 300);
+        assert_eq!(
+            position(&state, &monitors, None),
+            PhysicalPosition::new(2500, 300)
+        );
+    }
+
+    #[test]
+    fn missing_saved_monitor_falls_back_to_one_containing_the_point() {
+        let monitors = [monitor("eDP-1", 0, 1920), monitor("DP-3", 1920, 2560)];
+        let state = saved(Some("HDMI-A-1"), 2500, 300);
+        assert_eq!(
+            position(&state, &monitors, None),
+            PhysicalPosition::new(2500, 300)
+        );
+    }
+
+    #[test]
+    fn off_screen_position_goes_bottom_center() {
+        let monitors = [monitor("eDP-1", 0, 1920)];
+        // Undocked: the saved point was on a monitor that no longer exists
+        let state = saved(Some("HDMI-A-1"), 2500, 300);
+        let bottom_center = PhysicalPosition::new(960 - 180, 1080 - 480 - 45);
+        assert_eq!(position(&state, &monitors, None), bottom_center);
+
+        // On the saved monitor but too low to leave the window visible
+        let state = saved(Some("eDP-1"), 100, 1000);
+        assert_eq!(position(&state, &monitors, None), bottom_center);
+    }
+
+    #[test]
+    fn single_monitor_fallback() {
+        let monitors = [monitor("eDP-1", 0, 1920)];
+        let bottom_center = PhysicalPosition::new(780, 555);
+        // Never saved: nothing is restored
+        assert_eq!(
+            position(&WindowState::default(), &monitors, None),
+            bottom_center
+        );
+        // Saved without a monitor name
+        assert_eq!(
+            position(&saved(None, 50, 60), &monitors, None),
+            PhysicalPosition::new(50, 60)
+        );
+    }
+
+    #[test]
+    fn picks_the_entry_for_the_current_monitor() {
+        let laptop = monitor("eDP-1", 0, 1920);
+        let external = monitor("DP-1", 1920, 3840);
+        let monitors = [laptop.clone(), external.clone()];
+        let mut state = saved(Some("eDP-1"), 100, 200);
+        state.monitors.insert(
+            "DP-1".to_string(),
+            WindowPlacement {
+                x: 3000,
+                y: 100,
+                width: 720,
+                height: 960,
+            },
+        );
+
+        let on_external = resolve_position(&state, &monitors, Some(&external), WINDOW);
+        assert_eq!(on_external.position, PhysicalPosition::new(3000, 100));
+        assert_eq!(on_external.size, Some(PhysicalSize::new(720, 960)));
+        assert_eq!(
+            position(&state, &monitors, Some(&laptop)),
+            PhysicalPosition::new(100, 200)
+        );
+    }
+
+    #[test]
+    fn unnamed_monitors_are_keyed_by_geometry() {
+        let unnamed = MonitorArea {
+            name: None,
+            position: PhysicalPosition::new(1920, 0),
+            size: PhysicalSize::new(2560, 1440),
+        };
+        assert_eq!(unnamed.key(), "2560x1440@1920,0");
+        assert_eq!(monitor("eDP-1", 0, 1920).key(), "eDP-1");
+    }
+
+    #[test]
+    fn legacy_single_position_file_migrates() {
+        let state: WindowState =
+            serde_json::from_str(r#"{"monitor_name": "eDP-1", "x": 40, "y": 50}"#).unwrap();
+        assert_eq!(state, saved(Some("eDP-1"), 40, 50));
+
+        let state: WindowState =
+            serde_json::from_str(r#"{"monitor_name": null, "x": 40, "y": 50}"#).unwrap();
+        assert_eq!(state, saved(None, 40, 50));
+
+        // Round-trips in the new format
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<WindowState>(&json).unwrap(), state);
+    }
+}
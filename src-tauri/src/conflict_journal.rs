@@ -0,0 +1,209 @@
+//! Conflict Resolution Journal
+//! Before resolving a shortcut conflict rebinds one of the desktop's gsettings
+//! or dconf keys, the key's value is recorded here, in the app's data directory, so
+//! the change can be undone later from the setup wizard.
+
+use crate::atomic_write;
+use crate::environment::host_command;
+use crate::shortcut_conflict_detector::ShortcutConflict;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// One gsettings key we changed to resolve a conflict
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedConflict {
+    pub id: String,
+    /// Milliseconds since the Unix epoch in JSON
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+    /// The gsettings schema, or the dconf directory (`/org/gnome/...`) for
+    /// keys changed with `dconf write`
+    pub schema: String,
+    pub key: String,
+    /// The key's value before the change, as printed by `gsettings get`
+    /// or `dconf read`
+    pub old_value: String,
+    /// The command that changed it
+    pub command: String,
+    /// Who owned the shortcut (e.g., "GNOME Shell")
+    pub owner: String,
+    pub binding: String,
+    /// Set once the old value has been put back
+    #[serde(default)]
+    pub consumed: bool,
+}
+
+/// The journal file of resolved conflicts
+pub struct ConflictJournal {
+    path: PathBuf,
+}
+
+impl ConflictJournal {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// All entries, newest first. A missing or unreadable journal means
+    /// there are none.
+    pub fn list(&self) -> Vec<ResolvedConflict> {
+        let mut entries: Vec<ResolvedConflict> = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        entries
+    }
+
+    fn save(&self, entries: &[ResolvedConflict]) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(entries)?;
+        atomic_write::write_atomic(&self.path, json.as_bytes())
+    }
+
+    /// Records that `schema key`, holding `old_value`, is about to be changed
+    /// by `command` to resolve `conflict`
+    pub fn record(
+        &self,
+        conflict: &ShortcutConflict,
+        command: &str,
+        schema: &str,
+        key: &str,
+        old_value: &str,
+    ) -> io::Result<ResolvedConflict> {
+        let entry = ResolvedConflict {
+            id: Uuid::new_v4().to_string(),
+            created_at: Utc::now(),
+            schema: schema.to_string(),
+            key: key.to_string(),
+            old_value: old_value.to_string(),
+            command: command.to_string(),
+            owner: conflict.owner.clone(),
+            binding: conflict.binding.clone(),
+            consumed: false,
+        };
+        let mut entries = self.list();
+        entries.insert(0, entry.clone());
+        self.save(&entries)?;
+        Ok(entry)
+    }
+
+    /// Sets entry `id`'s key back to its old value and marks the entry
+    /// consumed, so it can't be undone twice
+    pub fn undo(&self, id: &str) -> Result<ResolvedConflict, String> {
+        let mut entries = self.list();
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| format!("Unknown conflict resolution '{}'", id))?;
+        if entry.consumed {
+            return Err(format!(
+                "The change to {} {} was already undone",
+                entry.schema, entry.key
+            ));
+        }
+
+        let output = if entry.schema.starts_with('/') {
+            host_command("dconf")
+                .args([
+                    "write",
+                    &format!("{}{}", entry.schema, entry.key),
+                    &entry.old_value,
+                ])
+                .output()
+                .map_err(|e| format!("Failed to run dconf: {}", e))?
+        } else {
+            host_command("gsettings")
+                .args(["set", &entry.schema, &entry.key, &entry.old_value])
+                .output()
+                .map_err(|e| format!("Failed to run gsettings: {}", e))?
+        };
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to restore {} {}: {}",
+                entry.schema,
+                entry.key,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        entry.consumed = true;
+        let entry = entry.clone();
+        self.save(&entries)
+            .map_err(|e| format!("Failed to update the conflict journal: {}", e))?;
+        log::info!(
+            "restored {} {} to {}",
+            entry.schema,
+            entry.key,
+            entry.old_value
+        );
+        Ok(entry)
+    }
+}
+
+impl Default for ConflictJournal {
+    fn default() -> Self {
+        let path = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("penguinclip")
+            .join("resolved-conflicts.json");
+        Self::new(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn conflict() -> ShortcutConflict {
+        ShortcutConflict {
+            binding: "<Super>v".to_string(),
+            conflicts_with: "Clipboard History".to_string(),
+            current_action: "Show notification list".to_string(),
+            owner: "GNOME Shell".to_string(),
+            settings_path: Vec::new(),
+            manual_steps: Vec::new(),
+            commands: Vec::new(),
+            resolution_steps: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_consume() {
+        let dir = temp_dir().join("penguinclip_test_conflict_journal");
+        let _ = fs::remove_dir_all(&dir);
+        let journal = ConflictJournal::new(dir.join("resolved-conflicts.json"));
+        assert!(journal.list().is_empty());
+
+        let command =
+            "gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super>m']\"";
+        let entry = journal
+            .record(
+                &conflict(),
+                command,
+                "org.gnome.shell.keybindings",
+                "toggle-message-tray",
+                "['<Super>v', '<Super>m']",
+            )
+            .unwrap();
+        let entries = journal.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, entry.id);
+        assert_eq!(entries[0].old_value, "['<Super>v', '<Super>m']");
+        assert!(!entries[0].consumed);
+
+        // Consumed entries are refused before gsettings is run
+        let mut entries = journal.list();
+        entries[0].consumed = true;
+        journal.save(&entries).unwrap();
+        assert!(journal
+            .undo(&entry.id)
+            .unwrap_err()
+            .contains("already undone"));
+        assert!(journal.undo("missing").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -3,6 +3,8 @@
 //! Provides a small file logger behind the `log` facade (so the rest of the
 //! code uses `log::info!` / `warn!` / `error!`), a panic hook that records
 //! crashes, and a redaction-safe diagnostics report the user can export.
+//! The full diagnostic report adds the setup state (permissions, shortcut
+//! tools, conflicts, rendering, watcher health and settings) as JSON.
 //!
 //! Privacy: clipboard content is never logged. Only host/session metadata and
 //! operational messages (ids, sizes, errors) are written, so the log and the
@@ -16,12 +18,29 @@ use std::sync::Mutex;
 
 use chrono::Utc;
 use log::{LevelFilter, Metadata, Record};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::clipboard_watch::{self, WatcherHealth};
+use crate::environment::{self, EnvironmentInfo};
+use crate::logger;
+use crate::permission_checker::{self, PermissionStatus};
+use crate::rendering_env::{self, RenderingEnv};
+use crate::session::{self, SessionInfo};
+use crate::shortcut_conflict_detector::ConflictDetectionResult;
+use crate::shortcut_setup::{self, ShortcutToolsStatus};
+use crate::user_settings::UserSettingsManager;
 
 const LOG_FILE_NAME: &str = "penguinclip.log";
 const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024; // rotate at 2 MiB
 const DEFAULT_RECENT_LINES: usize = 200;
 const DISABLED_MARKER: &str = "logging.disabled";
 
+/// Settings that hold credentials or the user's own data, replaced with
+/// `REDACTED` in the diagnostic report when set
+const REDACTED_SETTINGS: &[&str] = &["tenor_api_key", "excluded_patterns", "excluded_apps"];
+const REDACTED: &str = "<redacted>";
+
 /// Runtime switch the user can flip from Settings → Logs. The file logger checks
 /// it on every record, so disabling stops new writes immediately (and a marker
 /// file persists the choice across restarts).
@@ -167,10 +186,6 @@ impl log::Log for FileLogger {
         if !self.enabled(record.metadata()) {
             return;
         }
-        // Respect the user's runtime logging toggle (Settings → Logs).
-        if !LOGGING_ENABLED.load(Ordering::Relaxed) {
-            return;
-        }
         let line = format!(
             "{} [{:<5}] {}: {}\n",
             Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
@@ -178,6 +193,12 @@ impl log::Log for FileLogger {
             record.target(),
             record.args()
         );
+        // The in-memory buffer keeps lines for diagnostic reports either way
+        logger::record(&line);
+        // Respect the user's runtime logging toggle (Settings → Logs).
+        if !LOGGING_ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
         // Echo to stderr too (handy under journalctl / dev).
         eprint!("{}", line);
         if let Ok(mut guard) = self.file.lock() {
@@ -286,6 +307,87 @@ pub fn export_report(data_dir: &Path) -> Result<PathBuf, String> {
     Ok(path)
 }
 
+/// Everything needed to triage a setup problem, in one file
+#[derive(Serialize)]
+pub struct DiagnosticReport {
+    pub generated_at: String,
+    pub version: String,
+    pub environment: EnvironmentInfo,
+    pub session: SessionInfo,
+    pub permissions: PermissionStatus,
+    pub shortcut_tools: ShortcutToolsStatus,
+    pub conflicts: ConflictDetectionResult,
+    pub rendering: RenderingEnv,
+    pub watcher_health: WatcherHealth,
+    /// The user's settings with `REDACTED_SETTINGS` blanked
+    pub settings: Value,
+    /// The most recent lines logged by this process
+    pub recent_log: Vec<String>,
+}
+
+/// Replaces the non-empty `REDACTED_SETTINGS` in serialized settings
+fn redact_settings(mut settings: Value) -> Value {
+    if let Some(map) = settings.as_object_mut() {
+        for key in REDACTED_SETTINGS {
+            let Some(value) = map.get_mut(*key) else {
+                continue;
+            };
+            let empty = match value {
+                Value::String(s) => s.is_empty(),
+                Value::Array(items) => items.is_empty(),
+                _ => false,
+            };
+            if !empty {
+                *value = Value::String(REDACTED.to_string());
+            }
+        }
+    }
+    settings
+}
+
+/// Gathers the full diagnostic report. Runs the same checks as the setup
+/// wizard, so it takes a moment.
+pub async fn collect_diagnostic_report() -> DiagnosticReport {
+    let settings = serde_json::to_value(UserSettingsManager::new().load()).unwrap_or_default();
+    DiagnosticReport {
+        generated_at: Utc::now().to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        environment: environment::detect().clone(),
+        session: session::info().clone(),
+        permissions: permission_checker::check_permissions(),
+        shortcut_tools: shortcut_setup::check_shortcut_tools().await,
+        conflicts: shortcut_setup::detect_conflicts(),
+        rendering: rendering_env::get_rendering_env().clone(),
+        watcher_health: clipboard_watch::health(),
+        settings: redact_settings(settings),
+        recent_log: logger::recent(DEFAULT_RECENT_LINES),
+    }
+}
+
+/// Writes the full diagnostic report as JSON to `path` (a file, or a
+/// directory to put a timestamped file in), or to the data dir when `None`.
+/// Returns the file written.
+pub async fn write_diagnostic_report(
+    data_dir: &Path,
+    path: Option<&Path>,
+) -> Result<PathBuf, String> {
+    let file_name = format!(
+        "penguinclip-diagnostics-{}.json",
+        Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = match path {
+        Some(path) if path.is_dir() => path.join(file_name),
+        Some(path) => path.to_path_buf(),
+        None => data_dir.join(file_name),
+    };
+    let report = collect_diagnostic_report().await;
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("could not serialize diagnostics: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("could not write diagnostics file: {}", e))?;
+    log::info!("wrote diagnostic report to {}", path.display());
+    Ok(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,6 +457,22 @@ mod tests {
         assert!(report.contains("(no log entries)"));
     }
 
+    #[test]
+    fn test_redact_settings_blanks_secrets() {
+        let settings = serde_json::json!({
+            "tenor_api_key": "AIzaSecret",
+            "excluded_patterns": [],
+            "excluded_apps": ["keepassxc"],
+            "max_history_size": 50,
+        });
+        let redacted = redact_settings(settings);
+        assert_eq!(redacted["tenor_api_key"], REDACTED);
+        // Empty values stay as they are, so the report shows they're unset
+        assert_eq!(redacted["excluded_patterns"], serde_json::json!([]));
+        assert_eq!(redacted["excluded_apps"], REDACTED);
+        assert_eq!(redacted["max_history_size"], 50);
+    }
+
     #[test]
     fn test_collect_startup_info_reports_version() {
         let info = collect_startup_info(Path::new("/tmp/penguinclip"));
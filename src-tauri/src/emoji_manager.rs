@@ -1,18 +1,269 @@
 //! Emoji Manager Module
-//! Handles emoji usage tracking with LRU (Least Recently Used) semantics and disk persistence.
+//! Handles emoji usage tracking with LRU (Least Recently Used) semantics and disk persistence,
+//! and searching the embedded emoji dataset.
 
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Maximum number of recent emojis to track
 const MAX_RECENT_EMOJIS: usize = 20;
 
+/// Default half-life of an emoji use for `get_top_used_decayed`, in days
+pub const DEFAULT_DECAY_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Number of individual uses kept in the rolling usage log
+const MAX_USAGE_LOG: usize = 200;
+
+const MILLIS_PER_DAY: f64 = 86_400_000.0;
+
 /// Persistence filename
 const EMOJI_HISTORY_FILE: &str = "emoji_history.json";
 
+/// Embedded emoji dataset, one tab-separated emoji per line (see the header
+/// of the file for the columns).
+const EMOJI_DATA: &str = include_str!("../data/emoji.tsv");
+
+/// An emoji from the embedded dataset
+#[derive(Debug, Clone, Serialize)]
+pub struct EmojiInfo {
+    pub char: &'static str,
+    /// Unicode CLDR name, e.g. "thumbs up"
+    pub name: &'static str,
+    pub category: &'static str,
+    /// Shortcodes without colons, e.g. "thumbsup" for `:thumbsup:`
+    pub shortcodes: Vec<&'static str>,
+    pub keywords: Vec<&'static str>,
+    /// Whether the emoji accepts a skin tone modifier
+    pub skin_tones: bool,
+    /// Lowercased name, for case-insensitive matching
+    #[serde(skip)]
+    name_lower: String,
+}
+
+/// A picker category with its emojis, in dataset order
+#[derive(Debug, Clone, Serialize)]
+pub struct EmojiCategory {
+    pub name: &'static str,
+    pub emojis: Vec<EmojiInfo>,
+}
+
+/// The parsed dataset, built on first use.
+fn emoji_database() -> &'static [EmojiInfo] {
+    static DATABASE: OnceLock<Vec<EmojiInfo>> = OnceLock::new();
+    DATABASE.get_or_init(|| {
+        EMOJI_DATA
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let char = fields.next()?;
+                let category = fields.next()?;
+                let name = fields.next()?;
+                let shortcodes = fields.next()?.split_whitespace().collect();
+                let keywords = fields
+                    .next()?
+                    .split('|')
+                    .filter(|k| !k.is_empty())
+                    .collect();
+                let skin_tones = fields.next()? == "1";
+                Some(EmojiInfo {
+                    char,
+                    name,
+                    category,
+                    shortcodes,
+                    keywords,
+                    skin_tones,
+                    name_lower: name.to_lowercase(),
+                })
+            })
+            .collect()
+    })
+}
+
+/// All categories in picker order.
+pub fn emoji_categories() -> Vec<EmojiCategory> {
+    let mut categories: Vec<EmojiCategory> = Vec::new();
+    for emoji in emoji_database() {
+        match categories.last_mut() {
+            Some(category) if category.name == emoji.category => {
+                category.emojis.push(emoji.clone())
+            }
+            _ => categories.push(EmojiCategory {
+                name: emoji.category,
+                emojis: vec![emoji.clone()],
+            }),
+        }
+    }
+    categories
+}
+
+/// How well `emoji` matches a lowercased query; 0 means no match. Exact
+/// shortcodes rank above everything else.
+fn match_score(emoji: &EmojiInfo, query: &str) -> u32 {
+    let shortcode = query.trim_matches(':');
+    if emoji.shortcodes.contains(&shortcode) {
+        return 1000;
+    }
+    if emoji.name_lower == query {
+        return 600;
+    }
+
+    let word_prefix = |text: &str| {
+        text.split(|c: char| !c.is_alphanumeric())
+            .any(|word| word.starts_with(query))
+    };
+    if word_prefix(&emoji.name_lower) {
+        300
+    } else if emoji.shortcodes.iter().any(|c| c.starts_with(shortcode)) {
+        250
+    } else if emoji.keywords.iter().any(|k| word_prefix(k)) {
+        200
+    } else if emoji.name_lower.contains(query) {
+        100
+    } else if emoji.shortcodes.iter().any(|c| c.contains(shortcode))
+        || emoji.keywords.iter().any(|k| k.contains(query))
+    {
+        50
+    } else {
+        0
+    }
+}
+
+/// Zero-width joiner, gluing the parts of sequences like 🧑‍💻.
+const ZWJ: char = '\u{200D}';
+/// Variation selector requesting emoji presentation; dropped where a skin
+/// tone modifier takes its place.
+const VS16: char = '\u{FE0F}';
+/// 🤝 takes a tone on its own, but stays untoned inside 🧑‍🤝‍🧑.
+const HANDSHAKE: char = '\u{1F91D}';
+
+/// Fitzpatrick skin tone applied to emojis that support it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkinTone {
+    /// No modifier (the yellow emoji)
+    #[default]
+    Default,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+impl SkinTone {
+    fn modifier(self) -> Option<char> {
+        match self {
+            Self::Default => None,
+            Self::Light => Some('\u{1F3FB}'),
+            Self::MediumLight => Some('\u{1F3FC}'),
+            Self::Medium => Some('\u{1F3FD}'),
+            Self::MediumDark => Some('\u{1F3FE}'),
+            Self::Dark => Some('\u{1F3FF}'),
+        }
+    }
+
+    fn from_modifier(c: char) -> Option<Self> {
+        match c {
+            '\u{1F3FB}' => Some(Self::Light),
+            '\u{1F3FC}' => Some(Self::MediumLight),
+            '\u{1F3FD}' => Some(Self::Medium),
+            '\u{1F3FE}' => Some(Self::MediumDark),
+            '\u{1F3FF}' => Some(Self::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// Dataset entries keyed by their text without VS16, so 👍 and ✌ (as left
+/// over after removing a tone from ✌🏽) both resolve.
+fn emoji_index() -> &'static HashMap<String, &'static EmojiInfo> {
+    static INDEX: OnceLock<HashMap<String, &'static EmojiInfo>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        emoji_database()
+            .iter()
+            .map(|emoji| (emoji.char.replace(VS16, ""), emoji))
+            .collect()
+    })
+}
+
+/// Single characters that take a skin tone (Emoji_Modifier_Base).
+fn is_modifier_base(c: char) -> bool {
+    static BASES: OnceLock<HashSet<char>> = OnceLock::new();
+    BASES
+        .get_or_init(|| {
+            emoji_database()
+                .iter()
+                .filter(|emoji| emoji.skin_tones)
+                .filter_map(|emoji| {
+                    let mut chars = emoji.char.chars().filter(|c| *c != VS16);
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(c),
+                        _ => None,
+                    }
+                })
+                .collect()
+        })
+        .contains(&c)
+}
+
+/// Returns `base` with `tone` applied. Every person in a ZWJ sequence gets
+/// the modifier (🧑🏽‍🤝‍🧑🏽); emojis that don't take a tone come back unchanged.
+pub fn apply_skin_tone(base: &str, tone: SkinTone) -> String {
+    let (base, _) = split_skin_tone(base);
+    let Some(modifier) = tone.modifier() else {
+        return base;
+    };
+    let accepts_tone = emoji_index()
+        .get(&base.replace(VS16, ""))
+        .is_some_and(|emoji| emoji.skin_tones);
+    if !accepts_tone {
+        return base;
+    }
+
+    let in_sequence = base.contains(ZWJ);
+    base.split(ZWJ)
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) if is_modifier_base(first) && !(in_sequence && first == HANDSHAKE) => {
+                    let rest = chars.as_str();
+                    let rest = rest.strip_prefix(VS16).unwrap_or(rest);
+                    format!("{}{}{}", first, modifier, rest)
+                }
+                _ => part.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&ZWJ.to_string())
+}
+
+/// Splits an emoji into its untoned base and its tone. The tone is `None`
+/// when the parts of a sequence carry different tones. Text that isn't a
+/// toned emoji comes back as is, with `SkinTone::Default`.
+pub fn split_skin_tone(emoji: &str) -> (String, Option<SkinTone>) {
+    let tones: Vec<SkinTone> = emoji.chars().filter_map(SkinTone::from_modifier).collect();
+    let Some(&first) = tones.first() else {
+        return (emoji.to_string(), Some(SkinTone::Default));
+    };
+    let tone = tones.iter().all(|t| *t == first).then_some(first);
+
+    let stripped: String = emoji
+        .chars()
+        .filter(|c| SkinTone::from_modifier(*c).is_none())
+        .collect();
+    // Restore the VS16 the modifier replaced (✌🏽 -> ✌️)
+    let base = emoji_index()
+        .get(&stripped.replace(VS16, ""))
+        .map_or(stripped, |entry| entry.char.to_string());
+    (base, tone)
+}
+
 /// A single emoji usage entry
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EmojiUsage {
@@ -24,6 +275,37 @@ pub struct EmojiUsage {
     /// Last used timestamp (Unix epoch millis)
     #[serde(default = "current_time_millis")]
     pub last_used: u64,
+    /// Skin tone the emoji was last used with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skin_tone: Option<SkinTone>,
+}
+
+/// A recently used emoji as the picker shows it: the usage entry plus the
+/// emoji with its remembered tone applied
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RecentEmoji {
+    #[serde(flatten)]
+    pub usage: EmojiUsage,
+    /// The character to show and paste, e.g. 👍🏽 for a 👍 last used toned
+    pub toned: String,
+}
+
+impl From<EmojiUsage> for RecentEmoji {
+    fn from(usage: EmojiUsage) -> Self {
+        let toned = match usage.skin_tone {
+            Some(tone) => apply_skin_tone(&usage.char, tone),
+            None => usage.char.clone(),
+        };
+        Self { usage, toned }
+    }
+}
+
+/// One use of an emoji, kept in the rolling usage log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct UsageLogEntry {
+    char: String,
+    /// Unix epoch millis
+    at: u64,
 }
 
 /// Persistent storage format wrapper
@@ -32,12 +314,27 @@ pub struct EmojiUsage {
 struct EmojiHistoryWrapper {
     #[serde(default)]
     emojis: Vec<EmojiUsage>,
+    #[serde(default)]
+    default_skin_tone: SkinTone,
+    /// Pinned emojis, in the order they were added
+    #[serde(default)]
+    favorites: Vec<String>,
+    #[serde(default)]
+    usage_log: Vec<UsageLogEntry>,
 }
 
 /// Manages emoji usage tracking
 pub struct EmojiManager {
     /// Recent emojis ordered by recency (index 0 is most recent)
     recent: Vec<EmojiUsage>,
+    /// Tone the picker applies to emojis not used with one before
+    default_skin_tone: SkinTone,
+    /// Favorite emojis (untoned), never evicted from `recent`
+    favorites: Vec<String>,
+    /// The last `MAX_USAGE_LOG` uses, oldest first
+    usage_log: Vec<UsageLogEntry>,
+    /// Half-life used by `get_top_used_decayed`, in days
+    decay_half_life_days: f64,
     /// Path to the data directory
     data_dir: PathBuf,
 }
@@ -47,6 +344,10 @@ impl EmojiManager {
     pub fn new(data_dir: PathBuf) -> Self {
         let mut manager = Self {
             recent: Vec::with_capacity(MAX_RECENT_EMOJIS),
+            default_skin_tone: SkinTone::Default,
+            favorites: Vec::new(),
+            usage_log: Vec::new(),
+            decay_half_life_days: DEFAULT_DECAY_HALF_LIFE_DAYS,
             data_dir,
         };
 
@@ -57,30 +358,43 @@ impl EmojiManager {
         manager
     }
 
-    /// Record emoji usage (LRU semantics: move to front, increment count)
+    /// Record emoji usage (LRU semantics: move to front, increment count).
+    /// Skin tone variants count towards their base emoji, which remembers
+    /// the tone last used.
     pub fn record_usage(&mut self, emoji_char: &str) {
         let now = current_time_millis();
+        let (base, tone) = split_skin_tone(emoji_char);
 
         // Check if emoji exists in recent list
-        if let Some(index) = self.recent.iter().position(|e| e.char == emoji_char) {
+        if let Some(index) = self.recent.iter().position(|e| e.char == base) {
             // Remove existing entry to update it
             let mut entry = self.recent.remove(index);
             entry.use_count += 1;
             entry.last_used = now;
+            if tone.is_some() {
+                entry.skin_tone = tone;
+            }
             self.recent.insert(0, entry);
         } else {
             // Create new entry
             let entry = EmojiUsage {
-                char: emoji_char.to_string(),
+                char: base,
                 use_count: 1,
                 last_used: now,
+                skin_tone: tone,
             };
             self.recent.insert(0, entry);
         }
 
-        // Enforce capacity (LRU eviction from end)
-        if self.recent.len() > MAX_RECENT_EMOJIS {
-            self.recent.truncate(MAX_RECENT_EMOJIS);
+        self.evict_least_recent();
+
+        self.usage_log.push(UsageLogEntry {
+            char: self.recent[0].char.clone(),
+            at: now,
+        });
+        if self.usage_log.len() > MAX_USAGE_LOG {
+            let excess = self.usage_log.len() - MAX_USAGE_LOG;
+            self.usage_log.drain(..excess);
         }
 
         // Persist to disk
@@ -89,9 +403,105 @@ impl EmojiManager {
         }
     }
 
-    /// Get recent emojis (most recently used first)
+    /// Enforce capacity (LRU eviction from the end). Favorites keep their
+    /// usage however long ago they were used and don't count towards the
+    /// cap, so pinning many emojis doesn't push new ones straight back out.
+    /// The getters still return at most `MAX_RECENT_EMOJIS`.
+    fn evict_least_recent(&mut self) {
+        let favorites = &self.favorites;
+        let mut others = 0;
+        self.recent.retain(|e| {
+            if favorites.contains(&e.char) {
+                return true;
+            }
+            others += 1;
+            others <= MAX_RECENT_EMOJIS
+        });
+    }
+
+    /// Adds the emoji to favorites, or removes it if it is already one.
+    /// Returns the updated favorites.
+    pub fn toggle_favorite(&mut self, emoji_char: &str) -> Vec<String> {
+        let (base, _) = split_skin_tone(emoji_char);
+        match self.favorites.iter().position(|f| *f == base) {
+            Some(pos) => {
+                self.favorites.remove(pos);
+                self.evict_least_recent();
+            }
+            None => self.favorites.push(base),
+        }
+
+        if let Err(e) = self.save_to_disk() {
+            warn!("failed to save emoji history: {}", e);
+        }
+        self.favorites.clone()
+    }
+
+    pub fn get_favorites(&self) -> Vec<String> {
+        self.favorites.clone()
+    }
+
+    pub fn default_skin_tone(&self) -> SkinTone {
+        self.default_skin_tone
+    }
+
+    pub fn set_default_skin_tone(&mut self, tone: SkinTone) {
+        self.default_skin_tone = tone;
+        if let Err(e) = self.save_to_disk() {
+            warn!("failed to save emoji history: {}", e);
+        }
+    }
+
+    /// Get recent emojis (most recently used first), at most
+    /// `MAX_RECENT_EMOJIS`
     pub fn get_recent(&self) -> Vec<EmojiUsage> {
-        self.recent.clone()
+        self.recent
+            .iter()
+            .take(MAX_RECENT_EMOJIS)
+            .cloned()
+            .collect()
+    }
+
+    /// Recent emojis with the tone each was last used with applied
+    pub fn get_recent_toned(&self) -> Vec<RecentEmoji> {
+        self.get_recent()
+            .into_iter()
+            .map(RecentEmoji::from)
+            .collect()
+    }
+
+    /// Searches the emoji dataset by name, shortcode (with or without
+    /// colons) and keywords, case-insensitively. Recently used emojis rank
+    /// higher, but never above a better kind of match.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<EmojiInfo> {
+        let query = query.trim().to_lowercase();
+        if query.trim_matches(':').is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(u32, &EmojiInfo)> = emoji_database()
+            .iter()
+            .filter_map(|emoji| {
+                let score = match_score(emoji, &query);
+                (score > 0).then(|| (score + self.usage_bonus(emoji.char), emoji))
+            })
+            .collect();
+        // Stable, so equal scores keep dataset order
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, emoji)| emoji.clone())
+            .collect()
+    }
+
+    /// Ranking bonus for recently used emojis, below the gap between two
+    /// kinds of match.
+    fn usage_bonus(&self, emoji_char: &str) -> u32 {
+        self.recent
+            .iter()
+            .find(|e| e.char == emoji_char)
+            .map_or(0, |e| 20 + e.use_count.min(25))
     }
 
     /// Get top N most used emojis
@@ -107,6 +517,57 @@ impl EmojiManager {
         sorted
     }
 
+    /// Sets the half-life for `get_top_used_decayed`. Non-positive values
+    /// are ignored.
+    pub fn set_decay_half_life_days(&mut self, days: f64) {
+        if days > 0.0 {
+            self.decay_half_life_days = days;
+        }
+    }
+
+    /// Get top N emojis by usage that fades with age:
+    /// `use_count * 0.5^(age_days / half_life)`. Uses still in the usage log
+    /// each decay from their own time; older uses decay from the emoji's
+    /// oldest logged use, or from `last_used` if none are logged.
+    pub fn get_top_used_decayed(&self, n: usize) -> Vec<EmojiUsage> {
+        self.top_used_decayed_at(n, current_time_millis())
+    }
+
+    fn top_used_decayed_at(&self, n: usize, now: u64) -> Vec<EmojiUsage> {
+        let mut scored: Vec<(f64, &EmojiUsage)> = self
+            .recent
+            .iter()
+            .map(|entry| (self.decayed_score(entry, now), entry))
+            .collect();
+        scored.sort_by(|a, b| {
+            b.0.total_cmp(&a.0)
+                .then_with(|| b.1.last_used.cmp(&a.1.last_used))
+        });
+        scored
+            .into_iter()
+            .take(n)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    fn decayed_score(&self, entry: &EmojiUsage, now: u64) -> f64 {
+        let decay = |at: u64| {
+            let age_days = now.saturating_sub(at) as f64 / MILLIS_PER_DAY;
+            0.5f64.powf(age_days / self.decay_half_life_days)
+        };
+
+        let logged: Vec<u64> = self
+            .usage_log
+            .iter()
+            .filter(|logged| logged.char == entry.char)
+            .map(|logged| logged.at)
+            .collect();
+        let earlier_uses = entry.use_count.saturating_sub(logged.len() as u32);
+        let earlier_at = logged.iter().min().copied().unwrap_or(entry.last_used);
+
+        logged.iter().map(|at| decay(*at)).sum::<f64>() + earlier_uses as f64 * decay(earlier_at)
+    }
+
     // --- Persistence Helpers ---
 
     fn history_path(&self) -> PathBuf {
@@ -122,16 +583,59 @@ impl EmojiManager {
         let content = fs::read_to_string(&path).map_err(|e| format!("Read error: {}", e))?;
         let wrapper: EmojiHistoryWrapper =
             serde_json::from_str(&content).map_err(|e| format!("Parse error: {}", e))?;
+        self.apply_wrapper(wrapper);
 
-        self.recent = wrapper.emojis;
+        debug!("loaded {} recent emojis", self.recent.len());
+        Ok(())
+    }
+
+    fn apply_wrapper(&mut self, wrapper: EmojiHistoryWrapper) {
+        self.recent = merge_toned_usage(wrapper.emojis);
+        self.default_skin_tone = wrapper.default_skin_tone;
+        self.favorites = Vec::with_capacity(wrapper.favorites.len());
+        for favorite in &wrapper.favorites {
+            let (base, _) = split_skin_tone(favorite);
+            if !self.favorites.contains(&base) {
+                self.favorites.push(base);
+            }
+        }
+        self.usage_log = wrapper.usage_log;
+        for entry in &mut self.usage_log {
+            entry.char = split_skin_tone(&entry.char).0;
+        }
 
         // Ensure we respect limits even if disk file was modified manually
-        if self.recent.len() > MAX_RECENT_EMOJIS {
-            self.recent.truncate(MAX_RECENT_EMOJIS);
+        self.evict_least_recent();
+    }
+
+    fn to_wrapper(&self) -> EmojiHistoryWrapper {
+        EmojiHistoryWrapper {
+            emojis: self.recent.clone(),
+            default_skin_tone: self.default_skin_tone,
+            favorites: self.favorites.clone(),
+            usage_log: self.usage_log.clone(),
         }
+    }
 
-        debug!("loaded {} recent emojis", self.recent.len());
-        Ok(())
+    /// The usage history, favorites and skin tone in their on-disk format,
+    /// for settings export.
+    pub fn export_history(&self) -> serde_json::Value {
+        serde_json::to_value(self.to_wrapper()).unwrap_or_default()
+    }
+
+    /// Checks that `history` (from `export_history`) can be imported.
+    pub fn check_history(history: &serde_json::Value) -> Result<(), String> {
+        EmojiHistoryWrapper::deserialize(history)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid emoji history: {}", e))
+    }
+
+    /// Replaces the usage history with an exported one and saves it.
+    pub fn import_history(&mut self, history: &serde_json::Value) -> Result<(), String> {
+        let wrapper = EmojiHistoryWrapper::deserialize(history)
+            .map_err(|e| format!("Invalid emoji history: {}", e))?;
+        self.apply_wrapper(wrapper);
+        self.save_to_disk()
     }
 
     fn save_to_disk(&self) -> Result<(), String> {
@@ -140,11 +644,7 @@ impl EmojiManager {
                 .map_err(|e| format!("Failed to create data dir: {}", e))?;
         }
 
-        let wrapper = EmojiHistoryWrapper {
-            emojis: self.recent.clone(),
-        };
-
-        let content = serde_json::to_string_pretty(&wrapper)
+        let content = serde_json::to_string_pretty(&self.to_wrapper())
             .map_err(|e| format!("Serialize error: {}", e))?;
 
         fs::write(self.history_path(), content).map_err(|e| format!("Write error: {}", e))?;
@@ -152,6 +652,38 @@ impl EmojiManager {
     }
 }
 
+/// Folds entries saved before tones were tracked, which kept each toned
+/// variant (👍🏽) as an emoji of its own, into their base emoji. Counts add
+/// up, and the most recent variant's tone is remembered.
+fn merge_toned_usage(emojis: Vec<EmojiUsage>) -> Vec<EmojiUsage> {
+    let mut merged: Vec<EmojiUsage> = Vec::with_capacity(emojis.len());
+    for usage in emojis {
+        let (base, tone) = split_skin_tone(&usage.char);
+        let tone = if base == usage.char {
+            usage.skin_tone
+        } else {
+            tone
+        };
+        match merged.iter_mut().find(|e| e.char == base) {
+            Some(entry) => {
+                entry.use_count += usage.use_count;
+                if usage.last_used > entry.last_used {
+                    entry.last_used = usage.last_used;
+                    entry.skin_tone = tone.or(entry.skin_tone);
+                } else if entry.skin_tone.is_none() {
+                    entry.skin_tone = tone;
+                }
+            }
+            None => merged.push(EmojiUsage {
+                char: base,
+                skin_tone: tone,
+                ..usage
+            }),
+        }
+    }
+    merged
+}
+
 /// Helper to get current Unix timestamp in milliseconds
 fn current_time_millis() -> u64 {
     SystemTime::now()
@@ -213,6 +745,290 @@ mod tests {
         assert_eq!(recent[0].char, format!("emoji_{}", MAX_RECENT_EMOJIS + 4));
     }
 
+    #[test]
+    fn test_search_by_name_shortcode_and_keyword() {
+        let (manager, _dir) = get_temp_manager("emoji_search_test");
+
+        let chars = |query: &str| -> Vec<&str> {
+            manager
+                .search(query, 5)
+                .into_iter()
+                .map(|e| e.char)
+                .collect()
+        };
+        assert_eq!(chars(":thumbsup:")[0], "👍");
+        assert_eq!(chars("THUMBSUP")[0], "👍");
+        assert_eq!(chars("+1")[0], "👍");
+        assert!(chars("tears of joy").contains(&"😂"));
+        assert!(chars("france").contains(&"🇫🇷"));
+        assert!(chars("zzzzqx").is_empty());
+        assert!(chars("::").is_empty());
+        assert!(manager.search("face", 3).len() == 3);
+    }
+
+    #[test]
+    fn test_search_ranks_exact_shortcode_then_recent() {
+        let (mut manager, _dir) = get_temp_manager("emoji_search_rank_test");
+
+        // "heart" is the shortcode of ❤️, so it beats other hearts.
+        assert_eq!(manager.search("heart", 1)[0].char, "❤️");
+
+        let before: Vec<&str> = manager.search("cat", 10).iter().map(|e| e.char).collect();
+        let last = *before.last().unwrap();
+        manager.record_usage(last);
+        let after: Vec<&str> = manager.search("cat", 10).iter().map(|e| e.char).collect();
+        assert!(
+            after.iter().position(|c| *c == last) < before.iter().position(|c| *c == last),
+            "recently used emoji should move up"
+        );
+        assert_eq!(after[0], "🐱", "usage never beats the exact shortcode");
+    }
+
+    #[test]
+    fn test_categories_cover_the_dataset() {
+        let categories = emoji_categories();
+        assert_eq!(categories.len(), 9);
+        assert_eq!(categories[0].name, "Smileys & Emotion");
+        let total: usize = categories.iter().map(|c| c.emojis.len()).sum();
+        assert_eq!(total, emoji_database().len());
+    }
+
+    #[test]
+    fn test_apply_skin_tone() {
+        assert_eq!(apply_skin_tone("👍", SkinTone::Medium), "👍🏽");
+        assert_eq!(apply_skin_tone("👍", SkinTone::Default), "👍");
+        // The modifier replaces the emoji presentation selector
+        assert_eq!(apply_skin_tone("✌️", SkinTone::Dark), "✌🏿");
+        // Re-toning replaces the old tone
+        assert_eq!(apply_skin_tone("👍🏻", SkinTone::Dark), "👍🏿");
+    }
+
+    #[test]
+    fn test_apply_skin_tone_to_zwj_sequences() {
+        // Every person is toned, the joining handshake is not
+        assert_eq!(
+            apply_skin_tone("🧑‍🤝‍🧑", SkinTone::Medium),
+            "🧑🏽\u{200D}🤝\u{200D}🧑🏽"
+        );
+        assert_eq!(
+            apply_skin_tone("👩‍❤️‍👨", SkinTone::Light),
+            "👩🏻\u{200D}❤️\u{200D}👨🏻"
+        );
+        assert_eq!(apply_skin_tone("🧑‍💻", SkinTone::MediumDark), "🧑🏾\u{200D}💻");
+        assert_eq!(apply_skin_tone("🏋️‍♀️", SkinTone::MediumLight), "🏋🏼\u{200D}♀️");
+    }
+
+    #[test]
+    fn test_emoji_without_tones_is_unchanged() {
+        assert_eq!(apply_skin_tone("😀", SkinTone::Dark), "😀");
+        assert_eq!(apply_skin_tone("🇫🇷", SkinTone::Dark), "🇫🇷");
+        assert_eq!(apply_skin_tone("👨‍👩‍👧", SkinTone::Dark), "👨‍👩‍👧");
+        assert_eq!(apply_skin_tone("(╯°□°)╯", SkinTone::Dark), "(╯°□°)╯");
+    }
+
+    #[test]
+    fn test_record_usage_counts_tones_together() {
+        let (mut manager, dir) = get_temp_manager("emoji_skin_tone_test");
+
+        manager.record_usage("👍🏽");
+        manager.record_usage("👍🏿");
+        manager.record_usage("✌🏽");
+        manager.record_usage("🧑🏻‍🤝‍🧑🏿");
+
+        let recent = manager.get_recent();
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].char, "🧑‍🤝‍🧑");
+        assert_eq!(recent[0].skin_tone, None, "mixed tones are not remembered");
+        assert_eq!(recent[1].char, "✌️");
+        assert_eq!(recent[2].char, "👍");
+        assert_eq!(recent[2].use_count, 2);
+        assert_eq!(recent[2].skin_tone, Some(SkinTone::Dark));
+
+        manager.set_default_skin_tone(SkinTone::MediumLight);
+        let reloaded = EmojiManager::new(dir);
+        assert_eq!(reloaded.default_skin_tone(), SkinTone::MediumLight);
+        assert_eq!(reloaded.get_recent()[2].skin_tone, Some(SkinTone::Dark));
+
+        let toned = reloaded.get_recent_toned();
+        assert_eq!(toned[2].toned, "👍🏿");
+        assert_eq!(toned[1].toned, "✌🏽");
+        assert_eq!(toned[0].toned, "🧑‍🤝‍🧑");
+    }
+
+    #[test]
+    fn test_favorites_survive_lru_eviction() {
+        let (mut manager, dir) = get_temp_manager("emoji_favorites_test");
+
+        manager.record_usage("🦀");
+        assert_eq!(manager.toggle_favorite("🦀"), ["🦀"]);
+        assert_eq!(manager.toggle_favorite("👍🏽"), ["🦀", "👍"]);
+        for i in 0..MAX_RECENT_EMOJIS + 5 {
+            manager.record_usage(&format!("emoji_{}", i));
+        }
+
+        assert_eq!(manager.get_recent().len(), MAX_RECENT_EMOJIS);
+        assert_eq!(manager.recent.len(), MAX_RECENT_EMOJIS + 1);
+        assert_eq!(manager.recent.last().unwrap().char, "🦀");
+
+        let reloaded = EmojiManager::new(dir);
+        assert_eq!(reloaded.get_favorites(), ["🦀", "👍"]);
+
+        // Unfavoriting makes it evictable again
+        manager.toggle_favorite("🦀");
+        manager.record_usage("one more");
+        assert!(manager.recent.iter().all(|e| e.char != "🦀"));
+        assert_eq!(manager.get_favorites(), ["👍"]);
+    }
+
+    #[test]
+    fn test_favorites_do_not_push_out_new_emojis() {
+        let (mut manager, _dir) = get_temp_manager("emoji_many_favorites_test");
+
+        for i in 0..MAX_RECENT_EMOJIS + 2 {
+            let emoji = format!("favorite_{}", i);
+            manager.record_usage(&emoji);
+            manager.toggle_favorite(&emoji);
+        }
+        manager.record_usage("new");
+
+        let recent = manager.get_recent();
+        assert_eq!(recent[0].char, "new");
+        assert_eq!(recent.len(), MAX_RECENT_EMOJIS);
+        assert_eq!(manager.recent.len(), MAX_RECENT_EMOJIS + 3);
+    }
+
+    #[test]
+    fn test_loads_old_format_without_favorites() {
+        let (_, dir) = get_temp_manager("emoji_old_format_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(EMOJI_HISTORY_FILE),
+            r#"{ "emojis": [ { "char": "🚀", "use_count": 3, "last_used": 1700000000000 } ] }"#,
+        )
+        .unwrap();
+
+        let manager = EmojiManager::new(dir);
+        let recent = manager.get_recent();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].use_count, 3);
+        assert_eq!(recent[0].skin_tone, None);
+        assert!(manager.get_favorites().is_empty());
+        assert_eq!(manager.default_skin_tone(), SkinTone::Default);
+    }
+
+    #[test]
+    fn test_loads_legacy_toned_entries_as_their_base() {
+        let (_, dir) = get_temp_manager("emoji_legacy_tones_test");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(EMOJI_HISTORY_FILE),
+            r#"{
+                "emojis": [
+                    { "char": "👍🏿", "use_count": 2, "last_used": 1700000002000 },
+                    { "char": "🚀", "use_count": 1, "last_used": 1700000001000 },
+                    { "char": "👍🏽", "use_count": 3, "last_used": 1700000000000 }
+                ],
+                "favorites": ["👍🏽", "👍"],
+                "usage_log": [ { "char": "👍🏿", "at": 1700000002000 } ]
+            }"#,
+        )
+        .unwrap();
+
+        let manager = EmojiManager::new(dir);
+        let recent = manager.get_recent();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].char, "👍");
+        assert_eq!(recent[0].use_count, 5);
+        assert_eq!(recent[0].last_used, 1700000002000);
+        assert_eq!(recent[0].skin_tone, Some(SkinTone::Dark));
+        assert_eq!(recent[1].char, "🚀");
+        assert_eq!(manager.get_favorites(), ["👍"]);
+        assert_eq!(manager.usage_log[0].char, "👍");
+    }
+
+    fn usage(char: &str, use_count: u32, last_used: u64) -> EmojiUsage {
+        EmojiUsage {
+            char: char.to_string(),
+            use_count,
+            last_used,
+            skin_tone: None,
+        }
+    }
+
+    #[test]
+    fn test_top_used_decayed_prefers_recent_use() {
+        let (mut manager, _dir) = get_temp_manager("emoji_decay_test");
+        let day = MILLIS_PER_DAY as u64;
+        let now = 1000 * day;
+
+        manager.recent = vec![
+            usage("old-spam", 50, now - 180 * day), // 50 * 0.5^6 ≈ 0.8
+            usage("monthly", 10, now - 30 * day),   // 10 * 0.5 = 5
+            usage("fresh", 3, now - day),           // ≈ 2.9
+        ];
+
+        let order = |m: &EmojiManager| -> Vec<String> {
+            m.top_used_decayed_at(3, now)
+                .into_iter()
+                .map(|e| e.char)
+                .collect()
+        };
+        assert_eq!(order(&manager), ["monthly", "fresh", "old-spam"]);
+        let lifetime: Vec<String> = manager
+            .get_top_used(3)
+            .into_iter()
+            .map(|e| e.char)
+            .collect();
+        assert_eq!(lifetime, ["old-spam", "monthly", "fresh"]);
+
+        // A longer half-life brings back the lifetime favorite
+        manager.set_decay_half_life_days(365.0);
+        assert_eq!(order(&manager)[0], "old-spam");
+    }
+
+    #[test]
+    fn test_top_used_decayed_uses_the_usage_log() {
+        let (mut manager, _dir) = get_temp_manager("emoji_decay_log_test");
+        let day = MILLIS_PER_DAY as u64;
+        let now = 1000 * day;
+
+        // Both were last used today, but most of "bursty"'s uses were two
+        // half-lives ago: 1 + 3 * 0.25 = 1.75 against ≈ 2.9.
+        manager.recent = vec![usage("bursty", 4, now), usage("steady", 3, now - day)];
+        for at in [now - 60 * day, now - 60 * day, now - 60 * day, now] {
+            manager.usage_log.push(UsageLogEntry {
+                char: "bursty".into(),
+                at,
+            });
+        }
+        for at in [now - 2 * day, now - day, now - day] {
+            manager.usage_log.push(UsageLogEntry {
+                char: "steady".into(),
+                at,
+            });
+        }
+
+        let order: Vec<String> = manager
+            .top_used_decayed_at(2, now)
+            .into_iter()
+            .map(|e| e.char)
+            .collect();
+        assert_eq!(order, ["steady", "bursty"]);
+    }
+
+    #[test]
+    fn test_usage_log_is_capped() {
+        let (mut manager, dir) = get_temp_manager("emoji_usage_log_test");
+        for i in 0..MAX_USAGE_LOG + 10 {
+            manager.record_usage(if i % 2 == 0 { "🦀" } else { "👍🏽" });
+        }
+        assert_eq!(manager.usage_log.len(), MAX_USAGE_LOG);
+        assert_eq!(manager.usage_log.last().unwrap().char, "👍");
+
+        let reloaded = EmojiManager::new(dir);
+        assert_eq!(reloaded.usage_log.len(), MAX_USAGE_LOG);
+    }
+
     #[test]
     fn test_persistence() {
         let (mut manager, dir) = get_temp_manager("emoji_persist_test");
@@ -228,4 +1044,23 @@ mod tests {
         assert_eq!(recent[0].char, "🦀");
         assert_eq!(recent[1].char, "🚀");
     }
+
+    #[test]
+    fn test_history_export_import_round_trip() {
+        let (mut source, _) = get_temp_manager("penguinclip_test_emoji_export");
+        source.record_usage("🚀");
+        source.record_usage("🎉");
+        source.toggle_favorite("🚀");
+        let exported = source.export_history();
+        assert!(EmojiManager::check_history(&exported).is_ok());
+
+        let (mut target, dir) = get_temp_manager("penguinclip_test_emoji_import");
+        target.import_history(&exported).unwrap();
+        assert_eq!(target.get_recent(), source.get_recent());
+        assert_eq!(target.get_favorites(), vec!["🚀".to_string()]);
+        // Saved, so a fresh manager sees the imported history
+        assert_eq!(EmojiManager::new(dir).get_recent(), source.get_recent());
+
+        assert!(EmojiManager::check_history(&serde_json::json!({ "emojis": 3 })).is_err());
+    }
 }
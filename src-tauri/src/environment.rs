@@ -0,0 +1,437 @@
+//! Desktop Environment Detection
+//! Which desktop or window manager we run under, with the session type,
+//! compositor and desktop version. Detected once and shared by shortcut
+//! registration, conflict detection and the setup wizard. Desktops are
+//! recognized from `XDG_CURRENT_DESKTOP` and `XDG_SESSION_DESKTOP`; window
+//! managers that don't set them are found among the running processes.
+
+use crate::session::{self, SessionType};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Cached environment, detected on first use
+static ENVIRONMENT: OnceLock<EnvironmentInfo> = OnceLock::new();
+
+/// Whether `flatpak-spawn --host` works, checked on first use
+static HOST_SPAWN: OnceLock<bool> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DesktopEnvironment {
+    Gnome,
+    PopOs,
+    Budgie,
+    Cinnamon,
+    KdePlasma,
+    Xfce,
+    Mate,
+    Lxde,
+    Lxqt,
+    Cosmic,
+    Deepin,
+    Enlightenment,
+    // Window managers
+    I3,
+    Sway,
+    Hyprland,
+    Awesome,
+    Qtile,
+    River,
+    Wayfire,
+    Bspwm,
+    Unknown,
+}
+
+/// Names in the session variables, lowercase, checked in order. Pop!_OS
+/// (`pop:GNOME`) and Budgie (`Budgie:GNOME`) come before GNOME.
+const SESSION_NAMES: &[(&str, DesktopEnvironment)] = &[
+    ("pop", DesktopEnvironment::PopOs),
+    ("budgie", DesktopEnvironment::Budgie),
+    ("gnome", DesktopEnvironment::Gnome),
+    ("unity", DesktopEnvironment::Gnome),
+    ("pantheon", DesktopEnvironment::Gnome),
+    ("cinnamon", DesktopEnvironment::Cinnamon),
+    ("kde", DesktopEnvironment::KdePlasma),
+    ("plasma", DesktopEnvironment::KdePlasma),
+    ("xfce", DesktopEnvironment::Xfce),
+    ("mate", DesktopEnvironment::Mate),
+    ("lxde", DesktopEnvironment::Lxde),
+    ("lxqt", DesktopEnvironment::Lxqt),
+    ("cosmic", DesktopEnvironment::Cosmic),
+    ("deepin", DesktopEnvironment::Deepin),
+    ("enlightenment", DesktopEnvironment::Enlightenment),
+    ("i3", DesktopEnvironment::I3),
+    ("sway", DesktopEnvironment::Sway),
+    ("hyprland", DesktopEnvironment::Hyprland),
+    ("awesome", DesktopEnvironment::Awesome),
+    ("qtile", DesktopEnvironment::Qtile),
+    ("river", DesktopEnvironment::River),
+    ("wayfire", DesktopEnvironment::Wayfire),
+    ("bspwm", DesktopEnvironment::Bspwm),
+];
+
+/// Window managers found by process name when the session variables don't
+/// name them. bspwm leaves its keys to sxhkd.
+const WM_PROCESSES: &[(&str, DesktopEnvironment)] = &[
+    ("i3", DesktopEnvironment::I3),
+    ("sway", DesktopEnvironment::Sway),
+    ("hyprland", DesktopEnvironment::Hyprland),
+    ("Hyprland", DesktopEnvironment::Hyprland),
+    ("enlightenment", DesktopEnvironment::Enlightenment),
+    ("awesome", DesktopEnvironment::Awesome),
+    ("qtile", DesktopEnvironment::Qtile),
+    ("river", DesktopEnvironment::River),
+    ("wayfire", DesktopEnvironment::Wayfire),
+    ("sxhkd", DesktopEnvironment::Bspwm),
+];
+
+/// Compositor and window manager processes, as `/proc/<pid>/comm` names
+/// them. Compositors come before the X11 window managers that may run
+/// under picom.
+const COMPOSITORS: &[&str] = &[
+    "gnome-shell",
+    "kwin_wayland",
+    "kwin_x11",
+    "cosmic-comp",
+    "cinnamon",
+    "muffin",
+    "marco",
+    "xfwm4",
+    "budgie-wm",
+    "gala",
+    "deepin-kwin_x11",
+    "enlightenment",
+    "sway",
+    "Hyprland",
+    "river",
+    "wayfire",
+    "labwc",
+    "weston",
+    "picom",
+    "compton",
+    "i3",
+    "awesome",
+    "qtile",
+    "bspwm",
+    "openbox",
+];
+
+impl DesktopEnvironment {
+    /// The desktop named in `desktops`, the lowercased session variables,
+    /// else the window manager `is_running` finds
+    fn from_session(desktops: &str, is_running: impl Fn(&str) -> bool) -> Self {
+        SESSION_NAMES
+            .iter()
+            .find(|(name, _)| desktops.contains(name))
+            .or_else(|| WM_PROCESSES.iter().find(|(process, _)| is_running(process)))
+            .map_or(Self::Unknown, |&(_, de)| de)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gnome => "GNOME",
+            Self::PopOs => "Pop!_OS",
+            Self::Budgie => "Budgie",
+            Self::Cinnamon => "Cinnamon",
+            Self::KdePlasma => "KDE Plasma",
+            Self::Xfce => "XFCE",
+            Self::Mate => "MATE",
+            Self::Lxde => "LXDE",
+            Self::Lxqt => "LXQt",
+            Self::Cosmic => "COSMIC",
+            Self::Deepin => "Deepin",
+            Self::Enlightenment => "Enlightenment",
+            Self::I3 => "i3",
+            Self::Sway => "Sway",
+            Self::Hyprland => "Hyprland",
+            Self::Awesome => "awesome",
+            Self::Qtile => "qtile",
+            Self::River => "River",
+            Self::Wayfire => "Wayfire",
+            Self::Bspwm => "bspwm",
+            Self::Unknown => "Unknown",
+        }
+    }
+
+    /// A standalone window manager rather than a full desktop
+    pub fn is_window_manager(self) -> bool {
+        matches!(
+            self,
+            Self::I3
+                | Self::Sway
+                | Self::Hyprland
+                | Self::Awesome
+                | Self::Qtile
+                | Self::River
+                | Self::Wayfire
+                | Self::Bspwm
+        )
+    }
+
+    /// The command printing the desktop's version
+    fn version_command(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Gnome | Self::PopOs => Some(("gnome-shell", "--version")),
+            Self::Budgie => Some(("budgie-desktop", "--version")),
+            Self::Cinnamon => Some(("cinnamon", "--version")),
+            Self::KdePlasma => Some(("plasmashell", "--version")),
+            Self::Xfce => Some(("xfce4-session", "--version")),
+            Self::Mate => Some(("mate-session", "--version")),
+            Self::Lxqt => Some(("lxqt-session", "--version")),
+            Self::I3 => Some(("i3", "--version")),
+            Self::Sway => Some(("sway", "--version")),
+            Self::Hyprland => Some(("Hyprland", "--version")),
+            Self::Awesome => Some(("awesome", "--version")),
+            Self::Qtile => Some(("qtile", "--version")),
+            Self::River => Some(("river", "-version")),
+            Self::Wayfire => Some(("wayfire", "--version")),
+            Self::Bspwm => Some(("bspwm", "-v")),
+            Self::Lxde | Self::Cosmic | Self::Deepin | Self::Enlightenment | Self::Unknown => None,
+        }
+    }
+}
+
+/// What we know about the desktop session
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentInfo {
+    pub de: DesktopEnvironment,
+    /// `de`'s name; for unknown desktops, `XDG_CURRENT_DESKTOP` as set
+    pub name: String,
+    pub session_type: SessionType,
+    /// The running compositor or window manager, e.g. `kwin_wayland`
+    pub compositor: Option<String>,
+    pub is_flatpak: bool,
+    /// The desktop's version, e.g. `46.0` for GNOME Shell 46
+    pub version: Option<String>,
+}
+
+/// The environment, detected on the first call
+pub fn detect() -> &'static EnvironmentInfo {
+    ENVIRONMENT.get_or_init(|| {
+        let info = detect_uncached();
+        log::info!(
+            "detected desktop {} {} ({} session, compositor {})",
+            info.name,
+            info.version.as_deref().unwrap_or("(unknown version)"),
+            info.session_type.name(),
+            info.compositor.as_deref().unwrap_or("unknown")
+        );
+        info
+    })
+}
+
+fn detect_uncached() -> EnvironmentInfo {
+    let current = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let desktops = format!(
+        "{} {}",
+        current,
+        env::var("XDG_SESSION_DESKTOP").unwrap_or_default()
+    )
+    .to_lowercase();
+    let processes = running_processes();
+    let de = DesktopEnvironment::from_session(&desktops, |name| processes.contains(name));
+
+    EnvironmentInfo {
+        de,
+        name: match de {
+            DesktopEnvironment::Unknown if !current.is_empty() => current.to_uppercase(),
+            _ => de.name().to_string(),
+        },
+        session_type: session::get_session_type(),
+        compositor: COMPOSITORS
+            .iter()
+            .find(|name| processes.contains(**name))
+            .map(|name| name.to_string()),
+        is_flatpak: is_flatpak(),
+        version: de.version_command().and_then(|(program, arg)| {
+            let output = host_command(program).arg(arg).output().ok()?;
+            parse_version(&String::from_utf8_lossy(&output.stdout))
+        }),
+    }
+}
+
+/// Whether we run inside a Flatpak sandbox, where the host's desktop
+/// settings can't be written
+pub fn is_flatpak() -> bool {
+    std::path::Path::new("/.flatpak-info").exists() || env::var_os("FLATPAK_ID").is_some()
+}
+
+/// The sandbox we run in: `flatpak`, or `None` on the host. Other
+/// containers (toolbox sets `container=oci`, distrobox `podman`) share the
+/// host's session and settings, so they count as the host.
+pub fn sandbox() -> Option<String> {
+    sandbox_from(is_flatpak(), env::var("container").ok().as_deref())
+}
+
+fn sandbox_from(flatpak: bool, container: Option<&str>) -> Option<String> {
+    (flatpak || container.map(str::trim) == Some("flatpak")).then(|| "flatpak".to_string())
+}
+
+/// Whether we run in a Flatpak sandbox rather than on the host
+pub fn is_sandboxed() -> bool {
+    sandbox().is_some()
+}
+
+/// Whether the sandbox lets us run commands on the host with
+/// `flatpak-spawn --host`; a Flatpak needs `--talk-name=org.freedesktop.Flatpak`
+pub fn host_spawn_permitted() -> bool {
+    *HOST_SPAWN.get_or_init(|| {
+        Command::new("flatpak-spawn")
+            .args(["--host", "true"])
+            .output()
+            .is_ok_and(|o| o.status.success())
+    })
+}
+
+/// A command running `program` on the host: through `flatpak-spawn --host`
+/// when sandboxed and allowed to, else directly. Desktop tools like
+/// gsettings and xfconf-query only see the host's settings this way.
+pub fn host_command(program: &str) -> Command {
+    if is_sandboxed() && host_spawn_permitted() {
+        let mut command = Command::new("flatpak-spawn");
+        command.args(["--host", program]);
+        command
+    } else {
+        Command::new(program)
+    }
+}
+
+/// The command names of every process we can see
+fn running_processes() -> HashSet<String> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|entry| fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_string())
+        .collect()
+}
+
+/// The first version number in a `--version` output's first line:
+/// `GNOME Shell 46.0` → `46.0`, `awesome v4.3 (Too long)` → `4.3`
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v').trim_end_matches([',', ')']))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `XDG_CURRENT_DESKTOP` and `XDG_SESSION_DESKTOP` as each desktop sets them
+    const SESSIONS: &[(&str, &str, DesktopEnvironment)] = &[
+        ("ubuntu:GNOME", "ubuntu", DesktopEnvironment::Gnome),
+        ("GNOME", "gnome", DesktopEnvironment::Gnome),
+        ("pop:GNOME", "pop", DesktopEnvironment::PopOs),
+        ("Budgie:GNOME", "budgie-desktop", DesktopEnvironment::Budgie),
+        ("Unity", "ubuntu", DesktopEnvironment::Gnome),
+        ("Pantheon", "pantheon", DesktopEnvironment::Gnome),
+        ("X-Cinnamon", "cinnamon", DesktopEnvironment::Cinnamon),
+        ("KDE", "plasma", DesktopEnvironment::KdePlasma),
+        ("XFCE", "xfce", DesktopEnvironment::Xfce),
+        ("MATE", "mate", DesktopEnvironment::Mate),
+        ("LXDE", "LXDE", DesktopEnvironment::Lxde),
+        ("LXQt", "lxqt", DesktopEnvironment::Lxqt),
+        ("COSMIC", "cosmic", DesktopEnvironment::Cosmic),
+        ("Deepin", "deepin", DesktopEnvironment::Deepin),
+        (
+            "Enlightenment",
+            "enlightenment",
+            DesktopEnvironment::Enlightenment,
+        ),
+        ("i3", "i3", DesktopEnvironment::I3),
+        ("sway", "sway", DesktopEnvironment::Sway),
+        ("Hyprland", "hyprland", DesktopEnvironment::Hyprland),
+        ("", "awesome", DesktopEnvironment::Awesome),
+        ("qtile", "qtile", DesktopEnvironment::Qtile),
+        ("river", "river", DesktopEnvironment::River),
+        ("Wayfire", "wayfire", DesktopEnvironment::Wayfire),
+        ("bspwm", "bspwm", DesktopEnvironment::Bspwm),
+    ];
+
+    #[test]
+    fn test_desktop_from_session_variables() {
+        for &(current, session, expected) in SESSIONS {
+            let desktops = format!("{} {}", current, session).to_lowercase();
+            // Running processes only matter when the variables name nothing
+            let de = DesktopEnvironment::from_session(&desktops, |_| true);
+            assert_eq!(de, expected, "{} / {}", current, session);
+        }
+    }
+
+    fn running(names: &'static [&'static str]) -> impl Fn(&str) -> bool {
+        move |name| names.contains(&name)
+    }
+
+    #[test]
+    fn test_window_manager_from_processes() {
+        assert_eq!(
+            DesktopEnvironment::from_session(" ", running(&["Hyprland", "waybar"])),
+            DesktopEnvironment::Hyprland
+        );
+        assert_eq!(
+            DesktopEnvironment::from_session(" ", running(&["sxhkd", "bspwm"])),
+            DesktopEnvironment::Bspwm
+        );
+        assert_eq!(
+            DesktopEnvironment::from_session("", running(&["Xorg"])),
+            DesktopEnvironment::Unknown
+        );
+        assert!(DesktopEnvironment::Bspwm.is_window_manager());
+        assert!(!DesktopEnvironment::PopOs.is_window_manager());
+    }
+
+    #[test]
+    fn test_sandbox_from() {
+        assert_eq!(
+            sandbox_from(true, Some("flatpak")).as_deref(),
+            Some("flatpak")
+        );
+        assert_eq!(sandbox_from(true, None).as_deref(), Some("flatpak"));
+        assert_eq!(
+            sandbox_from(false, Some("flatpak")).as_deref(),
+            Some("flatpak")
+        );
+        assert_eq!(sandbox_from(false, Some("podman")), None);
+        assert_eq!(sandbox_from(false, Some("oci")), None);
+        assert_eq!(sandbox_from(false, Some("")), None);
+        assert_eq!(sandbox_from(false, None), None);
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("GNOME Shell 46.0\n").as_deref(), Some("46.0"));
+        assert_eq!(parse_version("plasmashell 6.0.3").as_deref(), Some("6.0.3"));
+        assert_eq!(
+            parse_version("xfce4-session 4.18.3 (Xfce 4.18)\n\nCopyright").as_deref(),
+            Some("4.18.3")
+        );
+        assert_eq!(
+            parse_version("i3 version 4.23 (2023-10-29) © 2009 Michael Stapelberg").as_deref(),
+            Some("4.23")
+        );
+        assert_eq!(
+            parse_version("awesome v4.3 (Too long)").as_deref(),
+            Some("4.3")
+        );
+        assert_eq!(parse_version("0.25.0").as_deref(), Some("0.25.0"));
+        assert_eq!(parse_version(""), None);
+    }
+}
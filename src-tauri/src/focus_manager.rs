@@ -2,9 +2,16 @@
 //! Tracks and restores window focus for proper paste injection on X11.
 //! Also provides X11 window activation using EWMH protocols.
 
+use crate::input_simulator;
+use crate::kwin;
+use crate::linux_shortcut_manager::{self, TilingWm};
+use crate::session;
+use crate::shell_helper;
 use log::{debug, warn};
+use parking_lot::Mutex;
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::OnceLock;
 
 use std::thread;
 
@@ -12,22 +19,83 @@ use std::time::{Duration, Instant};
 
 use x11rb::connection::Connection;
 
-use x11rb::protocol::xproto::{AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, InputFocus};
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, InputFocus, MapState,
+};
 
-/// Time to wait after restoring focus before allowing the paste to proceed
+/// Time to wait after restoring focus through compositor IPC, which can't be
+/// verified, before allowing the paste to proceed
 const FOCUS_RESTORE_DELAY: Duration = Duration::from_millis(150);
 
+/// Polling interval while waiting for restored focus to arrive
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Stores the ID of the window that had focus before we opened
 static LAST_FOCUSED_WINDOW: AtomicU32 = AtomicU32::new(0);
 
+/// Native X11 id of our own main window; 0 until known
+static OWN_WINDOW: AtomicU32 = AtomicU32::new(0);
+
+/// A window saved through compositor IPC on Wayland.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WaylandWindow {
+    /// Sway container id
+    Sway(i64),
+    /// Hyprland window address ("0x...")
+    Hyprland(String),
+    /// App id reported by the GNOME Shell helper extension
+    ShellHelper(String),
+    /// KWin's internal window id ("{uuid}")
+    KWin(String),
+}
+
+/// The Wayland window that had focus before we opened, on compositors with
+/// IPC (Sway, Hyprland, KWin scripting) or with the GNOME Shell helper
+/// extension
+static LAST_FOCUSED_WAYLAND_WINDOW: Mutex<Option<WaylandWindow>> = Mutex::new(None);
+
+/// The tiling WM, detected once (detection may spawn `pgrep`)
+static TILING_WM: OnceLock<Option<TilingWm>> = OnceLock::new();
+
+/// Whether the "no focus tracking" warning was logged, so it shows once
+/// rather than on every popup
+static UNTRACKED_FOCUS_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Registers our main window, so it is never saved as the paste target.
+pub fn set_own_window(window_id: u32) {
+    OWN_WINDOW.store(window_id, Ordering::SeqCst);
+}
+
+/// Whether the focused window should replace the saved paste target. Our own
+/// window never does (a quick re-open would otherwise make the next paste
+/// target PenguinClip itself), and neither does "no focus" (None or
+/// PointerRoot).
+pub fn should_update_saved_focus(current: u32, own_window: Option<u32>, saved: u32) -> bool {
+    current > 1 && own_window != Some(current) && current != saved
+}
+
 pub fn save_focused_window() {
+    if session::is_wayland() {
+        save_focused_wayland_window();
+    }
+
     match get_x11_connection() {
         Ok(conn) => match conn.get_input_focus() {
             Ok(cookie) => match cookie.reply() {
                 Ok(reply) => {
-                    let window_id = reply.focus;
-                    LAST_FOCUSED_WINDOW.store(window_id, Ordering::SeqCst);
-                    debug!("saved focused window: {}", window_id);
+                    let own_window = Some(OWN_WINDOW.load(Ordering::SeqCst)).filter(|&id| id != 0);
+                    // Focus may sit on a child of our window
+                    let current = match own_window {
+                        Some(own) if ancestors(&conn, reply.focus).contains(&own) => own,
+                        _ => reply.focus,
+                    };
+                    let saved = LAST_FOCUSED_WINDOW.load(Ordering::SeqCst);
+                    if should_update_saved_focus(current, own_window, saved) {
+                        LAST_FOCUSED_WINDOW.store(current, Ordering::SeqCst);
+                        debug!("saved focused window: {}", current);
+                    } else {
+                        debug!("kept saved window {} (focus on {})", saved, current);
+                    }
                 }
                 Err(e) => warn!("failed to get focus reply: {}", e),
             },
@@ -39,40 +107,301 @@ pub fn save_focused_window() {
     }
 }
 
-pub fn restore_focused_window() -> Result<(), String> {
+/// Why focus could not be given back to the saved window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusRestoreError {
+    /// No window was saved when we opened
+    NoSavedWindow,
+    /// The saved window has been closed
+    WindowGone(u32),
+    /// The window exists but focus stayed elsewhere (focus stealing
+    /// prevention); `focused` is where it was last seen
+    FocusDenied {
+        window_id: u32,
+        focused: Option<u32>,
+    },
+    /// No X connection
+    Unavailable(String),
+}
+
+impl std::fmt::Display for FocusRestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSavedWindow => write!(f, "No previous window saved"),
+            Self::WindowGone(id) => write!(f, "Window {} no longer exists", id),
+            Self::FocusDenied { window_id, focused } => match focused {
+                Some(focused) => write!(
+                    f,
+                    "Window manager kept focus on {} instead of {}",
+                    focused, window_id
+                ),
+                None => write!(f, "Window manager did not focus window {}", window_id),
+            },
+            Self::Unavailable(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Gives focus back to the window saved by `save_focused_window`, verifying
+/// that it arrived within the `paste_timing` focus timeout and retrying the
+/// activation up to `focus_retries` times.
+pub fn restore_focused_window() -> Result<(), FocusRestoreError> {
     let window_id = LAST_FOCUSED_WINDOW.load(Ordering::SeqCst);
 
     if window_id == 0 {
-        return Err("No previous window saved".to_string());
+        return Err(FocusRestoreError::NoSavedWindow);
     }
 
     debug!("restoring focus to window: {}", window_id);
 
-    // Primary: ask the window manager to activate the window via EWMH
-    // _NET_ACTIVE_WINDOW. This is the method WMs honour even with focus-stealing
-    // prevention, and — crucially — it routes keyboard focus into the app's real
-    // input widget. Raw SetInputFocus alone is unreliable for GTK apps (e.g. text
-    // editors) under Mutter, so the synthetic paste keystroke would land nowhere
-    // and the paste silently failed while terminals/browsers still worked.
-    if let Err(e) = x11_activate_window_by_id(window_id) {
-        warn!("EWMH activate failed, relying on SetInputFocus: {}", e);
+    let conn = get_x11_connection().map_err(FocusRestoreError::Unavailable)?;
+    let timings = input_simulator::paste_timings();
+    // Each attempt gives EWMH the first half of the timeout before
+    // reinforcing with SetInputFocus.
+    let step_timeout = Duration::from_millis(timings.focus_timeout_ms / 2);
+    let started = Instant::now();
+
+    for attempt in 0..=timings.focus_retries {
+        if !window_exists(&conn, window_id) {
+            return Err(FocusRestoreError::WindowGone(window_id));
+        }
+
+        // Primary: ask the window manager to activate the window via EWMH
+        // _NET_ACTIVE_WINDOW. This is the method WMs honour even with focus-stealing
+        // prevention, and — crucially — it routes keyboard focus into the app's real
+        // input widget. Raw SetInputFocus alone is unreliable for GTK apps (e.g. text
+        // editors) under Mutter, so the synthetic paste keystroke would land nowhere
+        // and the paste silently failed while terminals/browsers still worked.
+        match x11_activate_window_by_id(window_id) {
+            Ok(()) => {
+                if wait_for_focus(&conn, window_id, step_timeout) {
+                    debug!(
+                        "focus restored to {} via _NET_ACTIVE_WINDOW (attempt {}, {:?})",
+                        window_id,
+                        attempt + 1,
+                        started.elapsed()
+                    );
+                    return Ok(());
+                }
+            }
+            Err(e) => warn!("EWMH activate failed, relying on SetInputFocus: {}", e),
+        }
+
+        // Fallback/reinforcement: set input focus directly. Helps simple or
+        // override-redirect windows and WMs that ignore _NET_ACTIVE_WINDOW.
+        if let Err(e) = conn.set_input_focus(InputFocus::PARENT, window_id, x11rb::CURRENT_TIME) {
+            warn!("SetInputFocus failed: {}", e);
+        }
+        let _ = conn.flush();
+        if wait_for_focus(&conn, window_id, step_timeout) {
+            debug!(
+                "focus restored to {} via SetInputFocus (attempt {}, {:?})",
+                window_id,
+                attempt + 1,
+                started.elapsed()
+            );
+            return Ok(());
+        }
+        debug!(
+            "focus did not reach {} on attempt {}",
+            window_id,
+            attempt + 1
+        );
     }
 
-    // Fallback/reinforcement: also set input focus directly. Helps simple or
-    // override-redirect windows and WMs that ignore _NET_ACTIVE_WINDOW.
-    let conn = get_x11_connection()?;
-    if let Err(e) = conn.set_input_focus(InputFocus::PARENT, window_id, x11rb::CURRENT_TIME) {
-        warn!("SetInputFocus failed: {}", e);
+    if !window_exists(&conn, window_id) {
+        return Err(FocusRestoreError::WindowGone(window_id));
     }
-    let _ = conn.flush();
+    let focused = conn
+        .get_input_focus()
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|reply| reply.focus);
+    Err(FocusRestoreError::FocusDenied { window_id, focused })
+}
 
-    // Small delay to ensure the Window Manager processes the focus change
-    // before we attempt to simulate keystrokes
-    thread::sleep(FOCUS_RESTORE_DELAY);
+fn window_exists(conn: &impl Connection, window_id: u32) -> bool {
+    conn.get_window_attributes(window_id)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .is_some()
+}
+
+/// Polls the input focus until it is on `window_id` (or a window inside or
+/// around it: a frame or child) or `timeout` passes.
+fn wait_for_focus(conn: &impl Connection, window_id: u32, timeout: Duration) -> bool {
+    let start = Instant::now();
+    loop {
+        let focused = conn
+            .get_input_focus()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.focus);
+        if let Some(focused) = focused {
+            if focused == window_id
+                || ancestors(conn, focused).contains(&window_id)
+                || ancestors(conn, window_id).contains(&focused)
+            {
+                return true;
+            }
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(FOCUS_POLL_INTERVAL);
+    }
+}
+
+/// Parents of `window` up to (not including) the root, nearest first.
+fn ancestors(conn: &impl Connection, window: u32) -> Vec<u32> {
+    let mut chain = Vec::new();
+    let mut current = window;
+    for _ in 0..10 {
+        let Some(tree) = conn
+            .query_tree(current)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        else {
+            break;
+        };
+        if tree.parent == 0 || tree.parent == tree.root {
+            break;
+        }
+        chain.push(tree.parent);
+        current = tree.parent;
+    }
+    chain
+}
+
+/// Records the focused window through the compositor's IPC (KWin's through
+/// a script), or on GNOME through the Shell helper extension when it is
+/// installed. Elsewhere nothing is saved.
+fn save_focused_wayland_window() {
+    let window = match *TILING_WM.get_or_init(linux_shortcut_manager::detect_tiling_wm) {
+        Some(TilingWm::Sway) => run_json("swaymsg", &["-t", "get_tree"])
+            .and_then(|tree| sway_focused_con_id(&tree).ok_or("no focused container".into()))
+            .map(WaylandWindow::Sway),
+        Some(TilingWm::Hyprland) => run_json("hyprctl", &["activewindow", "-j"])
+            .and_then(|window| {
+                hyprland_window_address(&window).ok_or("no active window".to_string())
+            })
+            .map(WaylandWindow::Hyprland),
+        _ if session::is_kwin() && kwin::is_available() => kwin::active_window_id()
+            .and_then(|id| id.ok_or("no active window".to_string()))
+            .map(WaylandWindow::KWin),
+        _ if shell_helper::is_available() => shell_helper::focused_window()
+            .and_then(|window| window.ok_or("no focused window".to_string()))
+            .map(|window| {
+                debug!("Shell helper reports focus on {:?}", window.title);
+                WaylandWindow::ShellHelper(window.app_id)
+            }),
+        _ => {
+            if !UNTRACKED_FOCUS_WARNED.swap(true, Ordering::Relaxed) {
+                warn!(
+                    "no compositor IPC for focus tracking on this Wayland session; \
+                     pastes go to whichever window has focus"
+                );
+            }
+            return;
+        }
+    };
+    match window {
+        Ok(window) => {
+            debug!("saved focused Wayland window: {:?}", window);
+            *LAST_FOCUSED_WAYLAND_WINDOW.lock() = Some(window);
+        }
+        Err(e) => {
+            warn!("failed to read focused Wayland window: {}", e);
+            *LAST_FOCUSED_WAYLAND_WINDOW.lock() = None;
+        }
+    }
+}
+
+/// Refocuses the window saved by `save_focused_window` through the
+/// compositor's IPC (Sway, Hyprland, KWin) or the GNOME Shell helper. Fails
+/// on compositors without either.
+pub fn restore_focused_wayland_window() -> Result<(), String> {
+    let window = LAST_FOCUSED_WAYLAND_WINDOW
+        .lock()
+        .clone()
+        .ok_or("No previous Wayland window saved (compositor without IPC support?)")?;
+    debug!("restoring focus to Wayland window: {:?}", window);
+
+    let (program, args) = match &window {
+        WaylandWindow::Sway(con_id) => (
+            "swaymsg",
+            vec![format!("[con_id={}]", con_id), "focus".to_string()],
+        ),
+        WaylandWindow::Hyprland(address) => (
+            "hyprctl",
+            vec![
+                "dispatch".to_string(),
+                "focuswindow".to_string(),
+                format!("address:{}", address),
+            ],
+        ),
+        WaylandWindow::ShellHelper(app_id) => {
+            shell_helper::activate_window(app_id)
+                .map_err(|e| format!("Shell helper failed to activate {}: {}", app_id, e))?;
+            thread::sleep(FOCUS_RESTORE_DELAY);
+            return Ok(());
+        }
+        WaylandWindow::KWin(id) => {
+            kwin::activate_window(id)
+                .map_err(|e| format!("KWin failed to activate {}: {}", id, e))?;
+            thread::sleep(FOCUS_RESTORE_DELAY);
+            return Ok(());
+        }
+    };
+    let output = std::process::Command::new(program)
+        .args(&args)
+        .output()
+        .map_err(|e| format!("{} failed: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
 
+    thread::sleep(FOCUS_RESTORE_DELAY);
     Ok(())
 }
 
+/// Runs an IPC command and parses its JSON output.
+fn run_json(program: &str, args: &[&str]) -> Result<serde_json::Value, String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{} failed: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("{} output: {}", program, e))
+}
+
+/// Finds the focused container's id in `swaymsg -t get_tree` output.
+fn sway_focused_con_id(node: &serde_json::Value) -> Option<i64> {
+    if node["focused"].as_bool() == Some(true) {
+        return node["id"].as_i64();
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[key].as_array())
+        .flatten()
+        .find_map(sway_focused_con_id)
+}
+
+/// The window address in `hyprctl activewindow -j` output (`{}` when no
+/// window is focused).
+fn hyprland_window_address(window: &serde_json::Value) -> Option<String> {
+    window["address"]
+        .as_str()
+        .filter(|address| !address.is_empty())
+        .map(str::to_string)
+}
+
 pub fn get_focused_window() -> Option<u32> {
     let conn = get_x11_connection().ok()?;
 
@@ -114,12 +443,18 @@ pub fn x11_activate_window_by_id(window_id: u32) -> Result<(), String> {
     let (conn, screen_num) =
         x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
 
-    let screen = conn
-        .setup()
-        .roots
-        .get(screen_num)
-        .ok_or("Failed to get screen")?;
-    let root = screen.root;
+    // The request goes to the root of the screen the window lives on, which
+    // isn't the default one in multi-screen setups.
+    let root = match root_of(&conn, window_id) {
+        Some(root) => root,
+        None => {
+            conn.setup()
+                .roots
+                .get(screen_num)
+                .ok_or("Failed to get screen")?
+                .root
+        }
+    };
 
     // Get _NET_ACTIVE_WINDOW atom
     let net_active_window = conn
@@ -189,21 +524,45 @@ pub fn wait_for_window_by_title(title: &str, timeout: Duration) -> Option<u32> {
     None
 }
 
+/// Waits for the window `window_id` to be mapped (viewable), polling its map
+/// state. Unlike a title search this can't match an unrelated window.
+///
+/// # Returns
+/// * `true` once the window is viewable
+/// * `false` if the timeout passed or the window doesn't exist
+pub fn wait_for_window_mapped(window_id: u32, timeout: Duration) -> bool {
+    let Ok(conn) = get_x11_connection() else {
+        return false;
+    };
+    let start = Instant::now();
+
+    while start.elapsed() < timeout {
+        let attributes = conn
+            .get_window_attributes(window_id)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
+        match attributes {
+            Some(attrs) if attrs.map_state == MapState::VIEWABLE => {
+                debug!("window {} mapped after {:?}", window_id, start.elapsed());
+                return true;
+            }
+            Some(_) => thread::sleep(WINDOW_MAP_POLL_INTERVAL),
+            // BadWindow: it's gone (or never existed), no point waiting
+            None => return false,
+        }
+    }
+
+    warn!("timeout waiting for window {} to map", window_id);
+    false
+}
+
 /// Finds a window by its title using X11 primitives.
 /// This is more reliable than xdotool as it directly queries the X server.
 fn find_window_by_title(title: &str) -> Option<u32> {
-    let (conn, screen_num) = x11rb::connect(None).ok()?;
-    let screen = conn.setup().roots.get(screen_num)?;
-    let root = screen.root;
+    let (conn, _) = x11rb::connect(None).ok()?;
+    let windows = client_windows(&conn)?;
 
     // Get atoms we need
-    let net_client_list = conn
-        .intern_atom(false, b"_NET_CLIENT_LIST")
-        .ok()?
-        .reply()
-        .ok()?
-        .atom;
-
     let net_wm_name = conn
         .intern_atom(false, b"_NET_WM_NAME")
         .ok()?
@@ -218,18 +577,6 @@ fn find_window_by_title(title: &str) -> Option<u32> {
         .ok()?
         .atom;
 
-    // Get list of all client windows
-    let client_list = conn
-        .get_property(false, root, net_client_list, AtomEnum::WINDOW, 0, 1024)
-        .ok()?
-        .reply()
-        .ok()?;
-
-    let windows: Vec<u32> = client_list
-        .value32()
-        .map(|iter| iter.collect())
-        .unwrap_or_default();
-
     // Search each window for matching title
     for window in windows {
         // Try _NET_WM_NAME first (UTF-8)
@@ -260,16 +607,102 @@ fn find_window_by_title(title: &str) -> Option<u32> {
     None
 }
 
+/// Lists the top-level client windows the window manager knows about
+/// (`_NET_CLIENT_LIST`), across every screen's root window.
+fn client_windows(conn: &impl Connection) -> Option<Vec<u32>> {
+    let net_client_list = conn
+        .intern_atom(false, b"_NET_CLIENT_LIST")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let mut windows = Vec::new();
+    for screen in &conn.setup().roots {
+        let client_list = conn
+            .get_property(
+                false,
+                screen.root,
+                net_client_list,
+                AtomEnum::WINDOW,
+                0,
+                1024,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
+        if let Some(reply) = &client_list {
+            windows.extend(reply.value32().into_iter().flatten());
+        }
+    }
+    Some(windows)
+}
+
+/// Root window of the screen `window_id` is on.
+fn root_of(conn: &impl Connection, window_id: u32) -> Option<u32> {
+    Some(conn.query_tree(window_id).ok()?.reply().ok()?.root)
+}
+
+/// Root window of the screen `window_id` is on, or `None` if the window
+/// doesn't exist. Differs from the default root in multi-screen setups.
+pub fn window_root(window_id: u32) -> Option<u32> {
+    let conn = get_x11_connection().ok()?;
+    root_of(&conn, window_id)
+}
+
+/// True if `window_id` is still a managed client window. Window ids are
+/// reused by the server, so anything remembered for later must pass this
+/// before being activated.
+pub fn is_client_window(window_id: u32) -> bool {
+    let Ok(conn) = get_x11_connection() else {
+        return false;
+    };
+    client_windows(&conn).is_some_and(|windows| windows.contains(&window_id))
+}
+
+/// The top-level window a clipboard item was copied from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceWindow {
+    pub id: u32,
+    pub title: Option<String>,
+}
+
+/// Describes the client window that owns the input focus. The focused
+/// window is often a child, so parents are walked until one appears in
+/// `_NET_CLIENT_LIST`. `None` without an X connection or a managed window.
+pub fn focused_client_window() -> Option<SourceWindow> {
+    let conn = get_x11_connection().ok()?;
+    let clients = client_windows(&conn)?;
+    let mut window = conn.get_input_focus().ok()?.reply().ok()?.focus;
+    for _ in 0..10 {
+        if window == 0 || conn.setup().roots.iter().any(|s| s.root == window) {
+            return None;
+        }
+        if clients.contains(&window) {
+            return Some(SourceWindow {
+                id: window,
+                title: window_title(&conn, window),
+            });
+        }
+        window = conn.query_tree(window).ok()?.reply().ok()?.parent;
+    }
+    None
+}
+
 /// Checks if the currently focused X11 window is a terminal emulator.
 /// Queries WM_CLASS of the focused window and matches against known terminals.
 pub fn is_focused_window_terminal() -> bool {
+    active_window_class().is_some_and(|class| wm_class_is_terminal(&class))
+}
+
+/// Returns the lowercased WM_CLASS (`instance\0class\0`) of the window
+/// keystrokes will go to, or `None` when it can't be determined.
+pub fn active_window_class() -> Option<String> {
     // First try xdotool (works even when X11 direct connection is tricky)
-    if let Ok(result) = is_terminal_via_xdotool() {
-        return result;
+    if let Ok(class) = wm_class_via_xdotool() {
+        return Some(class);
     }
 
     // Fallback: query X11 WM_CLASS directly
-    is_terminal_via_x11().unwrap_or(false)
+    focused_window_class()
 }
 
 /// Known terminal WM_CLASS values (lowercase for comparison)
@@ -317,7 +750,7 @@ const TERMINAL_WM_CLASSES: &[&str] = &[
 /// contains "string" and would spuriously match the short "st" entry, marking
 /// every window a terminal. Single-token names match a whole alphanumeric token
 /// (so "st" doesn't match "string"); dotted/hyphenated ids match as a substring.
-fn wm_class_is_terminal(classes_lower: &str) -> bool {
+pub fn wm_class_is_terminal(classes_lower: &str) -> bool {
     let tokens: Vec<&str> = classes_lower
         .split(|c: char| !c.is_alphanumeric())
         .filter(|s| !s.is_empty())
@@ -332,7 +765,7 @@ fn wm_class_is_terminal(classes_lower: &str) -> bool {
 }
 
 /// Get WM_CLASS using xdotool to get window ID, then xprop to read WM_CLASS
-fn is_terminal_via_xdotool() -> Result<bool, String> {
+fn wm_class_via_xdotool() -> Result<String, String> {
     // Step 1: Get active window ID via xdotool
     let id_output = std::process::Command::new("xdotool")
         .arg("getactivewindow")
@@ -366,11 +799,100 @@ fn is_terminal_via_xdotool() -> Result<bool, String> {
     let value = raw.split('=').nth(1).unwrap_or("");
     debug!("focused window WM_CLASS (xprop): {}", value.trim());
 
-    Ok(wm_class_is_terminal(value))
+    Ok(xprop_wm_class(value))
+}
+
+/// Converts xprop's `"instance", "class"` to the X11 `instance\0class\0` form.
+fn xprop_wm_class(value: &str) -> String {
+    value
+        .split(',')
+        .map(|s| s.trim().trim_matches('"'))
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("{}\0", s))
+        .collect()
+}
+
+/// Returns the lowercased WM_CLASS (`instance\0class\0`) of the focused X11
+/// window, or `None` when it can't be determined (e.g. pure Wayland).
+pub fn focused_window_class() -> Option<String> {
+    focused_wm_class_x11().ok().flatten()
+}
+
+/// Returns the lowercased WM_CLASS of the window saved by
+/// `save_focused_window` (the app a paste will go to), or `None` if unknown.
+pub fn last_focused_window_class() -> Option<String> {
+    let window = LAST_FOCUSED_WINDOW.load(Ordering::SeqCst);
+    if window == 0 {
+        return None;
+    }
+    let conn = get_x11_connection().ok()?;
+    wm_class_of(&conn, window).ok().flatten()
 }
 
-/// Get WM_CLASS by querying X11 directly, walking up parent windows if needed
-fn is_terminal_via_x11() -> Result<bool, String> {
+/// The focused X11 window as seen by paste diagnostics.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FocusedWindowInfo {
+    pub id: u32,
+    pub title: Option<String>,
+    /// Lowercased instance and class names
+    pub wm_class: Option<String>,
+    pub is_terminal: bool,
+}
+
+/// Describes the focused X11 window, or `None` without an X connection.
+pub fn focused_window_info() -> Option<FocusedWindowInfo> {
+    let conn = get_x11_connection().ok()?;
+    let id = conn.get_input_focus().ok()?.reply().ok()?.focus;
+    if id == 0 {
+        return None;
+    }
+    let wm_class = wm_class_of(&conn, id).ok().flatten();
+    Some(FocusedWindowInfo {
+        id,
+        title: window_title(&conn, id),
+        is_terminal: wm_class.as_deref().is_some_and(wm_class_is_terminal),
+        wm_class: wm_class.map(|class| {
+            let names: Vec<&str> = class.split('\0').filter(|n| !n.is_empty()).collect();
+            names.join(", ")
+        }),
+    })
+}
+
+/// Reads the title of `start` (_NET_WM_NAME, else WM_NAME), walking up
+/// parent windows like `wm_class_of`.
+fn window_title(conn: &impl Connection, start: u32) -> Option<String> {
+    let net_wm_name = conn
+        .intern_atom(false, b"_NET_WM_NAME")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let mut window = start;
+    for _ in 0..10 {
+        for property in [net_wm_name, AtomEnum::WM_NAME.into()] {
+            let value = conn
+                .get_property(false, window, property, AtomEnum::ANY, 0, 256)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|reply| reply.value)
+                .unwrap_or_default();
+            if !value.is_empty() {
+                return Some(String::from_utf8_lossy(&value).into_owned());
+            }
+        }
+
+        let tree = conn.query_tree(window).ok()?.reply().ok()?;
+        if tree.parent == 0 || tree.parent == tree.root {
+            break;
+        }
+        window = tree.parent;
+    }
+    None
+}
+
+/// Reads WM_CLASS of the focused window, walking up parent windows if needed
+/// (the focused window may be a child without WM_CLASS).
+fn focused_wm_class_x11() -> Result<Option<String>, String> {
     let conn = get_x11_connection()?;
     let focused = {
         let cookie = conn
@@ -381,11 +903,15 @@ fn is_terminal_via_x11() -> Result<bool, String> {
     };
 
     if focused == 0 {
-        return Ok(false);
+        return Ok(None);
     }
 
-    // Try the focused window and its parents (focused window may be a child without WM_CLASS)
-    let mut window = focused;
+    wm_class_of(&conn, focused)
+}
+
+/// Reads WM_CLASS of `start`, walking up parent windows if needed.
+fn wm_class_of(conn: &impl Connection, start: u32) -> Result<Option<String>, String> {
+    let mut window = start;
     for _ in 0..10 {
         // Query WM_CLASS property (type STRING)
         let reply = conn
@@ -405,12 +931,7 @@ fn is_terminal_via_x11() -> Result<bool, String> {
             // WM_CLASS is two null-terminated strings: instance\0class\0
             let wm_class_raw = String::from_utf8_lossy(&reply.value).to_lowercase();
             debug!("window {} WM_CLASS (x11): {}", window, wm_class_raw);
-
-            if wm_class_is_terminal(&wm_class_raw) {
-                return Ok(true);
-            }
-            // Found a WM_CLASS but it's not a terminal
-            return Ok(false);
+            return Ok(Some(wm_class_raw));
         }
 
         // No WM_CLASS on this window, try parent
@@ -426,8 +947,8 @@ fn is_terminal_via_x11() -> Result<bool, String> {
         window = tree.parent;
     }
 
-    debug!("could not find WM_CLASS for focused window {}", focused);
-    Ok(false)
+    debug!("could not find WM_CLASS for window {}", start);
+    Ok(None)
 }
 
 /// Alternative activation that sets input focus directly.
@@ -447,10 +968,11 @@ pub fn x11_force_input_focus(window_id: u32) -> Result<(), String> {
 
 /// Combined activation strategy that tries multiple methods.
 /// This is the most robust approach for X11 focus acquisition.
-pub fn x11_robust_activate(title: &str) -> Result<(), String> {
-    // Step 1: Wait for window to appear in _NET_CLIENT_LIST
-    let window_id = wait_for_window_by_title(title, WINDOW_MAP_TIMEOUT)
-        .ok_or_else(|| format!("Window '{}' not found", title))?;
+pub fn x11_robust_activate(window_id: u32) -> Result<(), String> {
+    // Step 1: Wait for the window to be mapped
+    if !wait_for_window_mapped(window_id, WINDOW_MAP_TIMEOUT) {
+        return Err(format!("Window {} is not mapped", window_id));
+    }
 
     // Step 2: Try EWMH _NET_ACTIVE_WINDOW (preferred, WM-friendly)
     if let Err(e) = x11_activate_window_by_id(window_id) {
@@ -479,9 +1001,43 @@ pub fn x11_robust_activate(title: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Last-resort variant of `x11_robust_activate` for when the window id is
+/// unknown: finds the window by title substring, which can pick the wrong
+/// window if another title contains it.
+pub fn x11_robust_activate_by_title(title: &str) -> Result<(), String> {
+    let window_id = wait_for_window_by_title(title, WINDOW_MAP_TIMEOUT)
+        .ok_or_else(|| format!("Window '{}' not found", title))?;
+    x11_robust_activate(window_id)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::wm_class_is_terminal;
+    use super::{
+        hyprland_window_address, should_update_saved_focus, sway_focused_con_id,
+        wm_class_is_terminal, xprop_wm_class,
+    };
+
+    #[test]
+    fn quick_reopen_keeps_the_previous_target() {
+        const OWN: u32 = 0x3a00003;
+        const EDITOR: u32 = 0x1200007;
+        let mut saved = 0;
+        // Open from the editor, then re-open while our popup still has focus
+        for current in [EDITOR, OWN] {
+            if should_update_saved_focus(current, Some(OWN), saved) {
+                saved = current;
+            }
+        }
+        assert_eq!(saved, EDITOR);
+
+        // Switching to another app does replace it
+        assert!(should_update_saved_focus(0x1400002, Some(OWN), saved));
+        // Before our id is known, anything real is saved
+        assert!(should_update_saved_focus(OWN, None, EDITOR));
+        // No focus / PointerRoot is never a target
+        assert!(!should_update_saved_focus(0, Some(OWN), EDITOR));
+        assert!(!should_update_saved_focus(1, Some(OWN), EDITOR));
+    }
 
     #[test]
     fn detects_terminals() {
@@ -511,4 +1067,31 @@ mod tests {
             assert!(!wm_class_is_terminal(v), "should NOT be terminal: {v}");
         }
     }
+
+    #[test]
+    fn xprop_values_convert_to_x11_classes() {
+        let class = xprop_wm_class(" \"gnome-terminal-server\", \"Gnome-terminal\"\n");
+        assert_eq!(class, "gnome-terminal-server\0Gnome-terminal\0");
+    }
+
+    #[test]
+    fn finds_the_focused_wayland_window() {
+        let tree = serde_json::json!({
+            "id": 1, "focused": false,
+            "nodes": [{
+                "id": 4, "focused": false,
+                "nodes": [{"id": 7, "focused": false, "nodes": []}],
+                "floating_nodes": [{"id": 9, "focused": true, "nodes": []}]
+            }]
+        });
+        assert_eq!(sway_focused_con_id(&tree), Some(9));
+        assert_eq!(sway_focused_con_id(&serde_json::json!({"id": 1})), None);
+
+        let active = serde_json::json!({"address": "0x55d1c0a3b2f0", "class": "kitty"});
+        assert_eq!(
+            hyprland_window_address(&active).as_deref(),
+            Some("0x55d1c0a3b2f0")
+        );
+        assert_eq!(hyprland_window_address(&serde_json::json!({})), None);
+    }
 }
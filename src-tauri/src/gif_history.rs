@@ -0,0 +1,254 @@
+//! GIF History Module
+//! Remembers pasted GIFs (LRU, like `EmojiManager`) and favorite GIFs, with disk persistence.
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum number of recent GIFs to track (favorites don't count)
+const MAX_RECENT_GIFS: usize = 50;
+
+/// Persistence filename
+const GIF_HISTORY_FILE: &str = "gif_history.json";
+
+/// A single pasted or favorited GIF
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GifUsage {
+    /// URL of the GIF that gets pasted
+    pub url: String,
+    /// Small GIF shown in the picker grid
+    #[serde(default)]
+    pub preview_url: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    /// Number of times pasted
+    #[serde(default)]
+    pub use_count: u32,
+    /// Last used timestamp (Unix epoch millis)
+    #[serde(default = "current_time_millis")]
+    pub last_used: u64,
+    /// Favorites are never evicted
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// Persistent storage format: { "gifs": [...] }
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct GifHistoryWrapper {
+    #[serde(default)]
+    gifs: Vec<GifUsage>,
+}
+
+/// Manages recent and favorite GIFs
+pub struct GifHistoryManager {
+    /// GIFs ordered by recency (index 0 is most recent)
+    gifs: Vec<GifUsage>,
+    /// Path to the data directory
+    data_dir: PathBuf,
+}
+
+impl GifHistoryManager {
+    /// Create a new GIF history manager, loading history from disk if available
+    pub fn new(data_dir: PathBuf) -> Self {
+        let mut manager = Self {
+            gifs: Vec::new(),
+            data_dir,
+        };
+
+        if let Err(e) = manager.load_from_disk() {
+            warn!("failed to load GIF history: {}", e);
+        }
+
+        manager
+    }
+
+    /// Record a paste of `url` (LRU semantics: move to front, increment count).
+    /// Preview and dimensions are updated when given.
+    pub fn record_usage(
+        &mut self,
+        url: &str,
+        preview_url: Option<String>,
+        dimensions: Option<(u32, u32)>,
+    ) {
+        let mut entry = self.take_or_create(url);
+        entry.use_count += 1;
+        entry.last_used = current_time_millis();
+        if preview_url.is_some() {
+            entry.preview_url = preview_url;
+        }
+        if let Some((width, height)) = dimensions {
+            entry.width = Some(width);
+            entry.height = Some(height);
+        }
+        self.gifs.insert(0, entry);
+
+        self.evict_least_recent();
+        self.save();
+    }
+
+    /// Favorites or unfavorites `url`, adding it if it was never pasted.
+    /// Returns whether it is now a favorite.
+    pub fn toggle_favorite(&mut self, url: &str, preview_url: Option<String>) -> bool {
+        let favorite = match self.gifs.iter_mut().find(|g| g.url == url) {
+            Some(entry) => {
+                entry.favorite = !entry.favorite;
+                if entry.preview_url.is_none() {
+                    entry.preview_url = preview_url;
+                }
+                entry.favorite
+            }
+            None => {
+                let mut entry = self.take_or_create(url);
+                entry.preview_url = preview_url;
+                entry.favorite = true;
+                self.gifs.insert(0, entry);
+                true
+            }
+        };
+
+        self.evict_least_recent();
+        self.save();
+        favorite
+    }
+
+    /// Get recent and favorite GIFs (most recently used first)
+    pub fn get_recent(&self) -> Vec<GifUsage> {
+        self.gifs.clone()
+    }
+
+    /// Removes `url`'s entry for updating, or starts a new one.
+    fn take_or_create(&mut self, url: &str) -> GifUsage {
+        match self.gifs.iter().position(|g| g.url == url) {
+            Some(index) => self.gifs.remove(index),
+            None => GifUsage {
+                url: url.to_string(),
+                preview_url: None,
+                width: None,
+                height: None,
+                use_count: 0,
+                last_used: current_time_millis(),
+                favorite: false,
+            },
+        }
+    }
+
+    /// Keep at most `MAX_RECENT_GIFS` non-favorites, dropping the least
+    /// recently used.
+    fn evict_least_recent(&mut self) {
+        let mut kept = 0;
+        self.gifs.retain(|g| {
+            if g.favorite {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_RECENT_GIFS
+        });
+    }
+
+    // --- Persistence Helpers ---
+
+    fn history_path(&self) -> PathBuf {
+        self.data_dir.join(GIF_HISTORY_FILE)
+    }
+
+    fn save(&self) {
+        if let Err(e) = self.save_to_disk() {
+            warn!("failed to save GIF history: {}", e);
+        }
+    }
+
+    fn load_from_disk(&mut self) -> Result<(), String> {
+        let path = self.history_path();
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| format!("Read error: {}", e))?;
+        let wrapper: GifHistoryWrapper =
+            serde_json::from_str(&content).map_err(|e| format!("Parse error: {}", e))?;
+
+        self.gifs = wrapper.gifs;
+        self.evict_least_recent();
+
+        debug!("loaded {} recent GIFs", self.gifs.len());
+        Ok(())
+    }
+
+    fn save_to_disk(&self) -> Result<(), String> {
+        if !self.data_dir.exists() {
+            fs::create_dir_all(&self.data_dir)
+                .map_err(|e| format!("Failed to create data dir: {}", e))?;
+        }
+
+        let wrapper = GifHistoryWrapper {
+            gifs: self.gifs.clone(),
+        };
+
+        let content = serde_json::to_string_pretty(&wrapper)
+            .map_err(|e| format!("Serialize error: {}", e))?;
+
+        fs::write(self.history_path(), content).map_err(|e| format!("Write error: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Helper to get current Unix timestamp in milliseconds
+fn current_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn get_temp_manager(name: &str) -> (GifHistoryManager, PathBuf) {
+        let data_dir = temp_dir().join(name);
+        let _ = fs::remove_dir_all(&data_dir); // Ensure clean start
+        (GifHistoryManager::new(data_dir.clone()), data_dir)
+    }
+
+    #[test]
+    fn test_record_usage_and_persistence() {
+        let (mut manager, dir) = get_temp_manager("gif_history_test");
+
+        manager.record_usage("https://a.gif", Some("https://a-nano.gif".into()), None);
+        manager.record_usage("https://b.gif", None, Some((220, 124)));
+        manager.record_usage("https://a.gif", None, None);
+
+        let recent = GifHistoryManager::new(dir).get_recent();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].url, "https://a.gif");
+        assert_eq!(recent[0].use_count, 2);
+        assert_eq!(recent[0].preview_url.as_deref(), Some("https://a-nano.gif"));
+        assert_eq!((recent[1].width, recent[1].height), (Some(220), Some(124)));
+    }
+
+    #[test]
+    fn test_favorites_are_not_evicted() {
+        let (mut manager, _dir) = get_temp_manager("gif_favorites_test");
+
+        assert!(manager.toggle_favorite("https://fav.gif", Some("https://fav-nano.gif".into())));
+        for i in 0..MAX_RECENT_GIFS + 5 {
+            manager.record_usage(&format!("https://{}.gif", i), None, None);
+        }
+
+        let recent = manager.get_recent();
+        assert_eq!(recent.len(), MAX_RECENT_GIFS + 1);
+        assert!(recent
+            .iter()
+            .any(|g| g.url == "https://fav.gif" && g.favorite));
+        assert!(!recent.iter().any(|g| g.url == "https://0.gif"));
+
+        // Unfavoriting makes it evictable again
+        assert!(!manager.toggle_favorite("https://fav.gif", None));
+        assert_eq!(manager.get_recent().len(), MAX_RECENT_GIFS);
+    }
+}
@@ -4,15 +4,34 @@
 //! IMPORTANT: This module handles specific OS-level clipboard commands (wl-copy/xclip)
 //! to ensure GIFs are pasted as files (text/uri-list) rather than raw bytes or text.
 //! This is required for rich media pasting in apps like Discord/Chrome on Linux.
+//!
+//! Tenor may serve WebP, PNG, JPEG or MP4 instead of GIF. The real type is
+//! detected so the cached file gets the right extension; with `convert_to_gif`
+//! animated WebP is transcoded to GIF (MP4 is rejected, there is no video decoder).
+//!
+//! Some apps (Telegram Desktop) ignore `text/uri-list` and want the raw image
+//! data instead; `GifPasteFormat` picks which single target is offered.
 
+use crate::focus_manager;
+use crate::input_simulator;
 use crate::session;
+use crate::user_settings::UserSettings;
+use crate::wayland_source::{self, Offer};
 use arboard::Clipboard;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::webp::WebPDecoder;
+use image::AnimationDecoder;
+use parking_lot::Mutex;
+use reqwest::Url;
+use serde::Serialize;
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
-use std::io::Write;
+use std::io::{Cursor, Read, Write};
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
 use std::time::Duration;
 
 // --- Constants ---
@@ -21,10 +40,221 @@ const APP_CACHE_DIR: &str = "penguinclip/gifs";
 const MIME_URI_LIST: &str = "text/uri-list";
 const DOWNLOAD_TIMEOUT: u64 = 10;
 const WL_COPY_SETTLE_TIME: u64 = 150;
+const MAX_REDIRECTS: usize = 5;
+
+/// Largest GIF download accepted (30 MiB).
+pub const MAX_DOWNLOAD_BYTES: u64 = 30 * 1024 * 1024;
+
+/// SECURITY: Default hosts (and their subdomains) GIFs may be downloaded from,
+/// to prevent SSRF attacks. Configurable via `gif_allowed_hosts`.
+pub const DEFAULT_GIF_HOSTS: &[&str] = &["tenor.com", "giphy.com", "media.discordapp.net"];
+
+// --- Media Types ---
+
+/// The format of a downloaded file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MediaKind {
+    Gif,
+    Webp,
+    Png,
+    Jpeg,
+    Mp4,
+}
+
+impl MediaKind {
+    fn mime(self) -> &'static str {
+        match self {
+            Self::Gif => "image/gif",
+            Self::Webp => "image/webp",
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Mp4 => "video/mp4",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::Webp => "webp",
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Mp4 => "mp4",
+        }
+    }
+
+    /// Identifies the format from its magic bytes.
+    fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if bytes.len() >= 8 && &bytes[4..8] == b"ftyp" {
+            Some(Self::Mp4)
+        } else {
+            None
+        }
+    }
+
+    /// Maps a `Content-Type` header value, ignoring parameters.
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "image/gif" => Some(Self::Gif),
+            "image/webp" => Some(Self::Webp),
+            "image/png" => Some(Self::Png),
+            "image/jpeg" | "image/jpg" => Some(Self::Jpeg),
+            "video/mp4" => Some(Self::Mp4),
+            _ => None,
+        }
+    }
 
-/// SECURITY: Allowed domains for GIF downloads.
-/// Only URLs from these domains are permitted to prevent SSRF attacks.
-const ALLOWED_GIF_DOMAINS: &[&str] = &["media.tenor.com", "media1.tenor.com", "c.tenor.com"];
+    /// Magic bytes win over the header; unknown content is treated as GIF.
+    fn detect(bytes: &[u8], content_type: Option<&str>) -> Self {
+        Self::sniff(bytes)
+            .or_else(|| content_type.and_then(Self::from_content_type))
+            .unwrap_or(Self::Gif)
+    }
+}
+
+/// True for a WebP with the animation flag set in its VP8X header.
+fn is_animated_webp(bytes: &[u8]) -> bool {
+    bytes.len() > 20 && &bytes[12..16] == b"VP8X" && bytes[20] & 0x02 != 0
+}
+
+/// Re-encodes an animated WebP as a looping GIF.
+fn webp_to_gif(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let decoder =
+        WebPDecoder::new(Cursor::new(bytes)).map_err(|e| format!("Invalid WebP: {}", e))?;
+    let frames = decoder
+        .into_frames()
+        .collect_frames()
+        .map_err(|e| format!("Failed to decode WebP frames: {}", e))?;
+    if frames.is_empty() {
+        return Err("WebP has no animation frames".to_string());
+    }
+
+    let mut out = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut out);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| format!("GIF encode failed: {}", e))?;
+        encoder
+            .encode_frames(frames)
+            .map_err(|e| format!("GIF encode failed: {}", e))?;
+    }
+    Ok(out)
+}
+
+/// Works out the final format of downloaded bytes, transcoding animated
+/// WebP when `convert_to_gif` is on.
+fn prepare_media(
+    bytes: Vec<u8>,
+    content_type: Option<&str>,
+    convert_to_gif: bool,
+) -> Result<(Vec<u8>, MediaKind), String> {
+    let kind = MediaKind::detect(&bytes, content_type);
+    if !convert_to_gif {
+        return Ok((bytes, kind));
+    }
+
+    match kind {
+        MediaKind::Webp if is_animated_webp(&bytes) => {
+            log::debug!("Converting animated WebP to GIF");
+            Ok((webp_to_gif(&bytes)?, MediaKind::Gif))
+        }
+        MediaKind::Mp4 => Err(
+            "This GIF is only available as MP4 video, which can't be converted to GIF. \
+             Turn off 'convert to GIF' to paste the video instead."
+                .to_string(),
+        ),
+        _ => Ok((bytes, kind)),
+    }
+}
+
+// --- Paste Format ---
+
+/// Which clipboard target a pasted GIF is offered as. wl-copy and xclip serve
+/// a single target per process, so only one is offered at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GifPasteFormat {
+    /// `text/uri-list` pointing at the cached file (Discord, Slack, browsers)
+    UriList,
+    /// The file's bytes as `image/gif` (or its real type)
+    ImageData,
+    /// Chosen per target app from its WM_CLASS
+    #[default]
+    Auto,
+}
+
+/// WM_CLASS fragments of apps that only accept raw image data.
+const IMAGE_DATA_APPS: &[&str] = &["telegram"];
+
+/// WM_CLASS fragments of apps known to want a file URI.
+const URI_LIST_APPS: &[&str] = &["discord", "slack"];
+
+impl GifPasteFormat {
+    /// Parses the `gif_paste_format` setting; unknown values mean `Auto`.
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "uri-list" => Self::UriList,
+            "image-data" => Self::ImageData,
+            _ => Self::Auto,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::UriList => "uri-list",
+            Self::ImageData => "image-data",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Resolves `Auto` for the app with this (lowercased) WM_CLASS. Unknown
+    /// apps get a file URI.
+    fn resolve(self, wm_class: Option<&str>) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        let Some(class) = wm_class else {
+            return Self::UriList;
+        };
+        if URI_LIST_APPS.iter().any(|app| class.contains(app)) {
+            Self::UriList
+        } else if IMAGE_DATA_APPS.iter().any(|app| class.contains(app)) {
+            Self::ImageData
+        } else {
+            Self::UriList
+        }
+    }
+}
+
+/// GIF paste preferences from `UserSettings`.
+#[derive(Debug, Clone, Default)]
+pub struct GifPasteOptions {
+    pub convert_to_gif: bool,
+    pub format: GifPasteFormat,
+    pub url_policy: GifUrlPolicy,
+}
+
+impl GifPasteOptions {
+    pub fn from_settings(settings: &UserSettings) -> Self {
+        Self {
+            convert_to_gif: settings.convert_to_gif,
+            format: GifPasteFormat::from_setting(&settings.gif_paste_format),
+            url_policy: GifUrlPolicy {
+                allowed_hosts: settings.gif_allowed_hosts.clone(),
+                allow_any_host: settings.gif_allow_any_host,
+                allow_http: settings.gif_allow_http,
+            },
+        }
+    }
+}
 
 // --- Cache Management ---
 
@@ -45,91 +275,435 @@ impl GifCache {
         Ok(cache_dir)
     }
 
-    /// Generate a file path based on the URL hash.
-    fn get_path_for_url(url: &str) -> Result<PathBuf, String> {
+    /// Generate a file path based on the URL hash and the media's extension.
+    fn get_path_for_url(url: &str, kind: MediaKind) -> Result<PathBuf, String> {
         let mut hasher = DefaultHasher::new();
         url.hash(&mut hasher);
         let hash = hasher.finish();
 
-        Ok(Self::get_dir()?.join(format!("{}.gif", hash)))
+        Ok(Self::get_dir()?.join(format!("{}.{}", hash, kind.extension())))
     }
 }
 
-// --- Downloader ---
+// --- URL Policy ---
 
-struct Downloader;
+/// Why a GIF URL was refused. Serialized with a `reason` tag so the UI can
+/// explain the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum GifUrlError {
+    InvalidUrl,
+    SchemeNotAllowed {
+        scheme: String,
+    },
+    HostNotAllowed {
+        host: String,
+    },
+    /// Loopback, private, link-local (cloud metadata) and similar addresses
+    PrivateAddress {
+        host: String,
+    },
+    TooLarge {
+        limit: u64,
+    },
+    RedirectNotAllowed {
+        host: String,
+    },
+}
 
-impl Downloader {
-    /// Validate that a URL is safe to download from.
-    /// Only HTTPS URLs from whitelisted domains are allowed.
-    fn validate_url(url: &str) -> Result<(), String> {
-        // Must be HTTPS
-        if !url.starts_with("https://") {
-            return Err(format!("Only HTTPS URLs are allowed, got: {}", url));
-        }
-
-        // Extract hostname from URL
-        let without_scheme = &url[8..]; // skip "https://"
-        let host = without_scheme
-            .split('/')
-            .next()
-            .unwrap_or("")
-            .split(':')
-            .next()
-            .unwrap_or("");
-
-        // Check against whitelist
-        if !ALLOWED_GIF_DOMAINS.contains(&host) {
-            return Err(format!(
-                "Domain '{}' is not in the allowed list. Allowed: {:?}",
-                host, ALLOWED_GIF_DOMAINS
-            ));
+impl std::fmt::Display for GifUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUrl => write!(f, "Not a valid URL"),
+            Self::SchemeNotAllowed { scheme } => {
+                write!(f, "'{}' URLs are not allowed for GIFs", scheme)
+            }
+            Self::HostNotAllowed { host } => {
+                write!(f, "'{}' is not in the allowed GIF hosts", host)
+            }
+            Self::PrivateAddress { host } => {
+                write!(f, "'{}' is a local or private address", host)
+            }
+            Self::TooLarge { limit } => {
+                write!(f, "The GIF is larger than {} MB", limit / (1024 * 1024))
+            }
+            Self::RedirectNotAllowed { host } => {
+                write!(
+                    f,
+                    "The GIF URL redirected to '{}', which is not allowed",
+                    host
+                )
+            }
         }
+    }
+}
 
-        Ok(())
+impl std::error::Error for GifUrlError {}
+
+/// Which GIF URLs may be downloaded, from `UserSettings`.
+#[derive(Debug, Clone)]
+pub struct GifUrlPolicy {
+    /// Hosts allowed along with their subdomains (lowercase)
+    pub allowed_hosts: Vec<String>,
+    /// Skip the host allowlist. Local and private addresses stay blocked,
+    /// including public names that resolve to one (see `PublicResolver`).
+    pub allow_any_host: bool,
+    /// Also accept plain `http` URLs
+    pub allow_http: bool,
+}
+
+impl Default for GifUrlPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: DEFAULT_GIF_HOSTS.iter().map(|h| h.to_string()).collect(),
+            allow_any_host: false,
+            allow_http: false,
+        }
+    }
+}
+
+/// True for addresses a GIF should never come from.
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (v4.octets()[0] == 100 && v4.octets()[1] & 0xC0 == 64)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || first & 0xFE00 == 0xFC00 // unique local
+                || first & 0xFFC0 == 0xFE80 // link-local
+        }
+    }
+}
+
+impl GifUrlPolicy {
+    /// Checks `url` against the policy and returns it parsed.
+    pub fn check(&self, url: &str) -> Result<Url, GifUrlError> {
+        let parsed = Url::parse(url).map_err(|_| GifUrlError::InvalidUrl)?;
+        self.check_url(&parsed)?;
+        Ok(parsed)
     }
 
-    /// Downloads a URL to a local file.
-    /// SECURITY: Only allows HTTPS URLs from whitelisted domains.
-    pub fn download(url: &str, destination: &Path) -> Result<(), String> {
-        Self::validate_url(url)?;
-        eprintln!("[GifManager] Downloading: {}", url);
+    fn check_url(&self, url: &Url) -> Result<(), GifUrlError> {
+        match url.scheme() {
+            "https" => {}
+            "http" if self.allow_http => {}
+            scheme => {
+                return Err(GifUrlError::SchemeNotAllowed {
+                    scheme: scheme.to_string(),
+                })
+            }
+        }
+
+        let host = url
+            .host_str()
+            .filter(|h| !h.is_empty())
+            .ok_or(GifUrlError::InvalidUrl)?
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        let is_private = match host.trim_matches(|c| c == '[' || c == ']').parse() {
+            Ok(ip) => is_private_ip(ip),
+            Err(_) => host == "localhost" || host.ends_with(".localhost"),
+        };
+        if is_private {
+            return Err(GifUrlError::PrivateAddress { host });
+        }
+
+        let allowed = self.allow_any_host
+            || self
+                .allowed_hosts
+                .iter()
+                .any(|h| host == *h || host.ends_with(&format!(".{}", h)));
+        if allowed {
+            Ok(())
+        } else {
+            Err(GifUrlError::HostNotAllowed { host })
+        }
+    }
+}
+
+/// A failed GIF paste: either refused by the URL policy or failed on the way.
+/// Serialized with a `kind` tag for the UI.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GifPasteError {
+    Blocked {
+        #[serde(flatten)]
+        reason: GifUrlError,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<GifUrlError> for GifPasteError {
+    fn from(reason: GifUrlError) -> Self {
+        Self::Blocked {
+            message: reason.to_string(),
+            reason,
+        }
+    }
+}
+
+impl From<String> for GifPasteError {
+    fn from(message: String) -> Self {
+        Self::Failed { message }
+    }
+}
+
+impl std::fmt::Display for GifPasteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blocked { message, .. } | Self::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+/// Resolves GIF hosts for the download client, refusing names that resolve
+/// to a local or private address. `check_url` only sees IP literals; doing
+/// this in the resolver also covers redirects and the very addresses the
+/// client connects to.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let addrs = public_addrs(&host, addrs)?;
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// `addrs` if none of them is local or private
+fn public_addrs(host: &str, addrs: Vec<SocketAddr>) -> Result<Vec<SocketAddr>, GifUrlError> {
+    if addrs.iter().any(|addr| is_private_ip(addr.ip())) {
+        return Err(GifUrlError::PrivateAddress {
+            host: host.to_string(),
+        });
+    }
+    Ok(addrs)
+}
+
+/// Finds a policy error inside a reqwest error (raised by the redirect check
+/// or the resolver).
+fn policy_error(error: &reqwest::Error) -> Option<GifUrlError> {
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        if let Some(blocked) = e.downcast_ref::<GifUrlError>() {
+            return Some(blocked.clone());
+        }
+        source = e.source();
+    }
+    None
+}
+
+/// Reads at most `limit` bytes, failing instead of truncating.
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>, GifPasteError> {
+    let mut bytes = Vec::new();
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read bytes: {}", e))?;
+    if bytes.len() as u64 > limit {
+        return Err(GifUrlError::TooLarge { limit }.into());
+    }
+    Ok(bytes)
+}
 
+// --- Downloader ---
+
+/// A downloaded response body and its `Content-Type`.
+struct Download {
+    bytes: Vec<u8>,
+    content_type: Option<String>,
+}
+
+struct Downloader;
+
+impl Downloader {
+    /// Downloads a URL into memory.
+    /// SECURITY: The URL and every redirect must pass `policy`, no host may
+    /// resolve to a private address, and bodies over `MAX_DOWNLOAD_BYTES`
+    /// are refused before being buffered.
+    pub fn download(url: &str, policy: &GifUrlPolicy) -> Result<Download, GifPasteError> {
+        let url = policy.check(url)?;
+        log::debug!("Downloading: {}", url);
+
+        let redirect_policy = policy.clone();
         let client = reqwest::blocking::Client::builder()
             .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    return attempt.error("too many redirects");
+                }
+                match redirect_policy.check_url(attempt.url()) {
+                    Ok(()) => attempt.follow(),
+                    Err(GifUrlError::InvalidUrl) => attempt.error(GifUrlError::InvalidUrl),
+                    Err(_) => {
+                        let host = attempt.url().host_str().unwrap_or_default().to_string();
+                        attempt.error(GifUrlError::RedirectNotAllowed { host })
+                    }
+                }
+            }))
+            .dns_resolver(Arc::new(PublicResolver))
             .build()
             .map_err(|e| format!("Client build error: {}", e))?;
 
-        let response = client
-            .get(url)
-            .send()
-            .map_err(|e| format!("Network request failed: {}", e))?;
+        let response = client.get(url).send().map_err(|e| match policy_error(&e) {
+            Some(blocked) => blocked.into(),
+            None => GifPasteError::from(format!("Network request failed: {}", e)),
+        })?;
 
         if !response.status().is_success() {
-            return Err(format!("HTTP Error: {}", response.status()));
+            return Err(format!("HTTP Error: {}", response.status()).into());
         }
 
-        let bytes = response
-            .bytes()
-            .map_err(|e| format!("Failed to read bytes: {}", e))?;
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_DOWNLOAD_BYTES)
+        {
+            return Err(GifUrlError::TooLarge {
+                limit: MAX_DOWNLOAD_BYTES,
+            }
+            .into());
+        }
 
-        let mut file =
-            fs::File::create(destination).map_err(|e| format!("File creation failed: {}", e))?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
 
-        file.write_all(&bytes)
-            .map_err(|e| format!("File write failed: {}", e))?;
+        let bytes = read_limited(response, MAX_DOWNLOAD_BYTES)?;
 
-        eprintln!(
-            "[GifManager] Saved {} bytes to {:?}",
-            bytes.len(),
-            destination
-        );
-        Ok(())
+        Ok(Download {
+            bytes,
+            content_type,
+        })
+    }
+}
+
+// --- Clipboard Backends ---
+
+/// Which tools can put a GIF on the clipboard, and which one will be used.
+#[derive(Debug, Clone, Serialize)]
+pub struct GifClipboardStatus {
+    pub is_wayland: bool,
+    pub wl_copy_available: bool,
+    pub native_wayland_available: bool,
+    pub xclip_available: bool,
+    /// "wl-copy", "wayland-native", "xclip", or "none" (the URL is pasted as text)
+    pub active_backend: String,
+}
+
+/// Check which clipboard backend GIF paste uses
+#[tauri::command]
+pub fn check_gif_clipboard_tools() -> GifClipboardStatus {
+    let is_wayland = session::is_wayland();
+    let wl_copy_available = is_wayland && input_simulator::wl_clipboard_available();
+    let native_wayland_available = is_wayland && wayland_source::is_available();
+    let xclip_available = input_simulator::command_exists("xclip");
+
+    let active_backend = if wl_copy_available {
+        "wl-copy"
+    } else if native_wayland_available {
+        "wayland-native"
+    } else if xclip_available {
+        "xclip"
+    } else {
+        "none"
+    };
+
+    GifClipboardStatus {
+        is_wayland,
+        wl_copy_available,
+        native_wayland_available,
+        xclip_available,
+        active_backend: active_backend.to_string(),
+    }
+}
+
+// --- Clipboard Workers ---
+
+/// An `xclip` process we spawned that is serving a GIF to the clipboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardWorker {
+    pub pid: u32,
+    /// Clipboard target it serves (e.g. "text/uri-list")
+    pub target: String,
+    /// Cached file it points at
+    pub file: String,
+    /// Unix epoch millis
+    pub started_at: i64,
+}
+
+/// Our live `xclip` workers. Only ours are tracked, so stopping them never
+/// touches xclip processes started by other apps.
+static XCLIP_WORKERS: Mutex<Vec<(Child, ClipboardWorker)>> = Mutex::new(Vec::new());
+
+/// Drops workers that already exited (e.g. another app took the clipboard),
+/// reaping them so they don't linger as zombies.
+fn reap_workers(workers: &mut Vec<(Child, ClipboardWorker)>) {
+    workers.retain_mut(|(child, _)| matches!(child.try_wait(), Ok(None)));
+}
+
+fn register_worker(child: Child, target: &str, file: &Path) {
+    let info = ClipboardWorker {
+        pid: child.id(),
+        target: target.to_string(),
+        file: file.to_string_lossy().into_owned(),
+        started_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let mut workers = XCLIP_WORKERS.lock();
+    reap_workers(&mut workers);
+    workers.push((child, info));
+}
+
+/// Lists the clipboard workers that are still running.
+pub fn clipboard_workers() -> Vec<ClipboardWorker> {
+    let mut workers = XCLIP_WORKERS.lock();
+    reap_workers(&mut workers);
+    workers.iter().map(|(_, info)| info.clone()).collect()
+}
+
+/// Kills and reaps every clipboard worker we spawned. Called before a new
+/// GIF is copied and on app exit.
+pub fn stop_clipboard_workers() {
+    let workers: Vec<_> = XCLIP_WORKERS.lock().drain(..).collect();
+    for (mut child, info) in workers {
+        if let Ok(None) = child.try_wait() {
+            log::debug!("Stopping xclip worker {}", info.pid);
+            let _ = child.kill();
+        }
+        let _ = child.wait();
     }
 }
 
 // --- Clipboard Logic (The Critical Part) ---
 
+/// What gets written to wl-copy/xclip's stdin, and as which target.
+struct ClipboardPayload {
+    mime: &'static str,
+    bytes: Vec<u8>,
+    format: GifPasteFormat,
+    /// The cached file being pasted
+    path: PathBuf,
+}
+
 struct ClipboardHandler;
 
 impl ClipboardHandler {
@@ -138,25 +712,80 @@ impl ClipboardHandler {
         format!("file://{}\n", path.to_string_lossy())
     }
 
+    /// Builds the payload for `format` (already resolved, not `Auto`).
+    fn payload(
+        path: &Path,
+        kind: MediaKind,
+        format: GifPasteFormat,
+    ) -> Result<ClipboardPayload, String> {
+        if format == GifPasteFormat::ImageData {
+            let bytes = fs::read(path).map_err(|e| format!("File read failed: {}", e))?;
+            Ok(ClipboardPayload {
+                mime: kind.mime(),
+                bytes,
+                format,
+                path: path.to_path_buf(),
+            })
+        } else {
+            Ok(ClipboardPayload {
+                mime: MIME_URI_LIST,
+                bytes: Self::make_file_uri(path).into_bytes(),
+                format,
+                path: path.to_path_buf(),
+            })
+        }
+    }
+
+    /// Serves the GIF from our own Wayland data-control source, for when
+    /// `wl-copy` isn't installed. Both the file URI and the image data are
+    /// offered, the payload's type first.
+    fn copy_wayland_native(payload: &ClipboardPayload, kind: MediaKind) -> Result<(), String> {
+        log::debug!(
+            "Serving clipboard natively ({}, mode {})",
+            payload.mime,
+            payload.format.name()
+        );
+
+        let uri: Offer = (
+            MIME_URI_LIST.to_string(),
+            Arc::new(Self::make_file_uri(&payload.path).into_bytes()),
+        );
+        let image: Offer = if payload.format == GifPasteFormat::ImageData {
+            (payload.mime.to_string(), Arc::new(payload.bytes.clone()))
+        } else {
+            let bytes = fs::read(&payload.path).map_err(|e| format!("File read failed: {}", e))?;
+            (kind.mime().to_string(), Arc::new(bytes))
+        };
+
+        let offers = if payload.format == GifPasteFormat::ImageData {
+            vec![image, uri]
+        } else {
+            vec![uri, image]
+        };
+        wayland_source::serve(offers)
+    }
+
     /// Uses `wl-copy` to set clipboard on Wayland.
     ///
     /// CRITICAL: wl-copy forks to background to serve the paste request.
     /// We must write to its stdin, then let it detach.
-    fn copy_wayland(path: &Path) -> Result<(), String> {
-        let uri = Self::make_file_uri(path);
-
+    fn copy_wayland(payload: &ClipboardPayload) -> Result<(), String> {
         // Env vars are strictly required for wl-copy context
         let display =
             std::env::var("WAYLAND_DISPLAY").map_err(|_| "WAYLAND_DISPLAY not set".to_string())?;
         let runtime_dir =
             std::env::var("XDG_RUNTIME_DIR").map_err(|_| "XDG_RUNTIME_DIR not set".to_string())?;
 
-        eprintln!("[GifManager] Executing wl-copy ({})", MIME_URI_LIST);
+        log::debug!(
+            "Executing wl-copy ({}, mode {})",
+            payload.mime,
+            payload.format.name()
+        );
 
         let mut child = Command::new("wl-copy")
             .env("WAYLAND_DISPLAY", display)
             .env("XDG_RUNTIME_DIR", runtime_dir)
-            .args(["--type", MIME_URI_LIST])
+            .args(["--type", payload.mime])
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
@@ -165,7 +794,7 @@ impl ClipboardHandler {
 
         if let Some(mut stdin) = child.stdin.take() {
             stdin
-                .write_all(uri.as_bytes())
+                .write_all(&payload.bytes)
                 .map_err(|e| format!("Pipe write error: {}", e))?;
         }
 
@@ -184,7 +813,7 @@ impl ClipboardHandler {
                 Err(format!("wl-copy crashed: {}", stderr))
             }
             Ok(_) => {
-                eprintln!("[GifManager] wl-copy running in background");
+                log::debug!("wl-copy running in background");
                 Ok(())
             }
             Err(e) => Err(format!("Process status check failed: {}", e)),
@@ -193,23 +822,22 @@ impl ClipboardHandler {
 
     /// Uses `xclip` to set clipboard on X11.
     ///
-    /// CRITICAL: We spawn xclip and detach the thread so it persists.
-    fn copy_x11(path: &Path) -> Result<(), String> {
-        let uri = Self::make_file_uri(path);
+    /// CRITICAL: xclip keeps running to serve the clipboard. It is registered
+    /// as a worker so the previous one is stopped here and all are stopped on exit.
+    fn copy_x11(payload: &ClipboardPayload) -> Result<(), String> {
         let display = std::env::var("DISPLAY").map_err(|_| "DISPLAY not set".to_string())?;
 
-        eprintln!("[GifManager] Executing xclip ({})", MIME_URI_LIST);
+        stop_clipboard_workers();
+
+        log::debug!(
+            "Executing xclip ({}, mode {})",
+            payload.mime,
+            payload.format.name()
+        );
 
         let mut child = Command::new("xclip")
             .env("DISPLAY", display)
-            .args([
-                "-selection",
-                "clipboard",
-                "-t",
-                MIME_URI_LIST,
-                "-loops",
-                "0",
-            ])
+            .args(["-selection", "clipboard", "-t", payload.mime, "-loops", "0"])
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::null())
@@ -218,21 +846,18 @@ impl ClipboardHandler {
 
         if let Some(mut stdin) = child.stdin.take() {
             stdin
-                .write_all(uri.as_bytes())
+                .write_all(&payload.bytes)
                 .map_err(|e| format!("Pipe write error: {}", e))?;
         }
 
-        // Detach to allow xclip to serve requests indefinitely
-        std::thread::spawn(move || {
-            let _ = child.wait();
-        });
+        register_worker(child, payload.mime, &payload.path);
 
         Ok(())
     }
 
     /// Fallback: Just put the text URL on the clipboard.
     fn copy_url_fallback(url: &str) -> Result<(), String> {
-        eprintln!("[GifManager] Fallback: Setting clipboard to URL text");
+        log::debug!("Fallback: Setting clipboard to URL text");
         Clipboard::new()
             .map_err(|e| e.to_string())?
             .set_text(url)
@@ -242,48 +867,89 @@ impl ClipboardHandler {
 
 // --- Public API ---
 
-/// Downloads a GIF from the URL and returns the local file path.
-pub fn download_gif_to_file(url: &str) -> Result<PathBuf, String> {
-    let target_path = GifCache::get_path_for_url(url)?;
+/// Writes a download to the cache, named with its real (or converted)
+/// extension, and returns the path and final format.
+fn save_download(
+    url: &str,
+    download: Download,
+    convert_to_gif: bool,
+) -> Result<(PathBuf, MediaKind), String> {
+    let (bytes, kind) = prepare_media(
+        download.bytes,
+        download.content_type.as_deref(),
+        convert_to_gif,
+    )?;
+    let target_path = GifCache::get_path_for_url(url, kind)?;
 
-    // Check if we already have it to avoid redownload (optional optimization,
-    // but the original code overwrote every time. I'll maintain overwrite
-    // to ensure validity, but using `Downloader` keeps it clean).
-    Downloader::download(url, &target_path)?;
+    let mut file =
+        fs::File::create(&target_path).map_err(|e| format!("File creation failed: {}", e))?;
+    file.write_all(&bytes)
+        .map_err(|e| format!("File write failed: {}", e))?;
 
-    Ok(target_path)
+    log::debug!("Saved {} bytes to {:?}", bytes.len(), target_path);
+    Ok((target_path, kind))
+}
+
+/// Downloads a GIF from the URL and returns the local file path.
+/// The file is always re-downloaded so the cached copy stays valid.
+pub fn download_gif_to_file(
+    url: &str,
+    options: &GifPasteOptions,
+) -> Result<PathBuf, GifPasteError> {
+    let download = Downloader::download(url, &options.url_policy)?;
+    let (path, _) = save_download(url, download, options.convert_to_gif)?;
+    Ok(path)
 }
 
 /// Downloads GIF and sets clipboard.
 /// Returns Ok(Some(uri)) if successful (for history marking),
 /// Ok(Some(url)) if fallback used,
-/// Err if everything failed.
-pub fn paste_gif_to_clipboard_with_uri(url: &str) -> Result<Option<String>, String> {
+/// Err if the URL is blocked, everything failed, or the media can't be
+/// converted to GIF.
+pub fn paste_gif_to_clipboard_with_uri(
+    url: &str,
+    options: GifPasteOptions,
+) -> Result<Option<String>, GifPasteError> {
     let is_wayland = session::is_wayland();
-    eprintln!(
-        "[GifManager] Mode: {}",
-        if is_wayland { "Wayland" } else { "X11" }
-    );
+    log::debug!("Mode: {}", if is_wayland { "Wayland" } else { "X11" });
 
     // 1. Attempt Download
-    let gif_path = match download_gif_to_file(url) {
-        Ok(path) => path,
+    let download = match Downloader::download(url, &options.url_policy) {
+        Ok(download) => download,
+        Err(e @ GifPasteError::Blocked { .. }) => {
+            log::warn!("Blocked: {}", e);
+            return Err(e);
+        }
         Err(e) => {
-            eprintln!("[GifManager] Download failed ({}), using URL fallback.", e);
+            log::warn!("Download failed ({}), using URL fallback.", e);
             ClipboardHandler::copy_url_fallback(url)?;
             return Ok(Some(url.to_string()));
         }
     };
+    let (gif_path, kind) = save_download(url, download, options.convert_to_gif)?;
 
     // 2. Attempt Copy
-    let copy_result = if is_wayland {
-        ClipboardHandler::copy_wayland(&gif_path).or_else(|e| {
-            eprintln!("[GifManager] Wayland copy failed ({}), trying X11...", e);
-            ClipboardHandler::copy_x11(&gif_path)
-        })
-    } else {
-        ClipboardHandler::copy_x11(&gif_path)
+    let format = match options.format {
+        GifPasteFormat::Auto => {
+            GifPasteFormat::Auto.resolve(focus_manager::last_focused_window_class().as_deref())
+        }
+        format => format,
     };
+    let copy_result = ClipboardHandler::payload(&gif_path, kind, format).and_then(|payload| {
+        if is_wayland {
+            let wayland_result = if input_simulator::wl_clipboard_available() {
+                ClipboardHandler::copy_wayland(&payload)
+            } else {
+                ClipboardHandler::copy_wayland_native(&payload, kind)
+            };
+            wayland_result.or_else(|e| {
+                log::warn!("Wayland copy failed ({}), trying X11...", e);
+                ClipboardHandler::copy_x11(&payload)
+            })
+        } else {
+            ClipboardHandler::copy_x11(&payload)
+        }
+    });
 
     // 3. Handle Result
     match copy_result {
@@ -292,7 +958,7 @@ pub fn paste_gif_to_clipboard_with_uri(url: &str) -> Result<Option<String>, Stri
             Ok(Some(uri))
         }
         Err(e) => {
-            eprintln!("[GifManager] File copy failed ({}), using URL fallback.", e);
+            log::warn!("File copy failed ({}), using URL fallback.", e);
             ClipboardHandler::copy_url_fallback(url)?;
             Ok(Some(url.to_string()))
         }
@@ -312,8 +978,220 @@ mod tests {
 
     #[test]
     fn test_path_generation() {
-        let path = GifCache::get_path_for_url("http://example.com/cat.gif");
+        let path = GifCache::get_path_for_url("http://example.com/cat.gif", MediaKind::Gif);
         assert!(path.is_ok());
         assert!(path.unwrap().extension().unwrap() == "gif");
+
+        let path = GifCache::get_path_for_url("http://example.com/cat.gif", MediaKind::Webp);
+        assert!(path.unwrap().extension().unwrap() == "webp");
+    }
+
+    #[test]
+    fn test_url_policy() {
+        let policy = GifUrlPolicy::default();
+        assert!(policy.check("https://media.tenor.com/x/cat.gif").is_ok());
+        assert!(policy.check("https://media4.giphy.com/cat.gif").is_ok());
+        assert!(policy.check("https://media.discordapp.net/a.gif").is_ok());
+        assert_eq!(
+            policy.check("https://eviltenor.com/cat.gif"),
+            Err(GifUrlError::HostNotAllowed {
+                host: "eviltenor.com".into()
+            })
+        );
+        assert_eq!(
+            policy.check("http://media.tenor.com/cat.gif"),
+            Err(GifUrlError::SchemeNotAllowed {
+                scheme: "http".into()
+            })
+        );
+        assert!(matches!(
+            policy.check("file:///etc/passwd"),
+            Err(GifUrlError::SchemeNotAllowed { .. })
+        ));
+        assert_eq!(policy.check("not a url"), Err(GifUrlError::InvalidUrl));
+
+        // Any-host mode still refuses local and private addresses
+        let open = GifUrlPolicy {
+            allow_any_host: true,
+            allow_http: true,
+            ..GifUrlPolicy::default()
+        };
+        assert!(open.check("http://example.com/cat.gif").is_ok());
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "http://127.0.0.1:8080/",
+            "http://10.1.2.3/a.gif",
+            "http://[::1]/a.gif",
+            "http://[::ffff:192.168.0.1]/a.gif",
+            "http://localhost/a.gif",
+        ] {
+            assert!(
+                matches!(open.check(url), Err(GifUrlError::PrivateAddress { .. })),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_names_resolving_to_private_addresses_are_blocked() {
+        let public: SocketAddr = "93.184.215.14:0".parse().unwrap();
+        let private: SocketAddr = "10.0.0.7:0".parse().unwrap();
+        assert_eq!(public_addrs("example.com", vec![public]), Ok(vec![public]));
+        assert_eq!(
+            public_addrs("rebind.example", vec![public, private]),
+            Err(GifUrlError::PrivateAddress {
+                host: "rebind.example".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_blocked_error_is_typed_for_the_ui() {
+        let error = GifPasteError::from(GifUrlError::HostNotAllowed {
+            host: "example.com".into(),
+        });
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "blocked",
+                "reason": "host_not_allowed",
+                "host": "example.com",
+                "message": "'example.com' is not in the allowed GIF hosts",
+            })
+        );
+
+        let data = [0u8; 11];
+        assert!(read_limited(&data[..], 11).is_ok());
+        assert_eq!(
+            read_limited(&data[..], 10),
+            Err(GifUrlError::TooLarge { limit: 10 }.into())
+        );
+    }
+
+    #[test]
+    fn test_clipboard_workers_are_tracked_and_stopped() {
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        register_worker(child, MIME_URI_LIST, Path::new("/tmp/cat.gif"));
+
+        let workers = clipboard_workers();
+        assert!(workers
+            .iter()
+            .any(|w| w.pid == pid && w.file == "/tmp/cat.gif"));
+
+        stop_clipboard_workers();
+        assert!(clipboard_workers().is_empty());
+        assert!(!Path::new(&format!("/proc/{}", pid)).exists());
+    }
+
+    #[test]
+    fn test_auto_paste_format_by_app() {
+        let auto = GifPasteFormat::from_setting("auto");
+        assert_eq!(
+            auto.resolve(Some("telegram-desktop\0telegramdesktop\0")),
+            GifPasteFormat::ImageData
+        );
+        assert_eq!(
+            auto.resolve(Some("discord\0discord\0")),
+            GifPasteFormat::UriList
+        );
+        assert_eq!(
+            auto.resolve(Some("firefox\0firefox\0")),
+            GifPasteFormat::UriList
+        );
+        assert_eq!(auto.resolve(None), GifPasteFormat::UriList);
+
+        // Explicit choices ignore the app
+        assert_eq!(
+            GifPasteFormat::from_setting("image-data").resolve(Some("discord")),
+            GifPasteFormat::ImageData
+        );
+        assert_eq!(
+            GifPasteFormat::from_setting("uri-list").resolve(Some("telegram")),
+            GifPasteFormat::UriList
+        );
+    }
+
+    #[test]
+    fn test_media_detection() {
+        assert_eq!(MediaKind::detect(b"GIF89a....", None), MediaKind::Gif);
+        assert_eq!(
+            MediaKind::detect(b"RIFF\0\0\0\0WEBPVP8 ", Some("image/gif")),
+            MediaKind::Webp,
+            "magic bytes win over the header"
+        );
+        assert_eq!(
+            MediaKind::detect(b"\x89PNG\r\n\x1a\n", None),
+            MediaKind::Png
+        );
+        assert_eq!(
+            MediaKind::detect(&[0xFF, 0xD8, 0xFF, 0xE0], None),
+            MediaKind::Jpeg
+        );
+        assert_eq!(
+            MediaKind::detect(b"\0\0\0\x18ftypmp42", None),
+            MediaKind::Mp4
+        );
+        assert_eq!(
+            MediaKind::detect(b"????", Some("image/webp; charset=binary")),
+            MediaKind::Webp
+        );
+        assert_eq!(MediaKind::detect(b"????", None), MediaKind::Gif);
+    }
+
+    #[test]
+    fn test_prepare_media_conversion() {
+        let mp4 = b"\0\0\0\x18ftypmp42".to_vec();
+        assert!(prepare_media(mp4.clone(), None, true).is_err());
+        assert_eq!(prepare_media(mp4, None, false).unwrap().1, MediaKind::Mp4);
+
+        // A still WebP is kept as-is even when converting
+        let mut webp = Vec::new();
+        image::RgbaImage::new(2, 2)
+            .write_to(&mut Cursor::new(&mut webp), image::ImageFormat::WebP)
+            .unwrap();
+        assert!(!is_animated_webp(&webp));
+        let (bytes, kind) = prepare_media(webp.clone(), None, true).unwrap();
+        assert_eq!((bytes, kind), (webp.clone(), MediaKind::Webp));
+
+        // An animated WebP becomes a GIF
+        let animated = animate_webp(&webp, 2, 2);
+        assert!(is_animated_webp(&animated));
+        let (gif, kind) = prepare_media(animated.clone(), None, true).unwrap();
+        assert_eq!(kind, MediaKind::Gif);
+        assert_eq!(MediaKind::sniff(&gif), Some(MediaKind::Gif));
+        assert_eq!(
+            prepare_media(animated, None, false).unwrap().1,
+            MediaKind::Webp
+        );
+    }
+
+    /// Wraps the image chunk of a still WebP in a one-frame animation.
+    fn animate_webp(still: &[u8], width: u32, height: u32) -> Vec<u8> {
+        fn chunk(tag: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut out = tag.to_vec();
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                out.push(0);
+            }
+            out
+        }
+        let u24 = |v: u32| v.to_le_bytes()[..3].to_vec();
+
+        let mut vp8x = vec![0x12, 0, 0, 0]; // animation + alpha flags
+        vp8x.extend(u24(width - 1));
+        vp8x.extend(u24(height - 1));
+        let anim = [0, 0, 0, 0, 0, 0]; // background color, loop forever
+        let mut anmf = [u24(0), u24(0), u24(width - 1), u24(height - 1), u24(100)].concat();
+        anmf.push(0);
+        anmf.extend_from_slice(&still[12..]);
+
+        let mut body = b"WEBP".to_vec();
+        body.extend(chunk(b"VP8X", &vp8x));
+        body.extend(chunk(b"ANIM", &anim));
+        body.extend(chunk(b"ANMF", &anmf));
+        chunk(b"RIFF", &body)
     }
 }
@@ -0,0 +1,390 @@
+//! Global Shortcuts Portal
+//! Binds the shortcuts through `org.freedesktop.portal.GlobalShortcuts`.
+//! Inside a sandbox, or on Wayland desktops that implement the portal, the
+//! desktop owns the key grab and tells us when a shortcut fires, instead of
+//! us writing gsettings keys or config files.
+//! See: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.GlobalShortcuts.html
+
+use crate::linux_shortcut_manager::{all_shortcuts, HistoryBindings};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::Connection;
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const GLOBAL_SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+/// Shortcut id that opens the clipboard history
+pub const CLIPBOARD_HISTORY: &str = "clipboard-history";
+/// Shortcut id that opens the emoji picker
+pub const EMOJI_PICKER: &str = "emoji-picker";
+/// Prefix of the quick paste shortcut ids, `penguinclip-paste-N`
+const PASTE_INDEX_PREFIX: &str = "penguinclip-paste-";
+
+/// What an activated shortcut asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// The primary or secondary clipboard history shortcut
+    ToggleHistory,
+    ToggleEmoji,
+    /// Quick paste of history item N (1-based)
+    PasteIndex(usize),
+}
+
+/// The action behind a shortcut id the portal reports as activated
+pub fn action(id: &str) -> Option<Action> {
+    match id {
+        CLIPBOARD_HISTORY | "penguinclip-alt" => Some(Action::ToggleHistory),
+        EMOJI_PICKER => Some(Action::ToggleEmoji),
+        _ => id
+            .strip_prefix(PASTE_INDEX_PREFIX)?
+            .parse()
+            .ok()
+            .map(Action::PasteIndex),
+    }
+}
+
+/// Set once the portal session has bound our shortcuts
+static PORTAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Makes every request and session token unique within this process
+static TOKEN_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Whether the portal session currently owns our shortcuts
+pub fn is_active() -> bool {
+    PORTAL_ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Whether the portal should be tried before editing the desktop's settings
+pub fn is_preferred() -> bool {
+    crate::environment::is_flatpak() || crate::session::is_wayland()
+}
+
+/// Whether the session bus has a portal implementing GlobalShortcuts
+pub async fn is_available() -> bool {
+    match Connection::session().await {
+        Ok(connection) => interface_version(&connection).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// The `version` property of the GlobalShortcuts interface
+async fn interface_version(
+    connection: &Connection,
+) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+    let reply: OwnedValue = connection
+        .call_method(
+            Some(PORTAL_DESTINATION),
+            PORTAL_PATH,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(GLOBAL_SHORTCUTS_INTERFACE, "version"),
+        )
+        .await?
+        .body()
+        .deserialize()?;
+    Ok(u32::try_from(reply)?)
+}
+
+/// The shortcuts we ask the portal for: (id, description, preferred
+/// trigger). The same set the desktop handlers register, so the secondary
+/// and quick paste shortcuts work without touching the desktop's settings.
+/// The first two keep the ids they were bound under before.
+fn portal_shortcuts(bindings: &HistoryBindings) -> Vec<(&'static str, &'static str, String)> {
+    all_shortcuts(bindings)
+        .into_iter()
+        .map(|config| {
+            let (id, description) = match config.id {
+                "penguinclip" => (CLIPBOARD_HISTORY, "Open clipboard history"),
+                "penguinclip-emoji" => (EMOJI_PICKER, "Open emoji picker"),
+                id => (id, config.name),
+            };
+            (id, description, config.binding.portal())
+        })
+        .collect()
+}
+
+fn next_token() -> String {
+    format!(
+        "penguinclip_{}_{}",
+        std::process::id(),
+        TOKEN_COUNTER.fetch_add(1, Ordering::SeqCst)
+    )
+}
+
+/// The object path the portal will use for the request made with `token`
+fn request_path(unique_name: &str, token: &str) -> String {
+    let sender = unique_name.trim_start_matches(':').replace('.', "_");
+    format!("{}/request/{}/{}", PORTAL_PATH, sender, token)
+}
+
+/// Calls a portal method that answers through a `Request::Response` signal
+/// and returns the response's results. We subscribe to the signal before
+/// calling, so a fast reply can't be missed.
+async fn portal_request<B>(
+    connection: &Connection,
+    method: &str,
+    body: &B,
+    token: &str,
+) -> Result<HashMap<String, OwnedValue>, Box<dyn std::error::Error + Send + Sync>>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    use futures_lite::stream::StreamExt;
+    use zbus::{MatchRule, MessageStream};
+
+    let unique_name = connection
+        .unique_name()
+        .ok_or("D-Bus connection has no unique name")?
+        .to_string();
+    let path = request_path(&unique_name, token);
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("org.freedesktop.portal.Request")?
+        .member("Response")?
+        .path(path.as_str())?
+        .build();
+    let mut responses = MessageStream::for_match_rule(rule, connection, None).await?;
+
+    connection
+        .call_method(
+            Some(PORTAL_DESTINATION),
+            PORTAL_PATH,
+            Some(GLOBAL_SHORTCUTS_INTERFACE),
+            method,
+            body,
+        )
+        .await?;
+
+    let message = responses
+        .next()
+        .await
+        .ok_or_else(|| format!("{} got no response", method))??;
+    let (response, results): (u32, HashMap<String, OwnedValue>) = message.body().deserialize()?;
+    match response {
+        0 => Ok(results),
+        1 => Err(format!("{} was cancelled by the user", method).into()),
+        _ => Err(format!("{} failed (response {})", method, response).into()),
+    }
+}
+
+/// Lets the portal know our app id when we aren't sandboxed, so the
+/// shortcuts are stored under it. Older portals lack the registry; that's
+/// fine, they fall back to an empty app id.
+async fn register_host_app(connection: &Connection) {
+    if crate::environment::is_flatpak() {
+        return;
+    }
+    let options: HashMap<&str, Value> = HashMap::new();
+    if let Err(e) = connection
+        .call_method(
+            Some(PORTAL_DESTINATION),
+            PORTAL_PATH,
+            Some("org.freedesktop.host.portal.Registry"),
+            "Register",
+            &("org.penguinclip.clipboard-history", options),
+        )
+        .await
+    {
+        log::debug!("portal host registry unavailable: {}", e);
+    }
+}
+
+/// Sends new bindings to the running portal session, see `rebind`
+static REBIND: Mutex<Option<UnboundedSender<HistoryBindings>>> = Mutex::new(None);
+
+/// Has the running portal session bind `bindings` instead, in a new
+/// session. False if no session is running.
+pub fn rebind(bindings: &HistoryBindings) -> bool {
+    REBIND
+        .lock()
+        .as_ref()
+        .is_some_and(|sender| sender.send(bindings.clone()).is_ok())
+}
+
+/// Creates a GlobalShortcuts session, binds every shortcut (clipboard
+/// history, emoji picker and quick paste), then calls `on_activated` with
+/// the shortcut id each time one fires. `rebind` replaces the session with
+/// one for the new bindings. Returns once the session ends, or with an
+/// error if the shortcuts could not be bound.
+pub async fn run<F>(
+    bindings: &HistoryBindings,
+    on_activated: F,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    F: Fn(&str) + Send + Sync + 'static,
+{
+    let (sender, mut rebinds) = tokio::sync::mpsc::unbounded_channel();
+    *REBIND.lock() = Some(sender);
+    let result = serve(bindings.clone(), &on_activated, &mut rebinds).await;
+    *REBIND.lock() = None;
+    PORTAL_ACTIVE.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn serve<F>(
+    mut bindings: HistoryBindings,
+    on_activated: &F,
+    rebinds: &mut UnboundedReceiver<HistoryBindings>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    F: Fn(&str),
+{
+    use futures_lite::stream::StreamExt;
+    use zbus::{MatchRule, MessageStream};
+
+    let connection = Connection::session().await?;
+    let version = interface_version(&connection).await?;
+    log::info!("using the GlobalShortcuts portal (version {})", version);
+    register_host_app(&connection).await;
+
+    // Subscribe before binding so a keypress right after the dialog counts
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .sender(PORTAL_DESTINATION)?
+        .interface(GLOBAL_SHORTCUTS_INTERFACE)?
+        .member("Activated")?
+        .build();
+    let mut activations = MessageStream::for_match_rule(rule, &connection, None).await?;
+
+    loop {
+        let session = bind_session(&connection, &bindings).await?;
+        PORTAL_ACTIVE.store(true, Ordering::SeqCst);
+        log::info!("shortcuts bound through the GlobalShortcuts portal");
+
+        loop {
+            tokio::select! {
+                message = activations.next() => {
+                    let Some(message) = message else {
+                        log::info!("GlobalShortcuts portal session ended");
+                        return Ok(());
+                    };
+                    let Ok(message) = message else { continue };
+                    // Activated(session_handle: o, shortcut_id: s, timestamp: t, options: a{sv})
+                    let Ok((handle, id, _timestamp, _options)) = message.body().deserialize::<(
+                        OwnedObjectPath,
+                        String,
+                        u64,
+                        HashMap<String, OwnedValue>,
+                    )>() else {
+                        continue;
+                    };
+                    if handle == session {
+                        on_activated(&id);
+                    }
+                }
+                new_bindings = rebinds.recv() => {
+                    let Some(new_bindings) = new_bindings else {
+                        return Ok(());
+                    };
+                    close_session(&connection, &session).await;
+                    PORTAL_ACTIVE.store(false, Ordering::SeqCst);
+                    bindings = new_bindings;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Creates a session and binds every shortcut of `bindings` in it
+async fn bind_session(
+    connection: &Connection,
+    bindings: &HistoryBindings,
+) -> Result<OwnedObjectPath, Box<dyn std::error::Error + Send + Sync>> {
+    // CreateSession(options: a{sv}) -> results with session_handle: s
+    let token = next_token();
+    let mut options: HashMap<&str, Value> = HashMap::new();
+    options.insert("handle_token", Value::from(token.as_str()));
+    options.insert("session_handle_token", Value::from(next_token()));
+    let results = portal_request(connection, "CreateSession", &(options,), &token).await?;
+    let session_handle: String = results
+        .get("session_handle")
+        .ok_or("CreateSession returned no session handle")?
+        .try_clone()?
+        .try_into()?;
+    let session = OwnedObjectPath::try_from(session_handle)?;
+
+    // BindShortcuts(session_handle: o, shortcuts: a(sa{sv}), parent_window: s, options: a{sv})
+    let shortcuts: Vec<(&str, HashMap<&str, Value>)> = portal_shortcuts(bindings)
+        .into_iter()
+        .map(|(id, description, trigger)| {
+            let mut properties: HashMap<&str, Value> = HashMap::new();
+            properties.insert("description", Value::from(description));
+            properties.insert("preferred_trigger", Value::from(trigger));
+            (id, properties)
+        })
+        .collect();
+    let token = next_token();
+    let mut options: HashMap<&str, Value> = HashMap::new();
+    options.insert("handle_token", Value::from(token.as_str()));
+    let session_path: ObjectPath = session.as_ref();
+    let bound = portal_request(
+        connection,
+        "BindShortcuts",
+        &(session_path, shortcuts, "", options),
+        &token,
+    )
+    .await;
+    if let Err(e) = bound {
+        close_session(connection, &session).await;
+        return Err(e);
+    }
+    Ok(session)
+}
+
+/// Ends a session, releasing its shortcuts
+async fn close_session(connection: &Connection, session: &OwnedObjectPath) {
+    if let Err(e) = connection
+        .call_method(
+            Some(PORTAL_DESTINATION),
+            session.as_str(),
+            Some("org.freedesktop.portal.Session"),
+            "Close",
+            &(),
+        )
+        .await
+    {
+        log::debug!("could not close the portal session: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_path_from_unique_name() {
+        assert_eq!(
+            request_path(":1.42", "penguinclip_7_0"),
+            "/org/freedesktop/portal/desktop/request/1_42/penguinclip_7_0"
+        );
+    }
+
+    #[test]
+    fn test_portal_shortcuts_use_configured_binding() {
+        let bindings = HistoryBindings::default();
+        let shortcuts = portal_shortcuts(&bindings);
+        assert_eq!(shortcuts[0].0, CLIPBOARD_HISTORY);
+        assert_eq!(shortcuts[0].2, "LOGO+v");
+        assert_eq!(shortcuts[1].0, "penguinclip-alt");
+        assert_eq!(shortcuts[1].2, "CTRL+ALT+v");
+        assert_eq!(shortcuts[2].0, EMOJI_PICKER);
+        assert_eq!(shortcuts[2].2, "LOGO+period");
+        assert_eq!(shortcuts.len(), 12);
+        assert_eq!(shortcuts[11].0, "penguinclip-paste-9");
+    }
+
+    #[test]
+    fn test_action_for_shortcut_ids() {
+        assert_eq!(action(CLIPBOARD_HISTORY), Some(Action::ToggleHistory));
+        assert_eq!(action("penguinclip-alt"), Some(Action::ToggleHistory));
+        assert_eq!(action(EMOJI_PICKER), Some(Action::ToggleEmoji));
+        assert_eq!(action("penguinclip-paste-3"), Some(Action::PasteIndex(3)));
+        assert_eq!(action("penguinclip-paste-x"), None);
+        assert_eq!(action("unknown"), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! History Encryption
+//! Optional passphrase-based encryption of the history file. The key is
+//! derived with Argon2id and the file contents are sealed with
+//! ChaCha20-Poly1305. Keys only ever live in memory.
+//!
+//! An encrypted file is a small JSON envelope, so a locked history can be
+//! recognized (and left untouched) without the passphrase.
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+
+/// Envelope format version written to disk.
+const ENVELOPE_VERSION: u32 = 1;
+
+const SALT_LEN: usize = 16;
+
+/// Error returned when the passphrase does not decrypt the history.
+pub const WRONG_PASSPHRASE: &str = "Wrong passphrase";
+
+/// On-disk form of an encrypted history.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    penguinclip_encrypted: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// A key derived from the user's passphrase, with the salt it was derived
+/// with (stored alongside the ciphertext).
+#[derive(Clone)]
+pub struct HistoryKey {
+    key: Key,
+    salt: [u8; SALT_LEN],
+}
+
+impl std::fmt::Debug for HistoryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HistoryKey(..)")
+    }
+}
+
+impl HistoryKey {
+    /// Derives a key for a new passphrase, with a fresh random salt.
+    pub fn new(passphrase: &str) -> Result<Self, String> {
+        if passphrase.is_empty() {
+            return Err("The passphrase cannot be empty".to_string());
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(passphrase, salt)
+    }
+
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self, String> {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| format!("Key derivation failed: {}", e))?;
+        Ok(Self { key, salt })
+    }
+
+    /// Seals `plaintext` into an envelope, with a fresh nonce per call.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let cipher = ChaCha20Poly1305::new(&self.key);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "Encryption failed".to_string())?;
+        let envelope = Envelope {
+            penguinclip_encrypted: ENVELOPE_VERSION,
+            salt: BASE64.encode(self.salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        serde_json::to_vec(&envelope).map_err(|e| e.to_string())
+    }
+
+    /// True if `content` is an envelope sealed with this key, rather than
+    /// plain data or an envelope from another passphrase.
+    pub fn sealed(&self, content: &[u8]) -> bool {
+        std::str::from_utf8(content)
+            .ok()
+            .and_then(|content| open_envelope(content).ok())
+            .is_some_and(|(salt, ..)| salt == self.salt)
+    }
+
+    /// Decrypts an envelope sealed with this key, such as an image blob.
+    pub fn open(&self, content: &[u8]) -> Result<Vec<u8>, String> {
+        let content =
+            std::str::from_utf8(content).map_err(|_| "Not an encrypted file".to_string())?;
+        let (_, nonce, ciphertext) = open_envelope(content)?;
+        ChaCha20Poly1305::new(&self.key)
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| WRONG_PASSPHRASE.to_string())
+    }
+}
+
+/// True if `content` is an encrypted history envelope.
+pub fn is_encrypted(content: &str) -> bool {
+    serde_json::from_str::<Envelope>(content).is_ok()
+}
+
+/// Decrypts an envelope with `passphrase`. Returns the plaintext and the
+/// derived key, so later saves reuse it without deriving again. Fails with
+/// `WRONG_PASSPHRASE` if the passphrase doesn't match.
+pub fn decrypt(content: &str, passphrase: &str) -> Result<(Vec<u8>, HistoryKey), String> {
+    let (salt, nonce, ciphertext) = open_envelope(content)?;
+    let key = HistoryKey::derive(passphrase, salt)?;
+    let plaintext = key.open(content.as_bytes())?;
+    Ok((plaintext, key))
+}
+
+/// The salt, nonce and ciphertext of an envelope.
+fn open_envelope(content: &str) -> Result<([u8; SALT_LEN], Vec<u8>, Vec<u8>), String> {
+    let envelope: Envelope = serde_json::from_str(content)
+        .map_err(|e| format!("Not an encrypted history file: {}", e))?;
+    if envelope.penguinclip_encrypted != ENVELOPE_VERSION {
+        return Err(format!(
+            "Unsupported encrypted history version {}",
+            envelope.penguinclip_encrypted
+        ));
+    }
+
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .map_err(|e| format!("Corrupt encrypted history: {}", e))
+    };
+    let salt: [u8; SALT_LEN] = decode(&envelope.salt)?
+        .try_into()
+        .map_err(|_| "Corrupt encrypted history: bad salt".to_string())?;
+    let nonce = decode(&envelope.nonce)?;
+    if nonce.len() != 12 {
+        return Err("Corrupt encrypted history: bad nonce".to_string());
+    }
+    let ciphertext = decode(&envelope.ciphertext)?;
+    Ok((salt, nonce, ciphertext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_wrong_passphrase() {
+        let key = HistoryKey::new("correct horse").unwrap();
+        let sealed = key.encrypt(b"[\"secret\"]").unwrap();
+        let sealed = String::from_utf8(sealed).unwrap();
+
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("secret"));
+        assert!(!is_encrypted("[]"));
+
+        let (plaintext, _) = decrypt(&sealed, "correct horse").unwrap();
+        assert_eq!(plaintext, b"[\"secret\"]");
+        assert_eq!(
+            decrypt(&sealed, "battery staple").unwrap_err(),
+            WRONG_PASSPHRASE
+        );
+    }
+
+    #[test]
+    fn test_each_save_uses_a_fresh_nonce() {
+        let key = HistoryKey::new("pass").unwrap();
+        assert_ne!(key.encrypt(b"same").unwrap(), key.encrypt(b"same").unwrap());
+        assert!(HistoryKey::new("").is_err());
+    }
+
+    #[test]
+    fn test_open_with_the_key_that_sealed_it() {
+        let key = HistoryKey::new("pass").unwrap();
+        let other = HistoryKey::new("pass").unwrap();
+        let sealed = key.encrypt(b"\x89PNG").unwrap();
+
+        assert!(key.sealed(&sealed));
+        assert!(!other.sealed(&sealed));
+        assert!(!key.sealed(b"\x89PNG"));
+        assert_eq!(key.open(&sealed).unwrap(), b"\x89PNG");
+        assert_eq!(other.open(&sealed).unwrap_err(), WRONG_PASSPHRASE);
+    }
+}
@@ -1,65 +1,1301 @@
+use crate::clipboard_backend::ArboardBackend;
 use crate::focus_manager;
 use crate::session;
+use crate::user_settings::AppPasteRule;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::ConnectionExt as XprotoConnectionExt;
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
 
-type PasteStrategy = (&'static str, fn(bool) -> Result<(), String>);
+/// A tool that can send the paste keystroke
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteTool {
+    Xdotool,
+    XTest,
+    Wtype,
+    Ydotool,
+    Uinput,
+}
 
-/// Delay before starting the paste sequence to ensure window focus is stable
-const PRE_PASTE_DELAY_MS: u64 = 50;
+impl PasteTool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Xdotool => "xdotool",
+            Self::XTest => "XTest",
+            Self::Wtype => "wtype",
+            Self::Ydotool => "ydotool",
+            Self::Uinput => "uinput",
+        }
+    }
 
-/// Delay between key events to ensure proper registration
-const KEY_EVENT_DELAY_MS: u64 = 50;
+    fn send(self, combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
+        match self {
+            Self::Xdotool => simulate_paste_xdotool(combo, timings),
+            Self::XTest => simulate_paste_xtest(combo, timings),
+            Self::Wtype => simulate_paste_wtype(combo, timings),
+            Self::Ydotool => simulate_paste_ydotool(combo, timings),
+            Self::Uinput => simulate_paste_uinput(combo, timings),
+        }
+    }
+}
+
+/// The paste tools in the order they are tried, on X11 or elsewhere. The
+/// permission check and the paste diagnostics report from this same list.
+pub fn paste_tools(x11: bool) -> &'static [PasteTool] {
+    if x11 {
+        &[PasteTool::Xdotool, PasteTool::XTest, PasteTool::Uinput]
+    } else {
+        &[PasteTool::Wtype, PasteTool::Ydotool, PasteTool::Uinput]
+    }
+}
 
 /// Delay after device creation for uinput to be recognized
 const UINPUT_DEVICE_SETTLE_MS: u64 = 100;
 
-/// Delay after paste sequence completes
-const POST_PASTE_DELAY_MS: u64 = 30;
+/// Evdev codes are offset from X keycodes by 8 (the XKB "evdev" rules).
+const EVDEV_KEYCODE_OFFSET: u8 = 8;
 
-pub fn simulate_paste_keystroke() -> Result<(), String> {
-    // Give window manager time to settle focus before sending keystrokes
-    thread::sleep(Duration::from_millis(PRE_PASTE_DELAY_MS));
+// X keysyms for the non-letter keys a paste combo can use
+const XK_CONTROL_L: u32 = 0xffe3;
+const XK_SHIFT_L: u32 = 0xffe1;
+const XK_ALT_L: u32 = 0xffe9;
+const XK_SUPER_L: u32 = 0xffeb;
+const XK_INSERT: u32 = 0xff63;
 
-    // Detect if focused window is a terminal — terminals need Ctrl+Shift+V
-    let use_shift = if session::is_x11() {
-        focus_manager::is_focused_window_terminal()
-    } else {
-        false // On Wayland we can't easily detect; wl-paste handles it differently
+/// Evdev codes of the letters a to z (KEY_A, KEY_B, ...)
+const EVDEV_LETTERS: [u16; 26] = [
+    30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47, 17, 45,
+    21, 44,
+];
+
+/// Delays around the simulated paste, from the `paste_timing` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasteTimings {
+    /// Before the first key, so window focus is stable
+    pub pre_delay_ms: u64,
+    /// Between key events, so each one registers
+    pub key_delay_ms: u64,
+    /// After the combo, so the target app can process it
+    pub post_delay_ms: u64,
+    /// How long to wait for focus to reach the restored window
+    pub focus_timeout_ms: u64,
+    /// Extra activation attempts when focus doesn't arrive in time
+    pub focus_retries: u32,
+}
+
+impl PasteTimings {
+    pub const DEFAULT: Self = Self {
+        pre_delay_ms: 50,
+        key_delay_ms: 50,
+        post_delay_ms: 30,
+        focus_timeout_ms: 300,
+        focus_retries: 1,
+    };
+
+    /// Clamps each delay to a sane range (slow VMs to snappy desktops).
+    pub fn clamped(self) -> Self {
+        Self {
+            pre_delay_ms: self.pre_delay_ms.min(2000),
+            key_delay_ms: self.key_delay_ms.clamp(1, 500),
+            post_delay_ms: self.post_delay_ms.min(2000),
+            focus_timeout_ms: self.focus_timeout_ms.clamp(50, 5000),
+            focus_retries: self.focus_retries.min(5),
+        }
+    }
+}
+
+impl Default for PasteTimings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A key that can appear in a paste combo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComboKey {
+    Ctrl,
+    Shift,
+    Alt,
+    Super,
+    Insert,
+    /// Lowercase ASCII letter
+    Letter(char),
+}
+
+impl ComboKey {
+    fn parse(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+        match lower.as_str() {
+            "ctrl" | "control" => Some(Self::Ctrl),
+            "shift" => Some(Self::Shift),
+            "alt" => Some(Self::Alt),
+            "super" | "meta" | "win" => Some(Self::Super),
+            "insert" | "ins" => Some(Self::Insert),
+            _ => {
+                let mut chars = lower.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_lowercase() => Some(Self::Letter(c)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn is_modifier(self) -> bool {
+        matches!(self, Self::Ctrl | Self::Shift | Self::Alt | Self::Super)
+    }
+
+    fn name(self) -> String {
+        match self {
+            Self::Ctrl => "Ctrl".to_string(),
+            Self::Shift => "Shift".to_string(),
+            Self::Alt => "Alt".to_string(),
+            Self::Super => "Super".to_string(),
+            Self::Insert => "Insert".to_string(),
+            Self::Letter(c) => c.to_ascii_uppercase().to_string(),
+        }
+    }
+
+    /// Key name as xdotool expects it.
+    fn xdotool_name(self) -> String {
+        match self {
+            Self::Ctrl => "ctrl".to_string(),
+            Self::Shift => "shift".to_string(),
+            Self::Alt => "alt".to_string(),
+            Self::Super => "super".to_string(),
+            Self::Insert => "Insert".to_string(),
+            Self::Letter(c) => c.to_string(),
+        }
+    }
+
+    /// Modifier name for `wtype -M`, or key name for `wtype -k`.
+    fn wtype_name(self) -> String {
+        match self {
+            Self::Super => "logo".to_string(),
+            _ => self.xdotool_name(),
+        }
+    }
+
+    /// Keysyms that identify this key in an X keyboard mapping.
+    fn keysyms(self) -> Vec<u32> {
+        match self {
+            Self::Ctrl => vec![XK_CONTROL_L],
+            Self::Shift => vec![XK_SHIFT_L],
+            Self::Alt => vec![XK_ALT_L],
+            Self::Super => vec![XK_SUPER_L],
+            Self::Insert => vec![XK_INSERT],
+            Self::Letter(c) => vec![c as u32, c.to_ascii_uppercase() as u32],
+        }
+    }
+
+    /// Evdev code of the key on a US QWERTY keyboard.
+    fn qwerty_evdev(self) -> u16 {
+        match self {
+            Self::Ctrl => 29,   // KEY_LEFTCTRL
+            Self::Shift => 42,  // KEY_LEFTSHIFT
+            Self::Alt => 56,    // KEY_LEFTALT
+            Self::Super => 125, // KEY_LEFTMETA
+            Self::Insert => 110,
+            Self::Letter(c) => EVDEV_LETTERS[(c as u8 - b'a') as usize],
+        }
+    }
+
+    /// X keycode of the key on a US QWERTY keyboard.
+    fn qwerty_keycode(self) -> u8 {
+        self.qwerty_evdev() as u8 + EVDEV_KEYCODE_OFFSET
+    }
+}
+
+/// The keystroke sent to paste: modifiers followed by one key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteCombo {
+    keys: Vec<ComboKey>,
+}
+
+impl PasteCombo {
+    /// Ctrl+V, or Ctrl+Shift+V for terminals.
+    pub fn ctrl_v(use_shift: bool) -> Self {
+        let mut keys = vec![ComboKey::Ctrl];
+        if use_shift {
+            keys.push(ComboKey::Shift);
+        }
+        keys.push(ComboKey::Letter('v'));
+        Self { keys }
+    }
+
+    /// Shift+Insert, the traditional X11 paste key.
+    pub fn shift_insert() -> Self {
+        Self {
+            keys: vec![ComboKey::Shift, ComboKey::Insert],
+        }
+    }
+
+    /// Parses a combo such as "Ctrl+V" or "Shift+Insert" (case-insensitive):
+    /// any modifiers (Ctrl, Shift, Alt, Super) then one letter or Insert.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keys = Vec::new();
+        for part in text.split('+').map(str::trim) {
+            let key = ComboKey::parse(part)
+                .ok_or_else(|| format!("Unknown key '{}' in paste combo '{}'", part, text))?;
+            if keys.contains(&key) {
+                return Err(format!("'{}' appears twice in paste combo", part));
+            }
+            keys.push(key);
+        }
+        match keys.split_last() {
+            Some((last, modifiers))
+                if !last.is_modifier() && modifiers.iter().all(|k| k.is_modifier()) =>
+            {
+                Ok(Self { keys })
+            }
+            _ => Err(format!(
+                "Paste combo '{}' must be modifiers followed by one key, like Ctrl+V",
+                text
+            )),
+        }
+    }
+
+    fn xdotool_combo(&self) -> String {
+        let names: Vec<String> = self.keys.iter().map(|k| k.xdotool_name()).collect();
+        names.join("+")
+    }
+}
+
+impl std::fmt::Display for PasteCombo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self.keys.iter().map(|k| k.name()).collect();
+        f.write_str(&names.join("+"))
+    }
+}
+
+/// How to paste into an app, from the `paste_method` and `app_paste_rules`
+/// settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteMethod {
+    /// Ctrl+Shift+V in terminals, Ctrl+V elsewhere
+    Auto,
+    CtrlV,
+    CtrlShiftV,
+    ShiftInsert,
+    /// Type the clipboard text out as keystrokes (see `type_text`)
+    Type,
+}
+
+impl PasteMethod {
+    /// Setting values, in the order the settings UI lists them.
+    pub const NAMES: [&'static str; 5] = ["auto", "ctrl-v", "ctrl-shift-v", "shift-insert", "type"];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "auto" => Ok(Self::Auto),
+            "ctrl-v" => Ok(Self::CtrlV),
+            "ctrl-shift-v" => Ok(Self::CtrlShiftV),
+            "shift-insert" => Ok(Self::ShiftInsert),
+            "type" => Ok(Self::Type),
+            _ => Err(format!(
+                "Unknown paste method '{}' (expected one of: {})",
+                name,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::CtrlV => "ctrl-v",
+            Self::CtrlShiftV => "ctrl-shift-v",
+            Self::ShiftInsert => "shift-insert",
+            Self::Type => "type",
+        }
+    }
+
+    /// What to do for a window with this (lowercased) WM_CLASS. `use_shift`
+    /// adds Shift to Ctrl+V.
+    fn action(self, wm_class: Option<&str>, use_shift: bool) -> PasteAction {
+        let terminal = wm_class.is_some_and(focus_manager::wm_class_is_terminal);
+        PasteAction::Keys(match self {
+            Self::Auto => PasteCombo::ctrl_v(use_shift || terminal),
+            Self::CtrlV => PasteCombo::ctrl_v(use_shift),
+            Self::CtrlShiftV => PasteCombo::ctrl_v(true),
+            Self::ShiftInsert => PasteCombo::shift_insert(),
+            Self::Type => return PasteAction::Type,
+        })
+    }
+}
+
+/// What a paste does in the target window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PasteAction {
+    Keys(PasteCombo),
+    Type,
+}
+
+/// A WM_CLASS pattern: a glob if it contains `*` or `?`, else a substring.
+/// Either is matched against the instance and class names separately.
+#[derive(Debug, Clone)]
+enum WmClassPattern {
+    Substring(String),
+    Glob(regex::Regex),
+}
+
+impl WmClassPattern {
+    fn compile(pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim().to_lowercase();
+        if pattern.is_empty() {
+            return Err("WM_CLASS pattern is empty".to_string());
+        }
+        if !pattern.contains(['*', '?']) {
+            return Ok(Self::Substring(pattern));
+        }
+        let mut regex = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        regex::Regex::new(&regex)
+            .map(Self::Glob)
+            .map_err(|e| format!("Invalid WM_CLASS pattern '{}': {}", pattern, e))
+    }
+
+    /// `wm_class` is lowercased `instance\0class\0`.
+    fn matches(&self, wm_class: &str) -> bool {
+        wm_class
+            .split('\0')
+            .filter(|name| !name.is_empty())
+            .any(|name| match self {
+                Self::Substring(s) => name.contains(s.as_str()),
+                Self::Glob(re) => re.is_match(name),
+            })
+    }
+}
+
+/// A compiled per-app paste rule.
+#[derive(Debug, Clone)]
+pub struct PasteRule {
+    /// The pattern as written in the settings
+    pub pattern: String,
+    pub method: PasteMethod,
+    pub use_shift: bool,
+    compiled: WmClassPattern,
+}
+
+impl PasteRule {
+    pub fn new(pattern: &str, method: &str, use_shift: bool) -> Result<Self, String> {
+        Ok(Self {
+            pattern: pattern.trim().to_lowercase(),
+            method: PasteMethod::parse(method)?,
+            use_shift,
+            compiled: WmClassPattern::compile(pattern)?,
+        })
+    }
+}
+
+/// Paste settings: timings and which keystroke to send where.
+#[derive(Debug, Clone)]
+pub struct PasteConfig {
+    pub timings: PasteTimings,
+    /// Per-app rules; the first match wins
+    pub rules: Vec<PasteRule>,
+    /// Custom keystroke for apps without a rule
+    pub combo: Option<PasteCombo>,
+    /// Method for apps without a rule when there's no custom keystroke
+    pub method: PasteMethod,
+    /// Delay between characters for the type-out method
+    pub type_char_delay_ms: u64,
+}
+
+impl PasteConfig {
+    pub const DEFAULT: Self = Self {
+        timings: PasteTimings::DEFAULT,
+        rules: Vec::new(),
+        combo: None,
+        method: PasteMethod::Auto,
+        type_char_delay_ms: 10,
     };
 
-    let combo = if use_shift { "Ctrl+Shift+V" } else { "Ctrl+V" };
-    eprintln!("[SimulatePaste] Sending {}...", combo);
+    /// Whether picking the action depends on the target window.
+    fn needs_window_class(&self) -> bool {
+        !self.rules.is_empty() || (self.combo.is_none() && self.method == PasteMethod::Auto)
+    }
+
+    /// The first rule matching a window with this (lowercased) WM_CLASS.
+    fn rule_for(&self, wm_class: Option<&str>) -> Option<(usize, &PasteRule)> {
+        let wm_class = wm_class?;
+        self.rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.compiled.matches(wm_class))
+    }
+
+    /// What to do in a window with this (lowercased) WM_CLASS.
+    fn action_for(&self, wm_class: Option<&str>) -> PasteAction {
+        match (self.rule_for(wm_class), &self.combo) {
+            (Some((_, rule)), _) => rule.method.action(wm_class, rule.use_shift),
+            (None, Some(combo)) => PasteAction::Keys(combo.clone()),
+            (None, None) => self.method.action(wm_class, false),
+        }
+    }
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
 
-    const X11_STRATEGIES: &[PasteStrategy] = &[
-        ("xdotool", simulate_paste_xdotool),
-        ("XTest", simulate_paste_xtest),
-        ("uinput", simulate_paste_uinput),
-    ];
+static PASTE_CONFIG: Mutex<PasteConfig> = Mutex::new(PasteConfig::DEFAULT);
 
-    const NON_X11_STRATEGIES: &[PasteStrategy] = &[("uinput", simulate_paste_uinput)];
+/// Applies the paste settings (see `UserSettings::paste_config`).
+pub fn set_paste_config(config: PasteConfig) {
+    *PASTE_CONFIG.lock() = PasteConfig {
+        timings: config.timings.clamped(),
+        ..config
+    };
+}
 
-    let strategies = if session::is_x11() {
-        X11_STRATEGIES
+/// The active paste timings.
+pub fn paste_timings() -> PasteTimings {
+    PASTE_CONFIG.lock().timings
+}
+
+/// The active delay between typed characters.
+pub fn type_char_delay() -> u64 {
+    PASTE_CONFIG.lock().type_char_delay_ms
+}
+
+/// Which rule applies to a WM_CLASS, and what pasting there does.
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteRuleMatch {
+    /// Index into `app_paste_rules` (0 for a rule passed in), `None` if no
+    /// rule matched
+    pub rule_index: Option<usize>,
+    pub pattern: Option<String>,
+    /// The method used (a `paste_method` value)
+    pub method: String,
+    /// The keystroke sent, `None` when the text is typed out
+    pub keystroke: Option<String>,
+}
+
+/// Reports which paste rule would apply to a window with `wm_class` (either
+/// one name or xprop's `"instance", "class"`), for the settings UI. With
+/// `rule`, that rule is checked instead of the saved ones, so it can be
+/// tried before it is saved.
+#[tauri::command]
+pub fn test_paste_rule(
+    wm_class: String,
+    rule: Option<AppPasteRule>,
+) -> Result<PasteRuleMatch, String> {
+    let wm_class: String = wm_class
+        .to_lowercase()
+        .split([',', '\0'])
+        .map(|name| name.trim().trim_matches('"'))
+        .filter(|name| !name.is_empty())
+        .map(|name| format!("{}\0", name))
+        .collect();
+    let wm_class = Some(wm_class.as_str()).filter(|class| !class.is_empty());
+
+    let mut config = PASTE_CONFIG.lock().clone();
+    if let Some(rule) = rule {
+        config.rules = vec![PasteRule::new(
+            &rule.wm_class_pattern,
+            &rule.method,
+            rule.use_shift,
+        )?];
+    }
+    let rule = config.rule_for(wm_class);
+    let method = match (rule, &config.combo) {
+        (Some((_, rule)), _) => rule.method.name().to_string(),
+        (None, Some(_)) => "custom".to_string(),
+        (None, None) => config.method.name().to_string(),
+    };
+    Ok(PasteRuleMatch {
+        rule_index: rule.map(|(index, _)| index),
+        pattern: rule.map(|(_, rule)| rule.pattern.clone()),
+        method,
+        keystroke: match config.action_for(wm_class) {
+            PasteAction::Keys(combo) => Some(combo.to_string()),
+            PasteAction::Type => None,
+        },
+    })
+}
+
+/// Sends the paste keystroke for the focused window, using the configured
+/// timings, per-app rules and keystroke. Apps set to type-out get the
+/// clipboard text typed instead.
+pub fn simulate_paste_keystroke() -> Result<(), String> {
+    let config = PASTE_CONFIG.lock().clone();
+
+    // Give window manager time to settle focus before sending keystrokes
+    thread::sleep(Duration::from_millis(config.timings.pre_delay_ms));
+
+    // On Wayland we can't easily detect the focused app; wl-paste handles it differently
+    let wm_class = if session::is_x11() && config.needs_window_class() {
+        focus_manager::active_window_class()
     } else {
-        NON_X11_STRATEGIES
+        None
     };
+    match config.action_for(wm_class.as_deref()) {
+        PasteAction::Keys(combo) => send_paste_combo(&combo, &config.timings),
+        PasteAction::Type => match crate::clipboard_manager::read_system_text(&mut ArboardBackend)
+            .and_then(|text| text.ok_or_else(|| "clipboard holds no text".to_string()))
+        {
+            Ok(text) => type_text(&text, config.type_char_delay_ms),
+            Err(e) => {
+                // Images can't be typed; paste them normally
+                log::debug!("No text to type ({}), pasting instead", e);
+                match PasteMethod::Auto.action(wm_class.as_deref(), false) {
+                    PasteAction::Keys(combo) => send_paste_combo(&combo, &config.timings),
+                    PasteAction::Type => unreachable!("auto always sends keys"),
+                }
+            }
+        },
+    }
+}
+
+/// Sends `combo` with the first strategy that works.
+fn send_paste_combo(combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
+    log::debug!("Sending {}...", combo);
 
-    for (name, func) in strategies {
-        match func(use_shift) {
+    let mut failures = Vec::new();
+    for tool in paste_tools(session::is_x11()) {
+        let name = tool.name();
+        match tool.send(combo, timings) {
             Ok(()) => {
-                eprintln!("[SimulatePaste] {} sent via {}", combo, name);
+                log::debug!("{} sent via {}", combo, name);
                 // Small delay after paste to let the target app process it
-                thread::sleep(Duration::from_millis(POST_PASTE_DELAY_MS));
+                thread::sleep(Duration::from_millis(timings.post_delay_ms));
                 return Ok(());
             }
             Err(err) => {
-                eprintln!("[SimulatePaste] {} failed: {}", name, err);
+                log::warn!("{} failed: {}", name, err);
+                failures.push(format!("{}: {}", name, err));
+            }
+        }
+    }
+
+    Err(format!(
+        "All paste methods failed ({})",
+        failures.join("; ")
+    ))
+}
+
+pub(crate) fn command_exists(cmd: &str) -> bool {
+    std::process::Command::new("which")
+        .arg(cmd)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// True if `xdotool` is installed.
+pub fn xdotool_available() -> bool {
+    command_exists("xdotool")
+}
+
+/// True if `wl-copy` and `wl-paste` are installed.
+pub fn wl_clipboard_available() -> bool {
+    command_exists("wl-copy") && command_exists("wl-paste")
+}
+
+/// True if `wtype` is installed and there's a Wayland display to type into.
+pub fn wtype_available() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some() && command_exists("wtype")
+}
+
+/// The socket `ydotool` talks to `ydotoold` through.
+fn ydotool_socket_path() -> std::path::PathBuf {
+    if let Some(path) = std::env::var_os("YDOTOOL_SOCKET") {
+        return path.into();
+    }
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").unwrap_or_else(|| "/tmp".into());
+    std::path::Path::new(&runtime_dir).join(".ydotool_socket")
+}
+
+/// Checks that `ydotool` is installed and `ydotoold` is listening on its
+/// socket (a stale socket file refuses connections).
+pub fn ydotool_status() -> Result<(), String> {
+    if !command_exists("ydotool") {
+        return Err("ydotool is not installed".to_string());
+    }
+    connect_ydotoold()
+}
+
+/// True if `ydotoold` accepts connections on its socket, whether or not the
+/// `ydotool` client is installed.
+pub fn ydotoold_running() -> bool {
+    connect_ydotoold().is_ok()
+}
+
+fn connect_ydotoold() -> Result<(), String> {
+    let socket = ydotool_socket_path();
+    let probe = std::os::unix::net::UnixDatagram::unbound()
+        .map_err(|e| format!("socket probe failed: {}", e))?;
+    probe.connect(&socket).map_err(|e| {
+        format!(
+            "ydotoold is not running or not accessible at {}: {}",
+            socket.display(),
+            e
+        )
+    })
+}
+
+/// The XTest extension version, e.g. "2.2".
+fn xtest_version() -> Result<String, String> {
+    use x11rb::protocol::xtest::ConnectionExt as XtestConnectionExt;
+
+    let (conn, _) = x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+    let reply = conn
+        .xtest_get_version(2, 1)
+        .map_err(|e| format!("XTest version query failed: {}", e))?
+        .reply()
+        .map_err(|e| format!("XTest version query failed: {}", e))?;
+    Ok(format!("{}.{}", reply.major_version, reply.minor_version))
+}
+
+/// True if an X server with the XTest extension is reachable.
+pub fn xtest_available() -> bool {
+    xtest_version().is_ok()
+}
+
+/// Everything that decides whether and how a paste keystroke is sent,
+/// gathered without sending any keys.
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteDiagnostics {
+    pub session: String,
+    /// Environment variable the session type was detected from
+    pub session_source: String,
+    pub focused_window: Option<focus_manager::FocusedWindowInfo>,
+    /// What a paste into the focused window would send
+    pub paste_action: String,
+    pub xdotool_available: bool,
+    pub xtest_version: Option<String>,
+    pub uinput_writable: bool,
+    pub wtype_available: bool,
+    pub ydotool_available: bool,
+    pub ydotool_error: Option<String>,
+    /// Usable strategies, in the order they are tried
+    pub strategies: Vec<String>,
+    pub timings: PasteTimings,
+    pub summary: String,
+}
+
+impl PasteDiagnostics {
+    fn build_summary(&self) -> String {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        let mut lines = vec![format!(
+            "Session: {} (from {})",
+            self.session, self.session_source
+        )];
+        match &self.focused_window {
+            Some(window) => {
+                lines.push(format!(
+                    "Focused window: 0x{:x} \"{}\"",
+                    window.id,
+                    window.title.as_deref().unwrap_or("?")
+                ));
+                lines.push(format!(
+                    "WM_CLASS: {} (terminal: {})",
+                    window.wm_class.as_deref().unwrap_or("unknown"),
+                    yes_no(window.is_terminal)
+                ));
             }
+            None => lines.push("Focused window: unknown (no X11 access)".to_string()),
         }
+        lines.push(format!("Paste sends: {}", self.paste_action));
+        lines.push(format!("xdotool: {}", yes_no(self.xdotool_available)));
+        lines.push(format!(
+            "XTest: {}",
+            self.xtest_version.as_deref().unwrap_or("unavailable")
+        ));
+        lines.push(format!(
+            "/dev/uinput writable: {}",
+            yes_no(self.uinput_writable)
+        ));
+        lines.push(format!("wtype: {}", yes_no(self.wtype_available)));
+        lines.push(match &self.ydotool_error {
+            Some(e) => format!("ydotool: no ({})", e),
+            None => "ydotool: yes".to_string(),
+        });
+        lines.push(if self.strategies.is_empty() {
+            "Strategies: none usable, pasting will fail".to_string()
+        } else {
+            format!("Strategies: {}", self.strategies.join(" → "))
+        });
+        lines.push(format!(
+            "Timings: {} ms before, {} ms between keys, {} ms after",
+            self.timings.pre_delay_ms, self.timings.key_delay_ms, self.timings.post_delay_ms
+        ));
+        lines.join("\n")
     }
+}
+
+/// Reports session, focused window, available paste strategies and
+/// timings without sending any keys.
+pub fn diagnose_paste() -> PasteDiagnostics {
+    let config = PASTE_CONFIG.lock().clone();
+    let session = session::get_session_type();
+    let x11 = session == session::SessionType::X11;
 
-    Err("All paste methods failed".to_string())
+    let focused_window = focus_manager::focused_window_info();
+    let wm_class = if x11 {
+        focus_manager::active_window_class()
+    } else {
+        None
+    };
+    let paste_action = match config.action_for(wm_class.as_deref()) {
+        PasteAction::Keys(combo) => combo.to_string(),
+        PasteAction::Type => "type the text out".to_string(),
+    };
+
+    let xdotool_available = xdotool_available();
+    let xtest_version = xtest_version().ok();
+    let uinput_writable = crate::permission_checker::uinput_accessible();
+    let wtype_available = wtype_available();
+    let ydotool_error = ydotool_status().err();
+
+    let strategies = paste_tools(x11)
+        .iter()
+        .filter(|tool| match tool {
+            PasteTool::Xdotool => xdotool_available,
+            PasteTool::XTest => xtest_version.is_some(),
+            PasteTool::Wtype => wtype_available,
+            PasteTool::Ydotool => ydotool_error.is_none(),
+            PasteTool::Uinput => uinput_writable,
+        })
+        .map(|tool| tool.name().to_string())
+        .collect();
+
+    let mut report = PasteDiagnostics {
+        session: session.name().to_string(),
+        session_source: session::detection_source().to_string(),
+        focused_window,
+        paste_action,
+        xdotool_available,
+        xtest_version,
+        uinput_writable,
+        wtype_available,
+        ydotool_available: ydotool_error.is_none(),
+        ydotool_error,
+        strategies,
+        timings: config.timings,
+        summary: String::new(),
+    };
+    report.summary = report.build_summary();
+    report
+}
+
+/// Simulate the paste combo with `wtype` (virtual-keyboard protocol;
+/// wlroots compositors and KDE, not GNOME)
+fn simulate_paste_wtype(combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
+    if !wtype_available() {
+        return Err("wtype is not installed".to_string());
+    }
+
+    let (key, modifiers) = combo.keys.split_last().ok_or("empty combo")?;
+    let mut command = std::process::Command::new("wtype");
+    command.arg("-d").arg(timings.key_delay_ms.to_string());
+    for modifier in modifiers {
+        command.arg("-M").arg(modifier.wtype_name());
+    }
+    command.arg("-k").arg(key.wtype_name());
+    for modifier in modifiers.iter().rev() {
+        command.arg("-m").arg(modifier.wtype_name());
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run wtype: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("wtype failed: {}", stderr.trim()))
+    }
+}
+
+/// Simulate the paste combo with `ydotool`, which injects evdev codes
+/// through the `ydotoold` daemon's uinput device
+fn simulate_paste_ydotool(combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
+    ydotool_status()?;
+
+    // "code:1" presses, "code:0" releases; modifiers first, released last
+    let codes = combo_evdev_codes(combo);
+    let presses = codes.iter().map(|code| format!("{}:1", code));
+    let releases = codes.iter().rev().map(|code| format!("{}:0", code));
+
+    let output = std::process::Command::new("ydotool")
+        .arg("key")
+        .arg("--key-delay")
+        .arg(timings.key_delay_ms.to_string())
+        .args(presses.chain(releases))
+        .output()
+        .map_err(|e| format!("Failed to run ydotool: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("ydotool failed: {}", stderr.trim()))
+    }
+}
+
+/// Keycode → keysyms table as returned by `GetKeyboardMapping`.
+#[derive(Debug, Clone)]
+struct KeyboardMapping {
+    min_keycode: u8,
+    keysyms_per_keycode: u8,
+    keysyms: Vec<u32>,
+}
+
+impl KeyboardMapping {
+    /// Finds the keycode producing any of `keysyms`, preferring the lowest
+    /// column (unshifted, first group) so no extra modifier is needed.
+    fn keycode_for(&self, keysyms: &[u32]) -> Option<u8> {
+        self.find(keysyms, self.keysyms_per_keycode as usize)
+            .map(|(keycode, _)| keycode)
+    }
+
+    /// Like `keycode_for`, searching only the first `columns` columns and
+    /// also returning the column found.
+    fn find(&self, keysyms: &[u32], columns: usize) -> Option<(u8, usize)> {
+        let per = self.keysyms_per_keycode as usize;
+        if per == 0 {
+            return None;
+        }
+        let keycodes = self.keysyms.len() / per;
+        (0..columns.min(per)).find_map(|column| {
+            (0..keycodes).find_map(|index| {
+                let sym = self.keysyms[index * per + column];
+                keysyms
+                    .contains(&sym)
+                    .then(|| self.min_keycode.checked_add(index as u8))
+                    .flatten()
+                    .map(|keycode| (keycode, column))
+            })
+        })
+    }
+
+    /// The highest keycode with no keysyms, free to bind temporarily.
+    fn spare_keycode(&self) -> Option<u8> {
+        let per = self.keysyms_per_keycode as usize;
+        if per == 0 {
+            return None;
+        }
+        self.keysyms
+            .chunks(per)
+            .rposition(|syms| syms.iter().all(|&sym| sym == 0))
+            .and_then(|index| self.min_keycode.checked_add(index as u8))
+    }
+
+    /// The X keycode for `key` on this layout, or its QWERTY keycode if the
+    /// layout lacks the keysym.
+    fn keycode(&self, key: ComboKey) -> u8 {
+        self.keycode_for(&key.keysyms())
+            .unwrap_or_else(|| key.qwerty_keycode())
+    }
+}
+
+/// The current keyboard mapping and the connection whose MappingNotify
+/// events invalidate it (X sends MappingNotify to every client).
+struct KeymapCache {
+    conn: RustConnection,
+    mapping: Option<KeyboardMapping>,
+}
+
+static KEYMAP_CACHE: Mutex<Option<KeymapCache>> = Mutex::new(None);
+
+fn query_keyboard_mapping(conn: &RustConnection) -> Result<KeyboardMapping, String> {
+    let setup = conn.setup();
+    let (min, max) = (setup.min_keycode, setup.max_keycode);
+    let reply = conn
+        .get_keyboard_mapping(min, max - min + 1)
+        .map_err(|e| format!("GetKeyboardMapping failed: {}", e))?
+        .reply()
+        .map_err(|e| format!("GetKeyboardMapping failed: {}", e))?;
+    Ok(KeyboardMapping {
+        min_keycode: min,
+        keysyms_per_keycode: reply.keysyms_per_keycode,
+        keysyms: reply.keysyms,
+    })
+}
+
+/// The X keyboard mapping, cached until the layout changes.
+fn keyboard_mapping() -> Result<KeyboardMapping, String> {
+    let mut guard = KEYMAP_CACHE.lock();
+    if guard.is_none() {
+        let (conn, _) = x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+        *guard = Some(KeymapCache {
+            conn,
+            mapping: None,
+        });
+    }
+    let cache = guard.as_mut().expect("cache was just filled");
+
+    loop {
+        match cache.conn.poll_for_event() {
+            Ok(Some(Event::MappingNotify(_))) => cache.mapping = None,
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(e) => {
+                // Connection lost (e.g. X restarted); reconnect next time.
+                *guard = None;
+                return Err(format!("X11 event poll failed: {}", e));
+            }
+        }
+    }
+
+    if let Some(mapping) = &cache.mapping {
+        return Ok(mapping.clone());
+    }
+    let mapping = query_keyboard_mapping(&cache.conn)?;
+    cache.mapping = Some(mapping.clone());
+    Ok(mapping)
+}
+
+/// X keycodes for `combo` on the current layout, QWERTY if it can't be read.
+fn combo_keycodes(combo: &PasteCombo) -> Vec<u8> {
+    match keyboard_mapping() {
+        Ok(mapping) => combo.keys.iter().map(|&k| mapping.keycode(k)).collect(),
+        Err(e) => {
+            log::debug!("Using QWERTY keycodes: {}", e);
+            combo.keys.iter().map(|k| k.qwerty_keycode()).collect()
+        }
+    }
+}
+
+/// Longest text `type_text` will type; longer text is refused so a huge
+/// item can't keep typing for minutes
+pub const MAX_TYPED_CHARS: usize = 5000;
+
+const XK_RETURN: u32 = 0xff0d;
+const XK_TAB: u32 = 0xff09;
+
+/// Keysym typing `c`: Latin-1 characters are their own keysym, everything
+/// else uses the Unicode keysym range. Newlines become Return.
+fn char_keysym(c: char) -> Option<u32> {
+    match c {
+        '\n' | '\r' => Some(XK_RETURN),
+        '\t' => Some(XK_TAB),
+        c if c.is_control() => None,
+        ' '..='~' | '\u{a0}'..='\u{ff}' => Some(c as u32),
+        c => Some(0x0100_0000 + c as u32),
+    }
+}
+
+/// `text` as it is typed, with CRLF as one newline. Errors on text longer
+/// than `MAX_TYPED_CHARS` characters.
+fn typed_text(text: &str) -> Result<String, String> {
+    let text = text.replace("\r\n", "\n");
+    let count = text.chars().count();
+    if count > MAX_TYPED_CHARS {
+        return Err(format!(
+            "Text is too long to type ({} characters, limit {})",
+            count, MAX_TYPED_CHARS
+        ));
+    }
+    Ok(text)
+}
+
+/// The keysyms to type `text`, one per character. Control characters other
+/// than newlines and tabs are skipped.
+fn text_keysyms(text: &str) -> Vec<u32> {
+    text.chars().filter_map(char_keysym).collect()
+}
+
+/// Types `text` as keystrokes instead of pasting it, for targets that
+/// ignore Ctrl+V (VNC viewers, some Java apps, paste-blocking fields).
+/// Uses XTest on X11 and `wtype` on Wayland.
+pub fn type_text(text: &str, char_delay_ms: u64) -> Result<(), String> {
+    let text = typed_text(text)?;
+    let keysyms = text_keysyms(&text);
+    log::debug!("Typing {} characters...", keysyms.len());
+
+    if session::is_x11() {
+        type_keysyms_xtest(&keysyms, char_delay_ms)
+    } else if wtype_available() {
+        type_text_wtype(&text, char_delay_ms)
+    } else {
+        Err("Typing text on Wayland requires wtype".to_string())
+    }
+}
+
+/// Types keysyms with XTest. Keysyms missing from the layout (or only on
+/// AltGr levels) are bound to a spare keycode for the keystroke, and the
+/// keycode is cleared again afterwards.
+fn type_keysyms_xtest(keysyms: &[u32], char_delay_ms: u64) -> Result<(), String> {
+    use x11rb::protocol::xtest::ConnectionExt as XtestConnectionExt;
+    use x11rb::wrapper::ConnectionExt as WrapperConnectionExt;
+
+    let (conn, screen_num) =
+        x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+    let root_window = conn.setup().roots[screen_num].root;
+    conn.xtest_get_version(2, 1)
+        .map_err(|e| format!("XTest version query failed: {}", e))?
+        .reply()
+        .map_err(|e| format!("XTest version query failed: {}", e))?;
+
+    let mapping = query_keyboard_mapping(&conn)?;
+    let shift = mapping.keycode(ComboKey::Shift);
+    let spare = mapping.spare_keycode();
+    let mut bound: Option<u32> = None;
+
+    let mut type_all = || -> Result<(), String> {
+        for &keysym in keysyms {
+            let (keycode, shifted) = match mapping.find(&[keysym], 2) {
+                Some((keycode, column)) => (keycode, column == 1),
+                None => {
+                    let keycode = spare.ok_or("No free keycode to type unmapped characters")?;
+                    if bound != Some(keysym) {
+                        conn.change_keyboard_mapping(1, keycode, 1, &[keysym])
+                            .map_err(|e| format!("ChangeKeyboardMapping failed: {}", e))?;
+                        conn.sync()
+                            .map_err(|e| format!("Sync after remap failed: {}", e))?;
+                        bound = Some(keysym);
+                    }
+                    (keycode, false)
+                }
+            };
+
+            let mut sink = XtestSink {
+                conn: &conn,
+                root_window,
+            };
+            let keys = if shifted {
+                vec![shift, keycode]
+            } else {
+                vec![keycode]
+            };
+            press_and_release(&mut sink, &keys, 0).map_err(|e| e.to_string())?;
+            thread::sleep(Duration::from_millis(char_delay_ms));
+        }
+        Ok(())
+    };
+    let result = type_all();
+
+    if let (Some(keycode), Some(_)) = (spare, bound) {
+        // Let the target read the last key before its keysym disappears
+        thread::sleep(Duration::from_millis(char_delay_ms.max(10)));
+        let _ = conn.change_keyboard_mapping(1, keycode, 1, &[0]);
+        let _ = conn.sync();
+    }
+    result
+}
+
+/// Types text with `wtype`, which handles any Unicode character itself
+fn type_text_wtype(text: &str, char_delay_ms: u64) -> Result<(), String> {
+    let output = std::process::Command::new("wtype")
+        .arg("-d")
+        .arg(char_delay_ms.to_string())
+        .arg("--")
+        .arg(text)
+        .output()
+        .map_err(|e| format!("Failed to run wtype: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("wtype failed: {}", stderr.trim()))
+    }
+}
+
+/// Evdev codes for `combo`: from the X layout when an X server is reachable
+/// (X11 or XWayland), else the QWERTY positions. See `simulate_paste_uinput`.
+fn combo_evdev_codes(combo: &PasteCombo) -> Vec<u16> {
+    match keyboard_mapping() {
+        Ok(mapping) => combo
+            .keys
+            .iter()
+            .map(|&k| u16::from(mapping.keycode(k).saturating_sub(EVDEV_KEYCODE_OFFSET)))
+            .collect(),
+        Err(_) => combo.keys.iter().map(|k| k.qwerty_evdev()).collect(),
+    }
+}
+
+/// Sends single key events for a paste strategy.
+trait KeySink {
+    type Key: Copy;
+    fn send_key(&mut self, key: Self::Key, pressed: bool) -> std::io::Result<()>;
+}
+
+/// Keys pressed through a sink and not yet released. Dropping the guard
+/// releases them (best-effort, newest first), so an error or panic midway
+/// through a combo never leaves Ctrl logically held.
+struct HeldKeys<'a, S: KeySink> {
+    sink: &'a mut S,
+    held: Vec<S::Key>,
+}
+
+impl<S: KeySink> Drop for HeldKeys<'_, S> {
+    fn drop(&mut self) {
+        while let Some(key) = self.held.pop() {
+            if let Err(e) = self.sink.send_key(key, false) {
+                log::warn!("Failed to release held key: {}", e);
+            }
+        }
+    }
+}
+
+/// Presses `keys` in order and releases them in reverse, pausing
+/// `delay_ms` after each event. Keys still held on error are released.
+fn press_and_release<S: KeySink>(
+    sink: &mut S,
+    keys: &[S::Key],
+    delay_ms: u64,
+) -> std::io::Result<()> {
+    let mut guard = HeldKeys {
+        sink,
+        held: Vec::new(),
+    };
+    for &key in keys {
+        // Tracked before sending: a failed press may still have gone out,
+        // and releasing a key that isn't down is harmless
+        guard.held.push(key);
+        guard.sink.send_key(key, true)?;
+        thread::sleep(Duration::from_millis(delay_ms));
+    }
+    while let Some(&key) = guard.held.last() {
+        guard.sink.send_key(key, false)?;
+        guard.held.pop();
+        thread::sleep(Duration::from_millis(delay_ms));
+    }
+    Ok(())
+}
+
+/// Key events as XTest fake input, by X keycode
+struct XtestSink<'c, C> {
+    conn: &'c C,
+    root_window: u32,
+}
+
+impl<C> KeySink for XtestSink<'_, C>
+where
+    C: x11rb::connection::Connection + x11rb::protocol::xtest::ConnectionExt,
+{
+    type Key = u8;
+
+    fn send_key(&mut self, keycode: u8, pressed: bool) -> std::io::Result<()> {
+        use x11rb::wrapper::ConnectionExt as WrapperConnectionExt;
+
+        const KEY_PRESS: u8 = 2;
+        const KEY_RELEASE: u8 = 3;
+
+        let (key_type, action) = if pressed {
+            (KEY_PRESS, "press")
+        } else {
+            (KEY_RELEASE, "release")
+        };
+        fake_key(
+            self.conn,
+            key_type,
+            keycode,
+            self.root_window,
+            &format!("Failed to {} keycode {}", action, keycode),
+        )
+        .map_err(std::io::Error::other)?;
+        self.conn.sync().map_err(|e| {
+            std::io::Error::other(format!("Sync after {} {} failed: {}", action, keycode, e))
+        })
+    }
+}
+
+/// Key events as evdev input events (followed by SYN_REPORT), by evdev code
+struct EvdevWriter<W>(W);
+
+impl<W: std::io::Write> KeySink for EvdevWriter<W> {
+    type Key = u16;
+
+    fn send_key(&mut self, code: u16, pressed: bool) -> std::io::Result<()> {
+        fn make_event(type_: u16, code: u16, value: i32) -> [u8; 24] {
+            let mut event = [0u8; 24];
+            event[16..18].copy_from_slice(&type_.to_ne_bytes());
+            event[18..20].copy_from_slice(&code.to_ne_bytes());
+            event[20..24].copy_from_slice(&value.to_ne_bytes());
+            event
+        }
+
+        self.0
+            .write_all(&make_event(EV_KEY, code, i32::from(pressed)))?;
+        self.0.write_all(&make_event(EV_SYN, SYN_REPORT, 0))?;
+        self.0.flush()
+    }
+}
+
+/// Modifier keysyms a failed paste could leave held: Control, Shift, Alt
+/// and Super (left and right), and AltGr
+const MODIFIER_KEYSYMS: [u32; 9] = [
+    0xffe3, 0xffe4, 0xffe1, 0xffe2, 0xffe9, 0xffea, 0xffeb, 0xffec, 0xfe03,
+];
+
+/// Sends a release for every modifier key, for when a paste left one
+/// logically held. Uses XTest on X11, else releases whatever our virtual
+/// uinput keyboard still holds. The kernel ignores key-ups from a device
+/// that never pressed the key, so nothing else can be released reliably.
+#[tauri::command]
+pub fn release_all_modifiers() -> Result<(), String> {
+    let mut failures = Vec::new();
+    if session::is_x11() {
+        match release_modifiers_xtest() {
+            Ok(()) => return Ok(()),
+            Err(e) => failures.push(format!("XTest: {}", e)),
+        }
+    }
+    match release_modifiers_uinput() {
+        Ok(()) => return Ok(()),
+        Err(e) => failures.push(format!("uinput: {}", e)),
+    }
+    Err(format!(
+        "Could not release modifier keys ({})",
+        failures.join("; ")
+    ))
+}
+
+fn release_modifiers_xtest() -> Result<(), String> {
+    let (conn, screen_num) =
+        x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
+    let root_window = conn.setup().roots[screen_num].root;
+    let mapping = query_keyboard_mapping(&conn)?;
+    let mut keycodes: Vec<u8> = MODIFIER_KEYSYMS
+        .iter()
+        .filter_map(|&sym| mapping.keycode_for(&[sym]))
+        .collect();
+    keycodes.sort_unstable();
+    keycodes.dedup();
+
+    let mut sink = XtestSink {
+        conn: &conn,
+        root_window,
+    };
+    for keycode in keycodes {
+        sink.send_key(keycode, false).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Releases the keys the persistent virtual keyboard still holds
+fn release_modifiers_uinput() -> Result<(), String> {
+    let mut guard = UINPUT_DEVICE.lock();
+    let device = guard
+        .as_mut()
+        .ok_or("no virtual keyboard has pressed any keys")?;
+    let held: Vec<u16> = device.held.clone();
+    if held.is_empty() {
+        return Err("the virtual keyboard holds no keys".to_string());
+    }
+    for code in held.into_iter().rev() {
+        device
+            .send_key(code, false)
+            .map_err(|e| format!("uinput write failed: {}", e))?;
+    }
+    Ok(())
 }
 
 /// Helper for XTest input generation
@@ -76,15 +1312,12 @@ fn fake_key<C: x11rb::connection::Connection + x11rb::protocol::xtest::Connectio
     Ok(())
 }
 
-/// Simulate Ctrl+V (or Ctrl+Shift+V for terminals) using X11 XTest extension
-fn simulate_paste_xtest(use_shift: bool) -> Result<(), String> {
-    use x11rb::connection::Connection;
+/// Simulate the paste combo using X11 XTest extension
+fn simulate_paste_xtest(combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
     use x11rb::protocol::xtest::ConnectionExt as XtestConnectionExt;
     use x11rb::wrapper::ConnectionExt as WrapperConnectionExt; // Imported for sync()
 
-    const CTRL_L_KEYCODE: u8 = 37;
-    const SHIFT_L_KEYCODE: u8 = 50;
-    const V_KEYCODE: u8 = 55;
+    let keycodes = combo_keycodes(combo);
 
     let (conn, screen_num) =
         x11rb::connect(None).map_err(|e| format!("X11 connect failed: {}", e))?;
@@ -99,88 +1332,27 @@ fn simulate_paste_xtest(use_shift: bool) -> Result<(), String> {
     conn.sync()
         .map_err(|e| format!("Sync setup failed: {}", e))?;
 
-    // Press Ctrl
-    fake_key(
-        &conn,
-        2,
-        CTRL_L_KEYCODE,
+    let mut sink = XtestSink {
+        conn: &conn,
         root_window,
-        "Failed to press Ctrl",
-    )?;
-    conn.sync()
-        .map_err(|e| format!("Sync after Ctrl press failed: {}", e))?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Press Shift (if terminal)
-    if use_shift {
-        fake_key(
-            &conn,
-            2,
-            SHIFT_L_KEYCODE,
-            root_window,
-            "Failed to press Shift",
-        )?;
-        conn.sync()
-            .map_err(|e| format!("Sync after Shift press failed: {}", e))?;
-        thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-    }
-
-    // Press V
-    fake_key(&conn, 2, V_KEYCODE, root_window, "Failed to press V")?;
-    conn.sync()
-        .map_err(|e| format!("Sync after V press failed: {}", e))?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release V
-    fake_key(&conn, 3, V_KEYCODE, root_window, "Failed to release V")?;
-    conn.sync()
-        .map_err(|e| format!("Sync after V release failed: {}", e))?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release Shift (if terminal)
-    if use_shift {
-        fake_key(
-            &conn,
-            3,
-            SHIFT_L_KEYCODE,
-            root_window,
-            "Failed to release Shift",
-        )?;
-        conn.sync()
-            .map_err(|e| format!("Sync after Shift release failed: {}", e))?;
-        thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-    }
-
-    // Release Ctrl
-    fake_key(
-        &conn,
-        3,
-        CTRL_L_KEYCODE,
-        root_window,
-        "Failed to release Ctrl",
-    )?;
-    conn.sync()
-        .map_err(|e| format!("Final sync failed: {}", e))?;
-    Ok(())
+    };
+    press_and_release(&mut sink, &keycodes, timings.key_delay_ms).map_err(|e| e.to_string())
 }
 
-/// Simulate Ctrl+V (or Ctrl+Shift+V for terminals) using xdotool
-fn simulate_paste_xdotool(use_shift: bool) -> Result<(), String> {
-    let key_combo = if use_shift { "ctrl+shift+v" } else { "ctrl+v" };
+/// Simulate the paste combo using xdotool
+fn simulate_paste_xdotool(combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
+    let key_combo = combo.xdotool_combo();
 
     let output = std::process::Command::new("xdotool")
         .args(["key", "--delay"])
-        .arg(KEY_EVENT_DELAY_MS.to_string())
+        .arg(timings.key_delay_ms.to_string())
         .arg("--clearmodifiers")
-        .arg(key_combo)
+        .arg(&key_combo)
         .output()
         .map_err(|e| format!("Failed to run xdotool key: {}", e))?;
 
     if output.status.success() {
-        eprintln!(
-            "[SimulatePaste] xdotool sent {} to focused window",
-            key_combo
-        );
+        log::debug!("xdotool sent {} to focused window", key_combo);
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -188,159 +1360,532 @@ fn simulate_paste_xdotool(use_shift: bool) -> Result<(), String> {
     }
 }
 
-fn simulate_paste_uinput(use_shift: bool) -> Result<(), String> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-    use std::os::unix::io::AsRawFd;
+/// Simulate the paste combo through a virtual uinput keyboard.
+///
+/// uinput sends evdev codes, i.e. physical key positions that the compositor
+/// runs through the user's layout, so `KEY_V` types whatever letter sits on
+/// the QWERTY V key (on Dvorak that's "k"). When an X server is reachable
+/// (X11 or XWayland) the codes come from its layout instead: evdev code =
+/// X keycode - 8. Pure Wayland sessions keep the QWERTY positions.
+fn simulate_paste_uinput(combo: &PasteCombo, timings: &PasteTimings) -> Result<(), String> {
+    let codes = combo_evdev_codes(combo);
+    let started = Instant::now();
+
+    let mut guard = UINPUT_DEVICE.lock();
+    let created = guard.is_none();
+    if created {
+        *guard = Some(UinputDevice::create()?);
+    }
+    let device = guard.as_mut().expect("device was just created");
+
+    let result = match device.send_combo(&codes, timings.key_delay_ms) {
+        // The device node vanished (e.g. after suspend); make a new one
+        Err(e) if e.raw_os_error() == Some(libc::ENODEV) => {
+            log::warn!("uinput device went away, recreating it");
+            *guard = None;
+            let device = guard.insert(UinputDevice::create()?);
+            device.send_combo(&codes, timings.key_delay_ms)
+        }
+        result => result,
+    };
+    result.map_err(|e| format!("uinput write failed: {}", e))?;
+
+    log::debug!(
+        "uinput sent in {} ms ({} device)",
+        started.elapsed().as_millis(),
+        if created { "new" } else { "reused" }
+    );
+    Ok(())
+}
+
+/// The virtual keyboard, created on first use and kept until exit so
+/// pastes skip the device settle delay.
+static UINPUT_DEVICE: Mutex<Option<UinputDevice>> = Mutex::new(None);
+
+/// Destroys the virtual keyboard (on app exit).
+pub fn destroy_uinput_device() {
+    UINPUT_DEVICE.lock().take();
+}
+
+/// A virtual keyboard on /dev/uinput, destroyed when dropped.
+struct UinputDevice {
+    file: std::fs::File,
+    /// Keys pressed through this device and not released since, oldest first
+    held: Vec<u16>,
+}
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const SYN_REPORT: u16 = 0x00;
+
+/// Highest key code the virtual keyboard declares (KEY_MICMUTE), covering
+/// every key a layout-mapped combo can land on
+const UINPUT_MAX_KEY: u16 = 248;
+
+const UI_SET_EVBIT: libc::c_ulong = 0x40045564;
+const UI_SET_KEYBIT: libc::c_ulong = 0x40045565;
+const UI_DEV_SETUP: libc::c_ulong = 0x405c5503;
+const UI_DEV_CREATE: libc::c_ulong = 0x5501;
+const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
 
-    const EV_SYN: u16 = 0x00;
-    const EV_KEY: u16 = 0x01;
-    const SYN_REPORT: u16 = 0x00;
-    const KEY_LEFTCTRL: u16 = 29;
-    const KEY_LEFTSHIFT: u16 = 42;
-    const KEY_V: u16 = 47;
+impl UinputDevice {
+    fn create() -> Result<Self, String> {
+        use std::fs::OpenOptions;
+        use std::os::unix::io::AsRawFd;
 
-    fn make_event(type_: u16, code: u16, value: i32) -> [u8; 24] {
-        let mut event = [0u8; 24];
-        event[16..18].copy_from_slice(&type_.to_ne_bytes());
-        event[18..20].copy_from_slice(&code.to_ne_bytes());
-        event[20..24].copy_from_slice(&value.to_ne_bytes());
-        event
+        let started = Instant::now();
+        let file = OpenOptions::new()
+            .write(true)
+            .open("/dev/uinput")
+            .map_err(|e| format!("Failed to open /dev/uinput: {}", e))?;
+        let fd = file.as_raw_fd();
+
+        unsafe {
+            if libc::ioctl(fd, UI_SET_EVBIT, EV_KEY as libc::c_int) < 0 {
+                return Err("Failed to set EV_KEY".to_string());
+            }
+            for code in 1..=UINPUT_MAX_KEY {
+                if libc::ioctl(fd, UI_SET_KEYBIT, code as libc::c_int) < 0 {
+                    return Err(format!("Failed to set key bit {}", code));
+                }
+            }
+
+            #[repr(C)]
+            struct UinputSetup {
+                id: [u16; 4],
+                name: [u8; 80],
+                ff_effects_max: u32,
+            }
+
+            let mut setup = UinputSetup {
+                id: [0x03, 0x1234, 0x5678, 0x0001],
+                name: [0; 80],
+                ff_effects_max: 0,
+            };
+            let name = b"penguinclip-paste-helper";
+            setup.name[..name.len()].copy_from_slice(name);
+
+            if libc::ioctl(fd, UI_DEV_SETUP, &setup) < 0 {
+                return Err("Failed to setup uinput device".to_string());
+            }
+            if libc::ioctl(fd, UI_DEV_CREATE) < 0 {
+                return Err("Failed to create uinput device".to_string());
+            }
+        }
+
+        // Wait for the virtual device to be recognized by the system
+        thread::sleep(Duration::from_millis(UINPUT_DEVICE_SETTLE_MS));
+        log::debug!(
+            "uinput device created in {} ms",
+            started.elapsed().as_millis()
+        );
+        Ok(Self {
+            file,
+            held: Vec::new(),
+        })
     }
 
-    let mut uinput = OpenOptions::new()
-        .write(true)
-        .open("/dev/uinput")
-        .map_err(|e| format!("Failed to open /dev/uinput: {}", e))?;
+    /// Presses `codes` in order and releases them in reverse.
+    fn send_combo(&mut self, codes: &[u16], key_delay_ms: u64) -> std::io::Result<()> {
+        press_and_release(self, codes, key_delay_ms)
+    }
+}
 
-    const UI_SET_EVBIT: libc::c_ulong = 0x40045564;
-    const UI_SET_KEYBIT: libc::c_ulong = 0x40045565;
-    const UI_DEV_SETUP: libc::c_ulong = 0x405c5503;
-    const UI_DEV_CREATE: libc::c_ulong = 0x5501;
-    const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
+impl KeySink for UinputDevice {
+    type Key = u16;
 
-    unsafe {
-        if libc::ioctl(uinput.as_raw_fd(), UI_SET_EVBIT, EV_KEY as libc::c_int) < 0 {
-            return Err("Failed to set EV_KEY".to_string());
+    /// Sends through the device and keeps `held` up to date, so a key whose
+    /// release failed can still be let go later
+    fn send_key(&mut self, code: u16, pressed: bool) -> std::io::Result<()> {
+        if pressed && !self.held.contains(&code) {
+            // Tracked first: a failed press may still have gone out
+            self.held.push(code);
         }
-        if libc::ioctl(
-            uinput.as_raw_fd(),
-            UI_SET_KEYBIT,
-            KEY_LEFTCTRL as libc::c_int,
-        ) < 0
-        {
-            return Err("Failed to set KEY_LEFTCTRL".to_string());
+        EvdevWriter(&mut self.file).send_key(code, pressed)?;
+        if !pressed {
+            self.held.retain(|&held| held != code);
         }
-        if libc::ioctl(
-            uinput.as_raw_fd(),
-            UI_SET_KEYBIT,
-            KEY_LEFTSHIFT as libc::c_int,
-        ) < 0
-        {
-            return Err("Failed to set KEY_LEFTSHIFT".to_string());
+        Ok(())
+    }
+}
+
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        use std::os::unix::io::AsRawFd;
+
+        unsafe {
+            libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY);
         }
-        if libc::ioctl(uinput.as_raw_fd(), UI_SET_KEYBIT, KEY_V as libc::c_int) < 0 {
-            return Err("Failed to set KEY_V".to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XK_LOWER_V: u32 = 0x0076;
+    const XK_UPPER_V: u32 = 0x0056;
+
+    /// Two keysyms per keycode starting at keycode 8, from (keycode, syms).
+    fn mapping(keys: &[(u8, [u32; 2])]) -> KeyboardMapping {
+        let mut keysyms = vec![0; 2 * 248];
+        for (keycode, syms) in keys {
+            let index = (*keycode as usize - 8) * 2;
+            keysyms[index..index + 2].copy_from_slice(syms);
         }
+        KeyboardMapping {
+            min_keycode: 8,
+            keysyms_per_keycode: 2,
+            keysyms,
+        }
+    }
 
-        #[repr(C)]
-        struct UinputSetup {
-            id: [u16; 4],
-            name: [u8; 80],
-            ff_effects_max: u32,
+    fn keycodes(mapping: &KeyboardMapping, combo: &PasteCombo) -> Vec<u8> {
+        combo.keys.iter().map(|&k| mapping.keycode(k)).collect()
+    }
+
+    #[test]
+    fn test_keycodes_follow_the_layout() {
+        let ctrl_shift_v = PasteCombo::ctrl_v(true);
+
+        // QWERTY
+        let qwerty = mapping(&[
+            (37, [XK_CONTROL_L, 0]),
+            (50, [XK_SHIFT_L, 0]),
+            (55, [XK_LOWER_V, XK_UPPER_V]),
+        ]);
+        assert_eq!(keycodes(&qwerty, &ctrl_shift_v), [37, 50, 55]);
+
+        // Dvorak puts v on the QWERTY "." key; a remapped Ctrl is found too
+        let dvorak = mapping(&[
+            (66, [XK_CONTROL_L, 0]),
+            (50, [XK_SHIFT_L, 0]),
+            (55, [0x006b, 0x004b]), // k K
+            (60, [XK_LOWER_V, XK_UPPER_V]),
+        ]);
+        assert_eq!(keycodes(&dvorak, &ctrl_shift_v), [66, 50, 60]);
+    }
+
+    #[test]
+    fn test_keycode_lookup_prefers_unshifted_and_falls_back() {
+        // "V" only reachable shifted on keycode 30, unshifted "v" on 40
+        let map = mapping(&[(30, [0x0031, XK_UPPER_V]), (40, [XK_LOWER_V, 0])]);
+        assert_eq!(map.keycode_for(&[XK_LOWER_V, XK_UPPER_V]), Some(40));
+
+        // A layout without v (e.g. only Cyrillic) keeps the QWERTY keycode
+        let cyrillic = mapping(&[(55, [0x06cd, 0x06ed])]);
+        assert_eq!(cyrillic.keycode(ComboKey::Letter('v')), 55);
+    }
+
+    #[test]
+    fn test_parse_paste_combo() {
+        let combo = PasteCombo::parse(" shift + INSERT ").unwrap();
+        assert_eq!(combo.to_string(), "Shift+Insert");
+        assert_eq!(combo.xdotool_combo(), "shift+Insert");
+        assert_eq!(
+            PasteCombo::parse("Ctrl+V").unwrap(),
+            PasteCombo::ctrl_v(false)
+        );
+        assert_eq!(
+            PasteCombo::parse("control+shift+v").unwrap(),
+            PasteCombo::ctrl_v(true)
+        );
+
+        for bad in [
+            "",
+            "Ctrl",
+            "V+Ctrl",
+            "Ctrl+V+B",
+            "Ctrl+Ctrl+V",
+            "Ctrl+F13",
+            "Ctrl+é",
+        ] {
+            assert!(PasteCombo::parse(bad).is_err(), "{}", bad);
         }
+    }
 
-        let mut setup = UinputSetup {
-            id: [0x03, 0x1234, 0x5678, 0x0001],
-            name: [0; 80],
-            ff_effects_max: 0,
+    fn keys(action: PasteAction) -> String {
+        match action {
+            PasteAction::Keys(combo) => combo.to_string(),
+            PasteAction::Type => "type".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_paste_rules_pick_the_action() {
+        let config = PasteConfig {
+            rules: vec![
+                PasteRule::new("URxvt", "shift-insert", false).unwrap(),
+                PasteRule::new("org.*.remmina", "type", false).unwrap(),
+                PasteRule::new("discord", "ctrl-v", false).unwrap(),
+                PasteRule::new("kitty", "ctrl-v", true).unwrap(),
+            ],
+            ..PasteConfig::DEFAULT
         };
-        let name = b"penguinclip-paste-helper";
-        setup.name[..name.len()].copy_from_slice(name);
-
-        if libc::ioctl(uinput.as_raw_fd(), UI_DEV_SETUP, &setup) < 0 {
-            return Err("Failed to setup uinput device".to_string());
-        }
-        if libc::ioctl(uinput.as_raw_fd(), UI_DEV_CREATE) < 0 {
-            return Err("Failed to create uinput device".to_string());
-        }
-    }
-
-    // Wait for the virtual device to be recognized by the system
-    thread::sleep(Duration::from_millis(UINPUT_DEVICE_SETTLE_MS));
-
-    // Press Ctrl
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_LEFTCTRL, 1))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Press Shift (if terminal)
-    if use_shift {
-        uinput
-            .write_all(&make_event(EV_KEY, KEY_LEFTSHIFT, 1))
-            .map_err(|e| e.to_string())?;
-        uinput
-            .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-            .map_err(|e| e.to_string())?;
-        uinput.flush().map_err(|e| e.to_string())?;
-        thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-    }
-
-    // Press V
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_V, 1))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release V
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_V, 0))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    // Release Shift (if terminal)
-    if use_shift {
-        uinput
-            .write_all(&make_event(EV_KEY, KEY_LEFTSHIFT, 0))
-            .map_err(|e| e.to_string())?;
-        uinput
-            .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-            .map_err(|e| e.to_string())?;
-        uinput.flush().map_err(|e| e.to_string())?;
-        thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-    }
-
-    // Release Ctrl
-    uinput
-        .write_all(&make_event(EV_KEY, KEY_LEFTCTRL, 0))
-        .map_err(|e| e.to_string())?;
-    uinput
-        .write_all(&make_event(EV_SYN, SYN_REPORT, 0))
-        .map_err(|e| e.to_string())?;
-    uinput.flush().map_err(|e| e.to_string())?;
-
-    // Wait for events to be processed before destroying device
-    thread::sleep(Duration::from_millis(KEY_EVENT_DELAY_MS));
-
-    unsafe {
-        libc::ioctl(uinput.as_raw_fd(), UI_DEV_DESTROY);
-    }
-
-    // Small delay after device destruction
-    thread::sleep(Duration::from_millis(POST_PASTE_DELAY_MS));
+        let action = |class: &str| keys(config.action_for(Some(class)));
+        assert_eq!(action("urxvt\0urxvt\0"), "Shift+Insert");
+        assert_eq!(action("org.remmina.remmina\0org.remmina.remmina\0"), "type");
+        // Substring match
+        assert_eq!(action("discord\0discordcanary\0"), "Ctrl+V");
+        assert_eq!(action("kitty\0kitty\0"), "Ctrl+Shift+V");
+        // Globs match a whole name
+        assert_eq!(action("remmina\0org.remmina\0"), "Ctrl+V");
+        // No rule: auto detects terminals
+        assert_eq!(action("xterm\0xterm\0"), "Ctrl+Shift+V");
+        assert_eq!(keys(config.action_for(None)), "Ctrl+V");
 
-    Ok(())
+        // A custom keystroke applies where no rule does
+        let config = PasteConfig {
+            combo: Some(PasteCombo::parse("Alt+V").unwrap()),
+            ..config
+        };
+        assert_eq!(keys(config.action_for(Some("xterm\0xterm\0"))), "Alt+V");
+        assert_eq!(
+            keys(config.action_for(Some("urxvt\0urxvt\0"))),
+            "Shift+Insert"
+        );
+    }
+
+    #[test]
+    fn test_paste_rules_are_validated() {
+        assert!(PasteRule::new("xterm", "shift+insert", false).is_err());
+        assert!(PasteRule::new("  ", "ctrl-v", false).is_err());
+        // Glob metacharacters other than * and ? are literal
+        let rule = PasteRule::new("a.b[c]*", "ctrl-v", false).unwrap();
+        assert!(rule.compiled.matches("a.b[c]d\0"));
+        assert!(!rule.compiled.matches("axb[c]d\0"));
+    }
+
+    #[test]
+    fn test_paste_rule_checks_a_rule_passed_in() {
+        let rule = AppPasteRule {
+            wm_class_pattern: "urxvt".to_string(),
+            method: "shift-insert".to_string(),
+            use_shift: false,
+        };
+        let found =
+            test_paste_rule("\"urxvt\", \"URxvt\"".to_string(), Some(rule.clone())).unwrap();
+        assert_eq!(found.rule_index, Some(0));
+        assert_eq!(found.keystroke.as_deref(), Some("Shift+Insert"));
+
+        let other = test_paste_rule("xterm".to_string(), Some(rule.clone())).unwrap();
+        assert_eq!(other.rule_index, None);
+
+        let invalid = AppPasteRule {
+            method: "middle-click".to_string(),
+            ..rule
+        };
+        assert!(test_paste_rule("urxvt".to_string(), Some(invalid)).is_err());
+    }
+
+    #[test]
+    fn test_text_keysyms() {
+        assert_eq!(
+            text_keysyms(&typed_text("a\r\nb\n\tZ").unwrap()),
+            [0x61, XK_RETURN, 0x62, XK_RETURN, XK_TAB, 0x5a]
+        );
+        // Latin-1 keeps its code point, the rest uses Unicode keysyms
+        assert_eq!(text_keysyms("é€😀"), [0xe9, 0x0100_20ac, 0x0101_f600]);
+        // Other control characters are dropped
+        assert_eq!(text_keysyms("a\u{7}\u{1b}b"), [0x61, 0x62]);
+
+        // Long text is refused rather than cut off
+        let at_limit = "é".repeat(MAX_TYPED_CHARS);
+        assert_eq!(typed_text(&at_limit).unwrap(), at_limit);
+        assert!(typed_text(&"x".repeat(MAX_TYPED_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_typing_lookup_and_spare_keycode() {
+        let map = mapping(&[(38, [0x61, 0x41]), (24, [0x71, 0x51])]);
+        assert_eq!(map.find(&[0x41], 2), Some((38, 1)));
+        assert_eq!(map.find(&[0xe9], 2), None);
+        assert_eq!(map.spare_keycode(), Some(255));
+
+        let full = mapping(&[(255, [0x61, 0]), (254, [0x62, 0])]);
+        assert_eq!(full.spare_keycode(), Some(253));
+    }
+
+    #[test]
+    fn test_paste_diagnostics_summary() {
+        let mut report = PasteDiagnostics {
+            session: "X11".to_string(),
+            session_source: "XDG_SESSION_TYPE".to_string(),
+            focused_window: Some(focus_manager::FocusedWindowInfo {
+                id: 0x3a00007,
+                title: Some("~/src".to_string()),
+                wm_class: Some("kitty, kitty".to_string()),
+                is_terminal: true,
+            }),
+            paste_action: "Ctrl+Shift+V".to_string(),
+            xdotool_available: false,
+            xtest_version: Some("2.2".to_string()),
+            uinput_writable: false,
+            wtype_available: false,
+            ydotool_available: false,
+            ydotool_error: Some("ydotool is not installed".to_string()),
+            strategies: vec!["XTest".to_string()],
+            timings: PasteTimings::DEFAULT,
+            summary: String::new(),
+        };
+        let summary = report.build_summary();
+        for line in [
+            "Session: X11 (from XDG_SESSION_TYPE)",
+            "Focused window: 0x3a00007 \"~/src\"",
+            "WM_CLASS: kitty, kitty (terminal: yes)",
+            "Paste sends: Ctrl+Shift+V",
+            "ydotool: no (ydotool is not installed)",
+            "Strategies: XTest",
+            "Timings: 50 ms before, 50 ms between keys, 30 ms after",
+        ] {
+            assert!(summary.lines().any(|l| l == line), "missing {:?}", line);
+        }
+
+        report.strategies.clear();
+        report.focused_window = None;
+        let summary = report.build_summary();
+        assert!(summary.contains("Focused window: unknown"));
+        assert!(summary.contains("none usable"));
+    }
+
+    /// Records the evdev key events written through it, failing the
+    /// `fail_at`-th write (once).
+    struct FlakyWriter {
+        writes: usize,
+        fail_at: usize,
+        keys: Vec<(u16, i32)>,
+    }
+
+    impl std::io::Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            if self.writes == self.fail_at {
+                return Err(std::io::Error::from_raw_os_error(libc::ENODEV));
+            }
+            let type_ = u16::from_ne_bytes([buf[16], buf[17]]);
+            if type_ == EV_KEY {
+                let code = u16::from_ne_bytes([buf[18], buf[19]]);
+                let value = i32::from_ne_bytes([buf[20], buf[21], buf[22], buf[23]]);
+                self.keys.push((code, value));
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_failed_combo_releases_held_keys() {
+        // Ctrl, Shift, V: the V press (5th write, after two key+SYN pairs) fails
+        let mut writer = EvdevWriter(FlakyWriter {
+            writes: 0,
+            fail_at: 5,
+            keys: Vec::new(),
+        });
+        let err = press_and_release(&mut writer, &[29, 42, 47], 0).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENODEV));
+        assert_eq!(writer.0.keys, [(29, 1), (42, 1), (47, 0), (42, 0), (29, 0)]);
+
+        // A failed release is retried by the guard
+        let mut writer = EvdevWriter(FlakyWriter {
+            writes: 0,
+            fail_at: 7,
+            keys: Vec::new(),
+        });
+        assert!(press_and_release(&mut writer, &[29, 47], 0).is_err());
+        assert_eq!(writer.0.keys, [(29, 1), (47, 1), (47, 0), (29, 0)]);
+    }
+
+    #[test]
+    fn test_uinput_device_tracks_held_keys() {
+        let mut device = UinputDevice {
+            file: std::fs::OpenOptions::new()
+                .write(true)
+                .open("/dev/null")
+                .unwrap(),
+            held: Vec::new(),
+        };
+        device.send_key(29, true).unwrap();
+        device.send_key(47, true).unwrap();
+        device.send_key(29, true).unwrap();
+        assert_eq!(device.held, [29, 47]);
+        device.send_key(47, false).unwrap();
+        assert_eq!(device.held, [29]);
+        device.send_combo(&[42, 47], 0).unwrap();
+        assert_eq!(device.held, [29]);
+    }
+
+    #[test]
+    fn test_panicking_sink_releases_held_keys() {
+        struct PanickySink(Vec<(u8, bool)>);
+
+        impl KeySink for PanickySink {
+            type Key = u8;
+
+            fn send_key(&mut self, key: u8, pressed: bool) -> std::io::Result<()> {
+                if key == 55 && pressed {
+                    panic!("connection lost");
+                }
+                self.0.push((key, pressed));
+                Ok(())
+            }
+        }
+
+        let mut sink = PanickySink(Vec::new());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            press_and_release(&mut sink, &[37, 50, 55], 0)
+        }));
+        assert!(result.is_err());
+        assert_eq!(
+            sink.0,
+            [
+                (37, true),
+                (50, true),
+                (55, false),
+                (50, false),
+                (37, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_qwerty_codes() {
+        assert_eq!(ComboKey::Letter('v').qwerty_evdev(), 47);
+        assert_eq!(ComboKey::Letter('v').qwerty_keycode(), 55);
+        assert_eq!(ComboKey::Ctrl.qwerty_keycode(), 37);
+        assert_eq!(ComboKey::Shift.qwerty_keycode(), 50);
+        assert_eq!(ComboKey::Insert.qwerty_keycode(), 118);
+    }
+
+    #[test]
+    fn test_saved_timings_without_focus_fields_use_defaults() {
+        let timings: PasteTimings = serde_json::from_str(r#"{"pre_delay_ms": 80}"#).unwrap();
+        assert_eq!(timings.pre_delay_ms, 80);
+        assert_eq!(timings.focus_timeout_ms, 300);
+        assert_eq!(timings.focus_retries, 1);
+    }
+
+    #[test]
+    fn test_paste_timings_are_clamped() {
+        let timings = PasteTimings {
+            pre_delay_ms: 60_000,
+            key_delay_ms: 0,
+            post_delay_ms: 10,
+            focus_timeout_ms: 0,
+            focus_retries: 99,
+        }
+        .clamped();
+        assert_eq!(
+            timings,
+            PasteTimings {
+                pre_delay_ms: 2000,
+                key_delay_ms: 1,
+                post_delay_ms: 10,
+                focus_timeout_ms: 50,
+                focus_retries: 5,
+            }
+        );
+    }
 }
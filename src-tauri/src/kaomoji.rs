@@ -0,0 +1,162 @@
+//! Kaomoji
+//! The built-in kaomoji list (shared with the frontend) merged with the
+//! user's custom kaomoji from `UserSettings`, plus validation for adding
+//! custom entries.
+
+use crate::user_settings::CustomKaomoji;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Built-in kaomoji, the same file the picker bundles.
+const BUILTIN_DATA: &str = include_str!("../../src/data/kaomojis.json");
+
+/// Longest custom kaomoji accepted, in characters.
+pub const MAX_KAOMOJI_CHARS: usize = 128;
+
+/// Category given to custom kaomoji.
+const CUSTOM_CATEGORY: &str = "Custom";
+
+/// A kaomoji in search results.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Kaomoji {
+    pub text: String,
+    pub category: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// True for the user's own entries
+    #[serde(default)]
+    pub custom: bool,
+}
+
+fn builtin() -> &'static [Kaomoji] {
+    static BUILTIN: OnceLock<Vec<Kaomoji>> = OnceLock::new();
+    BUILTIN.get_or_init(|| {
+        serde_json::from_str(BUILTIN_DATA).unwrap_or_else(|e| {
+            log::warn!("failed to parse built-in kaomoji: {}", e);
+            Vec::new()
+        })
+    })
+}
+
+/// Trims `text` and checks it is non-empty and at most
+/// `MAX_KAOMOJI_CHARS` long.
+fn validate(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("A kaomoji cannot be empty".to_string());
+    }
+    if text.chars().count() > MAX_KAOMOJI_CHARS {
+        return Err(format!(
+            "A kaomoji can be at most {} characters",
+            MAX_KAOMOJI_CHARS
+        ));
+    }
+    Ok(text.to_string())
+}
+
+/// Adds a custom kaomoji. Fails if the text is invalid or already in the
+/// custom or built-in list.
+pub fn add_custom(
+    list: &mut Vec<CustomKaomoji>,
+    text: &str,
+    keywords: Vec<String>,
+) -> Result<(), String> {
+    let text = validate(text)?;
+    if list.iter().any(|k| k.text == text) || builtin().iter().any(|k| k.text == text) {
+        return Err(format!("'{}' is already in the kaomoji list", text));
+    }
+    let mut keywords: Vec<String> = keywords
+        .iter()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if keywords.is_empty() {
+        keywords.push("custom".to_string());
+    }
+    list.push(CustomKaomoji {
+        text,
+        category: CUSTOM_CATEGORY.to_string(),
+        keywords,
+    });
+    Ok(())
+}
+
+/// Removes the custom kaomoji with this text.
+pub fn remove_custom(list: &mut Vec<CustomKaomoji>, text: &str) -> Result<(), String> {
+    let text = text.trim();
+    let pos = list
+        .iter()
+        .position(|k| k.text == text)
+        .ok_or_else(|| format!("'{}' is not one of your kaomoji", text))?;
+    list.remove(pos);
+    Ok(())
+}
+
+/// Custom entries followed by the built-in list, filtered by `query` against
+/// text, keywords and category (case-insensitive). An empty query returns
+/// everything.
+pub fn search(query: &str, custom: &[CustomKaomoji]) -> Vec<Kaomoji> {
+    let term = query.trim().to_lowercase();
+    let matches = |text: &str, category: &str, keywords: &[String]| {
+        term.is_empty()
+            || text.to_lowercase().contains(&term)
+            || category.to_lowercase().contains(&term)
+            || keywords.iter().any(|k| k.to_lowercase().contains(&term))
+    };
+
+    let custom = custom
+        .iter()
+        .filter(|k| matches(&k.text, &k.category, &k.keywords))
+        .map(|k| Kaomoji {
+            text: k.text.clone(),
+            category: k.category.clone(),
+            keywords: k.keywords.clone(),
+            custom: true,
+        });
+    let builtin = builtin()
+        .iter()
+        .filter(|k| matches(&k.text, &k.category, &k.keywords))
+        .cloned();
+    custom.chain(builtin).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_validates_and_rejects_duplicates() {
+        let mut list = Vec::new();
+        add_custom(&mut list, "  (ノ°▽°)ノ ", vec!["Party".into()]).unwrap();
+        assert_eq!(list[0].text, "(ノ°▽°)ノ");
+        assert_eq!(list[0].keywords, ["party"]);
+
+        assert!(add_custom(&mut list, "(ノ°▽°)ノ", Vec::new()).is_err());
+        assert!(
+            add_custom(&mut list, "(^_^)", Vec::new()).is_err(),
+            "built-in"
+        );
+        assert!(add_custom(&mut list, "   ", Vec::new()).is_err());
+        assert!(add_custom(&mut list, &"ω".repeat(MAX_KAOMOJI_CHARS + 1), Vec::new()).is_err());
+        add_custom(&mut list, &"ω".repeat(MAX_KAOMOJI_CHARS), Vec::new()).unwrap();
+        assert_eq!(list[1].keywords, ["custom"]);
+
+        remove_custom(&mut list, "(ノ°▽°)ノ").unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(remove_custom(&mut list, "(ノ°▽°)ノ").is_err());
+    }
+
+    #[test]
+    fn test_search_merges_custom_first() {
+        let mut list = Vec::new();
+        add_custom(&mut list, "(ᵔ◡ᵔ)ノ✧", vec!["happy".into()]).unwrap();
+
+        let results = search("HAPPY", &list);
+        assert_eq!(results[0].text, "(ᵔ◡ᵔ)ノ✧");
+        assert!(results[0].custom);
+        assert!(results.iter().any(|k| k.text == "(^_^)" && !k.custom));
+
+        assert_eq!(search("", &list).len(), builtin().len() + 1);
+        assert!(search("no such kaomoji", &list).is_empty());
+    }
+}
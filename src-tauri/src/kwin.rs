@@ -0,0 +1,243 @@
+//! KWin Scripting Client
+//! KWin keeps window focus to itself on Plasma Wayland, but its scripting
+//! interface (`org.kde.KWin /Scripting`) runs JavaScript inside the
+//! compositor. A short script reads the active window's internal id and
+//! calls back over D-Bus; another activates the window with that id.
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+const KWIN_NAME: &str = "org.kde.KWin";
+const REPLY_PATH: &str = "/org/penguinclip/KWinReply";
+
+/// How long to wait for the capture script to call back
+const REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The active window, as `callDBus` arguments. Plasma 5 names it
+/// `activeClient`.
+const CAPTURE_SCRIPT: &str = r#"const w = workspace.activeWindow || workspace.activeClient;
+callDBus("{destination}", "{path}", "org.penguinclip.KWinReply", "Window",
+    w ? w.internalId.toString() : "");
+"#;
+
+const ACTIVATE_SCRIPT: &str = r#"const windows = workspace.windowList ? workspace.windowList() : workspace.clientList();
+const w = windows.find((w) => w.internalId.toString() === "{id}");
+if (w) {
+    if ("activeWindow" in workspace) workspace.activeWindow = w;
+    else workspace.activeClient = w;
+}
+"#;
+
+/// Where the capture script's callback lands
+struct Reply(Sender<String>);
+
+#[zbus::interface(name = "org.penguinclip.KWinReply")]
+impl Reply {
+    fn window(&self, id: String) {
+        let _ = self.0.send(id);
+    }
+}
+
+/// Whether KWin is on the session bus and answers `supportInformation`,
+/// which it only does with scripting compiled in. Probed once.
+pub fn is_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let probed = on_bus_thread(|| {
+            let connection = zbus::blocking::Connection::session()?;
+            connection
+                .call_method(
+                    Some(KWIN_NAME),
+                    "/KWin",
+                    Some("org.kde.KWin"),
+                    "supportInformation",
+                    &(),
+                )?
+                .body()
+                .deserialize::<String>()
+        });
+        match probed {
+            Ok(info) => !info.is_empty(),
+            Err(e) => {
+                log::debug!("KWin scripting unavailable: {}", e);
+                false
+            }
+        }
+    })
+}
+
+/// Runs blocking zbus calls on their own thread: zbus's blocking API drives
+/// a runtime of its own, which panics on a tokio worker.
+fn on_bus_thread<T: Send>(f: impl FnOnce() -> zbus::Result<T> + Send) -> Result<T, String> {
+    thread::scope(|scope| {
+        scope
+            .spawn(f)
+            .join()
+            .map_err(|_| "KWin call panicked".to_string())?
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// KWin's internal window ids are braced UUIDs. Checked before an id is
+/// pasted into a script.
+fn is_window_id(id: &str) -> bool {
+    id.strip_prefix('{')
+        .and_then(|id| id.strip_suffix('}'))
+        .is_some_and(|uuid| {
+            uuid.len() == 36 && uuid.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        })
+}
+
+/// Calls `method` on KWin's script loader
+fn scripting<B>(
+    connection: &zbus::blocking::Connection,
+    method: &str,
+    body: &B,
+) -> zbus::Result<zbus::Message>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    connection.call_method(
+        Some(KWIN_NAME),
+        "/Scripting",
+        Some("org.kde.kwin.Scripting"),
+        method,
+        body,
+    )
+}
+
+/// Loads `source` as a KWin script, runs it and unloads it again
+fn run_script(
+    connection: &zbus::blocking::Connection,
+    name: &str,
+    source: &str,
+) -> zbus::Result<()> {
+    let path = write_script(name, source).map_err(|e| zbus::Error::Failure(e.to_string()))?;
+
+    // A script left loaded under the same name makes loadScript fail
+    let _ = scripting(connection, "unloadScript", &(name,));
+    let result = load_and_run(connection, &path, name);
+    let _ = scripting(connection, "unloadScript", &(name,));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// Writes the script where KWin can read it but nobody else can swap it:
+/// under `$XDG_RUNTIME_DIR`, which only we can write to, or else as a new
+/// owner-only file in the temp directory
+fn write_script(name: &str, source: &str) -> std::io::Result<PathBuf> {
+    let file_name = format!("{}-{}.js", name, std::process::id());
+    let path = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => {
+            let path = PathBuf::from(runtime_dir).join(file_name);
+            // Left behind by an earlier run with our pid
+            let _ = fs::remove_file(&path);
+            path
+        }
+        None => std::env::temp_dir().join(file_name),
+    };
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(source.as_bytes())?;
+    Ok(path)
+}
+
+fn load_and_run(
+    connection: &zbus::blocking::Connection,
+    path: &Path,
+    name: &str,
+) -> zbus::Result<()> {
+    let id: i32 = scripting(
+        connection,
+        "loadScript",
+        &(path.to_string_lossy().as_ref(), name),
+    )?
+    .body()
+    .deserialize()?;
+    if id < 0 {
+        return Err(zbus::Error::Failure(format!(
+            "KWin refused script {}",
+            name
+        )));
+    }
+    // Plasma 6 puts scripts under /Scripting, Plasma 5 at the root
+    let ran = [format!("/Scripting/Script{}", id), format!("/{}", id)]
+        .iter()
+        .any(|object| {
+            connection
+                .call_method(
+                    Some(KWIN_NAME),
+                    object.as_str(),
+                    Some("org.kde.kwin.Script"),
+                    "run",
+                    &(),
+                )
+                .is_ok()
+        });
+    if !ran {
+        return Err(zbus::Error::Failure(format!(
+            "could not run KWin script {}",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// The internal id of KWin's active window, `None` when nothing has focus
+pub fn active_window_id() -> Result<Option<String>, String> {
+    on_bus_thread(|| {
+        let (sender, receiver) = mpsc::channel();
+        let connection = zbus::blocking::connection::Builder::session()?
+            .serve_at(REPLY_PATH, Reply(sender))?
+            .build()?;
+        let destination = connection
+            .unique_name()
+            .map(|name| name.to_string())
+            .ok_or_else(|| zbus::Error::Failure("no unique bus name".to_string()))?;
+        let script = CAPTURE_SCRIPT
+            .replace("{destination}", &destination)
+            .replace("{path}", REPLY_PATH);
+        run_script(&connection, "penguinclip-capture", &script)?;
+        receiver
+            .recv_timeout(REPLY_TIMEOUT)
+            .map_err(|_| zbus::Error::Failure("KWin script did not reply".to_string()))
+    })
+    .map(|id| Some(id).filter(|id| !id.is_empty()))
+}
+
+/// Asks KWin to activate the window with internal id `id`
+pub fn activate_window(id: &str) -> Result<(), String> {
+    if !is_window_id(id) {
+        return Err(format!("Invalid KWin window id '{}'", id));
+    }
+    on_bus_thread(|| {
+        let connection = zbus::blocking::Connection::session()?;
+        run_script(
+            &connection,
+            "penguinclip-activate",
+            &ACTIVATE_SCRIPT.replace("{id}", id),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_window_id() {
+        assert!(is_window_id("{5b9b8c4e-2f0a-4c4e-9d0b-3f1e6a7c8d9e}"));
+        assert!(!is_window_id("5b9b8c4e-2f0a-4c4e-9d0b-3f1e6a7c8d9e"));
+        assert!(!is_window_id("{5b9b8c4e-2f0a-4c4e-9d0b-3f1e6a7c8d9\"}"));
+        assert!(!is_window_id(""));
+    }
+}
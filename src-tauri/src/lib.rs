@@ -1,43 +1,66 @@
 //! PenguinClip - Security-hardened clipboard history manager for Linux
 //! Forked from Windows 11 Clipboard History For Linux
 
+pub mod atomic_write;
 pub mod autostart_manager;
+pub mod clipboard_backend;
 pub mod clipboard_manager;
+pub mod clipboard_watch;
 pub mod config_manager;
+pub mod conflict_journal;
 pub mod diagnostics;
 pub mod emoji_manager;
+pub mod environment;
 pub mod focus_manager;
+pub mod gif_history;
 pub mod gif_manager;
+pub mod global_shortcuts_portal;
+pub mod history_crypto;
 pub mod input_simulator;
+pub mod kaomoji;
+pub mod kwin;
+pub mod logger;
+pub mod paths;
 pub mod permission_checker;
+pub mod qr_codes;
 pub mod rendering_env;
 pub mod session;
+pub mod shell_helper;
+pub mod shortcut_backups;
 pub mod shortcut_conflict_detector;
 pub mod shortcut_setup;
+pub mod smart_actions;
+pub mod text_transforms;
 pub mod theme_manager;
 pub mod updater;
 pub mod user_settings;
+pub mod wayland_source;
 
 pub mod linux_shortcut_manager;
 
 pub use clipboard_manager::{ClipboardContent, ClipboardItem, ClipboardManager};
 pub use config_manager::ConfigManager;
 pub use emoji_manager::{EmojiManager, EmojiUsage};
+pub use environment::{DesktopEnvironment, EnvironmentInfo};
 pub use focus_manager::{restore_focused_window, save_focused_window};
 
-pub use focus_manager::x11_robust_activate;
+pub use focus_manager::{x11_robust_activate, x11_robust_activate_by_title};
 pub use gif_manager::paste_gif_to_clipboard_with_uri;
 pub use permission_checker::{
-    check_permissions, fix_permissions_now, is_first_run, mark_first_run_complete, reset_first_run,
-    PermissionStatus,
+    check_permissions, check_wayland_support, fix_permissions_now, fix_permissions_persistent,
+    is_first_run, is_shell_helper_available, mark_first_run_complete, reset_first_run,
+    FeatureSupport, FixStep, PermissionStatus, WaylandSupport,
 };
-pub use session::{get_session_type, is_wayland, is_x11, SessionType};
+pub use session::{get_session_type, is_wayland, is_x11, is_xwayland, SessionInfo, SessionType};
 pub use shortcut_conflict_detector::{
-    auto_resolve_conflicts, detect_shortcut_conflicts, ConflictDetectionResult, ShortcutConflict,
+    auto_resolve_conflicts, detect_binding_conflicts, detect_shortcut_conflicts,
+    ConflictDetectionResult, ConflictResolutionReport, ResolutionCommand, ShortcutConflict,
 };
 pub use shortcut_setup::{
-    check_shortcut_tools, detect_conflicts, get_desktop_environment, register_de_shortcut,
-    resolve_conflicts, ShortcutToolsStatus,
+    check_binding_conflicts, check_shortcut_tools, detect_conflicts, get_desktop_environment,
+    get_environment_info, get_session_info, get_shortcut_status, list_resolved_conflicts,
+    list_shortcut_backups, register_de_shortcut, resolve_conflicts, restore_shortcut_backup,
+    undo_conflict_resolution, unregister_de_shortcut, ShortcutToolsStatus,
 };
 pub use theme_manager::{
     clear_theme_cache, get_system_color_scheme, is_event_listener_running, start_theme_listener,
@@ -1,12 +1,17 @@
 //! Linux Desktop Environment Shortcut Manager
 
+use crate::atomic_write;
+use crate::environment::{self, DesktopEnvironment};
+use crate::paths;
+use crate::session;
+use crate::shortcut_backups;
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 // Characters that need encoding in INI section names: / \ [ ] = ; # and control chars
@@ -33,25 +38,347 @@ fn escape_xml(s: &str) -> String {
 // Configuration
 // =============================================================================
 
+/// A key modifier, declared in the order bindings are written. Serialized
+/// as COSMIC names them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Modifier {
+    Super,
+    Ctrl,
+    Alt,
+    Shift,
+}
+
+impl Modifier {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "super" | "meta" | "mod4" => Some(Self::Super),
+            "ctrl" | "control" | "primary" => Some(Self::Ctrl),
+            "alt" | "mod1" => Some(Self::Alt),
+            "shift" => Some(Self::Shift),
+            _ => None,
+        }
+    }
+}
+
+/// Keys other than letters, digits and F1-F12 a binding may use:
+/// (X keysym, KDE name, Linux input key code name)
+const NAMED_KEYS: &[(&str, &str, &str)] = &[
+    ("period", ".", "KEY_DOT"),
+    ("comma", ",", "KEY_COMMA"),
+    ("slash", "/", "KEY_SLASH"),
+    ("semicolon", ";", "KEY_SEMICOLON"),
+    ("minus", "-", "KEY_MINUS"),
+    ("space", "Space", "KEY_SPACE"),
+    ("Insert", "Ins", "KEY_INSERT"),
+];
+
+/// A global shortcut in the settings syntax, `<Super>v` or `<Ctrl><Alt>v`
+/// (GNOME's), convertible to each desktop's own syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    /// Sorted, without duplicates
+    modifiers: Vec<Modifier>,
+    /// A lowercase letter or digit, `F1`-`F12`, or a `NAMED_KEYS` keysym
+    key: String,
+}
+
+impl KeyBinding {
+    fn new(modifiers: &[Modifier], key: &str) -> Self {
+        Self {
+            modifiers: modifiers.to_vec(),
+            key: key.to_string(),
+        }
+    }
+
+    /// Parses `<Modifier>...key` (case-insensitive). At least one modifier
+    /// is required so a plain key is never grabbed globally.
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut rest = text.trim();
+        let mut modifiers = Vec::new();
+        while let Some(after) = rest.strip_prefix('<') {
+            let (name, tail) = after
+                .split_once('>')
+                .ok_or_else(|| format!("Unclosed '<' in shortcut '{}'", text))?;
+            let modifier = Modifier::parse(name)
+                .ok_or_else(|| format!("Unknown modifier '{}' in shortcut '{}'", name, text))?;
+            if modifiers.contains(&modifier) {
+                return Err(format!("'{}' appears twice in shortcut '{}'", name, text));
+            }
+            modifiers.push(modifier);
+            rest = tail.trim_start();
+        }
+        if modifiers.is_empty() {
+            return Err(format!(
+                "Shortcut '{}' needs at least one modifier, like <Super>v",
+                text
+            ));
+        }
+        let key = Self::parse_key(rest)
+            .ok_or_else(|| format!("Unknown key '{}' in shortcut '{}'", rest, text))?;
+        modifiers.sort();
+        Ok(Self { modifiers, key })
+    }
+
+    fn parse_key(name: &str) -> Option<String> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return c
+                .is_ascii_alphanumeric()
+                .then(|| c.to_ascii_lowercase().to_string());
+        }
+        if let Some(n) = name
+            .strip_prefix(['F', 'f'])
+            .and_then(|n| n.parse::<u8>().ok())
+        {
+            return (1..=12).contains(&n).then(|| format!("F{}", n));
+        }
+        NAMED_KEYS
+            .iter()
+            .find(|(keysym, ..)| keysym.eq_ignore_ascii_case(name))
+            .map(|(keysym, ..)| keysym.to_string())
+    }
+
+    /// This binding with `modifier` held too
+    pub fn with_modifier(&self, modifier: Modifier) -> Self {
+        let mut modifiers = self.modifiers.clone();
+        if !modifiers.contains(&modifier) {
+            modifiers.push(modifier);
+            modifiers.sort();
+        }
+        Self {
+            modifiers,
+            key: self.key.clone(),
+        }
+    }
+
+    /// Bindings to offer instead when this one is taken: the same key with
+    /// Shift, Alt or Ctrl held too, in that order
+    pub fn alternatives(&self) -> Vec<Self> {
+        [Modifier::Shift, Modifier::Alt, Modifier::Ctrl]
+            .into_iter()
+            .filter(|m| !self.modifiers.contains(m))
+            .map(|m| self.with_modifier(m))
+            .collect()
+    }
+
+    /// The modifiers spelled with `names`, indexed like `Modifier`
+    fn modifier_names(&self, names: [&'static str; 4]) -> Vec<&'static str> {
+        self.modifiers.iter().map(|&m| names[m as usize]).collect()
+    }
+
+    /// The key with letters uppercased, as KDE and Hyprland write them
+    fn upper_key(&self) -> String {
+        if self.key.len() == 1 {
+            self.key.to_ascii_uppercase()
+        } else {
+            self.key.clone()
+        }
+    }
+
+    /// GNOME, Cinnamon and MATE: `<Super><Alt>v`
+    pub fn gnome(&self) -> String {
+        let mods = self.modifier_names(["<Super>", "<Ctrl>", "<Alt>", "<Shift>"]);
+        format!("{}{}", mods.concat(), self.key)
+    }
+
+    /// The key as KDE writes it: letters uppercased, punctuation as itself
+    fn kde_key(&self) -> String {
+        NAMED_KEYS
+            .iter()
+            .find(|(keysym, ..)| *keysym == self.key)
+            .map_or_else(|| self.upper_key(), |(_, kde, _)| kde.to_string())
+    }
+
+    /// For people to read, in messages and instructions: `Super+Alt+V`
+    pub fn label(&self) -> String {
+        let mods = self.modifier_names(["Super", "Ctrl", "Alt", "Shift"]);
+        format!("{}+{}", mods.join("+"), self.kde_key())
+    }
+
+    /// KDE and LXQt: `Meta+Alt+V`
+    pub fn kde(&self) -> String {
+        let mods = self.modifier_names(["Meta", "Ctrl", "Alt", "Shift"]);
+        format!("{}+{}", mods.join("+"), self.kde_key())
+    }
+
+    /// XFCE: `<Super><Primary>v`
+    pub fn xfce(&self) -> String {
+        let mods = self.modifier_names(["<Super>", "<Primary>", "<Alt>", "<Shift>"]);
+        format!("{}{}", mods.concat(), self.key)
+    }
+
+    /// COSMIC modifier list: `Super, Alt`
+    pub fn cosmic_modifiers(&self) -> String {
+        self.modifier_names(["Super", "Ctrl", "Alt", "Shift"])
+            .join(", ")
+    }
+
+    /// i3 and Sway: `$mod+Mod1+v`, assuming `$mod` is Super
+    pub fn i3(&self) -> String {
+        let mods = self.modifier_names(["$mod", "Ctrl", "Mod1", "Shift"]);
+        format!("{}+{}", mods.join("+"), self.key)
+    }
+
+    /// Hyprland: `SUPER ALT, V`
+    pub fn hyprland(&self) -> String {
+        let mods = self.modifier_names(["SUPER", "CTRL", "ALT", "SHIFT"]);
+        format!("{}, {}", mods.join(" "), self.upper_key())
+    }
+
+    /// LXDE (Openbox): `W-A-v`
+    pub fn lxde(&self) -> String {
+        let mods = self.modifier_names(["W", "C", "A", "S"]);
+        format!("{}-{}", mods.join("-"), self.key)
+    }
+
+    /// awesome: the modifiers and key arguments of `awful.key`,
+    /// `{ "Mod4", "Mod1" }, "v"`
+    pub fn awesome(&self) -> String {
+        let mods: Vec<String> = self
+            .modifier_names(["Mod4", "Control", "Mod1", "Shift"])
+            .iter()
+            .map(|m| format!("\"{}\"", m))
+            .collect();
+        format!("{{ {} }}, {}", mods.join(", "), quoted_string(&self.key))
+    }
+
+    /// qtile: the modifiers and key arguments of `Key`, `["mod4", "mod1"], "v"`
+    pub fn qtile(&self) -> String {
+        let mods: Vec<String> = self
+            .modifier_names(["mod4", "control", "mod1", "shift"])
+            .iter()
+            .map(|m| format!("\"{}\"", m))
+            .collect();
+        format!("[{}], {}", mods.join(", "), quoted_string(&self.key))
+    }
+
+    /// River: `Super+Alt V`, the modifiers and key arguments of `riverctl map`
+    pub fn river(&self) -> String {
+        let mods = self.modifier_names(["Super", "Control", "Alt", "Shift"]);
+        let mods = if mods.is_empty() {
+            "None".to_string()
+        } else {
+            mods.join("+")
+        };
+        format!("{} {}", mods, self.upper_key())
+    }
+
+    /// Wayfire: `<super> <alt> KEY_V`
+    pub fn wayfire(&self) -> String {
+        let key = NAMED_KEYS
+            .iter()
+            .find(|(keysym, ..)| *keysym == self.key)
+            .map_or_else(
+                || format!("KEY_{}", self.key.to_ascii_uppercase()),
+                |(.., code)| code.to_string(),
+            );
+        let mut parts = self.modifier_names(["<super>", "<ctrl>", "<alt>", "<shift>"]);
+        parts.push(&key);
+        parts.join(" ")
+    }
+
+    /// sxhkd: `super + alt + v`
+    pub fn sxhkd(&self) -> String {
+        let mut parts = self.modifier_names(["super", "ctrl", "alt", "shift"]);
+        parts.push(&self.key);
+        parts.join(" + ")
+    }
+
+    /// GlobalShortcuts portal trigger (XDG shortcuts spec): `LOGO+ALT+v`
+    pub fn portal(&self) -> String {
+        let mut parts = self.modifier_names(["LOGO", "CTRL", "ALT", "SHIFT"]);
+        parts.push(&self.key);
+        parts.join("+")
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.gnome())
+    }
+}
+
+/// The two shortcuts that open the clipboard history, from the
+/// `shortcut_primary` and `shortcut_secondary` settings, the
+/// `shortcut_overrides` registered in place of any shortcut's binding, and
+/// the `disabled_shortcuts` left unregistered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryBindings {
+    pub primary: KeyBinding,
+    pub secondary: KeyBinding,
+    /// By shortcut id, e.g. `penguinclip` → `<Super><Shift>v`
+    pub overrides: BTreeMap<String, KeyBinding>,
+    /// Ids of the shortcuts the user unregistered
+    pub disabled: BTreeSet<String>,
+}
+
+impl Default for HistoryBindings {
+    /// Super+V, and Ctrl+Alt+V for desktops that keep Super+V
+    fn default() -> Self {
+        Self {
+            primary: KeyBinding::new(&[Modifier::Super], "v"),
+            secondary: KeyBinding::new(&[Modifier::Ctrl, Modifier::Alt], "v"),
+            overrides: BTreeMap::new(),
+            disabled: BTreeSet::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ShortcutConfig {
     pub id: &'static str,
     pub name: &'static str,
     pub command: &'static str,
     pub args: &'static str, // Command line arguments (e.g., "--emoji")
-    pub gnome_binding: &'static str,
-    pub kde_binding: &'static str,
-    pub xfce_binding: &'static str,
-    pub cosmic_mods: &'static str,
-    pub cosmic_key: &'static str,
+    pub binding: KeyBinding,
+    pub gnome_binding: String,
+    pub kde_binding: String,
+    pub xfce_binding: String,
+    pub cosmic_mods: String,
+    pub cosmic_key: String,
     // Tiling WM bindings
-    pub i3_binding: &'static str,
-    pub sway_binding: &'static str,
-    pub hyprland_binding: &'static str,
-    pub lxde_binding: &'static str,
+    pub i3_binding: String,
+    pub sway_binding: String,
+    pub hyprland_binding: String,
+    pub lxde_binding: String,
+    pub sxhkd_binding: String,
+    pub awesome_binding: String,
+    pub qtile_binding: String,
+    pub river_binding: String,
+    pub wayfire_binding: String,
 }
 
 impl ShortcutConfig {
+    fn new(
+        id: &'static str,
+        name: &'static str,
+        command: &'static str,
+        args: &'static str,
+        binding: &KeyBinding,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            command,
+            args,
+            binding: binding.clone(),
+            gnome_binding: binding.gnome(),
+            kde_binding: binding.kde(),
+            xfce_binding: binding.xfce(),
+            cosmic_mods: binding.cosmic_modifiers(),
+            cosmic_key: binding.key.clone(),
+            i3_binding: binding.i3(),
+            sway_binding: binding.i3(),
+            hyprland_binding: binding.hyprland(),
+            lxde_binding: binding.lxde(),
+            sxhkd_binding: binding.sxhkd(),
+            awesome_binding: binding.awesome(),
+            qtile_binding: binding.qtile(),
+            river_binding: binding.river(),
+            wayfire_binding: binding.wayfire(),
+        }
+    }
+
     /// Returns the full command string including any arguments
     pub fn full_command(&self) -> String {
         if self.args.is_empty() {
@@ -80,53 +407,111 @@ fn get_command_path() -> &'static str {
     "penguinclip"
 }
 
-const SHORTCUTS: &[ShortcutConfig] = &[
-    ShortcutConfig {
-        id: "penguinclip",
-        name: "Clipboard History",
-        command: "penguinclip", // Will be replaced at runtime
-        args: "",
-        gnome_binding: "<Super>v",
-        kde_binding: "Meta+V",
-        xfce_binding: "<Super>v",
-        cosmic_mods: "Super",
-        cosmic_key: "v",
-        i3_binding: "$mod+v",
-        sway_binding: "$mod+v",
-        hyprland_binding: "SUPER, V",
-        lxde_binding: "W-v",
-    },
-    ShortcutConfig {
-        id: "penguinclip-alt",
-        name: "Clipboard History (Alt)",
-        command: "penguinclip", // Will be replaced at runtime
-        args: "",
-        gnome_binding: "<Ctrl><Alt>v",
-        kde_binding: "Ctrl+Alt+V",
-        xfce_binding: "<Primary><Alt>v",
-        cosmic_mods: "Ctrl, Alt",
-        cosmic_key: "v",
-        i3_binding: "Ctrl+Mod1+v",
-        sway_binding: "Ctrl+Mod1+v",
-        hyprland_binding: "CTRL ALT, V",
-        lxde_binding: "C-A-v",
-    },
-    ShortcutConfig {
-        id: "penguinclip-emoji",
-        name: "Emoji Picker",
-        command: "penguinclip", // Will be replaced at runtime
-        args: "--emoji",
-        gnome_binding: "<Super>period",
-        kde_binding: "Meta+.",
-        xfce_binding: "<Super>period",
-        cosmic_mods: "Super",
-        cosmic_key: "period",
-        i3_binding: "$mod+period",
-        sway_binding: "$mod+period",
-        hyprland_binding: "SUPER, period",
-        lxde_binding: "W-period",
-    },
-];
+/// Numbered quick paste: Super+Alt+N pastes history item N. Plain Super+N
+/// is left alone since GNOME and KDE use it to switch to pinned apps.
+macro_rules! paste_index_shortcut {
+    ($n:literal, $command:expr) => {
+        ShortcutConfig::new(
+            concat!("penguinclip-paste-", $n),
+            concat!("Paste Clipboard Item ", $n),
+            $command,
+            concat!("--paste-index ", $n),
+            &KeyBinding::new(&[Modifier::Super, Modifier::Alt], $n),
+        )
+    };
+}
+
+/// Rebinds the shortcuts that have an override in `bindings`
+fn apply_overrides(configs: &mut [ShortcutConfig], bindings: &HistoryBindings) {
+    for config in configs {
+        if let Some(binding) = bindings.overrides.get(config.id) {
+            *config =
+                ShortcutConfig::new(config.id, config.name, config.command, config.args, binding);
+        }
+    }
+}
+
+/// The clipboard history shortcuts, bound as the settings say
+fn history_shortcuts(bindings: &HistoryBindings, command: &'static str) -> [ShortcutConfig; 2] {
+    let mut configs = [
+        ShortcutConfig::new(
+            "penguinclip",
+            "Clipboard History",
+            command,
+            "",
+            &bindings.primary,
+        ),
+        ShortcutConfig::new(
+            "penguinclip-alt",
+            "Clipboard History (Alt)",
+            command,
+            "",
+            &bindings.secondary,
+        ),
+    ];
+    apply_overrides(&mut configs, bindings);
+    configs
+}
+
+/// The emoji picker shortcut, Super+.
+pub fn emoji_binding() -> KeyBinding {
+    KeyBinding::new(&[Modifier::Super], "period")
+}
+
+/// Every shortcut we register
+fn shortcuts(bindings: &HistoryBindings, command: &'static str) -> Vec<ShortcutConfig> {
+    let mut shortcuts = history_shortcuts(bindings, command).to_vec();
+    shortcuts.push(ShortcutConfig::new(
+        "penguinclip-emoji",
+        "Emoji Picker",
+        command,
+        "--emoji",
+        &emoji_binding(),
+    ));
+    shortcuts.extend([
+        paste_index_shortcut!("1", command),
+        paste_index_shortcut!("2", command),
+        paste_index_shortcut!("3", command),
+        paste_index_shortcut!("4", command),
+        paste_index_shortcut!("5", command),
+        paste_index_shortcut!("6", command),
+        paste_index_shortcut!("7", command),
+        paste_index_shortcut!("8", command),
+        paste_index_shortcut!("9", command),
+    ]);
+    apply_overrides(&mut shortcuts, bindings);
+    shortcuts
+}
+
+/// Every shortcut we register, for desktops that bind them for us through
+/// the GlobalShortcuts portal. Disabled shortcuts are left out.
+pub fn all_shortcuts(bindings: &HistoryBindings) -> Vec<ShortcutConfig> {
+    shortcuts(bindings, "penguinclip")
+        .into_iter()
+        .filter(|config| !bindings.disabled.contains(config.id))
+        .collect()
+}
+
+/// The shortcuts conflict detection looks for: clipboard history and the
+/// emoji picker. Super+Alt+N for quick paste is left out.
+pub fn conflict_shortcuts(bindings: &HistoryBindings) -> Vec<ShortcutConfig> {
+    shortcuts(bindings, "penguinclip")
+        .into_iter()
+        .filter(|s| !s.id.starts_with("penguinclip-paste-"))
+        .collect()
+}
+
+/// A shortcut the user is about to choose, so its keys can be checked for
+/// conflicts before it is saved
+pub fn candidate_shortcut(binding: &KeyBinding) -> ShortcutConfig {
+    ShortcutConfig::new(
+        "penguinclip-candidate",
+        "New Shortcut",
+        "penguinclip",
+        "",
+        binding,
+    )
+}
 
 // =============================================================================
 // Error Handling
@@ -169,47 +554,170 @@ type Result<T> = std::result::Result<T, ShortcutError>;
 // Public API
 // =============================================================================
 
-pub fn register_global_shortcut() {
+/// What happened to one shortcut when (un)registering it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegistrationResult {
+    pub id: String,
+    pub name: String,
+    /// In the settings syntax, e.g. `<Super>v`
+    pub binding: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RegistrationResult {
+    /// Logs `result` of `action` ("register" or "unregister") on `config`
+    /// and turns it into a report entry
+    fn report(config: &ShortcutConfig, action: &str, result: Result<()>) -> Self {
+        match &result {
+            Ok(_) => log::info!("{}ed shortcut '{}'", action, config.name),
+            Err(e) => log::warn!("failed to {} shortcut '{}': {}", action, config.name, e),
+        }
+        Self {
+            id: config.id.to_string(),
+            name: config.name.to_string(),
+            binding: config.gnome_binding.clone(),
+            success: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// The shortcut with `id` (e.g. `penguinclip-alt`), or every shortcut when
+/// `id` is `None`. Errors on an unknown id.
+fn selected_shortcuts(
+    bindings: &HistoryBindings,
+    id: Option<&str>,
+) -> std::result::Result<Vec<ShortcutConfig>, String> {
+    let configs: Vec<ShortcutConfig> = shortcuts(bindings, get_command_path())
+        .into_iter()
+        .filter(|config| id.map_or(true, |id| config.id == id))
+        .collect();
+    if configs.is_empty() {
+        return Err(format!("Unknown shortcut '{}'", id.unwrap_or_default()));
+    }
+    Ok(configs)
+}
+
+/// Like `selected_shortcuts`, but all of them leaves out the disabled
+/// shortcuts; asking for one by id registers it regardless.
+fn shortcuts_to_register(
+    bindings: &HistoryBindings,
+    id: Option<&str>,
+) -> std::result::Result<Vec<ShortcutConfig>, String> {
+    Ok(selected_shortcuts(bindings, id)?
+        .into_iter()
+        .filter(|config| id.is_some() || !bindings.disabled.contains(config.id))
+        .collect())
+}
+
+/// Registers the shortcut with `id`, or every enabled shortcut when `id`
+/// is `None`, reporting each one. Errors on an unknown id.
+pub fn register_global_shortcut(
+    bindings: &HistoryBindings,
+    id: Option<&str>,
+) -> std::result::Result<Vec<RegistrationResult>, String> {
+    let configs = shortcuts_to_register(bindings, id)?;
     let handler = detect_handler();
-    let command_path = get_command_path();
     log::info!(
         "registering global shortcuts for environment '{}' (command: {})",
         handler.name(),
-        command_path
+        get_command_path()
     );
 
-    for shortcut in SHORTCUTS {
-        // Create a new config with the correct command path
-        let mut config = shortcut.clone();
-        config.command = command_path;
-
-        match handler.register(&config) {
-            Ok(_) => log::info!("registered shortcut '{}'", config.name),
-            Err(e) => log::warn!("failed to register shortcut '{}': {}", config.name, e),
-        }
-    }
+    Ok(configs
+        .iter()
+        .map(|config| RegistrationResult::report(config, "register", handler.register(config)))
+        .collect())
 }
 
-pub fn unregister_global_shortcut() {
+/// Unregisters the shortcut with `id`, or every shortcut when `id` is
+/// `None`, reporting each one. Errors on an unknown id.
+pub fn unregister_global_shortcut(
+    bindings: &HistoryBindings,
+    id: Option<&str>,
+) -> std::result::Result<Vec<RegistrationResult>, String> {
+    let configs = selected_shortcuts(bindings, id)?;
     let handler = detect_handler();
-    let command_path = get_command_path();
     log::info!(
         "unregistering global shortcuts for environment '{}'",
         handler.name()
     );
 
-    for shortcut in SHORTCUTS {
-        // Create a new config with the correct command path
-        let mut config = shortcut.clone();
-        config.command = command_path;
+    Ok(configs
+        .iter()
+        .map(|config| RegistrationResult::report(config, "unregister", handler.unregister(config)))
+        .collect())
+}
+
+/// Asks the current desktop to reread its shortcut config, after a file
+/// was restored from a backup
+pub fn reload_desktop_config() {
+    let handler = detect_handler();
+    log::info!(
+        "reloading shortcut config for environment '{}'",
+        handler.name()
+    );
+    handler.reload();
+}
+
+/// Moves the clipboard history shortcuts from the `old` bindings to `new`,
+/// leaving the other shortcuts and the disabled ones alone.
+pub fn rebind_global_shortcut(old: &HistoryBindings, new: &HistoryBindings) {
+    let handler = detect_handler();
+    let command_path = get_command_path();
 
-        match handler.unregister(&config) {
-            Ok(_) => log::info!("unregistered shortcut '{}'", config.name),
-            Err(e) => log::warn!("failed to unregister shortcut '{}': {}", config.name, e),
+    let old_configs = history_shortcuts(old, command_path);
+    for (before, after) in old_configs.iter().zip(history_shortcuts(new, command_path)) {
+        if before.gnome_binding == after.gnome_binding || new.disabled.contains(after.id) {
+            continue;
+        }
+        if let Err(e) = handler.unregister(before) {
+            log::warn!("failed to unregister shortcut '{}': {}", before.name, e);
+        }
+        match handler.register(&after) {
+            Ok(_) => log::info!(
+                "rebound shortcut '{}' from {} to {}",
+                after.name,
+                before.gnome_binding,
+                after.gnome_binding
+            ),
+            Err(e) => log::warn!("failed to register shortcut '{}': {}", after.name, e),
         }
     }
 }
 
+/// Whether one shortcut is bound right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShortcutStatus {
+    pub id: String,
+    pub name: String,
+    /// In the settings syntax, e.g. `<Super>v`
+    pub binding: String,
+    pub registered: bool,
+    /// Why the status couldn't be read; `registered` is false then
+    pub error: Option<String>,
+}
+
+/// Reads back every shortcut from the desktop's store, so a binding the
+/// user removed in their desktop settings shows up as missing.
+pub fn shortcut_status(bindings: &HistoryBindings) -> Vec<ShortcutStatus> {
+    let handler = detect_handler();
+    shortcuts(bindings, get_command_path())
+        .iter()
+        .map(|config| {
+            let result = handler.is_registered(config);
+            ShortcutStatus {
+                id: config.id.to_string(),
+                name: config.name.to_string(),
+                binding: config.gnome_binding.clone(),
+                registered: matches!(result, Ok(true)),
+                error: result.err().map(|e| e.to_string()),
+            }
+        })
+        .collect()
+}
+
 // =============================================================================
 // Traits & Abstractions
 // =============================================================================
@@ -218,100 +726,88 @@ trait ShortcutHandler {
     fn name(&self) -> &str;
     fn register(&self, shortcut: &ShortcutConfig) -> Result<()>;
     fn unregister(&self, shortcut: &ShortcutConfig) -> Result<()>;
+    /// Whether `shortcut` is currently bound to our command, read back from
+    /// the desktop's own store
+    fn is_registered(&self, shortcut: &ShortcutConfig) -> Result<bool>;
+    /// Makes the desktop reread config files changed behind its back, e.g.
+    /// by restoring a backup. Desktops that watch their files need nothing.
+    fn reload(&self) {}
 }
 
 fn detect_handler() -> Box<dyn ShortcutHandler> {
-    let xdg_current = env_var("XDG_CURRENT_DESKTOP").to_lowercase();
-    let xdg_session = env_var("XDG_SESSION_DESKTOP").to_lowercase();
-    let combined = format!("{} {}", xdg_current, xdg_session);
-
-    if combined.contains("gnome") || combined.contains("unity") || combined.contains("pantheon") {
-        return Box::new(GnomeHandler);
-    }
-    if combined.contains("cinnamon") {
-        return Box::new(CinnamonHandler);
-    }
-    // KDE Plasma 5 or 6
-    if combined.contains("kde") || combined.contains("plasma") {
-        return Box::new(KdeHandler);
-    }
-    if combined.contains("xfce") {
-        return Box::new(XfceHandler);
-    }
-    if combined.contains("mate") {
-        return Box::new(MateHandler);
-    }
-    if combined.contains("cosmic") {
-        return Box::new(CosmicHandler);
-    }
-    if combined.contains("lxqt") {
-        return Box::new(LxqtHandler);
-    }
-    if combined.contains("lxde") {
-        return Box::new(LxdeHandler);
-    }
-    if combined.contains("budgie") {
-        return Box::new(GnomeHandler); // Budgie uses gsettings like GNOME
-    }
-    if combined.contains("deepin") {
-        return Box::new(GnomeHandler); // Deepin uses gsettings like GNOME
-    }
-    // Tiling Window Managers
-    if combined.contains("i3") {
-        return Box::new(I3Handler);
-    }
-    if combined.contains("sway") {
-        return Box::new(SwayHandler);
-    }
-    if combined.contains("hyprland") {
-        return Box::new(HyprlandHandler);
-    }
-
-    // Heuristic Fallback - check running processes for tiling WMs
-    if is_process_running("i3") {
-        return Box::new(I3Handler);
-    }
-    if is_process_running("sway") {
-        return Box::new(SwayHandler);
-    }
-    if is_process_running("hyprland") || is_process_running("Hyprland") {
-        return Box::new(HyprlandHandler);
-    }
-
-    // Heuristic Fallback for traditional DEs
-    if Utils::command_exists("kwriteconfig5") || Utils::command_exists("kwriteconfig6") {
-        return Box::new(KdeHandler);
-    }
-    if Utils::command_exists("xfconf-query") {
-        return Box::new(XfceHandler);
+    match environment::detect().de {
+        DesktopEnvironment::Budgie => Box::new(BudgieHandler),
+        // Deepin uses gsettings like GNOME
+        DesktopEnvironment::Gnome | DesktopEnvironment::PopOs | DesktopEnvironment::Deepin => {
+            Box::new(GnomeHandler)
+        }
+        DesktopEnvironment::Cinnamon => Box::new(CinnamonHandler),
+        // KDE Plasma 5 or 6
+        DesktopEnvironment::KdePlasma => Box::new(KdeHandler),
+        DesktopEnvironment::Xfce => Box::new(XfceHandler),
+        DesktopEnvironment::Mate => Box::new(MateHandler),
+        DesktopEnvironment::Cosmic => Box::new(CosmicHandler),
+        DesktopEnvironment::Lxqt => Box::new(LxqtHandler),
+        DesktopEnvironment::Lxde => Box::new(LxdeHandler),
+        DesktopEnvironment::Enlightenment => Box::new(EnlightenmentHandler),
+        DesktopEnvironment::I3 => Box::new(I3Handler),
+        DesktopEnvironment::Sway => Box::new(SwayHandler),
+        DesktopEnvironment::Hyprland => Box::new(HyprlandHandler),
+        DesktopEnvironment::Awesome => Box::new(AwesomeHandler),
+        DesktopEnvironment::Qtile => Box::new(QtileHandler),
+        DesktopEnvironment::River => Box::new(RiverHandler),
+        DesktopEnvironment::Wayfire => Box::new(WayfireHandler),
+        // bspwm leaves keys to sxhkd, which other X11 WMs use too
+        DesktopEnvironment::Bspwm => Box::new(SxhkdHandler),
+        // Heuristic Fallback for traditional DEs
+        DesktopEnvironment::Unknown => {
+            if Utils::command_exists("kwriteconfig5") || Utils::command_exists("kwriteconfig6") {
+                Box::new(KdeHandler)
+            } else if Utils::command_exists("xfconf-query") {
+                Box::new(XfceHandler)
+            } else {
+                Box::new(GnomeHandler)
+            }
+        }
     }
+}
 
-    // Default fallback
-    Box::new(GnomeHandler)
+/// Tiling window managers with their own IPC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TilingWm {
+    I3,
+    Sway,
+    Hyprland,
 }
 
-fn is_process_running(name: &str) -> bool {
-    Command::new("pgrep")
-        .arg("-x")
-        .arg(name)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+/// The tiling WM we're running under, if any
+pub fn detect_tiling_wm() -> Option<TilingWm> {
+    match environment::detect().de {
+        DesktopEnvironment::I3 => Some(TilingWm::I3),
+        DesktopEnvironment::Sway => Some(TilingWm::Sway),
+        DesktopEnvironment::Hyprland => Some(TilingWm::Hyprland),
+        _ => None,
+    }
 }
 
 fn env_var(key: &str) -> String {
     env::var(key).unwrap_or_default()
 }
 
-/// Check if a line contains a $mod+v or mod4+v binding with proper word boundaries.
+/// Check if a line binds `binding` (an i3/Sway binding, lowercased, where
+/// `$mod` also matches a literal `mod4`) with proper word boundaries.
 /// This ensures we match "bindsym $mod+v" even at end of line or followed by comments.
-fn has_mod_v_binding(trimmed_line: &str) -> bool {
-    for pattern in &["$mod+v", "mod4+v"] {
+pub(crate) fn has_i3_binding(trimmed_line: &str, binding: &str) -> bool {
+    let literal = binding.replace("$mod", "mod4");
+    for pattern in [binding, literal.as_str()] {
         if let Some(idx) = trimmed_line.find(pattern) {
-            // Check what follows the pattern
+            // Check what surrounds the pattern
+            let before = trimmed_line[..idx].chars().next_back();
             let after = trimmed_line[idx + pattern.len()..].chars().next();
             // Valid word boundaries: end of string, space, tab, comment, semicolon
-            if matches!(after, None | Some(' ') | Some('\t') | Some('#') | Some(';')) {
+            if matches!(before, Some(' ') | Some('\t'))
+                && matches!(after, None | Some(' ') | Some('\t') | Some('#') | Some(';'))
+            {
                 return true;
             }
         }
@@ -319,113 +815,662 @@ fn has_mod_v_binding(trimmed_line: &str) -> bool {
     false
 }
 
-// =============================================================================
-// Utilities
-// =============================================================================
-
-struct Utils;
-
-impl Utils {
-    fn command_exists(cmd: &str) -> bool {
-        Command::new("which")
-            .arg(cmd)
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+/// Check if a Hyprland `bind = MODS, key, ...` line (lowercased) binds
+/// `binding` (`SUPER, V`).
+pub(crate) fn has_hyprland_binding(trimmed_line: &str, binding: &str) -> bool {
+    let Some((keyword, rest)) = trimmed_line.split_once('=') else {
+        return false;
+    };
+    if !keyword.trim().starts_with("bind") {
+        return false;
     }
+    let fields = |line: &str| -> Vec<String> {
+        line.split(',')
+            .take(2)
+            .map(|field| field.split_whitespace().collect::<Vec<_>>().join(" "))
+            .map(|field| field.to_lowercase())
+            .collect()
+    };
+    fields(rest) == fields(binding)
+}
 
-    fn run(cmd: &str, args: &[&str]) -> Result<String> {
-        let output = Command::new(cmd).args(args).output()?;
+/// Hyprland's modifier mask bits, by the names our bindings use
+const HYPRLAND_MODMASK: [(&str, u32); 4] = [("SUPER", 64), ("CTRL", 4), ("ALT", 8), ("SHIFT", 1)];
+
+/// A bind as reported by `hyprctl binds -j`
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct HyprlandBind {
+    pub modmask: u32,
+    pub key: String,
+    #[serde(default)]
+    pub submap: String,
+    #[serde(default)]
+    pub dispatcher: String,
+    #[serde(default)]
+    pub arg: String,
+}
 
-        if !output.status.success() {
-            return Err(ShortcutError::CommandFailed {
-                cmd: cmd.to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
-            });
+impl HyprlandBind {
+    /// Whether this is a bind of `binding` (`SUPER ALT, V`) outside any submap
+    pub(crate) fn binds(&self, binding: &str) -> bool {
+        let Some((mods, key)) = binding.split_once(',') else {
+            return false;
+        };
+        let mut mask = 0;
+        for name in mods.split_whitespace() {
+            match HYPRLAND_MODMASK
+                .iter()
+                .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            {
+                Some((_, bit)) => mask |= bit,
+                None => return false,
+            }
         }
-
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        self.submap.is_empty() && self.modmask == mask && self.key.eq_ignore_ascii_case(key.trim())
     }
 
-    /// Reads a file, creates a .bak copy, modifies content via callback,
-    /// then writes back atomically using a temp file rename strategy.
-    /// Returns Ok(true) if file was modified, Ok(false) if no changes were needed.
-    fn modify_file_atomic<F>(path: &Path, modifier: F) -> Result<bool>
-    where
-        F: FnOnce(String) -> Result<Option<String>>,
-    {
-        if !path.exists() {
-            // Create directory structure if missing
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent)?;
-            }
+    /// What the bind does (`exec, firefox`)
+    pub(crate) fn action(&self) -> String {
+        if self.arg.is_empty() {
+            self.dispatcher.clone()
+        } else {
+            format!("{}, {}", self.dispatcher, self.arg)
         }
+    }
+}
 
-        let content = if path.exists() {
-            // Create a single backup file (only if it doesn't exist yet)
-            let bak_path = path.with_extension("bak");
-            if !bak_path.exists() {
-                fs::copy(path, &bak_path)?;
-                println!("[Utils] Created backup: {:?}", bak_path);
-            }
+/// The binds Hyprland currently has, including those from sourced files and
+/// `hyprctl keyword`. `None` outside a running Hyprland session.
+pub(crate) fn hyprland_runtime_binds() -> Option<Vec<HyprlandBind>> {
+    let output = Utils::run("hyprctl", &["binds", "-j"]).ok()?;
+    serde_json::from_str(&output).ok()
+}
 
-            fs::read_to_string(path)?
-        } else {
-            String::new()
-        };
+const ADDED_MARKER: &str = "# Clipboard History (added by penguinclip)";
+const COMMENTED_SUFFIX: &str = " # Commented by penguinclip";
+
+/// Comments out the lines `conflicts` matches (given each line trimmed and
+/// lowercased), marked so `restore_lines` can undo it. `None` if none match.
+fn comment_out_lines(content: &str, conflicts: impl Fn(&str) -> bool) -> Option<String> {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let mut commented = false;
+    for line in lines.iter_mut() {
+        let trimmed = line.trim().to_lowercase();
+        // Skip if already a comment
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        if conflicts(&trimmed) {
+            *line = format!("# {}{}", line, COMMENTED_SUFFIX);
+            commented = true;
+        }
+    }
+    commented.then(|| lines.join("\n"))
+}
 
-        // Run modifier logic
-        let new_content = match modifier(content) {
-            Ok(Some(s)) => s,
-            Ok(None) => return Ok(false), // No changes needed
-            Err(e) => return Err(e),
-        };
+/// Restores the lines `comment_out_lines` commented out that `conflicts`
+/// matches. `None` if there are none.
+fn restore_lines(content: &str, conflicts: impl Fn(&str) -> bool) -> Option<String> {
+    let mut restored_any = false;
+    let lines: Vec<&str> = content
+        .lines()
+        .map(|line| {
+            let restored = line
+                .strip_suffix(COMMENTED_SUFFIX)
+                .and_then(|l| l.strip_prefix("# "))
+                .filter(|l| conflicts(&l.trim().to_lowercase()));
+            restored_any |= restored.is_some();
+            restored.unwrap_or(line)
+        })
+        .collect();
+    restored_any.then(|| lines.join("\n"))
+}
 
-        // Atomic Write Strategy: Write to .tmp, then rename
-        let tmp_path = path.with_extension(format!(
-            "tmp.{}",
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                .as_millis()
-        ));
+/// Appends `binding_line` to a tiling WM config, commenting out the lines
+/// `conflicts` matches (given each line trimmed and lowercased). Returns the
+/// new content and whether anything was commented out, or `None` when the
+/// line is already there.
+fn add_binding_line(
+    content: &str,
+    binding_line: &str,
+    conflicts: impl Fn(&str) -> bool,
+) -> Option<(String, bool)> {
+    if content.lines().any(|line| line.trim() == binding_line) {
+        return None;
+    }
 
-        let mut file = fs::File::create(&tmp_path)?;
-        file.write_all(new_content.as_bytes())?;
-        file.sync_all()?; // Ensure flush to disk
+    let commented = comment_out_lines(content, conflicts);
+    let had_existing = commented.is_some();
+    let content = commented.as_deref().unwrap_or(content);
 
-        // Atomic rename
-        fs::rename(&tmp_path, path)?;
+    // Add our binding at the end
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    lines.push(format!("\n{}", ADDED_MARKER));
+    lines.push(binding_line.to_string());
+    Some((lines.join("\n"), had_existing))
+}
 
-        Ok(true) // File was modified
+/// Removes `binding_line` and its marker comment, and restores the lines
+/// `add_binding_line` commented out for it. `None` if the line isn't there.
+fn remove_binding_line(
+    content: &str,
+    binding_line: &str,
+    conflicts: impl Fn(&str) -> bool,
+) -> Option<String> {
+    if !content.lines().any(|line| line.trim() == binding_line) {
+        return None;
     }
-}
 
-// =============================================================================
-// Implementations
-// =============================================================================
+    let mut new_lines: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if line.trim() == binding_line {
+            // Drop our comment line above it too
+            if new_lines.last().is_some_and(|l| l.trim() == ADDED_MARKER) {
+                new_lines.pop();
+            }
+            continue;
+        }
+        new_lines.push(line);
+    }
+    let content = new_lines.join("\n");
+    Some(restore_lines(&content, conflicts).unwrap_or(content))
+}
 
-// --- GNOME / Cinnamon Shared Logic ---
+/// The version from `i3 --version` output ("i3 version 4.22 (2023-01-02) ...")
+fn parse_i3_version(output: &str) -> Option<(u32, u32)> {
+    // Sway ships an `i3` compatibility name on some distros
+    let version = output.trim_start().strip_prefix("i3 version ")?;
+    let version = version.split_whitespace().next()?;
+    let mut parts = version.split('.').map(|part| {
+        let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<u32>().ok()
+    });
+    Some((parts.next()??, parts.next().flatten().unwrap_or(0)))
+}
 
-struct GSettings {
-    schema: &'static str,
-    list_key: &'static str,
-    path_prefix: &'static str,
-    binding_schema: &'static str,
+/// i3 understands `include` from 4.20 on
+fn i3_supports_include() -> bool {
+    Utils::run("i3", &["--version"])
+        .ok()
+        .and_then(|output| parse_i3_version(&output))
+        .is_some_and(|version| version >= (4, 20))
 }
 
-impl GSettings {
-    fn new_gnome() -> Self {
-        Self {
-            schema: "org.gnome.settings-daemon.plugins.media-keys",
-            list_key: "custom-keybindings",
-            path_prefix: "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings",
-            binding_schema: "org.gnome.settings-daemon.plugins.media-keys.custom-keybinding",
+/// Matches `text` against a shell glob with `*` and `?`
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was and how much text it has taken
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
         }
     }
+    pattern[p..].iter().all(|&c| c == '*')
+}
 
-    fn new_cinnamon() -> Self {
-        Self {
+/// A double-quoted Lua or Python string literal for `value`
+fn quoted_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The value from `awesome-client` output (`   string "x"`, `   boolean true`)
+fn parse_awesome_client_output(output: &str) -> Option<String> {
+    let (kind, value) = output.trim().split_once(' ')?;
+    let value = value.trim();
+    match kind {
+        "string" => value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .map(String::from),
+        _ => Some(value.to_string()),
+    }
+}
+
+/// Runs Lua in the running awesome and returns what it evaluated to
+fn awesome_eval(lua: &str) -> Result<Option<String>> {
+    Ok(parse_awesome_client_output(&Utils::run(
+        "awesome-client",
+        &[lua],
+    )?))
+}
+
+/// What the awesome keys bound to `binding` do, from their descriptions.
+/// `None` when awesome can't be asked.
+pub(crate) fn awesome_binds(binding: &KeyBinding) -> Option<Vec<String>> {
+    let wanted: Vec<String> = binding
+        .modifier_names(["Mod4", "Control", "Mod1", "Shift"])
+        .iter()
+        .map(|m| quoted_string(m))
+        .collect();
+    // awful.key adds variants with Lock/Mod2 too; the plain one is enough
+    let lua = format!(
+        r#"local wanted = {{ {} }}
+local found = {{}}
+for _, k in ipairs(root.keys()) do
+    local mods = k.modifiers or {{}}
+    local same = #mods == #wanted
+    for _, m in ipairs(wanted) do
+        local held = false
+        for _, n in ipairs(mods) do
+            if n == m then held = true end
+        end
+        same = same and held
+    end
+    if same and k.key:lower() == {} and k.group ~= "penguinclip" then
+        table.insert(found, k.description or "Unknown action")
+    end
+end
+return table.concat(found, "\n")"#,
+        wanted.join(", "),
+        quoted_string(&binding.key.to_lowercase())
+    );
+    let output = awesome_eval(&lua).ok()?.unwrap_or_default();
+    Some(
+        output
+            .lines()
+            .filter(|part| !part.is_empty())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Expands a leading `~` or `$HOME` in a config path
+fn expand_home(path: &str) -> String {
+    let home = env_var("HOME");
+    if let Some(rest) = path.strip_prefix('~') {
+        format!("{}{}", home, rest)
+    } else if let Some(rest) = path.strip_prefix("$HOME") {
+        format!("{}{}", home, rest)
+    } else {
+        path.to_string()
+    }
+}
+
+/// How deep we follow `include`s inside included files
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// The path an i3/Sway `include` line names, with `~` expanded
+fn include_target(line: &str) -> Option<String> {
+    let rest = line.trim().strip_prefix("include")?;
+    if !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some(expand_home(rest.trim().trim_matches(['"', '\''])))
+}
+
+/// The files a path with `*`/`?` globs names, sorted the way i3 and Sway
+/// load them
+fn expand_glob(pattern: &Path) -> Vec<PathBuf> {
+    let mut matches = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let part = component.as_os_str().to_string_lossy();
+        if !part.contains(['*', '?']) {
+            for path in matches.iter_mut() {
+                path.push(component);
+            }
+            continue;
+        }
+        matches = matches
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // Like the shell, wildcards skip hidden files
+                (part.starts_with('.') || !name.starts_with('.')) && glob_matches(&part, &name)
+            })
+            .map(|entry| entry.path())
+            .collect();
+    }
+    matches.retain(|path| path.is_file());
+    matches.sort();
+    matches
+}
+
+/// i3 or Sway configs and every file they pull in with `include`,
+/// recursively, each once and in load order. Missing files are left out.
+pub(crate) fn config_files_with_includes(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for root in roots {
+        collect_config_files(root, 0, &mut seen, &mut files);
+    }
+    files
+}
+
+fn collect_config_files(
+    path: &Path,
+    depth: usize,
+    seen: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) {
+    // Compare resolved paths so `a` including `../dir/a` stops too
+    let resolved = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if depth > MAX_INCLUDE_DEPTH || !seen.insert(resolved) {
+        return;
+    }
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+    files.push(path.to_path_buf());
+
+    // Relative includes are relative to the including file
+    let dir = path.parent().unwrap_or_else(|| Path::new("/"));
+    for target in content.lines().filter_map(include_target) {
+        for file in expand_glob(&dir.join(target)) {
+            collect_config_files(&file, depth + 1, seen, files);
+        }
+    }
+}
+
+/// Applies `edit` to the user's config at `main` and the files it includes,
+/// leaving out our drop-in and system files. Returns whether any changed.
+fn edit_included_configs(
+    main: &Path,
+    drop_in: &Path,
+    edit: impl Fn(&str) -> Option<String>,
+) -> Result<bool> {
+    let home = env_var("HOME");
+    let drop_in = fs::canonicalize(drop_in).unwrap_or_else(|_| drop_in.to_path_buf());
+    let mut modified = false;
+    for file in config_files_with_includes(&[main.to_path_buf()]) {
+        // System files like /etc/sway/config.d/* aren't ours to edit
+        if home.is_empty()
+            || !file.starts_with(&home)
+            || fs::canonicalize(&file).is_ok_and(|resolved| resolved == drop_in)
+        {
+            continue;
+        }
+        modified |= Utils::modify_file_atomic(&file, |content| Ok(edit(&content)))?;
+    }
+    Ok(modified)
+}
+
+const DROP_IN_HEADER: &str =
+    "# Managed by penguinclip; changes here are overwritten when shortcuts change";
+const INCLUDE_MARKER: &str = "# Clipboard History shortcuts (added by penguinclip)";
+
+/// A config file of our own that a tiling WM's main config pulls in with
+/// one `include`/`source` line, so the user's config is touched only once.
+struct DropIn {
+    /// Our file
+    path: PathBuf,
+    /// The WM's main config
+    main_config: PathBuf,
+    /// `include` (i3, Sway) or `source` (Hyprland, written `source = path`)
+    directive: &'static str,
+}
+
+impl DropIn {
+    /// A drop-in at `relative` to the main config's directory
+    fn new(main_config: PathBuf, relative: &str, directive: &'static str) -> Self {
+        let path = main_config
+            .parent()
+            .unwrap_or_else(|| Path::new("/"))
+            .join(relative);
+        Self {
+            path,
+            main_config,
+            directive,
+        }
+    }
+
+    /// The line we add to the main config, with `~` for the home directory
+    /// so synced dotfiles stay portable
+    fn include_line(&self) -> String {
+        let home = env_var("HOME");
+        let shown = match self.path.strip_prefix(&home) {
+            Ok(rest) if !home.is_empty() => format!("~/{}", rest.display()),
+            _ => self.path.display().to_string(),
+        };
+        if self.directive == "source" {
+            format!("source = {}", shown)
+        } else {
+            format!("{} {}", self.directive, shown)
+        }
+    }
+
+    /// Whether a main config line already pulls in our file, e.g. our own
+    /// line or `include ~/.config/sway/config.d/*`
+    fn includes_us(&self, line: &str) -> bool {
+        let target = if self.directive == "source" {
+            line.trim()
+                .strip_prefix("source")
+                .and_then(|rest| rest.trim_start().strip_prefix('='))
+                .map(|target| expand_home(target.trim().trim_matches(['"', '\''])))
+        } else {
+            include_target(line)
+        };
+        target.is_some_and(|target| glob_matches(&target, &self.path.to_string_lossy()))
+    }
+
+    fn contains(&self, binding_line: &str) -> Result<bool> {
+        let content = Utils::read_config(&self.path)?;
+        Ok(content.lines().any(|line| line.trim() == binding_line))
+    }
+
+    /// Adds `binding_line` to our file, and the include line to the main
+    /// config unless something there already pulls our file in. Returns
+    /// whether anything changed.
+    fn add(&self, binding_line: &str) -> Result<bool> {
+        let mut modified = false;
+        let content = Utils::read_config(&self.path)?;
+        if !content.lines().any(|line| line.trim() == binding_line) {
+            let mut content = if content.is_empty() {
+                format!("{}\n", DROP_IN_HEADER)
+            } else {
+                content
+            };
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(binding_line);
+            content.push('\n');
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Not `modify_file_atomic`: the file is ours alone, so there's
+            // nothing of the user's to keep in the backup store
+            atomic_write::write_atomic(&self.path, content.as_bytes())?;
+            modified = true;
+        }
+
+        modified |= Utils::modify_file_atomic(&self.main_config, |content| {
+            if content.lines().any(|line| self.includes_us(line)) {
+                return Ok(None);
+            }
+            let mut content = content;
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&format!("\n{}\n{}\n", INCLUDE_MARKER, self.include_line()));
+            Ok(Some(content))
+        })?;
+        Ok(modified)
+    }
+
+    /// Removes `binding_line` from our file. Once no bindings are left,
+    /// deletes the file and our include line. Returns whether anything
+    /// changed.
+    fn remove(&self, binding_line: &str) -> Result<bool> {
+        let content = Utils::read_config(&self.path)?;
+        if !content.lines().any(|line| line.trim() == binding_line) {
+            return Ok(false);
+        }
+
+        let remaining: Vec<&str> = content
+            .lines()
+            .filter(|line| line.trim() != binding_line)
+            .collect();
+        let has_bindings = remaining.iter().any(|line| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        });
+        if has_bindings {
+            let content = format!("{}\n", remaining.join("\n"));
+            atomic_write::write_atomic(&self.path, content.as_bytes())?;
+            return Ok(true);
+        }
+
+        fs::remove_file(&self.path)?;
+        let include_line = self.include_line();
+        Utils::modify_file_atomic(&self.main_config, |content| {
+            if !content.lines().any(|line| line.trim() == include_line) {
+                return Ok(None);
+            }
+            let mut lines: Vec<String> = Vec::new();
+            for line in content.lines() {
+                if line.trim() == include_line {
+                    // Drop our comment line above it too
+                    if lines.last().is_some_and(|l| l.trim() == INCLUDE_MARKER) {
+                        lines.pop();
+                    }
+                    continue;
+                }
+                lines.push(line.to_string());
+            }
+            Ok(Some(lines.join("\n")))
+        })?;
+        Ok(true)
+    }
+}
+
+// =============================================================================
+// Utilities
+// =============================================================================
+
+struct Utils;
+
+impl Utils {
+    /// Whether `cmd` is installed on the host, also from inside a sandbox
+    fn command_exists(cmd: &str) -> bool {
+        environment::host_command("which")
+            .arg(cmd)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Runs `cmd` on the host, see `environment::host_command`
+    fn run(cmd: &str, args: &[&str]) -> Result<String> {
+        let output = environment::host_command(cmd).args(args).output()?;
+
+        if !output.status.success() {
+            return Err(ShortcutError::CommandFailed {
+                cmd: cmd.to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// The XDG config directory, `~/.config` unless `XDG_CONFIG_HOME` moves it
+    fn config_dir() -> Result<PathBuf> {
+        paths::xdg_config_dir()
+            .ok_or_else(|| ShortcutError::UnsupportedEnvironment("HOME not set".into()))
+    }
+
+    /// Reads a config file, treating a missing one as empty
+    fn read_config(path: &Path) -> Result<String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Copies `content` into the shortcut backup store
+    fn backup_config(path: &Path, content: &str) -> Result<()> {
+        let backup = shortcut_backups::current_store().backup(path, content)?;
+        log::info!("backed up {} as {}", path.display(), backup.backup_file);
+        Ok(())
+    }
+
+    /// Reads a file, modifies content via callback, backs up the old content
+    /// to the shortcut backup store, then writes back atomically using a
+    /// temp file rename strategy.
+    /// Returns Ok(true) if file was modified, Ok(false) if no changes were needed.
+    fn modify_file_atomic<F>(path: &Path, modifier: F) -> Result<bool>
+    where
+        F: FnOnce(String) -> Result<Option<String>>,
+    {
+        if !path.exists() {
+            // Create directory structure if missing
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let existed = path.exists();
+        let content = if existed {
+            fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+
+        // Run modifier logic
+        let new_content = match modifier(content.clone()) {
+            Ok(Some(s)) => s,
+            Ok(None) => return Ok(false), // No changes needed
+            Err(e) => return Err(e),
+        };
+
+        // Keep the old contents so this change can be undone from the app
+        if existed {
+            Self::backup_config(path, &content)?;
+        }
+
+        // Atomic Write Strategy: Write to .tmp, then rename
+        atomic_write::write_atomic(path, new_content.as_bytes())?;
+
+        Ok(true) // File was modified
+    }
+}
+
+// =============================================================================
+// Implementations
+// =============================================================================
+
+// --- GNOME / Cinnamon Shared Logic ---
+
+struct GSettings {
+    schema: &'static str,
+    list_key: &'static str,
+    path_prefix: &'static str,
+    binding_schema: &'static str,
+}
+
+impl GSettings {
+    fn new_gnome() -> Self {
+        Self {
+            schema: "org.gnome.settings-daemon.plugins.media-keys",
+            list_key: "custom-keybindings",
+            path_prefix: "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings",
+            binding_schema: "org.gnome.settings-daemon.plugins.media-keys.custom-keybinding",
+        }
+    }
+
+    fn new_cinnamon() -> Self {
+        Self {
             schema: "org.cinnamon.desktop.keybindings",
             list_key: "custom-list",
             path_prefix: "/org/cinnamon/desktop/keybindings/custom-keybindings",
@@ -433,6 +1478,24 @@ impl GSettings {
         }
     }
 
+    /// Budgie 10.8+ runs its own fork of the GNOME settings daemon
+    fn new_budgie() -> Self {
+        Self {
+            schema: "org.buddiesofbudgie.settings-daemon.plugins.media-keys",
+            list_key: "custom-keybindings",
+            path_prefix:
+                "/org/buddiesofbudgie/settings-daemon/plugins/media-keys/custom-keybindings",
+            binding_schema:
+                "org.buddiesofbudgie.settings-daemon.plugins.media-keys.custom-keybinding",
+        }
+    }
+
+    /// Whether the schema is installed
+    fn is_installed(&self) -> bool {
+        Utils::run("gsettings", &["list-schemas"])
+            .is_ok_and(|schemas| schemas.lines().any(|line| line.trim() == self.schema))
+    }
+
     fn get_list(&self) -> Result<Vec<String>> {
         let output = Utils::run("gsettings", &["get", self.schema, self.list_key])?;
 
@@ -532,6 +1595,26 @@ impl GSettings {
         }
         Ok(())
     }
+
+    fn is_registered(&self, shortcut: &ShortcutConfig) -> Result<bool> {
+        if !Utils::command_exists("gsettings") {
+            return Err(ShortcutError::DependencyMissing("gsettings".into()));
+        }
+
+        let path = format!("{}/{}/", self.path_prefix, shortcut.id);
+        let entry_check = if self.path_prefix.contains("cinnamon") {
+            shortcut.id
+        } else {
+            &path
+        };
+        if !self.get_list()?.iter().any(|x| x.contains(entry_check)) {
+            return Ok(false);
+        }
+
+        let schema_path = format!("{}:{}", self.binding_schema, path);
+        let binding = Utils::run("gsettings", &["get", &schema_path, "binding"])?;
+        Ok(binding.contains(&format!("'{}'", shortcut.gnome_binding)))
+    }
 }
 
 // Wrappers
@@ -546,6 +1629,9 @@ impl ShortcutHandler for GnomeHandler {
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         GSettings::new_gnome().unregister(s)
     }
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        GSettings::new_gnome().is_registered(s)
+    }
 }
 
 struct CinnamonHandler;
@@ -559,37 +1645,200 @@ impl ShortcutHandler for CinnamonHandler {
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         GSettings::new_cinnamon().unregister(s)
     }
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        GSettings::new_cinnamon().is_registered(s)
+    }
+}
+
+/// Budgie's own settings daemon where installed, GNOME's before Budgie 10.8
+struct BudgieHandler;
+impl BudgieHandler {
+    fn gsettings() -> GSettings {
+        let budgie = GSettings::new_budgie();
+        if budgie.is_installed() {
+            budgie
+        } else {
+            GSettings::new_gnome()
+        }
+    }
+}
+
+impl ShortcutHandler for BudgieHandler {
+    fn name(&self) -> &str {
+        "Budgie"
+    }
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        Self::gsettings().register(s, false)
+    }
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        Self::gsettings().unregister(s)
+    }
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        Self::gsettings().is_registered(s)
+    }
 }
 
 // --- KDE Plasma Logic ---
 
+/// The `[services][<desktop file>]` group kglobalaccel keeps a launcher's
+/// shortcut in
+fn kde_service_group(desktop_file: &str) -> String {
+    format!("[services][{}]", desktop_file)
+}
+
+/// Sets `_launch=<binding>` in the service group of `desktop_file`, adding
+/// the group if it is missing
+fn set_kde_service_shortcut(content: &str, desktop_file: &str, binding: &str) -> Option<String> {
+    let group = kde_service_group(desktop_file);
+    let launch = format!("_launch={}", binding);
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+    match lines.iter().position(|line| line.trim() == group) {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| line.starts_with('['))
+                .map_or(lines.len(), |i| start + 1 + i);
+            match (start + 1..end).find(|&i| lines[i].starts_with("_launch=")) {
+                Some(i) if lines[i] == launch => return None,
+                Some(i) => lines[i] = launch,
+                None => lines.insert(start + 1, launch),
+            }
+        }
+        None => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(group);
+            lines.push(launch);
+        }
+    }
+    Some(lines.join("\n") + "\n")
+}
+
+/// Drops the service group of `desktop_file` along with the blank line
+/// that separated it from the group before
+fn remove_kde_service_shortcut(content: &str, desktop_file: &str) -> Option<String> {
+    let group = kde_service_group(desktop_file);
+    let mut new_lines: Vec<&str> = Vec::new();
+    let mut skip_block = false;
+    let mut removed = false;
+
+    for line in content.lines() {
+        if line.trim() == group {
+            skip_block = true;
+            removed = true;
+            if new_lines.last().is_some_and(|line| line.trim().is_empty()) {
+                new_lines.pop();
+            }
+            continue;
+        }
+        if line.starts_with('[') {
+            skip_block = false;
+        }
+        if !skip_block {
+            new_lines.push(line);
+        }
+    }
+
+    if !removed {
+        return None;
+    }
+    let mut new_content = new_lines.join("\n");
+    if !new_content.is_empty() {
+        new_content.push('\n');
+    }
+    Some(new_content)
+}
+
+/// The keys bound in the service group of `desktop_file`. Plasma 6
+/// separates several shortcuts for one action with tabs.
+fn kde_service_shortcuts<'a>(content: &'a str, desktop_file: &str) -> Vec<&'a str> {
+    let group = kde_service_group(desktop_file);
+    content
+        .lines()
+        .skip_while(|line| line.trim() != group)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.strip_prefix("_launch="))
+        .flat_map(|keys| keys.split('\t'))
+        .filter(|key| !key.is_empty() && *key != "none")
+        .collect()
+}
+
 struct KdeHandler;
 impl KdeHandler {
     fn get_config_path() -> Result<PathBuf> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        Ok(PathBuf::from(home).join(".config/khotkeysrc"))
+        Ok(Utils::config_dir()?.join("khotkeysrc"))
+    }
+
+    fn get_kglobalshortcuts_path() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("kglobalshortcutsrc"))
+    }
+
+    /// khotkeys was dropped in Plasma 6; when the version can't be told we
+    /// assume a current Plasma
+    fn uses_khotkeys() -> bool {
+        session::plasma_version().is_some_and(|major| major < 6)
+    }
+
+    /// Our own launcher for a shortcut. It must not be called
+    /// `penguinclip.desktop`, which would hide the app's menu entry.
+    fn desktop_file_name(s: &ShortcutConfig) -> String {
+        format!("{}-shortcut.desktop", s.id)
+    }
+
+    fn get_desktop_file_path(s: &ShortcutConfig) -> Result<PathBuf> {
+        let data_home = match env::var("XDG_DATA_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => {
+                let home = env::var("HOME")
+                    .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+                PathBuf::from(home).join(".local/share")
+            }
+        };
+        Ok(data_home
+            .join("applications")
+            .join(Self::desktop_file_name(s)))
+    }
+
+    /// A hidden launcher running the shortcut's command. kglobalaccel binds
+    /// shortcuts to desktop files, and reads `X-KDE-Shortcuts` as the default.
+    fn desktop_entry(s: &ShortcutConfig) -> String {
+        format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={}\nIcon=penguinclip\nNoDisplay=true\nStartupNotify=false\nX-KDE-Shortcuts={}\n",
+            s.name,
+            s.full_command(),
+            s.kde_binding
+        )
     }
 
     fn reload_kde() {
-        // Try both Plasma 5 and modern methods
-        let _ = Utils::run(
-            "qdbus",
-            &[
-                "org.kde.kglobalaccel",
+        let reloaded = zbus::blocking::Connection::session().and_then(|connection| {
+            connection.call_method(
+                Some("org.kde.kglobalaccel"),
                 "/kglobalaccel",
-                "org.kde.KGlobalAccel.reloadConfig",
-            ],
-        );
+                Some("org.kde.KGlobalAccel"),
+                "reloadConfig",
+                &(),
+            )
+        });
+        if let Err(e) = reloaded {
+            log::debug!("could not reload kglobalaccel: {}", e);
+        }
     }
-}
 
-impl ShortcutHandler for KdeHandler {
-    fn name(&self) -> &str {
-        "KDE Plasma"
+    /// Lets KService find a launcher we just wrote or removed
+    fn rebuild_service_cache() {
+        for cmd in ["kbuildsycoca6", "kbuildsycoca5"] {
+            if Utils::command_exists(cmd) {
+                let _ = Utils::run(cmd, &[]);
+                return;
+            }
+        }
     }
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+    fn register_khotkeys(s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
         let section_name = format!("Data_{}", s.id.replace('-', "_"));
 
@@ -643,12 +1892,10 @@ impl ShortcutHandler for KdeHandler {
             lines.push(entry);
             Ok(Some(lines.join("\n")))
         })?;
-
-        Self::reload_kde();
         Ok(())
     }
 
-    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+    fn unregister_khotkeys(s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
         let section_name = format!("Data_{}", s.id.replace('-', "_"));
 
@@ -677,10 +1924,107 @@ impl ShortcutHandler for KdeHandler {
             }
             Ok(Some(new_lines.join("\n")))
         })?;
+        Ok(())
+    }
+
+    fn is_registered_khotkeys(s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let trigger = format!("[Data_{}/Triggers/Trigger0]", s.id.replace('-', "_"));
+        let Some(start) = content.find(&trigger) else {
+            return Ok(false);
+        };
+
+        let key_line = format!("Key={}", s.kde_binding);
+        Ok(content[start..]
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.starts_with('['))
+            .any(|line| line.trim() == key_line))
+    }
+
+    /// Plasma 6: a hidden launcher plus its `[services]` group in kglobalshortcutsrc
+    fn register_service(s: &ShortcutConfig) -> Result<()> {
+        let desktop_path = Self::get_desktop_file_path(s)?;
+        let entry = Self::desktop_entry(s);
+        let wrote_launcher = Utils::modify_file_atomic(&desktop_path, |content| {
+            Ok((content != entry).then(|| entry.clone()))
+        })?;
+        if wrote_launcher {
+            Self::rebuild_service_cache();
+        }
+
+        let desktop_file = Self::desktop_file_name(s);
+        Utils::modify_file_atomic(&Self::get_kglobalshortcuts_path()?, |content| {
+            Ok(set_kde_service_shortcut(
+                &content,
+                &desktop_file,
+                &s.kde_binding,
+            ))
+        })?;
+        Ok(())
+    }
+
+    fn unregister_service(s: &ShortcutConfig) -> Result<()> {
+        let desktop_file = Self::desktop_file_name(s);
+        Utils::modify_file_atomic(&Self::get_kglobalshortcuts_path()?, |content| {
+            Ok(remove_kde_service_shortcut(&content, &desktop_file))
+        })?;
+
+        match fs::remove_file(Self::get_desktop_file_path(s)?) {
+            Ok(()) => Self::rebuild_service_cache(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(())
+    }
+
+    fn is_registered_service(s: &ShortcutConfig) -> Result<bool> {
+        if !Self::get_desktop_file_path(s)?.exists() {
+            return Ok(false);
+        }
+        let content = Utils::read_config(&Self::get_kglobalshortcuts_path()?)?;
+        Ok(kde_service_shortcuts(&content, &Self::desktop_file_name(s))
+            .iter()
+            .any(|key| key.eq_ignore_ascii_case(&s.kde_binding)))
+    }
+}
+
+impl ShortcutHandler for KdeHandler {
+    fn name(&self) -> &str {
+        "KDE Plasma"
+    }
 
+    fn reload(&self) {
+        Self::reload_kde();
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        if Self::uses_khotkeys() {
+            Self::register_khotkeys(s)?;
+        } else {
+            Self::register_service(s)?;
+        }
+        Self::reload_kde();
+        Ok(())
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        if Self::uses_khotkeys() {
+            Self::unregister_khotkeys(s)?;
+        } else {
+            Self::unregister_service(s)?;
+        }
         Self::reload_kde();
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        if Self::uses_khotkeys() {
+            Self::is_registered_khotkeys(s)
+        } else {
+            Self::is_registered_service(s)
+        }
+    }
 }
 
 // --- XFCE ---
@@ -698,7 +2042,7 @@ impl ShortcutHandler for XfceHandler {
         let property = format!("/commands/custom/{}", s.xfce_binding);
 
         // Check if exists to avoid error spam
-        let exists = Command::new("xfconf-query")
+        let exists = environment::host_command("xfconf-query")
             .args(["-c", "xfce4-keyboard-shortcuts", "-p", &property])
             .output()
             .map(|o| o.status.success())
@@ -735,6 +2079,22 @@ impl ShortcutHandler for XfceHandler {
         );
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        if !Utils::command_exists("xfconf-query") {
+            return Err(ShortcutError::DependencyMissing("xfconf-query".into()));
+        }
+        let property = format!("/commands/custom/{}", s.xfce_binding);
+        match Utils::run(
+            "xfconf-query",
+            &["-c", "xfce4-keyboard-shortcuts", "-p", &property],
+        ) {
+            Ok(command) => Ok(command == s.full_command()),
+            // The property doesn't exist
+            Err(ShortcutError::CommandFailed { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
 }
 
 // --- MATE ---
@@ -781,7 +2141,7 @@ impl ShortcutHandler for MateHandler {
                         "set",
                         "org.mate.Marco.global-keybindings",
                         &binding_key,
-                        s.gnome_binding,
+                        &s.gnome_binding,
                     ],
                 )?;
                 return Ok(());
@@ -821,85 +2181,167 @@ impl ShortcutHandler for MateHandler {
         }
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        if !Utils::command_exists("gsettings") {
+            return Err(ShortcutError::DependencyMissing("gsettings".into()));
+        }
+        let full_cmd = s.full_command();
+        for i in 1..=12 {
+            let current = Utils::run(
+                "gsettings",
+                &[
+                    "get",
+                    "org.mate.Marco.keybinding-commands",
+                    &format!("command-{}", i),
+                ],
+            )?;
+            if current.trim_matches('\'') == full_cmd {
+                let binding = Utils::run(
+                    "gsettings",
+                    &[
+                        "get",
+                        "org.mate.Marco.global-keybindings",
+                        &format!("run-command-{}", i),
+                    ],
+                )?;
+                return Ok(binding.trim_matches('\'') == s.gnome_binding);
+            }
+        }
+        Ok(false)
+    }
 }
 
 // --- COSMIC (Epoch 1.0+) ---
 
-// Indentation constants for COSMIC RON format
-const COSMIC_ENTRY_INDENT: &str = "    ";
-const COSMIC_FIELD_INDENT: &str = "        ";
-const COSMIC_MODIFIER_INDENT: &str = "            ";
+/// A key combination in COSMIC's custom shortcuts file, the map key of
+/// each entry: `(modifiers: [Super], key: "v", description: Some("..."))`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct CosmicBinding {
+    modifiers: Vec<Modifier>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keycode: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+/// The only action we write. Other actions (`System(Terminal)`,
+/// `Disable`, ...) are kept as raw RON and written back untouched.
+#[derive(Serialize, Deserialize)]
+enum CosmicAction {
+    Spawn(String),
+}
+
+/// COSMIC's custom shortcuts, binding to action. A BTreeMap so the file is
+/// always written in the same order.
+type CosmicShortcuts = std::collections::BTreeMap<CosmicBinding, Box<ron::value::RawValue>>;
+
+fn cosmic_parse_error(e: impl std::fmt::Display) -> ShortcutError {
+    ShortcutError::ParseError(format!("COSMIC shortcuts: {}", e))
+}
+
+fn parse_cosmic_shortcuts(content: &str) -> Result<CosmicShortcuts> {
+    if content.trim().is_empty() {
+        return Ok(CosmicShortcuts::new());
+    }
+    let shortcuts: CosmicShortcuts = ron::from_str(content).map_err(cosmic_parse_error)?;
+    // Raw values start with the whitespace after the `:`
+    Ok(shortcuts
+        .into_iter()
+        .map(|(binding, action)| (binding, action.trim_boxed()))
+        .collect())
+}
+
+/// Writes the shortcuts the way COSMIC Settings does. Comments in the
+/// original file are lost; the backup taken before each edit keeps them.
+fn write_cosmic_shortcuts(shortcuts: &CosmicShortcuts) -> Result<String> {
+    let mut content = ron::ser::to_string_pretty(shortcuts, ron::ser::PrettyConfig::default())
+        .map_err(cosmic_parse_error)?;
+    content.push('\n');
+    Ok(content)
+}
+
+fn is_cosmic_spawn(action: &ron::value::RawValue, command: &str) -> bool {
+    matches!(action.into_rust::<CosmicAction>(), Ok(CosmicAction::Spawn(c)) if c == command)
+}
+
+/// What the entries of a COSMIC shortcuts file bound to `s`'s keys do,
+/// other than ours. `None` if the file can't be parsed.
+pub(crate) fn cosmic_actions_bound_to(content: &str, s: &ShortcutConfig) -> Option<Vec<String>> {
+    let ours = CosmicHandler::binding(s);
+    let actions = parse_cosmic_shortcuts(content)
+        .ok()?
+        .into_iter()
+        .filter(|(binding, _)| CosmicHandler::same_keys(binding, &ours))
+        .filter(|(_, action)| !action.get_ron().contains("penguinclip"))
+        .map(|(binding, action)| {
+            binding
+                .description
+                .unwrap_or_else(|| action.get_ron().to_string())
+        })
+        .collect();
+    Some(actions)
+}
 
 struct CosmicHandler;
 impl CosmicHandler {
-    /// Escape special characters for RON string format
-    fn escape_ron_string(s: &str) -> String {
-        s.replace('\\', "\\\\")
-            .replace('"', "\\\"")
-            .replace('\n', "\\n")
-            .replace('\r', "\\r")
-            .replace('\t', "\\t")
-    }
-
-    /// Format modifiers for COSMIC RON format - each on its own line
-    /// Input: "Super" or "Ctrl, Alt" -> properly formatted RON array entries
-    fn format_modifiers(mods: &str) -> String {
-        let formatted: Vec<String> = mods
-            .split(',')
-            .map(|m| m.trim())
-            .filter(|m| !m.is_empty())
-            .map(|m| {
-                // Normalize modifier names to COSMIC's expected format
-                let normalized: String = match m.to_lowercase().as_str() {
-                    "ctrl" | "control" => "Ctrl".to_string(),
-                    "alt" => "Alt".to_string(),
-                    "super" | "meta" => "Super".to_string(),
-                    "shift" => "Shift".to_string(),
-                    _ => {
-                        // Fallback: normalize capitalization (First letter uppercase + rest lowercase)
-                        let mut chars = m.chars();
-                        match chars.next() {
-                            Some(first) => {
-                                let mut result = first.to_uppercase().to_string();
-                                result.push_str(&chars.as_str().to_lowercase());
-                                result
-                            }
-                            None => String::new(),
-                        }
-                    }
-                };
-                format!("{}{},", COSMIC_MODIFIER_INDENT, normalized)
-            })
-            .collect();
-        formatted.join("\n")
+    fn get_config_path() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom"))
     }
 
-    /// Build a COSMIC shortcut entry in proper RON format
-    fn build_entry(s: &ShortcutConfig) -> String {
-        let mods_formatted = Self::format_modifiers(s.cosmic_mods);
-        let full_cmd = Self::escape_ron_string(&s.full_command());
-        let name = Self::escape_ron_string(s.name);
-        let key = Self::escape_ron_string(s.cosmic_key);
+    fn binding(s: &ShortcutConfig) -> CosmicBinding {
+        CosmicBinding {
+            modifiers: s
+                .cosmic_mods
+                .split(',')
+                .filter_map(|m| Modifier::parse(m.trim()))
+                .collect(),
+            key: s.cosmic_key.clone(),
+            keycode: None,
+            description: Some(s.name.to_string()),
+        }
+    }
 
-        format!(
-            r#"{}(
-{}modifiers: [
-{}
-{}],
-{}key: "{}",
-{}description: Some("{}"),
-{}): Spawn("{}"),"#,
-            COSMIC_ENTRY_INDENT,
-            COSMIC_FIELD_INDENT,
-            mods_formatted,
-            COSMIC_FIELD_INDENT,
-            COSMIC_FIELD_INDENT,
-            key,
-            COSMIC_FIELD_INDENT,
-            name,
-            COSMIC_ENTRY_INDENT,
-            full_cmd
-        )
+    /// Whether `binding` is the key combination of `ours`, whatever its
+    /// description
+    fn same_keys(binding: &CosmicBinding, ours: &CosmicBinding) -> bool {
+        let mut modifiers = binding.modifiers.clone();
+        modifiers.sort();
+        modifiers.dedup();
+        modifiers == ours.modifiers && binding.key.eq_ignore_ascii_case(&ours.key)
+    }
+
+    /// Adds our entry, unless the key combination already runs our command
+    fn add_entry(content: &str, s: &ShortcutConfig) -> Result<Option<String>> {
+        let mut shortcuts = parse_cosmic_shortcuts(content)?;
+        let ours = Self::binding(s);
+        let command = s.full_command();
+        if shortcuts.iter().any(|(binding, action)| {
+            Self::same_keys(binding, &ours) && is_cosmic_spawn(action, &command)
+        }) {
+            return Ok(None);
+        }
+        let action = ron::value::RawValue::from_rust(&CosmicAction::Spawn(command))
+            .map_err(cosmic_parse_error)?;
+        shortcuts.insert(ours, action);
+        write_cosmic_shortcuts(&shortcuts).map(Some)
+    }
+
+    /// Removes every entry running our command on our key combination
+    fn remove_entry(content: &str, s: &ShortcutConfig) -> Result<Option<String>> {
+        let mut shortcuts = parse_cosmic_shortcuts(content)?;
+        let ours = Self::binding(s);
+        let command = s.full_command();
+        let before = shortcuts.len();
+        shortcuts.retain(|binding, action| {
+            !(Self::same_keys(binding, &ours) && is_cosmic_spawn(action, &command))
+        });
+        if shortcuts.len() == before {
+            return Ok(None);
+        }
+        write_cosmic_shortcuts(&shortcuts).map(Some)
     }
 }
 
@@ -909,147 +2351,62 @@ impl ShortcutHandler for CosmicHandler {
     }
 
     fn register(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        let path = PathBuf::from(home)
-            .join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom");
-
-        let full_cmd = s.full_command();
-        let entry = Self::build_entry(s);
-
-        Utils::modify_file_atomic(&path, |content| {
-            // Check if this command is already registered to avoid duplicates
-            if content.contains(&format!("Spawn(\"{}\")", full_cmd)) {
-                return Ok(None);
-            }
-
-            let trimmed = content.trim();
-
-            // If file is empty or doesn't start with '{', create new structure
-            if trimmed.is_empty() {
-                return Ok(Some(format!("{{\n{}\n}}", entry)));
-            }
-
-            // File should be a RON map: { ... }
-            if !trimmed.starts_with('{') {
-                // Reject unexpected formats instead of trying to wrap potentially malformed content
-                return Err(ShortcutError::ParseError(
-                    "Invalid COSMIC config format - expected RON map starting with '{'".into(),
-                ));
-            }
-
-            // Find the last '}' and insert before it
-            if let Some(pos) = content.rfind('}') {
-                let mut new_content = content.to_string();
-                new_content.insert_str(pos, &format!("{}\n", entry));
-                return Ok(Some(new_content));
-            }
-
-            Err(ShortcutError::ParseError(
-                "Invalid COSMIC config format - missing closing brace".into(),
-            ))
-        })?;
+        let path = Self::get_config_path()?;
+        Utils::modify_file_atomic(&path, |content| Self::add_entry(&content, s))?;
         Ok(())
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME").unwrap_or_default();
-        let path = PathBuf::from(home)
-            .join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom");
+        let path = Self::get_config_path()?;
 
         if !path.exists() {
             return Ok(());
         }
 
-        let full_cmd = s.full_command();
-        let spawn_pattern = format!("Spawn(\"{}\")", full_cmd);
-
-        Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(&spawn_pattern) {
-                return Ok(None);
-            }
-
-            // Parse and remove the entry block containing our command
-            // RON format: (key_tuple): Value, - we track depth to find entry boundaries
-            // depth starts at 0 before the opening '{'; depth 1 = inside outer map {}, depth 2+ = inside an entry
-            let mut result = String::new();
-            let mut depth = 0;
-            let mut in_entry = false;
-            let mut entry_start = 0;
-            let mut prev_depth: i32;
-
-            for c in content.chars() {
-                prev_depth = depth;
-
-                // Update depth first
-                if c == '{' || c == '(' {
-                    depth += 1;
-                } else if c == '}' || c == ')' {
-                    depth -= 1;
-                }
-
-                // Detect entry start: '(' that takes us from depth 1 to depth 2
-                if c == '(' && prev_depth == 1 && depth == 2 {
-                    entry_start = result.len();
-                    in_entry = true;
-                }
-
-                result.push(c);
-
-                // Detect entry end: ',' when we're at depth 1 (after the Spawn(...) closed)
-                if in_entry && depth == 1 && c == ',' {
-                    // Check if this entry contains our command
-                    let entry_content = &result[entry_start..];
-                    if entry_content.contains(&spawn_pattern) {
-                        // Remove this entry (including leading whitespace)
-                        let trim_start = result[..entry_start].trim_end().len();
-                        result.truncate(trim_start);
-                        result.push('\n');
-                    }
-                    in_entry = false;
-                }
-            }
-
-            // Clean up sequences of more than two consecutive newlines in a single pass
-            let mut cleaned = String::with_capacity(result.len());
-            let mut newline_count = 0;
-            for ch in result.chars() {
-                if ch == '\n' {
-                    if newline_count < 2 {
-                        cleaned.push('\n');
-                    }
-                    newline_count += 1;
-                } else {
-                    newline_count = 0;
-                    cleaned.push(ch);
-                }
-            }
-
-            Ok(Some(cleaned))
-        })?;
+        Utils::modify_file_atomic(&path, |content| Self::remove_entry(&content, s))?;
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let ours = Self::binding(s);
+        let command = s.full_command();
+        Ok(parse_cosmic_shortcuts(&content)?
+            .iter()
+            .any(|(binding, action)| {
+                Self::same_keys(binding, &ours) && is_cosmic_spawn(action, &command)
+            }))
+    }
 }
 
 // --- LXQt ---
 
 struct LxqtHandler;
+impl LxqtHandler {
+    fn get_config_path() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("lxqt/globalkeyshortcuts.conf"))
+    }
+
+    /// The INI section for a shortcut: URL-encoded keybinding followed by
+    /// shortcut ID. Only characters problematic for INI format are encoded:
+    /// / \ [ ] = ; # and spaces
+    fn section(s: &ShortcutConfig) -> String {
+        let encoded_binding = utf8_percent_encode(&s.kde_binding, INI_SECTION_ENCODE).to_string();
+        format!("{}/{}", encoded_binding, s.id)
+    }
+}
+
 impl ShortcutHandler for LxqtHandler {
     fn name(&self) -> &str {
         "LXQt"
     }
 
     fn register(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        let path = PathBuf::from(home).join(".config/lxqt/globalkeyshortcuts.conf");
+        let path = Self::get_config_path()?;
 
         let full_cmd = s.full_command();
         // LXQt uses INI format for shortcuts
-        // Section name is URL-encoded keybinding followed by shortcut ID
-        // Only encode characters problematic for INI format: / \ [ ] = ; # and spaces
-        let encoded_binding = utf8_percent_encode(s.kde_binding, INI_SECTION_ENCODE).to_string();
-        let section = format!("{}/{}", encoded_binding, s.id);
+        let section = Self::section(s);
         let entry = format!(
             "\n[{}]\nComment={}\nEnabled=true\nExec={}",
             section, s.name, full_cmd
@@ -1068,17 +2425,13 @@ impl ShortcutHandler for LxqtHandler {
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-        let path = PathBuf::from(home).join(".config/lxqt/globalkeyshortcuts.conf");
+        let path = Self::get_config_path()?;
 
         if !path.exists() {
             return Ok(());
         }
 
-        // Use same encoding as register for consistency
-        let encoded_binding = utf8_percent_encode(s.kde_binding, INI_SECTION_ENCODE).to_string();
-        let section = format!("{}/{}", encoded_binding, s.id);
+        let section = Self::section(s);
 
         Utils::modify_file_atomic(&path, |content| {
             if !content.contains(&format!("[{}]", section)) {
@@ -1105,194 +2458,397 @@ impl ShortcutHandler for LxqtHandler {
         })?;
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        Ok(content
+            .lines()
+            .any(|line| line.trim() == format!("[{}]", Self::section(s))))
+    }
 }
 
 // --- LXDE (Openbox) ---
 
-struct LxdeHandler;
-impl ShortcutHandler for LxdeHandler {
-    fn name(&self) -> &str {
-        "LXDE/Openbox"
-    }
+/// A `<keybind>` element of an Openbox rc.xml
+struct OpenboxKeybind {
+    key: String,
+    /// Unescaped text of its `<command>` (or legacy `<execute>`) elements
+    commands: Vec<String>,
+    /// Byte range of the whole element in the file
+    span: std::ops::Range<usize>,
+}
 
-    fn register(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+/// The `<keyboard>` section of an Openbox rc.xml
+struct OpenboxKeyboard {
+    keybinds: Vec<OpenboxKeybind>,
+    /// Byte range of `</keyboard>`, or of the whole element when it is an
+    /// empty `<keyboard/>`
+    close: std::ops::Range<usize>,
+    self_closing: bool,
+    /// Namespace prefix of the keyboard tag, `ob:` in `<ob:keyboard>`, for
+    /// the tags we add to be in the same namespace
+    prefix: String,
+}
 
-        // LXDE uses Openbox for window management
-        let path = PathBuf::from(&home).join(".config/openbox/lxde-rc.xml");
+impl OpenboxKeyboard {
+    /// Reads the keyboard section with an XML parser, so indentation,
+    /// attributes and namespace prefixes don't matter. Only byte offsets are
+    /// kept: edits splice text in, leaving the rest of the file untouched.
+    fn parse(content: &str) -> Result<Self> {
+        use quick_xml::events::Event;
 
-        // Fallback to default openbox config if LXDE-specific doesn't exist
-        let path = if path.exists() {
-            path
-        } else {
-            PathBuf::from(&home).join(".config/openbox/rc.xml")
+        let parse_error = |e: quick_xml::Error, at: u64| {
+            ShortcutError::ParseError(format!("{} at byte {}", e, at))
         };
-
-        if !path.exists() {
-            return Err(ShortcutError::Io(io::Error::new(
-                io::ErrorKind::NotFound,
-                "Openbox config not found",
-            )));
-        }
-
-        let full_cmd = s.full_command();
-        // The keybind XML to add - use the LXDE/Openbox-specific binding
-        // Escape XML special characters to prevent XML injection
-        let escaped_binding = escape_xml(s.lxde_binding);
-        let escaped_cmd = escape_xml(&full_cmd);
-        let keybind = format!(
-            r#"    <keybind key="{}">
-      <action name="Execute">
-        <command>{}</command>
-      </action>
-    </keybind>"#,
-            escaped_binding, escaped_cmd
-        );
-
-        Utils::modify_file_atomic(&path, |content| {
-            if content.contains(&format!("<command>{}</command>", escaped_cmd)) {
-                return Ok(None); // Already exists
-            }
-
-            // Find the </keyboard> closing tag and insert before it
-            if let Some(pos) = content.find("</keyboard>") {
-                let mut new_content = content.clone();
-                new_content.insert_str(pos, &format!("{}\n  ", keybind));
-
-                // Trigger openbox reconfigure
-                let _ = Utils::run("openbox", &["--reconfigure"]);
-
-                return Ok(Some(new_content));
+        let mut reader = quick_xml::Reader::from_str(content);
+        let mut in_keyboard = false;
+        let mut close = None;
+        let mut prefix = String::new();
+        // Keybinds being read (keychains nest them): key, start, commands
+        let mut open: Vec<(String, usize, Vec<String>)> = Vec::new();
+        let mut keybinds = Vec::new();
+        // Raw, still escaped text of the command element being read
+        let mut command: Option<String> = None;
+
+        loop {
+            let start = reader.buffer_position() as usize;
+            let event = reader
+                .read_event()
+                .map_err(|e| parse_error(e, reader.error_position()))?;
+            let end = reader.buffer_position() as usize;
+            match event {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"keyboard" if close.is_none() => {
+                        in_keyboard = true;
+                        prefix = openbox_prefix(&e);
+                    }
+                    b"keybind" if in_keyboard => {
+                        open.push((openbox_key_attribute(&e)?, start, Vec::new()));
+                    }
+                    b"command" | b"execute" if !open.is_empty() => command = Some(String::new()),
+                    _ => {}
+                },
+                Event::Empty(e) => match e.local_name().as_ref() {
+                    b"keyboard" if close.is_none() => {
+                        return Ok(Self {
+                            keybinds,
+                            close: start..end,
+                            self_closing: true,
+                            prefix: openbox_prefix(&e),
+                        });
+                    }
+                    b"keybind" if in_keyboard => keybinds.push(OpenboxKeybind {
+                        key: openbox_key_attribute(&e)?,
+                        commands: Vec::new(),
+                        span: start..end,
+                    }),
+                    _ => {}
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"keyboard" if in_keyboard => {
+                        in_keyboard = false;
+                        close = Some(start..end);
+                    }
+                    b"keybind" if in_keyboard => {
+                        if let Some((key, kb_start, commands)) = open.pop() {
+                            keybinds.push(OpenboxKeybind {
+                                key,
+                                commands,
+                                span: kb_start..end,
+                            });
+                        }
+                    }
+                    b"command" | b"execute" => {
+                        if let (Some(raw), Some((_, _, commands))) =
+                            (command.take(), open.last_mut())
+                        {
+                            let text = quick_xml::escape::unescape(&raw)
+                                .map_err(|e| ShortcutError::ParseError(e.to_string()))?;
+                            commands.push(text.trim().to_string());
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Text(e) => {
+                    if let Some(raw) = command.as_mut() {
+                        raw.push_str(&e.decode().map_err(|e| parse_error(e.into(), end as u64))?);
+                    }
+                }
+                Event::GeneralRef(e) => {
+                    if let Some(raw) = command.as_mut() {
+                        let name = e.decode().map_err(|e| parse_error(e.into(), end as u64))?;
+                        raw.push_str(&format!("&{};", name));
+                    }
+                }
+                Event::CData(e) => {
+                    if let Some(raw) = command.as_mut() {
+                        let text = e.decode().map_err(|e| parse_error(e.into(), end as u64))?;
+                        raw.push_str(&quick_xml::escape::escape(text.as_ref()));
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
             }
+        }
 
-            Err(ShortcutError::ParseError(
-                "Could not find </keyboard> in Openbox config".into(),
-            ))
+        let close = close.ok_or_else(|| {
+            ShortcutError::ParseError("No <keyboard> section in Openbox config".into())
         })?;
-        Ok(())
+        Ok(Self {
+            keybinds,
+            close,
+            self_closing: false,
+            prefix,
+        })
     }
 
-    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-
-        let path = PathBuf::from(&home).join(".config/openbox/lxde-rc.xml");
-        let path = if path.exists() {
-            path
-        } else {
-            PathBuf::from(&home).join(".config/openbox/rc.xml")
-        };
-
-        if !path.exists() {
-            return Ok(());
-        }
+    fn find<'a>(
+        &'a self,
+        key: &'a str,
+        command: &'a str,
+    ) -> impl Iterator<Item = &'a OpenboxKeybind> {
+        self.keybinds.iter().filter(move |kb| {
+            kb.key.eq_ignore_ascii_case(key) && kb.commands.iter().any(|c| c == command)
+        })
+    }
+}
 
-        let full_cmd = s.full_command();
-        let escaped_binding = escape_xml(s.lxde_binding);
-        let escaped_cmd = escape_xml(&full_cmd);
+fn openbox_prefix(e: &quick_xml::events::BytesStart) -> String {
+    e.name()
+        .prefix()
+        .map(|p| format!("{}:", String::from_utf8_lossy(p.as_ref())))
+        .unwrap_or_default()
+}
 
-        Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(&format!("<command>{}</command>", escaped_cmd)) {
-                return Ok(None);
-            }
+fn openbox_key_attribute(e: &quick_xml::events::BytesStart) -> Result<String> {
+    let key = e
+        .try_get_attribute("key")
+        .map_err(|e| ShortcutError::ParseError(e.to_string()))?;
+    match key {
+        Some(attr) => Ok(attr
+            .normalized_value(quick_xml::XmlVersion::Implicit1_0)
+            .map_err(|e| ShortcutError::ParseError(e.to_string()))?
+            .into_owned()),
+        None => Ok(String::new()),
+    }
+}
 
-            // Remove the keybind block - this is a simplified approach
-            // A proper XML parser would be better but adds dependency
-            let pattern = format!(
-                r#"    <keybind key="{}">
-      <action name="Execute">
-        <command>{}</command>
-      </action>
-    </keybind>"#,
-                escaped_binding, escaped_cmd
-            );
+/// The whitespace before `pos` when nothing else precedes it on its line
+fn line_indent(content: &str, pos: usize) -> Option<&str> {
+    let line_start = content[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &content[line_start..pos];
+    indent
+        .chars()
+        .all(|c| c == ' ' || c == '\t')
+        .then_some(indent)
+}
 
-            let new_content = content.replace(&pattern, "");
+/// Adds a keybind running `command` on `key` at the end of the keyboard
+/// section, indented like the keybinds already there
+fn add_openbox_keybind(content: &str, key: &str, command: &str) -> Result<Option<String>> {
+    let keyboard = OpenboxKeyboard::parse(content)?;
+    if keyboard.find(key, command).next().is_some() {
+        return Ok(None);
+    }
 
-            // Trigger openbox reconfigure
-            let _ = Utils::run("openbox", &["--reconfigure"]);
+    let close_indent = line_indent(content, keyboard.close.start).unwrap_or("");
+    let indent = keyboard
+        .keybinds
+        .first()
+        .and_then(|kb| line_indent(content, kb.span.start))
+        .map_or_else(|| format!("{}  ", close_indent), str::to_string);
+    let p = &keyboard.prefix;
+    let keybind = format!(
+        "{i}<{p}keybind key=\"{key}\">\n\
+         {i}  <{p}action name=\"Execute\">\n\
+         {i}    <{p}command>{command}</{p}command>\n\
+         {i}  </{p}action>\n\
+         {i}</{p}keybind>\n",
+        i = indent,
+        key = escape_xml(key),
+        command = escape_xml(command),
+    );
 
-            Ok(Some(new_content))
-        })?;
-        Ok(())
+    let mut new_content = content.to_string();
+    if keyboard.self_closing {
+        new_content.replace_range(
+            keyboard.close.clone(),
+            &format!("<{p}keyboard>\n{}{}</{p}keyboard>", keybind, close_indent),
+        );
+    } else if line_indent(content, keyboard.close.start).is_some() {
+        // `</keyboard>` starts its line: the keybind goes on the lines before
+        let line_start = keyboard.close.start - close_indent.len();
+        new_content.insert_str(line_start, &keybind);
+    } else {
+        new_content.insert_str(keyboard.close.start, &format!("\n{}", keybind));
     }
+    Ok(Some(new_content))
 }
 
-// --- i3 Window Manager ---
-
-struct I3Handler;
-impl I3Handler {
-    fn get_config_path() -> Result<PathBuf> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
-
-        // Check common i3 config locations
-        let paths = vec![
-            PathBuf::from(&home).join(".config/i3/config"),
-            PathBuf::from(&home).join(".i3/config"),
-        ];
+/// Removes every keybind running `command` on `key`, with the lines they
+/// were on, whatever their formatting
+fn remove_openbox_keybind(content: &str, key: &str, command: &str) -> Result<Option<String>> {
+    let keyboard = OpenboxKeyboard::parse(content)?;
+    let spans: Vec<_> = keyboard
+        .find(key, command)
+        .map(|kb| kb.span.clone())
+        .collect();
+    if spans.is_empty() {
+        return Ok(None);
+    }
 
-        for path in paths {
-            if path.exists() {
-                return Ok(path);
+    let mut new_content = content.to_string();
+    // From the end, so earlier offsets stay valid
+    for span in spans.into_iter().rev() {
+        let mut start = span.start;
+        let mut end = span.end;
+        if let Some(indent) = line_indent(content, start) {
+            let rest = &content[end..];
+            let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+            if rest[..line_end].trim().is_empty() {
+                start -= indent.len();
+                end += line_end;
             }
         }
-
-        // Default to the XDG config path
-        Ok(PathBuf::from(&home).join(".config/i3/config"))
+        new_content.replace_range(start..end, "");
     }
+    Ok(Some(new_content))
+}
 
-    fn reload_i3() {
-        // Send reload command to i3
-        let _ = Utils::run("i3-msg", &["reload"]);
+struct LxdeHandler;
+impl LxdeHandler {
+    /// LXDE uses Openbox for window management; falls back to the default
+    /// openbox config if the LXDE-specific one doesn't exist
+    fn get_config_path() -> Result<PathBuf> {
+        let dir = Utils::config_dir()?.join("openbox");
+        let path = dir.join("lxde-rc.xml");
+        if path.exists() {
+            Ok(path)
+        } else {
+            Ok(dir.join("rc.xml"))
+        }
     }
 }
 
-impl ShortcutHandler for I3Handler {
+impl ShortcutHandler for LxdeHandler {
     fn name(&self) -> &str {
-        "i3"
+        "LXDE/Openbox"
+    }
+
+    fn reload(&self) {
+        let _ = Utils::run("openbox", &["--reconfigure"]);
     }
 
     fn register(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
 
-        let full_cmd = s.full_command();
-        // i3 binding format: bindsym $mod+v exec command
-        let binding_line = format!("bindsym {} exec {}", s.i3_binding, full_cmd);
-
+        if !path.exists() {
+            return Err(ShortcutError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Openbox config not found",
+            )));
+        }
+
         let modified = Utils::modify_file_atomic(&path, |content| {
-            // Check if already registered
-            if content.contains(&full_cmd) {
-                return Ok(None);
-            }
+            add_openbox_keybind(&content, &s.lxde_binding, &s.full_command())
+        })?;
+        if modified {
+            self.reload();
+        }
+        Ok(())
+    }
 
-            // Check for existing $mod+v binding and comment it out
-            let mut lines: Vec<String> = content.lines().map(String::from).collect();
-            let mut had_existing = false;
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
 
-            for line in lines.iter_mut() {
-                let trimmed = line.trim().to_lowercase();
-                // Skip if already a comment
-                if trimmed.starts_with('#') {
-                    continue;
-                }
-                // Check for existing mod+v bindings (word boundary check)
-                if trimmed.starts_with("bindsym") && has_mod_v_binding(&trimmed) {
-                    *line = format!("# {} # Commented by penguinclip", line);
-                    had_existing = true;
-                }
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let modified = Utils::modify_file_atomic(&path, |content| {
+            remove_openbox_keybind(&content, &s.lxde_binding, &s.full_command())
+        })?;
+        if modified {
+            self.reload();
+        }
+        Ok(())
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        if content.is_empty() {
+            return Ok(false);
+        }
+        Ok(OpenboxKeyboard::parse(&content)?
+            .find(&s.lxde_binding, &s.full_command())
+            .next()
+            .is_some())
+    }
+}
+
+// --- i3 Window Manager ---
+
+struct I3Handler;
+impl I3Handler {
+    fn get_config_path() -> Result<PathBuf> {
+        let xdg_path = Utils::config_dir()?.join("i3/config");
+
+        // Check common i3 config locations
+        let mut paths = vec![xdg_path.clone()];
+        if let Ok(home) = env::var("HOME") {
+            paths.push(PathBuf::from(home).join(".i3/config"));
+        }
+
+        for path in paths {
+            if path.exists() {
+                return Ok(path);
             }
+        }
 
-            // Add our binding at the end
-            lines.push("\n# Clipboard History (added by penguinclip)".to_string());
-            lines.push(binding_line.clone());
+        // Default to the XDG config path
+        Ok(xdg_path)
+    }
+
+    /// Our bindings live in `config.d/penguinclip.conf` next to the config
+    fn drop_in() -> Result<DropIn> {
+        Ok(DropIn::new(
+            Self::get_config_path()?,
+            "config.d/penguinclip.conf",
+            "include",
+        ))
+    }
+
+    /// i3 binding format: bindsym $mod+v exec command
+    fn binding_line(s: &ShortcutConfig) -> String {
+        format!("bindsym {} exec {}", s.i3_binding, s.full_command())
+    }
+
+    fn reload_i3() {
+        // Send reload command to i3
+        let _ = Utils::run("i3-msg", &["reload"]);
+    }
+
+    /// For i3 before 4.20, which has no `include`: appends the binding to
+    /// the main config, commenting out existing bindings of the same keys
+    fn register_in_place(s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        let binding_line = Self::binding_line(s);
+        let binding = s.i3_binding.to_lowercase();
+
+        let modified = Utils::modify_file_atomic(&path, |content| {
+            let Some((new_content, had_existing)) =
+                add_binding_line(&content, &binding_line, |l| {
+                    l.starts_with("bindsym") && has_i3_binding(l, &binding)
+                })
+            else {
+                return Ok(None);
+            };
 
             if had_existing {
-                println!("[i3Handler] Commented out existing $mod+v binding(s)");
+                log::info!("Commented out existing {} binding(s)", s.i3_binding);
             }
 
-            Ok(Some(lines.join("\n")))
+            Ok(Some(new_content))
         })?;
 
         // Reload i3 only after file was successfully written
@@ -1301,57 +2857,82 @@ impl ShortcutHandler for I3Handler {
         }
         Ok(())
     }
+}
 
-    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let path = Self::get_config_path()?;
+impl ShortcutHandler for I3Handler {
+    fn name(&self) -> &str {
+        "i3"
+    }
 
-        if !path.exists() {
-            return Ok(());
+    fn reload(&self) {
+        Self::reload_i3();
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        if !i3_supports_include() {
+            return Self::register_in_place(s);
         }
 
-        let full_cmd = s.full_command();
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(&full_cmd) {
-                return Ok(None);
-            }
+        let path = Self::get_config_path()?;
+        let drop_in = Self::drop_in()?;
+        let binding_line = Self::binding_line(s);
+        let binding = s.i3_binding.to_lowercase();
 
-            let lines: Vec<&str> = content.lines().collect();
-            let mut new_lines: Vec<String> = Vec::new();
-            let mut skip_comment = false;
+        // Move a binding an older version added in place to the drop-in
+        let migrated = Utils::modify_file_atomic(&path, |content| {
+            Ok(remove_binding_line(&content, &binding_line, |_| false))
+        })?;
+        // i3 rejects a key bound twice, wherever the other binding lives
+        let commented = edit_included_configs(&path, &drop_in.path, |content| {
+            comment_out_lines(content, |l| {
+                l.starts_with("bindsym") && has_i3_binding(l, &binding)
+            })
+        })?;
+        if commented {
+            log::info!("Commented out existing {} binding(s)", s.i3_binding);
+        }
+        let added = drop_in.add(&binding_line)?;
 
-            for line in lines {
-                // Skip our comment line
-                if line.contains("# Clipboard History (added by penguinclip)") {
-                    skip_comment = true;
-                    continue;
-                }
-                // Skip our binding line
-                if skip_comment && line.contains(&full_cmd) {
-                    skip_comment = false;
-                    continue;
-                }
-                skip_comment = false;
-
-                // Restore commented out bindings
-                if line.contains("# Commented by penguinclip") {
-                    let restored = line
-                        .replace("# ", "")
-                        .replace(" # Commented by penguinclip", "");
-                    new_lines.push(restored);
-                } else {
-                    new_lines.push(line.to_string());
-                }
-            }
+        // Reload i3 only after file was successfully written
+        if migrated || commented || added {
+            Self::reload_i3();
+        }
+        Ok(())
+    }
 
-            Ok(Some(new_lines.join("\n")))
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        let drop_in = Self::drop_in()?;
+        let binding_line = Self::binding_line(s);
+        let binding = s.i3_binding.to_lowercase();
+
+        // Also clean up a binding added in place
+        let in_place = path.exists()
+            && Utils::modify_file_atomic(&path, |content| {
+                Ok(remove_binding_line(&content, &binding_line, |l| {
+                    l.starts_with("bindsym") && has_i3_binding(l, &binding)
+                }))
+            })?;
+        let restored = edit_included_configs(&path, &drop_in.path, |content| {
+            restore_lines(content, |l| {
+                l.starts_with("bindsym") && has_i3_binding(l, &binding)
+            })
         })?;
+        let dropped = drop_in.remove(&binding_line)?;
 
         // Reload i3 only after file was successfully written
-        if modified {
+        if in_place || restored || dropped {
             Self::reload_i3();
         }
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let binding_line = Self::binding_line(s);
+        Ok(content.lines().any(|line| line.trim() == binding_line)
+            || Self::drop_in()?.contains(&binding_line)?)
+    }
 }
 
 // --- Sway ---
@@ -1359,13 +2940,12 @@ impl ShortcutHandler for I3Handler {
 struct SwayHandler;
 impl SwayHandler {
     fn get_config_path() -> Result<PathBuf> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+        let xdg_path = Utils::config_dir()?.join("sway/config");
 
-        let paths = vec![
-            PathBuf::from(&home).join(".config/sway/config"),
-            PathBuf::from(&home).join(".sway/config"),
-        ];
+        let mut paths = vec![xdg_path.clone()];
+        if let Ok(home) = env::var("HOME") {
+            paths.push(PathBuf::from(home).join(".sway/config"));
+        }
 
         for path in paths {
             if path.exists() {
@@ -1373,7 +2953,20 @@ impl SwayHandler {
             }
         }
 
-        Ok(PathBuf::from(&home).join(".config/sway/config"))
+        Ok(xdg_path)
+    }
+
+    /// Our bindings live in `config.d/penguinclip.conf` next to the config
+    fn drop_in() -> Result<DropIn> {
+        Ok(DropIn::new(
+            Self::get_config_path()?,
+            "config.d/penguinclip.conf",
+            "include",
+        ))
+    }
+
+    fn binding_line(s: &ShortcutConfig) -> String {
+        format!("bindsym {} exec {}", s.sway_binding, s.full_command())
     }
 
     fn reload_sway() {
@@ -1386,44 +2979,33 @@ impl ShortcutHandler for SwayHandler {
         "Sway"
     }
 
+    fn reload(&self) {
+        Self::reload_sway();
+    }
+
     fn register(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
+        let drop_in = Self::drop_in()?;
+        let binding_line = Self::binding_line(s);
+        let binding = s.sway_binding.to_lowercase();
 
-        let full_cmd = s.full_command();
-        let binding_line = format!("bindsym {} exec {}", s.sway_binding, full_cmd);
-
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            if content.contains(&full_cmd) {
-                return Ok(None);
-            }
-
-            let mut lines: Vec<String> = content.lines().map(String::from).collect();
-            let mut had_existing = false;
-
-            for line in lines.iter_mut() {
-                let trimmed = line.trim().to_lowercase();
-                if trimmed.starts_with('#') {
-                    continue;
-                }
-                // Check for existing mod+v bindings (word boundary check)
-                if trimmed.starts_with("bindsym") && has_mod_v_binding(&trimmed) {
-                    *line = format!("# {} # Commented by penguinclip", line);
-                    had_existing = true;
-                }
-            }
-
-            lines.push("\n# Clipboard History (added by penguinclip)".to_string());
-            lines.push(binding_line.clone());
-
-            if had_existing {
-                println!("[SwayHandler] Commented out existing $mod+v binding(s)");
-            }
-
-            Ok(Some(lines.join("\n")))
+        // Move a binding an older version added in place to the drop-in
+        let migrated = Utils::modify_file_atomic(&path, |content| {
+            Ok(remove_binding_line(&content, &binding_line, |_| false))
+        })?;
+        // Sway would only warn about the duplicate and keep the later binding
+        let commented = edit_included_configs(&path, &drop_in.path, |content| {
+            comment_out_lines(content, |l| {
+                l.starts_with("bindsym") && has_i3_binding(l, &binding)
+            })
         })?;
+        if commented {
+            log::info!("Commented out existing {} binding(s)", s.sway_binding);
+        }
+        let added = drop_in.add(&binding_line)?;
 
         // Reload Sway only after file was successfully written
-        if modified {
+        if migrated || commented || added {
             Self::reload_sway();
         }
         Ok(())
@@ -1431,51 +3013,37 @@ impl ShortcutHandler for SwayHandler {
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
-
-        if !path.exists() {
-            return Ok(());
-        }
-
-        let full_cmd = s.full_command();
-        let modified = Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(&full_cmd) {
-                return Ok(None);
-            }
-
-            let lines: Vec<&str> = content.lines().collect();
-            let mut new_lines: Vec<String> = Vec::new();
-            let mut skip_comment = false;
-
-            for line in lines {
-                if line.contains("# Clipboard History (added by penguinclip)") {
-                    skip_comment = true;
-                    continue;
-                }
-                if skip_comment && line.contains(&full_cmd) {
-                    skip_comment = false;
-                    continue;
-                }
-                skip_comment = false;
-
-                if line.contains("# Commented by penguinclip") {
-                    let restored = line
-                        .replace("# ", "")
-                        .replace(" # Commented by penguinclip", "");
-                    new_lines.push(restored);
-                } else {
-                    new_lines.push(line.to_string());
-                }
-            }
-
-            Ok(Some(new_lines.join("\n")))
+        let drop_in = Self::drop_in()?;
+        let binding_line = Self::binding_line(s);
+        let binding = s.sway_binding.to_lowercase();
+
+        // Also clean up a binding an older version added in place
+        let in_place = path.exists()
+            && Utils::modify_file_atomic(&path, |content| {
+                Ok(remove_binding_line(&content, &binding_line, |l| {
+                    l.starts_with("bindsym") && has_i3_binding(l, &binding)
+                }))
+            })?;
+        let restored = edit_included_configs(&path, &drop_in.path, |content| {
+            restore_lines(content, |l| {
+                l.starts_with("bindsym") && has_i3_binding(l, &binding)
+            })
         })?;
+        let dropped = drop_in.remove(&binding_line)?;
 
         // Reload Sway only after file was successfully written
-        if modified {
+        if in_place || restored || dropped {
             Self::reload_sway();
         }
         Ok(())
     }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let binding_line = Self::binding_line(s);
+        Ok(content.lines().any(|line| line.trim() == binding_line)
+            || Self::drop_in()?.contains(&binding_line)?)
+    }
 }
 
 // --- Hyprland ---
@@ -1483,14 +3051,45 @@ impl ShortcutHandler for SwayHandler {
 struct HyprlandHandler;
 impl HyprlandHandler {
     fn get_config_path() -> Result<PathBuf> {
-        let home = env::var("HOME")
-            .map_err(|_| ShortcutError::UnsupportedEnvironment("HOME not set".into()))?;
+        Ok(Utils::config_dir()?.join("hypr/hyprland.conf"))
+    }
+
+    /// Our binds live in `penguinclip.conf` next to `hyprland.conf`
+    fn drop_in() -> Result<DropIn> {
+        Ok(DropIn::new(
+            Self::get_config_path()?,
+            "penguinclip.conf",
+            "source",
+        ))
+    }
+
+    /// Hyprland format: bind = SUPER, V, exec, command
+    fn binding_line(s: &ShortcutConfig) -> String {
+        format!("bind = {}", Self::bind_value(s))
+    }
+
+    /// The value of a bind, as `hyprctl keyword bind` takes it
+    fn bind_value(s: &ShortcutConfig) -> String {
+        format!("{}, exec, {}", s.hyprland_binding, s.full_command())
+    }
 
-        let xdg_config =
-            env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
+    fn is_ours(s: &ShortcutConfig, bind: &HyprlandBind) -> bool {
+        bind.binds(&s.hyprland_binding)
+            && bind.dispatcher == "exec"
+            && bind.arg.trim() == s.full_command()
+    }
 
-        let path = PathBuf::from(&xdg_config).join("hypr/hyprland.conf");
-        Ok(path)
+    fn hyprctl_keyword(keyword: &str, value: &str) -> Result<()> {
+        // hyprctl exits successfully and prints the error for a bad keyword
+        let output = Utils::run("hyprctl", &["keyword", keyword, value])?;
+        if output == "ok" {
+            Ok(())
+        } else {
+            Err(ShortcutError::CommandFailed {
+                cmd: format!("hyprctl keyword {}", keyword),
+                stderr: output,
+            })
+        }
     }
 }
 
@@ -1501,87 +3100,1721 @@ impl ShortcutHandler for HyprlandHandler {
 
     fn register(&self, s: &ShortcutConfig) -> Result<()> {
         let path = Self::get_config_path()?;
+        let binding_line = Self::binding_line(s);
 
-        let full_cmd = s.full_command();
-        // Hyprland format: bind = SUPER, V, exec, command
-        let binding_line = format!("bind = {}, exec, {}", s.hyprland_binding, full_cmd);
+        // Apply the bind for this session first. Writing the config below
+        // makes Hyprland reload, which drops runtime binds and reads ours
+        // back from the drop-in, so it ends up bound once either way.
+        if let Some(binds) = hyprland_runtime_binds() {
+            for bind in binds
+                .iter()
+                .filter(|b| b.binds(&s.hyprland_binding) && !Self::is_ours(s, b))
+            {
+                log::warn!(
+                    "{} is already bound to `{}` in Hyprland; both will run",
+                    s.hyprland_binding,
+                    bind.action()
+                );
+            }
+            if !binds.iter().any(|b| Self::is_ours(s, b)) {
+                Self::hyprctl_keyword("bind", &Self::bind_value(s))?;
+            }
+        }
 
+        // Move a bind an older version added in place to the drop-in, and
+        // give back the user's binds it commented out: the drop-in never
+        // touches them, so a later unregister couldn't
         Utils::modify_file_atomic(&path, |content| {
-            if content.contains(&full_cmd) {
-                return Ok(None);
-            }
+            Ok(remove_binding_line(&content, &binding_line, |l| {
+                has_hyprland_binding(l, &s.hyprland_binding)
+            }))
+        })?;
+        Self::drop_in()?.add(&binding_line)?;
+        Ok(())
+    }
 
-            let mut lines: Vec<String> = content.lines().map(String::from).collect();
-            let mut modified = false;
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        let binding_line = Self::binding_line(s);
+
+        // Also clean up a bind an older version added in place
+        if path.exists() {
+            Utils::modify_file_atomic(&path, |content| {
+                Ok(remove_binding_line(&content, &binding_line, |l| {
+                    has_hyprland_binding(l, &s.hyprland_binding)
+                }))
+            })?;
+        }
+        Self::drop_in()?.remove(&binding_line)?;
 
-            for line in lines.iter_mut() {
-                let trimmed = line.trim().to_lowercase();
-                if trimmed.starts_with('#') {
-                    continue;
-                }
-                // Check for existing SUPER, V bindings
-                if trimmed.starts_with("bind")
-                    && trimmed.contains("super")
-                    && (trimmed.contains(", v,") || trimmed.contains(",v,"))
-                {
-                    *line = format!("# {} # Commented by penguinclip", line);
-                    modified = true;
-                }
-            }
+        // Not `hyprctl keyword unbind`, which drops the user's binds of the
+        // same keys too. Hyprland reloads by itself after the write above
+        // unless autoreload is off; reloading drops only our bind.
+        if hyprland_runtime_binds().is_some_and(|binds| binds.iter().any(|b| Self::is_ours(s, b))) {
+            Utils::run("hyprctl", &["reload"])?;
+        }
+        Ok(())
+    }
+
+    fn reload(&self) {
+        let _ = Utils::run("hyprctl", &["reload"]);
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        if let Some(binds) = hyprland_runtime_binds() {
+            return Ok(binds.iter().any(|b| Self::is_ours(s, b)));
+        }
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let binding_line = Self::binding_line(s);
+        Ok(content.lines().any(|line| line.trim() == binding_line)
+            || Self::drop_in()?.contains(&binding_line)?)
+    }
+}
+
+// --- awesome ---
+
+const AWESOME_MODULE_HEADER: &str = r#"-- Managed by penguinclip; changes here are overwritten when shortcuts change
+-- Load it from rc.lua with: require("penguinclip")
+local awful = require("awful")
+penguinclip_keys = penguinclip_keys or {}"#;
+
+/// Whether an rc.lua line loads our module
+fn requires_penguinclip(line: &str) -> bool {
+    let line = line.trim();
+    if line.starts_with("--") {
+        return false;
+    }
+    let compact: String = line.split_whitespace().collect();
+    [
+        "require(\"penguinclip\")",
+        "require('penguinclip')",
+        "require\"penguinclip\"",
+        "require'penguinclip'",
+    ]
+    .iter()
+    .any(|call| compact.contains(call))
+}
+
+/// rc.lua is a program, too risky to edit: we add keys at runtime through
+/// `awesome-client` and keep them in a module rc.lua can `require`.
+struct AwesomeHandler;
+impl AwesomeHandler {
+    fn config_dir() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("awesome"))
+    }
+
+    /// The Lua registering `s` once, kept on one line so the module can be
+    /// edited line by line
+    fn key_line(s: &ShortcutConfig) -> String {
+        let slot = format!("penguinclip_keys[{}]", quoted_string(s.id));
+        format!(
+            "if not {slot} then {slot} = awful.key({}, function() awful.spawn({}) end, \
+             {{ description = {}, group = \"penguinclip\" }}) \
+             awful.keyboard.append_global_keybinding({slot}) end",
+            s.awesome_binding,
+            quoted_string(&s.full_command()),
+            quoted_string(s.name),
+        )
+    }
+
+    /// Whether rc.lua loads our module
+    fn module_required() -> Result<bool> {
+        let content = Utils::read_config(&Self::config_dir()?.join("rc.lua"))?;
+        Ok(content.lines().any(requires_penguinclip))
+    }
+}
 
-            lines.push("\n# Clipboard History (added by penguinclip)".to_string());
-            lines.push(binding_line.clone());
+impl ShortcutHandler for AwesomeHandler {
+    fn name(&self) -> &str {
+        "awesome"
+    }
 
-            if modified {
-                println!("[HyprlandHandler] Commented out existing SUPER+V binding(s)");
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        let key_line = Self::key_line(s);
+        let path = Self::config_dir()?.join("penguinclip.lua");
+        let content = Utils::read_config(&path)?;
+        if !content.lines().any(|line| line == key_line) {
+            let mut content = if content.is_empty() {
+                format!("{}\n", AWESOME_MODULE_HEADER)
+            } else {
+                content
+            };
+            content.push_str(&key_line);
+            content.push('\n');
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
             }
+            atomic_write::write_atomic(&path, content.as_bytes())?;
+        }
 
-            // Hyprland auto-reloads config, no explicit reload needed
-            Ok(Some(lines.join("\n")))
-        })?;
+        // Bind it for this session too. The module is saved either way, so
+        // awesome not answering only delays the shortcut to its next start.
+        if let Err(e) = awesome_eval(&format!("{}\n{}", AWESOME_MODULE_HEADER, key_line)) {
+            log::warn!("couldn't bind {} in the running awesome: {}", s.name, e);
+        }
+
+        if !Self::module_required()? {
+            log::info!(
+                "Add require(\"penguinclip\") to rc.lua to keep {} after a restart",
+                s.name
+            );
+        }
         Ok(())
     }
 
     fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
-        let path = Self::get_config_path()?;
+        let slot = format!("penguinclip_keys[{}]", quoted_string(s.id));
+        // Nothing to unbind when awesome isn't running
+        let _ = awesome_eval(&format!(
+            "local awful = require(\"awful\") if penguinclip_keys and {slot} then \
+             awful.keyboard.remove_global_keybinding({slot}) {slot} = nil end"
+        ));
 
-        if !path.exists() {
-            return Ok(());
+        let path = Self::config_dir()?.join("penguinclip.lua");
+        let key_line = Self::key_line(s);
+        let content = Utils::read_config(&path)?;
+        if content.lines().any(|line| line == key_line) {
+            let content: String = content
+                .lines()
+                .filter(|line| *line != key_line)
+                .map(|line| format!("{}\n", line))
+                .collect();
+            atomic_write::write_atomic(&path, content.as_bytes())?;
         }
+        Ok(())
+    }
 
-        let full_cmd = s.full_command();
-        Utils::modify_file_atomic(&path, |content| {
-            if !content.contains(&full_cmd) {
-                return Ok(None);
-            }
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let slot = format!("penguinclip_keys[{}]", quoted_string(s.id));
+        if let Ok(Some(bound)) =
+            awesome_eval(&format!("return penguinclip_keys ~= nil and {slot} ~= nil"))
+        {
+            return Ok(bound == "true");
+        }
 
-            let lines: Vec<&str> = content.lines().collect();
-            let mut new_lines: Vec<String> = Vec::new();
-            let mut skip_comment = false;
+        // Without awesome to ask, it counts once rc.lua loads our module
+        let content = Utils::read_config(&Self::config_dir()?.join("penguinclip.lua"))?;
+        let key_line = Self::key_line(s);
+        Ok(content.lines().any(|line| line == key_line) && Self::module_required()?)
+    }
+}
 
-            for line in lines {
-                if line.contains("# Clipboard History (added by penguinclip)") {
-                    skip_comment = true;
-                    continue;
-                }
-                if skip_comment && line.contains(&full_cmd) {
-                    skip_comment = false;
-                    continue;
-                }
-                skip_comment = false;
-
-                if line.contains("# Commented by penguinclip") {
-                    let restored = line
-                        .replace("# ", "")
-                        .replace(" # Commented by penguinclip", "");
-                    new_lines.push(restored);
-                } else {
-                    new_lines.push(line.to_string());
-                }
-            }
+// --- qtile ---
 
-            Ok(Some(new_lines.join("\n")))
-        })?;
-        Ok(())
+const QTILE_BLOCK_START: &str = "# >>> penguinclip shortcuts >>>";
+const QTILE_BLOCK_END: &str = "# <<< penguinclip shortcuts <<<";
+const QTILE_BLOCK_HEADER: &str = r#"# Added by PenguinClip; delete this block to remove its shortcuts
+from libqtile.config import Key as _PenguinclipKey
+from libqtile.lazy import lazy as _penguinclip_lazy"#;
+
+/// Adds `key_line` to our sentinel-delimited block at the end of
+/// config.py, creating the block if needed. `None` if it's already there.
+fn add_qtile_key(content: &str, key_line: &str) -> Option<String> {
+    if content.lines().any(|line| line == key_line) {
+        return None;
+    }
+    let mut lines: Vec<&str> = content.lines().collect();
+    match lines.iter().position(|line| line.trim() == QTILE_BLOCK_END) {
+        Some(end) => lines.insert(end, key_line),
+        None => {
+            lines.push("");
+            lines.push(QTILE_BLOCK_START);
+            lines.extend(QTILE_BLOCK_HEADER.lines());
+            lines.push(key_line);
+            lines.push(QTILE_BLOCK_END);
+        }
+    }
+    Some(format!("{}\n", lines.join("\n")))
+}
+
+/// Removes `key_line`, and the whole block once it has no keys left.
+/// `None` if the line isn't there.
+fn remove_qtile_key(content: &str, key_line: &str) -> Option<String> {
+    if !content.lines().any(|line| line == key_line) {
+        return None;
+    }
+    let mut lines: Vec<&str> = content.lines().filter(|line| *line != key_line).collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim() == QTILE_BLOCK_START);
+    let end = lines.iter().position(|line| line.trim() == QTILE_BLOCK_END);
+    if let (Some(start), Some(end)) = (start, end) {
+        let has_keys = lines[start..end]
+            .iter()
+            .any(|line| line.starts_with("keys.append("));
+        if !has_keys && start < end {
+            lines.drain(start..=end);
+            // And the blank line we put before it
+            if start > 0 && lines.get(start - 1).is_some_and(|l| l.trim().is_empty()) {
+                lines.remove(start - 1);
+            }
+        }
+    }
+    Some(format!("{}\n", lines.join("\n")))
+}
+
+/// Whether a config.py line binds `binding` (`["mod4"], "v"`) with `Key`,
+/// spelled with `mod` or the modifier names
+pub(crate) fn has_qtile_binding(line: &str, binding: &str) -> bool {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return false;
+    }
+    let compact = |text: &str| -> String {
+        text.split_whitespace()
+            .collect::<String>()
+            .replace('\'', "\"")
+            .to_lowercase()
+    };
+    let line = compact(line)
+        .replace("[mod,", "[\"mod4\",")
+        .replace("[mod]", "[\"mod4\"]");
+    line.contains(&format!("key({}", compact(binding)))
+}
+
+struct QtileHandler;
+impl QtileHandler {
+    fn get_config_path() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("qtile/config.py"))
+    }
+
+    fn key_line(s: &ShortcutConfig) -> String {
+        format!(
+            "keys.append(_PenguinclipKey({}, _penguinclip_lazy.spawn({}), desc={}))",
+            s.qtile_binding,
+            quoted_string(&s.full_command()),
+            quoted_string(s.name)
+        )
+    }
+
+    fn reload_qtile() {
+        let _ = Utils::run("qtile", &["cmd-obj", "-o", "cmd", "-f", "reload_config"]);
+    }
+}
+
+impl ShortcutHandler for QtileHandler {
+    fn name(&self) -> &str {
+        "qtile"
+    }
+
+    fn reload(&self) {
+        Self::reload_qtile();
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        // Configs split into a package have no single file to append to
+        if !path.exists() {
+            return Err(ShortcutError::UnsupportedEnvironment(format!(
+                "{} not found; add the shortcut to your qtile config manually",
+                path.display()
+            )));
+        }
+        let key_line = Self::key_line(s);
+
+        let modified = Utils::modify_file_atomic(&path, |content| {
+            // Editing the user's Python is off the table, so only warn
+            if content
+                .lines()
+                .any(|line| line != key_line && has_qtile_binding(line, &s.qtile_binding))
+            {
+                log::warn!(
+                    "{} is already bound in {}; qtile will run both",
+                    s.qtile_binding,
+                    path.display()
+                );
+            }
+            Ok(add_qtile_key(&content, &key_line))
+        })?;
+
+        // Reload qtile only after file was successfully written
+        if modified {
+            Self::reload_qtile();
+        }
+        Ok(())
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        if !path.exists() {
+            return Ok(());
+        }
+        let key_line = Self::key_line(s);
+
+        let modified =
+            Utils::modify_file_atomic(&path, |content| Ok(remove_qtile_key(&content, &key_line)))?;
+
+        // Reload qtile only after file was successfully written
+        if modified {
+            Self::reload_qtile();
+        }
+        Ok(())
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let key_line = Self::key_line(s);
+        Ok(content.lines().any(|line| line == key_line))
+    }
+}
+
+// --- River ---
+
+/// A single-quoted shell word for `value`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Whether an init/rc.d line maps `binding` (`Super V`) in normal mode
+pub(crate) fn has_river_mapping(line: &str, binding: &str) -> bool {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((mods, key)) = binding.split_once(' ') else {
+        return false;
+    };
+    words.len() >= 5
+        && words[..3] == ["riverctl", "map", "normal"]
+        && words[3].eq_ignore_ascii_case(mods)
+        && words[4].eq_ignore_ascii_case(key)
+}
+
+/// River is set up by an `init` script; we map keys at runtime through
+/// `riverctl` and, when `init` runs the files of `rc.d`, persist them there.
+struct RiverHandler;
+impl RiverHandler {
+    fn config_dir() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("river"))
+    }
+
+    fn snippet_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("rc.d/penguinclip"))
+    }
+
+    /// Whether `init` runs the files in our snippet's directory
+    fn init_sources_rc_d() -> Result<bool> {
+        let content = Utils::read_config(&Self::config_dir()?.join("init"))?;
+        Ok(content.lines().any(|line| {
+            let line = line.trim();
+            !line.starts_with('#') && line.contains("rc.d")
+        }))
+    }
+
+    fn map_line(s: &ShortcutConfig) -> String {
+        format!(
+            "riverctl map normal {} spawn {}",
+            s.river_binding,
+            shell_quote(&s.full_command())
+        )
+    }
+}
+
+impl ShortcutHandler for RiverHandler {
+    fn name(&self) -> &str {
+        "River"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        let (mods, key) = s.river_binding.split_once(' ').unwrap_or_default();
+        // Mapping the same keys again replaces the old mapping
+        Utils::run(
+            "riverctl",
+            &["map", "normal", mods, key, "spawn", &s.full_command()],
+        )?;
+
+        if !Self::init_sources_rc_d()? {
+            log::info!(
+                "Add `{}` to your River init to keep {} after a restart",
+                Self::map_line(s),
+                s.name
+            );
+            return Ok(());
+        }
+
+        let path = Self::snippet_path()?;
+        let map_line = Self::map_line(s);
+        let content = Utils::read_config(&path)?;
+        if !content.lines().any(|line| line == map_line) {
+            let mut content = if content.is_empty() {
+                format!("#!/bin/sh\n{}\n", DROP_IN_HEADER)
+            } else {
+                content
+            };
+            content.push_str(&map_line);
+            content.push('\n');
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            atomic_write::write_atomic(&path, content.as_bytes())?;
+            // Some init scripts run rc.d files instead of sourcing them
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let (mods, key) = s.river_binding.split_once(' ').unwrap_or_default();
+        // Nothing to unmap when River isn't running
+        let _ = Utils::run("riverctl", &["unmap", "normal", mods, key]);
+
+        let path = Self::snippet_path()?;
+        let map_line = Self::map_line(s);
+        let content = Utils::read_config(&path)?;
+        if content.lines().any(|line| line == map_line) {
+            let content: String = content
+                .lines()
+                .filter(|line| *line != map_line)
+                .map(|line| format!("{}\n", line))
+                .collect();
+            atomic_write::write_atomic(&path, content.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        // River can't list its mappings, so look where they'd be persisted
+        let dir = Self::config_dir()?;
+        let command = s.full_command();
+        for path in [Self::snippet_path()?, dir.join("init")] {
+            let content = Utils::read_config(&path)?;
+            if content
+                .lines()
+                .any(|line| has_river_mapping(line, &s.river_binding) && line.contains(&command))
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+// --- Wayfire ---
+
+/// Sets `key = value` pairs in an INI `[section]`, adding the section if
+/// missing. `None` if every pair is already set.
+fn set_ini_values(content: &str, section: &str, values: &[(String, String)]) -> Option<String> {
+    let header = format!("[{}]", section);
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let mut changed = false;
+
+    let Some(start) = lines.iter().position(|line| line.trim() == header) else {
+        if !lines.last().map_or(true, |line| line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(header);
+        lines.extend(
+            values
+                .iter()
+                .map(|(key, value)| format!("{} = {}", key, value)),
+        );
+        return Some(format!("{}\n", lines.join("\n")));
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map_or(lines.len(), |offset| start + 1 + offset);
+
+    let mut insert_at = end;
+    // Keep new entries before the blank lines ending the section
+    while insert_at > start + 1 && lines[insert_at - 1].trim().is_empty() {
+        insert_at -= 1;
+    }
+    for (key, value) in values {
+        let wanted = format!("{} = {}", key, value);
+        let existing = lines[start + 1..insert_at]
+            .iter()
+            .position(|line| line.split_once('=').is_some_and(|(k, _)| k.trim() == key));
+        match existing {
+            Some(offset) if lines[start + 1 + offset].trim() == wanted => {}
+            Some(offset) => {
+                lines[start + 1 + offset] = wanted;
+                changed = true;
+            }
+            None => {
+                lines.insert(insert_at, wanted);
+                insert_at += 1;
+                changed = true;
+            }
+        }
+    }
+    changed.then(|| format!("{}\n", lines.join("\n")))
+}
+
+/// Removes `keys` from an INI `[section]`. `None` if none are set.
+fn remove_ini_values(content: &str, section: &str, keys: &[String]) -> Option<String> {
+    let header = format!("[{}]", section);
+    let mut in_section = false;
+    let mut changed = false;
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                in_section = trimmed == header;
+            }
+            let remove = in_section
+                && trimmed
+                    .split_once('=')
+                    .is_some_and(|(key, _)| keys.iter().any(|k| k == key.trim()));
+            changed |= remove;
+            !remove
+        })
+        .collect();
+    changed.then(|| format!("{}\n", lines.join("\n")))
+}
+
+/// Wayfire's `command` plugin runs `command_NAME` on `binding_NAME`
+struct WayfireHandler;
+impl WayfireHandler {
+    fn get_config_path() -> Result<PathBuf> {
+        if let Ok(path) = env::var("WAYFIRE_CONFIG_FILE") {
+            return Ok(PathBuf::from(path));
+        }
+        Ok(Utils::config_dir()?.join("wayfire.ini"))
+    }
+
+    /// Option names for `s`: `binding_penguinclip_alt`, `command_penguinclip_alt`
+    fn option_names(s: &ShortcutConfig) -> (String, String) {
+        let name = s.id.replace('-', "_");
+        (format!("binding_{}", name), format!("command_{}", name))
+    }
+}
+
+impl ShortcutHandler for WayfireHandler {
+    fn name(&self) -> &str {
+        "Wayfire"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        let (binding_key, command_key) = Self::option_names(s);
+        let values = [
+            (binding_key, s.wayfire_binding.clone()),
+            (command_key, s.full_command()),
+        ];
+
+        // Wayfire reloads its config when the file changes
+        Utils::modify_file_atomic(&path, |content| {
+            Ok(set_ini_values(&content, "command", &values))
+        })?;
+        Ok(())
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let path = Self::get_config_path()?;
+        if !path.exists() {
+            return Ok(());
+        }
+        let (binding_key, command_key) = Self::option_names(s);
+
+        Utils::modify_file_atomic(&path, |content| {
+            Ok(remove_ini_values(
+                &content,
+                "command",
+                &[binding_key, command_key],
+            ))
+        })?;
+        Ok(())
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let content = Utils::read_config(&Self::get_config_path()?)?;
+        let (binding_key, _) = Self::option_names(s);
+        let binding = format!("{} = {}", binding_key, s.wayfire_binding);
+        Ok(content.lines().any(|line| line.trim() == binding))
+    }
+}
+
+// --- Enlightenment ---
+
+/// A key binding from `enlightenment_remote -binding-key-list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EnlightenmentBinding {
+    pub key: String,
+    /// `WIN`, `CTRL|ALT`, ... (`NONE` without any)
+    pub modifiers: String,
+    pub action: String,
+    pub params: String,
+}
+
+impl EnlightenmentBinding {
+    /// Whether this binds `binding` (Enlightenment calls Super `WIN`)
+    pub(crate) fn binds(&self, binding: &KeyBinding) -> bool {
+        let mut modifiers: Vec<String> = self
+            .modifiers
+            .split('|')
+            .filter(|m| !m.eq_ignore_ascii_case("NONE"))
+            .map(str::to_uppercase)
+            .collect();
+        modifiers.sort();
+        let mut wanted = binding.modifier_names(["WIN", "CTRL", "ALT", "SHIFT"]);
+        wanted.sort();
+        self.key.eq_ignore_ascii_case(&binding.key) && modifiers == wanted
+    }
+}
+
+/// Parses `REPLY: BINDING CONTEXT=ANY KEY="v" MODIFIERS=WIN ANY_MOD=NO
+/// ACTION="exec" PARAMS="..."` lines
+fn parse_enlightenment_bindings(output: &str) -> Vec<EnlightenmentBinding> {
+    let field = |line: &str, name: &str| -> Option<String> {
+        let rest = line.split_once(&format!(" {}=", name))?.1;
+        Some(match rest.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next().unwrap_or_default().to_string(),
+            None => rest
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        })
+    };
+    output
+        .lines()
+        .filter_map(|line| {
+            Some(EnlightenmentBinding {
+                key: field(line, "KEY")?,
+                modifiers: field(line, "MODIFIERS").unwrap_or_default(),
+                action: field(line, "ACTION").unwrap_or_default(),
+                params: field(line, "PARAMS").unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Enlightenment's key bindings, `None` without `enlightenment_remote`
+pub(crate) fn enlightenment_bindings() -> Option<Vec<EnlightenmentBinding>> {
+    let output = Utils::run("enlightenment_remote", &["-binding-key-list"]).ok()?;
+    Some(parse_enlightenment_bindings(&output))
+}
+
+/// Enlightenment keeps bindings in its binary `e.cfg`, which we don't
+/// edit; registering fails with directions to its settings instead.
+struct EnlightenmentHandler;
+
+impl ShortcutHandler for EnlightenmentHandler {
+    fn name(&self) -> &str {
+        "Enlightenment"
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        Err(ShortcutError::UnsupportedEnvironment(format!(
+            "Enlightenment shortcuts can't be set automatically; bind {} to `{}` in \
+             Settings → Input → Key Bindings",
+            s.gnome_binding,
+            s.full_command()
+        )))
+    }
+
+    fn unregister(&self, _s: &ShortcutConfig) -> Result<()> {
+        // We never added anything
+        Ok(())
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let bindings = enlightenment_bindings()
+            .ok_or_else(|| ShortcutError::DependencyMissing("enlightenment_remote".into()))?;
+        let command = s.full_command();
+        Ok(bindings
+            .iter()
+            .any(|b| b.action == "exec" && b.params.trim() == command))
+    }
+}
+
+// --- sxhkd (bspwm) ---
+
+/// Compares sxhkd hotkeys regardless of spacing and case
+fn normalize_sxhkd_hotkey(hotkey: &str) -> String {
+    hotkey.split_whitespace().collect::<String>().to_lowercase()
+}
+
+/// The config and extra config files on an sxhkd command line
+/// (`sxhkd -c CONFIG_FILE EXTRA_CONFIG...`)
+fn parse_sxhkd_args(args: &[String]) -> (Option<PathBuf>, Vec<PathBuf>) {
+    let mut config = None;
+    let mut extra = Vec::new();
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" => config = args.next().map(PathBuf::from),
+            // Options taking a value
+            "-m" | "-t" | "-r" | "-s" | "-a" => {
+                args.next();
+            }
+            _ if arg.starts_with('-') => {}
+            _ => extra.push(PathBuf::from(arg)),
+        }
+    }
+    (config, extra)
+}
+
+/// Whether `content` has our two-line block for `hotkey`
+fn has_sxhkd_block(content: &str, hotkey: &str, command: &str) -> bool {
+    let hotkey = normalize_sxhkd_hotkey(hotkey);
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .windows(2)
+        .any(|pair| normalize_sxhkd_hotkey(pair[0]) == hotkey && pair[1].trim() == command)
+}
+
+/// Appends a `hotkey` block running `command`. `None` if it's already there.
+fn add_sxhkd_block(content: &str, hotkey: &str, command: &str) -> Option<String> {
+    if has_sxhkd_block(content, hotkey, command) {
+        return None;
+    }
+    let mut content = content.to_string();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("\n{}\n{}\n\t{}\n", ADDED_MARKER, hotkey, command));
+    Some(content)
+}
+
+/// Removes the block `add_sxhkd_block` added. `None` if it isn't there.
+fn remove_sxhkd_block(content: &str, hotkey: &str, command: &str) -> Option<String> {
+    if !has_sxhkd_block(content, hotkey, command) {
+        return None;
+    }
+    let hotkey = normalize_sxhkd_hotkey(hotkey);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut kept: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if normalize_sxhkd_hotkey(lines[i]) == hotkey
+            && lines.get(i + 1).is_some_and(|l| l.trim() == command)
+        {
+            // Drop our comment line and the blank line above it too
+            if kept.last().is_some_and(|l| l.trim() == ADDED_MARKER) {
+                kept.pop();
+                if kept.last().is_some_and(|l| l.trim().is_empty()) {
+                    kept.pop();
+                }
+            }
+            i += 2;
+            continue;
+        }
+        kept.push(lines[i]);
+        i += 1;
+    }
+    Some(format!("{}\n", kept.join("\n")))
+}
+
+/// Whether a block other than ours binds `hotkey`
+fn sxhkd_hotkey_bound(content: &str, hotkey: &str, command: &str) -> bool {
+    let hotkey = normalize_sxhkd_hotkey(hotkey);
+    let lines: Vec<&str> = content.lines().collect();
+    lines.iter().enumerate().any(|(i, line)| {
+        // Commands are the indented lines below a hotkey
+        !line.starts_with([' ', '\t'])
+            && normalize_sxhkd_hotkey(line) == hotkey
+            && lines.get(i + 1).map_or(true, |l| l.trim() != command)
+    })
+}
+
+struct SxhkdHandler;
+impl SxhkdHandler {
+    fn config_dir() -> Result<PathBuf> {
+        Ok(Utils::config_dir()?.join("sxhkd"))
+    }
+
+    /// The files of the running sxhkd, read from its command line
+    fn running_configs() -> Option<(Option<PathBuf>, Vec<PathBuf>)> {
+        let pids = Utils::run("pgrep", &["-x", "sxhkd"]).ok()?;
+        let pid = pids.lines().next()?;
+        let cmdline = fs::read(format!("/proc/{}/cmdline", pid.trim())).ok()?;
+        let args: Vec<String> = cmdline
+            .split(|&b| b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).to_string())
+            .collect();
+        Some(parse_sxhkd_args(&args))
+    }
+
+    /// sxhkd's main config, our own file, and whether the running sxhkd
+    /// loads ours as an extra config
+    fn config_paths() -> Result<(PathBuf, PathBuf, bool)> {
+        let dir = Self::config_dir()?;
+        let drop_in = dir.join("penguinclip");
+        let (config, extra) = Self::running_configs().unwrap_or_default();
+        let config = config.unwrap_or_else(|| dir.join("sxhkdrc"));
+        let loads_drop_in = extra.contains(&drop_in);
+        Ok((config, drop_in, loads_drop_in))
+    }
+
+    fn reload_sxhkd() {
+        // SIGUSR1 makes sxhkd reread its config files
+        let _ = Utils::run("pkill", &["-USR1", "-x", "sxhkd"]);
+    }
+}
+
+impl ShortcutHandler for SxhkdHandler {
+    fn name(&self) -> &str {
+        "sxhkd"
+    }
+
+    fn reload(&self) {
+        Self::reload_sxhkd();
+    }
+
+    fn register(&self, s: &ShortcutConfig) -> Result<()> {
+        let (config, drop_in, loads_drop_in) = Self::config_paths()?;
+        let path = if loads_drop_in { drop_in } else { config };
+        let command = s.full_command();
+
+        let modified = Utils::modify_file_atomic(&path, |content| {
+            if sxhkd_hotkey_bound(&content, &s.sxhkd_binding, &command) {
+                log::warn!(
+                    "{} is already bound in {}; sxhkd runs only one of them",
+                    s.sxhkd_binding,
+                    path.display()
+                );
+            }
+            Ok(add_sxhkd_block(&content, &s.sxhkd_binding, &command))
+        })?;
+
+        // Reload sxhkd only after file was successfully written
+        if modified {
+            Self::reload_sxhkd();
+        }
+        Ok(())
+    }
+
+    fn unregister(&self, s: &ShortcutConfig) -> Result<()> {
+        let (config, drop_in, _) = Self::config_paths()?;
+        let command = s.full_command();
+
+        let mut modified = false;
+        for path in [config, drop_in] {
+            if path.exists() {
+                modified |= Utils::modify_file_atomic(&path, |content| {
+                    Ok(remove_sxhkd_block(&content, &s.sxhkd_binding, &command))
+                })?;
+            }
+        }
+
+        // Reload sxhkd only after file was successfully written
+        if modified {
+            Self::reload_sxhkd();
+        }
+        Ok(())
+    }
+
+    fn is_registered(&self, s: &ShortcutConfig) -> Result<bool> {
+        let (config, drop_in, _) = Self::config_paths()?;
+        let command = s.full_command();
+        for path in [config, drop_in] {
+            if has_sxhkd_block(&Utils::read_config(&path)?, &s.sxhkd_binding, &command) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shortcut_backups::BackupStore;
+    use std::env::temp_dir;
+
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = temp_dir().join(format!("penguinclip_test_dropin_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("config")
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        let binding = KeyBinding::parse(" <alt><SUPER> V ").unwrap();
+        assert_eq!(binding.to_string(), "<Super><Alt>v");
+        assert_eq!(
+            KeyBinding::parse("<Control>f5").unwrap().to_string(),
+            "<Ctrl>F5"
+        );
+        assert_eq!(
+            KeyBinding::parse("<Super>Period").unwrap().to_string(),
+            "<Super>period"
+        );
+
+        assert!(KeyBinding::parse("v").is_err());
+        assert!(KeyBinding::parse("<Super>").is_err());
+        assert!(KeyBinding::parse("<Hyper>v").is_err());
+        assert!(KeyBinding::parse("<Super><Super>v").is_err());
+        assert!(KeyBinding::parse("<Super v").is_err());
+        assert!(KeyBinding::parse("<Super>F13").is_err());
+    }
+
+    #[test]
+    fn test_default_bindings_in_each_format() {
+        let bindings = HistoryBindings::default();
+        let primary = &bindings.primary;
+        assert_eq!(primary.gnome(), "<Super>v");
+        assert_eq!(primary.label(), "Super+V");
+        assert_eq!(primary.kde(), "Meta+V");
+        assert_eq!(primary.xfce(), "<Super>v");
+        assert_eq!(primary.cosmic_modifiers(), "Super");
+        assert_eq!(primary.i3(), "$mod+v");
+        assert_eq!(primary.hyprland(), "SUPER, V");
+        assert_eq!(primary.lxde(), "W-v");
+        assert_eq!(primary.sxhkd(), "super + v");
+        assert_eq!(primary.awesome(), r#"{ "Mod4" }, "v""#);
+        assert_eq!(primary.qtile(), r#"["mod4"], "v""#);
+        assert_eq!(primary.river(), "Super V");
+        assert_eq!(primary.wayfire(), "<super> KEY_V");
+        assert_eq!(primary.portal(), "LOGO+v");
+
+        let secondary = &bindings.secondary;
+        assert_eq!(secondary.gnome(), "<Ctrl><Alt>v");
+        assert_eq!(secondary.label(), "Ctrl+Alt+V");
+        assert_eq!(secondary.kde(), "Ctrl+Alt+V");
+        assert_eq!(secondary.xfce(), "<Primary><Alt>v");
+        assert_eq!(secondary.cosmic_modifiers(), "Ctrl, Alt");
+        assert_eq!(secondary.i3(), "Ctrl+Mod1+v");
+        assert_eq!(secondary.hyprland(), "CTRL ALT, V");
+        assert_eq!(secondary.lxde(), "C-A-v");
+        assert_eq!(secondary.sxhkd(), "ctrl + alt + v");
+        assert_eq!(secondary.awesome(), r#"{ "Control", "Mod1" }, "v""#);
+        assert_eq!(secondary.qtile(), r#"["control", "mod1"], "v""#);
+        assert_eq!(secondary.river(), "Control+Alt V");
+        assert_eq!(secondary.wayfire(), "<ctrl> <alt> KEY_V");
+        assert_eq!(secondary.portal(), "CTRL+ALT+v");
+    }
+
+    #[test]
+    fn test_named_keys_in_each_format() {
+        let emoji = KeyBinding::parse("<Super>period").unwrap();
+        assert_eq!(emoji.gnome(), "<Super>period");
+        assert_eq!(emoji.label(), "Super+.");
+        assert_eq!(emoji.kde(), "Meta+.");
+        assert_eq!(emoji.i3(), "$mod+period");
+        assert_eq!(emoji.hyprland(), "SUPER, period");
+        assert_eq!(emoji.lxde(), "W-period");
+        assert_eq!(emoji.sxhkd(), "super + period");
+        assert_eq!(emoji.river(), "Super period");
+        assert_eq!(emoji.wayfire(), "<super> KEY_DOT");
+        assert_eq!(emoji.portal(), "LOGO+period");
+
+        let insert = KeyBinding::parse("<Shift><Super>insert").unwrap();
+        assert_eq!(insert.gnome(), "<Super><Shift>Insert");
+        assert_eq!(insert.kde(), "Meta+Shift+Ins");
+        assert_eq!(insert.i3(), "$mod+Shift+Insert");
+        assert_eq!(insert.hyprland(), "SUPER SHIFT, Insert");
+        assert_eq!(insert.lxde(), "W-S-Insert");
+    }
+
+    #[test]
+    fn test_shortcuts_use_configured_bindings() {
+        let bindings = HistoryBindings {
+            primary: KeyBinding::parse("<Super>c").unwrap(),
+            ..Default::default()
+        };
+        let configs = shortcuts(&bindings, "penguinclip");
+        assert_eq!(configs[0].gnome_binding, "<Super>c");
+        assert_eq!(configs[0].kde_binding, "Meta+C");
+        assert_eq!(configs[1].gnome_binding, "<Ctrl><Alt>v");
+        assert_eq!(configs[2].kde_binding, "Meta+.");
+        assert_eq!(configs[3].hyprland_binding, "SUPER ALT, 1");
+        assert_eq!(configs[3].full_command(), "penguinclip --paste-index 1");
+    }
+
+    #[test]
+    fn test_disabled_shortcuts_are_only_registered_by_id() {
+        let mut bindings = HistoryBindings::default();
+        bindings.disabled.insert("penguinclip-alt".to_string());
+        let all: Vec<&str> = shortcuts_to_register(&bindings, None)
+            .unwrap()
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert!(all.contains(&"penguinclip"));
+        assert!(!all.contains(&"penguinclip-alt"));
+        assert!(!all_shortcuts(&bindings)
+            .iter()
+            .any(|c| c.id == "penguinclip-alt"));
+        assert_eq!(
+            shortcuts_to_register(&bindings, Some("penguinclip-alt")).unwrap()[0].id,
+            "penguinclip-alt"
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_bindings_by_id() {
+        let mut bindings = HistoryBindings::default();
+        bindings.overrides.insert(
+            "penguinclip".to_string(),
+            KeyBinding::parse("<Super><Shift>v").unwrap(),
+        );
+        bindings.overrides.insert(
+            "penguinclip-emoji".to_string(),
+            KeyBinding::parse("<Super><Alt>period").unwrap(),
+        );
+        let configs = shortcuts(&bindings, "penguinclip");
+        assert_eq!(configs[0].gnome_binding, "<Super><Shift>v");
+        assert_eq!(configs[0].kde_binding, "Meta+Shift+V");
+        assert_eq!(configs[0].hyprland_binding, "SUPER SHIFT, V");
+        assert_eq!(configs[1].gnome_binding, "<Ctrl><Alt>v");
+        assert_eq!(configs[2].gnome_binding, "<Super><Alt>period");
+        assert_eq!(configs[2].full_command(), "penguinclip --emoji");
+        assert_eq!(
+            history_shortcuts(&bindings, "penguinclip")[0].i3_binding,
+            "$mod+Shift+v"
+        );
+
+        let alternatives: Vec<String> = KeyBinding::parse("<Super>v")
+            .unwrap()
+            .alternatives()
+            .iter()
+            .map(KeyBinding::gnome)
+            .collect();
+        assert_eq!(
+            alternatives,
+            ["<Super><Shift>v", "<Super><Alt>v", "<Super><Ctrl>v"]
+        );
+    }
+
+    #[test]
+    fn test_registration_result_reports_each_shortcut() {
+        let configs = shortcuts(&HistoryBindings::default(), "penguinclip");
+        let ok = RegistrationResult::report(&configs[0], "register", Ok(()));
+        assert_eq!(ok.id, "penguinclip");
+        assert_eq!(ok.binding, "<Super>v");
+        assert!(ok.success);
+        assert_eq!(ok.error, None);
+
+        let failed = RegistrationResult::report(
+            &configs[1],
+            "unregister",
+            Err(ShortcutError::CommandFailed {
+                cmd: "gsettings set".to_string(),
+                stderr: "No such schema".to_string(),
+            }),
+        );
+        assert!(!failed.success);
+        assert!(failed.error.unwrap().contains("No such schema"));
+
+        assert!(selected_shortcuts(&HistoryBindings::default(), Some("missing")).is_err());
+        let alt = selected_shortcuts(&HistoryBindings::default(), Some("penguinclip-alt")).unwrap();
+        assert_eq!(alt.len(), 1);
+    }
+
+    #[test]
+    fn test_tiling_wm_binding_lines_round_trip() {
+        let config = "bindsym $mod+v exec rofi\nbindsym $mod+c exec term";
+        let binding_line = "bindsym $mod+v exec penguinclip";
+        let conflicts = |l: &str| l.starts_with("bindsym") && has_i3_binding(l, "$mod+v");
+
+        let (added, had_existing) = add_binding_line(config, binding_line, conflicts).unwrap();
+        assert!(had_existing);
+        assert!(added.contains("# bindsym $mod+v exec rofi # Commented by penguinclip"));
+        assert!(add_binding_line(&added, binding_line, conflicts).is_none());
+
+        let removed = remove_binding_line(&added, binding_line, conflicts).unwrap();
+        assert_eq!(removed.trim_end(), config);
+    }
+
+    #[test]
+    fn test_binding_line_matching() {
+        assert!(has_i3_binding("bindsym mod4+v exec x", "$mod+v"));
+        assert!(has_i3_binding("bindsym $mod+v# note", "$mod+v"));
+        assert!(!has_i3_binding("bindsym $mod+v+x exec x", "$mod+v"));
+        assert!(!has_i3_binding(
+            "bindsym $mod+ctrl+mod1+v exec x",
+            "ctrl+mod1+v"
+        ));
+
+        assert!(has_hyprland_binding("bind = super, v, exec, x", "SUPER, V"));
+        assert!(has_hyprland_binding("binde=super,v,exec,x", "SUPER, V"));
+        assert!(!has_hyprland_binding(
+            "bind = super shift, v, exec, x",
+            "SUPER, V"
+        ));
+    }
+
+    #[test]
+    fn test_parse_i3_version() {
+        assert_eq!(
+            parse_i3_version("i3 version 4.22 (2023-01-02) © 2009 Michael Stapelberg"),
+            Some((4, 22))
+        );
+        assert_eq!(
+            parse_i3_version("i3 version 4.18.3 (2020-10-19)"),
+            Some((4, 18))
+        );
+        assert_eq!(parse_i3_version("sway version 1.8"), None);
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches(
+            "/a/config.d/*",
+            "/a/config.d/penguinclip.conf"
+        ));
+        assert!(glob_matches(
+            "/a/config.d/*.conf",
+            "/a/config.d/penguinclip.conf"
+        ));
+        assert!(glob_matches("/a/?onfig.d/*", "/a/config.d/x"));
+        assert!(!glob_matches(
+            "/a/config.d/*.conf",
+            "/a/config.d/penguinclip.bak"
+        ));
+        assert!(!glob_matches("/a/*", "/b/config.d/x"));
+    }
+
+    #[test]
+    fn test_drop_in_add_and_remove() {
+        let main_config = temp_config("add_remove");
+        let backups = main_config.parent().unwrap().join("backups");
+        shortcut_backups::with_store_dir(&backups, || drop_in_add_and_remove(&main_config));
+
+        // Each edit of the existing main config was backed up first
+        let taken = BackupStore::new(backups).list();
+        assert_eq!(taken.len(), 2);
+        assert!(taken.iter().all(|b| b.original_path == main_config));
+    }
+
+    fn drop_in_add_and_remove(main_config: &Path) {
+        fs::write(main_config, "bindsym $mod+Return exec term\n").unwrap();
+        let drop_in = DropIn::new(
+            main_config.to_path_buf(),
+            "config.d/penguinclip.conf",
+            "include",
+        );
+        let line = "bindsym $mod+v exec penguinclip";
+
+        assert!(drop_in.add(line).unwrap());
+        assert!(!drop_in.add(line).unwrap());
+        assert!(drop_in.contains(line).unwrap());
+        let main = fs::read_to_string(main_config).unwrap();
+        assert_eq!(main.matches(&drop_in.include_line()).count(), 1);
+        assert!(main.contains(INCLUDE_MARKER));
+
+        // A second binding shares the file and the include line
+        assert!(drop_in.add("bindsym $mod+x exec penguinclip").unwrap());
+        assert!(drop_in.remove(line).unwrap());
+        assert!(drop_in.path.exists());
+        assert!(drop_in.remove("bindsym $mod+x exec penguinclip").unwrap());
+        assert!(!drop_in.path.exists());
+        assert!(!drop_in.remove(line).unwrap());
+        assert_eq!(
+            fs::read_to_string(main_config).unwrap(),
+            "bindsym $mod+Return exec term\n"
+        );
+    }
+
+    #[test]
+    fn test_drop_in_respects_existing_include() {
+        let main_config = temp_config("existing_include");
+        let dir = main_config.parent().unwrap().display().to_string();
+        fs::write(&main_config, format!("include {}/config.d/*\n", dir)).unwrap();
+        let drop_in = DropIn::new(main_config.clone(), "config.d/penguinclip.conf", "include");
+
+        let backups = main_config.parent().unwrap().join("backups");
+        assert!(shortcut_backups::with_store_dir(&backups, || {
+            drop_in.add("bindsym $mod+v exec penguinclip")
+        })
+        .unwrap());
+        assert!(!fs::read_to_string(&main_config)
+            .unwrap()
+            .contains(INCLUDE_MARKER));
+
+        let hypr = DropIn::new(main_config, "penguinclip.conf", "source");
+        assert!(hypr.includes_us(&format!("source = {}/penguinclip.conf", dir)));
+        assert!(!hypr.includes_us(&format!("source = {}/other.conf", dir)));
+    }
+
+    #[test]
+    fn test_hyprland_runtime_bind_matching() {
+        let binds: Vec<HyprlandBind> = serde_json::from_str(
+            r#"[
+                {"locked": false, "modmask": 64, "submap": "", "key": "V",
+                 "dispatcher": "exec", "arg": "penguinclip"},
+                {"modmask": 72, "submap": "", "key": "v",
+                 "dispatcher": "togglefloating", "arg": ""},
+                {"modmask": 64, "submap": "resize", "key": "V",
+                 "dispatcher": "exec", "arg": "x"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(binds[0].binds("SUPER, V"));
+        assert!(!binds[0].binds("SUPER ALT, V"));
+        assert!(binds[1].binds("SUPER ALT, V"));
+        assert_eq!(binds[1].action(), "togglefloating");
+        assert!(!binds[2].binds("SUPER, V"));
+    }
+
+    #[test]
+    fn test_config_files_follow_nested_includes() {
+        let main = temp_config("nested_includes");
+        let dir = main.parent().unwrap().to_path_buf();
+        fs::create_dir_all(dir.join("config.d")).unwrap();
+        fs::create_dir_all(dir.join("keys")).unwrap();
+        fs::write(&main, "include config.d/*.conf\n").unwrap();
+        fs::write(dir.join("config.d/b.conf"), "# nothing\n").unwrap();
+        fs::write(
+            dir.join("config.d/a.conf"),
+            format!("include \"{}/keys/*\"\n", dir.display()),
+        )
+        .unwrap();
+        fs::write(dir.join("config.d/skipped.bak"), "").unwrap();
+        // A cycle back to the main config
+        fs::write(dir.join("keys/vim"), "include ../config\n").unwrap();
+
+        let names: Vec<String> = config_files_with_includes(&[main])
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["config", "a.conf", "vim", "b.conf"]);
+    }
+
+    #[test]
+    fn test_config_includes_stop_at_depth_limit() {
+        let main = temp_config("include_depth");
+        let dir = main.parent().unwrap().to_path_buf();
+        fs::write(&main, "include 0\n").unwrap();
+        for depth in 0..MAX_INCLUDE_DEPTH + 4 {
+            fs::write(
+                dir.join(depth.to_string()),
+                format!("include {}\n", depth + 1),
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            config_files_with_includes(&[main]).len(),
+            MAX_INCLUDE_DEPTH + 1
+        );
+    }
+
+    #[test]
+    fn test_comment_out_and_restore_lines() {
+        let conflicts = |l: &str| l.starts_with("bindsym") && has_i3_binding(l, "$mod+v");
+        let content = "bindsym $mod+v split v\nbindsym $mod+h split h";
+
+        let commented = comment_out_lines(content, conflicts).unwrap();
+        assert!(commented.starts_with("# bindsym $mod+v split v # Commented"));
+        assert_eq!(comment_out_lines(&commented, conflicts), None);
+        assert_eq!(restore_lines(&commented, conflicts).unwrap(), content);
+        assert_eq!(restore_lines(content, conflicts), None);
+    }
+
+    #[test]
+    fn test_sxhkd_block_round_trip() {
+        let content = "super + Return\n\talacritty\n";
+        let added = add_sxhkd_block(content, "super + v", "penguinclip").unwrap();
+        assert!(added.ends_with("super + v\n\tpenguinclip\n"));
+        assert_eq!(add_sxhkd_block(&added, "super+v", "penguinclip"), None);
+        assert!(!sxhkd_hotkey_bound(&added, "super + v", "penguinclip"));
+        assert!(sxhkd_hotkey_bound(
+            "Super + V\n\trofi\n",
+            "super + v",
+            "penguinclip"
+        ));
+        assert_eq!(
+            remove_sxhkd_block(&added, "super + v", "penguinclip").unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_parse_sxhkd_args() {
+        let args: Vec<String> = ["sxhkd", "-m", "1", "-c", "/a/rc", "/a/penguinclip"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let (config, extra) = parse_sxhkd_args(&args);
+        assert_eq!(config, Some(PathBuf::from("/a/rc")));
+        assert_eq!(extra, [PathBuf::from("/a/penguinclip")]);
+    }
+
+    #[test]
+    fn test_awesome_helpers() {
+        assert_eq!(quoted_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(
+            parse_awesome_client_output("   string \"Toggle floating\"\n"),
+            Some("Toggle floating".to_string())
+        );
+        assert_eq!(
+            parse_awesome_client_output("   boolean true"),
+            Some("true".to_string())
+        );
+        assert_eq!(parse_awesome_client_output(""), None);
+
+        assert!(requires_penguinclip("require(\"penguinclip\")"));
+        assert!(requires_penguinclip("  require 'penguinclip'"));
+        assert!(!requires_penguinclip("-- require(\"penguinclip\")"));
+    }
+
+    #[test]
+    fn test_handlers_follow_xdg_config_home() {
+        let config = temp_dir().join("penguinclip_test_xdg_handlers");
+        let _ = fs::remove_dir_all(&config);
+        fs::create_dir_all(config.join("qtile")).unwrap();
+        let shortcut = &shortcuts(&HistoryBindings::default(), "penguinclip")[0];
+        fs::write(
+            config.join("qtile/config.py"),
+            format!("keys = []\n{}\n", QtileHandler::key_line(shortcut)),
+        )
+        .unwrap();
+
+        crate::paths::with_config_home(&config, || {
+            assert_eq!(
+                KdeHandler::get_kglobalshortcuts_path().unwrap(),
+                config.join("kglobalshortcutsrc")
+            );
+            assert_eq!(
+                LxqtHandler::get_config_path().unwrap(),
+                config.join("lxqt/globalkeyshortcuts.conf")
+            );
+            assert_eq!(
+                LxdeHandler::get_config_path().unwrap(),
+                config.join("openbox/rc.xml")
+            );
+            assert_eq!(
+                CosmicHandler::get_config_path().unwrap(),
+                config.join("cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom")
+            );
+            assert_eq!(
+                I3Handler::get_config_path().unwrap(),
+                config.join("i3/config")
+            );
+            assert_eq!(
+                AwesomeHandler::config_dir().unwrap(),
+                config.join("awesome")
+            );
+            assert!(QtileHandler.is_registered(shortcut).unwrap());
+        });
+        let _ = fs::remove_dir_all(&config);
+    }
+
+    #[test]
+    fn test_qtile_block_round_trip() {
+        let content = "keys = [Key([mod], \"v\", lazy.window.toggle_floating())]\n";
+        let first = "keys.append(_PenguinclipKey([\"mod4\"], \"v\", x))";
+        let second = "keys.append(_PenguinclipKey([\"mod4\"], \"period\", x))";
+
+        let added = add_qtile_key(content, first).unwrap();
+        assert!(
+            added.contains(QTILE_BLOCK_START) && added.ends_with(&format!("{}\n", QTILE_BLOCK_END))
+        );
+        assert_eq!(add_qtile_key(&added, first), None);
+        let both = add_qtile_key(&added, second).unwrap();
+        assert_eq!(both.matches(QTILE_BLOCK_START).count(), 1);
+
+        let one = remove_qtile_key(&both, first).unwrap();
+        assert!(one.contains(second));
+        assert_eq!(remove_qtile_key(&one, second).unwrap(), content);
+    }
+
+    #[test]
+    fn test_qtile_binding_matching() {
+        let binding = KeyBinding::parse("<Super>v").unwrap().qtile();
+        assert!(has_qtile_binding(
+            "    Key([mod], 'v', lazy.window.toggle_floating()),",
+            &binding
+        ));
+        assert!(has_qtile_binding(
+            "Key([\"mod4\"], \"v\", lazy.spawn(x)),",
+            &binding
+        ));
+        assert!(!has_qtile_binding(
+            "Key([mod, \"shift\"], \"v\", x),",
+            &binding
+        ));
+        assert!(!has_qtile_binding("# Key([mod], \"v\", x),", &binding));
+    }
+
+    #[test]
+    fn test_river_mapping_lines() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert!(has_river_mapping(
+            "riverctl map normal super v spawn 'penguinclip'",
+            "Super V"
+        ));
+        assert!(!has_river_mapping(
+            "riverctl map locked Super V spawn x",
+            "Super V"
+        ));
+        assert!(!has_river_mapping(
+            "riverctl map normal Super+Shift V spawn x",
+            "Super V"
+        ));
+    }
+
+    #[test]
+    fn test_ini_values_round_trip() {
+        let content = "[core]\nplugins = command\n\n[command]\nbinding_term = <super> KEY_ENTER\ncommand_term = foot\n\n[expo]\ntoggle = <super>\n";
+        let values = [
+            (
+                "binding_penguinclip".to_string(),
+                "<super> KEY_V".to_string(),
+            ),
+            ("command_penguinclip".to_string(), "penguinclip".to_string()),
+        ];
+
+        let added = set_ini_values(content, "command", &values).unwrap();
+        assert!(added.contains("command_term = foot\nbinding_penguinclip = <super> KEY_V\ncommand_penguinclip = penguinclip\n\n[expo]"));
+        assert_eq!(set_ini_values(&added, "command", &values), None);
+
+        let keys = [values[0].0.clone(), values[1].0.clone()];
+        assert_eq!(
+            remove_ini_values(&added, "command", &keys).unwrap(),
+            content
+        );
+        assert_eq!(remove_ini_values(content, "command", &keys), None);
+
+        let created = set_ini_values("[core]\nplugins = command", "command", &values).unwrap();
+        assert_eq!(
+            created,
+            "[core]\nplugins = command\n\n[command]\nbinding_penguinclip = <super> KEY_V\ncommand_penguinclip = penguinclip\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_enlightenment_bindings() {
+        let output = "REPLY: BINDING CONTEXT=ANY KEY=\"v\" MODIFIERS=WIN ANY_MOD=NO ACTION=\"exec\" PARAMS=\"terminology -e vim\"\n\
+                      REPLY: BINDING CONTEXT=ANY KEY=\"Left\" MODIFIERS=CTRL|ALT ANY_MOD=NO ACTION=\"desk_flip_by\" PARAMS=\"-1 0\"\n";
+        let bindings = parse_enlightenment_bindings(output);
+        assert_eq!(bindings.len(), 2);
+        let super_v = KeyBinding::new(&[Modifier::Super], "v");
+        assert!(bindings[0].binds(&super_v));
+        assert_eq!(bindings[0].params, "terminology -e vim");
+        assert_eq!(bindings[1].modifiers, "CTRL|ALT");
+        assert!(!bindings[1].binds(&super_v));
+    }
+
+    const OPENBOX_LXDE: &str = include_str!("linux_shortcut_manager/fixtures/openbox-lxde-rc.xml");
+    const OPENBOX_LXQT: &str = include_str!("linux_shortcut_manager/fixtures/openbox-lxqt-rc.xml");
+    const OPENBOX_HAND_EDITED: &str =
+        include_str!("linux_shortcut_manager/fixtures/openbox-hand-edited-rc.xml");
+
+    #[test]
+    fn test_openbox_keybind_round_trip() {
+        for (fixture, indent) in [(OPENBOX_LXDE, "    "), (OPENBOX_LXQT, "\t\t")] {
+            let added = add_openbox_keybind(fixture, "W-v", "penguinclip")
+                .unwrap()
+                .unwrap();
+            assert!(added.contains(&format!(
+                "{i}<keybind key=\"W-v\">\n{i}  <action name=\"Execute\">\n{i}    <command>penguinclip</command>\n",
+                i = indent
+            )));
+            assert!(OpenboxKeyboard::parse(&added)
+                .unwrap()
+                .find("W-v", "penguinclip")
+                .next()
+                .is_some());
+            assert_eq!(
+                add_openbox_keybind(&added, "W-v", "penguinclip").unwrap(),
+                None
+            );
+
+            // Only our keybind goes, and the file is back to what it was
+            let removed = remove_openbox_keybind(&added, "W-v", "penguinclip")
+                .unwrap()
+                .unwrap();
+            assert_eq!(removed, fixture);
+            assert_eq!(
+                remove_openbox_keybind(fixture, "W-v", "penguinclip").unwrap(),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_openbox_hand_edited_config() {
+        let keyboard = OpenboxKeyboard::parse(OPENBOX_HAND_EDITED).unwrap();
+        assert_eq!(keyboard.prefix, "ob:");
+        let commands: Vec<(&str, &str)> = keyboard
+            .keybinds
+            .iter()
+            .flat_map(|kb| {
+                kb.commands
+                    .iter()
+                    .map(move |c| (kb.key.as_str(), c.as_str()))
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                ("W-Return", "alacritty"),
+                ("W-v", "penguinclip"),
+                ("W-s", r#"sh -c 'scrot && feh "$(ls -t | head -1)"'"#),
+                ("b", "firefox && true"),
+            ]
+        );
+
+        // Found despite the extra attribute, namespace prefix and whitespace
+        let removed = remove_openbox_keybind(OPENBOX_HAND_EDITED, "W-v", "penguinclip")
+            .unwrap()
+            .unwrap();
+        assert!(!removed.contains("penguinclip"));
+        assert!(removed.contains("<ob:keybind key=\"W-s\">"));
+        assert_eq!(OpenboxKeyboard::parse(&removed).unwrap().keybinds.len(), 4);
+
+        let added = add_openbox_keybind(&removed, "C-A-v", "penguinclip")
+            .unwrap()
+            .unwrap();
+        assert!(added.contains(
+            "  <ob:keybind key=\"C-A-v\">\n    <ob:action name=\"Execute\">\n      <ob:command>penguinclip</ob:command>\n    </ob:action>\n  </ob:keybind>\n</ob:keyboard>"
+        ));
+    }
+
+    #[test]
+    fn test_openbox_keyboard_missing_or_empty() {
+        assert!(
+            add_openbox_keybind("<openbox_config></openbox_config>", "W-v", "penguinclip").is_err()
+        );
+        assert!(add_openbox_keybind("<openbox_config><keyboard>", "W-v", "penguinclip").is_err());
+
+        let added = add_openbox_keybind(
+            "<openbox_config>\n  <keyboard/>\n</openbox_config>\n",
+            "W-v",
+            "penguinclip --emoji & more",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            added,
+            "<openbox_config>\n  <keyboard>\n    <keybind key=\"W-v\">\n      <action name=\"Execute\">\n        <command>penguinclip --emoji &amp; more</command>\n      </action>\n    </keybind>\n  </keyboard>\n</openbox_config>\n"
+        );
+        assert!(
+            remove_openbox_keybind(&added, "W-v", "penguinclip --emoji & more")
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    const COSMIC_CUSTOM: &str = include_str!("linux_shortcut_manager/fixtures/cosmic-custom");
+    const COSMIC_COMMENTED: &str =
+        include_str!("linux_shortcut_manager/fixtures/cosmic-custom-commented");
+
+    fn shortcut_by_id(id: &str) -> ShortcutConfig {
+        shortcuts(&HistoryBindings::default(), "penguinclip")
+            .into_iter()
+            .find(|s| s.id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_cosmic_round_trip() {
+        // A file as COSMIC Settings writes it comes back unchanged
+        let parsed = parse_cosmic_shortcuts(COSMIC_CUSTOM).unwrap();
+        assert_eq!(write_cosmic_shortcuts(&parsed).unwrap(), COSMIC_CUSTOM);
+
+        let primary = shortcut_by_id("penguinclip");
+        let emoji = shortcut_by_id("penguinclip-emoji");
+        let added = CosmicHandler::add_entry(COSMIC_CUSTOM, &primary)
+            .unwrap()
+            .unwrap();
+        let added = CosmicHandler::add_entry(&added, &emoji).unwrap().unwrap();
+        assert_eq!(CosmicHandler::add_entry(&added, &primary).unwrap(), None);
+        assert!(added.contains(
+            "    (\n        modifiers: [\n            Super,\n        ],\n        key: \"v\",\n        description: Some(\"Clipboard History\"),\n    ): Spawn(\"penguinclip\"),\n"
+        ));
+        assert!(added.contains("key: \"period\",\n        description: Some(\"Emoji Picker\"),\n    ): Spawn(\"penguinclip --emoji\"),"));
+        // Actions we don't model are written back as they were
+        assert!(added.contains("): Disable,"));
+        assert!(added.contains("): System(Screenshot),"));
+
+        let removed = CosmicHandler::remove_entry(&added, &emoji)
+            .unwrap()
+            .unwrap();
+        let removed = CosmicHandler::remove_entry(&removed, &primary)
+            .unwrap()
+            .unwrap();
+        assert_eq!(removed, COSMIC_CUSTOM);
+        assert_eq!(
+            CosmicHandler::remove_entry(COSMIC_CUSTOM, &primary).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cosmic_hand_edited_config() {
+        // Comments, compact entries and braces inside strings all parse
+        let original = parse_cosmic_shortcuts(COSMIC_COMMENTED).unwrap();
+        assert_eq!(original.len(), 3);
+
+        let primary = shortcut_by_id("penguinclip");
+        let added = CosmicHandler::add_entry(COSMIC_COMMENTED, &primary)
+            .unwrap()
+            .unwrap();
+        // Comments are not preserved (the backup keeps them), entries are
+        assert!(!added.contains("//") && !added.contains("/*"));
+        let reparsed = parse_cosmic_shortcuts(&added).unwrap();
+        assert_eq!(reparsed.len(), 4);
+        for (binding, action) in &original {
+            assert_eq!(reparsed[binding].get_ron(), action.get_ron());
+        }
+
+        let removed = CosmicHandler::remove_entry(&added, &primary)
+            .unwrap()
+            .unwrap();
+        assert_eq!(parse_cosmic_shortcuts(&removed).unwrap().len(), 3);
+        assert!(removed.contains(r#"Spawn("sh -c 'cosmic-files ~ }'")"#));
+        assert!(removed.contains("keycode: Some(107)"));
+    }
+
+    #[test]
+    fn test_cosmic_empty_and_invalid_config() {
+        let primary = shortcut_by_id("penguinclip");
+        let created = CosmicHandler::add_entry("", &primary).unwrap().unwrap();
+        assert_eq!(parse_cosmic_shortcuts(&created).unwrap().len(), 1);
+        assert_eq!(
+            CosmicHandler::remove_entry(&created, &primary)
+                .unwrap()
+                .unwrap(),
+            "{}\n"
+        );
+
+        // Refuse to rewrite a file we can't read rather than clobber it
+        assert!(
+            CosmicHandler::add_entry("{ (modifiers: [Super], key: \"t\"): ", &primary).is_err()
+        );
+    }
+
+    const KGLOBALSHORTCUTS_PLASMA6: &str =
+        include_str!("linux_shortcut_manager/fixtures/kglobalshortcutsrc-plasma6");
+
+    #[test]
+    fn test_kde_service_shortcut_round_trip() {
+        let primary = shortcut_by_id("penguinclip");
+        let desktop_file = KdeHandler::desktop_file_name(&primary);
+        assert_eq!(desktop_file, "penguinclip-shortcut.desktop");
+
+        let added =
+            set_kde_service_shortcut(KGLOBALSHORTCUTS_PLASMA6, &desktop_file, "Meta+V").unwrap();
+        assert!(added.ends_with(
+            "_launch=Print\n\n[services][penguinclip-shortcut.desktop]\n_launch=Meta+V\n"
+        ));
+        assert_eq!(kde_service_shortcuts(&added, &desktop_file), ["Meta+V"]);
+        assert_eq!(
+            set_kde_service_shortcut(&added, &desktop_file, "Meta+V"),
+            None
+        );
+
+        // Rebinding changes the key in place
+        let rebound = set_kde_service_shortcut(&added, &desktop_file, "Ctrl+Alt+V").unwrap();
+        assert_eq!(rebound.matches("[services][penguinclip").count(), 1);
+        assert_eq!(
+            kde_service_shortcuts(&rebound, &desktop_file),
+            ["Ctrl+Alt+V"]
+        );
+
+        assert_eq!(
+            remove_kde_service_shortcut(&rebound, &desktop_file).unwrap(),
+            KGLOBALSHORTCUTS_PLASMA6
+        );
+        assert_eq!(
+            remove_kde_service_shortcut(KGLOBALSHORTCUTS_PLASMA6, &desktop_file),
+            None
+        );
+        // Other launchers' keys are read the same way
+        assert_eq!(
+            kde_service_shortcuts(KGLOBALSHORTCUTS_PLASMA6, "org.kde.spectacle.desktop"),
+            ["Print"]
+        );
     }
 }
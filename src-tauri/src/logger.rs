@@ -0,0 +1,66 @@
+//! In-Memory Log Buffer
+//! Keeps the most recent log lines in memory so a diagnostic report can
+//! include them even when file logging is off or the log has been cleared.
+//! Fed by the file logger in `diagnostics`; nothing else writes to it.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+/// How many lines the buffer holds before dropping the oldest
+pub const CAPACITY: usize = 500;
+
+static RECENT: Mutex<RingBuffer> = Mutex::new(RingBuffer::new(CAPACITY));
+
+/// A fixed number of lines, oldest first
+pub struct RingBuffer {
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// The last `max_lines` lines, oldest first
+    pub fn tail(&self, max_lines: usize) -> Vec<String> {
+        let start = self.lines.len().saturating_sub(max_lines);
+        self.lines.range(start..).cloned().collect()
+    }
+}
+
+/// Remembers one formatted log line
+pub fn record(line: &str) {
+    RECENT.lock().push(line.trim_end().to_string());
+}
+
+/// The last `max_lines` lines logged by this process, oldest first
+pub fn recent(max_lines: usize) -> Vec<String> {
+    RECENT.lock().tail(max_lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let mut buffer = RingBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(format!("line {}", i));
+        }
+        assert_eq!(buffer.tail(10), ["line 2", "line 3", "line 4"]);
+        assert_eq!(buffer.tail(1), ["line 4"]);
+        assert!(buffer.tail(0).is_empty());
+    }
+}
@@ -3,13 +3,26 @@
 
 use log::{debug, warn};
 use parking_lot::Mutex;
+use penguinclip_lib::atomic_write::{self, LoadIssue};
 use penguinclip_lib::autostart_manager;
-use penguinclip_lib::clipboard_manager::{ClipboardItem, ClipboardManager};
+use penguinclip_lib::clipboard_backend::ArboardBackend;
+use penguinclip_lib::clipboard_manager::{
+    BulkDeleteResult, ClipboardContent, ClipboardItem, ClipboardManager, HistoryLockState,
+    HistoryPage, HistoryStats, ImportSummary, ItemMeta, PinToggleResult, SaveItemError,
+};
+use penguinclip_lib::clipboard_watch::{
+    self, ChangeSource, HealthChange, ImageReadThrottle, NoHistoryHint, ReadKind, SelectionSettle,
+    WatchStats, WatcherHealth,
+};
 use penguinclip_lib::config_manager::{resolve_window_position, ConfigManager};
-use penguinclip_lib::emoji_manager::{EmojiManager, EmojiUsage};
-use std::sync::atomic::{AtomicBool, Ordering};
+use penguinclip_lib::emoji_manager::{
+    self, EmojiCategory, EmojiInfo, EmojiManager, RecentEmoji, SkinTone,
+};
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{
     menu::{CheckMenuItem, Menu, MenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
@@ -17,14 +30,24 @@ use tauri::{
     WindowEvent,
 };
 
-use penguinclip_lib::focus_manager::x11_robust_activate;
+use penguinclip_lib::focus_manager::{self, x11_robust_activate, x11_robust_activate_by_title};
 use penguinclip_lib::focus_manager::{restore_focused_window, save_focused_window};
-use penguinclip_lib::input_simulator::simulate_paste_keystroke;
+use penguinclip_lib::gif_history::{GifHistoryManager, GifUsage};
+use penguinclip_lib::gif_manager::{self, ClipboardWorker, GifPasteError, GifPasteOptions};
+use penguinclip_lib::global_shortcuts_portal::{self, Action as PortalAction};
+use penguinclip_lib::history_crypto::{self, HistoryKey};
+use penguinclip_lib::input_simulator::{self, simulate_paste_keystroke, PasteDiagnostics};
+use penguinclip_lib::kaomoji::{self, Kaomoji};
 use penguinclip_lib::permission_checker;
-use penguinclip_lib::session::is_wayland;
+use penguinclip_lib::session::{is_wayland, is_x11};
 use penguinclip_lib::shortcut_setup;
-use penguinclip_lib::theme_manager::{self, ThemeInfo};
-use penguinclip_lib::user_settings::{UserSettings, UserSettingsManager};
+use penguinclip_lib::smart_actions;
+use penguinclip_lib::text_transforms;
+use penguinclip_lib::theme_manager::{self, AccentColor, ThemeInfo};
+use penguinclip_lib::user_settings::{
+    CustomKaomoji, ImportReport, ImportWarning, ImportWarningKind, SettingsBundle, SettingsImport,
+    UserSettings, UserSettingsManager, ValidationIssue, WindowClosePolicy,
+};
 
 /// Global flag to track if we started in background mode
 /// This is used to block the initial window show
@@ -35,23 +58,43 @@ static STARTED_IN_BACKGROUND: AtomicBool = AtomicBool::new(false);
 /// After the first user toggle, this is set to true to allow normal show/hide behavior
 static INITIAL_SHOW_ALLOWED: AtomicBool = AtomicBool::new(false);
 
-/// When true, the watcher stops recording new clipboard items (privacy pause).
-/// Runtime-only: resets to recording on restart so history can't silently stop
-/// for a forgotten pause.
-static RECORDING_PAUSED: AtomicBool = AtomicBool::new(false);
-
-/// Title of the main clipboard window. Used to locate the window for X11
-/// focus activation; MUST match the `main` window `title` in tauri.conf.json.
+/// Title of the main clipboard window. Only used to locate the window for X11
+/// focus activation when its native id is unknown; MUST match the `main`
+/// window `title` in tauri.conf.json.
 const MAIN_WINDOW_TITLE: &str = "PenguinClip";
 
 /// Application state shared across all handlers
 pub struct AppState {
     clipboard_manager: Arc<Mutex<ClipboardManager>>,
     emoji_manager: Arc<Mutex<EmojiManager>>,
+    gif_history: Arc<Mutex<GifHistoryManager>>,
     config_manager: Arc<Mutex<ConfigManager>>,
     is_mouse_inside: Arc<AtomicBool>,
+    /// When true, the watcher stops reading/recording the clipboard (incognito
+    /// mode). Runtime-only: resets on restart so history can't silently stop
+    /// for a forgotten pause.
+    is_monitoring_paused: Arc<AtomicBool>,
+    /// Bumped on every pause/resume so a pending auto-resume timer can tell it
+    /// has been superseded.
+    pause_generation: Arc<AtomicU64>,
+    /// Item ids waiting to be pasted one by one by `paste_next`, in the order
+    /// the user queued them.
+    paste_queue: Arc<Mutex<VecDeque<String>>>,
     /// App data directory (used for diagnostics/log paths).
     data_dir: std::path::PathBuf,
+    /// Native X11 window id of the main window; 0 until known (and on
+    /// Wayland).
+    main_window_xid: AtomicU32,
+    /// When the window hides by itself (`window_close_policy` setting).
+    window_close_policy: Mutex<WindowClosePolicy>,
+    /// Set by the UI's pin button: keeps the window open whatever the policy
+    /// says, until it is hidden explicitly.
+    window_pinned: AtomicBool,
+    /// Settings or window-state files found damaged at startup.
+    settings_load_issues: Vec<LoadIssue>,
+    /// The last previewed import and its path; committing applies exactly
+    /// this, even if the file changed since.
+    pending_import: Mutex<Option<(String, SettingsImport)>>,
 }
 
 // --- Commands ---
@@ -61,11 +104,96 @@ fn get_history(state: State<AppState>) -> Vec<ClipboardItem> {
     state.clipboard_manager.lock().get_history()
 }
 
+/// The page of history after item `after` in display order; `next` is the
+/// cursor for the page after it.
+#[tauri::command]
+fn get_history_page(
+    state: State<AppState>,
+    after: Option<String>,
+    limit: usize,
+) -> Result<HistoryPage, String> {
+    state
+        .clipboard_manager
+        .lock()
+        .get_history_page(after.as_deref(), limit)
+}
+
+/// The whole history without content payloads.
+#[tauri::command]
+fn get_history_meta(state: State<AppState>) -> Vec<ItemMeta> {
+    state.clipboard_manager.lock().get_history_meta()
+}
+
+/// Full content of a single item, fetched when the UI renders it.
+#[tauri::command]
+fn get_item_content(state: State<AppState>, id: String) -> Result<ClipboardContent, String> {
+    state
+        .clipboard_manager
+        .lock()
+        .get_item(&id)
+        .map(|item| item.content.clone())
+        .ok_or_else(|| format!("Item '{}' not found in history", id))
+}
+
 #[tauri::command]
 fn clear_history(state: State<AppState>) {
     state.clipboard_manager.lock().clear();
 }
 
+#[tauri::command]
+fn get_history_lock_state(state: State<AppState>) -> HistoryLockState {
+    state.clipboard_manager.lock().lock_state()
+}
+
+/// Sets, changes (re-encrypting the file) or, with an empty passphrase,
+/// removes the history passphrase.
+#[tauri::command]
+fn set_history_passphrase(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    // Argon2 is slow; derive the key before blocking the watcher
+    let key = if passphrase.is_empty() {
+        None
+    } else {
+        Some(HistoryKey::new(&passphrase)?)
+    };
+    let mut manager = state.clipboard_manager.lock();
+    manager.set_history_key(key)?;
+    let _ = app.emit("history-locked", manager.lock_state());
+    Ok(())
+}
+
+/// Decrypts the history and sends the merged result to the UI.
+#[tauri::command]
+fn unlock_history(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    let Some(content) = state.clipboard_manager.lock().locked_history_file()? else {
+        return Ok(());
+    };
+    // Derives the key, which is slow, without holding the manager
+    let (plaintext, key) = history_crypto::decrypt(&content, &passphrase)?;
+    let mut manager = state.clipboard_manager.lock();
+    manager.unlock_history(&plaintext, key)?;
+    let _ = app.emit("history-locked", manager.lock_state());
+    let _ = app.emit("history-sync", manager.get_history());
+    Ok(())
+}
+
+/// Saves the history encrypted and drops it from memory until unlocked.
+#[tauri::command]
+fn lock_history(app: AppHandle, state: State<AppState>) -> Result<(), String> {
+    let mut manager = state.clipboard_manager.lock();
+    manager.lock_history()?;
+    let _ = app.emit("history-locked", manager.lock_state());
+    let _ = app.emit("history-sync", manager.get_history());
+    Ok(())
+}
+
 /// Returns an actionable message if loading the history had a problem (e.g. the
 /// file was corrupted and recovered), or `None` after a clean load.
 #[tauri::command]
@@ -77,6 +205,18 @@ fn get_history_load_status(state: State<AppState>) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// Returns an actionable message if a settings file was damaged at startup
+/// (restored from its backup or reset to defaults), or `None` after a clean load.
+#[tauri::command]
+fn get_settings_load_status(state: State<AppState>) -> Option<String> {
+    let messages: Vec<&str> = state
+        .settings_load_issues
+        .iter()
+        .map(LoadIssue::message)
+        .collect();
+    (!messages.is_empty()).then(|| messages.join("\n"))
+}
+
 /// Returns a redaction-safe diagnostics report (environment + recent log tail).
 /// Never includes clipboard content.
 #[tauri::command]
@@ -90,6 +230,22 @@ fn export_diagnostics(state: State<AppState>) -> Result<String, String> {
     penguinclip_lib::diagnostics::export_report(&state.data_dir).map(|p| p.display().to_string())
 }
 
+/// Writes the full diagnostic report (environment, permissions, shortcut
+/// setup, watcher health, redacted settings and recent log) as JSON to
+/// `path`, or to the data dir, and returns the file's path.
+#[tauri::command]
+async fn generate_diagnostic_report(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    penguinclip_lib::diagnostics::write_diagnostic_report(
+        &state.data_dir,
+        path.as_deref().map(std::path::Path::new),
+    )
+    .await
+    .map(|p| p.display().to_string())
+}
+
 /// Returns the most recent log lines for display in Settings → Logs.
 #[tauri::command]
 fn get_recent_logs(state: State<AppState>) -> String {
@@ -102,6 +258,12 @@ fn clear_logs(state: State<AppState>) -> Result<(), String> {
     penguinclip_lib::diagnostics::clear_logs(&state.data_dir)
 }
 
+/// Lists the xclip processes still serving pasted GIFs (for debugging).
+#[tauri::command]
+fn get_clipboard_workers() -> Vec<ClipboardWorker> {
+    gif_manager::clipboard_workers()
+}
+
 /// Enables or disables file logging at runtime (persists across restarts).
 #[tauri::command]
 fn set_logging_enabled(state: State<AppState>, enabled: bool) {
@@ -129,20 +291,223 @@ async fn check_for_updates() -> Result<penguinclip_lib::updater::UpdateInfo, Str
         .map_err(|e| format!("The update check did not complete: {}", e))?
 }
 
+/// Writes the history to a versioned JSON file and returns the item count.
+#[tauri::command]
+fn export_history(
+    state: State<AppState>,
+    path: String,
+    include_images: bool,
+) -> Result<usize, String> {
+    state
+        .clipboard_manager
+        .lock()
+        .export_to(std::path::Path::new(&path), include_images)
+}
+
+/// Renders a text item as a QR code image, adds it to history and puts it on
+/// the clipboard.
+#[tauri::command]
+fn generate_qr(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> Result<ClipboardItem, String> {
+    let item = state.clipboard_manager.lock().generate_qr(&id)?;
+    let _ = app.emit("clipboard-changed", &item);
+    Ok(item)
+}
+
+/// Decodes the QR code(s) in an image item, optionally recording the result.
+#[tauri::command]
+fn decode_qr(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    add_to_history: bool,
+) -> Result<String, String> {
+    let (decoded, added) = state
+        .clipboard_manager
+        .lock()
+        .decode_qr(&id, add_to_history)?;
+    if let Some(item) = added {
+        let _ = app.emit("clipboard-changed", &item);
+    }
+    Ok(decoded)
+}
+
+/// Item counts, image storage, top domains and recent daily copies for the
+/// settings page.
+#[tauri::command]
+fn get_history_stats(state: State<AppState>) -> HistoryStats {
+    state.clipboard_manager.lock().history_stats()
+}
+
+/// Saves an item as a file ("Save image as…"); returns the absolute path.
+#[tauri::command]
+fn save_item_to_file(
+    state: State<AppState>,
+    id: String,
+    path: String,
+    overwrite: bool,
+) -> Result<String, SaveItemError> {
+    state
+        .clipboard_manager
+        .lock()
+        .save_item_to_file(&id, std::path::Path::new(&path), overwrite)
+        .map(|path| path.display().to_string())
+}
+
+/// Imports a history export, merging with or replacing the current history.
+#[tauri::command]
+fn import_history(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    merge: bool,
+) -> Result<ImportSummary, String> {
+    let mut manager = state.clipboard_manager.lock();
+    let summary = manager.import_from(std::path::Path::new(&path), merge)?;
+    let history = manager.get_history();
+    drop(manager);
+    let _ = app.emit("history-sync", &history);
+    Ok(summary)
+}
+
 #[tauri::command]
 fn delete_item(state: State<AppState>, id: String) {
     state.clipboard_manager.lock().remove_item(&id);
 }
 
+/// Deletes several items at once and sends a single `history-sync`.
 #[tauri::command]
-fn toggle_pin(state: State<AppState>, id: String) -> Option<ClipboardItem> {
-    let result = state.clipboard_manager.lock().toggle_pin(&id);
-    if result.is_none() {
-        warn!("toggle_pin: item '{}' not found in history", id);
-    }
+fn delete_items(app: AppHandle, state: State<AppState>, ids: Vec<String>) -> BulkDeleteResult {
+    let mut manager = state.clipboard_manager.lock();
+    let result = manager.delete_items(&ids);
+    let history = manager.get_history();
+    drop(manager);
+    let _ = app.emit("history-sync", &history);
     result
 }
 
+/// Deletes unpinned items older than `minutes`. Returns the number removed.
+#[tauri::command]
+fn clear_unpinned_older_than(app: AppHandle, state: State<AppState>, minutes: u64) -> usize {
+    let mut manager = state.clipboard_manager.lock();
+    let removed = manager.clear_unpinned_older_than(minutes);
+    let history = manager.get_history();
+    drop(manager);
+    if removed > 0 {
+        let _ = app.emit("history-sync", &history);
+    }
+    removed
+}
+
+/// Deletes unpinned items of one type (`"text"` or `"image"`). Returns the
+/// number removed.
+#[tauri::command]
+fn clear_by_type(
+    app: AppHandle,
+    state: State<AppState>,
+    content_type: String,
+) -> Result<usize, String> {
+    clear_type_and_sync(&app, &state, &content_type)
+}
+
+fn clear_type_and_sync(
+    app: &AppHandle,
+    state: &AppState,
+    content_type: &str,
+) -> Result<usize, String> {
+    let mut manager = state.clipboard_manager.lock();
+    let removed = manager.clear_type(content_type)?;
+    let history = manager.get_history();
+    drop(manager);
+    if removed > 0 {
+        debug!("cleared {} {} item(s)", removed, content_type);
+        let _ = app.emit("history-sync", &history);
+    }
+    Ok(removed)
+}
+
+/// Pins or unpins an item. Fails with a user-facing message if the item is
+/// gone or the pin limit is reached.
+#[tauri::command]
+fn toggle_pin(state: State<AppState>, id: String) -> Result<PinToggleResult, String> {
+    let mut manager = state.clipboard_manager.lock();
+    let item = manager.toggle_pin(&id).map_err(|e| {
+        warn!("toggle_pin: {}", e);
+        e
+    })?;
+    Ok(PinToggleResult {
+        item,
+        order: manager.history_order(),
+    })
+}
+
+/// Opens the URL or email detection at `index` of an item's Smart Actions.
+#[tauri::command]
+fn open_url(state: State<AppState>, id: String, index: usize) -> Result<(), String> {
+    let detection = {
+        let manager = state.clipboard_manager.lock();
+        let item = manager
+            .get_item(&id)
+            .ok_or_else(|| format!("Item '{}' not found in history", id))?;
+        item.detected
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("Item '{}' has no detection at index {}", id, index))?
+    };
+    smart_actions::open_detection(&detection)
+}
+
+/// Clipboard watcher counters (wake-ups, text/image reads), for verifying
+/// that an idle clipboard causes almost no reads.
+#[tauri::command]
+fn get_watcher_stats() -> WatchStats {
+    clipboard_watch::stats()
+}
+
+/// Clipboard read health: consecutive and total failures, the last error and
+/// the last successful read.
+#[tauri::command]
+fn get_watcher_health() -> WatcherHealth {
+    clipboard_watch::health()
+}
+
+/// Searches history; supports `tag:<name>` terms alongside plain text.
+#[tauri::command]
+fn search_history(state: State<AppState>, query: String) -> Vec<ClipboardItem> {
+    state.clipboard_manager.lock().search(&query)
+}
+
+#[tauri::command]
+fn get_items_by_tag(state: State<AppState>, tag: String) -> Vec<ClipboardItem> {
+    state.clipboard_manager.lock().get_items_by_tag(&tag)
+}
+
+/// Adds a tag to an item and returns the updated item.
+#[tauri::command]
+fn add_tag(state: State<AppState>, id: String, tag: String) -> Result<ClipboardItem, String> {
+    state.clipboard_manager.lock().add_tag(&id, &tag)
+}
+
+/// Removes a tag from an item and returns the updated item.
+#[tauri::command]
+fn remove_tag(state: State<AppState>, id: String, tag: String) -> Result<ClipboardItem, String> {
+    state.clipboard_manager.lock().remove_tag(&id, &tag)
+}
+
+/// Puts a text item on the PRIMARY selection for middle-click pasting.
+#[tauri::command]
+fn paste_to_primary(state: State<AppState>, id: String) -> Result<(), String> {
+    let mut manager = state.clipboard_manager.lock();
+    let item = manager
+        .get_item(&id)
+        .cloned()
+        .ok_or_else(|| format!("Item '{}' not found in history", id))?;
+    manager.set_primary_selection(&item)
+}
+
 #[tauri::command]
 fn toggle_favorite(state: State<AppState>, id: String) -> Option<ClipboardItem> {
     let result = state.clipboard_manager.lock().toggle_favorite(&id);
@@ -152,9 +517,82 @@ fn toggle_favorite(state: State<AppState>, id: String) -> Option<ClipboardItem>
     result
 }
 
+/// Recently used emojis, each with the skin tone it was last used with
+#[tauri::command]
+fn get_recent_emojis(state: State<AppState>) -> Vec<RecentEmoji> {
+    state.emoji_manager.lock().get_recent_toned()
+}
+
+/// Searches the emoji dataset by name, `:shortcode:` and keywords, with
+/// recently used emojis ranked higher.
+#[tauri::command]
+fn search_emojis(state: State<AppState>, query: String, limit: usize) -> Vec<EmojiInfo> {
+    state.emoji_manager.lock().search(&query, limit)
+}
+
+/// Favorites or unfavorites an emoji. Returns the updated favorites.
+#[tauri::command]
+fn toggle_favorite_emoji(state: State<AppState>, char: String) -> Vec<String> {
+    state.emoji_manager.lock().toggle_favorite(&char)
+}
+
+#[tauri::command]
+fn get_favorite_emojis(state: State<AppState>) -> Vec<String> {
+    state.emoji_manager.lock().get_favorites()
+}
+
+#[tauri::command]
+fn get_recent_gifs(state: State<AppState>) -> Vec<GifUsage> {
+    state.gif_history.lock().get_recent()
+}
+
+#[tauri::command]
+fn record_gif_usage(
+    state: State<AppState>,
+    url: String,
+    preview_url: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+) {
+    state
+        .gif_history
+        .lock()
+        .record_usage(&url, preview_url, width.zip(height));
+}
+
+/// Returns whether the GIF is now a favorite.
+#[tauri::command]
+fn toggle_favorite_gif(state: State<AppState>, url: String, preview_url: Option<String>) -> bool {
+    state.gif_history.lock().toggle_favorite(&url, preview_url)
+}
+
+/// The `limit` most used emojis, with recent uses weighing more (see the
+/// `emoji_decay_half_life_days` setting)
+#[tauri::command]
+fn get_top_used_emojis(state: State<AppState>, limit: usize) -> Vec<RecentEmoji> {
+    state
+        .emoji_manager
+        .lock()
+        .get_top_used_decayed(limit)
+        .into_iter()
+        .map(RecentEmoji::from)
+        .collect()
+}
+
+#[tauri::command]
+fn get_default_skin_tone(state: State<AppState>) -> SkinTone {
+    state.emoji_manager.lock().default_skin_tone()
+}
+
+#[tauri::command]
+fn set_default_skin_tone(state: State<AppState>, tone: SkinTone) {
+    state.emoji_manager.lock().set_default_skin_tone(tone);
+}
+
+/// Returns every emoji grouped by picker category.
 #[tauri::command]
-fn get_recent_emojis(state: State<AppState>) -> Vec<EmojiUsage> {
-    state.emoji_manager.lock().get_recent()
+fn get_emoji_categories() -> Vec<EmojiCategory> {
+    emoji_manager::emoji_categories()
 }
 
 #[tauri::command]
@@ -162,10 +600,22 @@ fn set_mouse_state(state: State<AppState>, inside: bool) {
     state.is_mouse_inside.store(inside, Ordering::Relaxed);
 }
 
-/// Whether clipboard recording is currently paused (toggled from the tray).
+/// Pauses clipboard monitoring. With `minutes`, monitoring resumes
+/// automatically after that long (unless paused/resumed again meanwhile).
+#[tauri::command]
+fn pause_monitoring(app: AppHandle, minutes: Option<u64>) {
+    MonitoringController::pause(&app, minutes);
+}
+
+#[tauri::command]
+fn resume_monitoring(app: AppHandle) {
+    MonitoringController::set_paused(&app, false);
+}
+
+/// Whether clipboard monitoring is currently paused (incognito mode).
 #[tauri::command]
-fn is_recording_paused() -> bool {
-    RECORDING_PAUSED.load(Ordering::SeqCst)
+fn is_monitoring_paused(state: State<AppState>) -> bool {
+    state.is_monitoring_paused.load(Ordering::SeqCst)
 }
 
 // --- User Settings Commands ---
@@ -176,14 +626,72 @@ fn get_user_settings() -> Result<UserSettings, String> {
     Ok(manager.load())
 }
 
+/// Adds a custom kaomoji and returns the updated custom list.
+#[tauri::command]
+fn add_custom_kaomoji(
+    app: AppHandle,
+    text: String,
+    keywords: Option<Vec<String>>,
+) -> Result<Vec<CustomKaomoji>, String> {
+    let manager = UserSettingsManager::new();
+    let mut settings = manager.load();
+    kaomoji::add_custom(
+        &mut settings.custom_kaomojis,
+        &text,
+        keywords.unwrap_or_default(),
+    )?;
+    manager.save(&settings)?;
+    notify_settings_changed(&app, &settings)?;
+    Ok(settings.custom_kaomojis)
+}
+
+/// Removes a custom kaomoji and returns the updated custom list.
+#[tauri::command]
+fn remove_custom_kaomoji(app: AppHandle, text: String) -> Result<Vec<CustomKaomoji>, String> {
+    let manager = UserSettingsManager::new();
+    let mut settings = manager.load();
+    kaomoji::remove_custom(&mut settings.custom_kaomojis, &text)?;
+    manager.save(&settings)?;
+    notify_settings_changed(&app, &settings)?;
+    Ok(settings.custom_kaomojis)
+}
+
+/// Lets every window know the saved settings changed, e.g. so the clipboard
+/// window's kaomoji picker picks up a new custom kaomoji
+fn notify_settings_changed(app: &AppHandle, settings: &UserSettings) -> Result<(), String> {
+    app.emit("app-settings-changed", settings)
+        .map_err(|e| format!("Failed to emit settings changed event: {}", e))
+}
+
+/// Searches custom and built-in kaomoji; custom entries come first.
+#[tauri::command]
+fn search_kaomojis(query: String) -> Vec<Kaomoji> {
+    let custom = UserSettingsManager::new().load().custom_kaomojis;
+    kaomoji::search(&query, &custom)
+}
+
 #[tauri::command]
 fn set_user_settings(
     app: AppHandle,
     state: State<AppState>,
-    new_settings: UserSettings,
-) -> Result<(), String> {
+    mut new_settings: UserSettings,
+) -> Result<Vec<ValidationIssue>, String> {
     let manager = UserSettingsManager::new();
+    let stored = manager.load();
+    // Kept by the shortcut register/unregister commands; the UI may hold a
+    // stale copy
+    new_settings.disabled_shortcuts = stored.disabled_shortcuts.clone();
+    new_settings.keep_shortcut_overrides(&stored);
+
+    // Reject bad paste rules, keystrokes or shortcuts instead of silently resetting them
+    let paste_config = new_settings.paste_config()?;
+    let shortcut_bindings = new_settings.shortcut_bindings()?;
+
+    let old_shortcut_bindings = stored.shortcut_bindings().unwrap_or_default();
     manager.save(&new_settings)?;
+    // Apply the values as saved: out of range ones are clamped, and the
+    // issues tell the UI what changed
+    let issues = new_settings.validate();
 
     // Update clipboard manager's max history size and exclusion rules if changed
     {
@@ -192,15 +700,43 @@ fn set_user_settings(
             clipboard_manager.set_max_history_size(new_settings.max_history_size);
         }
         clipboard_manager.set_excluded_patterns(&new_settings.excluded_patterns);
+        clipboard_manager.set_excluded_apps(&new_settings.excluded_apps);
+        clipboard_manager.set_sensitive_expiry(new_settings.sensitive_ttl());
+        clipboard_manager.set_pin_options(new_settings.pinned_on_top, new_settings.pin_limit());
+        clipboard_manager.set_smart_actions(new_settings.enable_smart_actions);
+        clipboard_watch::set_primary_tracking(new_settings.track_primary_selection);
+        input_simulator::set_paste_config(paste_config);
+        *state.window_close_policy.lock() = new_settings.window_close_policy();
+        clipboard_manager.set_item_size_limits(
+            new_settings.max_text_item_bytes,
+            new_settings.max_image_item_bytes,
+            new_settings.truncate_oversized_text(),
+        );
+    }
+    state
+        .emoji_manager
+        .lock()
+        .set_decay_half_life_days(new_settings.emoji_decay_half_life_days);
+
+    // Move the shortcuts if they changed: portal-bound ones in a new portal
+    // session, the rest by editing the desktop's config files
+    if shortcut_bindings != old_shortcut_bindings && global_shortcuts_portal::is_active() {
+        global_shortcuts_portal::rebind(&shortcut_bindings);
+    } else if shortcut_bindings != old_shortcut_bindings {
+        std::thread::spawn(move || {
+            penguinclip_lib::linux_shortcut_manager::rebind_global_shortcut(
+                &old_shortcut_bindings,
+                &shortcut_bindings,
+            );
+        });
     }
 
     // Emit event to notify all windows that settings have changed
-    app.emit("app-settings-changed", &new_settings)
-        .map_err(|e| format!("Failed to emit settings changed event: {}", e))?;
+    notify_settings_changed(&app, &new_settings)?;
 
-    // Refresh tray icon immediately to reflect possible dynamic setting change
+    // Refresh tray icon immediately to reflect possible tray icon setting changes
 
-    theme_manager::update_dynamic_tray_flag(new_settings.enable_dynamic_tray_icon);
+    theme_manager::update_tray_flags(&new_settings);
 
     let app_for_tray = app.clone();
     let settings_for_tray = new_settings.clone();
@@ -208,7 +744,83 @@ fn set_user_settings(
         theme_manager::refresh_tray_icon(&app_for_tray, &settings_for_tray).await;
     });
 
-    Ok(())
+    Ok(issues)
+}
+
+/// Writes the current settings, including custom kaomojis, to `path` as one
+/// JSON file, optionally with the emoji usage history.
+#[tauri::command]
+fn export_settings(
+    state: State<AppState>,
+    path: String,
+    include_emoji_history: Option<bool>,
+) -> Result<(), String> {
+    let bundle = SettingsBundle {
+        settings: UserSettingsManager::new().load(),
+        emoji_history: include_emoji_history
+            .unwrap_or(false)
+            .then(|| state.emoji_manager.lock().export_history()),
+    };
+    atomic_write::write_atomic(std::path::Path::new(&path), bundle.to_json()?.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Reads and validates an export file; an unusable emoji history is dropped
+/// with a warning rather than failing the whole import.
+fn read_settings_import(path: &str) -> Result<SettingsImport, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut import = SettingsBundle::parse_import(&content)?;
+    if let Some(history) = &import.emoji_history {
+        if let Err(e) = EmojiManager::check_history(history) {
+            warn!("{}", e);
+            import.warnings.push(ImportWarning {
+                field: "emoji_history".to_string(),
+                kind: ImportWarningKind::Rejected,
+                imported: history.clone(),
+                applied: None,
+            });
+            import.emoji_history = None;
+        }
+    }
+    Ok(import)
+}
+
+/// Lists what importing `path` would adjust or drop, without saving anything.
+#[tauri::command]
+fn preview_import(state: State<AppState>, path: String) -> Result<ImportReport, String> {
+    let import = read_settings_import(&path)?;
+    let report = import.report(false);
+    *state.pending_import.lock() = Some((path, import));
+    Ok(report)
+}
+
+/// Imports settings from an export file. Nothing is saved unless `commit` is
+/// true, so the UI can show the preview report and ask first; committing
+/// applies the previewed import rather than reading the file again.
+#[tauri::command]
+fn import_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    commit: bool,
+) -> Result<ImportReport, String> {
+    if !commit {
+        return preview_import(state, path);
+    }
+    let import = match state.pending_import.lock().take() {
+        Some((previewed, import)) if previewed == path => import,
+        _ => return Err(format!("Preview {} before importing it", path)),
+    };
+
+    // Settings first: the emoji history is only worth keeping if they saved
+    let emoji_manager = state.emoji_manager.clone();
+    let report = import.report(true);
+    set_user_settings(app, state, import.settings)?;
+    if let Some(history) = &import.emoji_history {
+        emoji_manager.lock().import_history(history)?;
+    }
+    Ok(report)
 }
 
 #[tauri::command]
@@ -238,6 +850,19 @@ async fn refresh_system_theme() -> ThemeInfo {
     theme_manager::get_system_color_scheme().await
 }
 
+/// Get the system accent color (portal, then GNOME and KDE settings)
+#[tauri::command]
+async fn get_system_accent_color() -> Option<AccentColor> {
+    theme_manager::get_system_accent_color().await
+}
+
+/// Re-applies the tray icon for the saved settings and the current color scheme
+#[tauri::command]
+async fn refresh_tray_icon(app: AppHandle) {
+    let settings = UserSettingsManager::new().load();
+    theme_manager::refresh_tray_icon(&app, &settings).await;
+}
+
 /// Check if the D-Bus event listener is running for theme changes
 #[tauri::command]
 fn is_theme_listener_active() -> bool {
@@ -255,7 +880,7 @@ async fn paste_item(app: AppHandle, state: State<'_, AppState>, id: String) -> R
     match item {
         Some(item) => {
             // 2. Prepare Environment (Hide Window -> Restore Focus)
-            WindowController::hide(&app);
+            WindowController::hide_for_paste(&app);
             PasteHelper::prepare_target_window().await?;
 
             // 3. Perform Paste
@@ -281,6 +906,210 @@ async fn paste_item(app: AppHandle, state: State<'_, AppState>, id: String) -> R
     Ok(())
 }
 
+/// Pastes an item back into the window it was copied from, or into the
+/// previously focused window when that one no longer exists.
+#[tauri::command]
+async fn paste_item_to_source(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let item = state.clipboard_manager.lock().get_item(&id).cloned();
+    let Some(item) = item else {
+        let history = state.clipboard_manager.lock().get_history();
+        let _ = app.emit("history-sync", &history);
+        return Err(format!("Item '{}' not found. History has been synced.", id));
+    };
+
+    WindowController::hide_for_paste(&app);
+    PasteHelper::prepare_source_window(item.source_window_id).await?;
+
+    let mut manager = state.clipboard_manager.lock();
+    manager.paste_item(&item).map_err(|e| e.to_string())?;
+    let history = manager.get_history();
+    drop(manager);
+    let _ = app.emit("history-sync", &history);
+    Ok(())
+}
+
+/// Types an item's text into the focused window as keystrokes, for targets
+/// that ignore pasting. The clipboard is left untouched.
+#[tauri::command]
+async fn type_item(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let item = state.clipboard_manager.lock().get_item(&id).cloned();
+    let Some(item) = item else {
+        return Err(format!("Item '{}' not found", id));
+    };
+    let text = item
+        .plain_text()
+        .ok_or("Images can't be typed; paste them instead")?
+        .to_string();
+    let char_delay_ms = input_simulator::type_char_delay();
+
+    WindowController::hide_for_paste(&app);
+    PasteHelper::prepare_target_window().await?;
+
+    tauri::async_runtime::spawn_blocking(move || input_simulator::type_text(&text, char_delay_ms))
+        .await
+        .map_err(|e| format!("Typing task failed: {}", e))?
+}
+
+/// Reports what a paste would do without sending keys. The focused window
+/// is read after `delay_secs` (up to 10) so the user can switch to the
+/// target app first.
+#[tauri::command]
+async fn diagnose_paste(delay_secs: Option<u64>) -> Result<PasteDiagnostics, String> {
+    tokio::time::sleep(Duration::from_secs(delay_secs.unwrap_or(0).min(10))).await;
+    tauri::async_runtime::spawn_blocking(input_simulator::diagnose_paste)
+        .await
+        .map_err(|e| format!("Diagnostics task failed: {}", e))
+}
+
+/// Pastes an item like `paste_item`, then deletes it from history. If any
+/// step of the paste fails the item is kept.
+#[tauri::command]
+async fn paste_item_and_delete(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let item = state.clipboard_manager.lock().get_item(&id).cloned();
+    let Some(item) = item else {
+        warn!(
+            "paste_item_and_delete: item '{}' not found in history; syncing frontend",
+            id
+        );
+        let history = state.clipboard_manager.lock().get_history();
+        let _ = app.emit("history-sync", &history);
+        return Err(format!("Item '{}' not found. History has been synced.", id));
+    };
+
+    WindowController::hide_for_paste(&app);
+    PasteHelper::prepare_target_window().await?;
+
+    let mut manager = state.clipboard_manager.lock();
+    manager.paste_and_remove(&item)?;
+    let history = manager.get_history();
+    drop(manager);
+    let _ = app.emit("history-sync", &history);
+    Ok(())
+}
+
+/// Pastes the item at 1-based `index` in history order straight into the
+/// focused window, without showing ours (numbered quick paste). Pinned items
+/// are skipped if the `quick_paste_skip_pinned` setting is on. An index past
+/// the end of history is logged and ignored.
+#[tauri::command]
+async fn paste_item_by_index(app: AppHandle, index: usize) -> Result<(), String> {
+    paste_by_index(&app, index).await
+}
+
+async fn paste_by_index(app: &AppHandle, index: usize) -> Result<(), String> {
+    let skip_pinned = UserSettingsManager::new().load().quick_paste_skip_pinned;
+    let state = app.state::<AppState>();
+    let Some(item) = state
+        .clipboard_manager
+        .lock()
+        .item_at_position(index, skip_pinned)
+    else {
+        warn!("paste_item_by_index: no history item at position {}", index);
+        return Ok(());
+    };
+
+    // The target app already has focus (the window was never shown), so
+    // only give the shortcut's modifier keys a moment to be released.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut manager = state.clipboard_manager.lock();
+    manager.paste_item(&item)?;
+    let history = manager.get_history();
+    drop(manager);
+    let _ = app.emit("history-sync", &history);
+    Ok(())
+}
+
+/// Parses `--paste-index N` from a command line.
+fn paste_index_arg(argv: &[String]) -> Option<usize> {
+    let pos = argv.iter().position(|arg| arg == "--paste-index")?;
+    argv.get(pos + 1)?.parse().ok()
+}
+
+/// Error returned by `paste_next` when nothing is queued, so the UI can stop
+/// prompting for the next paste.
+const PASTE_QUEUE_EMPTY: &str = "Paste queue is empty";
+
+/// Appends items to the paste queue in the given order. Ids not in history are
+/// ignored. Returns the resulting queue.
+#[tauri::command]
+fn queue_items(app: AppHandle, state: State<AppState>, ids: Vec<String>) -> Vec<String> {
+    let known: Vec<String> = {
+        let manager = state.clipboard_manager.lock();
+        ids.into_iter()
+            .filter(|id| manager.get_item(id).is_some())
+            .collect()
+    };
+    let mut queue = state.paste_queue.lock();
+    queue.extend(known);
+    let _ = app.emit("paste-queue-changed", queue.len());
+    queue.iter().cloned().collect()
+}
+
+/// Pastes the item at the front of the paste queue through the normal
+/// `paste_item` flow. Fails with `PASTE_QUEUE_EMPTY` when nothing is queued.
+#[tauri::command]
+async fn paste_next(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let (next, remaining) = {
+        let mut queue = state.paste_queue.lock();
+        (queue.pop_front(), queue.len())
+    };
+    let id = next.ok_or_else(|| PASTE_QUEUE_EMPTY.to_string())?;
+    let _ = app.emit("paste-queue-changed", remaining);
+    paste_item(app, state, id).await
+}
+
+#[tauri::command]
+fn clear_queue(app: AppHandle, state: State<AppState>) {
+    state.paste_queue.lock().clear();
+    let _ = app.emit("paste-queue-changed", 0usize);
+}
+
+#[tauri::command]
+fn get_queue(state: State<AppState>) -> Vec<String> {
+    state.paste_queue.lock().iter().cloned().collect()
+}
+
+/// Pastes an item's text after applying a named transform (see
+/// `text_transforms`). Image items and invalid input are rejected before the
+/// window is hidden.
+#[tauri::command]
+async fn paste_item_transformed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    transform: String,
+) -> Result<(), String> {
+    let transformed = {
+        let manager = state.clipboard_manager.lock();
+        let item = manager
+            .get_item(&id)
+            .ok_or_else(|| format!("Item '{}' not found in history", id))?;
+        let text = item
+            .plain_text()
+            .ok_or_else(|| "Image items cannot be transformed".to_string())?;
+        text_transforms::apply(&transform, text)?
+    };
+
+    WindowController::hide_for_paste(&app);
+    PasteHelper::prepare_target_window().await?;
+
+    let mut manager = state.clipboard_manager.lock();
+    manager.paste_derived_text(&id, &transformed)?;
+    let history = manager.get_history();
+    drop(manager);
+    let _ = app.emit("history-sync", &history);
+    Ok(())
+}
+
 #[tauri::command]
 async fn paste_text(
     app: AppHandle,
@@ -288,15 +1117,13 @@ async fn paste_text(
     text: String,
     item_type: Option<String>,
 ) -> Result<(), String> {
-    // 0. Record usage if applicable
-    if let Some(t) = item_type.as_deref() {
-        if t == "emoji" {
-            state.emoji_manager.lock().record_usage(&text);
-        }
+    // 0. Record usage if applicable; the emoji recents are for emoji only
+    if item_type.as_deref() == Some("emoji") {
+        state.emoji_manager.lock().record_usage(&text);
     }
 
     // 1. Prepare Environment
-    WindowController::hide(&app);
+    WindowController::hide_for_paste(&app);
     PasteHelper::prepare_target_window().await?;
 
     // 2. Set Clipboard & Mark
@@ -322,15 +1149,23 @@ async fn paste_gif_from_url(
     app: AppHandle,
     state: State<'_, AppState>,
     url: String,
-) -> Result<(), String> {
+    preview_url: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<(), GifPasteError> {
     // 1. Download (Blocking) - Window stays open to show loading if UI supports it
     let url_clone = url.clone();
+    let options = GifPasteOptions::from_settings(&UserSettingsManager::new().load());
     let file_uri = tokio::task::spawn_blocking(move || {
-        penguinclip_lib::gif_manager::paste_gif_to_clipboard_with_uri(&url_clone)
+        gif_manager::paste_gif_to_clipboard_with_uri(&url_clone, options)
     })
     .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| e.to_string())??;
+
+    state
+        .gif_history
+        .lock()
+        .record_usage(&url, preview_url, width.zip(height));
 
     // 2. Mark as pasted
     if let Some(uri) = file_uri {
@@ -342,7 +1177,7 @@ async fn paste_gif_from_url(
     }
 
     // 3. Prepare Environment & Paste
-    WindowController::hide(&app);
+    WindowController::hide_for_paste(&app);
     PasteHelper::prepare_target_window().await?;
 
     // The clipboard is already set by paste_gif_to_clipboard_with_uri, we just need to paste
@@ -351,9 +1186,22 @@ async fn paste_gif_from_url(
     Ok(())
 }
 
+/// Hides the clipboard window (Esc or the close button).
 #[tauri::command]
-async fn finish_paste(app: AppHandle) -> Result<(), String> {
+fn hide_window(app: AppHandle) {
     WindowController::hide(&app);
+}
+
+/// Pins the window open regardless of `window_close_policy`, or unpins it.
+/// The pin is dropped whenever the window hides.
+#[tauri::command]
+fn set_window_pinned(state: State<AppState>, pinned: bool) {
+    state.window_pinned.store(pinned, Ordering::Relaxed);
+}
+
+#[tauri::command]
+async fn finish_paste(app: AppHandle) -> Result<(), String> {
+    WindowController::hide_for_paste(&app);
     PasteHelper::prepare_target_window().await?;
     simulate_paste_keystroke().map_err(|e| e.to_string())?;
     Ok(())
@@ -380,7 +1228,8 @@ async fn finish_setup(app: AppHandle) -> Result<(), String> {
     }
 
     if let Some(main_window) = app.get_webview_window("main") {
-        WindowController::position_and_show(&main_window, &app);
+        let window_id = WindowController::main_window_xid(&main_window, &app);
+        WindowController::position_and_show(&main_window, &app, window_id);
     }
 
     let _ = app.emit("setup_complete", ());
@@ -389,14 +1238,47 @@ async fn finish_setup(app: AppHandle) -> Result<(), String> {
 
 // --- Helper for Paste Logic ---
 
+/// Native X11 window id of `window`, from its GTK window's XID.
+fn x11_window_id(window: &WebviewWindow) -> Option<u32> {
+    match window.window_handle().ok()?.as_raw() {
+        RawWindowHandle::Xlib(handle) => u32::try_from(handle.window).ok(),
+        RawWindowHandle::Xcb(handle) => Some(handle.window.get()),
+        _ => None,
+    }
+}
+
 struct PasteHelper;
 
 impl PasteHelper {
     /// Restores focus to the previous window and waits for it to settle.
     /// This ensures keystrokes are sent to the correct application.
     async fn prepare_target_window() -> Result<(), String> {
-        if let Err(e) = restore_focused_window() {
-            warn!("focus restoration failed before paste: {}", e);
+        // Sway, Hyprland and KWin restore through their IPC, GNOME through
+        // the Shell helper extension when installed; elsewhere (and for
+        // XWayland apps) the X11 path is the best we have
+        let wayland_restored = is_wayland()
+            && focus_manager::restore_focused_wayland_window()
+                .map_err(|e| warn!("Wayland focus restoration failed before paste: {}", e))
+                .is_ok();
+        if !wayland_restored {
+            if let Err(e) = restore_focused_window() {
+                warn!("focus restoration failed before paste: {}", e);
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(())
+    }
+
+    /// Activates the window an item was copied from, falling back to the
+    /// previously focused window when it has since closed.
+    async fn prepare_source_window(window_id: Option<u32>) -> Result<(), String> {
+        let Some(window_id) = window_id.filter(|&id| focus_manager::is_client_window(id)) else {
+            debug!("source window is gone; pasting into the previous window");
+            return Self::prepare_target_window().await;
+        };
+        if let Err(e) = focus_manager::x11_activate_window_by_id(window_id) {
+            warn!("source window activation failed: {}", e);
+            return Self::prepare_target_window().await;
         }
         tokio::time::sleep(Duration::from_millis(100)).await;
         Ok(())
@@ -428,7 +1310,7 @@ impl WindowController {
                 if let Some(tab_name) = tab {
                     let _ = app.emit("switch-tab", tab_name);
                 } else {
-                    let _ = window.hide();
+                    Self::hide(app);
                 }
             } else {
                 save_focused_window();
@@ -450,7 +1332,8 @@ impl WindowController {
                     }
                 }
 
-                Self::position_and_show(&window, app);
+                let window_id = Self::main_window_xid(&window, app);
+                Self::position_and_show(&window, app, window_id);
             }
         }
     }
@@ -462,18 +1345,57 @@ impl WindowController {
                 if is_wayland() {
                     state.config_manager.lock().sync_to_disk();
                 }
+                // A pin only lasts until the window closes
+                state.window_pinned.store(false, Ordering::Relaxed);
             }
             let _ = window.hide();
         }
     }
 
-    fn position_and_show(window: &WebviewWindow, app: &AppHandle) {
+    /// Hides the window before pasting, unless the close policy or a pin
+    /// keeps it open.
+    pub fn hide_for_paste(app: &AppHandle) {
+        if Self::may_auto_hide(app, WindowClosePolicy::hides_on_paste) {
+            Self::hide(app);
+        }
+    }
+
+    /// Whether the window may hide by itself: not pinned, and allowed by
+    /// the close policy through `policy_allows`.
+    fn may_auto_hide(app: &AppHandle, policy_allows: fn(WindowClosePolicy) -> bool) -> bool {
+        let Some(state) = app.try_state::<AppState>() else {
+            return true;
+        };
+        !state.window_pinned.load(Ordering::Relaxed)
+            && policy_allows(*state.window_close_policy.lock())
+    }
+
+    /// The main window's native X11 id, cached in `AppState` once known.
+    /// `None` on Wayland.
+    fn main_window_xid(window: &WebviewWindow, app: &AppHandle) -> Option<u32> {
+        if !is_x11() {
+            return None;
+        }
+        let state = app.state::<AppState>();
+        let cached = state.main_window_xid.load(Ordering::Relaxed);
+        if cached != 0 {
+            return Some(cached);
+        }
+        let xid = x11_window_id(window)?;
+        state.main_window_xid.store(xid, Ordering::Relaxed);
+        focus_manager::set_own_window(xid);
+        Some(xid)
+    }
+
+    /// Shows the window and, on X11, activates it. `window_id` is its native
+    /// X11 id; without one activation falls back to a title search.
+    fn position_and_show(window: &WebviewWindow, app: &AppHandle, window_id: Option<u32>) {
         let state = app.state::<AppState>();
 
         if is_wayland() {
             Self::position_for_wayland(window, &state);
         } else {
-            Self::position_for_non_wayland(window);
+            Self::position_for_non_wayland(window, window_id);
         }
 
         let is_wayland_session = is_wayland();
@@ -502,15 +1424,19 @@ impl WindowController {
                 let _ = window_clone.set_focus();
             } else {
                 // Use EWMH _NET_ACTIVE_WINDOW protocol with polling instead of fixed sleep.
-                // This waits for the window to actually appear in X11's client list
-                // before attempting activation, solving the race condition.
-                if let Err(e) = x11_robust_activate(MAIN_WINDOW_TITLE) {
+                // This waits for the window to actually be mapped before
+                // attempting activation, solving the race condition.
+                let activated = match window_id {
+                    Some(id) => x11_robust_activate(id),
+                    None => x11_robust_activate_by_title(MAIN_WINDOW_TITLE),
+                };
+                if let Err(e) = activated {
                     warn!(
                         "X11 window activation failed, falling back to xdotool: {}",
                         e
                     );
                     // Fallback: try xdotool as last resort
-                    let _ = Self::x11_activate_window_xdotool();
+                    let _ = Self::x11_activate_window_xdotool(window_id);
                 }
             }
 
@@ -518,16 +1444,21 @@ impl WindowController {
         });
     }
 
-    /// Activate window on X11 using xdotool (fallback method)
-    fn x11_activate_window_xdotool() -> Result<(), String> {
+    /// Activate window on X11 using xdotool (fallback method). Searches by
+    /// title only when the window id is unknown.
+    fn x11_activate_window_xdotool(window_id: Option<u32>) -> Result<(), String> {
         use std::process::Command;
 
-        let output = Command::new("xdotool")
-            .args(["search", "--name", MAIN_WINDOW_TITLE])
-            .output()
-            .map_err(|e| format!("xdotool search failed: {}", e))?;
-
-        let window_ids = String::from_utf8_lossy(&output.stdout);
+        let window_ids = match window_id {
+            Some(id) => id.to_string(),
+            None => {
+                let output = Command::new("xdotool")
+                    .args(["search", "--name", MAIN_WINDOW_TITLE])
+                    .output()
+                    .map_err(|e| format!("xdotool search failed: {}", e))?;
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+        };
         if let Some(window_id) = window_ids.lines().next() {
             Command::new("xdotool")
                 .args(["windowactivate", "--sync", window_id])
@@ -547,15 +1478,20 @@ impl WindowController {
                 let win_size = window.outer_size().unwrap_or(PhysicalSize::new(360, 480));
 
                 let window_state = config.get_state();
-                let pos = resolve_window_position(&window_state, &monitors, win_size);
+                let current = window.current_monitor().ok().flatten();
+                let placement =
+                    resolve_window_position(&window_state, &monitors, current.as_ref(), win_size);
 
-                let _ = window.set_position(pos);
+                if let Some(size) = placement.size {
+                    let _ = window.set_size(size);
+                }
+                let _ = window.set_position(placement.position);
             }
         }
     }
 
-    fn position_for_non_wayland(window: &WebviewWindow) {
-        let (cursor_x, cursor_y) = match Self::get_cursor_position(window) {
+    fn position_for_non_wayland(window: &WebviewWindow, window_id: Option<u32>) {
+        let (cursor_x, cursor_y) = match Self::get_cursor_position(window, window_id) {
             Some(pos) => pos,
             None => {
                 // Fallback: center the window if we can't get cursor position
@@ -602,7 +1538,7 @@ impl WindowController {
         PhysicalPosition::new(safe_x, safe_y)
     }
 
-    fn get_cursor_position(window: &WebviewWindow) -> Option<(i32, i32)> {
+    fn get_cursor_position(window: &WebviewWindow, window_id: Option<u32>) -> Option<(i32, i32)> {
         if let Ok(pos) = window.cursor_position() {
             return Some((pos.x as i32, pos.y as i32));
         }
@@ -611,7 +1547,8 @@ impl WindowController {
             if let Some(p) = Self::get_cursor_xdotool() {
                 return Some(p);
             }
-            if let Some(p) = Self::get_cursor_x11() {
+            let window_root = window_id.and_then(focus_manager::window_root);
+            if let Some(p) = Self::get_cursor_x11(window_root) {
                 return Some(p);
             }
         }
@@ -642,13 +1579,87 @@ impl WindowController {
         x.zip(y)
     }
 
-    fn get_cursor_x11() -> Option<(i32, i32)> {
+    /// Pointer position on the screen it is on. With several X screens the
+    /// coordinates only mean something on the window's own screen, so
+    /// `None` is returned when the pointer is on another root.
+    fn get_cursor_x11(window_root: Option<u32>) -> Option<(i32, i32)> {
         use x11rb::connection::Connection;
         use x11rb::protocol::xproto::ConnectionExt;
-        let (conn, n) = x11rb::connect(None).ok()?;
-        let root = conn.setup().roots.get(n)?.root;
-        let r = conn.query_pointer(root).ok()?.reply().ok()?;
-        Some((r.root_x as i32, r.root_y as i32))
+        let (conn, _) = x11rb::connect(None).ok()?;
+        for screen in &conn.setup().roots {
+            let Some(r) = conn
+                .query_pointer(screen.root)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+            else {
+                continue;
+            };
+            if !r.same_screen {
+                continue;
+            }
+            if window_root.is_some_and(|root| root != screen.root) {
+                debug!("pointer is on another X screen; not following it");
+                return None;
+            }
+            return Some((r.root_x as i32, r.root_y as i32));
+        }
+        None
+    }
+}
+
+// --- Monitoring Controller (Incognito Pause) ---
+
+/// The tray "Pause history" checkbox, kept in managed state so pause changes
+/// made through commands are reflected in the tray as well.
+struct PauseMenuItem(CheckMenuItem<tauri::Wry>);
+
+struct MonitoringController;
+
+impl MonitoringController {
+    /// Pauses monitoring, optionally scheduling an automatic resume.
+    pub fn pause(app: &AppHandle, minutes: Option<u64>) {
+        let generation = Self::set_paused(app, true);
+
+        let Some(minutes) = minutes.filter(|m| *m > 0) else {
+            return;
+        };
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_secs(minutes.saturating_mul(60)));
+            let Some(state) = app.try_state::<AppState>() else {
+                return;
+            };
+            // Only resume if nobody paused/resumed since this timer started.
+            if state.pause_generation.load(Ordering::SeqCst) == generation {
+                Self::set_paused(&app, false);
+            }
+        });
+    }
+
+    /// Applies the pause state, syncs the tray checkbox and notifies windows.
+    /// Returns the new pause generation.
+    pub fn set_paused(app: &AppHandle, paused: bool) -> u64 {
+        let state = app.state::<AppState>();
+        state.is_monitoring_paused.store(paused, Ordering::SeqCst);
+        let generation = state.pause_generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if let Some(item) = app.try_state::<PauseMenuItem>() {
+            let _ = item.0.set_checked(paused);
+        }
+        log::info!(
+            "clipboard monitoring {}",
+            if paused { "paused" } else { "resumed" }
+        );
+        let _ = app.emit("monitoring-state-changed", paused);
+        generation
+    }
+
+    pub fn toggle(app: &AppHandle) {
+        let paused = app
+            .state::<AppState>()
+            .is_monitoring_paused
+            .load(Ordering::SeqCst);
+        Self::set_paused(app, !paused);
     }
 }
 
@@ -668,9 +1679,7 @@ impl SettingsController {
             }
             None => {
                 // Fallback: recreate the window if it was somehow destroyed
-                eprintln!(
-                    "[SettingsController] Settings window missing, recreating as fallback..."
-                );
+                log::warn!("Settings window missing, recreating as fallback...");
 
                 match WebviewWindowBuilder::new(
                     app,
@@ -690,9 +1699,9 @@ impl SettingsController {
                 .build()
                 {
                     Ok(_) => {
-                        println!("[SettingsController] Settings window recreated successfully")
+                        log::info!("Settings window recreated successfully")
                     }
-                    Err(e) => eprintln!("[SettingsController] Failed to recreate window: {}", e),
+                    Err(e) => log::warn!("Failed to recreate window: {}", e),
                 }
             }
         }
@@ -704,43 +1713,140 @@ impl SettingsController {
 fn handle_window_moved_for_wayland(
     window: &WebviewWindow,
     state: &State<AppState>,
-    _pos: &PhysicalPosition<i32>,
+    pos: &PhysicalPosition<i32>,
 ) {
     if !is_wayland() || !window.is_visible().unwrap_or(false) {
         return;
     }
 
-    let _monitor_name = window
-        .current_monitor()
-        .ok()
-        .flatten()
-        .and_then(|m| m.name().map(|n| n.to_string()));
+    let Ok(size) = window.inner_size() else {
+        return;
+    };
+    let monitor = window.current_monitor().ok().flatten();
 
-    let _config = state.config_manager.lock();
+    let mut config = state.config_manager.lock();
     // UPDATE MEMORY ONLY (No Disk I/O here)
-    // config.update_state(monitor_name, pos.x, pos.y);
+    config.update_state(monitor.as_ref(), *pos, size);
+}
+
+/// Records a resize for the window's current monitor, like
+/// `handle_window_moved_for_wayland`.
+fn handle_window_resized_for_wayland(
+    window: &WebviewWindow,
+    state: &State<AppState>,
+    size: &PhysicalSize<u32>,
+) {
+    if !is_wayland() || !window.is_visible().unwrap_or(false) {
+        return;
+    }
+
+    let Ok(pos) = window.outer_position() else {
+        return;
+    };
+    let monitor = window.current_monitor().ok().flatten();
+
+    let mut config = state.config_manager.lock();
+    config.update_state(monitor.as_ref(), pos, *size);
 }
 
 // --- Background Listeners ---
 
-fn start_clipboard_watcher(app: AppHandle, clipboard_manager: Arc<Mutex<ClipboardManager>>) {
+/// True when the current clipboard content comes from a password manager and
+/// must not be recorded: either the owner set a no-history hint target, or
+/// (X11) the focused window at copy time is an excluded app. The content
+/// itself stays on the system clipboard untouched.
+fn is_password_manager_copy<F: FnMut() -> Option<Vec<String>>>(
+    clipboard_manager: &Mutex<ClipboardManager>,
+    no_history: &mut NoHistoryHint<F>,
+) -> bool {
+    if no_history.is_set() {
+        return true;
+    }
+    if !is_x11() {
+        return false;
+    }
+    focus_manager::focused_window_class()
+        .is_some_and(|class| clipboard_manager.lock().is_excluded_app(&class))
+}
+
+/// The window a copy came from, for "paste back to source" (X11 only).
+fn copy_source_window() -> Option<focus_manager::SourceWindow> {
+    if !is_x11() {
+        return None;
+    }
+    focus_manager::focused_client_window()
+}
+
+/// Tells the UI once when clipboard reads start failing, and again when they
+/// recover.
+fn emit_health_change(app: &AppHandle, change: Option<HealthChange>) {
+    match change {
+        Some(HealthChange::Failing(message)) => {
+            warn!("watcher: {}", message);
+            let _ = app.emit("clipboard-error", message);
+        }
+        Some(HealthChange::Recovered) => {
+            debug!("watcher: clipboard reads recovered");
+            let _ = app.emit("clipboard-recovered", ());
+        }
+        None => {}
+    }
+}
+
+fn start_clipboard_watcher(
+    app: AppHandle,
+    clipboard_manager: Arc<Mutex<ClipboardManager>>,
+    is_monitoring_paused: Arc<AtomicBool>,
+) {
     std::thread::spawn(move || {
         let mut last_text_hash: Option<u64> = None;
         let mut last_image_hash: Option<u64> = None;
-        let mut cleanup_counter = 0;
+        let mut last_sensitive_check = Instant::now();
+        let mut last_cleanup = Instant::now();
+        let mut was_paused = false;
 
         use penguinclip_lib::clipboard_manager::{
-            calculate_hash, read_system_html, read_system_image, read_system_text,
+            calculate_hash, read_system_html, read_system_image, read_system_primary_text,
+            read_system_targets, read_system_text,
         };
 
+        // The watcher's own handle, so reads never wait on the manager lock
+        let mut backend = ArboardBackend;
+
+        // Selection events on X11/Wayland (reads only after a change), polling
+        // otherwise.
+        // The setting is read once; changing it takes effect after a restart.
+        let force_polling = UserSettingsManager::new().load().force_clipboard_polling;
+        let mut changes = ChangeSource::start(force_polling);
+        let mut image_throttle = ImageReadThrottle::default();
+        let mut primary_settle = SelectionSettle::default();
+
         loop {
-            std::thread::sleep(Duration::from_millis(500));
-            cleanup_counter += 1;
+            let read_plan = changes.wait();
+            clipboard_watch::note_wakeup();
+
+            // Back off while text reads keep failing (e.g. after
+            // suspend/resume) instead of hammering a broken connection every
+            // tick. Image reads back off on their own, below.
+            let backoff = clipboard_watch::failure_backoff(ReadKind::Text);
+            if !backoff.is_zero() {
+                std::thread::sleep(backoff);
+            }
+
+            // Sensitive items use a much shorter TTL, so check them every ~5
+            // seconds instead of with the regular cleanup below.
+            if last_sensitive_check.elapsed() >= Duration::from_secs(5) {
+                last_sensitive_check = Instant::now();
+                if clipboard_manager.lock().expire_sensitive_items() {
+                    debug!("watcher: sensitive item expiry triggered history sync");
+                    let _ = app.emit("history-cleared", ());
+                }
+            }
 
-            // Background cleanup every ~30 seconds (60 * 500ms). Hold the lock
-            // only for the cleanup mutation itself.
-            if cleanup_counter >= 60 {
-                cleanup_counter = 0;
+            // Background cleanup every ~30 seconds. Hold the lock only for the
+            // cleanup mutation itself.
+            if last_cleanup.elapsed() >= Duration::from_secs(30) {
+                last_cleanup = Instant::now();
                 let interval_in_minutes = UserSettingsManager::new()
                     .load()
                     .auto_delete_interval_in_minutes();
@@ -756,44 +1862,180 @@ fn start_clipboard_watcher(app: AppHandle, clipboard_manager: Arc<Mutex<Clipboar
                 }
             }
 
-            // Privacy pause: keep cleanup running (above) but record nothing.
-            if RECORDING_PAUSED.load(Ordering::Relaxed) {
+            // Incognito pause: keep cleanup running (above) but don't read or
+            // record anything.
+            if is_monitoring_paused.load(Ordering::Relaxed) {
+                was_paused = true;
+                continue;
+            }
+
+            // First tick after resuming: whatever was copied during the pause
+            // is still on the clipboard. Adopt it as "already seen" so it is
+            // not recorded the moment monitoring resumes.
+            if was_paused {
+                was_paused = false;
+                last_text_hash = read_system_text(&mut backend)
+                    .ok()
+                    .flatten()
+                    .map(|t| calculate_hash(&t));
+                last_image_hash = read_system_image(&mut backend)
+                    .ok()
+                    .flatten()
+                    .map(|(_, h)| h);
+                if let Ok(Some(selected)) = read_system_primary_text(&mut backend) {
+                    primary_settle.mark_seen(calculate_hash(&selected));
+                }
                 continue;
             }
 
+            // PRIMARY selection (opt-in, X11). There are no change events for
+            // it here, so it is read on every wakeup and recorded only once
+            // the selection has settled.
+            if clipboard_watch::primary_tracking_enabled() {
+                if let Some(selected) = read_system_primary_text(&mut backend)
+                    .ok()
+                    .flatten()
+                    .filter(|t| !t.trim().is_empty())
+                {
+                    let hash = calculate_hash(&selected);
+                    if Some(hash) != last_text_hash
+                        && primary_settle.observe(hash, Instant::now())
+                        // PRIMARY owners don't set the hint; only the focused
+                        // app is checked.
+                        && !is_password_manager_copy(
+                            &clipboard_manager,
+                            &mut NoHistoryHint::new(Some(false), || None),
+                        )
+                    {
+                        let added = clipboard_manager.lock().add_primary_text(selected);
+                        if let Some(item) = added {
+                            let _ = app.emit("clipboard-changed", &item);
+                        }
+                    }
+                }
+            }
+
+            let Some(read_plan) = read_plan else {
+                continue;
+            };
+            // Queried lazily, so at most once per clipboard change.
+            let mut no_history = NoHistoryHint::new(read_plan.no_history, read_system_targets);
+
             // Read the OS clipboard WITHOUT holding the manager lock, so paste
             // and UI commands are never blocked behind a slow clipboard read.
             // Text
-            if let Ok(text) = read_system_text() {
-                if !text.is_empty() {
-                    let text_hash = calculate_hash(&text);
-
-                    if Some(text_hash) != last_text_hash {
-                        last_text_hash = Some(text_hash);
-                        last_image_hash = None;
+            let text = if read_plan.text {
+                clipboard_watch::note_text_read();
+                match read_system_text(&mut backend) {
+                    // No text on the clipboard is not a failure.
+                    Ok(text) => {
+                        let change = clipboard_watch::record_read_success(ReadKind::Text);
+                        emit_health_change(&app, change);
+                        text.filter(|t| !t.is_empty())
+                    }
+                    Err(e) => {
+                        debug!("watcher: text read failed: {}", e);
+                        let change = clipboard_watch::record_read_failure(ReadKind::Text, &e);
+                        emit_health_change(&app, change);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let text_hash = text.as_ref().map(calculate_hash);
+
+            if let (Some(text), Some(text_hash)) = (text, text_hash) {
+                if Some(text_hash) != last_text_hash {
+                    last_text_hash = Some(text_hash);
+                    // Copied text is usually still selected; don't record it
+                    // a second time from PRIMARY.
+                    primary_settle.mark_seen(text_hash);
+
+                    if is_password_manager_copy(&clipboard_manager, &mut no_history) {
+                        debug!("watcher: skipping copy from a password manager");
+                        continue;
+                    }
 
-                        // Fetch HTML (still lock-free) for rich-text support.
-                        let html = read_system_html();
+                    // Fetch HTML (still lock-free) for rich-text support.
+                    let html = read_system_html(&mut backend);
+                    let source = copy_source_window();
+
+                    // Upgrades a copied image-file reference into a real
+                    // image entry; otherwise stores the text.
+                    let (added, oversized) = {
+                        let mut manager = clipboard_manager.lock();
+                        let added = manager.add_clipboard_text(text, html, source);
+                        (added, manager.take_oversized_skip())
+                    };
+                    if let Some(item) = added {
+                        let _ = app.emit("clipboard-changed", &item);
+                    } else if let Some(skip) = oversized {
+                        let _ = app.emit("item-skipped-too-large", &skip);
+                    }
+                }
+            }
 
-                        // Upgrades a copied image-file reference into a real
-                        // image entry; otherwise stores the text.
-                        let added = clipboard_manager.lock().add_clipboard_text(text, html);
-                        if let Some(item) = added {
-                            let _ = app.emit("clipboard-changed", &item);
+            // Image. Full image reads are expensive (transfer + decode), so
+            // when polling they only happen right after the text side changed
+            // or on a slow backstop tick. The last image hash is kept across
+            // text changes and only forgotten once the clipboard is seen
+            // without an image, so a re-copied image is still recorded. After
+            // failed image reads, they wait out their own backoff.
+            let read_image = read_plan.image
+                && clipboard_watch::read_due(ReadKind::Image)
+                && (changes.is_event_driven() || image_throttle.should_read(text_hash));
+            let image = if read_image {
+                clipboard_watch::note_image_read();
+                let image = match read_system_image(&mut backend) {
+                    Ok(image) => {
+                        let change = clipboard_watch::record_read_success(ReadKind::Image);
+                        emit_health_change(&app, change);
+                        if image.is_none() {
+                            last_image_hash = None;
                         }
+                        image
                     }
+                    // A failed read says nothing about what is on the
+                    // clipboard, so the last image hash is kept.
+                    Err(e) => {
+                        debug!("watcher: image read failed: {}", e);
+                        let change = clipboard_watch::record_read_failure(ReadKind::Image, &e);
+                        emit_health_change(&app, change);
+                        None
+                    }
+                };
+                image
+            } else {
+                if read_plan.image {
+                    clipboard_watch::note_image_read_skipped();
+                } else {
+                    // The new selection offers no image at all.
+                    last_image_hash = None;
                 }
-            }
+                None
+            };
 
-            // Image
-            if let Ok(Some((image_data, hash))) = read_system_image() {
+            if let Some((image_data, hash)) = image {
                 if Some(hash) != last_image_hash {
                     last_image_hash = Some(hash);
                     last_text_hash = None;
 
-                    let added = clipboard_manager.lock().add_image(image_data, hash);
+                    if is_password_manager_copy(&clipboard_manager, &mut no_history) {
+                        debug!("watcher: skipping image from a password manager");
+                        continue;
+                    }
+
+                    let source = copy_source_window();
+                    let (added, oversized) = {
+                        let mut manager = clipboard_manager.lock();
+                        let added = manager.add_image_from(image_data, hash, source);
+                        (added, manager.take_oversized_skip())
+                    };
                     if let Some(item) = added {
                         let _ = app.emit("clipboard-changed", &item);
+                    } else if let Some(skip) = oversized {
+                        let _ = app.emit("item-skipped-too-large", &skip);
                     }
                 }
             }
@@ -827,16 +2069,37 @@ fn main() {
         println!("        --background Start minimized to system tray (for autostart)");
         println!("        --settings   Open settings window on startup");
         println!("        --emoji      Open with emoji picker tab selected");
+        println!("        --paste-index N");
+        println!(
+            "                     Paste history item N (1 = newest), starting in the tray if needed"
+        );
         println!();
         println!("SHORTCUTS:");
         println!("    Super+V          Open clipboard history");
         println!("    Super+.          Open emoji picker");
+        println!("    Super+Alt+1..9   Paste history item 1..9");
         println!("    Ctrl+Alt+V       Alternative shortcut");
         return;
     }
 
-    // Check if --background flag is present (start minimized to tray)
-    let start_in_background = args.iter().any(|arg| arg == "--background");
+    // --paste-index on first startup pastes once setup is done; a bad index
+    // is refused here rather than starting an instance that does nothing
+    let paste_index_on_start = if args.iter().any(|arg| arg == "--paste-index") {
+        match paste_index_arg(&args) {
+            Some(index) => Some(index),
+            None => {
+                eprintln!("--paste-index needs a number, e.g. --paste-index 1");
+                std::process::exit(2);
+            }
+        }
+    } else {
+        None
+    };
+
+    // Check if --background flag is present (start minimized to tray). A
+    // quick paste also starts there, so focus stays on the target window.
+    let start_in_background =
+        args.iter().any(|arg| arg == "--background") || paste_index_on_start.is_some();
     if start_in_background {
         println!("[Startup] Starting in background mode (system tray only)");
         STARTED_IN_BACKGROUND.store(true, Ordering::SeqCst);
@@ -853,6 +2116,7 @@ fn main() {
     let open_emoji_on_start_clone = open_emoji_on_start;
 
     let is_mouse_inside = Arc::new(AtomicBool::new(false));
+    let monitoring_paused = Arc::new(AtomicBool::new(false));
     let base_dir = dirs::data_local_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join("penguinclip");
@@ -878,18 +2142,39 @@ fn main() {
     let history_path = base_dir.join("history.json");
 
     // Load user settings to get max_history_size
-    let user_settings = UserSettingsManager::new().load();
+    let (user_settings, settings_issue) = UserSettingsManager::new().load_reporting();
     let clipboard_manager = Arc::new(Mutex::new(ClipboardManager::new(
         history_path,
         user_settings.max_history_size,
     )));
-    clipboard_manager
-        .lock()
-        .set_excluded_patterns(&user_settings.excluded_patterns);
+    {
+        let mut manager = clipboard_manager.lock();
+        manager.set_excluded_patterns(&user_settings.excluded_patterns);
+        manager.set_excluded_apps(&user_settings.excluded_apps);
+        manager.set_sensitive_expiry(user_settings.sensitive_ttl());
+        manager.set_pin_options(user_settings.pinned_on_top, user_settings.pin_limit());
+        manager.set_smart_actions(user_settings.enable_smart_actions);
+        clipboard_watch::set_primary_tracking(user_settings.track_primary_selection);
+        input_simulator::set_paste_config(user_settings.paste_config().unwrap_or_default());
+        manager.set_item_size_limits(
+            user_settings.max_text_item_bytes,
+            user_settings.max_image_item_bytes,
+            user_settings.truncate_oversized_text(),
+        );
+    }
 
     let emoji_manager = Arc::new(Mutex::new(EmojiManager::new(base_dir.clone())));
+    emoji_manager
+        .lock()
+        .set_decay_half_life_days(user_settings.emoji_decay_half_life_days);
+    let gif_history = Arc::new(Mutex::new(GifHistoryManager::new(base_dir.clone())));
 
-    let config_manager = Arc::new(Mutex::new(ConfigManager::new(base_dir)));
+    let mut config_manager = ConfigManager::new(base_dir);
+    let settings_load_issues: Vec<LoadIssue> = settings_issue
+        .into_iter()
+        .chain(config_manager.take_load_issue())
+        .collect();
+    let config_manager = Arc::new(Mutex::new(config_manager));
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -900,33 +2185,53 @@ fn main() {
         .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
             // Check if --settings flag is present
             if argv.iter().any(|arg| arg == "--settings") {
-                println!(
-                    "[SingleInstance] Secondary instance with --settings flag, opening settings..."
-                );
+                log::info!("Secondary instance with --settings flag, opening settings...");
                 SettingsController::show(app);
+            } else if argv.iter().any(|arg| arg == "--paste-index") {
+                match paste_index_arg(&argv) {
+                    Some(index) => {
+                        log::info!(
+                            "Secondary instance with --paste-index {}, pasting...",
+                            index
+                        );
+                        let app = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = paste_by_index(&app, index).await {
+                                warn!("quick paste of item {} failed: {}", index, e);
+                            }
+                        });
+                    }
+                    None => warn!("--paste-index needs a number, e.g. --paste-index 1"),
+                }
             } else if argv.iter().any(|arg| arg == "--emoji") {
-                println!(
-                    "[SingleInstance] Secondary instance with --emoji flag, opening emoji picker..."
-                );
+                log::info!("Secondary instance with --emoji flag, opening emoji picker...");
                 WindowController::toggle_with_tab(app, Some("emoji"));
             } else {
-                println!("[SingleInstance] Secondary instance detected, toggling window...");
+                log::info!("Secondary instance detected, toggling window...");
                 WindowController::toggle(app);
             }
         }))
         .manage(AppState {
             clipboard_manager: clipboard_manager.clone(),
             emoji_manager: emoji_manager.clone(),
+            gif_history,
             config_manager: config_manager.clone(),
             is_mouse_inside: is_mouse_inside.clone(),
+            is_monitoring_paused: monitoring_paused.clone(),
+            pause_generation: Arc::new(AtomicU64::new(0)),
+            paste_queue: Arc::new(Mutex::new(VecDeque::new())),
             data_dir,
+            main_window_xid: AtomicU32::new(0),
+            window_close_policy: Mutex::new(user_settings.window_close_policy()),
+            window_pinned: AtomicBool::new(false),
+            settings_load_issues: settings_load_issues.clone(),
+            pending_import: Mutex::new(None),
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::Destroyed = event {
-                if window.label() == "setup"
-                    && penguinclip_lib::permission_checker::is_first_run()
+                if window.label() == "setup" && penguinclip_lib::permission_checker::is_first_run()
                 {
-                    println!("[Setup] Setup window closed without completion. Exiting app.");
+                    log::info!("Setup window closed without completion. Exiting app.");
                     window.app_handle().exit(0);
                 }
             }
@@ -934,39 +2239,60 @@ fn main() {
         .setup(move |app| {
             let app_handle = app.handle().clone();
 
+            for issue in &settings_load_issues {
+                let _ = app.emit(issue.event(), issue.message());
+            }
+
             // FIRST THING: If started in background mode, immediately hide the main window
             // This runs before anything else to prevent the window from appearing
             if start_in_background_clone {
                 if let Some(main_window) = app.get_webview_window("main") {
                     let _ = main_window.hide();
-                    println!("[Setup] Immediately hiding main window for background mode");
+                    log::info!("Immediately hiding main window for background mode");
                 }
             }
 
             // Auto-migrate old autostart entries to use the wrapper script
             // This fixes existing installations where autostart points to the binary directly
             match autostart_manager::autostart_migrate() {
-                Ok(true) => println!("[Setup] Migrated autostart entry to use wrapper script"),
+                Ok(true) => log::info!("Migrated autostart entry to use wrapper script"),
                 Ok(false) => {} // No migration needed
-                Err(e) => eprintln!("[Setup] Failed to migrate autostart: {}", e),
+                Err(e) => log::warn!("Failed to migrate autostart: {}", e),
             }
 
             let show = MenuItem::with_id(app, "show", "Show Clipboard", true, None::<&str>)?;
             let pause = CheckMenuItem::with_id(
                 app,
                 "pause",
-                "Pause recording",
+                "Pause history",
+                true,
+                monitoring_paused.load(Ordering::SeqCst),
+                None::<&str>,
+            )?;
+            let clear_images =
+                MenuItem::with_id(app, "clear_images", "Clear images", true, None::<&str>)?;
+            let release_keys = MenuItem::with_id(
+                app,
+                "release_keys",
+                "Release stuck keys",
                 true,
-                RECORDING_PAUSED.load(Ordering::SeqCst),
                 None::<&str>,
             )?;
             let settings = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show, &pause, &settings, &quit])?;
-            // Cloned so the menu-event handler can reflect the new checked state.
-            let pause_item = pause.clone();
-
-
+            let menu = Menu::with_items(
+                app,
+                &[
+                    &show,
+                    &pause,
+                    &clear_images,
+                    &release_keys,
+                    &settings,
+                    &quit,
+                ],
+            )?;
+            // Managed so MonitoringController can keep the checkmark in sync.
+            app.manage(PauseMenuItem(pause.clone()));
 
             // Get temp directory for tray icon (avoids permission issues with XDG_RUNTIME_DIR)
             let temp_dir = std::env::temp_dir().join("penguinclip");
@@ -976,8 +2302,8 @@ fn main() {
             let settings_manager = UserSettingsManager::new();
             let settings = settings_manager.load();
 
-            // Initialize atomic flag for the listener loop
-            theme_manager::update_dynamic_tray_flag(settings.enable_dynamic_tray_icon);
+            // Initialize atomic flags for the listener loop
+            theme_manager::update_tray_flags(&settings);
 
             let (icon, use_template_icon) = theme_manager::initial_tray_icon(&settings);
 
@@ -991,15 +2317,17 @@ fn main() {
                     "quit" => app.exit(0),
                     "show" => WindowController::toggle(app),
                     "settings" => SettingsController::show(app),
-                    "pause" => {
-                        let paused = !RECORDING_PAUSED.load(Ordering::SeqCst);
-                        RECORDING_PAUSED.store(paused, Ordering::SeqCst);
-                        let _ = pause_item.set_checked(paused);
-                        log::info!(
-                            "clipboard recording {}",
-                            if paused { "paused" } else { "resumed" }
-                        );
-                        let _ = app.emit("recording-paused-changed", paused);
+                    "pause" => MonitoringController::toggle(app),
+                    "release_keys" => {
+                        if let Err(e) = input_simulator::release_all_modifiers() {
+                            warn!("tray release keys failed: {}", e);
+                        }
+                    }
+                    "clear_images" => {
+                        let state = app.state::<AppState>();
+                        if let Err(e) = clear_type_and_sync(app, &state, "image") {
+                            warn!("tray clear images failed: {}", e);
+                        }
                     }
                     _ => {}
                 })
@@ -1016,22 +2344,25 @@ fn main() {
 
             // Update icon asynchronously if dynamic is enabled (to fix the initial default icon)
             if settings.enable_dynamic_tray_icon {
-                 let app_handle_bg = app.handle().clone();
-                 let settings_bg = settings.clone();
-                 tauri::async_runtime::spawn(async move {
+                let app_handle_bg = app.handle().clone();
+                let settings_bg = settings.clone();
+                tauri::async_runtime::spawn(async move {
                     theme_manager::refresh_tray_icon(&app_handle_bg, &settings_bg).await;
-                 });
+                });
             }
 
             // Verify that settings window was created from config
             if app.get_webview_window("settings").is_none() {
-                eprintln!("[Setup] FATAL: Settings window missing from config");
+                log::error!("Settings window missing from config");
             } else {
-                println!("[Setup] Settings window created successfully from config");
+                log::info!("Settings window created successfully from config");
             }
 
             // Window Event Handlers (Focus & Move)
             let main_window = app.get_webview_window("main").unwrap();
+            if let Some(xid) = WindowController::main_window_xid(&main_window, &app_handle) {
+                debug!("main window X11 id: {:#x}", xid);
+            }
             let w_clone = main_window.clone();
             let app_handle_for_event = app_handle.clone();
 
@@ -1046,7 +2377,7 @@ fn main() {
                     // If started in background and initial show hasn't been allowed yet,
                     // immediately hide the window
                     if started_in_background && !initial_show_allowed {
-                        println!("[WindowController] Background mode: intercepted focus, hiding window");
+                        log::info!("Background mode: intercepted focus, hiding window");
                         let _ = w_clone.hide();
                     }
                 }
@@ -1055,6 +2386,12 @@ fn main() {
                     if state.is_mouse_inside.load(Ordering::Relaxed) {
                         return;
                     }
+                    if !WindowController::may_auto_hide(
+                        &app_handle_for_event,
+                        WindowClosePolicy::hides_on_focus_loss,
+                    ) {
+                        return;
+                    }
 
                     // Don't hide if settings window is visible (for live preview)
                     if let Some(settings_window) =
@@ -1065,40 +2402,86 @@ fn main() {
                         }
                     }
 
-                    if is_wayland() {
-                        state.config_manager.lock().sync_to_disk();
-                    }
-
-                    let _ = w_clone.hide();
+                    WindowController::hide(&app_handle_for_event);
                 }
 
                 WindowEvent::Moved(pos) => {
                     let state = w_clone.state::<AppState>();
                     handle_window_moved_for_wayland(&w_clone, &state, pos);
                 }
+                WindowEvent::Resized(size) => {
+                    let state = w_clone.state::<AppState>();
+                    handle_window_resized_for_wayland(&w_clone, &state, size);
+                }
                 _ => {}
             });
 
-            start_clipboard_watcher(app_handle.clone(), clipboard_manager.clone());
+            start_clipboard_watcher(
+                app_handle.clone(),
+                clipboard_manager.clone(),
+                monitoring_paused.clone(),
+            );
 
             // Start theme change listener (D-Bus event-based, more efficient than polling)
             {
                 let app_handle_for_theme = app_handle.clone();
                 tauri::async_runtime::spawn(async move {
-                    if let Err(e) =
-                        theme_manager::start_theme_listener(app_handle_for_theme).await
+                    if let Err(e) = theme_manager::start_theme_listener(app_handle_for_theme).await
                     {
-                        eprintln!("[ThemeManager] Failed to start theme listener: {}", e);
+                        log::warn!("Failed to start theme listener: {}", e);
                     }
                 });
             }
 
-            // Register global shortcut (Super+V) with the desktop environment
-            // This runs in a background thread to avoid blocking startup
-            std::thread::spawn(|| {
-                // Give the desktop environment a moment to settle
-                std::thread::sleep(std::time::Duration::from_secs(2));
-                penguinclip_lib::linux_shortcut_manager::register_global_shortcut();
+            // Register the global shortcuts (Super+V by default). Sandboxed and Wayland
+            // sessions use the GlobalShortcuts portal when it exists; otherwise (or if
+            // binding through it fails) they go into the desktop environment's settings
+            let shortcut_bindings = settings.shortcut_bindings().unwrap_or_default();
+            let app_handle_for_shortcuts = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if global_shortcuts_portal::is_preferred()
+                    && global_shortcuts_portal::is_available().await
+                {
+                    let app = app_handle_for_shortcuts.clone();
+                    let result = global_shortcuts_portal::run(&shortcut_bindings, move |id| {
+                        match global_shortcuts_portal::action(id) {
+                            Some(PortalAction::ToggleHistory) => WindowController::toggle(&app),
+                            Some(PortalAction::ToggleEmoji) => {
+                                WindowController::toggle_with_tab(&app, Some("emoji"))
+                            }
+                            Some(PortalAction::PasteIndex(index)) => {
+                                let app = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    if let Err(e) = paste_by_index(&app, index).await {
+                                        warn!("quick paste of item {} failed: {}", index, e);
+                                    }
+                                });
+                            }
+                            None => debug!("unknown portal shortcut {}", id),
+                        }
+                    })
+                    .await;
+                    match result {
+                        Ok(()) => return,
+                        Err(e) => warn!("GlobalShortcuts portal unusable, falling back: {}", e),
+                    }
+                }
+
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    // Give the desktop environment a moment to settle
+                    std::thread::sleep(std::time::Duration::from_secs(2));
+                    // A portal session may have been rebound to newer settings
+                    // before it failed
+                    let shortcut_bindings = UserSettingsManager::new()
+                        .load()
+                        .shortcut_bindings()
+                        .unwrap_or(shortcut_bindings);
+                    let _ = penguinclip_lib::linux_shortcut_manager::register_global_shortcut(
+                        &shortcut_bindings,
+                        None,
+                    );
+                })
+                .await;
             });
 
             // If --settings flag was passed on first startup, open the settings window
@@ -1106,6 +2489,17 @@ fn main() {
                 SettingsController::show(&app_handle);
             }
 
+            // If --paste-index was passed on first startup, paste now that
+            // history is loaded
+            if let Some(index) = paste_index_on_start {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = paste_by_index(&app_handle, index).await {
+                        warn!("quick paste of item {} failed: {}", index, e);
+                    }
+                });
+            }
+
             // If --emoji flag was passed on first startup, emit switch-tab event
             // This needs a small delay to ensure the frontend is ready
             if open_emoji_on_start_clone {
@@ -1139,14 +2533,17 @@ fn main() {
                             // Use unwrap_or(false) to safely handle cases where window was destroyed
                             match window_clone.is_visible() {
                                 Ok(true) => {
-                                    println!("[Startup] Background enforcer #{}: window was visible, hiding again", i + 1);
+                                    log::info!(
+                                        "Background enforcer #{}: window was visible, hiding again",
+                                        i + 1
+                                    );
                                     let _ = window_clone.hide();
                                 }
                                 Ok(false) => {} // Window exists but is hidden, nothing to do
                                 Err(_) => break, // Window was destroyed, stop the enforcer
                             }
                         }
-                        println!("[Startup] Background enforcer finished");
+                        log::info!("Background enforcer finished");
                     });
                 }
             }
@@ -1155,51 +2552,192 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             get_history,
+            get_history_page,
+            get_history_meta,
+            get_item_content,
             clear_history,
             get_history_load_status,
+            get_settings_load_status,
+            get_history_lock_state,
+            set_history_passphrase,
+            unlock_history,
+            lock_history,
             get_diagnostics_report,
             export_diagnostics,
+            generate_diagnostic_report,
             get_recent_logs,
             clear_logs,
             set_logging_enabled,
             is_logging_enabled,
             get_app_version,
             check_for_updates,
+            export_history,
+            import_history,
+            save_item_to_file,
+            get_history_stats,
+            generate_qr,
+            decode_qr,
             delete_item,
+            delete_items,
+            clear_unpinned_older_than,
+            clear_by_type,
             toggle_pin,
             toggle_favorite,
+            paste_to_primary,
+            search_history,
+            get_watcher_stats,
+            get_watcher_health,
+            open_url,
+            get_items_by_tag,
+            add_tag,
+            remove_tag,
             paste_item,
+            paste_item_to_source,
+            hide_window,
+            set_window_pinned,
+            paste_item_and_delete,
+            type_item,
+            input_simulator::test_paste_rule,
+            diagnose_paste,
+            input_simulator::release_all_modifiers,
+            paste_item_by_index,
+            paste_item_transformed,
+            queue_items,
+            paste_next,
+            clear_queue,
+            get_queue,
             paste_text,
             get_recent_emojis,
-            is_recording_paused,
+            search_emojis,
+            get_emoji_categories,
+            toggle_favorite_emoji,
+            get_favorite_emojis,
+            get_recent_gifs,
+            record_gif_usage,
+            toggle_favorite_gif,
+            get_clipboard_workers,
+            gif_manager::check_gif_clipboard_tools,
+            get_default_skin_tone,
+            set_default_skin_tone,
+            get_top_used_emojis,
+            pause_monitoring,
+            resume_monitoring,
+            is_monitoring_paused,
             paste_gif_from_url,
             finish_paste,
             finish_setup,
             set_mouse_state,
             get_user_settings,
             set_user_settings,
+            export_settings,
+            preview_import,
+            import_settings,
+            add_custom_kaomoji,
+            remove_custom_kaomoji,
+            search_kaomojis,
             is_settings_window_visible,
             show_settings,
             copy_text_to_clipboard,
             get_system_theme,
             refresh_system_theme,
             is_theme_listener_active,
+            get_system_accent_color,
+            refresh_tray_icon,
             permission_checker::check_permissions,
+            permission_checker::check_wayland_support,
             permission_checker::fix_permissions_now,
+            permission_checker::fix_permissions_persistent,
             permission_checker::is_first_run,
+            permission_checker::is_shell_helper_available,
             permission_checker::mark_first_run_complete,
             permission_checker::reset_first_run,
             shortcut_setup::get_desktop_environment,
+            shortcut_setup::get_environment_info,
+            shortcut_setup::get_session_info,
             shortcut_setup::register_de_shortcut,
+            shortcut_setup::unregister_de_shortcut,
+            shortcut_setup::get_shortcut_status,
             shortcut_setup::check_shortcut_tools,
             shortcut_setup::detect_conflicts,
+            shortcut_setup::check_binding_conflicts,
             shortcut_setup::resolve_conflicts,
+            shortcut_setup::list_resolved_conflicts,
+            shortcut_setup::undo_conflict_resolution,
+            shortcut_setup::list_shortcut_backups,
+            shortcut_setup::restore_shortcut_backup,
             autostart_manager::autostart_enable,
             autostart_manager::autostart_disable,
             autostart_manager::autostart_is_enabled,
             autostart_manager::autostart_migrate,
             penguinclip_lib::rendering_env::get_rendering_environment,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                gif_manager::stop_clipboard_workers();
+                input_simulator::destroy_uinput_device();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::Path;
+
+    /// Names of the functions marked as commands in `source`, ignoring its
+    /// test module
+    fn declared_commands(source: &str) -> Vec<String> {
+        let source = source.split("#[cfg(test)]").next().unwrap_or_default();
+        source
+            .split("#[tauri::command")
+            .skip(1)
+            .filter_map(|rest| {
+                let signature = rest.split_once("fn ")?.1;
+                let name = signature
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .next()?;
+                Some(name.to_string())
+            })
+            .collect()
+    }
+
+    fn rust_sources(dir: &Path, sources: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                rust_sources(&path, sources);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                sources.push(fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+
+    /// A command missing from the handler list only fails at runtime, when
+    /// the frontend invokes it
+    #[test]
+    fn test_every_command_is_registered() {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let main = fs::read_to_string(src.join("main.rs")).unwrap();
+        let handler = main
+            .split("generate_handler![")
+            .nth(1)
+            .and_then(|rest| rest.split(']').next())
+            .expect("main.rs has no generate_handler! list");
+        let registered: HashSet<&str> = handler
+            .split(',')
+            .filter_map(|entry| entry.trim().rsplit("::").next())
+            .collect();
+
+        let mut sources = Vec::new();
+        rust_sources(&src, &mut sources);
+        let missing: Vec<String> = sources
+            .iter()
+            .flat_map(|source| declared_commands(source))
+            .filter(|name| !registered.contains(name.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "unregistered commands: {:?}", missing);
+    }
 }
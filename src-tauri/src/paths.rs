@@ -0,0 +1,54 @@
+//! XDG Base Directories
+//! Where the desktop's config files live. Built from the environment on
+//! every call, so nothing hard-codes `~/.config`.
+//! See: https://specifications.freedesktop.org/basedir-spec/latest/
+
+use std::env;
+use std::path::PathBuf;
+
+/// `$XDG_CONFIG_HOME`, or `~/.config` when it is unset or not an absolute
+/// path (the spec says relative ones are invalid). `None` without a home.
+pub fn xdg_config_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Some(dir),
+        _ => env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(|home| PathBuf::from(home).join(".config")),
+    }
+}
+
+/// Runs `f` with `XDG_CONFIG_HOME` set to `dir`. Tests changing the
+/// variable take turns, since the environment is shared by every thread.
+#[cfg(test)]
+pub(crate) fn with_config_home<T>(dir: &std::path::Path, f: impl FnOnce() -> T) -> T {
+    use std::sync::Mutex;
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = env::var_os("XDG_CONFIG_HOME");
+    env::set_var("XDG_CONFIG_HOME", dir);
+    let result = f();
+    match previous {
+        Some(previous) => env::set_var("XDG_CONFIG_HOME", previous),
+        None => env::remove_var("XDG_CONFIG_HOME"),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_xdg_config_dir() {
+        let dir = env::temp_dir().join("penguinclip_test_xdg_config");
+        assert_eq!(with_config_home(&dir, xdg_config_dir), Some(dir));
+
+        // A relative XDG_CONFIG_HOME is ignored
+        let fallback = with_config_home(Path::new("relative/config"), xdg_config_dir);
+        if let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) {
+            assert_eq!(fallback, Some(PathBuf::from(home).join(".config")));
+        }
+    }
+}
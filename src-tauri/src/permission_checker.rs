@@ -1,18 +1,111 @@
 //! Permission checker module for PenguinClip
-//! Handles uinput permission verification and fixing
+//! Handles uinput permission verification and fixing, and reports what
+//! works in a Wayland session before setup
 
+use crate::environment;
+use crate::gif_manager::{self, GifClipboardStatus};
+use crate::input_simulator::{self, PasteTool};
+use crate::kwin;
+use crate::session::{self, SessionType};
+use crate::shell_helper;
+use crate::wayland_source;
 use std::fs::OpenOptions;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// udev rule giving the `input` group access to /dev/uinput on every boot
+const UDEV_RULE_PATH: &str = "/etc/udev/rules.d/99-penguinclip-uinput.rules";
+const UDEV_RULE: &str =
+    r#"KERNEL=="uinput", GROUP="input", MODE="0660", OPTIONS+="static_node=uinput""#;
+
+/// Run as root by `fix_permissions_persistent`, with the rule, its path and
+/// the user as `$1`-`$3`. Prints `ok <step>` or `failed <step>` per step.
+const PERSISTENT_FIX_SCRIPT: &str = r#"
+if printf '%s\n' "$1" > "$2"; then echo "ok udev_rule"; else echo "failed udev_rule"; fi
+if usermod -aG input "$3"; then echo "ok input_group"; else echo "failed input_group"; fi
+if udevadm control --reload-rules && udevadm trigger; then echo "ok udev_reload"; else echo "failed udev_reload"; fi
+"#;
+
 #[derive(serde::Serialize, Clone)]
 pub struct PermissionStatus {
+    pub session_type: SessionType,
     pub uinput_accessible: bool,
     pub uinput_path: String,
     pub user_in_input_group: bool,
+    /// X11 ways to send the paste keystroke, tried before uinput
+    pub xdotool_available: bool,
+    pub xtest_available: bool,
+    /// Wayland alternatives to uinput for the paste keystroke
+    pub wtype_available: bool,
+    pub ydotool_available: bool,
+    /// Why ydotool can't be used, if it can't
+    pub ydotool_error: Option<String>,
+    pub ydotoold_running: bool,
+    /// `wl-copy`/`wl-paste`, used on Wayland for GIFs and clipboard types
+    pub wl_clipboard_available: bool,
+    /// Which clipboard backend GIF paste will use
+    pub gif_clipboard: GifClipboardStatus,
+    /// Our udev rule is installed, so access survives a reboot
+    pub udev_rule_installed: bool,
+    /// An ACL on /dev/uinput grants us access until the next reboot
+    pub acl_applied: bool,
+    /// We were added to the `input` group, but this session started before
+    pub relogin_required: bool,
+    /// Where we run: `host`, `flatpak`, or the container named by the
+    /// `container` variable
+    pub environment: String,
+    /// Some way of sending the paste keystroke works in this session
+    pub paste_expected_to_work: bool,
+    /// What to do about the missing piece, or which tool will paste
     pub suggestion: String,
 }
 
+impl PermissionStatus {
+    /// The paste strategies usable here, in the order they are tried
+    fn paste_tools(&self) -> Vec<&'static str> {
+        input_simulator::paste_tools(self.session_type == SessionType::X11)
+            .iter()
+            .filter(|tool| match tool {
+                PasteTool::Xdotool => self.xdotool_available,
+                PasteTool::XTest => self.xtest_available,
+                PasteTool::Wtype => self.wtype_available,
+                PasteTool::Ydotool => self.ydotool_available,
+                PasteTool::Uinput => self.uinput_accessible,
+            })
+            .map(|tool| tool.name())
+            .collect()
+    }
+
+    /// Fills in `paste_expected_to_work` and a suggestion naming what is
+    /// missing for this session
+    fn assess(&mut self) {
+        let tools = self.paste_tools();
+        self.paste_expected_to_work = !tools.is_empty();
+        self.suggestion = if let Some(tool) = tools.first() {
+            let mut suggestion = format!("Paste will work using {}.", tool);
+            if self.session_type == SessionType::Wayland
+                && !self.wl_clipboard_available
+                && !self.gif_clipboard.native_wayland_available
+            {
+                suggestion.push_str(" Install wl-clipboard so GIFs paste as images.");
+            }
+            suggestion
+        } else if self.environment != "host" {
+            format!("/dev/uinput can't be used inside the {} sandbox. Pasting needs the RemoteDesktop portal and shortcuts the GlobalShortcuts portal instead; the host's permissions don't need fixing.", self.environment)
+        } else if self.session_type == SessionType::X11 {
+            "Neither xdotool nor the XTest extension is available. Install xdotool (e.g., 'sudo apt install xdotool') or click 'Fix Now' to use /dev/uinput.".to_string()
+        } else if self.ydotoold_running {
+            "ydotoold is running but the ydotool client is missing. Install ydotool to paste through it.".to_string()
+        } else if self.relogin_required {
+            "You were added to the 'input' group. Log out and back in to finish.".to_string()
+        } else if self.user_in_input_group {
+            "You're in the 'input' group but /dev/uinput is not accessible. Try logging out and back in.".to_string()
+        } else {
+            "Nothing can send the paste keystroke. Install wtype (not on GNOME) or run ydotoold with ydotool, or click 'Fix Now' to use /dev/uinput.".to_string()
+        };
+    }
+}
+
 /// Get the config directory path following XDG spec
 fn get_config_dir() -> PathBuf {
     dirs::config_dir()
@@ -27,34 +120,66 @@ fn get_config_path() -> PathBuf {
     get_config_dir().join("setup.json")
 }
 
+/// True if /dev/uinput can be opened for writing
+pub fn uinput_accessible() -> bool {
+    OpenOptions::new().write(true).open("/dev/uinput").is_ok()
+}
+
 /// Verify if the user has access to /dev/uinput
 #[tauri::command]
 pub fn check_permissions() -> PermissionStatus {
     let uinput_path = "/dev/uinput";
 
-    // Try to open for writing
-    let uinput_accessible = OpenOptions::new().write(true).open(uinput_path).is_ok();
+    let uinput_accessible = uinput_accessible();
 
     // Check if user is in input group
     let user_in_input_group = Command::new("groups")
         .output()
         .map(|o| String::from_utf8_lossy(&o.stdout).contains("input"))
         .unwrap_or(false);
+    let username = whoami::username().unwrap_or_default();
+    // `id` reads the group database rather than this session's groups
+    let relogin_required = !user_in_input_group
+        && Command::new("id")
+            .args(["-nG", &username])
+            .output()
+            .map(|o| {
+                String::from_utf8_lossy(&o.stdout)
+                    .split_whitespace()
+                    .any(|group| group == "input")
+            })
+            .unwrap_or(false);
+    let acl_applied = Command::new("getfacl")
+        .args(["-p", uinput_path])
+        .output()
+        .map(|o| acl_grants_rw(&String::from_utf8_lossy(&o.stdout), &username))
+        .unwrap_or(false);
+    let udev_rule_installed = Path::new(UDEV_RULE_PATH).exists();
 
-    let suggestion = if uinput_accessible {
-        "Permissions OK! Paste simulation will work.".to_string()
-    } else if user_in_input_group {
-        "You're in the 'input' group but /dev/uinput is not accessible. Try logging out and back in.".to_string()
-    } else {
-        "Missing permissions. Click 'Fix Permissions' or run: sudo usermod -aG input $USER && logout".to_string()
-    };
+    let ydotool_error = input_simulator::ydotool_status().err();
 
-    PermissionStatus {
+    let mut status = PermissionStatus {
+        session_type: session::get_session_type(),
         uinput_accessible,
         uinput_path: uinput_path.to_string(),
         user_in_input_group,
-        suggestion,
-    }
+        xdotool_available: input_simulator::xdotool_available(),
+        xtest_available: input_simulator::xtest_available(),
+        wtype_available: input_simulator::wtype_available(),
+        ydotool_available: ydotool_error.is_none(),
+        ydotool_error,
+        ydotoold_running: input_simulator::ydotoold_running(),
+        wl_clipboard_available: input_simulator::wl_clipboard_available(),
+        gif_clipboard: gif_manager::check_gif_clipboard_tools(),
+        udev_rule_installed,
+        acl_applied,
+        relogin_required,
+        environment: environment::sandbox().unwrap_or_else(|| "host".to_string()),
+        paste_expected_to_work: false,
+        suggestion: String::new(),
+    };
+    status.assess();
+    status
 }
 
 /// Check if a command exists in PATH
@@ -69,6 +194,12 @@ fn command_exists(cmd: &str) -> bool {
 /// Apply ACL for immediate access (requires pkexec/sudo)
 #[tauri::command]
 pub fn fix_permissions_now() -> Result<String, String> {
+    if let Some(sandbox) = environment::sandbox() {
+        return Err(format!(
+            "Device permissions can't be changed from inside the {} sandbox. Use the RemoteDesktop and GlobalShortcuts portals instead.",
+            sandbox
+        ));
+    }
     // Check required commands exist
     if !command_exists("pkexec") {
         return Err("pkexec not found. Install polkit or run manually: sudo setfacl -m u:$USER:rw /dev/uinput".to_string());
@@ -77,12 +208,36 @@ pub fn fix_permissions_now() -> Result<String, String> {
         return Err("setfacl not found. Install acl package (e.g., 'sudo apt install acl') or add yourself to the input group: sudo usermod -aG input $USER".to_string());
     }
 
+    let username = validated_username()?;
+
+    // Use pkexec for graphical password prompt
+    let status = Command::new("pkexec")
+        .args([
+            "setfacl",
+            "-m",
+            &format!("u:{}:rw", username),
+            "/dev/uinput",
+        ])
+        .status()
+        .map_err(|e| format!("Failed to run pkexec: {}", e))?;
+
+    if status.success() {
+        Ok("Permission granted! Paste should work now.".to_string())
+    } else {
+        Err("Failed to set permissions. Try running manually: sudo setfacl -m u:$USER:rw /dev/uinput".to_string())
+    }
+}
+
+/// The current user's name, refused if it could inject into an ACL or a
+/// command line run as root
+fn validated_username() -> Result<String, String> {
     let username = whoami::username().map_err(|e| format!("Failed to get username: {}", e))?;
 
     // SECURITY: Validate username to prevent ACL format injection.
     // Valid Linux usernames match: [a-z_][a-z0-9_-]*[$]?
     if username.is_empty()
         || username.len() > 256
+        || username.starts_with('-')
         || !username
             .chars()
             .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
@@ -92,23 +247,307 @@ pub fn fix_permissions_now() -> Result<String, String> {
             username
         ));
     }
+    Ok(username)
+}
 
-    // Use pkexec for graphical password prompt
-    let status = Command::new("pkexec")
+/// Whether `getfacl` output grants `user` read and write access
+fn acl_grants_rw(getfacl_output: &str, user: &str) -> bool {
+    let entry = format!("user:{}:rw", user);
+    getfacl_output
+        .lines()
+        .any(|line| line.trim_start().starts_with(&entry))
+}
+
+/// One step of `fix_permissions_persistent`
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FixStep {
+    /// `udev_rule`, `input_group` or `udev_reload`
+    pub step: String,
+    pub success: bool,
+}
+
+/// The steps the persistent fix script reported, in order
+fn parse_fix_steps(output: &str) -> Vec<FixStep> {
+    output
+        .lines()
+        .filter_map(|line| match line.trim().split_once(' ') {
+            Some(("ok", step)) => Some((step, true)),
+            Some(("failed", step)) => Some((step, false)),
+            _ => None,
+        })
+        .map(|(step, success)| FixStep {
+            step: step.to_string(),
+            success,
+        })
+        .collect()
+}
+
+/// Give the `input` group access to /dev/uinput for good: installs a udev
+/// rule, adds the user to the group and reloads udev, behind one pkexec
+/// prompt. Reports each step; the group only applies after logging in again.
+#[tauri::command]
+pub fn fix_permissions_persistent() -> Result<Vec<FixStep>, String> {
+    if let Some(sandbox) = environment::sandbox() {
+        return Err(format!(
+            "Device permissions can't be changed from inside the {} sandbox.",
+            sandbox
+        ));
+    }
+    if !command_exists("pkexec") {
+        return Err(format!(
+            "pkexec not found. Install polkit or run manually: echo '{}' | sudo tee {} && sudo usermod -aG input $USER",
+            UDEV_RULE, UDEV_RULE_PATH
+        ));
+    }
+    let username = validated_username()?;
+
+    let output = Command::new("pkexec")
         .args([
-            "setfacl",
-            "-m",
-            &format!("u:{}:rw", username),
-            "/dev/uinput",
+            "sh",
+            "-c",
+            PERSISTENT_FIX_SCRIPT,
+            "sh",
+            UDEV_RULE,
+            UDEV_RULE_PATH,
+            &username,
         ])
-        .status()
+        .output()
         .map_err(|e| format!("Failed to run pkexec: {}", e))?;
 
-    if status.success() {
-        Ok("Permission granted! Paste should work now.".to_string())
-    } else {
-        Err("Failed to set permissions. Try running manually: sudo setfacl -m u:$USER:rw /dev/uinput".to_string())
+    let steps = parse_fix_steps(&String::from_utf8_lossy(&output.stdout));
+    if steps.is_empty() {
+        // pkexec exits with 126 when the prompt is dismissed
+        return Err(match output.status.code() {
+            Some(126) => "Authorization was cancelled.".to_string(),
+            _ => format!(
+                "Failed to install the udev rule: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    for step in &steps {
+        log::info!(
+            "persistent permission fix: {} {}",
+            step.step,
+            if step.success { "succeeded" } else { "failed" }
+        );
+    }
+    Ok(steps)
+}
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// Whether one feature works in this session, and what the user will notice
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FeatureSupport {
+    pub feature: String,
+    pub works: bool,
+    pub impact: String,
+}
+
+/// What this Wayland session offers and which features degrade because of it
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct WaylandSupport {
+    pub session_type: SessionType,
+    /// The desktop and its compositor, e.g. "GNOME (Mutter)"
+    pub compositor: String,
+    /// wlr or ext data-control, for watching the clipboard without focus
+    pub data_control: bool,
+    pub global_shortcuts_portal: bool,
+    pub remote_desktop_portal: bool,
+    pub wl_clipboard_available: bool,
+    pub wtype_available: bool,
+    pub ydotool_available: bool,
+    pub uinput_accessible: bool,
+    /// The GNOME Shell helper extension is running
+    pub shell_helper: bool,
+    /// KWin runs our scripts, which save and restore focus on Plasma
+    pub kwin_scripting: bool,
+    /// GNOME Wayland without the helper, where installing it would bring
+    /// back focus restore
+    pub shell_helper_suggested: bool,
+    /// Empty outside Wayland, where nothing degrades
+    pub features: Vec<FeatureSupport>,
+}
+
+/// Interface names declared in D-Bus introspection XML
+fn introspected_interfaces(xml: &str) -> Vec<&str> {
+    xml.split("<interface name=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .collect()
+}
+
+/// The interfaces the desktop portal implements, empty without a session bus
+async fn portal_interfaces() -> Vec<String> {
+    let Ok(connection) = zbus::Connection::session().await else {
+        return Vec::new();
+    };
+    let reply = connection
+        .call_method(
+            Some(PORTAL_DESTINATION),
+            PORTAL_PATH,
+            Some("org.freedesktop.DBus.Introspectable"),
+            "Introspect",
+            &(),
+        )
+        .await;
+    match reply.and_then(|message| message.body().deserialize::<String>()) {
+        Ok(xml) => introspected_interfaces(&xml)
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        Err(e) => {
+            log::debug!("portal introspection failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+impl WaylandSupport {
+    /// What works and what doesn't, feature by feature
+    fn assess_features(&self, sandboxed: bool) -> Vec<FeatureSupport> {
+        let feature = |feature: &str, works: bool, impact: &str| FeatureSupport {
+            feature: feature.to_string(),
+            works,
+            impact: impact.to_string(),
+        };
+        let paste_tool = [
+            ("wtype", self.wtype_available),
+            ("ydotool", self.ydotool_available),
+            ("/dev/uinput", self.uinput_accessible),
+        ]
+        .into_iter()
+        .find(|(_, usable)| *usable)
+        .map(|(name, _)| name);
+
+        vec![
+            if self.data_control {
+                feature(
+                    "Clipboard history",
+                    true,
+                    "Copies are recorded as they happen, even while PenguinClip is hidden.",
+                )
+            } else {
+                feature(
+                    "Clipboard history",
+                    true,
+                    "The compositor has no data-control protocol, so the clipboard is polled. Copies are recorded with a short delay.",
+                )
+            },
+            if self.global_shortcuts_portal {
+                feature(
+                    "Global shortcut",
+                    true,
+                    "Registered through the GlobalShortcuts portal; the desktop may ask you to confirm it.",
+                )
+            } else if sandboxed {
+                feature(
+                    "Global shortcut",
+                    false,
+                    "No GlobalShortcuts portal, and the sandbox can't edit the desktop's settings. Bind a shortcut to PenguinClip by hand.",
+                )
+            } else {
+                feature(
+                    "Global shortcut",
+                    true,
+                    "No GlobalShortcuts portal; the shortcut is added to the desktop's own keyboard settings instead.",
+                )
+            },
+            match paste_tool {
+                Some(tool) => feature(
+                    "Automatic paste",
+                    true,
+                    &format!("The paste keystroke is sent with {}.", tool),
+                ),
+                None => feature(
+                    "Automatic paste",
+                    false,
+                    "Nothing can send the paste keystroke: install wtype (not on GNOME) or ydotool, or allow /dev/uinput on the next step. Until then, items are only copied and you press Ctrl+V yourself.",
+                ),
+            },
+            if self.shell_helper {
+                feature(
+                    "Focus restore",
+                    true,
+                    "The PenguinClip Shell Helper extension gives focus back to the window you were in.",
+                )
+            } else if self.kwin_scripting {
+                feature(
+                    "Focus restore",
+                    true,
+                    "A KWin script gives focus back to the window you were in.",
+                )
+            } else if self.shell_helper_suggested {
+                feature(
+                    "Focus restore",
+                    false,
+                    "GNOME doesn't let apps refocus other windows. Install the PenguinClip Shell Helper extension so pastes reach the window you were in.",
+                )
+            } else {
+                feature(
+                    "Focus restore",
+                    false,
+                    "Wayland doesn't let apps refocus other windows, so the window you were in only gets focus back if the compositor returns it.",
+                )
+            },
+            if self.wl_clipboard_available || self.data_control {
+                feature(
+                    "GIF paste",
+                    true,
+                    "GIFs are placed on the clipboard as images.",
+                )
+            } else {
+                feature(
+                    "GIF paste",
+                    false,
+                    "Install wl-clipboard so GIFs paste as images rather than links.",
+                )
+            },
+        ]
+    }
+}
+
+/// Reports what will and won't work in this session, before setup
+#[tauri::command]
+pub async fn check_wayland_support() -> WaylandSupport {
+    let compositor = session::compositor_name()
+        .map(str::to_string)
+        .unwrap_or_else(|| environment::detect().name.clone());
+    let portals = portal_interfaces().await;
+    let has_portal = |interface: &str| portals.iter().any(|p| p == interface);
+    let helper_running = shell_helper::is_available();
+
+    let mut support = WaylandSupport {
+        session_type: session::get_session_type(),
+        compositor,
+        data_control: wayland_source::is_available(),
+        global_shortcuts_portal: has_portal("org.freedesktop.portal.GlobalShortcuts"),
+        remote_desktop_portal: has_portal("org.freedesktop.portal.RemoteDesktop"),
+        wl_clipboard_available: input_simulator::wl_clipboard_available(),
+        wtype_available: input_simulator::wtype_available(),
+        ydotool_available: input_simulator::ydotool_status().is_ok(),
+        uinput_accessible: uinput_accessible(),
+        shell_helper: helper_running,
+        kwin_scripting: false,
+        shell_helper_suggested: false,
+        features: Vec::new(),
+    };
+    if support.session_type == SessionType::Wayland {
+        support.kwin_scripting = session::is_kwin() && kwin::is_available();
+        support.shell_helper_suggested =
+            !helper_running && session::gnome_shell_version().is_some();
+        support.features = support.assess_features(environment::is_sandboxed());
     }
+    support
+}
+
+/// Whether the GNOME Shell helper extension is running, so the wizard can
+/// tell when it has been installed
+#[tauri::command]
+pub fn is_shell_helper_available() -> bool {
+    shell_helper::is_available()
 }
 
 /// Check if this is the first run of the application
@@ -152,3 +591,122 @@ pub fn reset_first_run() -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(session_type: SessionType) -> PermissionStatus {
+        PermissionStatus {
+            session_type,
+            uinput_accessible: false,
+            uinput_path: "/dev/uinput".to_string(),
+            user_in_input_group: false,
+            xdotool_available: false,
+            xtest_available: false,
+            wtype_available: false,
+            ydotool_available: false,
+            ydotool_error: Some("ydotool is not installed".to_string()),
+            ydotoold_running: false,
+            wl_clipboard_available: false,
+            gif_clipboard: GifClipboardStatus {
+                is_wayland: session_type == SessionType::Wayland,
+                wl_copy_available: false,
+                native_wayland_available: false,
+                xclip_available: false,
+                active_backend: "none".to_string(),
+            },
+            udev_rule_installed: false,
+            acl_applied: false,
+            relogin_required: false,
+            environment: "host".to_string(),
+            paste_expected_to_work: false,
+            suggestion: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_assess_per_session() {
+        // XTest pastes on X11 without /dev/uinput
+        let mut x11 = status(SessionType::X11);
+        x11.xtest_available = true;
+        x11.wtype_available = true;
+        x11.assess();
+        assert!(x11.paste_expected_to_work);
+        assert_eq!(x11.suggestion, "Paste will work using XTest.");
+
+        // wtype doesn't help an X11 session
+        let mut x11 = status(SessionType::X11);
+        x11.wtype_available = true;
+        x11.assess();
+        assert!(!x11.paste_expected_to_work);
+        assert!(x11.suggestion.contains("xdotool"));
+
+        let mut wayland = status(SessionType::Wayland);
+        wayland.xdotool_available = true;
+        wayland.ydotoold_running = true;
+        wayland.assess();
+        assert!(!wayland.paste_expected_to_work);
+        assert!(wayland.suggestion.contains("client is missing"));
+
+        wayland.uinput_accessible = true;
+        wayland.assess();
+        assert!(wayland.paste_expected_to_work);
+        assert!(wayland.suggestion.contains("uinput"));
+        assert!(wayland.suggestion.contains("wl-clipboard"));
+
+        // GIFs are served natively without wl-clipboard
+        wayland.gif_clipboard.native_wayland_available = true;
+        wayland.assess();
+        assert!(!wayland.suggestion.contains("wl-clipboard"));
+    }
+
+    #[test]
+    fn test_introspected_interfaces() {
+        let xml = r#"<node>
+  <interface name="org.freedesktop.DBus.Properties">
+    <method name="Get"/>
+  </interface>
+  <interface name="org.freedesktop.portal.GlobalShortcuts"/>
+  <node name="request"/>
+</node>"#;
+        assert_eq!(
+            introspected_interfaces(xml),
+            [
+                "org.freedesktop.DBus.Properties",
+                "org.freedesktop.portal.GlobalShortcuts"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_acl_grants_rw() {
+        let output = "# file: /dev/uinput\n# owner: root\n# group: root\nuser::rw-\nuser:alice:rw-\ngroup::---\nmask::rw-\nother::---\n";
+        assert!(acl_grants_rw(output, "alice"));
+        assert!(!acl_grants_rw(output, "bob"));
+        assert!(!acl_grants_rw("user:alice:r--\n", "alice"));
+    }
+
+    #[test]
+    fn test_parse_fix_steps() {
+        let steps =
+            parse_fix_steps("ok udev_rule\nusermod: warning\nfailed input_group\nok udev_reload\n");
+        assert_eq!(
+            steps,
+            [
+                FixStep {
+                    step: "udev_rule".to_string(),
+                    success: true
+                },
+                FixStep {
+                    step: "input_group".to_string(),
+                    success: false
+                },
+                FixStep {
+                    step: "udev_reload".to_string(),
+                    success: true
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! QR Codes
+//! Renders text to QR code images and reads QR codes back out of images.
+//! Both happen in memory, in pure Rust.
+
+use arboard::ImageData;
+use image::Rgba;
+use qrcode::types::QrError;
+use qrcode::QrCode;
+
+/// Most bytes a QR code can hold at the default (medium) error correction
+/// level, with the largest symbol version (40).
+pub const QR_MAX_BYTES: usize = 2331;
+
+/// Rendered QR codes are at least this many pixels wide, so they stay
+/// scannable from a screen.
+const QR_MIN_DIMENSION: u32 = 256;
+
+/// Renders `text` as a black-on-white QR code image.
+pub fn encode(text: &str) -> Result<ImageData<'static>, String> {
+    let too_long = || {
+        format!(
+            "Text is too long for a QR code ({} bytes, at most {} bytes fit)",
+            text.len(),
+            QR_MAX_BYTES
+        )
+    };
+    if text.len() > QR_MAX_BYTES {
+        return Err(too_long());
+    }
+    let code = QrCode::new(text.as_bytes()).map_err(|e| match e {
+        QrError::DataTooLong => too_long(),
+        other => format!("Failed to encode QR code: {}", other),
+    })?;
+
+    let image = code
+        .render::<Rgba<u8>>()
+        .min_dimensions(QR_MIN_DIMENSION, QR_MIN_DIMENSION)
+        .build();
+    Ok(ImageData {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        bytes: image.into_raw().into(),
+    })
+}
+
+/// Scans a PNG for QR codes and returns the decoded strings, in the order
+/// they are found. Fails if none can be read.
+pub fn decode_png(png_bytes: &[u8]) -> Result<Vec<String>, String> {
+    let image = image::load_from_memory(png_bytes)
+        .map_err(|e| format!("Image load failed: {}", e))?
+        .into_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        image.width() as usize,
+        image.height() as usize,
+        |x, y| image.get_pixel(x as u32, y as u32).0[0],
+    );
+
+    let codes: Vec<String> = prepared
+        .detect_grids()
+        .iter()
+        .filter_map(|grid| match grid.decode() {
+            Ok((_, content)) => Some(content),
+            Err(e) => {
+                log::debug!("unreadable QR code: {}", e);
+                None
+            }
+        })
+        .filter(|content| !content.is_empty())
+        .collect();
+    if codes.is_empty() {
+        return Err("No QR code found in the image".to_string());
+    }
+    Ok(codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_renders_square_opaque_image() {
+        let image = encode("https://example.com").unwrap();
+        assert_eq!(image.width, image.height);
+        assert!(image.width >= QR_MIN_DIMENSION as usize);
+        assert_eq!(image.bytes.len(), image.width * image.height * 4);
+        assert!(image.bytes.chunks(4).all(|px| px[3] == 255));
+    }
+
+    #[test]
+    fn test_encode_reports_capacity() {
+        assert!(encode(&"a".repeat(QR_MAX_BYTES)).is_ok());
+        let err = encode(&"a".repeat(QR_MAX_BYTES + 1)).unwrap_err();
+        assert!(err.contains("2331 bytes"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_decode_reads_encoded_text() {
+        let rendered = encode("https://example.com").unwrap();
+        let image = image::RgbaImage::from_raw(
+            rendered.width as u32,
+            rendered.height as u32,
+            rendered.bytes.into_owned(),
+        )
+        .unwrap();
+        let mut png = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png).unwrap();
+
+        assert_eq!(decode_png(png.get_ref()).unwrap(), ["https://example.com"]);
+    }
+
+    #[test]
+    fn test_decode_without_code_fails() {
+        let image = image::RgbaImage::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+        let mut png = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png).unwrap();
+
+        assert!(decode_png(png.get_ref()).is_err());
+    }
+}
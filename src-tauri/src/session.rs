@@ -1,14 +1,31 @@
 //! Session Detection Module
-//! Detects whether we're running on Wayland or X11 session.
-//! Evaluated lazily once and cached for performance.
+//! Detects whether we're running on Wayland or X11 session, whether we are
+//! an XWayland client, which compositor runs the session and the GNOME Shell
+//! and Plasma versions. Evaluated lazily once and cached for performance.
 
+use crate::environment::{self, DesktopEnvironment};
+use crate::wayland_source;
 use std::env;
 use std::sync::OnceLock;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
 
-/// Cached session type singleton
-static SESSION_TYPE: OnceLock<SessionType> = OnceLock::new();
+/// Cached session type singleton, with the variable it was detected from
+static SESSION_TYPE: OnceLock<(SessionType, &'static str)> = OnceLock::new();
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Cached details beyond the session type
+static SESSION_INFO: OnceLock<SessionInfo> = OnceLock::new();
+
+/// Wayland globals that identify a compositor family, checked in order
+const COMPOSITOR_GLOBALS: &[(&str, &str)] = &[
+    ("hyprland_", "Hyprland"),
+    ("cosmic_", "COSMIC"),
+    ("org_kde_kwin_", "KWin"),
+    ("gtk_shell1", "Mutter"),
+    ("zwlr_layer_shell_v1", "wlroots"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 pub enum SessionType {
     Wayland,
     X11,
@@ -16,6 +33,14 @@ pub enum SessionType {
 }
 
 impl SessionType {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Wayland => "Wayland",
+            Self::X11 => "X11",
+            Self::Unknown => "Unknown",
+        }
+    }
+
     /// heuristic detection of the current session
     fn detect() -> (Self, &'static str) {
         // 1. Check XDG_SESSION_TYPE (Most reliable source)
@@ -44,10 +69,20 @@ impl SessionType {
 /// Get the cached session type.
 /// Detects the session if it hasn't been initialized yet.
 pub fn get_session_type() -> SessionType {
+    detection().0
+}
+
+/// The environment variable the session type was detected from ("None" if
+/// nothing matched).
+pub fn detection_source() -> &'static str {
+    detection().1
+}
+
+fn detection() -> (SessionType, &'static str) {
     *SESSION_TYPE.get_or_init(|| {
         let (session, source) = SessionType::detect();
         log::info!("detected {:?} session via {}", session, source);
-        session
+        (session, source)
     })
 }
 
@@ -63,10 +98,198 @@ pub fn is_x11() -> bool {
     get_session_type() == SessionType::X11
 }
 
+/// The session type and everything else we know about the session
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionInfo {
+    pub session_type: SessionType,
+    /// Environment variable the session type was detected from
+    pub detection_source: String,
+    /// We're an X11 client of XWayland inside a Wayland session
+    pub xwayland: bool,
+    /// `XDG_CURRENT_DESKTOP` plus the compositor or window manager's own
+    /// name, e.g. "GNOME (Mutter)"
+    pub compositor: Option<String>,
+    /// The compositor or window manager's own name alone, e.g. "KWin"
+    pub window_manager: Option<String>,
+    /// Major versions, when that desktop is running
+    pub gnome_shell_version: Option<u32>,
+    pub plasma_version: Option<u32>,
+}
+
+/// The cached session details, detected on the first call
+pub fn info() -> &'static SessionInfo {
+    SESSION_INFO.get_or_init(|| {
+        let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        let window_manager = detect_compositor();
+        let info = SessionInfo {
+            session_type: get_session_type(),
+            detection_source: detection_source().to_string(),
+            xwayland: is_xwayland_from(
+                env::var_os("WAYLAND_DISPLAY").is_some(),
+                env::var("GDK_BACKEND").ok().as_deref(),
+            ),
+            compositor: compositor_label(&desktop, window_manager.as_deref()),
+            window_manager,
+            gnome_shell_version: detect_gnome_shell_version(),
+            plasma_version: detect_plasma_version(),
+        };
+        log::info!(
+            "session compositor {} (xwayland: {})",
+            info.compositor.as_deref().unwrap_or("unknown"),
+            info.xwayland
+        );
+        info
+    })
+}
+
+/// True if a Wayland session runs us through XWayland, because our GDK
+/// backend was forced to x11. Focus handling and keystrokes then go
+/// through X11 but only reach other XWayland clients.
+pub fn is_xwayland() -> bool {
+    info().xwayland
+}
+
+fn is_xwayland_from(wayland_display: bool, gdk_backend: Option<&str>) -> bool {
+    wayland_display && gdk_backend.is_some_and(|backend| backend.trim().starts_with("x11"))
+}
+
+/// The compositor or window manager running the session, e.g. "GNOME
+/// (Mutter)" or "i3"
+pub fn compositor_name() -> Option<&'static str> {
+    info().compositor.as_deref()
+}
+
+/// Whether KWin runs the session. Decided by the compositor family, since
+/// the label reads "KDE (KWin)"; without one, by `XDG_CURRENT_DESKTOP`.
+pub fn is_kwin() -> bool {
+    let info = info();
+    is_kwin_from(
+        info.window_manager.as_deref(),
+        &env::var("XDG_CURRENT_DESKTOP").unwrap_or_default(),
+    )
+}
+
+fn is_kwin_from(window_manager: Option<&str>, desktop: &str) -> bool {
+    match window_manager {
+        Some(window_manager) => window_manager.eq_ignore_ascii_case("KWin"),
+        None => desktop.split(':').any(|desktop| desktop.trim() == "KDE"),
+    }
+}
+
+/// The running GNOME Shell's major version, e.g. 46
+pub fn gnome_shell_version() -> Option<u32> {
+    info().gnome_shell_version
+}
+
+/// The running Plasma's major version, e.g. 6
+pub fn plasma_version() -> Option<u32> {
+    info().plasma_version
+}
+
+/// Combines the desktop's name with the compositor's own, when they differ
+fn compositor_label(desktop: &str, compositor: Option<&str>) -> Option<String> {
+    let desktop = desktop.split(':').next_back().unwrap_or_default().trim();
+    match (desktop, compositor) {
+        ("", compositor) => compositor.map(str::to_string),
+        (desktop, Some(compositor)) if !compositor.eq_ignore_ascii_case(desktop) => {
+            Some(format!("{} ({})", desktop, compositor))
+        }
+        (desktop, _) => Some(desktop.to_string()),
+    }
+}
+
+/// The compositor family the advertised Wayland globals belong to
+fn compositor_family(globals: &[String]) -> Option<&'static str> {
+    COMPOSITOR_GLOBALS.iter().find_map(|(prefix, family)| {
+        globals
+            .iter()
+            .any(|interface| interface.starts_with(prefix))
+            .then_some(*family)
+    })
+}
+
+/// On Wayland the family the registry globals point to; on X11 the window
+/// manager's `_NET_WM_NAME` on its `_NET_SUPPORTING_WM_CHECK` window
+fn detect_compositor() -> Option<String> {
+    if get_session_type() == SessionType::Wayland {
+        let globals = wayland_source::registry_globals()?;
+        return compositor_family(&globals).map(str::to_string);
+    }
+    x11_window_manager_name()
+}
+
+fn x11_window_manager_name() -> Option<String> {
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let atom = |name: &[u8]| Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom);
+    let check = atom(b"_NET_SUPPORTING_WM_CHECK")?;
+    let net_wm_name = atom(b"_NET_WM_NAME")?;
+    let utf8_string = atom(b"UTF8_STRING")?;
+
+    let window = conn
+        .get_property(false, root, check, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?
+        .value32()?
+        .next()?;
+    let name = conn
+        .get_property(false, window, net_wm_name, utf8_string, 0, 256)
+        .ok()?
+        .reply()
+        .ok()?
+        .value;
+    String::from_utf8(name)
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+}
+
+fn detect_gnome_shell_version() -> Option<u32> {
+    let desktop = environment::detect();
+    if !matches!(
+        desktop.de,
+        DesktopEnvironment::Gnome | DesktopEnvironment::PopOs
+    ) {
+        return None;
+    }
+    major_version(desktop.version.as_deref()?)
+}
+
+/// The session sets `KDE_SESSION_VERSION`; outside of it we ask plasmashell
+fn detect_plasma_version() -> Option<u32> {
+    env::var("KDE_SESSION_VERSION")
+        .ok()
+        .and_then(|version| version.trim().parse().ok())
+        .or_else(|| {
+            let output = environment::host_command("plasmashell")
+                .arg("--version")
+                .output()
+                .ok()?;
+            parse_plasma_version(&String::from_utf8_lossy(&output.stdout))
+        })
+}
+
+/// The Plasma major version from `plasmashell --version` output ("plasmashell 6.1.5")
+fn parse_plasma_version(output: &str) -> Option<u32> {
+    let version = output.trim_start().strip_prefix("plasmashell ")?;
+    major_version(version)
+}
+
+/// The leading number of a version like "46.0"
+fn major_version(version: &str) -> Option<u32> {
+    let major: String = version
+        .trim_start()
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    major.parse().ok()
+}
+
 /// Explicitly initialize session detection.
 /// Useful to ensure the log message appears early in the application startup.
 pub fn init() {
     get_session_type();
+    info();
 }
 
 #[cfg(test)]
@@ -80,4 +303,67 @@ mod tests {
         let second = get_session_type();
         assert_eq!(first, second);
     }
+
+    #[test]
+    fn test_is_xwayland_from() {
+        assert!(is_xwayland_from(true, Some("x11")));
+        assert!(is_xwayland_from(true, Some("x11,wayland")));
+        assert!(!is_xwayland_from(true, Some("wayland,x11")));
+        assert!(!is_xwayland_from(true, None));
+        assert!(!is_xwayland_from(false, Some("x11")));
+    }
+
+    #[test]
+    fn test_compositor_label() {
+        assert_eq!(
+            compositor_label("ubuntu:GNOME", Some("Mutter")).as_deref(),
+            Some("GNOME (Mutter)")
+        );
+        assert_eq!(compositor_label("i3", Some("i3")).as_deref(), Some("i3"));
+        assert_eq!(
+            compositor_label("", Some("Openbox")).as_deref(),
+            Some("Openbox")
+        );
+        assert_eq!(compositor_label("KDE", None).as_deref(), Some("KDE"));
+        assert_eq!(compositor_label("", None), None);
+    }
+
+    #[test]
+    fn test_is_kwin_from() {
+        // Plasma Wayland: the label isn't "KWin", the family is
+        assert_eq!(
+            compositor_label("KDE", Some("KWin")).as_deref(),
+            Some("KDE (KWin)")
+        );
+        assert!(is_kwin_from(Some("KWin"), "KDE"));
+        assert!(is_kwin_from(None, "KDE"));
+        assert!(!is_kwin_from(Some("Mutter"), "GNOME"));
+        assert!(!is_kwin_from(None, "ubuntu:GNOME"));
+    }
+
+    #[test]
+    fn test_compositor_family() {
+        let globals = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            compositor_family(&globals(&["wl_seat", "gtk_shell1", "zwp_linear_dmabuf_v1"])),
+            Some("Mutter")
+        );
+        // Hyprland also advertises the wlroots protocols
+        assert_eq!(
+            compositor_family(&globals(&[
+                "zwlr_layer_shell_v1",
+                "hyprland_focus_grab_manager_v1"
+            ])),
+            Some("Hyprland")
+        );
+        assert_eq!(compositor_family(&globals(&["wl_seat"])), None);
+    }
+
+    #[test]
+    fn test_parse_plasma_version() {
+        assert_eq!(parse_plasma_version("plasmashell 6.1.5"), Some(6));
+        assert_eq!(parse_plasma_version("plasmashell 5.27.11\n"), Some(5));
+        assert_eq!(parse_plasma_version("kwin 6.0.0"), None);
+        assert_eq!(major_version("46.0"), Some(46));
+    }
 }
@@ -0,0 +1,107 @@
+//! GNOME Shell Helper Client
+//! Mutter gives Wayland clients no way to learn which window has focus or to
+//! activate another one. The companion PenguinClip GNOME Shell extension
+//! exports `org.penguinclip.ShellHelper` on the session bus to do both from
+//! inside the shell; this is the client side. Without the extension every
+//! call fails softly and focus handling falls back to what the compositor
+//! does on its own.
+
+use std::thread;
+
+const BUS_NAME: &str = "org.penguinclip.ShellHelper";
+const OBJECT_PATH: &str = "/org/penguinclip/ShellHelper";
+const INTERFACE: &str = "org.penguinclip.ShellHelper";
+
+/// The focused window as the extension reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusedWindow {
+    /// The app's id, e.g. "org.gnome.TextEditor.desktop"
+    pub app_id: String,
+    pub title: String,
+}
+
+/// Runs a blocking zbus call on its own thread: zbus's blocking API drives a
+/// runtime of its own, which panics on a tokio worker (paste runs on one).
+fn call<T: Send>(f: impl FnOnce() -> zbus::Result<T> + Send) -> Result<T, String> {
+    thread::scope(|scope| {
+        scope
+            .spawn(f)
+            .join()
+            .map_err(|_| "Shell helper call panicked".to_string())?
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Whether the extension's service is on the session bus right now. Checked
+/// on every call, since the extension can be enabled while we run.
+pub fn is_available() -> bool {
+    call(|| {
+        let connection = zbus::blocking::Connection::session()?;
+        connection
+            .call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "NameHasOwner",
+                &(BUS_NAME,),
+            )?
+            .body()
+            .deserialize::<bool>()
+    })
+    .unwrap_or(false)
+}
+
+/// An empty app id means no window has focus (e.g. the overview is open)
+fn focused_from_reply((app_id, title): (String, String)) -> Option<FocusedWindow> {
+    (!app_id.is_empty()).then_some(FocusedWindow { app_id, title })
+}
+
+/// The focused window, `None` when nothing has focus
+pub fn focused_window() -> Result<Option<FocusedWindow>, String> {
+    call(|| {
+        let connection = zbus::blocking::Connection::session()?;
+        connection
+            .call_method(
+                Some(BUS_NAME),
+                OBJECT_PATH,
+                Some(INTERFACE),
+                "GetFocusedWindow",
+                &(),
+            )?
+            .body()
+            .deserialize::<(String, String)>()
+    })
+    .map(focused_from_reply)
+}
+
+/// Asks the shell to activate the most recent window of `app_id`
+pub fn activate_window(app_id: &str) -> Result<(), String> {
+    call(|| {
+        let connection = zbus::blocking::Connection::session()?;
+        connection.call_method(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            Some(INTERFACE),
+            "ActivateWindow",
+            &(app_id,),
+        )?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focused_from_reply() {
+        assert_eq!(
+            focused_from_reply(("org.gnome.Ptyxis.desktop".into(), "~".into())),
+            Some(FocusedWindow {
+                app_id: "org.gnome.Ptyxis.desktop".to_string(),
+                title: "~".to_string(),
+            })
+        );
+        assert_eq!(focused_from_reply((String::new(), String::new())), None);
+    }
+}
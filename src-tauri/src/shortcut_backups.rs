@@ -0,0 +1,231 @@
+//! Shortcut Config Backups
+//! Every time shortcut registration rewrites a desktop config file, the
+//! previous contents are copied into the app's data directory and recorded
+//! in a manifest, so each change can be undone from the app. Only the
+//! newest `MAX_BACKUPS_PER_FILE` copies of each file are kept.
+
+use crate::atomic_write;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// How many backups of one config file are kept
+pub const MAX_BACKUPS_PER_FILE: usize = 10;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+thread_local! {
+    /// Backup directory set by `with_store_dir` for the current thread
+    static STORE_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// One saved copy of a config file, taken just before we changed it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortcutBackup {
+    pub id: String,
+    /// The config file the copy was taken from
+    pub original_path: PathBuf,
+    /// File name of the copy inside the backup directory
+    pub backup_file: String,
+    /// Milliseconds since the Unix epoch in JSON
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// The backup directory and its manifest
+pub struct BackupStore {
+    dir: PathBuf,
+}
+
+impl BackupStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.dir.join(MANIFEST_FILE)
+    }
+
+    /// All backups, newest first. A missing or unreadable manifest means
+    /// there are none.
+    pub fn list(&self) -> Vec<ShortcutBackup> {
+        let mut backups: Vec<ShortcutBackup> = fs::read_to_string(self.manifest_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+        backups
+    }
+
+    fn save_manifest(&self, backups: &[ShortcutBackup]) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(backups)?;
+        atomic_write::write_atomic(&self.manifest_path(), json.as_bytes())
+    }
+
+    /// Saves `content`, the current contents of `path`, as a new backup and
+    /// drops the oldest ones of that file beyond `MAX_BACKUPS_PER_FILE`.
+    pub fn backup(&self, path: &Path, content: &str) -> io::Result<ShortcutBackup> {
+        let id = Uuid::new_v4().to_string();
+        let created_at = Utc::now();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "config".to_string());
+        let backup = ShortcutBackup {
+            backup_file: format!(
+                "{}-{}-{}",
+                created_at.format("%Y%m%d-%H%M%S%.3f"),
+                &id[..8],
+                file_name
+            ),
+            id,
+            original_path: path.to_path_buf(),
+            created_at,
+        };
+        atomic_write::write_atomic(&self.dir.join(&backup.backup_file), content.as_bytes())?;
+
+        let mut backups = self.list();
+        backups.insert(0, backup.clone());
+        let mut kept_for_file = 0;
+        backups.retain(|b| {
+            if b.original_path != backup.original_path {
+                return true;
+            }
+            kept_for_file += 1;
+            if kept_for_file <= MAX_BACKUPS_PER_FILE {
+                return true;
+            }
+            let _ = fs::remove_file(self.dir.join(&b.backup_file));
+            false
+        });
+        self.save_manifest(&backups)?;
+        Ok(backup)
+    }
+
+    /// Writes backup `id` back over its original file. The contents being
+    /// replaced are backed up first, so a restore can be undone too.
+    pub fn restore(&self, id: &str) -> Result<ShortcutBackup, String> {
+        let backup = self
+            .list()
+            .into_iter()
+            .find(|b| b.id == id)
+            .ok_or_else(|| format!("Unknown shortcut backup '{}'", id))?;
+        let content = fs::read_to_string(self.dir.join(&backup.backup_file))
+            .map_err(|e| format!("Failed to read backup {}: {}", backup.backup_file, e))?;
+
+        match fs::read_to_string(&backup.original_path) {
+            Ok(current) if current != content => {
+                self.backup(&backup.original_path, &current)
+                    .map_err(|e| format!("Failed to back up current config: {}", e))?;
+            }
+            _ => {}
+        }
+        atomic_write::write_atomic(&backup.original_path, content.as_bytes()).map_err(|e| {
+            format!(
+                "Failed to restore {}: {}",
+                backup.original_path.display(),
+                e
+            )
+        })?;
+        log::info!(
+            "restored {} from backup {}",
+            backup.original_path.display(),
+            backup.id
+        );
+        Ok(backup)
+    }
+}
+
+/// The store config edits on this thread are backed up into: the directory
+/// given to `with_store_dir`, else the default one in the data directory.
+pub fn current_store() -> BackupStore {
+    STORE_DIR
+        .with(|dir| dir.borrow().clone())
+        .map(BackupStore::new)
+        .unwrap_or_default()
+}
+
+/// Runs `f` with config edits on this thread backed up into `dir`, so tests
+/// editing temporary files keep their backups out of the real data directory.
+pub fn with_store_dir<T>(dir: &Path, f: impl FnOnce() -> T) -> T {
+    let previous = STORE_DIR.with(|d| d.replace(Some(dir.to_path_buf())));
+    let result = f();
+    STORE_DIR.with(|d| *d.borrow_mut() = previous);
+    result
+}
+
+impl Default for BackupStore {
+    fn default() -> Self {
+        let dir = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("penguinclip")
+            .join("shortcut-backups");
+        Self::new(dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn temp_store(name: &str) -> (BackupStore, PathBuf) {
+        let dir = temp_dir().join(format!("penguinclip_test_backups_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        (BackupStore::new(dir.join("backups")), dir)
+    }
+
+    #[test]
+    fn test_restore_writes_backup_over_original() {
+        let (store, dir) = temp_store("restore");
+        let config = dir.join("config");
+        fs::write(&config, "bindsym $mod+v exec other\n").unwrap();
+        let backup = store
+            .backup(&config, "bindsym $mod+v exec other\n")
+            .unwrap();
+        fs::write(&config, "bindsym $mod+v exec penguinclip\n").unwrap();
+
+        store.restore(&backup.id).unwrap();
+        assert_eq!(
+            fs::read_to_string(&config).unwrap(),
+            "bindsym $mod+v exec other\n"
+        );
+        // The replaced contents were kept, newest first
+        let backups = store.list();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(
+            fs::read_to_string(store.dir.join(&backups[0].backup_file)).unwrap(),
+            "bindsym $mod+v exec penguinclip\n"
+        );
+        assert!(store.restore("missing").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_backups_are_capped_per_file() {
+        let (store, dir) = temp_store("cap");
+        let config = dir.join("config");
+        let other = dir.join("rc.xml");
+        store.backup(&other, "<openbox_config/>").unwrap();
+        for i in 0..MAX_BACKUPS_PER_FILE + 3 {
+            store.backup(&config, &format!("version {}", i)).unwrap();
+        }
+
+        let backups = store.list();
+        let for_config: Vec<_> = backups
+            .iter()
+            .filter(|b| b.original_path == config)
+            .collect();
+        assert_eq!(for_config.len(), MAX_BACKUPS_PER_FILE);
+        assert!(backups.iter().any(|b| b.original_path == other));
+        // Pruned copies are deleted, not just dropped from the manifest
+        let files = fs::read_dir(&store.dir).unwrap().count();
+        assert_eq!(files, MAX_BACKUPS_PER_FILE + 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,26 +1,130 @@
 //! Shortcut Conflict Detection for Various Desktop Environments
-//! Detects existing shortcuts that conflict with Super+V across different DEs
-
+//! Detects existing shortcuts that take the keys of our clipboard history
+//! and emoji picker shortcuts across different DEs
+
+use crate::conflict_journal::ConflictJournal;
+use crate::environment::{self, host_command, DesktopEnvironment};
+use crate::linux_shortcut_manager::{
+    awesome_binds, candidate_shortcut, config_files_with_includes, conflict_shortcuts,
+    cosmic_actions_bound_to, enlightenment_bindings, has_hyprland_binding, has_i3_binding,
+    has_qtile_binding, has_river_mapping, hyprland_runtime_binds, HistoryBindings, HyprlandBind,
+    KeyBinding, Modifier, ShortcutConfig,
+};
+use crate::paths::xdg_config_dir;
+use crate::session;
 use std::env;
+use std::fmt::Write;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
 /// Represents a detected shortcut conflict
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ShortcutConflict {
     /// The shortcut binding that conflicts (e.g., "Super+V")
     pub binding: String,
+    /// Which of our shortcuts it takes (e.g., "Emoji Picker")
+    pub conflicts_with: String,
     /// What the shortcut currently does
     pub current_action: String,
     /// The desktop environment or application that owns this shortcut
     pub owner: String,
-    /// Command or instructions to resolve the conflict
-    pub resolution_command: Option<String>,
-    /// Human-readable resolution steps
+    /// Where the shortcut is changed in the desktop's settings
+    /// (e.g., ["Settings", "Keyboard", "Keyboard Shortcuts"])
+    pub settings_path: Vec<String>,
+    /// Steps to resolve the conflict by hand, after opening `settings_path`
+    pub manual_steps: Vec<String>,
+    /// Commands that resolve the conflict without the user's help
+    pub commands: Vec<ResolutionCommand>,
+    /// The resolution above as markdown, for frontends that predate the
+    /// structured fields
     pub resolution_steps: String,
 }
 
+/// A command that resolves a conflict
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ResolutionCommand {
+    /// What running it changes
+    pub description: String,
+    pub command: String,
+    /// Whether it takes the shortcut away from its owner entirely, rather
+    /// than moving it to other keys
+    pub destructive: bool,
+}
+
+impl ShortcutConflict {
+    /// A conflict over `target`'s keys (written as `binding`) with no
+    /// resolution yet
+    fn new(
+        target: &ShortcutConfig,
+        binding: impl Into<String>,
+        current_action: impl Into<String>,
+        owner: impl Into<String>,
+    ) -> Self {
+        Self {
+            binding: binding.into(),
+            conflicts_with: target.name.to_string(),
+            current_action: current_action.into(),
+            owner: owner.into(),
+            settings_path: Vec::new(),
+            manual_steps: Vec::new(),
+            commands: Vec::new(),
+            resolution_steps: String::new(),
+        }
+        .render_steps()
+    }
+
+    fn in_settings(mut self, path: &[&str]) -> Self {
+        self.settings_path = path.iter().map(|page| page.to_string()).collect();
+        self.render_steps()
+    }
+
+    fn steps(mut self, steps: Vec<String>) -> Self {
+        self.manual_steps = steps;
+        self.render_steps()
+    }
+
+    fn command(mut self, description: String, command: String, destructive: bool) -> Self {
+        self.commands.push(ResolutionCommand {
+            description,
+            command,
+            destructive,
+        });
+        self.render_steps()
+    }
+
+    /// Regenerates `resolution_steps` from the structured fields
+    fn render_steps(mut self) -> Self {
+        let mut steps = Vec::new();
+        if !self.settings_path.is_empty() {
+            steps.push(format!("Open {}", self.settings_path.join(" → ")));
+        }
+        steps.extend(self.manual_steps.iter().cloned());
+
+        let mut text = String::new();
+        if !steps.is_empty() {
+            text.push_str("**To resolve manually:**\n");
+            for (i, step) in steps.iter().enumerate() {
+                let _ = writeln!(text, "{}. {}", i + 1, step);
+            }
+        }
+        if !self.commands.is_empty() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(match self.commands.len() {
+                1 => "**Or run this command:**\n```\n",
+                _ => "**Or run these commands:**\n```\n",
+            });
+            for command in &self.commands {
+                let _ = writeln!(text, "{}", command.command);
+            }
+            text.push_str("```");
+        }
+        self.resolution_steps = text.trim_end().to_string();
+        self
+    }
+}
+
 /// Result of conflict detection for all shortcuts
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ConflictDetectionResult {
@@ -34,126 +138,186 @@ pub struct ConflictDetectionResult {
     pub message: String,
 }
 
-/// Main entry point for conflict detection
-pub fn detect_shortcut_conflicts() -> ConflictDetectionResult {
-    let de = get_desktop_environment();
-    let conflicts = match de.as_str() {
-        "GNOME" => detect_gnome_conflicts(),
-        "Pop" | "Pop!_OS" => detect_pop_shell_conflicts(),
-        "COSMIC" => detect_cosmic_conflicts(),
-        "KDE Plasma" => detect_kde_conflicts(),
-        "i3" | "i3wm" => detect_i3_conflicts(),
-        "Sway" => detect_sway_conflicts(),
-        "Hyprland" => detect_hyprland_conflicts(),
-        "Cinnamon" => detect_cinnamon_conflicts(),
-        "XFCE" => detect_xfce_conflicts(),
-        _ => Vec::new(),
+/// Outcome of `auto_resolve_conflicts`
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ConflictResolutionReport {
+    /// Conflicts whose commands ran, as "owner (binding)"
+    pub resolved: Vec<String>,
+    /// Conflicts left alone because resolving them would remove the
+    /// owner's shortcut and destructive commands weren't allowed
+    pub skipped: Vec<String>,
+}
+
+/// Main entry point for conflict detection: looks for other uses of the
+/// clipboard history shortcuts in `bindings` and of the emoji picker's
+pub fn detect_shortcut_conflicts(bindings: &HistoryBindings) -> ConflictDetectionResult {
+    let desktop = environment::detect();
+    let targets = conflict_shortcuts(bindings);
+    let conflicts = desktop_conflicts(desktop.de, &targets);
+    conflict_result(&desktop.name, &targets, conflicts)
+}
+
+/// Other uses of `binding`, a shortcut the user is about to choose. On
+/// gsettings desktops every schema is searched, not only the keys known to
+/// take our default shortcuts.
+pub fn detect_binding_conflicts(binding: &KeyBinding) -> ConflictDetectionResult {
+    let desktop = environment::detect();
+    let targets = [candidate_shortcut(binding)];
+    let conflicts = match desktop.de {
+        DesktopEnvironment::Gnome | DesktopEnvironment::PopOs | DesktopEnvironment::Budgie => {
+            let mut conflicts = gsettings_scan_conflicts(&targets);
+            conflicts.extend(gnome_custom_shortcut_conflicts(&targets));
+            conflicts
+        }
+        DesktopEnvironment::Cinnamon | DesktopEnvironment::Mate => {
+            gsettings_scan_conflicts(&targets)
+        }
+        de => desktop_conflicts(de, &targets),
     };
+    conflict_result(&desktop.name, &targets, conflicts)
+}
+
+/// Runs the detector for desktop `de`; desktops without one have no conflicts
+fn desktop_conflicts(de: DesktopEnvironment, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    match de {
+        DesktopEnvironment::Gnome => detect_gnome_conflicts(targets),
+        DesktopEnvironment::Budgie => detect_budgie_conflicts(targets),
+        DesktopEnvironment::Enlightenment => detect_enlightenment_conflicts(targets),
+        DesktopEnvironment::PopOs => detect_pop_shell_conflicts(targets),
+        DesktopEnvironment::Cosmic => detect_cosmic_conflicts(targets),
+        DesktopEnvironment::KdePlasma => detect_kde_conflicts(targets),
+        DesktopEnvironment::I3 => detect_i3_conflicts(targets),
+        DesktopEnvironment::Sway => detect_sway_conflicts(targets),
+        DesktopEnvironment::Hyprland => detect_hyprland_conflicts(targets),
+        DesktopEnvironment::Awesome => detect_awesome_conflicts(targets),
+        DesktopEnvironment::Qtile => detect_qtile_conflicts(targets),
+        DesktopEnvironment::River => detect_river_conflicts(targets),
+        DesktopEnvironment::Wayfire => detect_wayfire_conflicts(targets),
+        DesktopEnvironment::Bspwm => detect_sxhkd_conflicts(targets),
+        DesktopEnvironment::Cinnamon => detect_cinnamon_conflicts(targets),
+        DesktopEnvironment::Xfce => detect_xfce_conflicts(targets),
+        DesktopEnvironment::Mate
+        | DesktopEnvironment::Lxde
+        | DesktopEnvironment::Lxqt
+        | DesktopEnvironment::Deepin
+        | DesktopEnvironment::Unknown => Vec::new(),
+    }
+}
 
+fn conflict_result(
+    de: &str,
+    targets: &[ShortcutConfig],
+    conflicts: Vec<ShortcutConflict>,
+) -> ConflictDetectionResult {
     // Only true if there are actual conflicts AND all of them can be auto-resolved
     let can_auto_resolve =
-        !conflicts.is_empty() && conflicts.iter().all(|c| c.resolution_command.is_some());
+        !conflicts.is_empty() && conflicts.iter().all(|c| !c.commands.is_empty());
     let message = if conflicts.is_empty() {
         "No shortcut conflicts detected.".to_string()
     } else {
         format!(
-            "{} shortcut conflict(s) detected that may prevent Super+V from working.",
-            conflicts.len()
+            "{} shortcut conflict(s) detected that may keep {} from working.",
+            conflicts.len(),
+            taken_bindings(targets, &conflicts).join(", ")
         )
     };
 
     ConflictDetectionResult {
-        desktop_environment: de,
+        desktop_environment: de.to_string(),
         conflicts,
         can_auto_resolve,
         message,
     }
 }
 
-/// Resolve all detected conflicts automatically where possible
-pub fn auto_resolve_conflicts() -> Result<Vec<String>, String> {
-    let result = detect_shortcut_conflicts();
-    let mut resolved = Vec::new();
+/// The bindings of the `targets` that `conflicts` take, as people write
+/// them (`Super+V`)
+fn taken_bindings(targets: &[ShortcutConfig], conflicts: &[ShortcutConflict]) -> Vec<String> {
+    let mut taken: Vec<String> = targets
+        .iter()
+        .filter(|target| conflicts.iter().any(|c| c.conflicts_with == target.name))
+        .map(|target| target.binding.label())
+        .collect();
+    taken.dedup();
+    taken
+}
 
-    for conflict in result.conflicts {
-        if let Some(cmd) = conflict.resolution_command {
-            match run_resolution_command(&cmd) {
-                Ok(_) => resolved.push(format!(
-                    "Resolved: {} ({})",
-                    conflict.owner, conflict.binding
-                )),
+/// Resolve all detected conflicts automatically where possible. Commands
+/// that take a shortcut away from its owner only run with
+/// `allow_destructive`. The old value of every gsettings key changed is
+/// recorded in the conflict journal first, so each change can be undone.
+pub fn auto_resolve_conflicts(
+    bindings: &HistoryBindings,
+    allow_destructive: bool,
+) -> Result<ConflictResolutionReport, String> {
+    let result = detect_shortcut_conflicts(bindings);
+    let journal = ConflictJournal::default();
+    let mut report = ConflictResolutionReport::default();
+
+    for conflict in &result.conflicts {
+        let name = format!("{} ({})", conflict.owner, conflict.binding);
+        for command in &conflict.commands {
+            if command.destructive && !allow_destructive {
+                report.skipped.push(name.clone());
+                continue;
+            }
+            let cmd = &command.command;
+            if let Some((schema, key)) = setting_target(cmd) {
+                let old_value = read_setting(schema, key).ok_or_else(|| {
+                    format!("Failed to read the current value of {} {}", schema, key)
+                })?;
+                journal
+                    .record(conflict, cmd, schema, key, &old_value)
+                    .map_err(|e| {
+                        format!(
+                            "Failed to record {} {} before changing it: {}",
+                            schema, key, e
+                        )
+                    })?;
+            }
+            match run_resolution_command(cmd) {
+                Ok(_) => report.resolved.push(name.clone()),
                 Err(e) => return Err(format!("Failed to resolve {}: {}", conflict.owner, e)),
             }
         }
     }
 
-    Ok(resolved)
+    // A conflict with several commands shows up once per command
+    report.resolved.dedup();
+    report.skipped.dedup();
+    Ok(report)
 }
 
-fn get_desktop_environment() -> String {
-    let xdg_current = env::var("XDG_CURRENT_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let xdg_session = env::var("XDG_SESSION_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let combined = format!("{} {}", xdg_current, xdg_session);
-
-    // Check for Pop!_OS specifically (uses pop:GNOME)
-    if combined.contains("pop") {
-        return "Pop".to_string();
-    }
-    if combined.contains("gnome") || combined.contains("unity") || combined.contains("pantheon") {
-        return "GNOME".to_string();
-    }
-    if combined.contains("cosmic") {
-        return "COSMIC".to_string();
-    }
-    if combined.contains("kde") || combined.contains("plasma") {
-        return "KDE Plasma".to_string();
-    }
-    if combined.contains("cinnamon") {
-        return "Cinnamon".to_string();
-    }
-    if combined.contains("xfce") {
-        return "XFCE".to_string();
-    }
-    // Tiling window managers
-    if combined.contains("i3") {
-        return "i3".to_string();
-    }
-    if combined.contains("sway") {
-        return "Sway".to_string();
-    }
-    if combined.contains("hyprland") {
-        return "Hyprland".to_string();
-    }
-
-    // Check running processes for tiling WMs (they often don't set XDG vars properly)
-    if is_process_running("i3") {
-        return "i3".to_string();
-    }
-    if is_process_running("sway") {
-        return "Sway".to_string();
-    }
-    if is_process_running("hyprland") || is_process_running("Hyprland") {
-        return "Hyprland".to_string();
+/// The setting a `gsettings set <schema> <key> <value>` or
+/// `dconf write <dir><key> <value>` command changes, as (schema, key) or
+/// (dconf directory, key)
+fn setting_target(command: &str) -> Option<(&str, &str)> {
+    let mut words = command.split_whitespace();
+    match (words.next(), words.next(), words.next(), words.next()) {
+        (Some("gsettings"), Some("set"), Some(schema), Some(key)) => Some((schema, key)),
+        (Some("dconf"), Some("write"), Some(path), Some(_)) => {
+            let split = path.rfind('/')? + 1;
+            Some((&path[..split], &path[split..]))
+        }
+        _ => None,
     }
-
-    xdg_current.to_uppercase()
 }
 
-fn is_process_running(name: &str) -> bool {
-    Command::new("pgrep")
-        .arg("-x")
-        .arg(name)
+/// The current value of a setting named like `setting_target`'s
+fn read_setting(schema: &str, key: &str) -> Option<String> {
+    if !schema.starts_with('/') {
+        return gsettings_get(schema, key);
+    }
+    host_command("dconf")
+        .args(["read", &format!("{}{}", schema, key)])
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
 }
 
 fn run_resolution_command(cmd: &str) -> Result<(), String> {
-    let output = Command::new("sh")
+    let output = host_command("sh")
         .arg("-c")
         .arg(cmd)
         .output()
@@ -167,7 +331,7 @@ fn run_resolution_command(cmd: &str) -> Result<(), String> {
 }
 
 fn command_exists(cmd: &str) -> bool {
-    Command::new("which")
+    host_command("which")
         .arg(cmd)
         .output()
         .map(|o| o.status.success())
@@ -178,7 +342,7 @@ fn gsettings_get(schema: &str, key: &str) -> Option<String> {
     if !command_exists("gsettings") {
         return None;
     }
-    Command::new("gsettings")
+    host_command("gsettings")
         .args(["get", schema, key])
         .output()
         .ok()
@@ -186,96 +350,607 @@ fn gsettings_get(schema: &str, key: &str) -> Option<String> {
         .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
 }
 
+/// A gsettings key holding a list of shortcuts, any of which may be ours
+struct GSettingsShortcut {
+    schema: &'static str,
+    key: &'static str,
+    /// What it does
+    action: &'static str,
+    owner: &'static str,
+    /// Where it is changed in the desktop's settings
+    settings_path: &'static [&'static str],
+    /// Move the action to Shift plus our keys instead of unbinding it
+    keep_with_shift: bool,
+}
+
+/// The shortcuts in a gsettings strv value (`['<Super>v', '<Super>n']`)
+fn gsettings_strv(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches("@as")
+        .trim()
+        .trim_matches(['[', ']'])
+        .split(',')
+        .map(|binding| binding.trim().trim_matches(['\'', '"']).to_string())
+        .filter(|binding| !binding.is_empty())
+        .collect()
+}
+
+/// Whether a gsettings strv value has exactly `binding`
+fn gsettings_has_binding(value: &str, binding: &KeyBinding) -> bool {
+    gsettings_strv(value)
+        .iter()
+        .any(|entry| KeyBinding::parse(entry).is_ok_and(|entry| entry == *binding))
+}
+
+/// `value` without `binding`, or with it moved to Shift+`binding`, written
+/// back as a gsettings strv
+fn gsettings_strv_without(value: &str, binding: &KeyBinding, keep_with_shift: bool) -> String {
+    let shifted = binding.with_modifier(Modifier::Shift).gnome();
+    let mut entries: Vec<String> = Vec::new();
+    for entry in gsettings_strv(value) {
+        let entry = match KeyBinding::parse(&entry) {
+            Ok(parsed) if parsed == *binding && keep_with_shift => shifted.clone(),
+            Ok(parsed) if parsed == *binding => continue,
+            _ => entry,
+        };
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    let quoted: Vec<String> = entries.iter().map(|e| format!("'{}'", e)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// The value that takes `binding` out of shortcut value `value`, moving
+/// it to Shift+`binding` with `keep_with_shift`. A few keys hold one
+/// shortcut as a plain string rather than a list.
+fn cleared_shortcut_value(value: &str, binding: &KeyBinding, keep_with_shift: bool) -> String {
+    if !value.trim_start().starts_with('\'') {
+        return gsettings_strv_without(value, binding, keep_with_shift);
+    }
+    match keep_with_shift {
+        true => format!("'{}'", binding.with_modifier(Modifier::Shift).gnome()),
+        false => "''".to_string(),
+    }
+}
+
+fn gsettings_set_command(schema: &str, key: &str, value: &str) -> String {
+    format!("gsettings set {} {} \"{}\"", schema, key, value)
+}
+
+/// A conflict with a key of a schema we know nothing about, resolved by
+/// hand in its owner's settings
+fn unknown_key_conflict(target: &ShortcutConfig, schema: &str, key: &str) -> ShortcutConflict {
+    let owner = schema_owner(schema);
+    ShortcutConflict::new(
+        target,
+        target.gnome_binding.clone(),
+        format!("{} ({})", key, schema),
+        owner.clone(),
+    )
+    .steps(vec![
+        format!("Open the keyboard shortcut settings of {}", owner),
+        format!("Find the shortcut using {}", target.gnome_binding),
+        "Change it to a different binding or clear it".to_string(),
+    ])
+}
+
+/// A conflict with shortcut key `schema key`, currently `value`. `write`
+/// makes the command that sets the key to a new value. Keys from one of
+/// our tables (`known`) are described by it, and keep their action on
+/// Shift where it says so; any other key is cleared.
+fn shortcut_key_conflict(
+    target: &ShortcutConfig,
+    schema: &str,
+    key: &str,
+    value: &str,
+    known: Option<&GSettingsShortcut>,
+    write: impl Fn(&str) -> String,
+) -> ShortcutConflict {
+    let keep_with_shift = known.is_some_and(|known| known.keep_with_shift);
+    let command = write(&cleared_shortcut_value(
+        value,
+        &target.binding,
+        keep_with_shift,
+    ));
+    let Some(known) = known else {
+        return unknown_key_conflict(target, schema, key).command(
+            format!("Clear {} ({})", key, schema),
+            command,
+            true,
+        );
+    };
+
+    let shifted = target.binding.with_modifier(Modifier::Shift).gnome();
+    let (change, description) = if known.keep_with_shift {
+        (
+            format!(
+                "Change {} to {} (or disable it)",
+                target.gnome_binding, shifted
+            ),
+            format!("Move \"{}\" to {}", known.action, shifted),
+        )
+    } else {
+        (
+            format!("Clear the {} binding", target.gnome_binding),
+            format!("Remove {} from \"{}\"", target.gnome_binding, known.action),
+        )
+    };
+    ShortcutConflict::new(
+        target,
+        target.gnome_binding.clone(),
+        known.action,
+        known.owner,
+    )
+    .in_settings(known.settings_path)
+    .steps(vec![format!("Find \"{}\"", known.action), change])
+    .command(description, command, !known.keep_with_shift)
+}
+
+/// Conflicts with the gsettings keys in `keys`, which can be set back
+/// with gsettings
+fn gsettings_conflicts<'a>(
+    keys: impl IntoIterator<Item = &'a GSettingsShortcut>,
+    targets: &[ShortcutConfig],
+) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for entry in keys {
+        let Some(value) = gsettings_get(entry.schema, entry.key) else {
+            continue;
+        };
+        for target in targets {
+            if !gsettings_has_binding(&value, &target.binding) {
+                continue;
+            }
+            conflicts.push(shortcut_key_conflict(
+                target,
+                entry.schema,
+                entry.key,
+                &value,
+                Some(entry),
+                |new| gsettings_set_command(entry.schema, entry.key, new),
+            ));
+        }
+    }
+    conflicts
+}
+
+/// Schema prefixes and who owns their keys, most specific first
+const SCHEMA_OWNERS: &[(&str, &str)] = &[
+    ("org.gnome.shell.extensions.dash-to-panel", "Dash to Panel"),
+    ("org.gnome.shell.extensions.dash-to-dock", "Dash to Dock"),
+    ("org.gnome.shell.extensions.gsconnect", "GSConnect"),
+    ("org.gnome.shell.extensions.pop-shell", "Pop Shell"),
+    ("org.gnome.shell.extensions", "GNOME Shell extension"),
+    ("org.gnome.shell", "GNOME Shell"),
+    ("org.gnome.settings-daemon", "GNOME Settings"),
+    ("org.gnome.desktop.wm", "GNOME Window Manager"),
+    ("org.gnome.mutter", "GNOME Window Manager"),
+    ("org.freedesktop.ibus", "IBus"),
+    ("com.solus-project", "Budgie"),
+    ("org.cinnamon", "Cinnamon"),
+    ("org.mate", "MATE"),
+];
+
+fn schema_owner(schema: &str) -> String {
+    SCHEMA_OWNERS
+        .iter()
+        .find(|(prefix, _)| schema.starts_with(prefix))
+        .map_or_else(|| schema.to_string(), |(_, owner)| owner.to_string())
+}
+
+/// Every key of every installed schema, defaults included, by searching
+/// `gsettings list-recursively`
+fn gsettings_scan_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    if !command_exists("gsettings") {
+        return Vec::new();
+    }
+    match host_command("gsettings").arg("list-recursively").output() {
+        Ok(output) if output.status.success() => {
+            gsettings_scan_conflicts_in(&String::from_utf8_lossy(&output.stdout), targets)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Keys in `gsettings list-recursively` output (`schema key value` lines)
+/// holding one of `targets`' keys
+fn gsettings_scan_conflicts_in(listing: &str, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for line in listing.lines() {
+        let mut parts = line.splitn(3, ' ');
+        let (Some(schema), Some(key), Some(value)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // Only shortcuts have modifiers in angle brackets
+        if !value.contains('<') {
+            continue;
+        }
+        for target in targets {
+            if !gsettings_has_binding(value, &target.binding) {
+                continue;
+            }
+            conflicts.push(shortcut_key_conflict(
+                target,
+                schema,
+                key,
+                value,
+                None,
+                |new| gsettings_set_command(schema, key, new),
+            ));
+        }
+    }
+    conflicts
+}
+
 // =============================================================================
 // GNOME Conflict Detection
 // =============================================================================
 
-fn detect_gnome_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+/// GNOME Shell and IBus keys that can hold one of our shortcuts. IBus binds
+/// Super+. to its emoji picker by default.
+const GNOME_SHORTCUTS: &[GSettingsShortcut] = &[
+    GSettingsShortcut {
+        schema: "org.gnome.shell.keybindings",
+        key: "toggle-message-tray",
+        action: "Open Notification Center / Message Tray",
+        owner: "GNOME Shell",
+        settings_path: &["Settings", "Keyboard", "Keyboard Shortcuts"],
+        keep_with_shift: true,
+    },
+    GSettingsShortcut {
+        schema: "org.gnome.shell.keybindings",
+        key: "toggle-quick-settings",
+        action: "Toggle Quick Settings",
+        owner: "GNOME Shell",
+        settings_path: &["Settings", "Keyboard", "Keyboard Shortcuts"],
+        keep_with_shift: false,
+    },
+    GSettingsShortcut {
+        schema: "org.freedesktop.ibus.panel.emoji",
+        key: "hotkey",
+        action: "Emoji Choice",
+        owner: "IBus",
+        settings_path: &["IBus Preferences (ibus-setup)", "Emoji"],
+        keep_with_shift: false,
+    },
+    GSettingsShortcut {
+        schema: "org.freedesktop.ibus.panel.emoji",
+        key: "unicode-hotkey",
+        action: "Unicode Code Point",
+        owner: "IBus",
+        settings_path: &["IBus Preferences (ibus-setup)", "Emoji"],
+        keep_with_shift: false,
+    },
+];
+
+const MEDIA_KEYS_SCHEMA: &str = "org.gnome.settings-daemon.plugins.media-keys";
+
+fn detect_gnome_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let conflicts = gnome_based_conflicts(&[GNOME_SHORTCUTS], targets);
+    if session::gnome_shell_version().is_some_and(|major| major < 41) {
+        return with_legacy_gnome_settings(conflicts);
+    }
+    conflicts
+}
 
-    // Check for notification center shortcut (toggle-message-tray)
-    if let Some(binding) = gsettings_get("org.gnome.shell.keybindings", "toggle-message-tray") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Open Notification Center / Message Tray".to_string(),
-                owner: "GNOME Shell".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super><Shift>v']\"".to_string()
-                ),
-                resolution_steps: r#"**To resolve manually:**
-1. Open Settings → Keyboard → Keyboard Shortcuts
-2. Search for "Notification" or "Message Tray"
-3. Change Super+V to Super+Shift+V (or disable it)
-
-**Or run this command:**
-```
-gsettings set org.gnome.shell.keybindings toggle-message-tray "['<Super><Shift>v']"
-```"#.to_string(),
+/// GNOME 40 and older had a Keyboard Shortcuts panel of its own rather than
+/// a page in the Keyboard panel
+fn with_legacy_gnome_settings(conflicts: Vec<ShortcutConflict>) -> Vec<ShortcutConflict> {
+    conflicts
+        .into_iter()
+        .map(|conflict| {
+            if conflict
+                .settings_path
+                .starts_with(&["Settings".to_string(), "Keyboard".to_string()])
+            {
+                conflict.in_settings(&["Settings", "Keyboard Shortcuts"])
+            } else {
+                conflict
+            }
+        })
+        .collect()
+}
+
+/// Conflicts on GNOME and the desktops built on it, whose own keys are in
+/// `tables`. Every shortcut in the user's dconf database is searched, in
+/// any schema or extension; without dconf only the table keys and custom
+/// shortcuts are checked.
+fn gnome_based_conflicts(
+    tables: &[&'static [GSettingsShortcut]],
+    targets: &[ShortcutConfig],
+) -> Vec<ShortcutConflict> {
+    let known: Vec<&GSettingsShortcut> = tables.iter().flat_map(|table| table.iter()).collect();
+    let Some(dump) = dconf_dump() else {
+        let mut conflicts = gsettings_conflicts(known.iter().copied(), targets);
+        conflicts.extend(gnome_custom_shortcut_conflicts(targets));
+        return conflicts;
+    };
+    let mut conflicts = dconf_conflicts_in(&dump, &known, targets);
+    // The dump only holds keys the user changed, so table keys still at
+    // their default (Super+V for the message tray) are read with gsettings
+    conflicts.extend(gsettings_conflicts(keys_at_default(&known, &dump), targets));
+    conflicts
+}
+
+/// The keys of `known` that aren't in a dconf dump
+fn keys_at_default<'a>(
+    known: &[&'a GSettingsShortcut],
+    dump: &[DconfKey],
+) -> Vec<&'a GSettingsShortcut> {
+    known
+        .iter()
+        .copied()
+        .filter(|entry| {
+            !dump
+                .iter()
+                .any(|key| key.key == entry.key && key.schema() == entry.schema)
+        })
+        .collect()
+}
+
+/// One key from `dconf dump` output
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DconfKey {
+    /// The key's directory, with a slash at each end
+    /// (`/org/gnome/shell/keybindings/`)
+    dir: String,
+    key: String,
+    /// The value in GVariant text format (`['<Super>v']`)
+    value: String,
+}
+
+impl DconfKey {
+    /// The schema of the key's directory. Relocatable schemas, like custom
+    /// shortcuts, get their path instead, which still finds their owner.
+    fn schema(&self) -> String {
+        self.dir.trim_matches('/').replace('/', ".")
+    }
+}
+
+/// The keys in `dconf dump` output: keyfile groups named after directories
+/// (`[org/gnome/shell/keybindings]`) holding `key=value` lines
+fn parse_dconf_dump(dump: &str) -> Vec<DconfKey> {
+    let mut keys = Vec::new();
+    let mut dir: Option<String> = None;
+    for line in dump.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let group = group.trim_matches('/');
+            dir = Some(match group {
+                "" => "/".to_string(),
+                _ => format!("/{}/", group),
             });
+            continue;
         }
+        let (Some(dir), Some((key, value))) = (&dir, line.split_once('=')) else {
+            continue;
+        };
+        keys.push(DconfKey {
+            dir: dir.clone(),
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        });
     }
+    keys
+}
 
-    // Check for Clipboard shortcut in GNOME 45+ (if applicable)
-    if let Some(binding) = gsettings_get("org.gnome.shell.keybindings", "toggle-quick-settings") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Toggle Quick Settings".to_string(),
-                owner: "GNOME Shell".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.gnome.shell.keybindings toggle-quick-settings \"[]\""
-                        .to_string(),
-                ),
-                resolution_steps:
-                    "Disable the Quick Settings shortcut in GNOME Settings → Keyboard → Shortcuts"
-                        .to_string(),
-            });
+/// The user's whole dconf database
+fn dconf_dump() -> Option<Vec<DconfKey>> {
+    if !command_exists("dconf") {
+        return None;
+    }
+    host_command("dconf")
+        .args(["dump", "/"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_dconf_dump(&String::from_utf8_lossy(&o.stdout)))
+}
+
+/// Whether a GVariant value has `binding` in any of its strings, so lists
+/// and dictionaries of shortcuts count too
+fn gvariant_has_binding(value: &str, binding: &KeyBinding) -> bool {
+    value
+        .split('\'')
+        .skip(1)
+        .step_by(2)
+        .any(|text| KeyBinding::parse(text).is_ok_and(|parsed| parsed == *binding))
+}
+
+/// Keys in a dconf dump holding one of `targets`' keys. Keys in `known`
+/// are described by their table; others are cleared with `dconf write`.
+fn dconf_conflicts_in(
+    dump: &[DconfKey],
+    known: &[&GSettingsShortcut],
+    targets: &[ShortcutConfig],
+) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for entry in dump.iter().filter(|key| key.value.contains('<')) {
+        let siblings = || dump.iter().filter(|key| key.dir == entry.dir);
+        // Skip our own custom shortcuts
+        if siblings().any(|key| key.value.contains("penguinclip")) {
+            continue;
+        }
+        let schema = entry.schema();
+        let write = |value: &str| format!("dconf write {}{} \"{}\"", entry.dir, entry.key, value);
+        for target in targets {
+            if !gvariant_has_binding(&entry.value, &target.binding) {
+                continue;
+            }
+            if entry.dir.contains("/custom-keybindings/") {
+                let name = siblings()
+                    .find(|key| key.key == "name")
+                    .map(|key| key.value.trim_matches('\''))
+                    .unwrap_or_default();
+                conflicts.push(custom_shortcut_conflict(target, name).command(
+                    format!("Clear the binding of \"{}\"", name),
+                    write("''"),
+                    true,
+                ));
+            } else if entry.value.starts_with('{') {
+                // Dictionaries name their shortcuts; leave those to their owner
+                conflicts.push(unknown_key_conflict(target, &schema, &entry.key));
+            } else {
+                let known = known
+                    .iter()
+                    .copied()
+                    .find(|known| known.schema == schema && known.key == entry.key);
+                conflicts.push(shortcut_key_conflict(
+                    target,
+                    &schema,
+                    &entry.key,
+                    &entry.value,
+                    known,
+                    write,
+                ));
+            }
         }
     }
+    conflicts
+}
 
+/// Custom shortcuts from GNOME Settings, which gnome-settings-daemon runs,
+/// that take one of our keys
+fn gnome_custom_shortcut_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Some(paths) = gsettings_get(MEDIA_KEYS_SCHEMA, "custom-keybindings") else {
+        return Vec::new();
+    };
+
+    let mut conflicts = Vec::new();
+    for path in gsettings_strv(&paths) {
+        let schema = format!("{}.custom-keybinding:{}", MEDIA_KEYS_SCHEMA, path);
+        let get = |key: &str| gsettings_get(&schema, key).unwrap_or_default();
+        let command = get("command");
+        if command.contains("penguinclip") {
+            continue;
+        }
+        let binding = get("binding");
+        let name = get("name").trim_matches('\'').to_string();
+        for target in targets {
+            if !gsettings_has_binding(&binding, &target.binding) {
+                continue;
+            }
+            conflicts.push(custom_shortcut_conflict(target, &name));
+        }
+    }
     conflicts
 }
 
+/// A GNOME custom shortcut called `name` on `target`'s keys
+fn custom_shortcut_conflict(target: &ShortcutConfig, name: &str) -> ShortcutConflict {
+    ShortcutConflict::new(
+        target,
+        target.gnome_binding.clone(),
+        name,
+        "GNOME Custom Shortcut",
+    )
+    .in_settings(&[
+        "Settings",
+        "Keyboard",
+        "View and Customize Shortcuts",
+        "Custom Shortcuts",
+    ])
+    .steps(vec![
+        format!("Find \"{}\"", name),
+        format!("Change its {} binding or remove it", target.gnome_binding),
+    ])
+}
+
 // =============================================================================
 // Pop!_OS / Pop Shell Conflict Detection
 // =============================================================================
 
-fn detect_pop_shell_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+const POP_SHELL_SHORTCUTS: &[GSettingsShortcut] = &[GSettingsShortcut {
+    schema: "org.gnome.shell.extensions.pop-shell",
+    key: "tile-enter",
+    action: "Enter Tiling Mode",
+    owner: "Pop Shell",
+    settings_path: &["Pop!_OS Settings", "Keyboard", "Customize Shortcuts"],
+    keep_with_shift: true,
+}];
 
+fn detect_pop_shell_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
     // Pop Shell uses org.gnome.shell.extensions.pop-shell for some shortcuts
     // Also inherits GNOME's notification tray shortcut
+    gnome_based_conflicts(&[GNOME_SHORTCUTS, POP_SHELL_SHORTCUTS], targets)
+}
 
-    // Check GNOME's notification center first
-    conflicts.extend(detect_gnome_conflicts());
-
-    // Check Pop Shell specific shortcuts
-    if let Some(binding) = gsettings_get("org.gnome.shell.extensions.pop-shell", "tile-enter") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Enter Tiling Mode".to_string(),
-                owner: "Pop Shell".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.gnome.shell.extensions.pop-shell tile-enter \"['<Super><Shift>v']\"".to_string()
-                ),
-                resolution_steps: r#"**To resolve manually:**
-1. Open Pop!_OS Settings → Keyboard → Customize Shortcuts
-2. Find "Pop Shell: Enter Tile Mode"
-3. Change it to a different binding
-
-**Or run:**
-```
-gsettings set org.gnome.shell.extensions.pop-shell tile-enter "['<Super><Shift>v']"
-```"#.to_string(),
-            });
+// =============================================================================
+// Budgie Conflict Detection
+// =============================================================================
+
+/// Budgie window manager keys that some distros put on Super+V
+const BUDGIE_SHORTCUTS: &[GSettingsShortcut] = &[
+    GSettingsShortcut {
+        schema: "com.solus-project.budgie-wm",
+        key: "toggle-raven",
+        action: "Toggle Raven sidebar",
+        owner: "Budgie",
+        settings_path: &["Budgie Desktop Settings", "Raven"],
+        keep_with_shift: true,
+    },
+    GSettingsShortcut {
+        schema: "com.solus-project.budgie-wm",
+        key: "toggle-notifications",
+        action: "Open Raven notifications",
+        owner: "Budgie",
+        settings_path: &["Budgie Desktop Settings", "Raven"],
+        keep_with_shift: true,
+    },
+    GSettingsShortcut {
+        schema: "com.solus-project.budgie-wm",
+        key: "clear-notifications",
+        action: "Clear notifications",
+        owner: "Budgie",
+        settings_path: &["Budgie Desktop Settings", "Raven"],
+        keep_with_shift: true,
+    },
+];
+
+fn detect_budgie_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    // Budgie still runs GNOME Shell-style keybindings for some actions
+    gnome_based_conflicts(&[GNOME_SHORTCUTS, BUDGIE_SHORTCUTS], targets)
+}
+
+// =============================================================================
+// Enlightenment Conflict Detection
+// =============================================================================
+
+fn detect_enlightenment_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let bindings = enlightenment_bindings().unwrap_or_default();
+    let mut conflicts = Vec::new();
+    for target in targets {
+        for binding in &bindings {
+            if !binding.binds(&target.binding) || binding.params.contains("penguinclip") {
+                continue;
+            }
+            conflicts.push(
+                ShortcutConflict::new(
+                    target,
+                    target.kde_binding.replace("Meta", "Win"),
+                    if binding.params.is_empty() {
+                        binding.action.clone()
+                    } else {
+                        format!("{} {}", binding.action, binding.params)
+                    },
+                    "Enlightenment",
+                )
+                .in_settings(&["Settings", "Input", "Key Bindings"])
+                .steps(vec![
+                    format!(
+                        "Find the {} binding",
+                        target.kde_binding.replace("Meta", "Win")
+                    ),
+                    "Change it to a different key or delete it".to_string(),
+                ]),
+            );
         }
     }
-
     conflicts
 }
 
@@ -283,58 +958,34 @@ gsettings set org.gnome.shell.extensions.pop-shell tile-enter "['<Super><Shift>v
 // COSMIC Desktop Conflict Detection
 // =============================================================================
 
-fn detect_cosmic_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
-
-    let home = match env::var("HOME") {
-        Ok(h) => h,
-        Err(_) => return conflicts,
+fn detect_cosmic_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Some(config) = xdg_config_dir() else {
+        return Vec::new();
     };
 
     // COSMIC stores shortcuts in ~/.config/cosmic/com.system76.CosmicSettings.Shortcuts/
-    let shortcuts_path =
-        PathBuf::from(&home).join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom");
-
-    if let Ok(content) = fs::read_to_string(&shortcuts_path) {
-        // Check for Super+V bindings
-        if content.to_lowercase().contains("super")
-            && content.to_lowercase().contains("\"v\"")
-            && !content.contains("penguinclip")
-        {
-            conflicts.push(ShortcutConflict {
-                binding: "Super+V".to_string(),
-                current_action: "Unknown COSMIC shortcut".to_string(),
-                owner: "COSMIC Desktop".to_string(),
-                resolution_command: None,
-                resolution_steps: r#"**To resolve manually:**
-1. Open COSMIC Settings → Keyboard → Shortcuts
-2. Find any shortcut using Super+V
-3. Change it to a different binding or remove it"#
-                    .to_string(),
-            });
-        }
-    }
-
-    // Also check system-level COSMIC shortcuts
-    let system_shortcuts = PathBuf::from(&home)
-        .join(".config/cosmic/com.system76.CosmicSettings.Shortcuts/v1/system_actions");
+    let shortcuts_path = config.join("cosmic/com.system76.CosmicSettings.Shortcuts/v1/custom");
+    let Ok(content) = fs::read_to_string(&shortcuts_path) else {
+        return Vec::new();
+    };
+    cosmic_conflicts_in(&content, targets)
+}
 
-    if let Ok(content) = fs::read_to_string(&system_shortcuts) {
-        if content.to_lowercase().contains("super") && content.to_lowercase().contains("\"v\"") {
-            conflicts.push(ShortcutConflict {
-                binding: "Super+V".to_string(),
-                current_action: "COSMIC System Action".to_string(),
-                owner: "COSMIC Desktop".to_string(),
-                resolution_command: None,
-                resolution_steps: r#"**COSMIC System Shortcut Conflict:**
-1. Open COSMIC Settings → Keyboard → Shortcuts → System
-2. Find the Super+V binding
-3. Change or disable it"#
-                    .to_string(),
-            });
+fn cosmic_conflicts_in(content: &str, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for target in targets {
+        let binding = target.binding.label();
+        for action in cosmic_actions_bound_to(content, target).unwrap_or_default() {
+            conflicts.push(
+                ShortcutConflict::new(target, binding.clone(), action, "COSMIC Desktop")
+                    .in_settings(&["COSMIC Settings", "Keyboard", "Shortcuts"])
+                    .steps(vec![
+                        format!("Find the shortcut using {}", binding),
+                        "Change it to a different binding or remove it".to_string(),
+                    ]),
+            );
         }
     }
-
     conflicts
 }
 
@@ -342,67 +993,45 @@ fn detect_cosmic_conflicts() -> Vec<ShortcutConflict> {
 // KDE Plasma Conflict Detection
 // =============================================================================
 
-fn detect_kde_conflicts() -> Vec<ShortcutConflict> {
+fn detect_kde_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
     let mut conflicts = Vec::new();
 
-    let home = match env::var("HOME") {
-        Ok(h) => h,
-        Err(_) => return conflicts,
+    let Some(config) = xdg_config_dir() else {
+        return conflicts;
     };
 
-    // Check kglobalshortcutsrc for Meta+V bindings
-    let shortcuts_path = PathBuf::from(&home).join(".config/kglobalshortcutsrc");
+    // Check kglobalshortcutsrc for bindings of our keys
+    let shortcuts_path = config.join("kglobalshortcutsrc");
 
     if let Ok(content) = fs::read_to_string(&shortcuts_path) {
-        for line in content.lines() {
-            if line.contains("Meta+V") || line.contains("Meta+v") {
-                // Try to extract the action name
-                if let Some(action) = extract_kde_action(&content, line) {
-                    // Skip if it's our own shortcut
-                    if action.contains("clipboard-history") || action.contains("win11") {
-                        continue;
-                    }
-
-                    conflicts.push(ShortcutConflict {
-                        binding: "Meta+V".to_string(),
-                        current_action: action.clone(),
-                        owner: "KDE Plasma".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**To resolve manually:**
-1. Open System Settings → Shortcuts → Global Shortcuts
-2. Find "{}"
-3. Change or clear the Meta+V binding
-
-**Alternative:** Use the search function to find "Meta+V" bindings"#,
-                            action
-                        ),
-                    });
-                }
-            }
-        }
+        conflicts.extend(kde_conflicts_in(&content, targets));
     }
 
     // Check for Klipper (KDE's built-in clipboard manager)
-    let klipper_path = PathBuf::from(&home).join(".config/klipperrc");
+    let klipper_path = config.join("klipperrc");
     if klipper_path.exists() {
         if let Ok(content) = fs::read_to_string(&klipper_path) {
-            if content.contains("Meta+V") {
-                conflicts.push(ShortcutConflict {
-                    binding: "Meta+V".to_string(),
-                    current_action: "Klipper Clipboard History".to_string(),
-                    owner: "Klipper".to_string(),
-                    resolution_command: None,
-                    resolution_steps: r#"**Klipper Conflict:**
-KDE's built-in clipboard manager (Klipper) may use Meta+V.
-
-1. Right-click the Klipper icon in the system tray
-2. Click "Configure Klipper"
-3. Go to "Shortcuts" and change or disable the shortcut
-
-**Alternatively:** Disable Klipper entirely if you prefer this app."#
-                        .to_string(),
-                });
+            for target in targets {
+                if !content.contains(&target.kde_binding) {
+                    continue;
+                }
+                conflicts.push(
+                    ShortcutConflict::new(
+                        target,
+                        target.kde_binding.clone(),
+                        "Klipper Clipboard History",
+                        "Klipper",
+                    )
+                    .steps(vec![
+                        "Right-click the Klipper icon in the system tray".to_string(),
+                        "Click \"Configure Klipper\"".to_string(),
+                        format!(
+                            "Go to \"Shortcuts\" and change or disable {}",
+                            target.kde_binding
+                        ),
+                        "Or disable Klipper entirely if you prefer this app".to_string(),
+                    ]),
+                );
             }
         }
     }
@@ -410,54 +1039,107 @@ KDE's built-in clipboard manager (Klipper) may use Meta+V.
     conflicts
 }
 
-fn extract_kde_action(content: &str, target_line: &str) -> Option<String> {
-    // KDE shortcut format: action=shortcut,default,description
-    // We need to find the section header [Component] above the line
-    let lines: Vec<&str> = content.lines().collect();
-    let mut current_section = String::new();
+fn kde_conflicts_in(content: &str, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for target in targets {
+        let binding = &target.kde_binding;
+        for action in kde_actions_bound_to(content, binding) {
+            conflicts.push(
+                ShortcutConflict::new(target, binding.clone(), action.clone(), "KDE Plasma")
+                    .in_settings(&["System Settings", "Shortcuts", "Global Shortcuts"])
+                    .steps(vec![
+                        format!("Find \"{}\"", action),
+                        format!("Change or clear the {} binding", binding),
+                        format!("If it is hard to find, search for \"{}\"", binding),
+                    ]),
+            );
+        }
+    }
+    conflicts
+}
 
-    for line in lines {
-        if line.starts_with('[') && line.ends_with(']') {
-            current_section = line[1..line.len() - 1].to_string();
+/// The actions in a kglobalshortcutsrc that `binding` triggers, skipping
+/// our own. Components list `action=active,default,description`, while
+/// Plasma 6 launchers live in `[services][app.desktop]` groups that hold the
+/// bare shortcut (`_launch=Meta+V`). Plasma 6 separates several shortcuts
+/// for one action with tabs.
+fn kde_actions_bound_to(content: &str, binding: &str) -> Vec<String> {
+    let mut actions = Vec::new();
+    let mut component = "";
+    let mut service: Option<&str> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            service = group.strip_prefix("services][");
+            component = service.unwrap_or(group);
+            continue;
         }
-        if line == target_line {
-            // Extract action name from the line
-            if let Some(eq_pos) = line.find('=') {
-                let action_part = &line[..eq_pos];
-                return Some(format!("{}: {}", current_section, action_part));
-            }
-            return Some(current_section);
+        let Some((action, value)) = line.split_once('=') else {
+            continue;
+        };
+        if action.starts_with("_k_") {
+            continue;
         }
+        let active = match service {
+            Some(_) => value,
+            None => value.split(',').next().unwrap_or(""),
+        };
+        if !active
+            .split('\t')
+            .any(|key| key.trim().eq_ignore_ascii_case(binding))
+        {
+            continue;
+        }
+        // Skip if it's our own shortcut
+        let ours = ["penguinclip", "clipboard-history", "win11"];
+        if ours.iter().any(|name| component.contains(name)) {
+            continue;
+        }
+        actions.push(match service {
+            Some(desktop_file) if action == "_launch" => {
+                desktop_file.trim_end_matches(".desktop").to_string()
+            }
+            _ => format!("{}: {}", component, action),
+        });
     }
-    None
+    actions
 }
 
 // =============================================================================
 // i3 Window Manager Conflict Detection
 // =============================================================================
 
-fn detect_i3_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
-
-    let config_paths = get_i3_config_paths();
+fn detect_i3_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    i3_conflicts_in(&get_i3_config_paths(), targets)
+}
 
-    for path in config_paths {
-        if let Ok(content) = fs::read_to_string(&path) {
-            // Look for bindsym $mod+v or bindsym Mod4+v
-            for line in content.lines() {
-                let line_lower = line.to_lowercase().trim().to_string();
+/// Whether an i3 or Sway config line binds `target`'s keys to something else
+fn is_i3_conflict(line: &str, target: &ShortcutConfig) -> bool {
+    let line_lower = line.to_lowercase().trim().to_string();
+    // Skip comments
+    if line_lower.starts_with('#') {
+        return false;
+    }
+    (line_lower.starts_with("bindsym") || line_lower.starts_with("bindcode"))
+        && has_i3_binding(&line_lower, &target.i3_binding.to_lowercase())
+        && !line_lower.contains("penguinclip")
+        && !line_lower.contains("clipboard-history")
+        && !line_lower.contains("win11")
+}
 
-                // Skip comments
-                if line_lower.starts_with('#') {
-                    continue;
-                }
+/// Conflicts in the given i3 configs and the files they `include`
+fn i3_conflicts_in(config_paths: &[PathBuf], targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
 
-                // Check for Super+V bindings (Mod4 is typically Super)
-                if (line_lower.contains("bindsym") || line_lower.contains("bindcode"))
-                    && (line_lower.contains("mod4+v") || line_lower.contains("$mod+v"))
-                    && !line_lower.contains("clipboard-history")
-                    && !line_lower.contains("win11")
-                {
+    for path in config_files_with_includes(config_paths) {
+        if let Ok(content) = fs::read_to_string(&path) {
+            // Look for bindsym $mod+v or bindsym Mod4+v, and our other keys
+            for (line, target) in content
+                .lines()
+                .flat_map(|line| targets.iter().map(move |target| (line, target)))
+            {
+                if is_i3_conflict(line, target) {
                     // Extract the action
                     let action = line
                         .split_whitespace()
@@ -465,34 +1147,29 @@ fn detect_i3_conflicts() -> Vec<ShortcutConflict> {
                         .collect::<Vec<_>>()
                         .join(" ");
 
-                    conflicts.push(ShortcutConflict {
-                        binding: "$mod+v / Mod4+v".to_string(),
-                        current_action: if action.is_empty() {
-                            "Unknown action".to_string()
-                        } else {
-                            action
-                        },
-                        owner: "i3 config".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**i3 Config Conflict:**
-Found in: {}
-
-**To resolve:**
-1. Edit your i3 config: `{}`
-2. Find the line with `bindsym $mod+v` or `bindsym Mod4+v`
-3. Change it to a different binding or comment it out
-
-**Then add:**
-```
-bindsym $mod+v exec penguinclip
-```
-
-4. Reload i3: Press $mod+Shift+r"#,
-                            path.display(),
-                            path.display()
-                        ),
-                    });
+                    conflicts.push(
+                        ShortcutConflict::new(
+                            target,
+                            target.i3_binding.clone(),
+                            if action.is_empty() {
+                                "Unknown action".to_string()
+                            } else {
+                                action
+                            },
+                            "i3 config",
+                        )
+                        .steps(vec![
+                            format!("Edit your i3 config: {}", path.display()),
+                            format!("Find the line with \"bindsym {}\"", target.i3_binding),
+                            "Change it to a different binding or comment it out".to_string(),
+                            format!(
+                                "Add \"bindsym {} exec {}\"",
+                                target.i3_binding,
+                                target.full_command()
+                            ),
+                            "Reload i3: Press $mod+Shift+r".to_string(),
+                        ]),
+                    );
                 }
             }
         }
@@ -502,71 +1179,68 @@ bindsym $mod+v exec penguinclip
 }
 
 fn get_i3_config_paths() -> Vec<PathBuf> {
-    let home = env::var("HOME").unwrap_or_default();
-    vec![
-        PathBuf::from(&home).join(".config/i3/config"),
-        PathBuf::from(&home).join(".i3/config"),
-        PathBuf::from("/etc/i3/config"),
-    ]
+    let mut paths: Vec<PathBuf> = xdg_config_dir()
+        .map(|config| config.join("i3/config"))
+        .into_iter()
+        .collect();
+    if let Ok(home) = env::var("HOME") {
+        paths.push(PathBuf::from(home).join(".i3/config"));
+    }
+    paths.push(PathBuf::from("/etc/i3/config"));
+    paths
 }
 
 // =============================================================================
 // Sway Window Manager Conflict Detection
 // =============================================================================
 
-fn detect_sway_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+fn detect_sway_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    sway_conflicts_in(&get_sway_config_paths(), targets)
+}
 
-    let config_paths = get_sway_config_paths();
+/// Conflicts in the given Sway configs and the files they `include`
+fn sway_conflicts_in(
+    config_paths: &[PathBuf],
+    targets: &[ShortcutConfig],
+) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
 
-    for path in config_paths {
+    for path in config_files_with_includes(config_paths) {
         if let Ok(content) = fs::read_to_string(&path) {
-            for line in content.lines() {
-                let line_lower = line.to_lowercase().trim().to_string();
-
-                if line_lower.starts_with('#') {
-                    continue;
-                }
-
-                if (line_lower.contains("bindsym") || line_lower.contains("bindcode"))
-                    && (line_lower.contains("mod4+v") || line_lower.contains("$mod+v"))
-                    && !line_lower.contains("clipboard-history")
-                    && !line_lower.contains("win11")
-                {
+            for (line, target) in content
+                .lines()
+                .flat_map(|line| targets.iter().map(move |target| (line, target)))
+            {
+                if is_i3_conflict(line, target) {
                     let action = line
                         .split_whitespace()
                         .skip(2)
                         .collect::<Vec<_>>()
                         .join(" ");
 
-                    conflicts.push(ShortcutConflict {
-                        binding: "$mod+v / Mod4+v".to_string(),
-                        current_action: if action.is_empty() {
-                            "Unknown action".to_string()
-                        } else {
-                            action
-                        },
-                        owner: "Sway config".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**Sway Config Conflict:**
-Found in: {}
-
-**To resolve:**
-1. Edit your Sway config: `{}`
-2. Find the line with `bindsym $mod+v`
-3. Change it to a different binding or comment it out
-
-**Then add:**
-```
-bindsym $mod+v exec penguinclip
-```
-
-4. Reload Sway: Press $mod+Shift+c"#,
-                            path.display(),
-                            path.display()
-                        ),
-                    });
+                    conflicts.push(
+                        ShortcutConflict::new(
+                            target,
+                            target.sway_binding.clone(),
+                            if action.is_empty() {
+                                "Unknown action".to_string()
+                            } else {
+                                action
+                            },
+                            "Sway config",
+                        )
+                        .steps(vec![
+                            format!("Edit your Sway config: {}", path.display()),
+                            format!("Find the line with \"bindsym {}\"", target.sway_binding),
+                            "Change it to a different binding or comment it out".to_string(),
+                            format!(
+                                "Add \"bindsym {} exec {}\"",
+                                target.sway_binding,
+                                target.full_command()
+                            ),
+                            "Reload Sway: Press $mod+Shift+c".to_string(),
+                        ]),
+                    );
                 }
             }
         }
@@ -576,26 +1250,37 @@ bindsym $mod+v exec penguinclip
 }
 
 fn get_sway_config_paths() -> Vec<PathBuf> {
-    let home = env::var("HOME").unwrap_or_default();
-    vec![
-        PathBuf::from(&home).join(".config/sway/config"),
-        PathBuf::from(&home).join(".sway/config"),
-        PathBuf::from("/etc/sway/config"),
-    ]
+    let mut paths: Vec<PathBuf> = xdg_config_dir()
+        .map(|config| config.join("sway/config"))
+        .into_iter()
+        .collect();
+    if let Ok(home) = env::var("HOME") {
+        paths.push(PathBuf::from(home).join(".sway/config"));
+    }
+    paths.push(PathBuf::from("/etc/sway/config"));
+    paths
 }
 
 // =============================================================================
 // Hyprland Conflict Detection
 // =============================================================================
 
-fn detect_hyprland_conflicts() -> Vec<ShortcutConflict> {
+fn detect_hyprland_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    // The running compositor also knows binds from sourced files
+    if let Some(binds) = hyprland_runtime_binds() {
+        return hyprland_runtime_conflicts(&binds, targets);
+    }
+
     let mut conflicts = Vec::new();
 
     let config_paths = get_hyprland_config_paths();
 
     for path in config_paths {
         if let Ok(content) = fs::read_to_string(&path) {
-            for line in content.lines() {
+            for (line, target) in content
+                .lines()
+                .flat_map(|line| targets.iter().map(move |target| (line, target)))
+            {
                 let line_lower = line.to_lowercase().trim().to_string();
 
                 if line_lower.starts_with('#') {
@@ -603,9 +1288,8 @@ fn detect_hyprland_conflicts() -> Vec<ShortcutConflict> {
                 }
 
                 // Hyprland uses bind = SUPER, V, exec, command
-                if line_lower.starts_with("bind")
-                    && line_lower.contains("super")
-                    && (line_lower.contains(", v,") || line_lower.contains(",v,"))
+                if has_hyprland_binding(&line_lower, &target.hyprland_binding)
+                    && !line_lower.contains("penguinclip")
                     && !line_lower.contains("clipboard-history")
                     && !line_lower.contains("win11")
                 {
@@ -617,30 +1301,28 @@ fn detect_hyprland_conflicts() -> Vec<ShortcutConflict> {
                         "Unknown action".to_string()
                     };
 
-                    conflicts.push(ShortcutConflict {
-                        binding: "SUPER, V".to_string(),
-                        current_action: action,
-                        owner: "Hyprland config".to_string(),
-                        resolution_command: None,
-                        resolution_steps: format!(
-                            r#"**Hyprland Config Conflict:**
-Found in: {}
-
-**To resolve:**
-1. Edit your Hyprland config: `{}`
-2. Find the line with `bind = SUPER, V, ...`
-3. Change it to a different binding or comment it out
-
-**Then add:**
-```
-bind = SUPER, V, exec, penguinclip
-```
-
-4. The config auto-reloads, or reload manually"#,
-                            path.display(),
-                            path.display()
-                        ),
-                    });
+                    conflicts.push(
+                        ShortcutConflict::new(
+                            target,
+                            target.hyprland_binding.clone(),
+                            action,
+                            "Hyprland config",
+                        )
+                        .steps(vec![
+                            format!("Edit your Hyprland config: {}", path.display()),
+                            format!(
+                                "Find the line with \"bind = {}, ...\"",
+                                target.hyprland_binding
+                            ),
+                            "Change it to a different binding or comment it out".to_string(),
+                            format!(
+                                "Add \"bind = {}, exec, {}\"",
+                                target.hyprland_binding,
+                                target.full_command()
+                            ),
+                            "Hyprland reloads the config automatically".to_string(),
+                        ]),
+                    );
                 }
             }
         }
@@ -649,94 +1331,952 @@ bind = SUPER, V, exec, penguinclip
     conflicts
 }
 
+fn hyprland_runtime_conflicts(
+    binds: &[HyprlandBind],
+    targets: &[ShortcutConfig],
+) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for target in targets {
+        let binding = &target.hyprland_binding;
+        let others = binds
+            .iter()
+            .filter(|bind| bind.binds(binding))
+            .filter(|bind| {
+                let arg = bind.arg.to_lowercase();
+                !["penguinclip", "clipboard-history", "win11"]
+                    .iter()
+                    .any(|ours| arg.contains(ours))
+            });
+        for bind in others {
+            conflicts.push(
+                ShortcutConflict::new(target, binding.clone(), bind.action(), "Hyprland config")
+                    .steps(vec![
+                        format!(
+            "Find the \"bind = {}, ...\" line in hyprland.conf or a file it sources",
+            binding
+        ),
+                        "Change it to a different binding or comment it out".to_string(),
+                        format!(
+                            "To free it for this session only, run \"hyprctl keyword unbind {}\"",
+                            binding
+                        ),
+                    ]),
+            );
+        }
+    }
+    conflicts
+}
+
 fn get_hyprland_config_paths() -> Vec<PathBuf> {
-    let home = env::var("HOME").unwrap_or_default();
-    let xdg_config = env::var("XDG_CONFIG_HOME").unwrap_or_else(|_| format!("{}/.config", home));
+    xdg_config_dir()
+        .map(|config| config.join("hypr/hyprland.conf"))
+        .into_iter()
+        .collect()
+}
 
-    vec![
-        PathBuf::from(&xdg_config).join("hypr/hyprland.conf"),
-        PathBuf::from(&home).join(".config/hypr/hyprland.conf"),
-    ]
+// =============================================================================
+// awesome Conflict Detection
+// =============================================================================
+
+fn detect_awesome_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Some(config) = xdg_config_dir() else {
+        return Vec::new();
+    };
+    let rc_lua = config.join("awesome/rc.lua");
+
+    let mut conflicts = Vec::new();
+    for target in targets {
+        // The running awesome knows keys from every file rc.lua loads
+        let actions = awesome_binds(&target.binding)
+            .unwrap_or_else(|| awesome_rc_binds(&rc_lua, &target.awesome_binding));
+        let keys = target.binding.label();
+        conflicts.extend(actions.into_iter().map(|action| {
+            ShortcutConflict::new(
+                target,
+                target.awesome_binding.clone(),
+                action,
+                "awesome config",
+            )
+            .steps(vec![
+                format!(
+                    "Find the awful.key({}, ...) binding for {} in {} or a file it loads",
+                    target.awesome_binding,
+                    keys,
+                    rc_lua.display()
+                ),
+                "Change it to a different key or remove it".to_string(),
+                "Restart awesome: Press Super+Ctrl+r".to_string(),
+            ])
+        }));
+    }
+    conflicts
+}
+
+/// Keys in rc.lua bound to `binding` (`{ "Mod4" }, "v"`), for when awesome
+/// isn't running to ask. `modkey` counts as Mod4.
+fn awesome_rc_binds(rc_lua: &Path, binding: &str) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(rc_lua) else {
+        return Vec::new();
+    };
+    let wanted: String = binding.split_whitespace().collect();
+    content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("--"))
+        .filter(|line| {
+            let compact: String = line
+                .split_whitespace()
+                .collect::<String>()
+                .replace("modkey", "\"Mod4\"");
+            compact.contains(&format!("awful.key({}", wanted))
+        })
+        .filter(|line| !line.contains("penguinclip"))
+        .map(|line| {
+            // awful.key's data table usually describes it
+            line.split("description")
+                .nth(1)
+                .and_then(|rest| rest.split('"').nth(1))
+                .unwrap_or("Unknown action")
+                .to_string()
+        })
+        .collect()
 }
 
 // =============================================================================
-// Cinnamon Conflict Detection
+// qtile Conflict Detection
+// =============================================================================
+
+fn detect_qtile_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Some(config) = xdg_config_dir() else {
+        return Vec::new();
+    };
+    qtile_conflicts_in(&config.join("qtile/config.py"), targets)
+}
+
+fn qtile_conflicts_in(path: &Path, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    targets
+        .iter()
+        .flat_map(|target| content.lines().map(move |line| (line, target)))
+        .filter(|(line, target)| has_qtile_binding(line, &target.qtile_binding))
+        .filter(|(line, _)| !line.contains("penguinclip"))
+        .map(|(line, target)| {
+            // Key(mods, key, *commands, desc=...)
+            let action = line
+                .split_once("lazy.")
+                .map(|(_, rest)| {
+                    // Up to Key's closing parenthesis
+                    let rest = rest.trim_end().trim_end_matches(',');
+                    format!("lazy.{}", rest.strip_suffix(')').unwrap_or(rest))
+                })
+                .unwrap_or_else(|| "Unknown action".to_string());
+            ShortcutConflict::new(target, target.qtile_binding.clone(), action, "qtile config")
+                .steps(vec![
+                    format!("Edit your qtile config: {}", path.display()),
+                    format!("Find the Key({}, ...) entry", target.qtile_binding),
+                    "Change it to a different key or remove it".to_string(),
+                    "Reload qtile: Press Super+Ctrl+r".to_string(),
+                ])
+        })
+        .collect()
+}
+
+// =============================================================================
+// River Conflict Detection
 // =============================================================================
 
-fn detect_cinnamon_conflicts() -> Vec<ShortcutConflict> {
+fn detect_river_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Some(config) = xdg_config_dir() else {
+        return Vec::new();
+    };
+    river_conflicts_in(&config.join("river"), targets)
+}
+
+/// Conflicts in River's `init` and the snippets in `rc.d`
+fn river_conflicts_in(config_dir: &Path, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut paths = vec![config_dir.join("init")];
+    if let Ok(entries) = fs::read_dir(config_dir.join("rc.d")) {
+        let mut snippets: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        snippets.sort();
+        paths.extend(snippets);
+    }
+
     let mut conflicts = Vec::new();
+    for path in paths {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for (line, target) in content
+            .lines()
+            .flat_map(|line| targets.iter().map(move |target| (line, target)))
+        {
+            if !has_river_mapping(line, &target.river_binding) || line.contains("penguinclip") {
+                continue;
+            }
+            let action = line
+                .split_whitespace()
+                .skip(5)
+                .collect::<Vec<_>>()
+                .join(" ");
+            conflicts.push(
+                ShortcutConflict::new(target, target.river_binding.clone(), action, "River init")
+                    .steps(vec![
+                        format!("Edit {}", path.display()),
+                        format!(
+                            "Find the \"riverctl map normal {} ...\" line",
+                            target.river_binding
+                        ),
+                        "Change it to a different key or remove it".to_string(),
+                        format!(
+                            "To free it for this session only, run \"riverctl unmap normal {}\"",
+                            target.river_binding
+                        ),
+                    ]),
+            );
+        }
+    }
+    conflicts
+}
+
+// =============================================================================
+// Wayfire Conflict Detection
+// =============================================================================
+
+fn detect_wayfire_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let path = match env::var("WAYFIRE_CONFIG_FILE") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => match xdg_config_dir() {
+            Some(config) => config.join("wayfire.ini"),
+            None => return Vec::new(),
+        },
+    };
+    wayfire_conflicts_in(&path, targets)
+}
+
+/// Any Wayfire option, in any plugin's section, bound to one of our keys
+fn wayfire_conflicts_in(path: &Path, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
 
-    // Check for notification center / calendar shortcut
-    if let Some(binding) = gsettings_get("org.cinnamon.desktop.keybindings", "show-desklets") {
-        let binding_lower = binding.to_lowercase();
-        if binding_lower.contains("super") && binding_lower.contains("v") {
-            conflicts.push(ShortcutConflict {
-                binding: "<Super>v".to_string(),
-                current_action: "Show Desklets".to_string(),
-                owner: "Cinnamon".to_string(),
-                resolution_command: Some(
-                    "gsettings set org.cinnamon.desktop.keybindings show-desklets \"['<Super><Shift>v']\"".to_string()
+    let mut conflicts = Vec::new();
+    let mut section = "";
+    for line in content.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.starts_with("binding_penguinclip") {
+            continue;
+        }
+        let compact = |text: &str| text.split_whitespace().collect::<String>().to_lowercase();
+        let value = compact(value);
+        let Some(target) = targets
+            .iter()
+            .find(|target| compact(&target.wayfire_binding) == value)
+        else {
+            continue;
+        };
+        conflicts.push(
+            ShortcutConflict::new(
+                target,
+                target.wayfire_binding.clone(),
+                format!("{} ({})", key, section),
+                "Wayfire config",
+            )
+            .steps(vec![
+                format!("Edit your Wayfire config: {}", path.display()),
+                format!(
+                    "In the [{}] section, change {} to a different binding",
+                    section, key
                 ),
-                resolution_steps: r#"**To resolve manually:**
-1. Open System Settings → Keyboard → Shortcuts
-2. Find "Show Desklets"
-3. Change Super+V to Super+Shift+V"#.to_string(),
-            });
+                "Wayfire reloads the config automatically".to_string(),
+            ]),
+        );
+    }
+    conflicts
+}
+
+// =============================================================================
+// bspwm / sxhkd Conflict Detection
+// =============================================================================
+
+fn detect_sxhkd_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let Some(config) = xdg_config_dir() else {
+        return Vec::new();
+    };
+    sxhkd_conflicts_in(&config.join("sxhkd/sxhkdrc"), targets)
+}
+
+fn sxhkd_conflicts_in(path: &Path, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+
+    let Ok(content) = fs::read_to_string(path) else {
+        return conflicts;
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        // Hotkeys start a line; their commands are indented below them
+        if line.starts_with([' ', '\t']) || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let compact = |text: &str| text.split_whitespace().collect::<String>().to_lowercase();
+        let hotkey = compact(line).replace("mod4", "super");
+        let Some(target) = targets
+            .iter()
+            .find(|target| compact(&target.sxhkd_binding) == hotkey)
+        else {
+            continue;
+        };
+        let action = lines.get(i + 1).map(|l| l.trim()).unwrap_or_default();
+        if action.contains("penguinclip") {
+            continue;
         }
+
+        conflicts.push(
+            ShortcutConflict::new(
+                target,
+                target.sxhkd_binding.clone(),
+                if action.is_empty() {
+                    "Unknown action".to_string()
+                } else {
+                    action.to_string()
+                },
+                "sxhkd config",
+            )
+            .steps(vec![
+                format!("Edit your sxhkd config: {}", path.display()),
+                format!("Find the \"{}\" block", target.sxhkd_binding),
+                "Change it to a different hotkey or comment it out".to_string(),
+                format!(
+                    "Add \"{}\" with \"{}\" indented on the next line",
+                    target.sxhkd_binding,
+                    target.full_command()
+                ),
+                "Reload sxhkd: pkill -USR1 -x sxhkd".to_string(),
+            ]),
+        );
     }
 
     conflicts
 }
 
 // =============================================================================
-// XFCE Conflict Detection
+// Cinnamon Conflict Detection
 // =============================================================================
 
-fn detect_xfce_conflicts() -> Vec<ShortcutConflict> {
-    let mut conflicts = Vec::new();
+const CINNAMON_SHORTCUTS: &[GSettingsShortcut] = &[GSettingsShortcut {
+    schema: "org.cinnamon.desktop.keybindings",
+    key: "show-desklets",
+    action: "Show Desklets",
+    owner: "Cinnamon",
+    settings_path: &["System Settings", "Keyboard", "Shortcuts"],
+    keep_with_shift: true,
+}];
+
+fn detect_cinnamon_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    gsettings_conflicts(CINNAMON_SHORTCUTS, targets)
+}
 
+// =============================================================================
+// XFCE Conflict Detection
+// =============================================================================
+
+fn detect_xfce_conflicts(targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
     if !command_exists("xfconf-query") {
-        return conflicts;
+        return Vec::new();
     }
 
-    // Check for Super+V in XFCE keyboard shortcuts
-    let output = Command::new("xfconf-query")
+    // Check for our keys in XFCE keyboard shortcuts
+    let output = host_command("xfconf-query")
         .args(["-c", "xfce4-keyboard-shortcuts", "-l", "-v"])
         .output();
 
-    if let Ok(output) = output {
-        let content = String::from_utf8_lossy(&output.stdout);
-        for line in content.lines() {
-            let line_lower = line.to_lowercase();
-            if line_lower.contains("<super>v")
-                && !line_lower.contains("clipboard-history")
-                && !line_lower.contains("win11")
-            {
-                conflicts.push(ShortcutConflict {
-                    binding: "<Super>v".to_string(),
-                    current_action: line.to_string(),
-                    owner: "XFCE".to_string(),
-                    resolution_command: None,
-                    resolution_steps: r#"**To resolve manually:**
-1. Open Settings → Keyboard → Application Shortcuts
-2. Find the Super+V binding
-3. Change or remove it"#
-                        .to_string(),
-                });
-            }
-        }
+    match output {
+        Ok(output) => xfce_conflicts_in(&String::from_utf8_lossy(&output.stdout), targets),
+        Err(_) => Vec::new(),
     }
+}
 
+/// Conflicts in `xfconf-query -l -v` output, one `/property/<Super>v  value`
+/// per line
+fn xfce_conflicts_in(listing: &str, targets: &[ShortcutConfig]) -> Vec<ShortcutConflict> {
+    let mut conflicts = Vec::new();
+    for line in listing.lines() {
+        let line_lower = line.to_lowercase();
+        if line_lower.contains("penguinclip")
+            || line_lower.contains("clipboard-history")
+            || line_lower.contains("win11")
+        {
+            continue;
+        }
+        let Some(property) = line.split_whitespace().next() else {
+            continue;
+        };
+        let keys = property.rsplit('/').next().unwrap_or_default();
+        let Ok(binding) = KeyBinding::parse(keys) else {
+            continue;
+        };
+        for target in targets.iter().filter(|target| target.binding == binding) {
+            conflicts.push(
+                ShortcutConflict::new(target, target.xfce_binding.clone(), line, "XFCE")
+                    .in_settings(&["Settings", "Keyboard", "Application Shortcuts"])
+                    .steps(vec![
+                        format!("Find the {} binding", target.xfce_binding),
+                        "Change or remove it".to_string(),
+                    ]),
+            );
+        }
+    }
     conflicts
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::env::temp_dir;
+
+    fn targets() -> Vec<ShortcutConfig> {
+        conflict_shortcuts(&HistoryBindings::default())
+    }
 
     #[test]
     fn test_detect_conflicts_runs() {
         // Just verify it doesn't panic when running
-        let _result = detect_shortcut_conflicts();
+        let _result = detect_shortcut_conflicts(&HistoryBindings::default());
+    }
+
+    #[test]
+    fn test_targets_cover_alt_and_emoji_shortcuts() {
+        let names: Vec<&str> = targets().iter().map(|t| t.name).collect();
+        assert_eq!(
+            names,
+            [
+                "Clipboard History",
+                "Clipboard History (Alt)",
+                "Emoji Picker"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_result_message_names_the_configured_binding() {
+        let targets = conflict_shortcuts(&HistoryBindings {
+            primary: KeyBinding::parse("<Super><Alt>c").unwrap(),
+            ..Default::default()
+        });
+        let conflict = ShortcutConflict::new(&targets[0], "<Super><Alt>c", "Calendar", "GNOME");
+        let result = conflict_result("GNOME", &targets, vec![conflict]);
+        assert_eq!(
+            result.message,
+            "1 shortcut conflict(s) detected that may keep Super+Alt+C from working."
+        );
+        assert_eq!(
+            conflict_result("GNOME", &targets, Vec::new()).message,
+            "No shortcut conflicts detected."
+        );
+    }
+
+    #[test]
+    fn test_legacy_gnome_settings_path() {
+        let target = &targets()[0];
+        let conflicts = with_legacy_gnome_settings(vec![
+            custom_shortcut_conflict(target, "Terminal"),
+            ShortcutConflict::new(target, target.gnome_binding.clone(), "Tiling", "Pop Shell")
+                .in_settings(&["Pop!_OS Settings", "Keyboard"]),
+        ]);
+        assert_eq!(
+            conflicts[0].settings_path,
+            ["Settings", "Keyboard Shortcuts"]
+        );
+        assert!(conflicts[0]
+            .resolution_steps
+            .contains("Open Settings → Keyboard Shortcuts\n"));
+        assert_eq!(conflicts[1].settings_path, ["Pop!_OS Settings", "Keyboard"]);
+    }
+
+    #[test]
+    fn test_hyprland_runtime_conflicts() {
+        let binds: Vec<HyprlandBind> = serde_json::from_str(
+            r#"[
+                {"modmask": 64, "key": "V", "dispatcher": "togglefloating", "arg": ""},
+                {"modmask": 64, "key": "V", "dispatcher": "exec", "arg": "penguinclip"},
+                {"modmask": 65, "key": "V", "dispatcher": "exec", "arg": "cliphist"},
+                {"modmask": 12, "key": "V", "dispatcher": "exec", "arg": "cliphist"}
+            ]"#,
+        )
+        .unwrap();
+
+        let conflicts = hyprland_runtime_conflicts(&binds, &targets());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].current_action, "togglefloating");
+        assert_eq!(conflicts[0].conflicts_with, "Clipboard History");
+        assert_eq!(conflicts[1].binding, "CTRL ALT, V");
+        assert_eq!(conflicts[1].conflicts_with, "Clipboard History (Alt)");
+    }
+
+    #[test]
+    fn test_i3_conflicts_follow_includes() {
+        let dir = temp_dir().join("penguinclip_test_conflict_includes");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("config.d")).unwrap();
+        fs::write(dir.join("config"), "include config.d/*.conf\n").unwrap();
+        fs::write(
+            dir.join("config.d/keys.conf"),
+            "include ../config\nbindsym $mod+v split v\nbindsym $mod+period exec rofimoji\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("config.d/penguinclip.conf"),
+            "bindsym $mod+v exec penguinclip\n",
+        )
+        .unwrap();
+
+        let conflicts = i3_conflicts_in(&[dir.join("config")], &targets());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].current_action, "split v");
+        assert!(conflicts[0].resolution_steps.contains("keys.conf"));
+        assert_eq!(conflicts[1].current_action, "exec rofimoji");
+        assert_eq!(conflicts[1].conflicts_with, "Emoji Picker");
+    }
+
+    #[test]
+    fn test_sxhkd_conflicts() {
+        let dir = temp_dir().join("penguinclip_test_conflict_sxhkd");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sxhkdrc");
+        fs::write(
+            &path,
+            "super + v\n\tbspc node -t floating\n\nctrl + alt + v\n\tpenguinclip\n",
+        )
+        .unwrap();
+
+        let conflicts = sxhkd_conflicts_in(&path, &targets());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].current_action, "bspc node -t floating");
+    }
+
+    #[test]
+    fn test_awesome_rc_conflicts() {
+        let dir = temp_dir().join("penguinclip_test_conflict_awesome");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let rc_lua = dir.join("rc.lua");
+        fs::write(
+            &rc_lua,
+            r#"awful.key({ modkey }, "v", function() end, {description = "paste", group = "client"}),
+-- awful.key({ modkey }, "v", function() end),
+awful.key({ modkey, "Shift" }, "v", function() end),
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(awesome_rc_binds(&rc_lua, r#"{ "Mod4" }, "v""#), ["paste"]);
+        assert!(awesome_rc_binds(&rc_lua, r#"{ "Mod4" }, "period""#).is_empty());
+    }
+
+    #[test]
+    fn test_qtile_conflicts() {
+        let dir = temp_dir().join("penguinclip_test_conflict_qtile");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.py");
+        fs::write(
+            &path,
+            r#"keys = [
+    Key([mod], "v", lazy.window.toggle_floating()),
+    Key([mod, "shift"], "v", lazy.spawn("x")),
+]
+keys.append(_PenguinclipKey(["mod4"], "v", _penguinclip_lazy.spawn("penguinclip")))
+"#,
+        )
+        .unwrap();
+
+        let conflicts = qtile_conflicts_in(&path, &targets());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].current_action, "lazy.window.toggle_floating()");
+    }
+
+    #[test]
+    fn test_detectors_follow_xdg_config_home() {
+        let config = temp_dir().join("penguinclip_test_conflict_xdg");
+        let _ = fs::remove_dir_all(&config);
+        fs::create_dir_all(config.join("qtile")).unwrap();
+        fs::write(
+            config.join("qtile/config.py"),
+            "keys = [Key([mod], \"v\", lazy.window.toggle_floating())]\n",
+        )
+        .unwrap();
+        fs::write(
+            config.join("kglobalshortcutsrc"),
+            "[kwin]\nWindow Maximize=Meta+V,Meta+PgUp,Maximize Window\n",
+        )
+        .unwrap();
+
+        crate::paths::with_config_home(&config, || {
+            assert_eq!(detect_qtile_conflicts(&targets()).len(), 1);
+            let kde = detect_kde_conflicts(&targets());
+            assert_eq!(kde.len(), 1);
+            assert_eq!(kde[0].current_action, "kwin: Window Maximize");
+            assert_eq!(get_i3_config_paths()[0], config.join("i3/config"));
+        });
+        let _ = fs::remove_dir_all(&config);
+    }
+
+    #[test]
+    fn test_river_conflicts() {
+        let dir = temp_dir().join("penguinclip_test_conflict_river");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("rc.d")).unwrap();
+        fs::write(
+            dir.join("init"),
+            "riverctl map normal Super V toggle-float\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("rc.d/penguinclip"),
+            "riverctl map normal Super V spawn 'penguinclip'\n",
+        )
+        .unwrap();
+
+        let conflicts = river_conflicts_in(&dir, &targets());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].current_action, "toggle-float");
+    }
+
+    #[test]
+    fn test_wayfire_conflicts() {
+        let dir = temp_dir().join("penguinclip_test_conflict_wayfire");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wayfire.ini");
+        fs::write(
+            &path,
+            "[scale]\ntoggle = <super>KEY_V\n\n[expo]\ntoggle = <super> KEY_DOT\n\n[command]\nbinding_penguinclip = <super> KEY_V\n",
+        )
+        .unwrap();
+
+        let conflicts = wayfire_conflicts_in(&path, &targets());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].current_action, "toggle (scale)");
+        assert_eq!(conflicts[1].current_action, "toggle (expo)");
+        assert_eq!(conflicts[1].conflicts_with, "Emoji Picker");
+    }
+
+    #[test]
+    fn test_gsettings_has_binding() {
+        let targets = targets();
+        let super_v = &targets[0].binding;
+        assert!(gsettings_has_binding("['<Super>v']", super_v));
+        assert!(gsettings_has_binding("['<Super>n', '<Super>V']", super_v));
+        assert!(!gsettings_has_binding("['<Super><Shift>v']", super_v));
+        assert!(!gsettings_has_binding("@as []", super_v));
+        assert!(gsettings_has_binding("'<Super>v'", super_v));
+        // IBus' default emoji hotkeys
+        assert!(gsettings_has_binding(
+            "['<Super>period', '<Super>semicolon']",
+            &targets[2].binding
+        ));
+        assert!(gsettings_has_binding(
+            "['<Primary><Alt>v']",
+            &targets[1].binding
+        ));
+    }
+
+    #[test]
+    fn test_gsettings_scan_conflicts() {
+        let listing = "org.gnome.settings-daemon.plugins.media-keys home ['<Super>e']\n\
+                       org.gnome.settings-daemon.plugins.media-keys email @as []\n\
+                       org.gnome.desktop.wm.keybindings show-desktop ['<Super>d', '<Super>e']\n\
+                       org.gnome.shell.extensions.dash-to-dock shortcut-text '<Super>e'\n\
+                       org.gnome.desktop.interface gtk-theme 'Adwaita'\n";
+        let binding = KeyBinding::parse("<Super>e").unwrap();
+        let conflicts = gsettings_scan_conflicts_in(listing, &[candidate_shortcut(&binding)]);
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts[0].owner, "GNOME Settings");
+        assert_eq!(
+            conflicts[0].current_action,
+            "home (org.gnome.settings-daemon.plugins.media-keys)"
+        );
+        assert_eq!(
+            conflicts[1].commands[0].command,
+            "gsettings set org.gnome.desktop.wm.keybindings show-desktop \"['<Super>d']\""
+        );
+        assert!(conflicts[1].commands[0].destructive);
+        assert_eq!(conflicts[2].owner, "Dash to Dock");
+        assert!(conflicts[2].commands[0]
+            .command
+            .ends_with("shortcut-text \"''\""));
+    }
+
+    #[test]
+    fn test_conflict_serialization() {
+        let target = &targets()[0];
+        let conflict = ShortcutConflict::new(
+            target,
+            "<Super>v",
+            "Open Notification Center / Message Tray",
+            "GNOME Shell",
+        )
+        .in_settings(&["Settings", "Keyboard", "Keyboard Shortcuts"])
+        .steps(vec![
+            "Find \"Open Notification Center / Message Tray\"".to_string()
+        ])
+        .command(
+            "Move \"Open Notification Center / Message Tray\" to <Super><Shift>v".to_string(),
+            "gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super><Shift>v']\""
+                .to_string(),
+            false,
+        );
+
+        let json = serde_json::to_value(&conflict).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "binding": "<Super>v",
+                "conflicts_with": "Clipboard History",
+                "current_action": "Open Notification Center / Message Tray",
+                "owner": "GNOME Shell",
+                "settings_path": ["Settings", "Keyboard", "Keyboard Shortcuts"],
+                "manual_steps": ["Find \"Open Notification Center / Message Tray\""],
+                "commands": [{
+                    "description": "Move \"Open Notification Center / Message Tray\" to <Super><Shift>v",
+                    "command": "gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super><Shift>v']\"",
+                    "destructive": false
+                }],
+                "resolution_steps": "**To resolve manually:**\n\
+                    1. Open Settings → Keyboard → Keyboard Shortcuts\n\
+                    2. Find \"Open Notification Center / Message Tray\"\n\
+                    \n\
+                    **Or run this command:**\n\
+                    ```\n\
+                    gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super><Shift>v']\"\n\
+                    ```"
+            })
+        );
+    }
+
+    #[test]
+    fn test_resolution_steps_without_commands() {
+        let conflict = ShortcutConflict::new(&targets()[0], "$mod+v", "split v", "i3 config")
+            .steps(vec!["Edit your i3 config: ~/.config/i3/config".to_string()]);
+        assert!(conflict.commands.is_empty());
+        assert_eq!(
+            conflict.resolution_steps,
+            "**To resolve manually:**\n1. Edit your i3 config: ~/.config/i3/config"
+        );
+        let json = serde_json::to_value(&conflict).unwrap();
+        assert_eq!(json["settings_path"], serde_json::json!([]));
+        assert_eq!(json["commands"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_setting_target() {
+        assert_eq!(
+            setting_target(
+                "gsettings set org.gnome.shell.keybindings toggle-message-tray \"['<Super>m']\""
+            ),
+            Some(("org.gnome.shell.keybindings", "toggle-message-tray"))
+        );
+        assert_eq!(
+            setting_target("dconf write /org/gnome/desktop/wm/keybindings/close \"['<Alt>F4']\""),
+            Some(("/org/gnome/desktop/wm/keybindings/", "close"))
+        );
+        assert_eq!(
+            setting_target("sed -i 's/^bindsym/# bindsym/' ~/.config/i3/config"),
+            None
+        );
+    }
+
+    const DCONF_GNOME: &str = include_str!("shortcut_conflict_detector/fixtures/dconf-dump-gnome");
+    const DCONF_POP: &str = include_str!("shortcut_conflict_detector/fixtures/dconf-dump-pop");
+
+    #[test]
+    fn test_parse_dconf_dump() {
+        let dump = parse_dconf_dump(DCONF_GNOME);
+        assert_eq!(
+            dump[0],
+            DconfKey {
+                dir: "/org/gnome/desktop/input-sources/".to_string(),
+                key: "sources".to_string(),
+                value: "[('xkb', 'us'), ('xkb', 'de')]".to_string(),
+            }
+        );
+        let custom = dump
+            .iter()
+            .find(|key| key.value == "'copyq toggle'")
+            .unwrap();
+        assert_eq!(
+            custom.dir,
+            "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/"
+        );
+        assert_eq!(
+            dump.iter()
+                .find(|key| key.key == "toggle-message-tray")
+                .unwrap()
+                .schema(),
+            "org.gnome.shell.keybindings"
+        );
+    }
+
+    #[test]
+    fn test_dconf_conflicts() {
+        let dump = parse_dconf_dump(DCONF_GNOME);
+        let known: Vec<&GSettingsShortcut> = GNOME_SHORTCUTS.iter().collect();
+        let conflicts = dconf_conflicts_in(&dump, &known, &targets());
+        let found: Vec<(&str, &str, &str)> = conflicts
+            .iter()
+            .map(|c| {
+                (
+                    c.owner.as_str(),
+                    c.current_action.as_str(),
+                    c.conflicts_with.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "GNOME Window Manager",
+                    "toggle-maximized (org.gnome.desktop.wm.keybindings)",
+                    "Clipboard History"
+                ),
+                ("GNOME Custom Shortcut", "CopyQ", "Clipboard History"),
+                (
+                    "Dash to Panel",
+                    "shortcut-text (org.gnome.shell.extensions.dash-to-panel)",
+                    "Emoji Picker"
+                ),
+                (
+                    "GSConnect",
+                    "keybindings (org.gnome.shell.extensions.gsconnect.device.3f2b9c1e5d7a4e08)",
+                    "Clipboard History"
+                ),
+            ]
+        );
+        // Each key is rewritten in place, keeping its other shortcuts
+        assert_eq!(
+            conflicts[0].commands[0].command,
+            "dconf write /org/gnome/desktop/wm/keybindings/toggle-maximized \"['<Super>Up']\""
+        );
+        assert_eq!(
+            conflicts[1].commands[0].command,
+            "dconf write /org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/binding \"''\""
+        );
+        assert_eq!(
+            conflicts[2].commands[0].command,
+            "dconf write /org/gnome/shell/extensions/dash-to-panel/shortcut-text \"''\""
+        );
+        assert!(conflicts[3].commands.is_empty());
+    }
+
+    #[test]
+    fn test_dconf_known_keys() {
+        let dump = parse_dconf_dump(DCONF_POP);
+        let known: Vec<&GSettingsShortcut> =
+            GNOME_SHORTCUTS.iter().chain(POP_SHELL_SHORTCUTS).collect();
+        let conflicts = dconf_conflicts_in(&dump, &known, &targets());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].owner, "Pop Shell");
+        assert_eq!(conflicts[0].current_action, "Enter Tiling Mode");
+        assert_eq!(
+            conflicts[0].commands[0].command,
+            "dconf write /org/gnome/shell/extensions/pop-shell/tile-enter \"['<Super><Shift>v']\""
+        );
+        assert!(!conflicts[0].commands[0].destructive);
+
+        // The cleared message tray shortcut is in the dump, so only the keys
+        // still at their default are left to gsettings
+        let defaults: Vec<&str> = keys_at_default(&known, &dump)
+            .iter()
+            .map(|entry| entry.key)
+            .collect();
+        assert_eq!(
+            defaults,
+            vec!["toggle-quick-settings", "hotkey", "unicode-hotkey"]
+        );
+    }
+
+    #[test]
+    fn test_gsettings_strv_without() {
+        let targets = targets();
+        assert_eq!(
+            gsettings_strv_without("['<Super>v']", &targets[0].binding, true),
+            "['<Super><Shift>v']"
+        );
+        assert_eq!(
+            gsettings_strv_without("['<Super>v']", &targets[0].binding, false),
+            "[]"
+        );
+        assert_eq!(
+            gsettings_strv_without(
+                "['<Super>period', '<Super>semicolon']",
+                &targets[2].binding,
+                false
+            ),
+            "['<Super>semicolon']"
+        );
+    }
+
+    #[test]
+    fn test_xfce_conflicts() {
+        let listing = "/commands/custom/<Super>v          xfce4-clipman-history\n\
+                       /commands/custom/<Primary><Alt>v   penguinclip\n\
+                       /commands/custom/override          true\n\
+                       /xfwm4/custom/<Super>period        show_desktop_key\n";
+        let conflicts = xfce_conflicts_in(listing, &targets());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].conflicts_with, "Clipboard History");
+        assert_eq!(conflicts[1].binding, "<Super>period");
+        assert_eq!(conflicts[1].conflicts_with, "Emoji Picker");
+    }
+
+    #[test]
+    fn test_cosmic_conflicts() {
+        let content = r#"{
+    (modifiers: [Super], key: "period"): Spawn("gnome-characters"),
+    (modifiers: [Super], key: "v", description: Some("Clipboard History")): Spawn("penguinclip"),
+    (modifiers: [Ctrl, Alt], key: "v"): Disable,
+}"#;
+        let conflicts = cosmic_conflicts_in(content, &targets());
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].conflicts_with, "Clipboard History (Alt)");
+        assert_eq!(conflicts[0].current_action, "Disable");
+        assert_eq!(conflicts[1].conflicts_with, "Emoji Picker");
+        assert_eq!(conflicts[1].current_action, "Spawn(\"gnome-characters\")");
+    }
+
+    #[test]
+    fn test_kde_plasma6_conflicts() {
+        let content = format!(
+            "{}\n[services][penguinclip-shortcut.desktop]\n_launch=Meta+V\n\n[services][org.example.notes.desktop]\n_launch=Meta+N\tMeta+V\n",
+            include_str!("linux_shortcut_manager/fixtures/kglobalshortcutsrc-plasma6")
+        );
+        assert_eq!(
+            kde_actions_bound_to(&content, "Meta+V"),
+            [
+                "org.kde.klipper.desktop: show-on-mouse-pos",
+                "org.example.notes"
+            ]
+        );
+        // Only the active shortcut counts, not the default
+        assert_eq!(
+            kde_actions_bound_to("[kwin]\nOverview=none,Meta+V,Toggle Overview\n", "Meta+V"),
+            Vec::<String>::new()
+        );
+        assert_eq!(kde_actions_bound_to(&content, "Meta+W"), ["kwin: Overview"]);
+
+        let conflicts = kde_conflicts_in(&content, &targets());
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts
+            .iter()
+            .all(|c| c.conflicts_with == "Clipboard History"));
     }
 }
@@ -1,109 +1,227 @@
 //! Shortcut setup commands for the frontend
 //! Provides Tauri commands to register/unregister shortcuts from the Setup Wizard
 
-use std::env;
-
+use crate::conflict_journal::{ConflictJournal, ResolvedConflict};
+use crate::environment::{self, DesktopEnvironment, EnvironmentInfo};
+use crate::global_shortcuts_portal;
+use crate::input_simulator;
+use crate::linux_shortcut_manager::{
+    conflict_shortcuts, register_global_shortcut, reload_desktop_config, shortcut_status,
+    unregister_global_shortcut, HistoryBindings, KeyBinding, RegistrationResult, ShortcutStatus,
+};
+use crate::paths::xdg_config_dir;
+use crate::session::{self, SessionInfo};
+use crate::shortcut_backups::{BackupStore, ShortcutBackup};
 use crate::shortcut_conflict_detector::{
-    auto_resolve_conflicts, detect_shortcut_conflicts, ConflictDetectionResult,
+    auto_resolve_conflicts, detect_binding_conflicts, detect_shortcut_conflicts,
+    ConflictDetectionResult, ConflictResolutionReport,
 };
+use crate::user_settings::UserSettingsManager;
 
 /// Get the current desktop environment name
 #[tauri::command]
 pub fn get_desktop_environment() -> String {
-    let xdg_current = env::var("XDG_CURRENT_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let xdg_session = env::var("XDG_SESSION_DESKTOP")
-        .unwrap_or_default()
-        .to_lowercase();
-    let combined = format!("{} {}", xdg_current, xdg_session);
-
-    // Check for Pop!_OS specifically (uses pop:GNOME)
-    if combined.contains("pop") {
-        return "Pop!_OS".to_string();
-    }
-    if combined.contains("gnome") || combined.contains("unity") || combined.contains("pantheon") {
-        "GNOME".to_string()
-    } else if combined.contains("cinnamon") {
-        "Cinnamon".to_string()
-    } else if combined.contains("kde") || combined.contains("plasma") {
-        "KDE Plasma".to_string()
-    } else if combined.contains("xfce") {
-        "XFCE".to_string()
-    } else if combined.contains("mate") {
-        "MATE".to_string()
-    } else if combined.contains("lxde") {
-        "LXDE".to_string()
-    } else if combined.contains("lxqt") {
-        "LXQt".to_string()
-    } else if combined.contains("cosmic") {
-        "COSMIC".to_string()
-    } else if combined.contains("budgie") {
-        "Budgie".to_string()
-    } else if combined.contains("deepin") {
-        "Deepin".to_string()
-    } else if combined.contains("i3") {
-        "i3".to_string()
-    } else if combined.contains("sway") {
-        "Sway".to_string()
-    } else if combined.contains("hyprland") {
-        "Hyprland".to_string()
-    } else {
-        // Check for running tiling WMs
-        if is_process_running("i3") {
-            "i3".to_string()
-        } else if is_process_running("sway") {
-            "Sway".to_string()
-        } else if is_process_running("hyprland") || is_process_running("Hyprland") {
-            "Hyprland".to_string()
-        } else {
-            xdg_current.to_uppercase()
-        }
-    }
+    environment::detect().name.clone()
 }
 
-fn is_process_running(name: &str) -> bool {
-    std::process::Command::new("pgrep")
-        .arg("-x")
-        .arg(name)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+/// The desktop, session type, compositor and versions we detected, for
+/// the diagnostics page
+#[tauri::command]
+pub fn get_environment_info() -> EnvironmentInfo {
+    environment::detect().clone()
+}
+
+/// Whether we run through XWayland, the compositor's name and the GNOME
+/// Shell and Plasma versions, for the diagnostics page
+#[tauri::command]
+pub fn get_session_info() -> SessionInfo {
+    session::info().clone()
 }
 
-/// Detect shortcut conflicts for Super+V
+/// Detect shortcut conflicts for the configured clipboard history shortcuts
+/// and the emoji picker
 #[tauri::command]
 pub fn detect_conflicts() -> ConflictDetectionResult {
-    detect_shortcut_conflicts()
+    let bindings = UserSettingsManager::new()
+        .load()
+        .shortcut_bindings()
+        .unwrap_or_default();
+    detect_shortcut_conflicts(&bindings)
 }
 
-/// Automatically resolve detected conflicts
+/// Other uses of `binding` (`<Super>e`), so the settings can warn before a
+/// shortcut that is already taken is saved
 #[tauri::command]
-pub fn resolve_conflicts() -> Result<Vec<String>, String> {
-    auto_resolve_conflicts()
+pub fn check_binding_conflicts(binding: String) -> Result<ConflictDetectionResult, String> {
+    let binding = KeyBinding::parse(&binding)?;
+    Ok(detect_binding_conflicts(&binding))
 }
 
-/// Register the global shortcut with the desktop environment
-/// This calls the existing linux_shortcut_manager
+/// Automatically resolve detected conflicts. Shortcuts are only taken away
+/// from their owner, rather than moved, with `allow_destructive`; the
+/// conflicts that would need that are reported as skipped.
 #[tauri::command]
-pub fn register_de_shortcut() -> Result<String, String> {
+pub fn resolve_conflicts(allow_destructive: bool) -> Result<ConflictResolutionReport, String> {
+    let bindings = UserSettingsManager::new()
+        .load()
+        .shortcut_bindings()
+        .unwrap_or_default();
+    auto_resolve_conflicts(&bindings, allow_destructive)
+}
+
+/// Desktop shortcuts changed by resolving conflicts, newest first
+#[tauri::command]
+pub fn list_resolved_conflicts() -> Vec<ResolvedConflict> {
+    ConflictJournal::default().list()
+}
+
+/// Give a shortcut changed by resolving a conflict its previous value back
+#[tauri::command]
+pub fn undo_conflict_resolution(entry_id: String) -> Result<ResolvedConflict, String> {
+    ConflictJournal::default().undo(&entry_id)
+}
+
+/// Register one shortcut by id or, without an id, all the ones the user
+/// hasn't unregistered with the desktop environment, reporting each one so
+/// failures can be retried. A shortcut registered by id is enabled again.
+/// With `binding_override`, shortcut `shortcut_id` is moved to that binding
+/// instead, e.g. `<Super><Shift>v` when Super+V is taken.
+/// The list is empty while a GlobalShortcuts portal session holds the
+/// shortcuts; if binding them through the portal failed or was cancelled,
+/// they go to the desktop environment as usual.
+#[tauri::command]
+pub async fn register_de_shortcut(
+    shortcut_id: Option<String>,
+    binding_override: Option<String>,
+) -> Result<Vec<RegistrationResult>, String> {
+    if global_shortcuts_portal::is_active() {
+        return Ok(Vec::new());
+    }
+
+    if let Some(binding) = binding_override {
+        let id = shortcut_id.ok_or("A binding override needs a shortcut id")?;
+        return tauri::async_runtime::spawn_blocking(move || register_override(&id, &binding))
+            .await
+            .map_err(|_| "Shortcut registration thread failed unexpectedly.".to_string())?;
+    }
+
+    let bindings = UserSettingsManager::new()
+        .load()
+        .shortcut_bindings()
+        .unwrap_or_default();
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        register_global_shortcut(&bindings, shortcut_id.as_deref())
+    })
+    .await
+    .map_err(|_| "Shortcut registration thread failed unexpectedly.".to_string())??;
+    record_disabled(&results, false);
+    Ok(results)
+}
+
+/// Saves the shortcuts in `results` that were (un)registered successfully
+/// as `disabled` or enabled, so startup registers only the enabled ones
+fn record_disabled(results: &[RegistrationResult], disabled: bool) {
+    let manager = UserSettingsManager::new();
+    let mut settings = manager.load();
+    let mut changed = false;
+    for result in results.iter().filter(|r| r.success) {
+        changed |= if disabled {
+            settings.disabled_shortcuts.insert(result.id.clone())
+        } else {
+            settings.disabled_shortcuts.remove(&result.id)
+        };
+    }
+    if changed {
+        if let Err(e) = manager.save(&settings) {
+            log::warn!("failed to save the disabled shortcuts: {}", e);
+        }
+    }
+}
+
+/// Moves shortcut `id` from whatever binding it is registered with to
+/// `binding`, and records the override in the settings once the desktop
+/// accepted it. On failure the previous binding is registered again.
+fn register_override(id: &str, binding: &str) -> Result<Vec<RegistrationResult>, String> {
+    let binding = KeyBinding::parse(binding)?;
+    let manager = UserSettingsManager::new();
+    let mut settings = manager.load();
+    let bindings = settings.shortcut_bindings().unwrap_or_default();
+
+    // Errors on an unknown id before anything is changed
+    unregister_global_shortcut(&bindings, Some(id))?;
+    let mut overridden = bindings.clone();
+    overridden.overrides.insert(id.to_string(), binding.clone());
+    let results = register_global_shortcut(&overridden, Some(id))?;
+
+    if results.iter().all(|r| r.success) {
+        settings
+            .shortcut_overrides
+            .insert(id.to_string(), binding.to_string());
+        settings.disabled_shortcuts.remove(id);
+        manager.save(&settings)?;
+    } else {
+        let _ = unregister_global_shortcut(&overridden, Some(id));
+        let _ = register_global_shortcut(&bindings, Some(id));
+    }
+    Ok(results)
+}
+
+/// Unregister one shortcut by id (`penguinclip`, `penguinclip-alt`,
+/// `penguinclip-emoji`, ...) or, without an id, all of them. They stay
+/// unregistered across restarts until registered again by id.
+#[tauri::command]
+pub fn unregister_de_shortcut(
+    shortcut_id: Option<String>,
+) -> Result<Vec<RegistrationResult>, String> {
+    let bindings = UserSettingsManager::new()
+        .load()
+        .shortcut_bindings()
+        .unwrap_or_default();
     let (tx, rx) = std::sync::mpsc::channel();
     std::thread::spawn(move || {
-        crate::linux_shortcut_manager::register_global_shortcut();
-        let _ = tx.send(());
+        let results = unregister_global_shortcut(&bindings, shortcut_id.as_deref());
+        if let Ok(results) = &results {
+            record_disabled(results, true);
+        }
+        let _ = tx.send(results);
     });
 
-    match rx.recv() {
-        Ok(()) => {
-            Ok("Shortcut registration completed. Check the app logs for details.".to_string())
-        }
-        Err(_) => Err("Shortcut registration thread failed unexpectedly.".to_string()),
-    }
+    rx.recv()
+        .unwrap_or_else(|_| Err("Shortcut unregistration thread failed unexpectedly.".to_string()))
+}
+
+/// Whether each shortcut is currently registered with the desktop environment
+#[tauri::command]
+pub fn get_shortcut_status() -> Vec<ShortcutStatus> {
+    let bindings = UserSettingsManager::new()
+        .load()
+        .shortcut_bindings()
+        .unwrap_or_default();
+    shortcut_status(&bindings)
+}
+
+/// Config file backups taken before each shortcut change, newest first
+#[tauri::command]
+pub fn list_shortcut_backups() -> Vec<ShortcutBackup> {
+    BackupStore::default().list()
+}
+
+/// Restore a config file from a backup and have the desktop reload it
+#[tauri::command]
+pub fn restore_shortcut_backup(id: String) -> Result<ShortcutBackup, String> {
+    let backup = BackupStore::default().restore(&id)?;
+    reload_desktop_config();
+    Ok(backup)
 }
 
 /// Check if the DE shortcut manager has the tools needed
 #[tauri::command]
-pub fn check_shortcut_tools() -> ShortcutToolsStatus {
+pub async fn check_shortcut_tools() -> ShortcutToolsStatus {
+    // Whether the portal holds the shortcuts, not whether it exists: a
+    // failed or cancelled bind leaves them to the desktop's settings
+    let portal_available = global_shortcuts_portal::is_active();
+    let sandboxed = environment::is_sandboxed();
+    let host_spawn_permitted = sandboxed && environment::host_spawn_permitted();
     {
         let gsettings = command_exists("gsettings");
         let kwriteconfig5 = command_exists("kwriteconfig5");
@@ -111,38 +229,111 @@ pub fn check_shortcut_tools() -> ShortcutToolsStatus {
         let xfconf_query = command_exists("xfconf-query");
         let dconf = command_exists("dconf");
 
-        let de = get_desktop_environment();
-
-        let can_register = match de.as_str() {
-            "GNOME" | "Pop!_OS" | "Cinnamon" | "MATE" | "Budgie" | "Deepin" => gsettings || dconf,
-            "KDE Plasma" => kwriteconfig5 || kwriteconfig6,
-            "XFCE" => xfconf_query,
-            "LXQt" => true,     // Uses config files
-            "LXDE" => true,     // Uses config files
-            "COSMIC" => true,   // Uses config files
-            "i3" => true,       // Uses config files
-            "Sway" => true,     // Uses config files
-            "Hyprland" => true, // Uses config files
-            _ => gsettings,     // Fallback to gsettings
+        let desktop = environment::detect();
+
+        let can_register = match desktop.de {
+            DesktopEnvironment::Gnome
+            | DesktopEnvironment::PopOs
+            | DesktopEnvironment::Cinnamon
+            | DesktopEnvironment::Mate
+            | DesktopEnvironment::Budgie
+            | DesktopEnvironment::Deepin => gsettings || dconf,
+            DesktopEnvironment::KdePlasma => kwriteconfig5 || kwriteconfig6,
+            DesktopEnvironment::Xfce => xfconf_query,
+            DesktopEnvironment::Lxqt => true,   // Uses config files
+            DesktopEnvironment::Lxde => true,   // Uses config files
+            DesktopEnvironment::Cosmic => true, // Uses config files
+            DesktopEnvironment::I3 => true,     // Uses config files
+            DesktopEnvironment::Sway => true,   // Uses config files
+            DesktopEnvironment::Hyprland => true, // Uses config files
+            DesktopEnvironment::Bspwm => true,  // Uses sxhkd's config files
+            DesktopEnvironment::Awesome => command_exists("awesome-client"),
+            DesktopEnvironment::Enlightenment => false, // Bindings live in the binary e.cfg
+            DesktopEnvironment::Qtile => qtile_config_exists(),
+            DesktopEnvironment::River => command_exists("riverctl"),
+            DesktopEnvironment::Wayfire => true, // Uses config files
+            DesktopEnvironment::Unknown => gsettings, // Fallback to gsettings
         };
+        // A sandbox that can't run host commands can't edit the desktop's settings
+        let can_register =
+            (can_register && (!sandboxed || host_spawn_permitted)) || portal_available;
 
         // Check for conflicts
-        let conflicts = detect_shortcut_conflicts();
+        let bindings = UserSettingsManager::new()
+            .load()
+            .shortcut_bindings()
+            .unwrap_or_default();
+        let conflicts = detect_shortcut_conflicts(&bindings);
+        let binding_overrides = if portal_available {
+            Vec::new()
+        } else {
+            binding_overrides(&bindings, &conflicts)
+        };
 
         ShortcutToolsStatus {
-            desktop_environment: de.clone(),
+            desktop_environment: desktop.name.clone(),
             gsettings_available: gsettings,
             kde_tools_available: kwriteconfig5 || kwriteconfig6,
             xfce_tools_available: xfconf_query,
             can_register_automatically: can_register,
-            manual_instructions: get_manual_instructions(&de),
+            portal_available,
+            sandboxed,
+            host_spawn_permitted,
+            manual_instructions: get_manual_instructions(desktop.de, &bindings),
             has_conflicts: !conflicts.conflicts.is_empty(),
             conflict_count: conflicts.conflicts.len(),
             can_auto_resolve_conflicts: conflicts.can_auto_resolve,
+            binding_overrides,
+            wtype_available: input_simulator::wtype_available(),
+            ydotool_available: input_simulator::ydotool_status().is_ok(),
         }
     }
 }
 
+/// Other bindings a conflicting shortcut could be registered with
+#[derive(serde::Serialize)]
+pub struct BindingOverride {
+    pub shortcut_id: String,
+    pub name: String,
+    /// The binding that is taken, e.g. `<Super>v`
+    pub binding: String,
+    /// Free alternatives, best first
+    pub candidates: Vec<String>,
+}
+
+/// For each of our shortcuts with a conflict, the alternatives to its
+/// binding that nothing else on the desktop uses
+fn binding_overrides(
+    bindings: &HistoryBindings,
+    conflicts: &ConflictDetectionResult,
+) -> Vec<BindingOverride> {
+    let ours = conflict_shortcuts(bindings);
+    ours.iter()
+        // Each desktop writes the taken keys its own way (`Meta+V`, `$mod+v`),
+        // so conflicts are matched to our shortcut by name
+        .filter(|config| {
+            conflicts
+                .conflicts
+                .iter()
+                .any(|c| c.conflicts_with == config.name)
+        })
+        .map(|config| BindingOverride {
+            shortcut_id: config.id.to_string(),
+            name: config.name.to_string(),
+            binding: config.gnome_binding.clone(),
+            candidates: config
+                .binding
+                .alternatives()
+                .into_iter()
+                .filter(|candidate| ours.iter().all(|other| other.binding != *candidate))
+                .filter(|candidate| detect_binding_conflicts(candidate).conflicts.is_empty())
+                .map(|candidate| candidate.to_string())
+                .collect(),
+        })
+        .filter(|proposal| !proposal.candidates.is_empty())
+        .collect()
+}
+
 #[derive(serde::Serialize)]
 pub struct ShortcutToolsStatus {
     pub desktop_environment: String,
@@ -150,168 +341,357 @@ pub struct ShortcutToolsStatus {
     pub kde_tools_available: bool,
     pub xfce_tools_available: bool,
     pub can_register_automatically: bool,
+    /// Shortcuts are bound through the GlobalShortcuts portal
+    pub portal_available: bool,
+    /// We run in a Flatpak or other container
+    pub sandboxed: bool,
+    /// The sandbox lets us run the desktop's tools on the host with
+    /// `flatpak-spawn --host`
+    pub host_spawn_permitted: bool,
     pub manual_instructions: String,
     pub has_conflicts: bool,
     pub conflict_count: usize,
     pub can_auto_resolve_conflicts: bool,
+    /// Bindings to register instead of the ones that conflict
+    pub binding_overrides: Vec<BindingOverride>,
+    /// Wayland tools the shortcut's paste keystroke can be sent with
+    pub wtype_available: bool,
+    pub ydotool_available: bool,
+}
+
+/// We only append to a single config.py, not package-style configs
+fn qtile_config_exists() -> bool {
+    xdg_config_dir().is_some_and(|config| config.join("qtile/config.py").exists())
 }
 
 fn command_exists(cmd: &str) -> bool {
-    std::process::Command::new("which")
+    environment::host_command("which")
         .arg(cmd)
         .output()
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
 
-fn get_manual_instructions(de: &str) -> String {
-    match de {
-        "GNOME" => r#"**GNOME Settings:**
-1. Open Settings → Keyboard → Keyboard Shortcuts → Custom Shortcuts
-2. Click "+" to add a new shortcut
-3. Name: "Clipboard History"
-4. Command: `penguinclip`
-5. Shortcut: Press Super+V
+/// GNOME's note on freeing the Notification Center's Super+V, when the
+/// primary binding is still that default
+fn message_tray_note(desktop: &str, bindings: &HistoryBindings) -> String {
+    if bindings.primary != HistoryBindings::default().primary {
+        return String::new();
+    }
+    format!(
+        r#"
 
-**⚠️ Note:** GNOME uses Super+V for the Notification Center by default.
+**⚠️ Note:** {} uses Super+V for the Notification Center by default.
 To free up Super+V, run:
 ```
 gsettings set org.gnome.shell.keybindings toggle-message-tray "['<Super><Shift>v']"
-```"#
-            .to_string(),
+```"#,
+        desktop
+    )
+}
 
-        "Pop!_OS" => r#"**Pop!_OS / Pop Shell:**
+/// Setup steps for `de`, with the configured clipboard history `bindings`
+/// written the way that desktop writes them
+fn get_manual_instructions(de: DesktopEnvironment, bindings: &HistoryBindings) -> String {
+    let primary = &bindings.primary;
+    let secondary = &bindings.secondary;
+    match de {
+        // GNOME 40 and older had a Keyboard Shortcuts panel of its own
+        DesktopEnvironment::Gnome => format!(
+            r#"**GNOME Settings:**
+1. Open Settings → {}
+2. Click "+" to add a new shortcut
+3. Name: "Clipboard History"
+4. Command: `penguinclip`
+5. Shortcut: Press {}{}"#,
+            if session::gnome_shell_version().is_some_and(|major| major < 41) {
+                "Keyboard Shortcuts"
+            } else {
+                "Keyboard → Keyboard Shortcuts → Custom Shortcuts"
+            },
+            primary.label(),
+            message_tray_note("GNOME", bindings)
+        ),
+
+        DesktopEnvironment::PopOs => format!(
+            r#"**Pop!_OS / Pop Shell:**
 1. Open Settings → Keyboard → Keyboard Shortcuts → Custom Shortcuts
 2. Add a new shortcut with command: `penguinclip`
-3. Set the shortcut to Super+V
+3. Set the shortcut to {0}{1}
 
-**⚠️ Note:** Pop!_OS inherits GNOME's Super+V for Notification Center.
-To free up Super+V, run:
-```
-gsettings set org.gnome.shell.keybindings toggle-message-tray "['<Super><Shift>v']"
-```
-
-If Pop Shell uses Super+V for tiling, change it in:
-Settings → Keyboard → Customize Shortcuts → Pop Shell"#
-            .to_string(),
+If Pop Shell uses {0} for tiling, change it in:
+Settings → Keyboard → Customize Shortcuts → Pop Shell"#,
+            primary.label(),
+            message_tray_note("Pop!_OS (like GNOME)", bindings)
+        ),
 
-        "KDE Plasma" => r#"**KDE System Settings:**
+        DesktopEnvironment::KdePlasma => format!(
+            r#"**KDE System Settings:**
 1. Open System Settings → Shortcuts → Custom Shortcuts
 2. Click "Edit" → "New" → "Global Shortcut" → "Command/URL"
 3. Name: "Clipboard History"
-4. Trigger: Click and press Meta+V
+4. Trigger: Click and press {0}
 5. Action: `penguinclip`
 
-**⚠️ Note:** If Klipper (KDE's clipboard) uses Meta+V:
+**⚠️ Note:** If Klipper (KDE's clipboard) uses {0}:
 1. Right-click Klipper in system tray → Configure
-2. Change or disable its shortcut"#
-            .to_string(),
+2. Change or disable its shortcut"#,
+            primary.kde()
+        ),
 
-        "Cinnamon" => r#"**Cinnamon Settings:**
+        DesktopEnvironment::Cinnamon => format!(
+            r#"**Cinnamon Settings:**
 1. Open System Settings → Keyboard → Shortcuts → Custom Shortcuts
 2. Click "Add custom shortcut"
 3. Name: "Clipboard History"
 4. Command: `penguinclip`
-5. Click on the shortcut area and press Super+V"#
-            .to_string(),
+5. Click on the shortcut area and press {}"#,
+            primary.label()
+        ),
 
-        "XFCE" => r#"**XFCE Settings:**
+        DesktopEnvironment::Xfce => format!(
+            r#"**XFCE Settings:**
 1. Open Settings → Keyboard → Application Shortcuts
 2. Click "Add"
 3. Command: `penguinclip`
-4. Press Super+V when prompted"#
-            .to_string(),
+4. Press {} when prompted"#,
+            primary.label()
+        ),
 
-        "MATE" => r#"**MATE Control Center:**
+        DesktopEnvironment::Mate => format!(
+            r#"**MATE Control Center:**
 1. Open Control Center → Keyboard Shortcuts
 2. Click "Add"
 3. Name: "Clipboard History"
 4. Command: `penguinclip`
-5. Click on the shortcut and press Super+V"#
-            .to_string(),
+5. Click on the shortcut and press {}"#,
+            primary.label()
+        ),
 
-        "LXQt" => r#"**LXQt Configuration:**
+        DesktopEnvironment::Lxqt => format!(
+            r#"**LXQt Configuration:**
 1. Open LXQt Configuration → Shortcut Keys
 2. Click "Add"
 3. Description: "Clipboard History"
 4. Command: `penguinclip`
-5. Set shortcut to Meta+V"#
-            .to_string(),
+5. Set shortcut to {}"#,
+            primary.kde()
+        ),
 
-        "LXDE" => r#"**LXDE/Openbox:**
+        DesktopEnvironment::Lxde => format!(
+            r#"**LXDE/Openbox:**
 1. Edit ~/.config/openbox/lxde-rc.xml
 2. Add in <keyboard> section:
 
-<keybind key="Super_L+v">
+<keybind key="{}">
   <action name="Execute">
     <command>penguinclip</command>
   </action>
 </keybind>
 
-3. Run: openbox --reconfigure"#
-            .to_string(),
+3. Run: openbox --reconfigure"#,
+            primary.lxde()
+        ),
 
-        "COSMIC" => r#"**COSMIC Settings:**
+        DesktopEnvironment::Cosmic => format!(
+            r#"**COSMIC Settings:**
 1. Open Settings → Keyboard → Custom Shortcuts
 2. Add new shortcut
 3. Command: `penguinclip`
-4. Binding: Super+V
+4. Binding: {0}
 
-**Note:** If there's a conflict, check System shortcuts for Super+V bindings."#
-            .to_string(),
+**Note:** If there's a conflict, check System shortcuts for {0} bindings."#,
+            primary.label()
+        ),
 
-        "i3" => r#"**i3 Configuration:**
+        DesktopEnvironment::I3 => format!(
+            r#"**i3 Configuration:**
 1. Edit your i3 config: `~/.config/i3/config`
-2. Comment out or remove any existing `bindsym $mod+v` line
+2. Comment out or remove any existing `bindsym {0}` line
 3. Add this line:
 ```
-bindsym $mod+v exec penguinclip
+bindsym {0} exec penguinclip
 ```
 4. Reload i3: Press $mod+Shift+r
 
 **Alternative shortcut:**
 ```
-bindsym Ctrl+Mod1+v exec penguinclip
-```"#
-            .to_string(),
-
-        "Sway" => r#"**Sway Configuration:**
+bindsym {1} exec penguinclip
+```"#,
+            primary.i3(),
+            secondary.i3()
+        ),
+
+        DesktopEnvironment::Sway => format!(
+            r#"**Sway Configuration:**
 1. Edit your Sway config: `~/.config/sway/config`
-2. Comment out or remove any existing `bindsym $mod+v` line
+2. Comment out or remove any existing `bindsym {0}` line
 3. Add this line:
 ```
-bindsym $mod+v exec penguinclip
+bindsym {0} exec penguinclip
 ```
 4. Reload Sway: Press $mod+Shift+c
 
 **Alternative shortcut:**
 ```
-bindsym Ctrl+Mod1+v exec penguinclip
-```"#
-            .to_string(),
-
-        "Hyprland" => r#"**Hyprland Configuration:**
+bindsym {1} exec penguinclip
+```"#,
+            primary.i3(),
+            secondary.i3()
+        ),
+
+        DesktopEnvironment::Hyprland => format!(
+            r#"**Hyprland Configuration:**
 1. Edit your Hyprland config: `~/.config/hypr/hyprland.conf`
-2. Comment out or remove any existing `bind = SUPER, V, ...` line
+2. Comment out or remove any existing `bind = {0}, ...` line
 3. Add this line:
 ```
-bind = SUPER, V, exec, penguinclip
+bind = {0}, exec, penguinclip
 ```
 4. Config auto-reloads (or press Super+M to reload manually)
 
 **Alternative shortcut:**
 ```
-bind = CTRL ALT, V, exec, penguinclip
-```"#
-            .to_string(),
+bind = {1}, exec, penguinclip
+```"#,
+            primary.hyprland(),
+            secondary.hyprland()
+        ),
+
+        DesktopEnvironment::Budgie => format!(
+            r#"**Budgie Settings:**
+1. Open Budgie Control Center → Keyboard → Keyboard Shortcuts → Custom Shortcuts
+2. Click "+" to add a new shortcut
+3. Name: "Clipboard History"
+4. Command: `penguinclip`
+5. Shortcut: Press {0}
+
+**⚠️ Note:** On some distros Raven or notifications use {0}.
+Check Budgie Desktop Settings → Raven, or run:
+```
+gsettings list-recursively com.solus-project.budgie-wm | grep -i '{1}'
+```"#,
+            primary.label(),
+            primary.gnome()
+        ),
+
+        DesktopEnvironment::Enlightenment => format!(
+            r#"**Enlightenment Settings:**
+1. Open Settings → Input → Key Bindings
+2. Remove or change any existing {} binding
+3. Click "Add Binding" and press {}
+4. Under Action, choose Launch → Command
+5. Action Params: `penguinclip`
+6. Click Apply"#,
+            primary.kde().replace("Meta", "Win"),
+            primary.label()
+        ),
+
+        DesktopEnvironment::Awesome => format!(
+            r#"**awesome Configuration:**
+PenguinClip adds its keys while awesome runs and keeps them in
+`~/.config/awesome/penguinclip.lua`. To keep them after a restart:
+1. Edit your awesome config: `~/.config/awesome/rc.lua`
+2. Remove any existing `awful.key({0}, ...)` binding
+3. Add this line near the end, after your global keys are set:
+```
+require("penguinclip")
+```
+4. Restart awesome: Press Super+Ctrl+r
+
+**Or bind it yourself in rc.lua:**
+```
+awful.key({0}, function() awful.spawn("penguinclip") end,
+          {{ description = "Clipboard History", group = "penguinclip" }})
+```"#,
+            primary.awesome()
+        ),
+
+        DesktopEnvironment::Qtile => format!(
+            r#"**qtile Configuration:**
+1. Edit your qtile config: `~/.config/qtile/config.py`
+2. Remove any existing `Key({0}, ...)` entry from `keys`
+3. Add this entry to `keys`:
+```
+Key({0}, lazy.spawn("penguinclip"), desc="Clipboard History"),
+```
+4. Reload qtile: Press Super+Ctrl+r
+
+**Alternative shortcut:**
+```
+Key({1}, lazy.spawn("penguinclip"), desc="Clipboard History"),
+```"#,
+            primary.qtile(),
+            secondary.qtile()
+        ),
+
+        DesktopEnvironment::River => format!(
+            r#"**River Configuration:**
+1. Edit your River init: `~/.config/river/init`
+2. Remove any existing `riverctl map normal {0} ...` line
+3. Add this line:
+```
+riverctl map normal {0} spawn penguinclip
+```
+4. Run the same command in a terminal to apply it now
 
-        _ => r#"**Generic Instructions:**
+**Alternative shortcut:**
+```
+riverctl map normal {1} spawn penguinclip
+```"#,
+            primary.river(),
+            secondary.river()
+        ),
+
+        DesktopEnvironment::Wayfire => format!(
+            r#"**Wayfire Configuration:**
+1. Edit your Wayfire config: `~/.config/wayfire.ini`
+2. Remove or change any existing binding set to `{0}`
+3. Add these lines to the `[command]` section:
+```
+binding_penguinclip = {0}
+command_penguinclip = penguinclip
+```
+4. Wayfire reloads the config automatically
+
+**Alternative shortcut:**
+```
+binding_penguinclip = {1}
+```"#,
+            primary.wayfire(),
+            secondary.wayfire()
+        ),
+
+        DesktopEnvironment::Bspwm => format!(
+            r#"**bspwm / sxhkd Configuration:**
+1. Edit your sxhkd config: `~/.config/sxhkd/sxhkdrc`
+2. Comment out or remove any existing `{0}` block
+3. Add these lines:
+```
+{0}
+    penguinclip
+```
+4. Reload sxhkd: `pkill -USR1 -x sxhkd`
+
+**Alternative shortcut:**
+```
+{1}
+    penguinclip
+```"#,
+            primary.sxhkd(),
+            secondary.sxhkd()
+        ),
+
+        _ => format!(
+            r#"**Generic Instructions:**
 1. Open your desktop environment's keyboard shortcuts settings
 2. Add a new custom shortcut
 3. Command: `penguinclip`
-4. Shortcut: Super+V (or your preferred combination)
+4. Shortcut: {0} (or your preferred combination)
 
-**Alternative:** Use Ctrl+Alt+V if Super+V conflicts with your DE."#
-            .to_string(),
+**Alternative:** Use {1} if {0} conflicts with your DE."#,
+            primary.label(),
+            secondary.label()
+        ),
     }
 }
@@ -0,0 +1,271 @@
+//! Smart Actions
+//! Detects URLs, email addresses, hex colors and phone numbers in copied text
+//! at capture time, so the frontend can render action chips without
+//! re-parsing every item.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+// --- Constants ---
+
+/// Maximum detections stored per item.
+const MAX_DETECTIONS: usize = 8;
+
+/// Only the start of very long texts is scanned, keeping detection cheap
+/// enough for the watcher loop.
+const MAX_SCAN_BYTES: usize = 16 * 1024;
+
+/// Characters stripped from the end of a detected URL (sentence punctuation
+/// and closing quotes/brackets that are almost never part of the link).
+const URL_TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', '\'', '"', ']', '}'];
+
+/// Something actionable found in an item's text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SmartDetection {
+    /// An http(s)/ftp link, scheme lowercased
+    Url(String),
+    /// An email address
+    Email(String),
+    /// A 3- or 6-digit hex color, lowercased and including the `#`
+    Color { hex: String },
+    /// A phone number as written in the text
+    Phone(String),
+}
+
+// --- Patterns ---
+
+fn url_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"(?i)\b(?:https?|ftp)://[^\s<>"'`]+"#).unwrap())
+}
+
+fn email_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap()
+    })
+}
+
+fn color_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"#(?:[0-9a-fA-F]{6}|[0-9a-fA-F]{3})\b").unwrap())
+}
+
+fn phone_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)|\d{2,4})[\s.-]\d{3,4}[\s.-]\d{3,4}")
+            .unwrap()
+    })
+}
+
+// --- Extraction ---
+
+/// Strips trailing punctuation, and a closing parenthesis that has no
+/// matching opening one inside the URL (e.g. "(see https://x.org/a)").
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(URL_TRAILING_PUNCTUATION);
+        let trimmed = if trimmed.ends_with(')')
+            && trimmed.matches('(').count() < trimmed.matches(')').count()
+        {
+            &trimmed[..trimmed.len() - 1]
+        } else {
+            trimmed
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// Lowercases the scheme so `HTTPS://Example.com` opens like any other link.
+fn normalize_url(url: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, rest)) => format!("{}://{}", scheme.to_lowercase(), rest),
+        None => url.to_string(),
+    }
+}
+
+/// True if the match is not glued to surrounding word characters.
+fn is_isolated(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(|c| c.is_alphanumeric() || c == '&')
+        && !after.is_some_and(|c| c.is_alphanumeric())
+}
+
+/// Finds actionable values in `text`, URLs first, capped at
+/// `MAX_DETECTIONS`. Emails, colors and phone numbers inside a detected URL
+/// are not reported separately.
+pub fn detect(text: &str) -> Vec<SmartDetection> {
+    let mut end = text.len().min(MAX_SCAN_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let text = &text[..end];
+
+    let mut detections = Vec::new();
+    let mut url_spans = Vec::new();
+
+    for m in url_regex().find_iter(text) {
+        let url = trim_url(m.as_str());
+        // A bare scheme ("https://") is not a link.
+        if url
+            .split_once("://")
+            .is_some_and(|(_, rest)| !rest.is_empty())
+        {
+            url_spans.push(m.start()..m.start() + url.len());
+            detections.push(SmartDetection::Url(normalize_url(url)));
+        }
+    }
+    let in_url = |start: usize| url_spans.iter().any(|span| span.contains(&start));
+
+    for m in email_regex().find_iter(text) {
+        if !in_url(m.start()) {
+            detections.push(SmartDetection::Email(m.as_str().to_string()));
+        }
+    }
+
+    for m in color_regex().find_iter(text) {
+        if !in_url(m.start()) && is_isolated(text, m.start(), m.end()) {
+            detections.push(SmartDetection::Color {
+                hex: m.as_str().to_lowercase(),
+            });
+        }
+    }
+
+    for m in phone_regex().find_iter(text) {
+        let digits = m.as_str().chars().filter(char::is_ascii_digit).count();
+        if !in_url(m.start()) && (7..=15).contains(&digits) && is_isolated(text, m.start(), m.end())
+        {
+            detections.push(SmartDetection::Phone(m.as_str().to_string()));
+        }
+    }
+
+    detections.dedup();
+    detections.truncate(MAX_DETECTIONS);
+    detections
+}
+
+// --- Actions ---
+
+/// Opens a URL or email detection with the desktop's default handler
+/// (`xdg-open`). Colors and phone numbers have nothing to open.
+pub fn open_detection(detection: &SmartDetection) -> Result<(), String> {
+    let target = match detection {
+        SmartDetection::Url(url) => url.clone(),
+        SmartDetection::Email(email) => format!("mailto:{}", email),
+        other => return Err(format!("{:?} cannot be opened", other)),
+    };
+
+    let mut child = Command::new("xdg-open")
+        .arg(&target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn xdg-open: {}", e))?;
+
+    // Reap the child in the background so it doesn't linger as a zombie.
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(text: &str) -> Vec<String> {
+        detect(text)
+            .into_iter()
+            .filter_map(|d| match d {
+                SmartDetection::Url(url) => Some(url),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_url_trailing_punctuation_and_parens() {
+        assert_eq!(
+            urls("See https://example.com/docs."),
+            ["https://example.com/docs"]
+        );
+        assert_eq!(
+            urls("(via https://example.com/a)"),
+            ["https://example.com/a"]
+        );
+        assert_eq!(
+            urls("https://en.wikipedia.org/wiki/Rust_(programming_language), ok?"),
+            ["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+        );
+        assert_eq!(
+            urls("\"https://example.com/q?a=1\""),
+            ["https://example.com/q?a=1"]
+        );
+        assert!(urls("https://").is_empty());
+    }
+
+    #[test]
+    fn test_url_uppercase_scheme_is_normalized() {
+        assert_eq!(
+            urls("HTTPS://Example.com/Path"),
+            ["https://Example.com/Path"]
+        );
+        assert_eq!(urls("Ftp://files.example.org"), ["ftp://files.example.org"]);
+    }
+
+    #[test]
+    fn test_hex_colors() {
+        let colors = |text: &str| {
+            detect(text)
+                .into_iter()
+                .filter_map(|d| match d {
+                    SmartDetection::Color { hex } => Some(hex),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(colors("color: #FFF;"), ["#fff"]);
+        assert_eq!(colors("background: #1a2B3c"), ["#1a2b3c"]);
+        assert!(colors("#1234").is_empty(), "4 digits is not a color");
+        assert!(colors("#abcdefg").is_empty());
+        assert!(colors("issue#123abc").is_empty());
+        assert!(colors("&#123;").is_empty(), "HTML entity is not a color");
+        assert!(colors("https://example.com/#fff").is_empty());
+    }
+
+    #[test]
+    fn test_emails_and_phones() {
+        let found = detect("Mail jane.doe+tag@Example.co.uk or call +1 (555) 123-4567");
+        assert!(found.contains(&SmartDetection::Email(
+            "jane.doe+tag@Example.co.uk".to_string()
+        )));
+        assert!(found.contains(&SmartDetection::Phone("+1 (555) 123-4567".to_string())));
+
+        // Credentials embedded in a URL are not a separate email.
+        assert_eq!(
+            detect("https://user@example.com/x"),
+            [SmartDetection::Url(
+                "https://user@example.com/x".to_string()
+            )]
+        );
+        // Dates and versions are not phone numbers.
+        assert!(detect("2024-01-15 v1.2.3").is_empty());
+    }
+
+    #[test]
+    fn test_detections_are_capped() {
+        let text = (0..20)
+            .map(|i| format!("https://example.com/{} ", i))
+            .collect::<String>();
+        assert_eq!(detect(&text).len(), MAX_DETECTIONS);
+    }
+}
@@ -0,0 +1,99 @@
+//! Text Transforms
+//! Named transformations applied to an item's text before it is pasted
+//! (case changes, whitespace cleanup, JSON formatting).
+//!
+//! Adding a transform is one entry in `TRANSFORMS` plus a test.
+
+type TransformFn = fn(&str) -> Result<String, String>;
+
+/// Registry of available transforms, keyed by the name the frontend sends.
+const TRANSFORMS: &[(&str, TransformFn)] = &[
+    ("upper", |text| Ok(text.to_uppercase())),
+    ("lower", |text| Ok(text.to_lowercase())),
+    ("trim", |text| Ok(text.trim().to_string())),
+    ("collapse_whitespace", collapse_whitespace),
+    ("json_pretty", json_pretty),
+    ("json_minify", json_minify),
+];
+
+/// Names of all registered transforms, in registry order.
+pub fn available() -> Vec<&'static str> {
+    TRANSFORMS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Applies the transform called `name` to `text`.
+pub fn apply(name: &str, text: &str) -> Result<String, String> {
+    let (_, transform) = TRANSFORMS.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+        format!(
+            "Unknown transform '{}'. Available: {}",
+            name,
+            available().join(", ")
+        )
+    })?;
+    transform(text)
+}
+
+/// Replaces every run of whitespace (including newlines) with a single space
+/// and trims the ends.
+fn collapse_whitespace(text: &str) -> Result<String, String> {
+    Ok(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn parse_json(text: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(text.trim()).map_err(|e| format!("Not valid JSON: {}", e))
+}
+
+fn json_pretty(text: &str) -> Result<String, String> {
+    serde_json::to_string_pretty(&parse_json(text)?).map_err(|e| e.to_string())
+}
+
+fn json_minify(text: &str) -> Result<String, String> {
+    serde_json::to_string(&parse_json(text)?).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_and_whitespace_transforms() {
+        assert_eq!(apply("upper", "Grüße").unwrap(), "GRÜSSE");
+        assert_eq!(apply("lower", "HeLLo").unwrap(), "hello");
+        assert_eq!(apply("trim", "  padded \n").unwrap(), "padded");
+        assert_eq!(
+            apply("collapse_whitespace", " a \t b\n\n c ").unwrap(),
+            "a b c"
+        );
+    }
+
+    #[test]
+    fn test_json_transforms() {
+        assert_eq!(
+            apply("json_pretty", r#"{"a":[1,2]}"#).unwrap(),
+            "{\n  \"a\": [\n    1,\n    2\n  ]\n}"
+        );
+        assert_eq!(
+            apply("json_minify", "{\n  \"a\": 1\n}\n").unwrap(),
+            r#"{"a":1}"#
+        );
+        // Nothing but whitespace changes
+        let minified = apply("json_minify", r#"{"z": 1, "a": [2, {"b": null}]}"#).unwrap();
+        assert!(!minified.contains(char::is_whitespace));
+        assert_eq!(
+            parse_json(&minified).unwrap(),
+            serde_json::json!({"z": 1, "a": [2, {"b": null}]})
+        );
+        let err = apply("json_pretty", "{not json").unwrap_err();
+        assert!(
+            err.starts_with("Not valid JSON"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_unknown_transform_lists_available() {
+        let err = apply("rot13", "abc").unwrap_err();
+        assert!(err.contains("upper") && err.contains("json_minify"));
+    }
+}
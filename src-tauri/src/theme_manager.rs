@@ -1,8 +1,10 @@
 //! Theme Manager Module
-//! Detects system color scheme preference via XDG Desktop Portal.
+//! Detects system color scheme and accent color via XDG Desktop Portal.
 //! This is essential for DEs like COSMIC that use the portal standard
 //! instead of GNOME settings.
 
+use crate::environment::{self, host_command, DesktopEnvironment};
+use crate::paths::xdg_config_dir;
 use crate::user_settings::UserSettings;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -14,12 +16,18 @@ use tokio::sync::RwLock;
 /// Cached system theme preference
 static SYSTEM_THEME: OnceLock<RwLock<Option<ColorScheme>>> = OnceLock::new();
 
+/// Cached system accent color
+static SYSTEM_ACCENT: OnceLock<RwLock<Option<(u8, u8, u8)>>> = OnceLock::new();
+
 /// Flag to track if the event listener is running
 static EVENT_LISTENER_RUNNING: AtomicBool = AtomicBool::new(false);
 
 /// Cached setting for dynamic tray icon (avoids disk I/O in listener loop)
 static DYNAMIC_ICON_ENABLED: AtomicBool = AtomicBool::new(false);
 
+/// Cached setting for the symbolic (template) tray icon
+static SYMBOLIC_ICON_ENABLED: AtomicBool = AtomicBool::new(false);
+
 /// Color scheme values from the XDG Desktop Portal
 /// See: https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
@@ -60,62 +68,63 @@ pub struct ThemeInfo {
     pub source: String,
 }
 
+impl ThemeInfo {
+    fn new(scheme: ColorScheme, source: &str) -> Self {
+        ThemeInfo {
+            color_scheme: scheme,
+            prefers_dark: scheme.is_dark(),
+            source: source.to_string(),
+        }
+    }
+}
+
 /// Query the XDG Desktop Portal for the system color scheme.
 /// This works with COSMIC, GNOME, KDE, and other portal-compliant DEs.
+/// Without a portal answer, falls back to COSMIC's config file, GNOME's
+/// gsettings and, on KDE or an unknown desktop, KDE's kdeglobals, in that
+/// order.
 pub async fn get_system_color_scheme() -> ThemeInfo {
     // Try to get cached value first
     let cache = SYSTEM_THEME.get_or_init(|| RwLock::new(None));
 
     // Check cache
     if let Some(scheme) = *cache.read().await {
-        return ThemeInfo {
-            color_scheme: scheme,
-            prefers_dark: scheme.is_dark(),
-            source: "cache".to_string(),
-        };
+        return ThemeInfo::new(scheme, "cache");
     }
 
     // Query the portal
     match query_portal_color_scheme().await {
+        Ok(ColorScheme::NoPreference) => {
+            log::debug!("Portal reports no color scheme preference, trying fallbacks")
+        }
         Ok(scheme) => {
             // Cache the result
             *cache.write().await = Some(scheme);
-            ThemeInfo {
-                color_scheme: scheme,
-                prefers_dark: scheme.is_dark(),
-                source: "xdg-portal".to_string(),
-            }
+            return ThemeInfo::new(scheme, "xdg-portal");
         }
-        Err(e) => {
-            eprintln!(
-                "[ThemeManager] Portal query failed: {}, trying fallbacks",
-                e
-            );
-            // Try COSMIC config file fallback
-            match read_cosmic_theme_file() {
-                Ok(is_dark) => {
-                    let scheme = if is_dark {
-                        ColorScheme::Dark
-                    } else {
-                        ColorScheme::Light
-                    };
-                    ThemeInfo {
-                        color_scheme: scheme,
-                        prefers_dark: is_dark,
-                        source: "cosmic-config".to_string(),
-                    }
-                }
-                Err(_) => {
-                    // Default to no preference (let frontend handle it)
-                    ThemeInfo {
-                        color_scheme: ColorScheme::NoPreference,
-                        prefers_dark: false,
-                        source: "default".to_string(),
-                    }
-                }
-            }
+        Err(e) => log::warn!("Portal query failed: {}, trying fallbacks", e),
+    }
+
+    // The fallbacks aren't cached: no change signal would clear them
+    if let Ok(is_dark) = read_cosmic_theme_file() {
+        let scheme = if is_dark {
+            ColorScheme::Dark
+        } else {
+            ColorScheme::Light
+        };
+        return ThemeInfo::new(scheme, "cosmic-config");
+    }
+    if let Some(scheme) = read_gsettings_color_scheme() {
+        return ThemeInfo::new(scheme, "gsettings");
+    }
+    if trusts_kdeglobals(environment::detect().de) {
+        if let Some(scheme) = read_kdeglobals_color_scheme() {
+            return ThemeInfo::new(scheme, "kdeglobals");
         }
     }
+
+    // Default to no preference (let frontend handle it)
+    ThemeInfo::new(ColorScheme::NoPreference, "default")
 }
 
 /// Refresh the tray icon manually (e.g. after settings change).
@@ -128,10 +137,10 @@ pub async fn refresh_tray_icon(
     update_tray_icon_with_settings(app_handle, theme_info.prefers_dark, settings);
 }
 
-/// Query the XDG Desktop Portal via D-Bus
-async fn query_portal_color_scheme() -> Result<ColorScheme, Box<dyn std::error::Error + Send + Sync>>
-{
-    use zbus::zvariant::Value;
+/// Read one setting from the XDG Desktop Portal via D-Bus
+async fn read_portal_setting(
+    key: &str,
+) -> Result<zbus::zvariant::OwnedValue, Box<dyn std::error::Error + Send + Sync>> {
     use zbus::Connection;
 
     // Connect to the session bus
@@ -140,17 +149,26 @@ async fn query_portal_color_scheme() -> Result<ColorScheme, Box<dyn std::error::
     // Call the Settings.Read method
     // Interface: org.freedesktop.portal.Settings
     // Method: Read(namespace: string, key: string) -> variant
-    let reply: zbus::zvariant::OwnedValue = connection
+    let reply = connection
         .call_method(
             Some("org.freedesktop.portal.Desktop"),
             "/org/freedesktop/portal/desktop",
             Some("org.freedesktop.portal.Settings"),
             "Read",
-            &("org.freedesktop.appearance", "color-scheme"),
+            &("org.freedesktop.appearance", key),
         )
         .await?
         .body()
         .deserialize()?;
+    Ok(reply)
+}
+
+/// Query the portal's color scheme
+async fn query_portal_color_scheme() -> Result<ColorScheme, Box<dyn std::error::Error + Send + Sync>>
+{
+    use zbus::zvariant::Value;
+
+    let reply = read_portal_setting("color-scheme").await?;
 
     // The return value is a variant containing the actual value
     // For color-scheme, it's a uint32 wrapped in a variant (sometimes double-wrapped)
@@ -173,8 +191,8 @@ async fn query_portal_color_scheme() -> Result<ColorScheme, Box<dyn std::error::
 /// Fallback: Read COSMIC's theme config file directly
 /// Path: ~/.config/cosmic/com.system76.CosmicTheme.Mode/v1/is_dark
 fn read_cosmic_theme_file() -> Result<bool, Box<dyn std::error::Error>> {
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-    let config_path = home.join(".config/cosmic/com.system76.CosmicTheme.Mode/v1/is_dark");
+    let config = xdg_config_dir().ok_or("Could not find the config directory")?;
+    let config_path = config.join("cosmic/com.system76.CosmicTheme.Mode/v1/is_dark");
 
     if config_path.exists() {
         let content = std::fs::read_to_string(&config_path)?;
@@ -190,21 +208,261 @@ fn read_cosmic_theme_file() -> Result<bool, Box<dyn std::error::Error>> {
                 .into());
             }
         };
-        eprintln!(
-            "[ThemeManager] Read COSMIC config file: is_dark={}",
-            is_dark
-        );
+        log::debug!("Read COSMIC config file: is_dark={}", is_dark);
         return Ok(is_dark);
     }
 
     Err("COSMIC config file not found".into())
 }
 
-/// Clear the cached theme value (useful when system theme changes)
+/// Maps GNOME's `color-scheme` value; "default" is no preference
+fn gnome_color_scheme(value: &str) -> Option<ColorScheme> {
+    match value.trim().trim_matches('\'') {
+        "prefer-dark" => Some(ColorScheme::Dark),
+        "prefer-light" => Some(ColorScheme::Light),
+        _ => None,
+    }
+}
+
+/// Fallback for GNOME without the portal setting: `color-scheme` (GNOME 42
+/// and later), else a GTK theme named "...-dark"
+fn read_gsettings_color_scheme() -> Option<ColorScheme> {
+    let get = |key: &str| {
+        let output = host_command("gsettings")
+            .args(["get", "org.gnome.desktop.interface", key])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    get("color-scheme")
+        .and_then(|value| gnome_color_scheme(&value))
+        .or_else(|| {
+            get("gtk-theme")
+                .filter(|theme| theme.trim_matches('\'').to_lowercase().ends_with("-dark"))
+                .map(|_| ColorScheme::Dark)
+        })
+}
+
+/// The value of `key` in `[section]` of an INI file like kdeglobals
+fn ini_value<'a>(content: &'a str, section: &str, key: &str) -> Option<&'a str> {
+    let header = format!("[{}]", section);
+    content
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != header)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            (k.trim() == key).then_some(v.trim())
+        })
+}
+
+/// Parses KDE's "r,g,b" colors (an alpha component is ignored)
+fn parse_rgb(value: &str) -> Option<(u8, u8, u8)> {
+    let parts: Vec<u8> = value
+        .split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [r, g, b] | [r, g, b, _] => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+/// KDE's color scheme from kdeglobals: dark when the scheme's name says so,
+/// else judged by the window background. `None` when neither is set.
+fn kdeglobals_color_scheme(content: &str) -> Option<ColorScheme> {
+    let name = ini_value(content, "General", "ColorScheme");
+    if name.is_some_and(|name| name.to_lowercase().contains("dark")) {
+        return Some(ColorScheme::Dark);
+    }
+    match ini_value(content, "Colors:Window", "BackgroundNormal").and_then(parse_rgb) {
+        Some((r, g, b)) => {
+            let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+            Some(if luma < 128.0 {
+                ColorScheme::Dark
+            } else {
+                ColorScheme::Light
+            })
+        }
+        None => name.map(|_| ColorScheme::Light),
+    }
+}
+
+/// Whether kdeglobals speaks for the desktop's colors: on other known
+/// desktops it is just left over from running a KDE app.
+fn trusts_kdeglobals(de: DesktopEnvironment) -> bool {
+    matches!(
+        de,
+        DesktopEnvironment::KdePlasma | DesktopEnvironment::Unknown
+    )
+}
+
+/// Fallback for KDE and unknown desktops: `~/.config/kdeglobals`
+fn read_kdeglobals_color_scheme() -> Option<ColorScheme> {
+    let content = std::fs::read_to_string(xdg_config_dir()?.join("kdeglobals")).ok()?;
+    kdeglobals_color_scheme(&content)
+}
+
+/// The system accent color
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct AccentColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// "#rrggbb"
+    pub hex: String,
+    /// Source of the detection (for debugging)
+    pub source: String,
+}
+
+impl AccentColor {
+    fn new((r, g, b): (u8, u8, u8), source: &str) -> Self {
+        AccentColor {
+            r,
+            g,
+            b,
+            hex: format!("#{:02x}{:02x}{:02x}", r, g, b),
+            source: source.to_string(),
+        }
+    }
+}
+
+/// Query the system accent color: the portal first, then GNOME's gsettings,
+/// then KDE's kdeglobals. `None` when no source has one.
+pub async fn get_system_accent_color() -> Option<AccentColor> {
+    let cache = SYSTEM_ACCENT.get_or_init(|| RwLock::new(None));
+    if let Some(rgb) = *cache.read().await {
+        return Some(AccentColor::new(rgb, "cache"));
+    }
+
+    match read_portal_setting("accent-color").await {
+        Ok(value) => match accent_from_portal(&value) {
+            Some(rgb) => {
+                *cache.write().await = Some(rgb);
+                return Some(AccentColor::new(rgb, "xdg-portal"));
+            }
+            None => log::debug!("Portal accent color is unset, trying fallbacks"),
+        },
+        Err(e) => log::debug!("Portal accent color query failed: {}, trying fallbacks", e),
+    }
+
+    // Like the color scheme fallbacks, these aren't cached
+    if let Some(rgb) = read_gsettings_accent_color() {
+        return Some(AccentColor::new(rgb, "gsettings"));
+    }
+    if let Some(rgb) = read_kdeglobals_accent_color() {
+        return Some(AccentColor::new(rgb, "kdeglobals"));
+    }
+    None
+}
+
+/// Reads the portal's `accent-color`: an (ddd) tuple of channels in 0–1,
+/// where values outside that range mean unset. Some backends send 0–255
+/// instead, as doubles or integers; those are accepted too.
+fn accent_from_portal(value: &zbus::zvariant::Value) -> Option<(u8, u8, u8)> {
+    use zbus::zvariant::Value;
+
+    let mut value = value;
+    while let Value::Value(inner) = value {
+        value = inner;
+    }
+    let Value::Structure(structure) = value else {
+        return None;
+    };
+    let channels: Vec<f64> = structure
+        .fields()
+        .iter()
+        .map(|field| match field {
+            Value::F64(v) => Some(*v),
+            Value::U8(v) => Some(f64::from(*v)),
+            Value::I16(v) => Some(f64::from(*v)),
+            Value::U16(v) => Some(f64::from(*v)),
+            Value::I32(v) => Some(f64::from(*v)),
+            Value::U32(v) => Some(f64::from(*v)),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let doubles = structure
+        .fields()
+        .iter()
+        .all(|field| matches!(field, Value::F64(_)));
+    accent_from_channels(&channels, doubles)
+}
+
+/// Scales three channels to 0–255. Doubles all within 0–1 are fractions;
+/// anything else is taken as 0–255 already. `None` for other counts,
+/// negative or too large values.
+fn accent_from_channels(channels: &[f64], doubles: bool) -> Option<(u8, u8, u8)> {
+    let &[r, g, b] = channels else {
+        return None;
+    };
+    let channels = [r, g, b];
+    if channels.iter().any(|c| !(0.0..=255.0).contains(c)) {
+        return None;
+    }
+    let scale = if doubles && channels.iter().all(|c| *c <= 1.0) {
+        255.0
+    } else {
+        1.0
+    };
+    let [r, g, b] = channels.map(|c| (c * scale).round() as u8);
+    Some((r, g, b))
+}
+
+/// GNOME's named accent colors (`accent-color`, GNOME 47 and later), as
+/// libadwaita draws them
+fn gnome_accent_color(name: &str) -> Option<(u8, u8, u8)> {
+    match name.trim().trim_matches('\'') {
+        "blue" => Some((0x35, 0x84, 0xe4)),
+        "teal" => Some((0x21, 0x90, 0xa4)),
+        "green" => Some((0x3a, 0x94, 0x4a)),
+        "yellow" => Some((0xc8, 0x88, 0x00)),
+        "orange" => Some((0xed, 0x5b, 0x00)),
+        "red" => Some((0xe6, 0x2d, 0x42)),
+        "pink" => Some((0xd5, 0x61, 0x99)),
+        "purple" => Some((0x91, 0x41, 0xac)),
+        "slate" => Some((0x6f, 0x83, 0x96)),
+        _ => None,
+    }
+}
+
+fn read_gsettings_accent_color() -> Option<(u8, u8, u8)> {
+    let output = host_command("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "accent-color"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    gnome_accent_color(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// KDE's accent: the one picked in System Settings, else the color
+/// scheme's focus decoration
+fn kdeglobals_accent_color(content: &str) -> Option<(u8, u8, u8)> {
+    ini_value(content, "General", "AccentColor")
+        .or_else(|| ini_value(content, "Colors:Window", "DecorationFocus"))
+        .and_then(parse_rgb)
+}
+
+fn read_kdeglobals_accent_color() -> Option<(u8, u8, u8)> {
+    let content = std::fs::read_to_string(xdg_config_dir()?.join("kdeglobals")).ok()?;
+    kdeglobals_accent_color(&content)
+}
+
+/// Clear the cached theme and accent values (useful when system theme changes)
 pub async fn clear_theme_cache() {
     if let Some(cache) = SYSTEM_THEME.get() {
         *cache.write().await = None;
     }
+    if let Some(cache) = SYSTEM_ACCENT.get() {
+        *cache.write().await = None;
+    }
 }
 
 /// Start listening for theme changes via D-Bus signals
@@ -218,15 +476,15 @@ pub async fn start_theme_listener(
     }
 
     tokio::spawn(async move {
-        eprintln!("[ThemeManager] Starting D-Bus event listener for theme changes");
+        log::debug!("Starting D-Bus event listener for theme changes");
 
         match listen_for_theme_changes(app_handle).await {
             Ok(_) => {
-                eprintln!("[ThemeManager] Theme listener ended gracefully");
+                log::debug!("Theme listener ended gracefully");
                 EVENT_LISTENER_RUNNING.store(false, Ordering::SeqCst);
             }
             Err(e) => {
-                eprintln!("[ThemeManager] Theme listener error: {}", e);
+                log::warn!("Theme listener error: {}", e);
                 EVENT_LISTENER_RUNNING.store(false, Ordering::SeqCst);
             }
         }
@@ -235,45 +493,82 @@ pub async fn start_theme_listener(
     Ok(())
 }
 
-/// Update the cached dynamic tray icon setting
-pub fn update_dynamic_tray_flag(enabled: bool) {
-    DYNAMIC_ICON_ENABLED.store(enabled, Ordering::Relaxed);
+/// Update the cached tray icon settings
+pub fn update_tray_flags(settings: &UserSettings) {
+    DYNAMIC_ICON_ENABLED.store(settings.enable_dynamic_tray_icon, Ordering::Relaxed);
+    SYMBOLIC_ICON_ENABLED.store(settings.symbolic_tray_icon, Ordering::Relaxed);
 }
 
-/// Helper to get the initial tray icon.
-/// Uses a default icon initially to avoid blocking startup, then updates asynchronously.
-pub fn initial_tray_icon(_settings: &UserSettings) -> (Image<'static>, bool) {
-    eprintln!("[Tray] Initializing with default icon (non-blocking).");
-
-    let icon =
-        Image::from_bytes(include_bytes!("../icons/icon.png")).expect("Failed to load tray icon");
-    (icon, false)
+/// Which tray icon to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrayIcon {
+    /// The full-color app icon
+    Default,
+    /// White glyph, for dark panels
+    Light,
+    /// Dark glyph, for light panels
+    Dark,
+    /// Black monochrome glyph, for light panels. Also flagged as a template
+    /// icon, though Linux trays ignore that and show it as is
+    Symbolic,
 }
 
-fn get_icon_bytes(enable_dynamic: bool, _is_dark: bool) -> &'static [u8] {
-    if enable_dynamic {
-        // Both icon-light.png and icon-dark.png are currently identical,
-        // so use a single path regardless of theme until distinct icons are provided.
-        include_bytes!("../icons/icon-light.png")
-    } else {
-        include_bytes!("../icons/icon.png")
+impl TrayIcon {
+    /// The symbolic icon follows the color scheme whether or not the
+    /// dynamic icon is on: since the panel won't recolor it, dark panels get
+    /// the white glyph instead.
+    fn select(enable_dynamic: bool, enable_symbolic: bool, is_dark: bool) -> Self {
+        match (enable_symbolic, enable_dynamic, is_dark) {
+            (true, _, true) => TrayIcon::Light,
+            (true, _, false) => TrayIcon::Symbolic,
+            (false, true, true) => TrayIcon::Light,
+            (false, true, false) => TrayIcon::Dark,
+            (false, false, _) => TrayIcon::Default,
+        }
+    }
+
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            TrayIcon::Default => include_bytes!("../icons/icon.png"),
+            TrayIcon::Light => include_bytes!("../icons/icon-light.png"),
+            TrayIcon::Dark => include_bytes!("../icons/icon-dark.png"),
+            TrayIcon::Symbolic => include_bytes!("../icons/icon-symbolic.png"),
+        }
+    }
+
+    fn is_template(self) -> bool {
+        self == TrayIcon::Symbolic
     }
 }
 
-fn apply_icon_to_tray(app: &tauri::AppHandle, icon_bytes: &[u8]) {
+/// Helper to get the initial tray icon.
+/// Uses a theme-independent icon initially to avoid blocking startup; the
+/// dynamic variants are picked asynchronously once the color scheme is known.
+pub fn initial_tray_icon(settings: &UserSettings) -> (Image<'static>, bool) {
+    log::debug!("Initializing with default icon (non-blocking).");
+
+    let variant = TrayIcon::select(false, settings.symbolic_tray_icon, false);
+    let icon = Image::from_bytes(variant.bytes()).expect("Failed to load tray icon");
+    (icon, variant.is_template())
+}
+
+fn apply_icon_to_tray(app: &tauri::AppHandle, variant: TrayIcon) {
     if let Some(tray) = app.tray_by_id("main-tray") {
-        if let Ok(icon) = Image::from_bytes(icon_bytes) {
+        if let Ok(icon) = Image::from_bytes(variant.bytes()) {
             let _ = tray.set_icon(Some(icon));
-            let _ = tray.set_icon_as_template(false);
+            let _ = tray.set_icon_as_template(variant.is_template());
         }
     }
 }
 
 fn update_tray_icon(app: &tauri::AppHandle, is_dark: bool) {
-    // Determine target based on cached atomic setting (avoids disk I/O)
-    let enable_dynamic = DYNAMIC_ICON_ENABLED.load(Ordering::Relaxed);
-    let icon_bytes = get_icon_bytes(enable_dynamic, is_dark);
-    apply_icon_to_tray(app, icon_bytes);
+    // Determine target based on cached atomic settings (avoids disk I/O)
+    let variant = TrayIcon::select(
+        DYNAMIC_ICON_ENABLED.load(Ordering::Relaxed),
+        SYMBOLIC_ICON_ENABLED.load(Ordering::Relaxed),
+        is_dark,
+    );
+    apply_icon_to_tray(app, variant);
 }
 
 /// Optimized update that takes the settings directly
@@ -282,8 +577,12 @@ pub fn update_tray_icon_with_settings(
     is_dark: bool,
     settings: &UserSettings,
 ) {
-    let icon_bytes = get_icon_bytes(settings.enable_dynamic_tray_icon, is_dark);
-    apply_icon_to_tray(app, icon_bytes);
+    let variant = TrayIcon::select(
+        settings.enable_dynamic_tray_icon,
+        settings.symbolic_tray_icon,
+        is_dark,
+    );
+    apply_icon_to_tray(app, variant);
 }
 
 /// Listen for SettingChanged signals from the XDG Desktop Portal
@@ -307,7 +606,14 @@ async fn listen_for_theme_changes(
 
     let mut stream = MessageStream::for_match_rule(rule, &connection, None).await?;
 
-    eprintln!("[ThemeManager] Listening for theme change signals...");
+    // Older GNOME portals lack the appearance namespace but still relay
+    // GNOME's own interface settings
+    let watch_gnome_interface = query_portal_color_scheme().await.is_err();
+    if watch_gnome_interface {
+        log::debug!("No portal appearance namespace; watching org.gnome.desktop.interface");
+    }
+
+    log::debug!("Listening for theme change signals...");
 
     while let Some(msg) = stream.next().await {
         if let Ok(msg) = msg {
@@ -316,51 +622,58 @@ async fn listen_for_theme_changes(
             if let Ok((namespace, key, value)) =
                 body.deserialize::<(String, String, zbus::zvariant::OwnedValue)>()
             {
-                if namespace == "org.freedesktop.appearance" && key == "color-scheme" {
-                    // Parse the new color scheme value
-                    if let Ok(color_value) = value.downcast_ref::<u32>() {
-                        let scheme = ColorScheme::from_portal_value(color_value);
-
-                        // Check if theme actually changed before emitting
-                        let cache = SYSTEM_THEME.get_or_init(|| RwLock::new(None));
-                        let mut cache_guard = cache.write().await;
-                        let previous_scheme = *cache_guard;
-
-                        // Represent NoPreference by clearing the cache (None)
-                        // This allows the frontend to fall back to CSS media queries
-                        let new_cache_value = if scheme == ColorScheme::NoPreference {
-                            None
-                        } else {
-                            Some(scheme)
+                if namespace == "org.freedesktop.appearance" && key == "accent-color" {
+                    update_accent_color(&app_handle, &value).await;
+                    continue;
+                }
+
+                // Parse the new color scheme value
+                let changed_scheme = match (namespace.as_str(), key.as_str()) {
+                    ("org.freedesktop.appearance", "color-scheme") => value
+                        .downcast_ref::<u32>()
+                        .ok()
+                        .map(ColorScheme::from_portal_value),
+                    ("org.gnome.desktop.interface", "color-scheme") if watch_gnome_interface => {
+                        value.downcast_ref::<&str>().ok().map(|value| {
+                            gnome_color_scheme(value).unwrap_or(ColorScheme::NoPreference)
+                        })
+                    }
+                    _ => None,
+                };
+                if let Some(scheme) = changed_scheme {
+                    // Check if theme actually changed before emitting
+                    let cache = SYSTEM_THEME.get_or_init(|| RwLock::new(None));
+                    let mut cache_guard = cache.write().await;
+                    let previous_scheme = *cache_guard;
+
+                    // Represent NoPreference by clearing the cache (None)
+                    // This allows the frontend to fall back to CSS media queries
+                    let new_cache_value = if scheme == ColorScheme::NoPreference {
+                        None
+                    } else {
+                        Some(scheme)
+                    };
+
+                    // Only emit if the theme actually changed
+                    if previous_scheme != new_cache_value {
+                        log::debug!("Theme changed via D-Bus signal: {:?}", scheme);
+
+                        // Update cache to reflect the new state
+                        *cache_guard = new_cache_value;
+
+                        // Emit Tauri event to notify frontend
+                        let theme_info = ThemeInfo {
+                            color_scheme: scheme,
+                            prefers_dark: scheme.is_dark(),
+                            source: "dbus-signal".to_string(),
                         };
 
-                        // Only emit if the theme actually changed
-                        if previous_scheme != new_cache_value {
-                            eprintln!(
-                                "[ThemeManager] Theme changed via D-Bus signal: {:?}",
-                                scheme
-                            );
-
-                            // Update cache to reflect the new state
-                            *cache_guard = new_cache_value;
-
-                            // Emit Tauri event to notify frontend
-                            let theme_info = ThemeInfo {
-                                color_scheme: scheme,
-                                prefers_dark: scheme.is_dark(),
-                                source: "dbus-signal".to_string(),
-                            };
-
-                            if let Err(e) = app_handle.emit("system-theme-changed", &theme_info) {
-                                eprintln!(
-                                    "[ThemeManager] Failed to emit theme change event: {}",
-                                    e
-                                );
-                            }
-
-                            // Also update the tray icon immediately
-                            update_tray_icon(&app_handle, scheme.is_dark());
+                        if let Err(e) = app_handle.emit("system-theme-changed", &theme_info) {
+                            log::warn!("Failed to emit theme change event: {}", e);
                         }
+
+                        // Also update the tray icon immediately
+                        update_tray_icon(&app_handle, scheme.is_dark());
                     }
                 }
             }
@@ -370,6 +683,26 @@ async fn listen_for_theme_changes(
     Ok(())
 }
 
+/// Caches an accent color from a SettingChanged signal and tells the
+/// frontend, which gets `null` when the accent was unset
+async fn update_accent_color(app_handle: &tauri::AppHandle, value: &zbus::zvariant::Value<'_>) {
+    use tauri::Emitter;
+
+    let rgb = accent_from_portal(value);
+    let cache = SYSTEM_ACCENT.get_or_init(|| RwLock::new(None));
+    let mut cache_guard = cache.write().await;
+    if *cache_guard == rgb {
+        return;
+    }
+    *cache_guard = rgb;
+    log::debug!("Accent color changed via D-Bus signal: {:?}", rgb);
+
+    let accent = rgb.map(|rgb| AccentColor::new(rgb, "dbus-signal"));
+    if let Err(e) = app_handle.emit("system-accent-changed", &accent) {
+        log::warn!("Failed to emit accent change event: {}", e);
+    }
+}
+
 /// Check if the event listener is running
 pub fn is_event_listener_running() -> bool {
     EVENT_LISTENER_RUNNING.load(Ordering::SeqCst)
@@ -390,6 +723,128 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cosmic_theme_file_follows_xdg_config_home() {
+        let config = std::env::temp_dir().join("penguinclip_test_cosmic_theme");
+        let dir = config.join("cosmic/com.system76.CosmicTheme.Mode/v1");
+        let _ = std::fs::remove_dir_all(&config);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("is_dark"), "true\n").unwrap();
+
+        let is_dark = crate::paths::with_config_home(&config, || read_cosmic_theme_file().ok());
+        assert_eq!(is_dark, Some(true));
+        let _ = std::fs::remove_dir_all(&config);
+    }
+
+    const BREEZE_KDEGLOBALS: &str = "[ColorEffects:Disabled]
+Color=56,56,56
+
+[Colors:Window]
+BackgroundAlternate=227,229,231
+BackgroundNormal=239,240,241
+DecorationFocus=61,174,233
+
+[General]
+ColorScheme=Breeze
+Name=Breeze
+";
+
+    const BREEZE_DARK_KDEGLOBALS: &str = "[Colors:Window]
+BackgroundAlternate=42,46,50
+BackgroundNormal=49,54,59
+DecorationFocus=61,174,233
+
+[General]
+ColorScheme=BreezeDark
+Name=Breeze Dark
+";
+
+    #[test]
+    fn test_kdeglobals_color_scheme() {
+        assert_eq!(
+            kdeglobals_color_scheme(BREEZE_KDEGLOBALS),
+            Some(ColorScheme::Light)
+        );
+        assert_eq!(
+            kdeglobals_color_scheme(BREEZE_DARK_KDEGLOBALS),
+            Some(ColorScheme::Dark)
+        );
+        // A dark scheme without "dark" in its name, judged by the background
+        let custom = "[Colors:Window]\nBackgroundNormal=22,25,37\n\n[General]\nColorScheme=Sweet\n";
+        assert_eq!(kdeglobals_color_scheme(custom), Some(ColorScheme::Dark));
+        assert_eq!(kdeglobals_color_scheme("[General]\nfixed=Hack,10\n"), None);
+        assert_eq!(
+            ini_value(BREEZE_KDEGLOBALS, "Colors:Window", "DecorationFocus"),
+            Some("61,174,233")
+        );
+        assert_eq!(ini_value(BREEZE_KDEGLOBALS, "General", "Color"), None);
+        assert_eq!(parse_rgb("61, 174, 233"), Some((61, 174, 233)));
+        assert!(trusts_kdeglobals(DesktopEnvironment::KdePlasma));
+        assert!(trusts_kdeglobals(DesktopEnvironment::Unknown));
+        assert!(!trusts_kdeglobals(DesktopEnvironment::Gnome));
+        assert!(!trusts_kdeglobals(DesktopEnvironment::Hyprland));
+        assert_eq!(parse_rgb("61,174"), None);
+    }
+
+    #[test]
+    fn test_accent_from_portal() {
+        use zbus::zvariant::Value;
+
+        // The spec's (ddd) in 0–1, wrapped in a variant as Read returns it
+        let fractions = Value::Value(Box::new(Value::from((0.2_f64, 0.5176_f64, 0.8941_f64))));
+        assert_eq!(accent_from_portal(&fractions), Some((51, 132, 228)));
+        // Doubles and integers in 0–255
+        let doubles = Value::from((53.0_f64, 132.0_f64, 228.0_f64));
+        assert_eq!(accent_from_portal(&doubles), Some((53, 132, 228)));
+        let ints = Value::from((53_u32, 132_u32, 228_u32));
+        assert_eq!(accent_from_portal(&ints), Some((53, 132, 228)));
+        // Out of range means unset
+        let unset = Value::from((-1.0_f64, -1.0_f64, -1.0_f64));
+        assert_eq!(accent_from_portal(&unset), None);
+        assert_eq!(accent_from_portal(&Value::from(1_u32)), None);
+        assert_eq!(accent_from_channels(&[0.1, 0.2], true), None);
+        assert_eq!(
+            accent_from_channels(&[1.0, 1.0, 1.0], true),
+            Some((255, 255, 255))
+        );
+    }
+
+    #[test]
+    fn test_accent_fallbacks() {
+        assert_eq!(gnome_accent_color("'blue'\n"), Some((0x35, 0x84, 0xe4)));
+        assert_eq!(gnome_accent_color("'magenta'"), None);
+        assert_eq!(
+            kdeglobals_accent_color(BREEZE_DARK_KDEGLOBALS),
+            Some((61, 174, 233))
+        );
+        let picked =
+            "[General]\nAccentColor=233,100,61\n\n[Colors:Window]\nDecorationFocus=61,174,233\n";
+        assert_eq!(kdeglobals_accent_color(picked), Some((233, 100, 61)));
+        assert_eq!(AccentColor::new((61, 174, 233), "test").hex, "#3daee9");
+    }
+
+    #[test]
+    fn test_gnome_color_scheme() {
+        assert_eq!(
+            gnome_color_scheme("'prefer-dark'\n"),
+            Some(ColorScheme::Dark)
+        );
+        assert_eq!(gnome_color_scheme("prefer-light"), Some(ColorScheme::Light));
+        assert_eq!(gnome_color_scheme("'default'"), None);
+    }
+
+    #[test]
+    fn test_tray_icon_select() {
+        assert_eq!(TrayIcon::select(true, false, true), TrayIcon::Light);
+        assert_eq!(TrayIcon::select(true, false, false), TrayIcon::Dark);
+        assert_eq!(TrayIcon::select(false, false, true), TrayIcon::Default);
+        assert_eq!(TrayIcon::select(true, true, false), TrayIcon::Symbolic);
+        assert_eq!(TrayIcon::select(false, true, false), TrayIcon::Symbolic);
+        assert_eq!(TrayIcon::select(false, true, true), TrayIcon::Light);
+        assert!(TrayIcon::Symbolic.is_template());
+        assert_ne!(TrayIcon::Light.bytes(), TrayIcon::Dark.bytes());
+    }
+
     #[test]
     fn test_is_dark() {
         assert!(ColorScheme::Dark.is_dark());
@@ -1,23 +1,33 @@
 //! User Settings Module
 //! Handles persistence of user preferences (theme mode, background opacity) in a separate JSON file.
 
-use log::warn;
+use crate::atomic_write::{self, LoadIssue};
+use crate::input_simulator::{PasteCombo, PasteConfig, PasteMethod, PasteRule, PasteTimings};
+use crate::linux_shortcut_manager::{HistoryBindings, KeyBinding};
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
+pub mod migrations;
+
 const USER_SETTINGS_FILE: &str = "user_settings.json";
 
 /// User-configurable settings for the application
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserSettings {
+    /// Settings file schema version, see `migrations`. Always written as
+    /// `migrations::CURRENT_VERSION`; a file from a newer build is never
+    /// overwritten.
+    #[serde(default)]
+    pub schema_version: u32,
     /// Theme mode: "system", "dark", or "light"
     pub theme_mode: String,
     /// Background opacity for dark mode (0.0 to 1.0)
-    /// Default matches the original glass-effect alpha of 0.05
+    /// Defaults to 1.0 (fully opaque)
     pub dark_background_opacity: f32,
     /// Background opacity for light mode (0.0 to 1.0)
-    /// Default matches the original glass-effect-light alpha of 0.85
+    /// Defaults to 1.0 (fully opaque)
     pub light_background_opacity: f32,
 
     // --- Feature Flags ---
@@ -26,6 +36,10 @@ pub struct UserSettings {
     #[serde(default = "default_true")]
     pub enable_dynamic_tray_icon: bool,
 
+    /// Use a monochrome tray icon, white or black to suit the color scheme
+    #[serde(default)]
+    pub symbolic_tray_icon: bool,
+
     /// Enable Smart Actions (URL, Color, Email detection)
     #[serde(default = "default_true")]
     pub enable_smart_actions: bool,
@@ -39,6 +53,31 @@ pub struct UserSettings {
     #[serde(default = "default_max_history_size")]
     pub max_history_size: usize,
 
+    /// Show pinned items as a block at the top, newest pin first
+    #[serde(default = "default_true")]
+    pub pinned_on_top: bool,
+
+    /// Maximum number of pinned items (0 means unlimited)
+    #[serde(default)]
+    pub max_pinned_items: usize,
+
+    /// Numbered quick paste (`--paste-index N`) counts only unpinned items
+    #[serde(default = "default_true")]
+    pub quick_paste_skip_pinned: bool,
+
+    /// Largest text item recorded, in bytes (1 KiB to 256 MiB)
+    #[serde(default = "default_max_text_item_bytes")]
+    pub max_text_item_bytes: usize,
+
+    /// Largest image recorded, in raw RGBA bytes (1 MiB to 256 MiB)
+    #[serde(default = "default_max_image_item_bytes")]
+    pub max_image_item_bytes: usize,
+
+    /// What to do with text over the size limit: "truncate" or "skip".
+    /// Oversized images are always skipped.
+    #[serde(default = "default_oversized_text_action")]
+    pub oversized_text_action: String,
+
     /// Auto-delete interval value (0 means disabled)
     #[serde(default = "default_zero")]
     pub auto_delete_interval: u64,
@@ -53,11 +92,96 @@ pub struct UserSettings {
     #[serde(default)]
     pub excluded_patterns: Vec<String>,
 
+    /// Extra application names (WM_CLASS) whose copies are never recorded, on
+    /// top of the built-in password-manager list.
+    #[serde(default)]
+    pub excluded_apps: Vec<String>,
+
+    /// Flag items that look like secrets (API keys, tokens, card numbers,
+    /// random passwords) and remove them after `sensitive_ttl_seconds`,
+    /// regardless of the auto-delete interval. Opt-in.
+    #[serde(default)]
+    pub auto_expire_sensitive: bool,
+
+    /// Lifetime of items flagged as sensitive, in seconds (5 to 86400)
+    #[serde(default = "default_sensitive_ttl")]
+    pub sensitive_ttl_seconds: u64,
+
+    // --- Selections ---
+    /// Also record text that is merely selected (the X11 PRIMARY selection,
+    /// pasted with a middle click). Opt-in; X11 only.
+    #[serde(default)]
+    pub track_primary_selection: bool,
+
+    // --- Pasting ---
+    /// Delays around the simulated paste keystroke; raise them for slow VMs
+    #[serde(default)]
+    pub paste_timing: PasteTimings,
+
+    /// Keystroke sent to paste, e.g. "Alt+V" (empty = use `paste_method`)
+    #[serde(default)]
+    pub paste_combo: String,
+
+    /// Paste method for apps without a rule: "auto" (Ctrl+Shift+V in
+    /// terminals, else Ctrl+V), "ctrl-v", "ctrl-shift-v", "shift-insert" or
+    /// "type" (type the text out)
+    #[serde(default = "default_paste_method")]
+    pub paste_method: String,
+
+    /// Per-app paste methods, matched on WM_CLASS (X11 only); the first
+    /// matching rule wins
+    #[serde(default)]
+    pub app_paste_rules: Vec<AppPasteRule>,
+
+    /// Delay between characters when typing an item out instead of
+    /// pasting it (0 to 1000 ms)
+    #[serde(default = "default_type_char_delay")]
+    pub type_char_delay_ms: u64,
+
+    // --- Shortcuts ---
+    /// Global shortcut that opens the clipboard history, e.g. "<Super>v"
+    #[serde(default = "default_shortcut_primary")]
+    pub shortcut_primary: String,
+
+    /// Second shortcut for the history, for desktops that keep the first
+    #[serde(default = "default_shortcut_secondary")]
+    pub shortcut_secondary: String,
+
+    /// Bindings registered instead of the configured ones, by shortcut id
+    /// (`penguinclip` → `<Super><Shift>v`), for desktops where the default
+    /// is taken. Also records what was registered, so unregistering removes
+    /// the right binding.
+    #[serde(default)]
+    pub shortcut_overrides: BTreeMap<String, String>,
+
+    /// Ids of the shortcuts the user unregistered, so startup and rebinding
+    /// leave them unregistered. Registering one by id enables it again.
+    #[serde(default)]
+    pub disabled_shortcuts: BTreeSet<String>,
+
+    // --- Window ---
+    /// When the clipboard window hides by itself: "on_focus_loss" (when
+    /// another window is focused, or after pasting), "on_paste" (only after
+    /// pasting) or "manual" (only on Esc or the close button)
+    #[serde(default = "default_window_close_policy")]
+    pub window_close_policy: String,
+
+    // --- Debugging ---
+    /// Always poll the clipboard instead of using X11/Wayland change events.
+    /// Read at startup.
+    #[serde(default)]
+    pub force_clipboard_polling: bool,
+
     // --- Custom Data ---
     /// User-defined Kaomojis
     #[serde(default)]
     pub custom_kaomojis: Vec<CustomKaomoji>,
 
+    /// Days after which an emoji use counts half as much when ranking the
+    /// most used emojis (1 to 365, default 30)
+    #[serde(default = "default_emoji_decay_half_life_days")]
+    pub emoji_decay_half_life_days: f64,
+
     // --- UI Scale ---
     /// UI scale factor for the clipboard window (0.5 to 2.0, default 1.0)
     #[serde(default = "default_ui_scale")]
@@ -67,6 +191,69 @@ pub struct UserSettings {
     /// Tenor API key for GIF search (empty = disabled, user must provide their own)
     #[serde(default)]
     pub tenor_api_key: String,
+
+    /// Transcode animated WebP GIFs to real GIFs before pasting. MP4-only
+    /// results are rejected while this is on.
+    #[serde(default)]
+    pub convert_to_gif: bool,
+
+    /// How GIFs are put on the clipboard: "uri-list" (a file link),
+    /// "image-data" (raw image bytes), or "auto" (chosen per target app)
+    #[serde(default = "default_gif_paste_format")]
+    pub gif_paste_format: String,
+
+    /// Hosts GIFs may be downloaded from, including their subdomains
+    #[serde(default = "default_gif_allowed_hosts")]
+    pub gif_allowed_hosts: Vec<String>,
+
+    /// Download GIFs from any public host, ignoring `gif_allowed_hosts`.
+    /// Local and private addresses are still refused.
+    #[serde(default)]
+    pub gif_allow_any_host: bool,
+
+    /// Also accept plain http GIF URLs
+    #[serde(default)]
+    pub gif_allow_http: bool,
+}
+
+/// When the clipboard window hides without being closed explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowClosePolicy {
+    OnFocusLoss,
+    OnPaste,
+    Manual,
+}
+
+impl WindowClosePolicy {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "on_focus_loss" => Some(Self::OnFocusLoss),
+            "on_paste" => Some(Self::OnPaste),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+
+    pub fn hides_on_focus_loss(self) -> bool {
+        self == Self::OnFocusLoss
+    }
+
+    pub fn hides_on_paste(self) -> bool {
+        self != Self::Manual
+    }
+}
+
+/// Pastes into windows matching `wm_class_pattern` with `method` (a
+/// `paste_method` value).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppPasteRule {
+    /// Substring of the WM_CLASS instance or class name, or a glob with
+    /// `*` and `?` matching a whole name
+    pub wm_class_pattern: String,
+    pub method: String,
+    /// Send Ctrl+Shift+V instead of Ctrl+V ("auto" and "ctrl-v" only)
+    #[serde(default)]
+    pub use_shift: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -85,10 +272,61 @@ fn default_max_history_size() -> usize {
     crate::clipboard_manager::DEFAULT_MAX_HISTORY_SIZE
 }
 
+fn default_max_text_item_bytes() -> usize {
+    crate::clipboard_manager::DEFAULT_MAX_TEXT_ITEM_BYTES
+}
+
+fn default_max_image_item_bytes() -> usize {
+    crate::clipboard_manager::DEFAULT_MAX_IMAGE_ITEM_BYTES
+}
+
+fn default_oversized_text_action() -> String {
+    "truncate".to_string()
+}
+
+fn default_sensitive_ttl() -> u64 {
+    crate::clipboard_manager::DEFAULT_SENSITIVE_TTL_SECS
+}
+
+fn default_gif_paste_format() -> String {
+    "auto".to_string()
+}
+
+fn default_gif_allowed_hosts() -> Vec<String> {
+    crate::gif_manager::DEFAULT_GIF_HOSTS
+        .iter()
+        .map(|h| h.to_string())
+        .collect()
+}
+
+fn default_paste_method() -> String {
+    "auto".to_string()
+}
+
+fn default_window_close_policy() -> String {
+    "on_focus_loss".to_string()
+}
+
+fn default_shortcut_primary() -> String {
+    HistoryBindings::default().primary.to_string()
+}
+
+fn default_shortcut_secondary() -> String {
+    HistoryBindings::default().secondary.to_string()
+}
+
+fn default_type_char_delay() -> u64 {
+    10
+}
+
 fn default_ui_scale() -> f32 {
     1.0
 }
 
+fn default_emoji_decay_half_life_days() -> f64 {
+    crate::emoji_manager::DEFAULT_DECAY_HALF_LIFE_DAYS
+}
+
 fn default_zero() -> u64 {
     0
 }
@@ -100,24 +338,136 @@ fn default_unit() -> String {
 impl Default for UserSettings {
     fn default() -> Self {
         Self {
+            schema_version: migrations::CURRENT_VERSION,
             theme_mode: "system".to_string(),
             dark_background_opacity: 1.0,
             light_background_opacity: 1.0,
             enable_dynamic_tray_icon: true,
+            symbolic_tray_icon: false,
             enable_smart_actions: true,
             enable_ui_polish: true,
             max_history_size: default_max_history_size(),
+            pinned_on_top: true,
+            max_pinned_items: 0,
+            quick_paste_skip_pinned: true,
+            max_text_item_bytes: default_max_text_item_bytes(),
+            max_image_item_bytes: default_max_image_item_bytes(),
+            oversized_text_action: default_oversized_text_action(),
             auto_delete_interval: 0,
             auto_delete_unit: "hours".to_string(),
             excluded_patterns: Vec::new(),
+            excluded_apps: Vec::new(),
+            auto_expire_sensitive: false,
+            sensitive_ttl_seconds: default_sensitive_ttl(),
+            track_primary_selection: false,
+            paste_timing: PasteTimings::default(),
+            paste_combo: String::new(),
+            paste_method: default_paste_method(),
+            app_paste_rules: Vec::new(),
+            type_char_delay_ms: default_type_char_delay(),
+            shortcut_primary: default_shortcut_primary(),
+            shortcut_secondary: default_shortcut_secondary(),
+            shortcut_overrides: BTreeMap::new(),
+            disabled_shortcuts: BTreeSet::new(),
+            window_close_policy: default_window_close_policy(),
+            force_clipboard_polling: false,
             custom_kaomojis: Vec::new(),
+            emoji_decay_half_life_days: default_emoji_decay_half_life_days(),
             ui_scale: default_ui_scale(),
             tenor_api_key: String::new(),
+            convert_to_gif: false,
+            gif_paste_format: default_gif_paste_format(),
+            gif_allowed_hosts: default_gif_allowed_hosts(),
+            gif_allow_any_host: false,
+            gif_allow_http: false,
         }
     }
 }
 
 impl UserSettings {
+    /// The pin limit, or `None` when pinning is unlimited.
+    pub fn pin_limit(&self) -> Option<usize> {
+        (self.max_pinned_items > 0).then_some(self.max_pinned_items)
+    }
+
+    /// Whether oversized text is truncated rather than skipped.
+    pub fn truncate_oversized_text(&self) -> bool {
+        self.oversized_text_action == "truncate"
+    }
+
+    /// The sensitive-item lifetime when auto-expiry is enabled, else `None`.
+    pub fn sensitive_ttl(&self) -> Option<u64> {
+        self.auto_expire_sensitive
+            .then_some(self.sensitive_ttl_seconds)
+    }
+
+    /// The paste keystroke override, or `None` for automatic.
+    pub fn paste_combo(&self) -> Result<Option<PasteCombo>, String> {
+        if self.paste_combo.trim().is_empty() {
+            return Ok(None);
+        }
+        PasteCombo::parse(&self.paste_combo).map(Some)
+    }
+
+    /// The paste settings, or an error naming the first unknown paste
+    /// method or invalid keystroke.
+    pub fn paste_config(&self) -> Result<PasteConfig, String> {
+        let rules = self
+            .app_paste_rules
+            .iter()
+            .map(|rule| {
+                PasteRule::new(&rule.wm_class_pattern, &rule.method, rule.use_shift)
+                    .map_err(|e| format!("Paste rule for '{}': {}", rule.wm_class_pattern, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(PasteConfig {
+            timings: self.paste_timing,
+            rules,
+            combo: self.paste_combo()?,
+            method: PasteMethod::parse(&self.paste_method)?,
+            type_char_delay_ms: self.type_char_delay_ms,
+        })
+    }
+
+    /// Takes the shortcut overrides from `stored`, the settings saved before
+    /// this edit, since the UI may hold a stale copy. A history shortcut
+    /// whose configured binding was edited loses its override, so the new
+    /// binding is the one registered.
+    pub fn keep_shortcut_overrides(&mut self, stored: &UserSettings) {
+        let edited =
+            |new: &str, old: &str| KeyBinding::parse(new).ok() != KeyBinding::parse(old).ok();
+        let mut overrides = stored.shortcut_overrides.clone();
+        if edited(&self.shortcut_primary, &stored.shortcut_primary) {
+            overrides.remove("penguinclip");
+        }
+        if edited(&self.shortcut_secondary, &stored.shortcut_secondary) {
+            overrides.remove("penguinclip-alt");
+        }
+        self.shortcut_overrides = overrides;
+    }
+
+    /// The clipboard history shortcuts, or an error naming the first one
+    /// that doesn't parse.
+    pub fn shortcut_bindings(&self) -> Result<HistoryBindings, String> {
+        let overrides = self
+            .shortcut_overrides
+            .iter()
+            .map(|(id, binding)| KeyBinding::parse(binding).map(|b| (id.clone(), b)))
+            .collect::<Result<_, _>>()?;
+        Ok(HistoryBindings {
+            primary: KeyBinding::parse(&self.shortcut_primary)?,
+            secondary: KeyBinding::parse(&self.shortcut_secondary)?,
+            overrides,
+            disabled: self.disabled_shortcuts.clone(),
+        })
+    }
+
+    /// The window close policy (the default for unknown values).
+    pub fn window_close_policy(&self) -> WindowClosePolicy {
+        WindowClosePolicy::parse(&self.window_close_policy)
+            .unwrap_or(WindowClosePolicy::OnFocusLoss)
+    }
+
     pub fn auto_delete_interval_in_minutes(&self) -> u64 {
         if self.auto_delete_interval == 0 {
             return 0;
@@ -134,26 +484,439 @@ impl UserSettings {
         }
     }
 
-    /// Validates and clamps opacity values to the valid range [0.0, 1.0]
-    pub fn validate(&mut self) {
-        self.dark_background_opacity = self.dark_background_opacity.clamp(0.0, 1.0);
-        self.light_background_opacity = self.light_background_opacity.clamp(0.0, 1.0);
+    /// Clamps or resets invalid values, returning one issue per value that
+    /// was changed. Canonical rewrites of valid input (keystroke spelling,
+    /// lowercased app names) are not issues.
+    pub fn validate(&mut self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        let opacity = self.dark_background_opacity.clamp(0.0, 1.0);
+        fix(
+            &mut issues,
+            "dark_background_opacity",
+            &mut self.dark_background_opacity,
+            opacity,
+            "must be between 0 and 1",
+        );
+        let opacity = self.light_background_opacity.clamp(0.0, 1.0);
+        fix(
+            &mut issues,
+            "light_background_opacity",
+            &mut self.light_background_opacity,
+            opacity,
+            "must be between 0 and 1",
+        );
 
-        // Validate theme_mode
-        if !["system", "dark", "light"].contains(&self.theme_mode.as_str()) {
-            self.theme_mode = "system".to_string();
+        fix_choice(
+            &mut issues,
+            "theme_mode",
+            &mut self.theme_mode,
+            &["system", "dark", "light"],
+            "system".to_string(),
+        );
+
+        let size = self.max_history_size.clamp(1, 100_000);
+        fix(
+            &mut issues,
+            "max_history_size",
+            &mut self.max_history_size,
+            size,
+            "must be between 1 and 100000",
+        );
+
+        // 0 = unlimited, otherwise up to 100000
+        let pins = self.max_pinned_items.min(100_000);
+        fix(
+            &mut issues,
+            "max_pinned_items",
+            &mut self.max_pinned_items,
+            pins,
+            "must be at most 100000",
+        );
+
+        let bytes = self.max_text_item_bytes.clamp(1024, 256 * 1024 * 1024);
+        fix(
+            &mut issues,
+            "max_text_item_bytes",
+            &mut self.max_text_item_bytes,
+            bytes,
+            "must be between 1 KiB and 256 MiB",
+        );
+        let bytes = self
+            .max_image_item_bytes
+            .clamp(1024 * 1024, 256 * 1024 * 1024);
+        fix(
+            &mut issues,
+            "max_image_item_bytes",
+            &mut self.max_image_item_bytes,
+            bytes,
+            "must be between 1 MiB and 256 MiB",
+        );
+        fix_choice(
+            &mut issues,
+            "oversized_text_action",
+            &mut self.oversized_text_action,
+            &["truncate", "skip"],
+            default_oversized_text_action(),
+        );
+
+        let ttl = self.sensitive_ttl_seconds.clamp(5, 86_400);
+        fix(
+            &mut issues,
+            "sensitive_ttl_seconds",
+            &mut self.sensitive_ttl_seconds,
+            ttl,
+            "must be between 5 seconds and 1 day",
+        );
+
+        let half_life = if self.emoji_decay_half_life_days.is_finite() {
+            self.emoji_decay_half_life_days.clamp(1.0, 365.0)
+        } else {
+            default_emoji_decay_half_life_days()
+        };
+        fix(
+            &mut issues,
+            "emoji_decay_half_life_days",
+            &mut self.emoji_decay_half_life_days,
+            half_life,
+            "must be between 1 and 365 days",
+        );
+
+        let scale = self.ui_scale.clamp(0.5, 2.0);
+        fix(
+            &mut issues,
+            "ui_scale",
+            &mut self.ui_scale,
+            scale,
+            "must be between 0.5 and 2",
+        );
+
+        let timings = self.paste_timing.clamped();
+        fix(
+            &mut issues,
+            "paste_timing",
+            &mut self.paste_timing,
+            timings,
+            "delays out of range were clamped",
+        );
+        match self.paste_combo() {
+            Ok(Some(combo)) => self.paste_combo = combo.to_string(),
+            Ok(None) => self.paste_combo = String::new(),
+            Err(e) => fix(
+                &mut issues,
+                "paste_combo",
+                &mut self.paste_combo,
+                String::new(),
+                &format!("{}; using the default paste keystroke", e),
+            ),
+        }
+
+        if let Err(e) = PasteMethod::parse(&self.paste_method) {
+            fix(
+                &mut issues,
+                "paste_method",
+                &mut self.paste_method,
+                default_paste_method(),
+                &format!("{}; using automatic paste", e),
+            );
+        }
+        let mut rules: Vec<AppPasteRule> = Vec::new();
+        for rule in &self.app_paste_rules {
+            match PasteRule::new(&rule.wm_class_pattern, &rule.method, rule.use_shift) {
+                Ok(compiled) => rules.push(AppPasteRule {
+                    wm_class_pattern: compiled.pattern,
+                    ..rule.clone()
+                }),
+                Err(e) => issues.push(ValidationIssue {
+                    field: "app_paste_rules".to_string(),
+                    submitted: serde_json::to_value(rule).unwrap_or_default(),
+                    applied: serde_json::Value::Null,
+                    reason: format!("dropped the rule for '{}': {}", rule.wm_class_pattern, e),
+                }),
+            }
+        }
+        self.app_paste_rules = rules;
+        let delay = self.type_char_delay_ms.min(1000);
+        fix(
+            &mut issues,
+            "type_char_delay_ms",
+            &mut self.type_char_delay_ms,
+            delay,
+            "must be at most 1000 ms",
+        );
+
+        for (field, value, default) in [
+            (
+                "shortcut_primary",
+                &mut self.shortcut_primary,
+                default_shortcut_primary(),
+            ),
+            (
+                "shortcut_secondary",
+                &mut self.shortcut_secondary,
+                default_shortcut_secondary(),
+            ),
+        ] {
+            match KeyBinding::parse(value) {
+                Ok(binding) => *value = binding.to_string(),
+                Err(e) => fix(
+                    &mut issues,
+                    field,
+                    value,
+                    default,
+                    &format!("{}; using the default shortcut", e),
+                ),
+            }
+        }
+        self.shortcut_overrides
+            .retain(|id, binding| match KeyBinding::parse(binding) {
+                Ok(parsed) => {
+                    *binding = parsed.to_string();
+                    true
+                }
+                Err(e) => {
+                    issues.push(ValidationIssue {
+                        field: "shortcut_overrides".to_string(),
+                        submitted: serde_json::Value::from(binding.as_str()),
+                        applied: serde_json::Value::Null,
+                        reason: format!("dropped the override for '{}': {}", id, e),
+                    });
+                    false
+                }
+            });
+
+        if WindowClosePolicy::parse(&self.window_close_policy).is_none() {
+            fix(
+                &mut issues,
+                "window_close_policy",
+                &mut self.window_close_policy,
+                default_window_close_policy(),
+                "must be one of: on_focus_loss, on_paste, manual",
+            );
         }
 
-        // Validate max_history_size (1 to 100000)
-        self.max_history_size = self.max_history_size.clamp(1, 100_000);
+        fix_choice(
+            &mut issues,
+            "gif_paste_format",
+            &mut self.gif_paste_format,
+            &["uri-list", "image-data", "auto"],
+            default_gif_paste_format(),
+        );
 
-        // Validate ui_scale (0.5 to 2.0)
-        self.ui_scale = self.ui_scale.clamp(0.5, 2.0);
+        fix_choice(
+            &mut issues,
+            "auto_delete_unit",
+            &mut self.auto_delete_unit,
+            &["minutes", "hours", "days", "weeks"],
+            "hours".to_string(),
+        );
 
-        // Validate auto_delete_unit
-        if !["minutes", "hours", "days", "weeks"].contains(&self.auto_delete_unit.as_str()) {
-            self.auto_delete_unit = "hours".to_string();
+        // Normalize excluded apps: WM_CLASS matching is case-insensitive
+        let mut apps: Vec<String> = Vec::new();
+        for app in &self.excluded_apps {
+            let app = app.trim().to_lowercase();
+            if !app.is_empty() && !apps.contains(&app) {
+                apps.push(app);
+            }
         }
+        self.excluded_apps = apps;
+
+        // Normalize GIF hosts: bare lowercase hostnames
+        let mut hosts: Vec<String> = Vec::new();
+        for host in &self.gif_allowed_hosts {
+            let host = host.trim().trim_matches('.').to_lowercase();
+            if !host.is_empty() && !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+        self.gif_allowed_hosts = hosts;
+
+        issues
+    }
+}
+
+/// A setting `validate` had to change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub field: String,
+    /// The value as submitted
+    pub submitted: serde_json::Value,
+    /// The value now in use; null when it was dropped
+    pub applied: serde_json::Value,
+    pub reason: String,
+}
+
+/// Replaces `value` with `fixed`, recording an issue if they differ.
+fn fix<T: PartialEq + Serialize>(
+    issues: &mut Vec<ValidationIssue>,
+    field: &str,
+    value: &mut T,
+    fixed: T,
+    reason: &str,
+) {
+    if *value != fixed {
+        issues.push(ValidationIssue {
+            field: field.to_string(),
+            submitted: serde_json::to_value(&*value).unwrap_or_default(),
+            applied: serde_json::to_value(&fixed).unwrap_or_default(),
+            reason: reason.to_string(),
+        });
+        *value = fixed;
+    }
+}
+
+/// Resets `value` to `default` unless it is one of `allowed`.
+fn fix_choice(
+    issues: &mut Vec<ValidationIssue>,
+    field: &str,
+    value: &mut String,
+    allowed: &[&str],
+    default: String,
+) {
+    if !allowed.contains(&value.as_str()) {
+        let reason = format!("must be one of: {}", allowed.join(", "));
+        fix(issues, field, value, default, &reason);
+    }
+}
+
+/// A settings export file: the settings (including custom kaomojis) and,
+/// optionally, the emoji usage history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub settings: UserSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji_history: Option<serde_json::Value>,
+}
+
+/// How an imported setting differs from what will be applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportWarningKind {
+    /// Out of range or invalid; `validate` changed or dropped it
+    Adjusted,
+    /// Wrong type or unparseable; the default is used instead
+    Rejected,
+    /// Not a setting this version knows; ignored
+    Unknown,
+}
+
+/// One imported setting that won't be applied as written
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportWarning {
+    pub field: String,
+    pub kind: ImportWarningKind,
+    /// The value in the import file
+    pub imported: serde_json::Value,
+    /// The value that will be used, `None` when the field is ignored
+    pub applied: Option<serde_json::Value>,
+}
+
+/// A parsed import file, ready to be previewed or applied
+#[derive(Debug, Clone)]
+pub struct SettingsImport {
+    /// Validated settings
+    pub settings: UserSettings,
+    pub emoji_history: Option<serde_json::Value>,
+    pub warnings: Vec<ImportWarning>,
+}
+
+/// What the settings UI shows before and after an import
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub warnings: Vec<ImportWarning>,
+    pub includes_emoji_history: bool,
+    /// False for a preview: nothing was saved yet
+    pub applied: bool,
+}
+
+impl SettingsImport {
+    pub fn report(&self, applied: bool) -> ImportReport {
+        ImportReport {
+            warnings: self.warnings.clone(),
+            includes_emoji_history: self.emoji_history.is_some(),
+            applied,
+        }
+    }
+}
+
+impl SettingsBundle {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))
+    }
+
+    /// Parses an export file (a bare `user_settings.json` works too) without
+    /// giving up on the whole file for one bad value: each field is checked on
+    /// its own, and every field that was dropped or changed is reported.
+    pub fn parse_import(content: &str) -> Result<SettingsImport, String> {
+        let value: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| format!("Not a valid settings file: {}", e))?;
+        let serde_json::Value::Object(mut bundle) = value else {
+            return Err("Not a valid settings file: expected a JSON object".to_string());
+        };
+        let emoji_history = bundle.remove("emoji_history");
+        let mut imported = match bundle.remove("settings") {
+            Some(settings) => settings,
+            None => serde_json::Value::Object(bundle),
+        };
+        migrations::migrate(&mut imported);
+        let serde_json::Value::Object(imported) = imported else {
+            return Err("Not a valid settings file: \"settings\" must be an object".to_string());
+        };
+
+        let mut warnings = Vec::new();
+        let defaults = to_fields(&UserSettings::default());
+        let mut merged = defaults.clone();
+        for (field, value) in &imported {
+            if field == "schema_version" {
+                continue;
+            }
+            let kind = if !defaults.contains_key(field) {
+                ImportWarningKind::Unknown
+            } else {
+                let mut candidate = merged.clone();
+                candidate.insert(field.clone(), value.clone());
+                let candidate = serde_json::Value::Object(candidate);
+                if serde_json::from_value::<UserSettings>(candidate.clone()).is_ok() {
+                    if let serde_json::Value::Object(candidate) = candidate {
+                        merged = candidate;
+                    }
+                    continue;
+                }
+                ImportWarningKind::Rejected
+            };
+            warnings.push(ImportWarning {
+                field: field.clone(),
+                kind,
+                imported: value.clone(),
+                applied: defaults.get(field).cloned(),
+            });
+        }
+
+        let mut settings: UserSettings = serde_json::from_value(serde_json::Value::Object(merged))
+            .map_err(|e| format!("Not a valid settings file: {}", e))?;
+        for issue in settings.validate() {
+            warnings.push(ImportWarning {
+                field: issue.field,
+                kind: ImportWarningKind::Adjusted,
+                imported: issue.submitted,
+                applied: (!issue.applied.is_null()).then_some(issue.applied),
+            });
+        }
+
+        Ok(SettingsImport {
+            settings,
+            emoji_history,
+            warnings,
+        })
+    }
+}
+
+/// `settings` as a JSON object, one entry per field
+fn to_fields(settings: &UserSettings) -> serde_json::Map<String, serde_json::Value> {
+    match serde_json::to_value(settings) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => serde_json::Map::new(),
     }
 }
 
@@ -181,46 +944,74 @@ impl UserSettingsManager {
     /// Loads user settings from the config file
     /// Returns default settings if the file doesn't exist or is invalid
     pub fn load(&self) -> UserSettings {
-        let path = self.settings_path();
-
-        if !path.exists() {
-            return UserSettings::default();
-        }
+        self.load_reporting().0
+    }
 
-        match fs::read_to_string(&path) {
-            Ok(content) => match serde_json::from_str::<UserSettings>(&content) {
-                Ok(mut settings) => {
-                    settings.validate();
-                    settings
-                }
-                Err(e) => {
-                    warn!("failed to parse settings file: {}. Using defaults.", e);
-                    UserSettings::default()
-                }
-            },
-            Err(e) => {
-                warn!("failed to read settings file: {}. Using defaults.", e);
-                UserSettings::default()
+    /// Like `load`, also reporting a damaged settings file that was restored
+    /// from its backup or replaced by defaults. Only the first load after
+    /// the damage reports it: the file is repaired or moved aside.
+    ///
+    /// Files from an older schema are migrated and saved back once.
+    pub fn load_reporting(&self) -> (UserSettings, Option<LoadIssue>) {
+        let (loaded, issue) = atomic_write::load_with_backup(&self.settings_path(), |content| {
+            let mut value: serde_json::Value =
+                serde_json::from_str(content).map_err(|e| e.to_string())?;
+            let migrated = migrations::migrate(&mut value);
+            let settings =
+                serde_json::from_value::<UserSettings>(value).map_err(|e| e.to_string())?;
+            Ok((settings, migrated))
+        });
+        let Some((mut settings, migrated)) = loaded else {
+            return (UserSettings::default(), issue);
+        };
+        settings.validate();
+        if migrated {
+            match self.save(&settings) {
+                Ok(()) => debug!(
+                    "migrated settings file to schema {}",
+                    migrations::CURRENT_VERSION
+                ),
+                Err(e) => warn!("failed to save migrated settings: {}", e),
             }
         }
+        (settings, issue)
+    }
+
+    /// Schema version of the settings file on disk, if it has one
+    fn stored_version(&self) -> Option<u64> {
+        let content = std::fs::read_to_string(self.settings_path()).ok()?;
+        let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+        value.get("schema_version")?.as_u64()
     }
 
-    /// Saves user settings to the config file
+    /// Saves user settings to the config file atomically, keeping a backup.
+    /// Refuses to overwrite a file from a newer build, which would drop the
+    /// fields this build doesn't know.
     pub fn save(&self, settings: &UserSettings) -> Result<(), String> {
-        // Ensure the config directory exists
-        if !self.config_dir.exists() {
-            fs::create_dir_all(&self.config_dir)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        if let Some(version) = self
+            .stored_version()
+            .filter(|&v| v > migrations::CURRENT_VERSION as u64)
+        {
+            return Err(format!(
+                "Settings file is from a newer version of PenguinClip (schema {}); not overwriting it",
+                version
+            ));
         }
 
         // Validate settings before saving
         let mut validated_settings = settings.clone();
-        validated_settings.validate();
+        for issue in validated_settings.validate() {
+            warn!(
+                "setting {}: {} ({} -> {})",
+                issue.field, issue.reason, issue.submitted, issue.applied
+            );
+        }
+        validated_settings.schema_version = migrations::CURRENT_VERSION;
 
         let content = serde_json::to_string_pretty(&validated_settings)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-        fs::write(self.settings_path(), content)
+        atomic_write::save_with_backup(&self.settings_path(), content.as_bytes())
             .map_err(|e| format!("Failed to write settings file: {}", e))?;
 
         Ok(())
@@ -251,6 +1042,7 @@ mod tests {
             theme_mode: "invalid".to_string(),
             dark_background_opacity: 1.5,
             light_background_opacity: -0.5,
+            emoji_decay_half_life_days: 0.0,
             ..Default::default()
         };
         settings.validate();
@@ -258,5 +1050,362 @@ mod tests {
         assert_eq!(settings.theme_mode, "system");
         assert!((settings.dark_background_opacity - 1.0).abs() < f32::EPSILON);
         assert!(settings.light_background_opacity.abs() < f32::EPSILON);
+        assert_eq!(settings.emoji_decay_half_life_days, 1.0);
+
+        settings.emoji_decay_half_life_days = f64::NAN;
+        settings.validate();
+        assert_eq!(settings.emoji_decay_half_life_days, 30.0);
+    }
+
+    #[test]
+    fn test_validate_reports_each_clamped_field() {
+        let mut settings = UserSettings {
+            dark_background_opacity: 1.5,
+            light_background_opacity: -0.5,
+            max_history_size: 0,
+            max_pinned_items: 200_000,
+            max_text_item_bytes: 10,
+            max_image_item_bytes: 10,
+            sensitive_ttl_seconds: 1,
+            ui_scale: 9.0,
+            type_char_delay_ms: 5000,
+            paste_timing: PasteTimings {
+                key_delay_ms: 0,
+                ..PasteTimings::DEFAULT
+            },
+            ..Default::default()
+        };
+        let issues = settings.validate();
+
+        let applied = |field: &str| {
+            issues
+                .iter()
+                .find(|issue| issue.field == field)
+                .map(|issue| issue.applied.clone())
+                .unwrap_or_else(|| panic!("no issue for {}", field))
+        };
+        assert_eq!(issues.len(), 10, "{:?}", issues);
+        assert_eq!(applied("dark_background_opacity"), 1.0);
+        assert_eq!(applied("light_background_opacity"), 0.0);
+        assert_eq!(applied("max_history_size"), 1);
+        assert_eq!(applied("max_pinned_items"), 100_000);
+        assert_eq!(applied("max_text_item_bytes"), 1024);
+        assert_eq!(applied("max_image_item_bytes"), 1024 * 1024);
+        assert_eq!(applied("sensitive_ttl_seconds"), 5);
+        assert_eq!(applied("ui_scale"), 2.0);
+        assert_eq!(applied("type_char_delay_ms"), 1000);
+        assert_eq!(applied("paste_timing")["key_delay_ms"], 1);
+
+        let ui_scale = issues.iter().find(|i| i.field == "ui_scale").unwrap();
+        assert_eq!(ui_scale.submitted, 9.0);
+        assert!((settings.ui_scale - 2.0).abs() < f32::EPSILON);
+
+        // Already valid settings produce no issues
+        assert_eq!(settings.validate(), vec![]);
+        assert_eq!(UserSettings::default().validate(), vec![]);
+    }
+
+    #[test]
+    fn test_validate_reports_reset_choices() {
+        let mut settings = UserSettings {
+            theme_mode: "purple".to_string(),
+            oversized_text_action: "explode".to_string(),
+            window_close_policy: "sometimes".to_string(),
+            gif_paste_format: "bmp".to_string(),
+            auto_delete_unit: "fortnights".to_string(),
+            paste_method: "telepathy".to_string(),
+            paste_combo: "Ctrl+Banana".to_string(),
+            app_paste_rules: vec![AppPasteRule {
+                wm_class_pattern: "xterm".to_string(),
+                method: "middle-click".to_string(),
+                use_shift: false,
+            }],
+            // Normalizing valid input is not an issue
+            excluded_apps: vec![" KeePassXC ".to_string()],
+            ..Default::default()
+        };
+        let mut fields: Vec<String> = settings
+            .validate()
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec![
+                "app_paste_rules",
+                "auto_delete_unit",
+                "gif_paste_format",
+                "oversized_text_action",
+                "paste_combo",
+                "paste_method",
+                "theme_mode",
+                "window_close_policy",
+            ]
+        );
+        assert_eq!(settings.excluded_apps, vec!["keepassxc"]);
+    }
+
+    #[test]
+    fn test_window_close_policy() {
+        let mut settings = UserSettings {
+            window_close_policy: "manual".to_string(),
+            ..Default::default()
+        };
+        settings.validate();
+        assert_eq!(settings.window_close_policy(), WindowClosePolicy::Manual);
+        assert!(!WindowClosePolicy::Manual.hides_on_paste());
+        assert!(WindowClosePolicy::OnPaste.hides_on_paste());
+        assert!(!WindowClosePolicy::OnPaste.hides_on_focus_loss());
+
+        settings.window_close_policy = "sometimes".to_string();
+        settings.validate();
+        assert_eq!(settings.window_close_policy, "on_focus_loss");
+    }
+
+    #[test]
+    fn test_validate_paste_combo() {
+        let mut settings = UserSettings {
+            paste_combo: "shift+insert".to_string(),
+            ..Default::default()
+        };
+        settings.validate();
+        assert_eq!(settings.paste_combo, "Shift+Insert");
+
+        settings.paste_combo = "Ctrl+Banana".to_string();
+        assert!(settings.paste_combo().is_err());
+        settings.validate();
+        assert_eq!(settings.paste_combo, "");
+    }
+
+    #[test]
+    fn test_validate_shortcuts() {
+        let mut settings = UserSettings {
+            shortcut_primary: "<super> C".to_string(),
+            shortcut_secondary: "Ctrl+Alt+V".to_string(),
+            ..Default::default()
+        };
+        assert!(settings.shortcut_bindings().is_err());
+
+        let issues = settings.validate();
+        assert_eq!(settings.shortcut_primary, "<Super>c");
+        assert_eq!(settings.shortcut_secondary, "<Ctrl><Alt>v");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "shortcut_secondary");
+        assert_eq!(
+            settings.shortcut_bindings().unwrap().primary.kde(),
+            "Meta+C"
+        );
+
+        settings.shortcut_overrides = BTreeMap::from([
+            ("penguinclip".to_string(), "<super><shift> V".to_string()),
+            ("penguinclip-emoji".to_string(), "<Super>".to_string()),
+        ]);
+        assert!(settings.shortcut_bindings().is_err());
+        let issues = settings.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "shortcut_overrides");
+        assert_eq!(
+            settings.shortcut_overrides,
+            BTreeMap::from([("penguinclip".to_string(), "<Super><Shift>v".to_string())])
+        );
+        assert_eq!(
+            settings.shortcut_bindings().unwrap().overrides["penguinclip"].kde(),
+            "Meta+Shift+V"
+        );
+    }
+
+    #[test]
+    fn test_edited_binding_drops_its_override() {
+        let stored = UserSettings {
+            shortcut_overrides: BTreeMap::from([
+                ("penguinclip".to_string(), "<Super><Shift>v".to_string()),
+                ("penguinclip-alt".to_string(), "<Super><Alt>v".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let mut unchanged = UserSettings::default();
+        unchanged.keep_shortcut_overrides(&stored);
+        assert_eq!(unchanged.shortcut_overrides, stored.shortcut_overrides);
+
+        let mut edited = UserSettings {
+            shortcut_primary: "<Super>c".to_string(),
+            ..Default::default()
+        };
+        edited.keep_shortcut_overrides(&stored);
+        assert_eq!(
+            edited.shortcut_overrides.keys().collect::<Vec<_>>(),
+            ["penguinclip-alt"]
+        );
+        assert_eq!(
+            edited.shortcut_bindings().unwrap().primary.gnome(),
+            "<Super>c"
+        );
+    }
+
+    #[test]
+    fn test_unknown_paste_rule_method_is_rejected() {
+        let mut settings = UserSettings {
+            app_paste_rules: vec![
+                AppPasteRule {
+                    wm_class_pattern: " URxvt ".to_string(),
+                    method: "shift-insert".to_string(),
+                    use_shift: false,
+                },
+                AppPasteRule {
+                    wm_class_pattern: "xterm".to_string(),
+                    method: "middle-click".to_string(),
+                    use_shift: false,
+                },
+            ],
+            ..Default::default()
+        };
+        let err = settings.paste_config().unwrap_err();
+        assert!(
+            err.contains("xterm") && err.contains("middle-click"),
+            "{}",
+            err
+        );
+
+        settings.validate();
+        assert_eq!(settings.app_paste_rules.len(), 1);
+        assert_eq!(settings.app_paste_rules[0].wm_class_pattern, "urxvt");
+        assert!(settings.paste_config().is_ok());
+    }
+
+    #[test]
+    fn test_truncated_settings_file_loads_backup() {
+        let config_dir = std::env::temp_dir().join("penguinclip_test_settings_truncated");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        let manager = UserSettingsManager { config_dir };
+        let saved = UserSettings {
+            theme_mode: "dark".to_string(),
+            ..Default::default()
+        };
+        manager.save(&saved).unwrap();
+        // Simulate a write cut short by a crash
+        std::fs::write(manager.settings_path(), r#"{"theme_mode": "da"#).unwrap();
+
+        let (settings, issue) = manager.load_reporting();
+        assert_eq!(settings.theme_mode, "dark");
+        assert_eq!(
+            issue.as_ref().map(LoadIssue::event),
+            Some("settings-recovered")
+        );
+    }
+
+    #[test]
+    fn test_old_schema_is_migrated_and_saved_back() {
+        let config_dir = std::env::temp_dir().join("penguinclip_test_settings_migrate");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let manager = UserSettingsManager { config_dir };
+        std::fs::write(
+            manager.settings_path(),
+            include_str!("user_settings/fixtures/v0.json"),
+        )
+        .unwrap();
+
+        let settings = manager.load();
+        assert_eq!(settings.custom_kaomojis[0].text, "(•‿•)");
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(manager.settings_path()).unwrap())
+                .unwrap();
+        assert_eq!(saved["schema_version"], migrations::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_newer_schema_is_not_overwritten() {
+        let config_dir = std::env::temp_dir().join("penguinclip_test_settings_newer");
+        let _ = std::fs::remove_dir_all(&config_dir);
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let manager = UserSettingsManager { config_dir };
+        let newer = serde_json::json!({
+            "schema_version": migrations::CURRENT_VERSION + 1,
+            "theme_mode": "dark",
+            "dark_background_opacity": 1.0,
+            "light_background_opacity": 1.0,
+            "field_from_the_future": true,
+        })
+        .to_string();
+        std::fs::write(manager.settings_path(), &newer).unwrap();
+
+        let settings = manager.load();
+        assert_eq!(settings.theme_mode, "dark");
+        assert!(manager.save(&settings).is_err());
+        assert_eq!(
+            std::fs::read_to_string(manager.settings_path()).unwrap(),
+            newer
+        );
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let settings = UserSettings {
+            theme_mode: "light".to_string(),
+            max_history_size: 250,
+            excluded_apps: vec!["keepassxc".to_string()],
+            custom_kaomojis: vec![CustomKaomoji {
+                text: "(•‿•)".to_string(),
+                category: "Custom".to_string(),
+                keywords: vec!["smile".to_string()],
+            }],
+            ..Default::default()
+        };
+        let bundle = SettingsBundle {
+            settings: settings.clone(),
+            emoji_history: Some(serde_json::json!({ "emojis": [] })),
+        };
+
+        let import = SettingsBundle::parse_import(&bundle.to_json().unwrap()).unwrap();
+        assert_eq!(import.warnings, vec![]);
+        assert_eq!(to_fields(&import.settings), to_fields(&settings));
+        assert!(import.emoji_history.is_some());
+
+        // A bare user_settings.json imports too
+        let bare = serde_json::to_string(&settings).unwrap();
+        let import = SettingsBundle::parse_import(&bare).unwrap();
+        assert_eq!(to_fields(&import.settings), to_fields(&settings));
+        assert!(import.emoji_history.is_none());
+    }
+
+    #[test]
+    fn test_import_reports_adjusted_rejected_and_unknown_fields() {
+        let content = serde_json::json!({
+            "settings": {
+                "theme_mode": "dark",
+                "dark_background_opacity": 1.5,
+                "max_history_size": "lots",
+                "no_such_setting": true,
+            }
+        })
+        .to_string();
+
+        let import = SettingsBundle::parse_import(&content).unwrap();
+        assert_eq!(import.settings.theme_mode, "dark");
+        assert_eq!(
+            import.settings.max_history_size,
+            UserSettings::default().max_history_size
+        );
+        let kind_of = |field: &str| {
+            import
+                .warnings
+                .iter()
+                .find(|w| w.field == field)
+                .map(|w| w.kind)
+        };
+        assert_eq!(import.warnings.len(), 3);
+        assert_eq!(
+            kind_of("dark_background_opacity"),
+            Some(ImportWarningKind::Adjusted)
+        );
+        assert_eq!(
+            kind_of("max_history_size"),
+            Some(ImportWarningKind::Rejected)
+        );
+        assert_eq!(kind_of("no_such_setting"), Some(ImportWarningKind::Unknown));
+
+        assert!(SettingsBundle::parse_import("[1, 2]").is_err());
     }
 }
@@ -0,0 +1,117 @@
+//! Settings Schema Migrations
+//! Upgrades a raw `user_settings.json` value from older schema versions
+//! before it is deserialized into `UserSettings`. `serde(default)` covers
+//! added fields; renamed fields and changed meanings are handled here.
+//!
+//! `MIGRATIONS[n]` upgrades a version `n` file to version `n + 1`. Files
+//! written before versioning have no `schema_version` and count as 0.
+
+use log::warn;
+use serde_json::{Map, Value};
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// Ordered upgrades; append new ones, never reorder or edit old ones.
+const MIGRATIONS: &[Migration] = &[v0_to_v1];
+
+/// Schema version written by this build.
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Upgrades `value` in place to `CURRENT_VERSION` and stamps the version.
+/// Returns true if anything ran, i.e. the file should be saved back.
+/// Files from a newer build are left alone.
+pub fn migrate(value: &mut Value) -> bool {
+    let Some(fields) = value.as_object_mut() else {
+        return false;
+    };
+    let version = fields
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if version > CURRENT_VERSION as u64 {
+        warn!(
+            "settings file is from a newer version (schema {}, this build knows {}); loading as is",
+            version, CURRENT_VERSION
+        );
+        return false;
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(fields);
+    }
+    fields.insert("schema_version".to_string(), CURRENT_VERSION.into());
+    version < CURRENT_VERSION as u64
+}
+
+/// v0 → v1: the original theme fields had no serde defaults, so a
+/// hand-trimmed v0 file missing one of them would fail to parse and be
+/// treated as corrupt.
+fn v0_to_v1(fields: &mut Map<String, Value>) {
+    fields
+        .entry("theme_mode")
+        .or_insert_with(|| "system".into());
+    fields
+        .entry("dark_background_opacity")
+        .or_insert_with(|| 1.0.into());
+    fields
+        .entry("light_background_opacity")
+        .or_insert_with(|| 1.0.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_settings::UserSettings;
+
+    /// A settings file as saved by each schema version, oldest first.
+    const FIXTURES: [&str; 2] = [
+        include_str!("fixtures/v0.json"),
+        include_str!("fixtures/v1.json"),
+    ];
+
+    fn load(fixture: &str) -> (UserSettings, bool) {
+        let mut value: Value = serde_json::from_str(fixture).unwrap();
+        let migrated = migrate(&mut value);
+        (serde_json::from_value(value).unwrap(), migrated)
+    }
+
+    #[test]
+    fn test_every_version_has_a_fixture() {
+        assert_eq!(FIXTURES.len(), CURRENT_VERSION as usize + 1);
+    }
+
+    #[test]
+    fn test_fixtures_load_to_the_same_settings() {
+        for (version, fixture) in FIXTURES.iter().enumerate() {
+            let (settings, migrated) = load(fixture);
+            assert_eq!(migrated, version < CURRENT_VERSION as usize, "v{}", version);
+            assert_eq!(settings.schema_version, CURRENT_VERSION, "v{}", version);
+            assert_eq!(settings.theme_mode, "dark", "v{}", version);
+            assert!((settings.dark_background_opacity - 0.8).abs() < f32::EPSILON);
+            assert_eq!(settings.max_history_size, 100, "v{}", version);
+            assert!(!settings.enable_dynamic_tray_icon, "v{}", version);
+            assert_eq!(settings.auto_delete_interval_in_minutes(), 2 * 24 * 60);
+            assert_eq!(settings.excluded_patterns, ["^sk-"], "v{}", version);
+            assert_eq!(settings.custom_kaomojis[0].text, "(•‿•)", "v{}", version);
+            assert!((settings.ui_scale - 1.25).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_v0_missing_original_fields_gets_defaults() {
+        let mut value = serde_json::json!({ "max_history_size": 20 });
+        assert!(migrate(&mut value));
+        let settings: UserSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.theme_mode, "system");
+        assert!((settings.light_background_opacity - 1.0).abs() < f32::EPSILON);
+        assert_eq!(settings.max_history_size, 20);
+    }
+
+    #[test]
+    fn test_newer_version_is_left_alone() {
+        let mut value = serde_json::json!({ "schema_version": CURRENT_VERSION + 1 });
+        let before = value.clone();
+        assert!(!migrate(&mut value));
+        assert_eq!(value, before);
+    }
+}
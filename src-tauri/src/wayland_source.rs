@@ -0,0 +1,217 @@
+//! Native Wayland Clipboard Source
+//! Puts data on the Wayland clipboard through the data-control protocols
+//! (ext, or wlroots' older copy), without shelling out to `wl-copy`. The
+//! source is owned by a background thread that answers paste requests until
+//! another client takes the clipboard or the app exits.
+
+use log::{debug, warn};
+use std::fs::File;
+use std::io::Write;
+use std::sync::Arc;
+use std::thread;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::{wl_registry::WlRegistry, wl_seat::WlSeat};
+use wayland_client::{
+    delegate_noop, event_created_child, Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::ext::data_control::v1::client::{
+    ext_data_control_device_v1::{self, ExtDataControlDeviceV1},
+    ext_data_control_manager_v1::ExtDataControlManagerV1,
+    ext_data_control_offer_v1::ExtDataControlOfferV1,
+    ext_data_control_source_v1::{self, ExtDataControlSourceV1},
+};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+    zwlr_data_control_source_v1::{self, ZwlrDataControlSourceV1},
+};
+
+/// Data offered under one MIME type.
+pub type Offer = (String, Arc<Vec<u8>>);
+
+/// Serving state: what we offer, and whether the compositor has cancelled
+/// the source (someone else owns the clipboard now).
+struct SourceState {
+    offers: Vec<Offer>,
+    cancelled: bool,
+}
+
+impl SourceState {
+    /// Writes the data for `mime_type` to the requesting client's pipe. The
+    /// write runs on its own thread so a slow reader can't stall the source.
+    fn send(&self, mime_type: &str, fd: impl Into<File>) {
+        let Some((_, data)) = self.offers.iter().find(|(mime, _)| mime == mime_type) else {
+            debug!("wayland source: no data for requested type {}", mime_type);
+            return;
+        };
+        let data = Arc::clone(data);
+        let mut pipe: File = fd.into();
+        thread::spawn(move || {
+            if let Err(e) = pipe.write_all(&data) {
+                debug!("wayland source: write failed: {}", e);
+            }
+        });
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for SourceState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: <WlRegistry as Proxy>::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(SourceState: ignore WlSeat);
+delegate_noop!(SourceState: ZwlrDataControlManagerV1);
+delegate_noop!(SourceState: ExtDataControlManagerV1);
+delegate_noop!(SourceState: ignore ZwlrDataControlOfferV1);
+delegate_noop!(SourceState: ignore ExtDataControlOfferV1);
+
+/// The wlr and ext protocols only differ in names; see `clipboard_watch`.
+macro_rules! data_control_source_dispatch {
+    ($device:ty, $device_mod:ident, $offer:ty, $source:ty, $source_mod:ident) => {
+        impl Dispatch<$device, ()> for SourceState {
+            fn event(
+                _: &mut Self,
+                _: &$device,
+                event: $device_mod::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                // We only write the clipboard; drop offers as they arrive.
+                match event {
+                    $device_mod::Event::Selection { id: Some(offer) }
+                    | $device_mod::Event::PrimarySelection { id: Some(offer) } => offer.destroy(),
+                    _ => {}
+                }
+            }
+
+            event_created_child!(SourceState, $device, [
+                $device_mod::EVT_DATA_OFFER_OPCODE => ($offer, ()),
+            ]);
+        }
+
+        impl Dispatch<$source, ()> for SourceState {
+            fn event(
+                state: &mut Self,
+                _: &$source,
+                event: $source_mod::Event,
+                _: &(),
+                _: &Connection,
+                _: &QueueHandle<Self>,
+            ) {
+                match event {
+                    $source_mod::Event::Send { mime_type, fd } => state.send(&mime_type, fd),
+                    $source_mod::Event::Cancelled => state.cancelled = true,
+                    _ => {}
+                }
+            }
+        }
+    };
+}
+
+data_control_source_dispatch!(
+    ZwlrDataControlDeviceV1,
+    zwlr_data_control_device_v1,
+    ZwlrDataControlOfferV1,
+    ZwlrDataControlSourceV1,
+    zwlr_data_control_source_v1
+);
+data_control_source_dispatch!(
+    ExtDataControlDeviceV1,
+    ext_data_control_device_v1,
+    ExtDataControlOfferV1,
+    ExtDataControlSourceV1,
+    ext_data_control_source_v1
+);
+
+/// True if the compositor offers a data-control protocol (wlroots-based
+/// compositors and newer ones; not GNOME).
+pub fn is_available() -> bool {
+    registry_globals().is_some_and(|globals| {
+        globals.iter().any(|interface| {
+            interface == ExtDataControlManagerV1::interface().name
+                || interface == ZwlrDataControlManagerV1::interface().name
+        })
+    })
+}
+
+/// The interface names of every global the compositor advertises, or `None`
+/// without a Wayland display.
+pub fn registry_globals() -> Option<Vec<String>> {
+    let conn = Connection::connect_to_env().ok()?;
+    let (globals, _) = registry_queue_init::<SourceState>(&conn).ok()?;
+    Some(
+        globals
+            .contents()
+            .with_list(|list| list.iter().map(|g| g.interface.clone()).collect()),
+    )
+}
+
+/// Takes the clipboard, offering each `(mime type, data)` pair (the first is
+/// the preferred one), and serves it from a background thread until another
+/// client takes the clipboard.
+pub fn serve(offers: Vec<Offer>) -> Result<(), String> {
+    if offers.is_empty() {
+        return Err("nothing to offer".to_string());
+    }
+
+    let conn =
+        Connection::connect_to_env().map_err(|e| format!("Wayland connect failed: {}", e))?;
+    let (globals, mut queue) =
+        registry_queue_init::<SourceState>(&conn).map_err(|e| format!("registry: {}", e))?;
+    let qh = queue.handle();
+
+    let seat: WlSeat = globals
+        .bind(&qh, 1..=1, ())
+        .map_err(|e| format!("no wl_seat: {}", e))?;
+
+    if let Ok(manager) = globals.bind::<ExtDataControlManagerV1, _, _>(&qh, 1..=1, ()) {
+        let device = manager.get_data_device(&seat, &qh, ());
+        let source = manager.create_data_source(&qh, ());
+        for (mime, _) in &offers {
+            source.offer(mime.clone());
+        }
+        device.set_selection(Some(&source));
+    } else if let Ok(manager) = globals.bind::<ZwlrDataControlManagerV1, _, _>(&qh, 1..=2, ()) {
+        let device = manager.get_data_device(&seat, &qh, ());
+        let source = manager.create_data_source(&qh, ());
+        for (mime, _) in &offers {
+            source.offer(mime.clone());
+        }
+        device.set_selection(Some(&source));
+    } else {
+        return Err("compositor offers no data-control protocol".to_string());
+    }
+
+    let mut state = SourceState {
+        offers,
+        cancelled: false,
+    };
+    // Surface protocol errors here rather than in the serving thread.
+    queue
+        .roundtrip(&mut state)
+        .map_err(|e| format!("roundtrip: {}", e))?;
+
+    thread::Builder::new()
+        .name("wayland-clipboard-source".to_string())
+        .spawn(move || {
+            while !state.cancelled {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    warn!("wayland source: dispatch error: {}", e);
+                    break;
+                }
+            }
+            debug!("wayland source: clipboard taken over, stopped serving");
+        })
+        .map_err(|e| format!("spawn source thread: {}", e))?;
+
+    Ok(())
+}
@@ -1,6 +1,5 @@
 import { useState, useCallback, useEffect, useRef } from 'react'
 import { clsx } from 'clsx'
-import { getCurrentWindow } from '@tauri-apps/api/window'
 import { listen } from '@tauri-apps/api/event'
 import { invoke } from '@tauri-apps/api/core'
 import { useClipboardHistory } from './hooks/useClipboardHistory'
@@ -12,7 +11,7 @@ import { GifPicker } from './components/GifPicker'
 import { KaomojiPicker } from './components/KaomojiPicker'
 import { SymbolPicker } from './components/SymbolPicker'
 import { calculateSecondaryOpacity, calculateTertiaryOpacity } from './utils/themeUtils'
-import { useSystemThemePreference } from './utils/systemTheme'
+import { useSystemAccentColor, useSystemThemePreference } from './utils/systemTheme'
 import { useRenderingEnv } from './hooks/useRenderingEnv'
 import type { ActiveTab, UserSettings } from './types/clipboard'
 import { ClipboardTab } from './components/ClipboardTab'
@@ -36,6 +35,7 @@ const DEFAULT_SETTINGS: UserSettings = {
   enable_smart_actions: true,
   enable_ui_polish: true,
   enable_dynamic_tray_icon: true,
+  symbolic_tray_icon: false,
   max_history_size: 50,
   auto_delete_interval: 0,
   auto_delete_unit: 'hours',
@@ -107,6 +107,9 @@ function ClipboardApp() {
   const [settingsLoaded, setSettingsLoaded] = useState(false)
   const [loadStatus, setLoadStatus] = useState<string | null>(null)
 
+  const [appearance, setAppearance] = useState<AppearanceTokens>(loadAppearance)
+  const systemAccent = useSystemAccentColor()
+
   const isDark = useThemeMode(settings.theme_mode)
   const renderingEnv = useRenderingEnv()
   const opacity = renderingEnv.transparency_disabled
@@ -127,6 +130,11 @@ function ClipboardApp() {
     togglePin,
     toggleFavorite,
     pasteItem,
+    pasteItemToSource,
+    typeItem,
+    hasMore,
+    loadMore,
+    loadFullContent,
   } = useClipboardHistory()
 
   // Transient confirmation toasts for actions that keep the window open
@@ -220,18 +228,44 @@ function ClipboardApp() {
       .catch((err) => console.error('Failed to fetch history load status:', err))
   }, [])
 
+  // Surface a damaged settings file (restored from backup or reset to defaults).
+  // The events fire at startup, possibly before this listener exists, so the
+  // status is also fetched once.
+  useEffect(() => {
+    invoke<string | null>('get_settings_load_status')
+      .then((status) => {
+        if (status) setLoadStatus(status)
+      })
+      .catch((err) => console.error('Failed to fetch settings load status:', err))
+
+    const unlistenRecovered = listen<string>('settings-recovered', (event) => {
+      setLoadStatus(event.payload)
+    })
+    const unlistenCorrupt = listen<string>('settings-corrupt', (event) => {
+      setLoadStatus(event.payload)
+    })
+    return () => {
+      unlistenRecovered.then((unlisten) => unlisten())
+      unlistenCorrupt.then((unlisten) => unlisten())
+    }
+  }, [])
+
   // Apply theme class when isDark changes
   useEffect(() => {
     applyThemeClass(isDark)
   }, [isDark])
 
-  // Apply user appearance tokens (accent / glass / roundness) on load, and keep
-  // them in sync live when changed from the Settings window.
+  // Apply user appearance tokens (accent / glass / roundness), following the
+  // desktop accent when asked to, and keep them in sync live when changed from
+  // the Settings window.
+  useEffect(() => {
+    applyAppearance(appearance, systemAccent?.hex)
+  }, [appearance, systemAccent])
+
   useEffect(() => {
-    applyAppearance(loadAppearance())
     applyStoredTheme()
     const unlistenAppearance = listen<AppearanceTokens>('appearance-changed', (event) => {
-      applyAppearance(event.payload)
+      setAppearance(event.payload)
     })
     const unlistenTheme = listen<string>('theme-changed', (event) => {
       applyTheme(event.payload)
@@ -267,7 +301,7 @@ function ClipboardApp() {
       if (e.key === 'Escape') {
         e.preventDefault()
         try {
-          await getCurrentWindow().hide()
+          await invoke('hide_window')
         } catch (err) {
           console.error('Failed to hide window:', err)
         }
@@ -338,6 +372,11 @@ function ClipboardApp() {
             togglePin={handleTogglePin}
             toggleFavorite={handleToggleFavorite}
             onPaste={pasteItem}
+            onPasteToSource={pasteItemToSource}
+            onType={typeItem}
+            hasMore={hasMore}
+            onLoadMore={loadMore}
+            onNeedFullContent={loadFullContent}
             settings={settings}
             tabBarRef={tabBarRef}
           />
@@ -356,6 +395,11 @@ function ClipboardApp() {
             togglePin={handleTogglePin}
             toggleFavorite={handleToggleFavorite}
             onPaste={pasteItem}
+            onPasteToSource={pasteItemToSource}
+            onType={typeItem}
+            hasMore={hasMore}
+            onLoadMore={loadMore}
+            onNeedFullContent={loadFullContent}
             settings={settings}
             tabBarRef={tabBarRef}
           />
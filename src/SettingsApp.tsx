@@ -17,7 +17,14 @@ import {
   Info,
 } from 'lucide-react'
 
-import type { UserSettings, CustomKaomoji, BooleanSettingKey } from './types/clipboard'
+import type {
+  UserSettings,
+  CustomKaomoji,
+  BooleanSettingKey,
+  ImportReport,
+  PasteTimings,
+  ValidationIssue,
+} from './types/clipboard'
 
 interface UpdateInfo {
   current_version: string
@@ -26,10 +33,32 @@ interface UpdateInfo {
   release_url: string
   release_notes: string
 }
+
+/** The desktop session, as detected by the backend */
+interface EnvironmentInfo {
+  de: string
+  name: string
+  session_type: 'Wayland' | 'X11' | 'Unknown'
+  compositor: string | null
+  is_flatpak: boolean
+  version: string | null
+}
+
+/** Session details beyond its type, as detected by the backend */
+interface SessionInfo {
+  session_type: 'Wayland' | 'X11' | 'Unknown'
+  detection_source: string
+  xwayland: boolean
+  compositor: string | null
+  window_manager: string | null
+  gnome_shell_version: number | null
+  plasma_version: number | null
+}
 import { FeaturesSection } from './components/FeaturesSection'
 import { Switch } from './components/Switch'
 import { PenguinLogo } from './components/PenguinLogo'
-import { useSystemThemePreference } from './utils/systemTheme'
+import { PasteRulesEditor, PASTE_METHODS } from './components/PasteRulesEditor'
+import { useSystemAccentColor, useSystemThemePreference } from './utils/systemTheme'
 import {
   loadAppearance,
   saveAppearance,
@@ -44,6 +73,7 @@ import {
   applyStoredTheme,
 } from './utils/applyTheme'
 import { THEME_PACKS } from './utils/themePacks'
+import { formatShortcut } from './utils/shortcutLabel'
 import {
   loadSearchPrefs,
   saveSearchPrefs,
@@ -52,8 +82,44 @@ import {
   type SearchTrigger,
 } from './utils/searchPrefs'
 
+type ShortcutField = 'shortcut_primary' | 'shortcut_secondary'
+
+/** Another program's use of a shortcut, from `check_binding_conflicts` */
+interface BindingConflict {
+  binding: string
+  current_action: string
+  owner: string
+}
+
+/** A shortcut waiting for the user to confirm it despite conflicts */
+interface PendingShortcut {
+  field: ShortcutField
+  value: string
+  conflicts: BindingConflict[]
+}
+
 const MIN_HISTORY_SIZE = 1
 const MAX_HISTORY_SIZE = 100_000
+const MIN_EMOJI_HALF_LIFE_DAYS = 1
+const MAX_EMOJI_HALF_LIFE_DAYS = 365
+const DEFAULT_EMOJI_HALF_LIFE_DAYS = 30
+
+const DEFAULT_PASTE_TIMINGS: PasteTimings = {
+  pre_delay_ms: 50,
+  key_delay_ms: 50,
+  post_delay_ms: 30,
+  focus_timeout_ms: 300,
+  focus_retries: 1,
+}
+
+/** Paste timing inputs: field, label, unit and the range the backend clamps to */
+const PASTE_TIMING_FIELDS: [keyof PasteTimings, string, string, number, number][] = [
+  ['pre_delay_ms', 'Delay before pasting', 'ms', 0, 2000],
+  ['key_delay_ms', 'Delay between keys', 'ms', 1, 500],
+  ['post_delay_ms', 'Delay after pasting', 'ms', 0, 2000],
+  ['focus_timeout_ms', 'Focus wait', 'ms', 50, 5000],
+  ['focus_retries', 'Focus retries', '', 0, 5],
+]
 
 const DEFAULT_SETTINGS: UserSettings = {
   theme_mode: 'system',
@@ -62,6 +128,7 @@ const DEFAULT_SETTINGS: UserSettings = {
   enable_smart_actions: true,
   enable_ui_polish: true,
   enable_dynamic_tray_icon: true,
+  symbolic_tray_icon: false,
   max_history_size: 50,
   custom_kaomojis: [],
   ui_scale: 1,
@@ -69,6 +136,9 @@ const DEFAULT_SETTINGS: UserSettings = {
   auto_delete_unit: 'hours',
   excluded_patterns: [],
   tenor_api_key: '',
+  window_close_policy: 'on_focus_loss',
+  shortcut_primary: '<Super>v',
+  shortcut_secondary: '<Ctrl><Alt>v',
 }
 
 type ThemeMode = 'system' | 'dark' | 'light'
@@ -188,6 +258,17 @@ const SETTINGS_CATEGORIES = [
 
 type SettingsCat = (typeof SETTINGS_CATEGORIES)[number]['id']
 
+/** Summarizes an import report, e.g. "3 values were adjusted" */
+function describeImport(report: ImportReport): string {
+  const adjusted = report.warnings.filter((w) => w.kind === 'adjusted').length
+  const dropped = report.warnings.length - adjusted
+  const parts = [
+    adjusted > 0 && `${adjusted} value${adjusted === 1 ? ' was' : 's were'} adjusted`,
+    dropped > 0 && `${dropped} value${dropped === 1 ? ' was' : 's were'} ignored`,
+  ].filter(Boolean)
+  return parts.length > 0 ? parts.join(', ') : 'All values are valid'
+}
+
 function SettingsApp() {
   const [settings, setSettings] = useState<UserSettings>(DEFAULT_SETTINGS)
   const [activeCat, setActiveCat] = useState<SettingsCat>('appearance')
@@ -206,17 +287,20 @@ function SettingsApp() {
   // Apply appearance tokens + the stored theme on mount so this window matches,
   // and provide single updaters that persist, apply live, and broadcast to the
   // clipboard window so both re-theme instantly.
+  const systemAccent = useSystemAccentColor()
+
   useEffect(() => {
-    applyAppearance(appearance)
     applyStoredTheme()
-    // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [])
 
+  useEffect(() => {
+    applyAppearance(appearance, systemAccent?.hex)
+  }, [appearance, systemAccent])
+
   const updateAppearance = useCallback((patch: Partial<AppearanceTokens>) => {
     setAppearance((prev) => {
       const next = { ...prev, ...patch }
       saveAppearance(next)
-      applyAppearance(next)
       emit('appearance-changed', next).catch(() => {})
       return next
     })
@@ -283,12 +367,57 @@ function SettingsApp() {
   // Diagnostics export state
   const [diagnosticsMessage, setDiagnosticsMessage] = useState<string | null>(null)
   const [isExporting, setIsExporting] = useState(false)
+  const [environment, setEnvironment] = useState<EnvironmentInfo | null>(null)
+  const [sessionInfo, setSessionInfo] = useState<SessionInfo | null>(null)
+
+  useEffect(() => {
+    invoke<EnvironmentInfo>('get_environment_info')
+      .then(setEnvironment)
+      .catch((err) => console.error('Failed to detect the desktop environment:', err))
+    invoke<SessionInfo>('get_session_info')
+      .then(setSessionInfo)
+      .catch((err) => console.error('Failed to detect the session:', err))
+  }, [])
+
+  // Settings backup: export to / import from a JSON file. An import is
+  // previewed first and only saved once the user confirms.
+  const [backupPath, setBackupPath] = useState('')
+  const [includeEmojiHistory, setIncludeEmojiHistory] = useState(false)
+  const [backupMessage, setBackupMessage] = useState<string | null>(null)
+  const [importPreview, setImportPreview] = useState<ImportReport | null>(null)
+
+  // Paste diagnostics: the report is read after a delay so the user can
+  // focus the app they're trying to paste into
+  const [pasteReport, setPasteReport] = useState<string | null>(null)
+  const [isDiagnosingPaste, setIsDiagnosingPaste] = useState(false)
+
+  const handleDiagnosePaste = useCallback(async () => {
+    setIsDiagnosingPaste(true)
+    setPasteReport(null)
+    try {
+      const report = await invoke<{ summary: string }>('diagnose_paste', { delaySecs: 3 })
+      setPasteReport(report.summary)
+    } catch (err) {
+      setPasteReport(`Failed to diagnose paste: ${String(err)}`)
+    } finally {
+      setIsDiagnosingPaste(false)
+    }
+  }, [])
+
+  const handleReleaseKeys = useCallback(async () => {
+    try {
+      await invoke('release_all_modifiers')
+      setPasteReport('Released Ctrl, Shift, Alt and Super.')
+    } catch (err) {
+      setPasteReport(String(err))
+    }
+  }, [])
 
   const handleExportDiagnostics = useCallback(async () => {
     setIsExporting(true)
     setDiagnosticsMessage(null)
     try {
-      const path = await invoke<string>('export_diagnostics')
+      const path = await invoke<string>('generate_diagnostic_report', { path: null })
       setDiagnosticsMessage(`Saved diagnostics to ${path}`)
     } catch (err) {
       setDiagnosticsMessage(
@@ -299,6 +428,46 @@ function SettingsApp() {
     }
   }, [])
 
+  const handleExportSettings = useCallback(async () => {
+    setImportPreview(null)
+    try {
+      await invoke('export_settings', {
+        path: backupPath,
+        includeEmojiHistory,
+      })
+      setBackupMessage(`Saved settings to ${backupPath}`)
+    } catch (err) {
+      setBackupMessage(`Failed to export settings: ${String(err)}`)
+    }
+  }, [backupPath, includeEmojiHistory])
+
+  const handlePreviewImport = useCallback(async () => {
+    try {
+      const report = await invoke<ImportReport>('preview_import', { path: backupPath })
+      setImportPreview(report)
+      setBackupMessage(`${describeImport(report)}. Apply the import to save it.`)
+    } catch (err) {
+      setImportPreview(null)
+      setBackupMessage(`Failed to read settings: ${String(err)}`)
+    }
+  }, [backupPath])
+
+  const handleApplyImport = useCallback(async () => {
+    try {
+      const report = await invoke<ImportReport>('import_settings', {
+        path: backupPath,
+        commit: true,
+      })
+      const loaded = await invoke<UserSettings>('get_user_settings')
+      setSettings(loaded)
+      applyBackgroundOpacity(loaded)
+      setImportPreview(null)
+      setBackupMessage(`Imported settings. ${describeImport(report)}.`)
+    } catch (err) {
+      setBackupMessage(`Failed to import settings: ${String(err)}`)
+    }
+  }, [backupPath])
+
   // Update check state
   const [appVersion, setAppVersion] = useState('')
   const [updateStatus, setUpdateStatus] = useState<{ message: string; url?: string } | null>(null)
@@ -385,12 +554,18 @@ function SettingsApp() {
     setSaveMessage(null)
 
     try {
-      await invoke('set_user_settings', { newSettings })
-      setSaveMessage('Saved')
-      setTimeout(() => setSaveMessage(null), 2000)
+      const issues = await invoke<ValidationIssue[]>('set_user_settings', { newSettings })
+      if (issues.length > 0) {
+        console.warn('Some settings were adjusted:', issues)
+        setSaveMessage(issues.map((issue) => `${issue.field} ${issue.reason}`).join('; '))
+        setTimeout(() => setSaveMessage(null), 5000)
+      } else {
+        setSaveMessage('Saved')
+        setTimeout(() => setSaveMessage(null), 2000)
+      }
     } catch (err) {
       console.error('Failed to save settings:', err)
-      setSaveMessage('Error saving')
+      setSaveMessage(typeof err === 'string' ? err : 'Error saving')
     } finally {
       setIsSaving(false)
     }
@@ -408,6 +583,29 @@ function SettingsApp() {
     [saveSettings]
   )
 
+  // Shortcuts are checked against the desktop's own before saving; one that
+  // is taken waits for confirmation
+  const [pendingShortcut, setPendingShortcut] = useState<PendingShortcut | null>(null)
+
+  const proposeShortcut = useCallback(
+    async (field: ShortcutField, value: string) => {
+      try {
+        const result = await invoke<{ conflicts: BindingConflict[] }>('check_binding_conflicts', {
+          binding: value,
+        })
+        if (result.conflicts.length > 0) {
+          setPendingShortcut({ field, value, conflicts: result.conflicts })
+          return
+        }
+      } catch {
+        // A binding that doesn't parse is rejected when saving
+      }
+      setPendingShortcut(null)
+      updateSettings({ [field]: value })
+    },
+    [updateSettings]
+  )
+
   // Handle theme mode change
   const handleThemeModeChange = (mode: ThemeMode) => {
     updateSettings({ theme_mode: mode })
@@ -470,27 +668,28 @@ function SettingsApp() {
   }
 
   // Custom Kaomoji Handlers
-  const addCustomKaomoji = useCallback(() => {
+  // The backend validates, dedupes and saves; it returns the updated list
+  const addCustomKaomoji = useCallback(async () => {
     const val = newKaomoji.trim()
     if (!val) return
 
-    const newItem: CustomKaomoji = {
-      text: val,
-      category: 'Custom',
-      keywords: ['custom'],
+    try {
+      const list = await invoke<CustomKaomoji[]>('add_custom_kaomoji', { text: val })
+      setSettings((prev) => ({ ...prev, custom_kaomojis: list }))
+      setNewKaomoji('')
+    } catch (err) {
+      console.error('Failed to add kaomoji:', err)
     }
+  }, [newKaomoji])
 
-    updateSettings({ custom_kaomojis: [...settings.custom_kaomojis, newItem] })
-    setNewKaomoji('')
-  }, [newKaomoji, settings.custom_kaomojis, updateSettings])
-
-  const removeCustomKaomojiAt = useCallback(
-    (index: number) => {
-      const newList = settings.custom_kaomojis.filter((_, i) => i !== index)
-      updateSettings({ custom_kaomojis: newList })
-    },
-    [settings.custom_kaomojis, updateSettings]
-  )
+  const removeCustomKaomoji = useCallback(async (text: string) => {
+    try {
+      const list = await invoke<CustomKaomoji[]>('remove_custom_kaomoji', { text })
+      setSettings((prev) => ({ ...prev, custom_kaomojis: list }))
+    } catch (err) {
+      console.error('Failed to remove kaomoji:', err)
+    }
+  }, [])
 
   // Handle window close
   const handleClose = async () => {
@@ -671,10 +870,84 @@ function SettingsApp() {
             className="rounded-xl p-6 border shadow-sm transition-all bg-[var(--surface-1)] border-[color:var(--surface-border)]"
           >
             <h2 className="text-base font-semibold mb-3">Keyboard</h2>
+            <div className="grid grid-cols-2 gap-3 mb-5">
+              {(
+                [
+                  ['shortcut_primary', 'Open clipboard'],
+                  ['shortcut_secondary', 'Open clipboard (alternative)'],
+                ] as const
+              ).map(([field, label]) => (
+                <label key={field} className="flex flex-col gap-1.5">
+                  <span className="text-sm font-medium">{label}</span>
+                  {/* Saved on blur so half-typed bindings never reach the desktop */}
+                  <input
+                    key={`${settings[field]}-${pendingShortcut?.field === field}`}
+                    type="text"
+                    defaultValue={
+                      pendingShortcut?.field === field
+                        ? pendingShortcut.value
+                        : (settings[field] ?? DEFAULT_SETTINGS[field])
+                    }
+                    onBlur={(e) => {
+                      const value = e.target.value.trim()
+                      if (value && value !== settings[field]) proposeShortcut(field, value)
+                    }}
+                    placeholder={DEFAULT_SETTINGS[field]}
+                    className={clsx(
+                      'w-full px-3 py-2 rounded-lg border outline-none transition-all font-mono text-sm',
+                      isDark
+                        ? 'bg-white/5 border-white/10 focus:border-win11-bg-accent text-white'
+                        : 'bg-gray-50 border-gray-200 focus:border-win11-bg-accent text-gray-800'
+                    )}
+                  />
+                </label>
+              ))}
+            </div>
+            {pendingShortcut && (
+              <div
+                role="alert"
+                className={clsx(
+                  'mb-5 p-3 rounded-lg border text-sm',
+                  isDark
+                    ? 'bg-amber-500/10 border-amber-500/30 text-amber-200'
+                    : 'bg-amber-50 border-amber-200 text-amber-800'
+                )}
+              >
+                <ul className="space-y-0.5 mb-2">
+                  {pendingShortcut.conflicts.map((conflict, i) => (
+                    <li key={`${conflict.owner}-${i}`}>
+                      <span className="font-mono">{pendingShortcut.value}</span> is already used by{' '}
+                      {conflict.owner} for "{conflict.current_action}"
+                    </li>
+                  ))}
+                </ul>
+                <div className="flex gap-2">
+                  <button
+                    onClick={() => {
+                      updateSettings({ [pendingShortcut.field]: pendingShortcut.value })
+                      setPendingShortcut(null)
+                    }}
+                    className="px-3 py-1 rounded-md text-xs font-medium border border-current"
+                  >
+                    Use anyway
+                  </button>
+                  <button
+                    onClick={() => setPendingShortcut(null)}
+                    className="px-3 py-1 rounded-md text-xs font-medium opacity-80 hover:opacity-100"
+                  >
+                    Keep current shortcut
+                  </button>
+                </div>
+              </div>
+            )}
             <div className="space-y-2 text-sm">
               {[
-                ['Open clipboard', 'Super + V'],
-                ['Emoji picker', 'Super + .'],
+                ['Open clipboard', formatShortcut(settings.shortcut_primary ?? '<Super>v')],
+                [
+                  'Open clipboard (alternative)',
+                  formatShortcut(settings.shortcut_secondary ?? '<Ctrl><Alt>v'),
+                ],
+                ['Emoji picker', 'Super+.'],
                 ['Paste selected', 'Enter'],
                 ['Navigate items', '↑ / ↓ / Tab'],
                 ['Close window', 'Esc'],
@@ -839,6 +1112,19 @@ function SettingsApp() {
                 isDark={isDark}
               />
             </div>
+            <div className="flex items-center justify-between mt-4">
+              <div>
+                <div className="text-sm font-medium">Symbolic Tray Icon</div>
+                <div className={clsx('text-xs mt-0.5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                  Use a monochrome icon, white on dark panels and black on light ones.
+                </div>
+              </div>
+              <Switch
+                checked={settings.symbolic_tray_icon}
+                onChange={() => handleToggle('symbolic_tray_icon')}
+                isDark={isDark}
+              />
+            </div>
           </div>
         </section>
 
@@ -961,6 +1247,25 @@ function SettingsApp() {
             </div>
           </div>
 
+          {/* Desktop accent colour */}
+          <div className="flex items-center justify-between mt-6">
+            <div>
+              <label className="text-sm font-medium">Use system accent color</label>
+              <p className={clsx('text-xs mt-0.5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                {systemAccent
+                  ? `Follows your desktop's accent (${systemAccent.hex}) instead of the color above.`
+                  : "Your desktop doesn't publish an accent color, so the color above is used."}
+              </p>
+            </div>
+            <Switch
+              checked={!!appearance.followSystemAccent}
+              onChange={() =>
+                updateAppearance({ followSystemAccent: !appearance.followSystemAccent })
+              }
+              isDark={isDark}
+            />
+          </div>
+
           {/* Rounded window corners */}
           <div className="flex items-center justify-between mt-6">
             <div>
@@ -1073,6 +1378,46 @@ function SettingsApp() {
           </div>
         </section>
 
+        {/* Window Close Policy Section */}
+        <section
+          hidden={activeCat !== 'clipboard'}
+          className={clsx(
+            'rounded-xl p-6 border shadow-sm transition-all',
+            'bg-[var(--surface-1)] border-[color:var(--surface-border)]'
+          )}
+        >
+          <div className="mb-4">
+            <h2 className="text-base font-semibold">Close Window</h2>
+            <p className={clsx('text-xs mt-0.5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+              When the clipboard window hides by itself. Esc and the close button always work.
+            </p>
+          </div>
+          <div className="flex gap-2">
+            {(
+              [
+                ['on_focus_loss', 'When unfocused'],
+                ['on_paste', 'After pasting'],
+                ['manual', 'Manually'],
+              ] as const
+            ).map(([policy, label]) => (
+              <button
+                key={policy}
+                onClick={() => updateSettings({ window_close_policy: policy })}
+                className={clsx(
+                  'flex-1 py-2.5 rounded-lg border transition-all text-xs font-semibold',
+                  (settings.window_close_policy ?? 'on_focus_loss') === policy
+                    ? 'bg-win11-bg-accent text-white border-win11-bg-accent'
+                    : isDark
+                      ? 'bg-white/5 border-white/10 text-gray-400 hover:bg-white/10'
+                      : 'bg-gray-50 border-gray-200 text-gray-600 hover:bg-gray-100'
+                )}
+              >
+                {label}
+              </button>
+            ))}
+          </div>
+        </section>
+
         {/* UI Scale Section */}
         <section
           hidden={activeCat !== 'appearance'}
@@ -1175,6 +1520,40 @@ function SettingsApp() {
                 )}
               />
             </div>
+            <div className="flex justify-between items-center">
+              <div>
+                <label htmlFor="emoji-half-life" className="text-sm font-medium">
+                  Emoji Ranking Half-life
+                </label>
+                <p className={clsx('text-xs mt-0.5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                  Days after which an emoji use counts half as much when ranking your most used
+                  emojis ({MIN_EMOJI_HALF_LIFE_DAYS} - {MAX_EMOJI_HALF_LIFE_DAYS})
+                </p>
+              </div>
+              <input
+                id="emoji-half-life"
+                type="number"
+                min={MIN_EMOJI_HALF_LIFE_DAYS}
+                max={MAX_EMOJI_HALF_LIFE_DAYS}
+                value={settings.emoji_decay_half_life_days ?? DEFAULT_EMOJI_HALF_LIFE_DAYS}
+                onChange={(e) => {
+                  const parsed = Number.parseFloat(e.target.value)
+                  if (Number.isNaN(parsed)) return
+                  const value = Math.max(
+                    MIN_EMOJI_HALF_LIFE_DAYS,
+                    Math.min(MAX_EMOJI_HALF_LIFE_DAYS, parsed)
+                  )
+                  updateSettings({ emoji_decay_half_life_days: value })
+                }}
+                className={clsx(
+                  'w-28 text-right font-mono border rounded-md transition-all focus:outline-none focus:ring-2 focus:ring-win11-bg-accent/50',
+                  'input-number-compact no-number-spinner',
+                  isDark
+                    ? 'bg-white/5 border-white/10 text-white'
+                    : 'bg-gray-50 border-gray-200 text-gray-900'
+                )}
+              />
+            </div>
           </div>
         </section>
 
@@ -1224,9 +1603,9 @@ function SettingsApp() {
             {/* List */}
             {settings.custom_kaomojis.length > 0 ? (
               <div className="grid grid-cols-2 md:grid-cols-3 gap-2 max-h-48 overflow-y-auto scrollbar-win11 pr-1">
-                {settings.custom_kaomojis.map((item, idx) => (
+                {settings.custom_kaomojis.map((item) => (
                   <div
-                    key={idx}
+                    key={item.text}
                     className={clsx(
                       'group flex items-center justify-between px-3 py-2 rounded-md border transition-colors',
                       isDark ? 'bg-white/5 border-white/10' : 'bg-gray-50 border-gray-200'
@@ -1236,7 +1615,7 @@ function SettingsApp() {
                       {item.text}
                     </span>
                     <button
-                      onClick={() => removeCustomKaomojiAt(idx)}
+                      onClick={() => removeCustomKaomoji(item.text)}
                       className="opacity-0 group-hover:opacity-100 p-1 text-red-500 hover:bg-red-500/10 rounded transition-all"
                       title="Delete"
                     >
@@ -1409,6 +1788,162 @@ function SettingsApp() {
           </div>
         </section>
 
+        {/* Paste Section */}
+        <section
+          hidden={activeCat !== 'advanced'}
+          className={clsx(
+            'rounded-xl p-6 border shadow-sm transition-all',
+            'bg-[var(--surface-1)] border-[color:var(--surface-border)]'
+          )}
+        >
+          <h2 className="text-base font-semibold mb-1">Pasting</h2>
+          <p className={clsx('text-xs mb-5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+            How the paste keystroke is sent. Raise the delays if pastes go missing on a slow
+            machine or VM.
+          </p>
+
+          <div className="space-y-4">
+            <div>
+              <p className="text-sm font-medium">Paste method</p>
+              <p className={clsx('text-xs mt-0.5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                For apps without a rule below. Type it out works where pasting is blocked.
+              </p>
+              <div className="flex gap-2 mt-2">
+                {PASTE_METHODS.map(([method, label]) => (
+                  <button
+                    key={method}
+                    onClick={() => updateSettings({ paste_method: method })}
+                    className={clsx(
+                      'flex-1 py-2 rounded-lg border transition-all text-xs font-semibold',
+                      (settings.paste_method ?? 'auto') === method
+                        ? 'bg-win11-bg-accent text-white border-win11-bg-accent'
+                        : isDark
+                          ? 'bg-white/5 border-white/10 text-gray-400 hover:bg-white/10'
+                          : 'bg-gray-50 border-gray-200 text-gray-600 hover:bg-gray-100'
+                    )}
+                  >
+                    {label}
+                  </button>
+                ))}
+              </div>
+            </div>
+
+            <div className="flex justify-between items-center">
+              <div>
+                <label htmlFor="paste-combo" className="text-sm font-medium">
+                  Paste keystroke
+                </label>
+                <p className={clsx('text-xs mt-0.5', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                  e.g. Shift+Insert, in place of the paste method. Leave empty to use the method.
+                </p>
+              </div>
+              <input
+                id="paste-combo"
+                type="text"
+                key={settings.paste_combo ?? ''}
+                defaultValue={settings.paste_combo ?? ''}
+                placeholder="Automatic"
+                onBlur={(e) => {
+                  const value = e.target.value.trim()
+                  if (value !== (settings.paste_combo ?? '')) {
+                    updateSettings({ paste_combo: value })
+                  }
+                }}
+                className={clsx(
+                  'w-40 px-2 py-1 text-sm font-mono border rounded-md transition-all focus:outline-none focus:ring-2 focus:ring-win11-bg-accent/50',
+                  isDark
+                    ? 'bg-white/5 border-white/10 text-white'
+                    : 'bg-gray-50 border-gray-200 text-gray-900'
+                )}
+              />
+            </div>
+
+            {PASTE_TIMING_FIELDS.map(([field, label, unit, min, max]) => {
+              const timings = settings.paste_timing ?? DEFAULT_PASTE_TIMINGS
+              return (
+                <div key={field} className="flex justify-between items-center">
+                  <label htmlFor={`paste-${field}`} className="text-sm font-medium">
+                    {label}
+                    {unit && (
+                      <span
+                        className={clsx(
+                          'ml-1 text-xs',
+                          isDark ? 'text-gray-400' : 'text-gray-500'
+                        )}
+                      >
+                        ({unit})
+                      </span>
+                    )}
+                  </label>
+                  <input
+                    id={`paste-${field}`}
+                    type="number"
+                    min={min}
+                    max={max}
+                    value={timings[field]}
+                    onChange={(e) => {
+                      const parsed = Number.parseInt(e.target.value, 10)
+                      if (Number.isNaN(parsed)) return
+                      const value = Math.max(min, Math.min(max, parsed))
+                      updateSettings({ paste_timing: { ...timings, [field]: value } })
+                    }}
+                    className={clsx(
+                      'w-28 text-right font-mono border rounded-md transition-all focus:outline-none focus:ring-2 focus:ring-win11-bg-accent/50',
+                      'input-number-compact no-number-spinner',
+                      isDark
+                        ? 'bg-white/5 border-white/10 text-white'
+                        : 'bg-gray-50 border-gray-200 text-gray-900'
+                    )}
+                  />
+                </div>
+              )
+            })}
+
+            <div className="flex justify-between items-center">
+              <label htmlFor="type-char-delay" className="text-sm font-medium">
+                Delay between typed characters
+                <span className={clsx('ml-1 text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                  (ms)
+                </span>
+              </label>
+              <input
+                id="type-char-delay"
+                type="number"
+                min={0}
+                max={1000}
+                value={settings.type_char_delay_ms ?? 10}
+                onChange={(e) => {
+                  const parsed = Number.parseInt(e.target.value, 10)
+                  if (Number.isNaN(parsed)) return
+                  updateSettings({ type_char_delay_ms: Math.max(0, Math.min(1000, parsed)) })
+                }}
+                className={clsx(
+                  'w-28 text-right font-mono border rounded-md transition-all focus:outline-none focus:ring-2 focus:ring-win11-bg-accent/50',
+                  'input-number-compact no-number-spinner',
+                  isDark
+                    ? 'bg-white/5 border-white/10 text-white'
+                    : 'bg-gray-50 border-gray-200 text-gray-900'
+                )}
+              />
+            </div>
+
+            <div>
+              <p className="text-sm font-medium">Per-app rules</p>
+              <p
+                className={clsx('text-xs mt-0.5 mb-2', isDark ? 'text-gray-400' : 'text-gray-500')}
+              >
+                Paste differently into apps matched by window class (X11 only). The first matching
+                rule wins.
+              </p>
+              <PasteRulesEditor
+                rules={settings.app_paste_rules ?? []}
+                onChange={(rules) => updateSettings({ app_paste_rules: rules })}
+                isDark={isDark}
+              />
+            </div>
+          </div>
+        </section>
+
         {/* Diagnostics Section */}
         <section
           hidden={activeCat !== 'advanced'}
@@ -1420,12 +1955,28 @@ function SettingsApp() {
           <div className="p-6 border-b border-inherit">
             <h2 className="text-base font-semibold mb-1">Diagnostics</h2>
             <p className={clsx('text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>
-              Export a report (app version, environment, and recent logs) to help
-              with troubleshooting. Clipboard content is never included.
+              Export a report (app version, environment, permissions, shortcut setup, settings
+              and recent logs) to help with troubleshooting. Clipboard content and API keys are
+              never included.
             </p>
           </div>
 
           <div className="p-6 space-y-3">
+            {environment && (
+              <p className={clsx('text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                Desktop: {environment.name}
+                {environment.version ? ` ${environment.version}` : ''} ·{' '}
+                {environment.session_type} session
+                {environment.compositor ? ` · ${environment.compositor}` : ''}
+                {environment.is_flatpak ? ' · Flatpak' : ''}
+              </p>
+            )}
+            {sessionInfo?.compositor && (
+              <p className={clsx('text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>
+                Compositor: {sessionInfo.compositor}
+                {sessionInfo.xwayland ? ' · running through XWayland' : ''}
+              </p>
+            )}
             <button
               onClick={handleExportDiagnostics}
               disabled={isExporting}
@@ -1447,6 +1998,118 @@ function SettingsApp() {
                 {diagnosticsMessage}
               </p>
             )}
+
+            <button
+              onClick={handleDiagnosePaste}
+              disabled={isDiagnosingPaste}
+              className={clsx(
+                'px-4 py-2 text-sm font-medium rounded-lg transition-all',
+                'bg-win11-bg-accent text-white hover:opacity-90 active:scale-95',
+                isDiagnosingPaste && 'opacity-60 cursor-not-allowed'
+              )}
+            >
+              {isDiagnosingPaste ? 'Switch to the target app…' : 'Diagnose paste'}
+            </button>
+            <button
+              onClick={handleReleaseKeys}
+              className={clsx(
+                'ml-2 px-4 py-2 text-sm font-medium rounded-lg transition-all border',
+                'border-[color:var(--surface-border)] hover:opacity-90 active:scale-95'
+              )}
+            >
+              Release stuck keys
+            </button>
+            <p className={clsx('text-[11px]', isDark ? 'text-gray-400' : 'text-gray-500')}>
+              Checks how pasting would work without sending any keys. Focus the app
+              you paste into within 3 seconds.
+            </p>
+            {pasteReport && (
+              <pre
+                className={clsx(
+                  'text-[11px] leading-relaxed whitespace-pre-wrap break-all rounded-lg p-3',
+                  isDark ? 'bg-black/20 text-gray-300' : 'bg-gray-50 text-gray-700'
+                )}
+              >
+                {pasteReport}
+              </pre>
+            )}
+          </div>
+
+          <div className="p-6 border-t border-inherit space-y-3">
+            <h2 className="text-base font-semibold mb-1">Backup &amp; Restore</h2>
+            <p className={clsx('text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>
+              Export your settings and custom kaomoji to a file, or import them from one.
+              Imports are checked first and show any values that had to change.
+            </p>
+            <input
+              type="text"
+              value={backupPath}
+              onChange={(e) => {
+                setBackupPath(e.target.value)
+                setImportPreview(null)
+              }}
+              placeholder="/home/you/penguinclip-settings.json"
+              className={clsx(
+                'w-full px-3 py-2 rounded-md border text-sm focus:outline-none focus:ring-2 focus:ring-win11-bg-accent/50 transition-all',
+                isDark
+                  ? 'bg-white/5 border-white/10 text-white placeholder-gray-500'
+                  : 'bg-gray-50 border-gray-200 text-gray-900 placeholder-gray-400'
+              )}
+            />
+            <label className="flex items-center gap-2 text-xs">
+              <input
+                type="checkbox"
+                checked={includeEmojiHistory}
+                onChange={(e) => setIncludeEmojiHistory(e.target.checked)}
+              />
+              Include emoji history in the export
+            </label>
+            <div className="flex gap-2">
+              <button
+                onClick={handleExportSettings}
+                disabled={!backupPath.trim()}
+                className="px-4 py-2 text-sm font-medium rounded-lg transition-all bg-win11-bg-accent text-white hover:opacity-90 active:scale-95 disabled:opacity-60"
+              >
+                Export settings
+              </button>
+              <button
+                onClick={importPreview ? handleApplyImport : handlePreviewImport}
+                disabled={!backupPath.trim()}
+                className={clsx(
+                  'px-4 py-2 text-sm font-medium rounded-lg transition-all border',
+                  'border-[color:var(--surface-border)] hover:opacity-90 active:scale-95 disabled:opacity-60'
+                )}
+              >
+                {importPreview ? 'Apply import' : 'Import settings…'}
+              </button>
+            </div>
+            {backupMessage && (
+              <p
+                className={clsx(
+                  'text-[11px] leading-relaxed break-all',
+                  isDark ? 'text-gray-400' : 'text-gray-500'
+                )}
+              >
+                {backupMessage}
+              </p>
+            )}
+            {importPreview && importPreview.warnings.length > 0 && (
+              <ul
+                className={clsx(
+                  'text-[11px] leading-relaxed rounded-lg p-3 space-y-1',
+                  isDark ? 'bg-black/20 text-gray-300' : 'bg-gray-50 text-gray-700'
+                )}
+              >
+                {importPreview.warnings.map((w) => (
+                  <li key={w.field} className="break-all">
+                    <span className="font-mono">{w.field}</span>:{' '}
+                    {w.kind === 'unknown'
+                      ? 'unknown setting, ignored'
+                      : `${JSON.stringify(w.imported)} → ${JSON.stringify(w.applied)}`}
+                  </li>
+                ))}
+              </ul>
+            )}
           </div>
         </section>
 
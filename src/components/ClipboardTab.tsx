@@ -34,6 +34,14 @@ export function ClipboardTab(props: {
   togglePin: (id: string) => void
   toggleFavorite: (id: string) => void
   onPaste: (id: string) => void
+  onPasteToSource?: (id: string) => void
+  /** Types the item's text out instead of pasting it */
+  onType?: (id: string) => void
+  /** More history can be loaded after the items in `history` */
+  hasMore?: boolean
+  onLoadMore?: () => void
+  /** Fetches the whole text of items a page cut short */
+  onNeedFullContent?: (ids: string[]) => void
   settings: UserSettings
   tabBarRef: React.RefObject<TabBarRef | null>
 }) {
@@ -48,6 +56,11 @@ export function ClipboardTab(props: {
     togglePin,
     toggleFavorite,
     onPaste,
+    onPasteToSource,
+    onType,
+    hasMore = false,
+    onLoadMore,
+    onNeedFullContent,
     settings,
     tabBarRef,
   } = props
@@ -243,6 +256,32 @@ export function ClipboardTab(props: {
     }
   }, [])
 
+  // The next page loads when the end of the list scrolls into view. The
+  // observer is renewed per page so a short page that leaves the end in
+  // view still loads another.
+  const endOfListRef = useRef<HTMLDivElement | null>(null)
+  useEffect(() => {
+    const end = endOfListRef.current
+    if (!end || !hasMore || !onLoadMore) return
+    const observer = new IntersectionObserver(
+      (entries) => {
+        if (entries.some((entry) => entry.isIntersecting)) onLoadMore()
+      },
+      { rootMargin: '200px' }
+    )
+    observer.observe(end)
+    return () => observer.disconnect()
+  }, [history, hasMore, onLoadMore])
+
+  // A search covers the whole history: load the remaining pages, and the
+  // full text of items a page cut short
+  useEffect(() => {
+    if (!searchQuery) return
+    if (hasMore) onLoadMore?.()
+    const truncated = history.filter((item) => item.content_truncated).map((item) => item.id)
+    if (truncated.length) onNeedFullContent?.(truncated)
+  }, [searchQuery, history, hasMore, onLoadMore, onNeedFullContent])
+
   // Fuzzy search index, rebuilt only when the history changes so that
   // per-keystroke searching stays fast even with large histories.
   const fuse = useMemo(() => createHistoryFuse(history), [history])
@@ -356,6 +395,8 @@ export function ClipboardTab(props: {
       index={index}
       isFocused={index === focusedIndex}
       onPaste={onPaste}
+      onPasteToSource={onPasteToSource}
+      onType={onType}
       onDelete={deleteItem}
       onTogglePin={togglePin}
       onToggleFavorite={toggleFavorite}
@@ -499,6 +540,7 @@ export function ClipboardTab(props: {
           {filteredHistory.map((item, index) => renderHistoryItem(item, index))}
         </div>
       )}
+      {hasMore && <div ref={endOfListRef} aria-hidden="true" className="h-px" />}
     </>
   )
 }
@@ -1,6 +1,8 @@
+import { useEffect, useState } from 'react'
 import { getCurrentWindow } from '@tauri-apps/api/window'
 import { invoke } from '@tauri-apps/api/core'
-import { Settings, X } from 'lucide-react'
+import { listen } from '@tauri-apps/api/event'
+import { Pin, Settings, X } from 'lucide-react'
 import { clsx } from 'clsx'
 
 interface DragHandleProps {
@@ -9,6 +11,16 @@ interface DragHandleProps {
 
 export function DragHandle({ isDark }: DragHandleProps) {
   const appWindow = getCurrentWindow()
+  // Keeps the window open regardless of the close policy; the backend drops
+  // the pin whenever the window hides, so start unpinned on every show.
+  const [pinned, setPinned] = useState(false)
+
+  useEffect(() => {
+    const unlisten = listen('window-shown', () => setPinned(false))
+    return () => {
+      unlisten.then((fn) => fn())
+    }
+  }, [])
 
   const handleMouseDown = async (e: React.MouseEvent) => {
     if (e.button !== 0) return
@@ -21,7 +33,15 @@ export function DragHandle({ isDark }: DragHandleProps) {
 
   const handleClose = (e: React.MouseEvent) => {
     e.stopPropagation()
-    appWindow.hide()
+    invoke('hide_window').catch(console.error)
+  }
+
+  const handleTogglePinned = (e: React.MouseEvent) => {
+    e.stopPropagation()
+    const next = !pinned
+    invoke('set_window_pinned', { pinned: next })
+      .then(() => setPinned(next))
+      .catch(console.error)
   }
 
   const handleOpenSettings = (e: React.MouseEvent) => {
@@ -44,6 +64,23 @@ export function DragHandle({ isDark }: DragHandleProps) {
       />
 
       <div className="absolute right-4 top-1/2 -translate-y-1/2 flex items-center gap-1 pt-4 z-10">
+        <button
+          onClick={handleTogglePinned}
+          onMouseDown={(e) => e.stopPropagation()}
+          className={clsx(
+            'p-1 rounded-md cursor-pointer transition-colors',
+            pinned
+              ? 'text-win11-bg-accent'
+              : isDark
+                ? 'text-white/50 hover:text-white/80 hover:bg-white/10'
+                : 'text-black/50 hover:text-black/80 hover:bg-black/10'
+          )}
+          tabIndex={-1}
+          title={pinned ? 'Unpin window' : 'Keep window open'}
+        >
+          <Pin className="w-4 h-4" fill={pinned ? 'currentColor' : 'none'} />
+        </button>
+
         <button
           onClick={handleOpenSettings}
           onMouseDown={(e) => e.stopPropagation()}
@@ -59,6 +59,7 @@ export function ImageContent({
 }) {
   if (item.content.type !== 'Image') return null
   const { width, height, base64 } = item.content.data
+  const label = imageLabel(width, height, item.byte_size)
 
   if (effectiveCompact) {
     return (
@@ -68,7 +69,7 @@ export function ImageContent({
           isDark ? 'text-win11-text-tertiary' : 'text-win11Light-text-secondary'
         )}
       >
-        Image ({width}×{height})
+        Image · {label}
       </span>
     )
   }
@@ -85,7 +86,7 @@ export function ImageContent({
         className="max-w-full max-h-28 rounded-[10px] border border-[var(--surface-border)] object-contain bg-black/10"
       />
       <span className="absolute bottom-1 right-1 text-xs px-1.5 py-0.5 rounded-md bg-black/55 text-white backdrop-blur-sm">
-        {width}×{height}
+        {label}
       </span>
     </div>
   )
@@ -126,3 +127,16 @@ export function Timestamp({
     </span>
   )
 }
+
+/** "PNG · 2.4 MB · 1920×1080", without the size for items saved before it
+ * was recorded. */
+function imageLabel(width: number, height: number, byteSize?: number): string {
+  const dims = `${width}×${height}`
+  return byteSize ? `PNG · ${formatBytes(byteSize)} · ${dims}` : dims
+}
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+}
@@ -1,6 +1,6 @@
 import { useCallback, forwardRef, useRef, useMemo } from 'react'
 import { clsx } from 'clsx'
-import { Pin, Star, X } from 'lucide-react'
+import { CornerDownLeft, Keyboard, Pin, Star, X } from 'lucide-react'
 import type { ClipboardItem } from '../../types/clipboard'
 import type { MatchRange } from '../../utils/highlightMatches'
 import { getCardBackgroundStyle } from '../../utils/themeUtils'
@@ -18,6 +18,10 @@ import { detectCategory, CATEGORY_CONFIG } from '../../utils/categoryDetection'
 interface HistoryItemProps {
   item: ClipboardItem
   onPaste: (id: string) => void
+  /** Pastes into the window the item was copied from */
+  onPasteToSource?: (id: string) => void
+  /** Types the item's text out, for apps that block pasting */
+  onType?: (id: string) => void
   onDelete: (id: string) => void
   onTogglePin: (id: string) => void
   onToggleFavorite: (id: string) => void
@@ -38,6 +42,8 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
   {
     item,
     onPaste,
+    onPasteToSource,
+    onType,
     onDelete,
     onTogglePin,
     onToggleFavorite,
@@ -88,6 +94,24 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
     onPaste(item.id)
   }, [item.id, onPaste])
 
+  // Handle paste back to the source window with stopPropagation
+  const handlePasteToSource = useCallback(
+    (e: React.MouseEvent) => {
+      e.stopPropagation()
+      onPasteToSource?.(item.id)
+    },
+    [item.id, onPasteToSource]
+  )
+
+  // Handle typing the text out with stopPropagation
+  const handleType = useCallback(
+    (e: React.MouseEvent) => {
+      e.stopPropagation()
+      onType?.(item.id)
+    },
+    [item.id, onType]
+  )
+
   // Handle delete with stopPropagation
   const handleDelete = useCallback(
     (e: React.MouseEvent) => {
@@ -242,6 +266,42 @@ export const HistoryItem = forwardRef<HTMLDivElement, HistoryItemProps>(function
             onActionClick={handleSmartAction}
           />
 
+          {/* Paste back to source button */}
+          {onPasteToSource && item.source_window_title && (
+            <button
+              onPointerDown={handlePointerDownPreventDefault}
+              onClick={handlePasteToSource}
+              className={clsx(
+                'p-1.5 rounded-md transition-colors',
+                isDark
+                  ? 'text-win11-text-tertiary hover:bg-win11-bg-tertiary'
+                  : 'text-win11Light-text-secondary hover:bg-win11Light-bg-tertiary'
+              )}
+              title={`Paste back into ${item.source_window_title}`}
+              tabIndex={-1}
+            >
+              <CornerDownLeft className="w-4 h-4" />
+            </button>
+          )}
+
+          {/* Type out button, for text only */}
+          {onType && item.content.type !== 'Image' && (
+            <button
+              onPointerDown={handlePointerDownPreventDefault}
+              onClick={handleType}
+              className={clsx(
+                'p-1.5 rounded-md transition-colors',
+                isDark
+                  ? 'text-win11-text-tertiary hover:bg-win11-bg-tertiary'
+                  : 'text-win11Light-text-secondary hover:bg-win11Light-bg-tertiary'
+              )}
+              title="Type it out instead of pasting"
+              tabIndex={-1}
+            >
+              <Keyboard className="w-4 h-4" />
+            </button>
+          )}
+
           {/* Favorite button */}
           <button
             onPointerDown={handlePointerDownPreventDefault}
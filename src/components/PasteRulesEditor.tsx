@@ -0,0 +1,189 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { clsx } from 'clsx'
+import { Plus, X } from 'lucide-react'
+
+import type { AppPasteRule, PasteMethod, PasteRuleMatch } from '../types/clipboard'
+import { Switch } from './Switch'
+
+/** Paste methods in the order they are offered, with their labels */
+export const PASTE_METHODS: [PasteMethod, string][] = [
+  ['auto', 'Automatic'],
+  ['ctrl-v', 'Ctrl+V'],
+  ['ctrl-shift-v', 'Ctrl+Shift+V'],
+  ['shift-insert', 'Shift+Insert'],
+  ['type', 'Type it out'],
+]
+
+const fieldClass = (isDark: boolean) =>
+  clsx(
+    'px-2 py-1 text-sm border rounded-md transition-all focus:outline-none focus:ring-2 focus:ring-win11-bg-accent/50',
+    isDark ? 'bg-white/5 border-white/10 text-white' : 'bg-gray-50 border-gray-200 text-gray-900'
+  )
+
+/** What pasting into the tested window does, in words */
+function describeMatch(match: PasteRuleMatch): string {
+  const action = match.keystroke ? `sends ${match.keystroke}` : 'types the text out'
+  if (match.rule_index === null) {
+    return `No rule matches; the default method ${action}.`
+  }
+  return `Rule ${match.rule_index + 1} (${match.pattern}) matches and ${action}.`
+}
+
+/**
+ * Per-app paste rules: a WM_CLASS pattern and the paste method used for
+ * matching windows. The first matching rule wins. Patterns are saved when
+ * the field loses focus, so half-typed ones never reach the backend.
+ */
+export function PasteRulesEditor({
+  rules,
+  onChange,
+  isDark,
+}: {
+  rules: AppPasteRule[]
+  onChange: (rules: AppPasteRule[]) => void
+  isDark: boolean
+}) {
+  const [testClass, setTestClass] = useState('')
+  const [testResult, setTestResult] = useState<string | null>(null)
+
+  const updateRule = (index: number, partial: Partial<AppPasteRule>) => {
+    onChange(rules.map((rule, i) => (i === index ? { ...rule, ...partial } : rule)))
+  }
+
+  const testRules = async () => {
+    const wmClass = testClass.trim()
+    if (!wmClass) return
+    try {
+      const match = await invoke<PasteRuleMatch>('test_paste_rule', { wmClass })
+      setTestResult(describeMatch(match))
+    } catch (err) {
+      setTestResult(String(err))
+    }
+  }
+
+  return (
+    <div className="space-y-3">
+      {rules.length > 0 && (
+        <ul className="space-y-2">
+          {rules.map((rule, index) => (
+            <li key={index} className="flex items-center gap-2">
+              <input
+                type="text"
+                key={rule.wm_class_pattern}
+                defaultValue={rule.wm_class_pattern}
+                placeholder="WM_CLASS, e.g. urxvt"
+                aria-label={`Rule ${index + 1} window class`}
+                onBlur={(e) => {
+                  const value = e.target.value.trim()
+                  if (value && value !== rule.wm_class_pattern) {
+                    updateRule(index, { wm_class_pattern: value })
+                  }
+                }}
+                className={clsx('flex-1 min-w-0 font-mono', fieldClass(isDark))}
+              />
+              <select
+                value={rule.method}
+                aria-label={`Rule ${index + 1} paste method`}
+                onChange={(e) => updateRule(index, { method: e.target.value as PasteMethod })}
+                className={fieldClass(isDark)}
+              >
+                {PASTE_METHODS.map(([method, label]) => (
+                  <option key={method} value={method}>
+                    {label}
+                  </option>
+                ))}
+              </select>
+              {(rule.method === 'auto' || rule.method === 'ctrl-v') && (
+                <label className="flex items-center gap-1.5 text-xs">
+                  <Switch
+                    checked={rule.use_shift}
+                    onChange={(useShift) => updateRule(index, { use_shift: useShift })}
+                    isDark={isDark}
+                  />
+                  Shift
+                </label>
+              )}
+              <button
+                onClick={() => onChange(rules.filter((_, i) => i !== index))}
+                aria-label={`Remove rule ${index + 1}`}
+                className={clsx(
+                  'flex-shrink-0 rounded p-1 transition-colors',
+                  isDark
+                    ? 'hover:bg-red-500/15 text-gray-400 hover:text-red-400'
+                    : 'hover:bg-red-50 text-gray-500 hover:text-red-600'
+                )}
+              >
+                <X className="h-3.5 w-3.5" aria-hidden="true" />
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      <AddRule isDark={isDark} onAdd={(rule) => onChange([...rules, rule])} />
+
+      <div className="flex items-center gap-2">
+        <input
+          type="text"
+          value={testClass}
+          placeholder="Test a WM_CLASS, e.g. org.remmina.Remmina"
+          aria-label="Window class to test"
+          onChange={(e) => setTestClass(e.target.value)}
+          onKeyDown={(e) => e.key === 'Enter' && testRules()}
+          className={clsx('flex-1 min-w-0 font-mono', fieldClass(isDark))}
+        />
+        <button
+          onClick={testRules}
+          disabled={!testClass.trim()}
+          className={clsx(
+            'px-3 py-1 rounded-md text-xs font-medium transition-colors disabled:opacity-50',
+            isDark ? 'bg-white/10 hover:bg-white/15' : 'bg-gray-100 hover:bg-gray-200'
+          )}
+        >
+          Test
+        </button>
+      </div>
+      {testResult && (
+        <p className={clsx('text-xs', isDark ? 'text-gray-400' : 'text-gray-500')}>{testResult}</p>
+      )}
+    </div>
+  )
+}
+
+/** A row for a new rule; it is added once it has a pattern */
+function AddRule({ isDark, onAdd }: { isDark: boolean; onAdd: (rule: AppPasteRule) => void }) {
+  const [pattern, setPattern] = useState('')
+
+  const add = () => {
+    const value = pattern.trim()
+    if (!value) return
+    onAdd({ wm_class_pattern: value, method: 'auto', use_shift: false })
+    setPattern('')
+  }
+
+  return (
+    <div className="flex items-center gap-2">
+      <input
+        type="text"
+        value={pattern}
+        placeholder="Add a rule for a WM_CLASS"
+        aria-label="New rule window class"
+        onChange={(e) => setPattern(e.target.value)}
+        onKeyDown={(e) => e.key === 'Enter' && add()}
+        className={clsx('flex-1 min-w-0 font-mono', fieldClass(isDark))}
+      />
+      <button
+        onClick={add}
+        disabled={!pattern.trim()}
+        aria-label="Add rule"
+        className={clsx(
+          'rounded-md p-1.5 transition-colors disabled:opacity-50',
+          isDark ? 'bg-white/10 hover:bg-white/15' : 'bg-gray-100 hover:bg-gray-200'
+        )}
+      >
+        <Plus className="h-3.5 w-3.5" aria-hidden="true" />
+      </button>
+    </div>
+  )
+}
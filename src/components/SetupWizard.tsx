@@ -14,32 +14,144 @@ import {
   Copy,
   AlertCircle,
   Zap,
+  Monitor,
+  XCircle,
 } from 'lucide-react'
 
+interface GifClipboardStatus {
+  is_wayland: boolean
+  wl_copy_available: boolean
+  native_wayland_available: boolean
+  xclip_available: boolean
+  active_backend: 'wl-copy' | 'wayland-native' | 'xclip' | 'none'
+}
+
+/** How each GIF clipboard backend is described on the permissions step */
+const GIF_BACKEND_LABELS: Record<GifClipboardStatus['active_backend'], string> = {
+  'wl-copy': 'GIFs are copied with wl-copy.',
+  'wayland-native': 'GIFs are copied through the Wayland clipboard directly.',
+  xclip: 'GIFs are copied with xclip.',
+  none: 'No clipboard tool for GIFs was found, so they paste as links.',
+}
+
 interface PermissionStatus {
+  session_type: 'Wayland' | 'X11' | 'Unknown'
   uinput_accessible: boolean
   uinput_path: string
   user_in_input_group: boolean
+  xdotool_available: boolean
+  xtest_available: boolean
+  wtype_available: boolean
+  ydotool_available: boolean
+  ydotool_error: string | null
+  ydotoold_running: boolean
+  wl_clipboard_available: boolean
+  gif_clipboard: GifClipboardStatus
+  udev_rule_installed: boolean
+  acl_applied: boolean
+  relogin_required: boolean
+  environment: string
+  paste_expected_to_work: boolean
   suggestion: string
 }
 
+interface FeatureSupport {
+  feature: string
+  works: boolean
+  impact: string
+}
+
+interface WaylandSupport {
+  session_type: 'Wayland' | 'X11' | 'Unknown'
+  compositor: string
+  data_control: boolean
+  global_shortcuts_portal: boolean
+  remote_desktop_portal: boolean
+  wl_clipboard_available: boolean
+  wtype_available: boolean
+  ydotool_available: boolean
+  uinput_accessible: boolean
+  shell_helper: boolean
+  kwin_scripting: boolean
+  shell_helper_suggested: boolean
+  features: FeatureSupport[]
+}
+
+interface FixStep {
+  step: string
+  success: boolean
+}
+
+const FIX_STEP_LABELS: Record<string, string> = {
+  udev_rule: 'Install udev rule',
+  input_group: "Add you to the 'input' group",
+  udev_reload: 'Reload udev rules',
+}
+
 interface ShortcutToolsStatus {
   desktop_environment: string
   gsettings_available: boolean
   kde_tools_available: boolean
   xfce_tools_available: boolean
   can_register_automatically: boolean
+  portal_available: boolean
+  sandboxed: boolean
+  host_spawn_permitted: boolean
   manual_instructions: string
   has_conflicts: boolean
   conflict_count: number
   can_auto_resolve_conflicts: boolean
+  binding_overrides: BindingOverride[]
+}
+
+interface BindingOverride {
+  shortcut_id: string
+  name: string
+  binding: string
+  candidates: string[]
+}
+
+interface ShortcutBackup {
+  id: string
+  original_path: string
+  backup_file: string
+  created_at: number
+}
+
+interface ResolvedConflict {
+  id: string
+  created_at: number
+  schema: string
+  key: string
+  old_value: string
+  command: string
+  owner: string
+  binding: string
+  consumed: boolean
+}
+
+interface RegistrationResult {
+  id: string
+  name: string
+  binding: string
+  success: boolean
+  error: string | null
+}
+
+interface ResolutionCommand {
+  description: string
+  command: string
+  destructive: boolean
 }
 
 interface ShortcutConflict {
   binding: string
+  conflicts_with: string
   current_action: string
   owner: string
-  resolution_command: string | null
+  settings_path: string[]
+  manual_steps: string[]
+  commands: ResolutionCommand[]
   resolution_steps: string
 }
 
@@ -50,6 +162,13 @@ interface ConflictDetectionResult {
   message: string
 }
 
+/** Result of `resolve_conflicts`; entries read "owner (binding)" */
+interface ConflictResolutionReport {
+  resolved: string[]
+  /** Left alone because only removing the owner's shortcut would resolve them */
+  skipped: string[]
+}
+
 interface SetupWizardProps {
   readonly onComplete: () => void
 }
@@ -108,17 +227,29 @@ const WizardButton = ({
 export function SetupWizard({ onComplete }: SetupWizardProps) {
   const [step, setStep] = useState(0)
   const [permissions, setPermissions] = useState<PermissionStatus | null>(null)
+  const [waylandSupport, setWaylandSupport] = useState<WaylandSupport | null>(null)
+  const [checkingShellHelper, setCheckingShellHelper] = useState(false)
   const [shortcutTools, setShortcutTools] = useState<ShortcutToolsStatus | null>(null)
   const [conflicts, setConflicts] = useState<ConflictDetectionResult | null>(null)
   const [fixing, setFixing] = useState(false)
   const [fixError, setFixError] = useState<string | null>(null)
+  const [fixSteps, setFixSteps] = useState<FixStep[]>([])
   const [registeringShortcut, setRegisteringShortcut] = useState(false)
   const [shortcutRegistered, setShortcutRegistered] = useState(false)
+  const [registrationFailures, setRegistrationFailures] = useState<RegistrationResult[]>([])
   const [showManualInstructions, setShowManualInstructions] = useState(false)
   const [resolvingConflicts, setResolvingConflicts] = useState(false)
   const [conflictsResolved, setConflictsResolved] = useState(false)
   const [conflictError, setConflictError] = useState<string | null>(null)
+  // Whether auto-fix may clear shortcuts it can't move to other keys
+  const [allowDestructive, setAllowDestructive] = useState(false)
   const [copied, setCopied] = useState(false)
+  // When the wizard started changing shortcut configs, to undo everything since
+  const [changesSince, setChangesSince] = useState<number | null>(null)
+  const [undoingChanges, setUndoingChanges] = useState(false)
+  // Desktop shortcuts changed by earlier conflict fixes that can be put back
+  const [restorableConflicts, setRestorableConflicts] = useState<ResolvedConflict[]>([])
+  const [restoringShortcuts, setRestoringShortcuts] = useState(false)
   const [hoveredButton, setHoveredButton] = useState<string | null>(null)
   const { enableAutostart } = useAutostart()
   const isDark = useSystemThemePreference()
@@ -152,8 +283,10 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
 
   useEffect(() => {
     checkPermissions()
+    checkWaylandSupport()
     checkShortcutTools()
     checkConflicts()
+    loadResolvedConflicts()
   }, [])
 
   const checkPermissions = async () => {
@@ -165,6 +298,28 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
     }
   }
 
+  const checkWaylandSupport = async () => {
+    try {
+      setWaylandSupport(await invoke<WaylandSupport>('check_wayland_support'))
+    } catch (e) {
+      console.error('Failed to check Wayland support:', e)
+    }
+  }
+
+  // Re-checks after the user installs the GNOME Shell helper extension
+  const handleCheckShellHelper = async () => {
+    setCheckingShellHelper(true)
+    try {
+      if (await invoke<boolean>('is_shell_helper_available')) {
+        await checkWaylandSupport()
+      }
+    } catch (e) {
+      console.error('Failed to check for the Shell helper:', e)
+    } finally {
+      setCheckingShellHelper(false)
+    }
+  }
+
   const checkShortcutTools = async () => {
     try {
       const status = await invoke<ShortcutToolsStatus>('check_shortcut_tools')
@@ -183,20 +338,96 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
     }
   }
 
+  const loadResolvedConflicts = async () => {
+    try {
+      const entries = await invoke<ResolvedConflict[]>('list_resolved_conflicts')
+      setRestorableConflicts(entries.filter((entry) => !entry.consumed))
+    } catch (e) {
+      console.error('Failed to list resolved conflicts:', e)
+    }
+  }
+
+  // Entries are newest first, so a key changed twice ends on its oldest value
+  const undoResolvedConflicts = async (entries: ResolvedConflict[]) => {
+    for (const entry of entries) {
+      await invoke<ResolvedConflict>('undo_conflict_resolution', { entryId: entry.id })
+    }
+    await loadResolvedConflicts()
+  }
+
+  const handleRestorePreviousShortcuts = async () => {
+    setRestoringShortcuts(true)
+    setConflictError(null)
+    try {
+      await undoResolvedConflicts(restorableConflicts)
+      setConflictsResolved(false)
+      await checkConflicts()
+      await checkShortcutTools()
+    } catch (e) {
+      console.error('Failed to restore previous shortcuts:', e)
+      setConflictError(String(e))
+    } finally {
+      setRestoringShortcuts(false)
+    }
+  }
+
+  const markChangesStart = () => {
+    if (!changesSince) setChangesSince(Date.now())
+  }
+
+  const handleUndoChanges = async () => {
+    if (!changesSince) return
+    setUndoingChanges(true)
+    try {
+      const backups = await invoke<ShortcutBackup[]>('list_shortcut_backups')
+      // Backups are newest first; the oldest one per file since we started
+      // holds that file as it was before the wizard touched it
+      const originals = new Map<string, ShortcutBackup>()
+      for (const backup of backups) {
+        if (backup.created_at >= changesSince) originals.set(backup.original_path, backup)
+      }
+      for (const backup of originals.values()) {
+        await invoke<ShortcutBackup>('restore_shortcut_backup', { id: backup.id })
+      }
+      await undoResolvedConflicts(
+        restorableConflicts.filter((entry) => entry.created_at >= changesSince)
+      )
+      setChangesSince(null)
+      setShortcutRegistered(false)
+      setRegistrationFailures([])
+      setConflictsResolved(false)
+      await checkConflicts()
+      await checkShortcutTools()
+    } catch (e) {
+      console.error('Failed to undo shortcut changes:', e)
+      setConflictError(String(e))
+    } finally {
+      setUndoingChanges(false)
+    }
+  }
+
   const handleResolveConflicts = async () => {
+    markChangesStart()
     setResolvingConflicts(true)
     setConflictError(null)
     try {
-      await invoke<string[]>('resolve_conflicts')
-      setConflictsResolved(true)
+      const report = await invoke<ConflictResolutionReport>('resolve_conflicts', {
+        allowDestructive,
+      })
+      if (report.skipped.length > 0) {
+        setConflictError(
+          `Not changed without permission to remove shortcuts: ${report.skipped.join(', ')}`
+        )
+      }
+      setConflictsResolved(report.skipped.length === 0)
+      await loadResolvedConflicts()
       // Refresh conflict status
       await checkConflicts()
       await checkShortcutTools()
       // After resolving conflicts, automatically register the shortcut
       // so the user doesn't have to click a separate button
       try {
-        await invoke<string>('register_de_shortcut')
-        setShortcutRegistered(true)
+        await registerShortcuts()
       } catch (regErr) {
         console.error('Auto-register after conflict fix failed:', regErr)
         // Not fatal — user can still click "Register Automatically"
@@ -223,12 +454,77 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
     }
   }
 
+  const handleFixPermanently = async () => {
+    setFixing(true)
+    setFixError(null)
+    try {
+      setFixSteps(await invoke<FixStep[]>('fix_permissions_persistent'))
+      await checkPermissions()
+    } catch (e) {
+      console.error('Failed to install udev rule:', e)
+      setFixError(String(e))
+    } finally {
+      setFixing(false)
+    }
+  }
+
+  // Registers every shortcut, or only the given ids, and keeps the ones that
+  // failed so they can be retried. Returns whether all of them are bound now.
+  // One id at a time: each registration rewrites the same desktop config,
+  // so parallel calls could drop each other's entries.
+  const registerShortcuts = async (ids?: string[]) => {
+    let results: RegistrationResult[] = []
+    if (ids) {
+      for (const shortcutId of ids) {
+        results = results.concat(
+          await invoke<RegistrationResult[]>('register_de_shortcut', { shortcutId })
+        )
+      }
+    } else {
+      results = await invoke<RegistrationResult[]>('register_de_shortcut')
+    }
+    const failures = results.filter((r) => !r.success)
+    setRegistrationFailures(failures)
+    setShortcutRegistered(failures.length === 0)
+    return failures.length === 0
+  }
+
+  // Registers a shortcut with a free binding instead of the taken one,
+  // then the rest of the shortcuts
+  const handleUseOverride = async (shortcutId: string, bindingOverride: string) => {
+    markChangesStart()
+    setRegisteringShortcut(true)
+    setConflictError(null)
+    try {
+      const results = await invoke<RegistrationResult[]>('register_de_shortcut', {
+        shortcutId,
+        bindingOverride,
+      })
+      const failures = results.filter((r) => !r.success)
+      if (failures.length > 0) {
+        setRegistrationFailures(failures)
+        return
+      }
+      await registerShortcuts()
+      await checkConflicts()
+      await checkShortcutTools()
+    } catch (e) {
+      console.error('Failed to register shortcut with another binding:', e)
+      setConflictError(String(e))
+    } finally {
+      setRegisteringShortcut(false)
+    }
+  }
+
   const handleRegisterShortcut = async () => {
+    markChangesStart()
     setRegisteringShortcut(true)
     try {
-      await invoke<string>('register_de_shortcut')
-      setShortcutRegistered(true)
-      setTimeout(() => setStep(3), 1500)
+      const retrying = registrationFailures.map((r) => r.id)
+      const allBound = await registerShortcuts(retrying.length > 0 ? retrying : undefined)
+      if (allBound) {
+        setTimeout(() => setStep(4), 1500)
+      }
     } catch (e) {
       console.error('Failed to register shortcut:', e)
       setShowManualInstructions(true)
@@ -239,7 +535,7 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
 
   const handleEnableAutostart = async () => {
     await enableAutostart()
-    setStep(4)
+    setStep(5)
   }
 
   const handleComplete = async () => {
@@ -323,7 +619,94 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
       </Button>
     </div>,
 
-    // Step 1: Permissions
+    // Step 1: What works in this session
+    <div key="compatibility" className="animate-fade-in">
+      <div className="text-center mb-6">
+        <div
+          className={clsx(
+            'w-14 h-14 mx-auto rounded-full flex items-center justify-center mb-4',
+            isDark ? 'bg-win11-bg-tertiary' : 'bg-win11Light-bg-tertiary'
+          )}
+        >
+          <Monitor
+            className={clsx(
+              'w-7 h-7',
+              isDark ? 'text-win11-text-secondary' : 'text-win11Light-text-secondary'
+            )}
+          />
+        </div>
+        <h2
+          className={clsx(
+            'text-lg font-semibold mb-1',
+            isDark ? 'text-win11-text-primary' : 'text-win11Light-text-primary'
+          )}
+        >
+          Your Desktop
+        </h2>
+        <p
+          className={clsx(
+            'text-sm',
+            isDark ? 'text-win11-text-secondary' : 'text-win11Light-text-secondary'
+          )}
+        >
+          {waylandSupport
+            ? `${waylandSupport.compositor}, ${waylandSupport.session_type} session`
+            : 'Checking your session...'}
+        </p>
+      </div>
+
+      {waylandSupport && waylandSupport.features.length === 0 && (
+        <div className={clsx('mb-4', statusCardClass('success'))}>
+          <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+          <span>Every feature is supported in this session.</span>
+        </div>
+      )}
+
+      {waylandSupport && waylandSupport.features.length > 0 && (
+        <ul className="mb-4 space-y-2">
+          {waylandSupport.features.map((f) => (
+            <li key={f.feature} className={statusCardClass(f.works ? 'success' : 'warning')}>
+              {f.works ? (
+                <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+              ) : (
+                <XCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+              )}
+              <div>
+                <p className="font-medium">{f.feature}</p>
+                <p className="text-xs opacity-90">{f.impact}</p>
+              </div>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {waylandSupport?.shell_helper_suggested && (
+        <div className={clsx('mb-4', statusCardClass('warning'))}>
+          <AlertCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+          <div className="flex-1">
+            <p className="mb-2">
+              Install the PenguinClip Shell Helper GNOME extension, then log out and back in, so
+              PenguinClip can paste into the window you were using.
+            </p>
+            <Button
+              id="check-shell-helper"
+              onClick={handleCheckShellHelper}
+              disabled={checkingShellHelper}
+            >
+              {checkingShellHelper ? 'Checking...' : 'Check Again'}
+            </Button>
+          </div>
+        </div>
+      )}
+
+      <div className="flex gap-3 justify-center">
+        <Button id="compat-continue" onClick={() => setStep(2)} primary>
+          Continue
+        </Button>
+      </div>
+    </div>,
+
+    // Step 2: Permissions
     <div key="permissions" className="animate-fade-in">
       <div className="text-center mb-6">
         <div
@@ -361,10 +744,10 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
         <div
           className={clsx(
             'mb-4',
-            statusCardClass(permissions.uinput_accessible ? 'success' : 'warning')
+            statusCardClass(permissions.paste_expected_to_work ? 'success' : 'warning')
           )}
         >
-          {permissions.uinput_accessible ? (
+          {permissions.paste_expected_to_work ? (
             <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
           ) : (
             <AlertTriangle className="w-5 h-5 flex-shrink-0 mt-0.5" />
@@ -373,21 +756,98 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
         </div>
       )}
 
+      {permissions && (
+        <p
+          className={clsx(
+            'mb-4 text-xs text-center',
+            isDark ? 'text-win11-text-secondary' : 'text-win11Light-text-secondary'
+          )}
+        >
+          {GIF_BACKEND_LABELS[permissions.gif_clipboard.active_backend]}
+        </p>
+      )}
+
+      {permissions?.session_type === 'Wayland' && (
+        <ul
+          className={clsx(
+            'mb-4 text-xs space-y-1',
+            isDark ? 'text-win11-text-secondary' : 'text-win11Light-text-secondary'
+          )}
+        >
+          <li>
+            {permissions.wtype_available ? '✓' : '✗'} wtype
+            {!permissions.wtype_available && ' — not installed'}
+          </li>
+          <li>
+            {permissions.ydotool_available ? '✓' : '✗'} ydotool
+            {permissions.ydotool_error && ` — ${permissions.ydotool_error}`}
+          </li>
+          <li>
+            {permissions.uinput_accessible ? '✓' : '✗'} {permissions.uinput_path}
+            {!permissions.uinput_accessible && ' — no write access'}
+          </li>
+        </ul>
+      )}
+
+      {permissions && (permissions.udev_rule_installed || permissions.acl_applied) && (
+        <p
+          className={clsx(
+            'mb-4 text-xs text-center',
+            isDark ? 'text-win11-text-secondary' : 'text-win11Light-text-secondary'
+          )}
+        >
+          {permissions.udev_rule_installed
+            ? 'Permanent fix active: the udev rule is installed.'
+            : 'Temporary fix active: access lasts until the next reboot.'}
+        </p>
+      )}
+
+      {fixSteps.length > 0 && (
+        <div
+          className={clsx(
+            'mb-4',
+            statusCardClass(fixSteps.every((s) => s.success) ? 'success' : 'warning')
+          )}
+        >
+          <div>
+            <ul className="text-xs space-y-1">
+              {fixSteps.map((s) => (
+                <li key={s.step}>
+                  {s.success ? '✓' : '✗'} {FIX_STEP_LABELS[s.step] ?? s.step}
+                </li>
+              ))}
+            </ul>
+            {permissions?.relogin_required && (
+              <p className="text-xs mt-2">Log out and back in for the group change to apply.</p>
+            )}
+          </div>
+        </div>
+      )}
+
       {fixError && <div className={clsx('mb-4', statusCardClass('error'))}>{fixError}</div>}
 
       <div className="flex gap-3 justify-center">
-        {!permissions?.uinput_accessible && (
+        {permissions && !permissions.paste_expected_to_work && (
           <Button id="fix" onClick={handleFixPermissions} disabled={fixing}>
             {fixing ? 'Fixing...' : 'Fix Now'}
           </Button>
         )}
-        <Button id="perm-continue" onClick={() => setStep(2)} primary>
-          {permissions?.uinput_accessible ? 'Continue' : 'Skip'}
+        {/* Offered until uinput access survives a reboot, if paste relies on it */}
+        {permissions &&
+          !permissions.udev_rule_installed &&
+          permissions.environment === 'host' &&
+          (!permissions.paste_expected_to_work || permissions.acl_applied) && (
+            <Button id="fix-permanent" onClick={handleFixPermanently} disabled={fixing}>
+              Fix Permanently
+            </Button>
+          )}
+        <Button id="perm-continue" onClick={() => setStep(3)} primary>
+          {permissions?.paste_expected_to_work ? 'Continue' : 'Skip'}
         </Button>
       </div>
     </div>,
 
-    // Step 2: Shortcut Configuration
+    // Step 3: Shortcut Configuration
     <div key="shortcut" className="animate-fade-in">
       <div className="text-center mb-6">
         <div
@@ -460,10 +920,14 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
               {conflicts.conflicts.length} shortcut conflict
               {conflicts.conflicts.length > 1 ? 's' : ''} detected
             </p>
-            <p className="text-xs opacity-90 mb-2">
-              Super+V is already used by {conflicts.conflicts[0].owner} for "
-              {conflicts.conflicts[0].current_action}"
-            </p>
+            <ul className="text-xs opacity-90 mb-2 space-y-0.5">
+              {conflicts.conflicts.map((conflict, i) => (
+                <li key={`${conflict.owner}-${conflict.binding}-${i}`}>
+                  {conflict.binding} ({conflict.conflicts_with}) is already used by{' '}
+                  {conflict.owner} for "{conflict.current_action}"
+                </li>
+              ))}
+            </ul>
             {conflicts.can_auto_resolve && (
               <div className="space-y-1">
                 <Button
@@ -477,8 +941,19 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
                   </span>
                 </Button>
                 <p className="text-xs opacity-60">
-                  This will comment out conflicting lines in your config. A backup will be created.
+                  This will comment out conflicting lines in your config or rebind the
+                  desktop's shortcuts. The previous settings are kept so you can undo it.
                 </p>
+                {conflicts.conflicts.some((c) => c.commands.some((cmd) => cmd.destructive)) && (
+                  <label className="flex items-center gap-2 text-xs opacity-75">
+                    <input
+                      type="checkbox"
+                      checked={allowDestructive}
+                      onChange={(e) => setAllowDestructive(e.target.checked)}
+                    />
+                    Also clear shortcuts that can't be moved to other keys
+                  </label>
+                )}
               </div>
             )}
             {!conflicts.can_auto_resolve && (
@@ -486,6 +961,25 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
                 Manual resolution required. See instructions below.
               </p>
             )}
+            {shortcutTools?.binding_overrides.map((proposal) => (
+              <div key={proposal.shortcut_id} className="mt-2 space-y-1">
+                <p className="text-xs opacity-75">
+                  Or keep {proposal.binding} as it is and register {proposal.name} as:
+                </p>
+                <div className="flex flex-wrap gap-2">
+                  {proposal.candidates.map((candidate) => (
+                    <Button
+                      key={candidate}
+                      id={`override-${proposal.shortcut_id}-${candidate}`}
+                      onClick={() => handleUseOverride(proposal.shortcut_id, candidate)}
+                      disabled={registeringShortcut}
+                    >
+                      {candidate}
+                    </Button>
+                  ))}
+                </div>
+              </div>
+            ))}
           </div>
         </div>
       )}
@@ -507,6 +1001,29 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
         </div>
       )}
 
+      {shortcutTools?.sandboxed &&
+        !shortcutTools.host_spawn_permitted &&
+        !shortcutTools.portal_available && (
+          <div className={clsx('mb-4', statusCardClass('warning'))}>
+            <AlertTriangle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+            <span>
+              PenguinClip runs in a sandbox that can't change your desktop's settings. Allow it to
+              run host commands (for a Flatpak: <code>--talk-name=org.freedesktop.Flatpak</code>)
+              or set the shortcut up manually.
+            </span>
+          </div>
+        )}
+
+      {shortcutTools?.portal_available && !shortcutRegistered && (
+        <div className={clsx('mb-4', statusCardClass('success'))}>
+          <Keyboard className="w-5 h-5 flex-shrink-0 mt-0.5" />
+          <span>
+            Your desktop manages global shortcuts through the GlobalShortcuts portal. It may ask
+            you to confirm Super+V, and you can change it later in your system settings.
+          </span>
+        </div>
+      )}
+
       {shortcutRegistered && (
         <div className={clsx('mb-4', statusCardClass('success'))}>
           <CheckCircle className="w-5 h-5 flex-shrink-0 mt-0.5" />
@@ -514,6 +1031,22 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
         </div>
       )}
 
+      {registrationFailures.length > 0 && (
+        <div className={clsx('mb-4', statusCardClass('error'))}>
+          <AlertTriangle className="w-5 h-5 flex-shrink-0 mt-0.5" />
+          <div>
+            <p className="font-medium">Some shortcuts could not be registered</p>
+            <ul className="text-xs opacity-90 mt-1 space-y-1">
+              {registrationFailures.map((r) => (
+                <li key={r.id}>
+                  {r.name} ({r.binding}): {r.error ?? 'Unknown error'}
+                </li>
+              ))}
+            </ul>
+          </div>
+        </div>
+      )}
+
       {showManualInstructions && shortcutTools && (
         <div className="mb-4 space-y-3">
           <div className={statusCardClass('warning')}>
@@ -546,7 +1079,13 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
               disabled={registeringShortcut}
               primary
             >
-              {registeringShortcut ? 'Registering...' : 'Register Automatically'}
+              {registeringShortcut
+                ? 'Registering...'
+                : registrationFailures.length > 0
+                  ? 'Retry failed shortcuts'
+                  : shortcutTools?.portal_available
+                  ? 'Register with Desktop Portal'
+                  : 'Register Automatically'}
             </Button>
           )}
 
@@ -556,9 +1095,25 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
           </Button>
         )}
 
+        {changesSince && (shortcutRegistered || conflictsResolved) && (
+          <Button id="undo-shortcut-changes" onClick={handleUndoChanges} disabled={undoingChanges}>
+            {undoingChanges ? 'Undoing...' : 'Undo changes'}
+          </Button>
+        )}
+
+        {!changesSince && restorableConflicts.length > 0 && (
+          <Button
+            id="restore-previous-shortcuts"
+            onClick={handleRestorePreviousShortcuts}
+            disabled={restoringShortcuts}
+          >
+            {restoringShortcuts ? 'Restoring...' : 'Restore previous shortcuts'}
+          </Button>
+        )}
+
         <Button
           id="shortcut-continue"
-          onClick={() => setStep(3)}
+          onClick={() => setStep(4)}
           primary={shortcutRegistered || showManualInstructions}
         >
           {shortcutRegistered || showManualInstructions ? 'Continue' : 'Skip'}
@@ -566,7 +1121,7 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
       </div>
     </div>,
 
-    // Step 3: Autostart
+    // Step 4: Autostart
     <div key="autostart" className="animate-fade-in">
       <div className="text-center mb-6">
         <div
@@ -604,13 +1159,13 @@ export function SetupWizard({ onComplete }: SetupWizardProps) {
         <Button id="enable-autostart" onClick={handleEnableAutostart} primary>
           Yes, enable
         </Button>
-        <Button id="skip-autostart" onClick={() => setStep(4)}>
+        <Button id="skip-autostart" onClick={() => setStep(5)}>
           No thanks
         </Button>
       </div>
     </div>,
 
-    // Step 4: Done
+    // Step 5: Done
     <div key="done" className="text-center animate-fade-in">
       <div className="mb-6">
         <div
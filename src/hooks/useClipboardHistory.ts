@@ -1,7 +1,15 @@
-import { useState, useEffect, useCallback } from 'react'
+import { useState, useEffect, useCallback, useRef } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { listen, UnlistenFn } from '@tauri-apps/api/event'
-import type { ClipboardItem } from '../types/clipboard'
+import type {
+  ClipboardContent,
+  ClipboardItem,
+  HistoryPage,
+  PinToggleResult,
+} from '../types/clipboard'
+
+/** Items fetched per `get_history_page` call */
+const HISTORY_PAGE_SIZE = 50
 
 /**
  * Hook for managing clipboard history
@@ -10,19 +18,84 @@ export function useClipboardHistory() {
   const [history, setHistory] = useState<ClipboardItem[]>([])
   const [isLoading, setIsLoading] = useState(true)
   const [error, setError] = useState<string | null>(null)
+  // Cursor for the next page: the id of the last item loaded, or null once
+  // the whole history is loaded
+  const [nextCursor, setNextCursor] = useState<string | null>(null)
+  // Incremented per fetch so a stale page is dropped
+  const fetchGeneration = useRef(0)
+  const loadingMore = useRef(false)
+  // Items loaded so far, so a refresh keeps as many as the user scrolled to
+  const loadedCount = useRef(0)
+  loadedCount.current = history.length
+  // Items whose full content was requested since the last refresh
+  const contentRequested = useRef(new Set<string>())
 
-  // Fetch initial history
+  // Fetch the top of the history again. Later pages are loaded on scroll.
   const fetchHistory = useCallback(async () => {
+    const generation = ++fetchGeneration.current
     try {
       setIsLoading(true)
-      const items = await invoke<ClipboardItem[]>('get_history')
-      setHistory(items)
+      const first = await invoke<HistoryPage>('get_history_page', {
+        after: null,
+        limit: Math.max(HISTORY_PAGE_SIZE, loadedCount.current),
+      })
+      if (generation !== fetchGeneration.current) return
+      setHistory(first.items)
+      contentRequested.current.clear()
+      setNextCursor(first.next)
       setError(null)
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed to fetch history')
     } finally {
-      setIsLoading(false)
+      if (generation === fetchGeneration.current) setIsLoading(false)
+    }
+  }, [])
+
+  // Append the page after the last item loaded
+  const loadMore = useCallback(async () => {
+    if (!nextCursor || loadingMore.current) return
+    const generation = fetchGeneration.current
+    loadingMore.current = true
+    try {
+      const page = await invoke<HistoryPage>('get_history_page', {
+        after: nextCursor,
+        limit: HISTORY_PAGE_SIZE,
+      })
+      if (generation !== fetchGeneration.current) return
+      setHistory((prev) => {
+        const loaded = new Set(prev.map((item) => item.id))
+        return [...prev, ...page.items.filter((item) => !loaded.has(item.id))]
+      })
+      setNextCursor(page.next)
+    } catch {
+      // The cursor's item was deleted; start over from the top
+      if (generation === fetchGeneration.current) await fetchHistory()
+    } finally {
+      loadingMore.current = false
     }
+  }, [nextCursor, fetchHistory])
+
+  // Replace items whose text a page cut short with their full content, for
+  // searching all of it. Each item is fetched once per page load.
+  const loadFullContent = useCallback(async (ids: string[]) => {
+    const wanted = ids.filter((id) => !contentRequested.current.has(id))
+    wanted.forEach((id) => contentRequested.current.add(id))
+    if (!wanted.length) return
+    const contents = await Promise.all(
+      wanted.map((id) =>
+        invoke<ClipboardContent>('get_item_content', { id }).then(
+          (content) => [id, content] as const,
+          () => null
+        )
+      )
+    )
+    const byId = new Map(contents.flatMap((entry) => (entry ? [entry] : [])))
+    setHistory((prev) =>
+      prev.map((item) => {
+        const content = byId.get(item.id)
+        return content ? { ...item, content, content_truncated: false } : item
+      })
+    )
   }, [])
 
   // Dismiss the current error (e.g. after the user closes its banner)
@@ -52,31 +125,13 @@ export function useClipboardHistory() {
   const togglePin = useCallback(
     async (id: string) => {
       try {
-        const updatedItem = await invoke<ClipboardItem>('toggle_pin', { id })
-        if (updatedItem) {
-          setHistory((prev) => {
-            // Remove the item from its current position
-            const otherItems = prev.filter((item) => item.id !== id)
-            const pinnedItems = otherItems.filter((item) => item.pinned)
-            const unpinnedItems = otherItems.filter((item) => !item.pinned)
-
-            if (updatedItem.pinned) {
-              // Item was pinned - add to the end of pinned items (top of list)
-              return [...pinnedItems, updatedItem, ...unpinnedItems]
-            } else {
-              // Item was unpinned - insert in correct position by timestamp
-              const allUnpinned = [updatedItem, ...unpinnedItems]
-              allUnpinned.sort(
-                (a, b) => new Date(b.timestamp).getTime() - new Date(a.timestamp).getTime()
-              )
-              return [...pinnedItems, ...allUnpinned]
-            }
-          })
-        } else {
-          // Item not found - refresh history
-          console.warn('[useClipboardHistory] Toggle pin returned null, refreshing history')
-          await fetchHistory()
-        }
+        const { item: updatedItem, order } = await invoke<PinToggleResult>('toggle_pin', { id })
+        setHistory((prev) => {
+          // Follow the backend's ordering; items not loaded yet stay unloaded
+          const byId = new Map(prev.map((item) => [item.id, item]))
+          byId.set(updatedItem.id, updatedItem)
+          return order.flatMap((itemId) => byId.get(itemId) ?? [])
+        })
       } catch (err) {
         console.warn('[useClipboardHistory] Toggle pin failed, refreshing history')
         await fetchHistory()
@@ -125,6 +180,32 @@ export function useClipboardHistory() {
     [fetchHistory]
   )
 
+  // Paste an item back into the window it was copied from
+  const pasteItemToSource = useCallback(
+    async (id: string) => {
+      try {
+        await invoke('paste_item_to_source', { id })
+      } catch (err) {
+        const errorMessage = err instanceof Error ? err.message : String(err)
+        console.warn('[useClipboardHistory] Paste to source failed, refreshing history:', errorMessage)
+        await fetchHistory()
+        setError(errorMessage)
+      }
+    },
+    [fetchHistory]
+  )
+
+  // Type an item's text into the focused window, for apps that block pasting
+  const typeItem = useCallback(async (id: string) => {
+    try {
+      await invoke('type_item', { id })
+    } catch (err) {
+      const errorMessage = err instanceof Error ? err.message : String(err)
+      console.warn('[useClipboardHistory] Typing failed:', errorMessage)
+      setError(errorMessage)
+    }
+  }, [])
+
   // Listen for clipboard changes
   useEffect(() => {
     fetchHistory()
@@ -166,7 +247,10 @@ export function useClipboardHistory() {
 
       const uSync = await listen<ClipboardItem[]>('history-sync', async (event) => {
         console.log('[useClipboardHistory] history-sync event received')
+        // The full history; supersedes any page still in flight
+        fetchGeneration.current++
         setHistory(event.payload)
+        setNextCursor(null)
       })
       if (!isMounted) {
         uSync()
@@ -191,10 +275,15 @@ export function useClipboardHistory() {
     error,
     clearError,
     fetchHistory,
+    hasMore: nextCursor !== null,
+    loadMore,
+    loadFullContent,
     clearHistory,
     deleteItem,
     togglePin,
     toggleFavorite,
     pasteItem,
+    pasteItemToSource,
+    typeItem,
   }
 }
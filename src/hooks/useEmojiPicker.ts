@@ -11,6 +11,19 @@ import { searchEmojis, preloadSearchIndex } from '../services/emojiSearch'
 interface RecentEmoji {
   char: string
   use_count: number
+  /** Tone last used with this emoji (snake_case SkinTone) */
+  skin_tone?: string | null
+  /** The emoji with that tone applied, as it should be shown and pasted */
+  toned: string
+}
+
+/** Recent emojis as full Emoji objects, carrying the tone they were last used with */
+function withEmojiData(recent: RecentEmoji[], emojis: Emoji[]): Emoji[] {
+  const emojiMap = new Map(emojis.map((e) => [e.char, e]))
+  return recent.flatMap((r) => {
+    const emoji = emojiMap.get(r.char)
+    return emoji ? [{ ...emoji, char: r.toned }] : []
+  })
 }
 
 export function useEmojiPicker() {
@@ -38,11 +51,7 @@ export function useEmojiPicker() {
       try {
         const recent: RecentEmoji[] = await invoke('get_recent_emojis')
         // Map recent chars back to full Emoji objects
-        const emojiMap = new Map(allEmojis.map((e) => [e.char, e]))
-        const recentWithData = recent
-          .map((r) => emojiMap.get(r.char))
-          .filter((e): e is Emoji => e !== undefined)
-        setRecentEmojis(recentWithData)
+        setRecentEmojis(withEmojiData(recent, allEmojis))
       } catch (err) {
         console.error('Failed to load recent emojis:', err)
         setRecentEmojis([])
@@ -63,8 +72,9 @@ export function useEmojiPicker() {
 
     // Default: show recent first, then all
     if (recentEmojis.length > 0) {
-      const recentChars = new Set(recentEmojis.map((e) => e.char))
-      const nonRecent = allEmojis.filter((e) => !recentChars.has(e.char))
+      // Recent emojis may carry a tone, so match them by name
+      const recentNames = new Set(recentEmojis.map((e) => e.name))
+      const nonRecent = allEmojis.filter((e) => !recentNames.has(e.name))
       return [...recentEmojis, ...nonRecent]
     }
 
@@ -77,11 +87,7 @@ export function useEmojiPicker() {
       await invoke('paste_text', { text: emoji.char, itemType: 'emoji' })
       // Refresh recent emojis after paste
       const recent: RecentEmoji[] = await invoke('get_recent_emojis')
-      const emojiMap = new Map(loadEmojis().map((e) => [e.char, e]))
-      const recentWithData = recent
-        .map((r) => emojiMap.get(r.char))
-        .filter((e): e is Emoji => e !== undefined)
-      setRecentEmojis(recentWithData)
+      setRecentEmojis(withEmojiData(recent, loadEmojis()))
     } catch (err) {
       console.error('Failed to paste emoji:', err)
     }
@@ -5,7 +5,7 @@
 import { useState, useEffect, useCallback, useRef } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { fetchTrendingGifs, searchGifs } from '../services/gifService'
-import type { Gif } from '../types/gif'
+import type { Gif, GifPasteError } from '../types/gif'
 
 /** Debounce delay for search input (ms) */
 const SEARCH_DEBOUNCE_MS = 300
@@ -100,7 +100,12 @@ export function useGifPicker() {
     setIsPasting(true)
     try {
       // 1. Download and copy to clipboard
-      await invoke('paste_gif_from_url', { url: gif.fullUrl })
+      await invoke('paste_gif_from_url', {
+        url: gif.fullUrl,
+        previewUrl: gif.previewUrl,
+        width: gif.width,
+        height: gif.height,
+      })
 
       // 2. Reset loading state BEFORE hiding window
       setIsPasting(false)
@@ -116,6 +121,10 @@ export function useGifPicker() {
       }, 100)
     } catch (err) {
       console.error('Failed to paste GIF:', err)
+      const pasteError = err as GifPasteError
+      if (pasteError?.kind === 'blocked') {
+        setError(pasteError.message)
+      }
       setIsPasting(false)
     }
   }, [])
@@ -40,6 +40,39 @@ export interface ClipboardItem {
   pinned: boolean
   favorited: boolean
   preview: string
+  /** Full content size in bytes (PNG size for images); absent or 0 if unknown */
+  byte_size?: number
+  /** Selection the item was captured from; 'primary' means selected text */
+  source_selection?: 'clipboard' | 'primary'
+  /** X11 window the item was copied from, if known */
+  source_window_id?: number | null
+  /** Title of that window at copy time */
+  source_window_title?: string | null
+  /** Set on page items whose text was cut short; `get_item_content` has all of it */
+  content_truncated?: boolean
+}
+
+/** One page of history from `get_history_page` */
+export interface HistoryPage {
+  items: ClipboardItem[]
+  /** Id of the last item, the `after` cursor for the next page; null at the end */
+  next: string | null
+  /** Number of items in the full history */
+  total: number
+}
+
+/** Result of `toggle_pin` */
+export interface PinToggleResult {
+  item: ClipboardItem
+  /** Ids of the whole history in display order */
+  order: string[]
+}
+
+/** Payload of the `history-locked` event and `get_history_lock_state` */
+export interface HistoryLockState {
+  encrypted: boolean
+  /** History file not unlocked yet; ask for the passphrase */
+  locked: boolean
 }
 
 /** Active tab in the UI */
@@ -69,6 +102,15 @@ export interface ThemeInfo {
   source: string
 }
 
+export interface AccentColor {
+  r: number
+  g: number
+  b: number
+  /** "#rrggbb" */
+  hex: string
+  source: string
+}
+
 export interface Kaomoji {
   id: string
   text: string
@@ -79,20 +121,107 @@ export interface Kaomoji {
 export type CustomKaomoji = Omit<Kaomoji, 'id'>
 
 export interface UserSettings {
+  /** Settings file schema version; stamped by the backend on save */
+  schema_version?: number
   theme_mode: ThemeMode
   dark_background_opacity: number
   light_background_opacity: number
   enable_smart_actions: boolean
   enable_ui_polish: boolean
   enable_dynamic_tray_icon: boolean
+  /** Monochrome tray icon, white or black to suit the color scheme */
+  symbolic_tray_icon: boolean
   max_history_size: number
   auto_delete_interval: number
   auto_delete_unit: 'minutes' | 'hours' | 'days' | 'weeks'
   /** Regex patterns; clipboard text matching any is never recorded (privacy). */
   excluded_patterns: string[]
   custom_kaomojis: CustomKaomoji[]
+  /** Days after which an emoji use counts half when ranking the most used */
+  emoji_decay_half_life_days?: number
   ui_scale: number
   tenor_api_key: string
+  /** When the clipboard window hides by itself */
+  window_close_policy?: 'on_focus_loss' | 'on_paste' | 'manual'
+  /** Global shortcut that opens the clipboard, e.g. "<Super>v" */
+  shortcut_primary?: string
+  /** Alternative shortcut, e.g. "<Ctrl><Alt>v" */
+  shortcut_secondary?: string
+  /** Bindings registered instead of the configured ones, by shortcut id */
+  shortcut_overrides?: Record<string, string>
+  /** Ids of the shortcuts the user unregistered; startup leaves them alone */
+  disabled_shortcuts?: string[]
+  /** Delays around the simulated paste keystroke */
+  paste_timing?: PasteTimings
+  /** Keystroke sent to paste, e.g. "Shift+Insert"; empty picks one per app */
+  paste_combo?: string
+  /** Paste method for apps without a rule */
+  paste_method?: PasteMethod
+  /** Per-app paste methods, matched on WM_CLASS; the first match wins */
+  app_paste_rules?: AppPasteRule[]
+  /** Delay between characters when typing an item out (0 to 1000 ms) */
+  type_char_delay_ms?: number
+}
+
+/** How a paste is sent: "auto" picks Ctrl+Shift+V in terminals, else Ctrl+V */
+export type PasteMethod = 'auto' | 'ctrl-v' | 'ctrl-shift-v' | 'shift-insert' | 'type'
+
+/** Pastes into windows whose WM_CLASS matches `wm_class_pattern` with `method` */
+export interface AppPasteRule {
+  /** Substring of the instance or class name, or a glob with * and ? */
+  wm_class_pattern: string
+  method: PasteMethod
+  /** Send Ctrl+Shift+V instead of Ctrl+V ("auto" and "ctrl-v" only) */
+  use_shift: boolean
+}
+
+/** Which paste rule applies to a WM_CLASS, as reported by test_paste_rule */
+export interface PasteRuleMatch {
+  /** Index into app_paste_rules, null if no rule matched */
+  rule_index: number | null
+  pattern: string | null
+  method: string
+  /** The keystroke sent, null when the text is typed out */
+  keystroke: string | null
+}
+
+/** Paste delays in milliseconds; the backend clamps each to a sane range */
+export interface PasteTimings {
+  /** Before the first key, so window focus is stable */
+  pre_delay_ms: number
+  /** Between key events */
+  key_delay_ms: number
+  /** After the keystroke, so the target app can process it */
+  post_delay_ms: number
+  /** How long to wait for focus to reach the restored window */
+  focus_timeout_ms: number
+  /** Extra activation attempts when focus doesn't arrive in time */
+  focus_retries: number
+}
+
+/** A setting the backend clamped or reset when saving */
+export interface ValidationIssue {
+  field: string
+  submitted: unknown
+  /** The value now in use; null when it was dropped */
+  applied: unknown | null
+  reason: string
+}
+
+/** One imported setting that won't be applied exactly as written */
+export interface ImportWarning {
+  field: string
+  kind: 'adjusted' | 'rejected' | 'unknown'
+  imported: unknown
+  /** The value that will be used; null when the field is ignored */
+  applied: unknown | null
+}
+
+/** Result of previewing or applying a settings import */
+export interface ImportReport {
+  warnings: ImportWarning[]
+  includes_emoji_history: boolean
+  applied: boolean
 }
 
 /** Rendering environment info from the backend */
@@ -53,3 +53,29 @@ export interface Gif {
   width: number
   height: number
 }
+
+/** Recently pasted or favorited GIF, from get_recent_gifs */
+export interface RecentGif {
+  url: string
+  preview_url: string | null
+  width: number | null
+  height: number | null
+  use_count: number
+  last_used: number
+  favorite: boolean
+}
+
+/** Error from paste_gif_from_url; `blocked` means the URL policy refused it */
+export type GifPasteError =
+  | {
+      kind: 'blocked'
+      reason:
+        | 'invalid_url'
+        | 'scheme_not_allowed'
+        | 'host_not_allowed'
+        | 'private_address'
+        | 'too_large'
+        | 'redirect_not_allowed'
+      message: string
+    }
+  | { kind: 'failed'; message: string }
@@ -9,6 +9,9 @@
 export interface AppearanceTokens {
   /** Accent colour as #rrggbb. Drives active states, focus rings, primary actions. */
   accent: string
+  /** Use the desktop's accent colour instead of `accent` whenever the desktop
+   * publishes one. */
+  followSystemAccent?: boolean
   /** Round the main window's corners. Off by default (a squared opaque window
    * avoids dark corner triangles on compositors without alpha). */
   roundedCorners?: boolean
@@ -18,6 +21,7 @@ const STORAGE_KEY = 'penguinclip-appearance'
 
 export const DEFAULT_APPEARANCE: AppearanceTokens = {
   accent: '#0078d4',
+  followSystemAccent: false,
   roundedCorners: false,
 }
 
@@ -66,12 +70,19 @@ function lighten({ r, g, b }: { r: number; g: number; b: number }, amount: numbe
   return `rgb(${mix(r)}, ${mix(g)}, ${mix(b)})`
 }
 
+/** The accent in effect: the desktop's (`systemAccent`, "#rrggbb") when the
+ * tokens follow it and it is known, else the user's pick. */
+export function effectiveAccent(tokens: AppearanceTokens, systemAccent?: string | null): string {
+  return tokens.followSystemAccent && systemAccent ? systemAccent : tokens.accent
+}
+
 /** Write the accent tokens onto :root so the whole app re-themes immediately. */
-export function applyAppearance(tokens: AppearanceTokens): void {
+export function applyAppearance(tokens: AppearanceTokens, systemAccent?: string | null): void {
   const root = document.documentElement
-  const rgb = hexToRgb(tokens.accent)
+  const accent = effectiveAccent(tokens, systemAccent)
+  const rgb = hexToRgb(accent)
 
-  root.style.setProperty('--accent', tokens.accent)
+  root.style.setProperty('--accent', accent)
   root.style.setProperty('--accent-hover', lighten(rgb, 0.12))
   root.style.setProperty('--accent-subtle', `rgba(${rgb.r}, ${rgb.g}, ${rgb.b}, 0.12)`)
   root.style.setProperty('--accent-ring', `rgba(${rgb.r}, ${rgb.g}, ${rgb.b}, 0.5)`)
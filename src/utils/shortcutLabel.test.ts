@@ -0,0 +1,19 @@
+import { describe, it, expect } from 'vitest'
+import { formatShortcut } from './shortcutLabel'
+
+describe('formatShortcut', () => {
+  it('writes the default bindings the way people read them', () => {
+    expect(formatShortcut('<Super>v')).toBe('Super+V')
+    expect(formatShortcut('<Ctrl><Alt>v')).toBe('Ctrl+Alt+V')
+  })
+
+  it('orders modifiers like the backend and names punctuation keys', () => {
+    expect(formatShortcut('<Shift><Super>period')).toBe('Super+Shift+.')
+    expect(formatShortcut('<Primary><Alt>F5')).toBe('Ctrl+Alt+F5')
+  })
+
+  it('leaves text that is not a binding alone', () => {
+    expect(formatShortcut('Enter')).toBe('Enter')
+    expect(formatShortcut('<Hyper>v')).toBe('<Hyper>v')
+  })
+})
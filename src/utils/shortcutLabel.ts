@@ -0,0 +1,46 @@
+/**
+ * Readable labels for shortcuts in the settings syntax (`<Super>v`,
+ * `<Ctrl><Alt>v`), written the way the backend's `KeyBinding::label` writes
+ * them: `Super+V`, `Ctrl+Alt+V`.
+ */
+
+const MODIFIER_LABELS: Record<string, string> = {
+  super: 'Super',
+  meta: 'Super',
+  logo: 'Super',
+  ctrl: 'Ctrl',
+  control: 'Ctrl',
+  primary: 'Ctrl',
+  alt: 'Alt',
+  mod1: 'Alt',
+  shift: 'Shift',
+}
+
+const MODIFIER_ORDER = ['Super', 'Ctrl', 'Alt', 'Shift']
+
+const KEY_LABELS: Record<string, string> = {
+  period: '.',
+  comma: ',',
+  slash: '/',
+  semicolon: ';',
+  minus: '-',
+  space: 'Space',
+  insert: 'Ins',
+}
+
+/** `<Super>v` → `Super+V`; text that isn't a binding is returned as is. */
+export function formatShortcut(binding: string): string {
+  const modifiers: string[] = []
+  let rest = binding.trim()
+  let match: RegExpMatchArray | null
+  while ((match = rest.match(/^<([^>]+)>\s*/))) {
+    const label = MODIFIER_LABELS[match[1].toLowerCase()]
+    if (!label) return binding
+    if (!modifiers.includes(label)) modifiers.push(label)
+    rest = rest.slice(match[0].length)
+  }
+  if (modifiers.length === 0 || !rest) return binding
+  modifiers.sort((a, b) => MODIFIER_ORDER.indexOf(a) - MODIFIER_ORDER.indexOf(b))
+  const key = KEY_LABELS[rest.toLowerCase()] ?? (rest.length === 1 ? rest.toUpperCase() : rest)
+  return [...modifiers, key].join('+')
+}
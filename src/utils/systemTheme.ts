@@ -1,7 +1,7 @@
 import { useEffect, useRef, useState } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
-import type { ThemeInfo } from '../types/clipboard'
+import type { AccentColor, ThemeInfo } from '../types/clipboard'
 
 /**
  * Query the backend for system color scheme via XDG Desktop Portal.
@@ -96,3 +96,27 @@ export function useSystemThemePreference(): boolean {
 
   return systemPrefersDark
 }
+
+/**
+ * Hook for the desktop's accent color, or null when it publishes none.
+ * Follows changes the backend relays from the portal.
+ */
+export function useSystemAccentColor(): AccentColor | null {
+  const [accent, setAccent] = useState<AccentColor | null>(null)
+
+  useEffect(() => {
+    invoke<AccentColor | null>('get_system_accent_color')
+      .then(setAccent)
+      .catch((error) => console.warn('[systemTheme] Failed to get accent color:', error))
+
+    const unlistenPromise = listen<AccentColor | null>('system-accent-changed', (event) => {
+      setAccent(event.payload)
+    })
+
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten())
+    }
+  }, [])
+
+  return accent
+}